@@ -0,0 +1,75 @@
+//! Embeds build metadata reported by the health endpoint.
+
+use std::{
+  fs,
+  path::Path,
+  process::Command,
+  time::{SystemTime, UNIX_EPOCH},
+};
+
+fn main() {
+  println!("cargo:rerun-if-env-changed=GIT_COMMIT");
+  println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+  println!("cargo:rerun-if-changed=../migrations");
+  println!("cargo:rerun-if-changed=../.git/HEAD");
+  println!("cargo:rerun-if-changed=../.git/refs");
+
+  println!("cargo:rustc-env=CAYOPAY_GIT_COMMIT={}", git_commit());
+  println!(
+    "cargo:rustc-env=CAYOPAY_BUILD_TIMESTAMP={}",
+    build_timestamp()
+  );
+  println!(
+    "cargo:rustc-env=CAYOPAY_MIGRATION_VERSION={}",
+    migration_version()
+  );
+}
+
+/// `GIT_COMMIT` wins so builds without a `.git` directory (e.g. Docker) can
+/// still report it.
+fn git_commit() -> String {
+  if let Ok(commit) = std::env::var("GIT_COMMIT") {
+    if !commit.trim().is_empty() {
+      return commit.trim().to_string();
+    }
+  }
+
+  Command::new("git")
+    .args(["rev-parse", "--short", "HEAD"])
+    .output()
+    .ok()
+    .filter(|o| o.status.success())
+    .and_then(|o| String::from_utf8(o.stdout).ok())
+    .map(|s| s.trim().to_string())
+    .filter(|s| !s.is_empty())
+    .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// Unix seconds, honouring `SOURCE_DATE_EPOCH` for reproducible builds.
+fn build_timestamp() -> u64 {
+  std::env::var("SOURCE_DATE_EPOCH")
+    .ok()
+    .and_then(|s| s.parse().ok())
+    .unwrap_or_else(|| {
+      SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+    })
+}
+
+/// Version prefix of the newest migration shipped with this build.
+fn migration_version() -> i64 {
+  let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../migrations");
+
+  fs::read_dir(dir)
+    .into_iter()
+    .flatten()
+    .filter_map(|entry| entry.ok())
+    .filter_map(|entry| {
+      let name = entry.file_name().into_string().ok()?;
+      name.split('_').next()?.parse::<i64>().ok()
+    })
+    .max()
+    .unwrap_or_default()
+}
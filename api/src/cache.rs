@@ -0,0 +1,32 @@
+use application::config::Config;
+use axum::http::{header, HeaderValue};
+use tower_http::set_header::SetResponseHeaderLayer;
+
+/// Caching behaviour shared by a group of routes.
+#[derive(Debug, Clone, Copy)]
+pub enum CacheClass {
+  /// Live data such as balances, never kept by browsers or proxies
+  NoStore,
+}
+
+impl CacheClass {
+  /// Sets `Cache-Control` on responses that don't already carry one, so
+  /// handlers can still override it.
+  pub fn layer(self, config: &Config) -> SetResponseHeaderLayer<HeaderValue> {
+    let (configured, fallback) = match self {
+      CacheClass::NoStore => (&config.cache_control_no_store, "no-store"),
+    };
+
+    let value = HeaderValue::from_str(configured).unwrap_or_else(|_| {
+      tracing::warn!(
+        "Invalid Cache-Control value {:?} for {:?}, using {:?}",
+        configured,
+        self,
+        fallback
+      );
+      HeaderValue::from_static(fallback)
+    });
+
+    SetResponseHeaderLayer::if_not_present(header::CACHE_CONTROL, value)
+  }
+}
@@ -0,0 +1,124 @@
+use std::{
+  collections::hash_map::DefaultHasher,
+  convert::Infallible,
+  hash::{Hash, Hasher},
+};
+
+use axum::{
+  async_trait,
+  extract::FromRequestParts,
+  http::{header, request::Parts, HeaderValue, StatusCode},
+  response::{IntoResponse, Response},
+};
+
+use domain::types::ListVersion;
+
+use crate::extractor::Authz;
+
+/// Listings answered with an ETag are kept by browsers, but only for the
+/// signed-in user and only after asking whether they are still current.
+const REVALIDATE: &str = "private, no-cache";
+
+/// Weak validator of a listing as one caller sees it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ETag(String);
+
+impl ETag {
+  /// Tag of a listing at `version`. Callers with other permissions see other
+  /// fields, so their tags differ too.
+  pub fn for_listing(version: ListVersion, authz: &Authz) -> Self {
+    let mut hasher = DefaultHasher::new();
+    authz.0.id.hash(&mut hasher);
+    authz.permissions().hash(&mut hasher);
+
+    Self(format!(
+      "W/\"{:x}-{:x}-{:x}\"",
+      version.count,
+      version.changed_at.map_or(0, |at| at.timestamp_micros()),
+      hasher.finish()
+    ))
+  }
+
+  /// The tag without its weak prefix, as compared by [`IfNoneMatch`].
+  fn opaque(&self) -> &str {
+    self.0.trim_start_matches("W/")
+  }
+}
+
+/// The `If-None-Match` header of a request, if any.
+#[derive(Debug, Default)]
+pub struct IfNoneMatch(Option<String>);
+
+impl IfNoneMatch {
+  /// Whether the client already has `etag`, by weak comparison.
+  pub fn matches(&self, etag: &ETag) -> bool {
+    let Some(header) = &self.0 else {
+      return false;
+    };
+
+    header
+      .split(',')
+      .map(str::trim)
+      .any(|candidate| candidate == "*" || candidate.trim_start_matches("W/") == etag.opaque())
+  }
+}
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for IfNoneMatch {
+  type Rejection = Infallible;
+
+  async fn from_request_parts(parts: &mut Parts, _: &S) -> Result<Self, Self::Rejection> {
+    Ok(Self(
+      parts
+        .headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string),
+    ))
+  }
+}
+
+/// Response to a conditional GET: the body, or `304 Not Modified` when the
+/// client's copy is still current. Both carry the ETag.
+pub enum Conditional<T> {
+  NotModified(ETag),
+  Modified(ETag, T),
+}
+
+impl<T: IntoResponse> IntoResponse for Conditional<T> {
+  fn into_response(self) -> Response {
+    let (etag, mut response) = match self {
+      Conditional::NotModified(etag) => (etag, StatusCode::NOT_MODIFIED.into_response()),
+      Conditional::Modified(etag, body) => (etag, body.into_response()),
+    };
+
+    let headers = response.headers_mut();
+    if let Ok(value) = HeaderValue::from_str(&etag.0) {
+      headers.insert(header::ETAG, value);
+    }
+    headers.insert(header::CACHE_CONTROL, HeaderValue::from_static(REVALIDATE));
+
+    response
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn if_none_match(value: &str) -> IfNoneMatch {
+    IfNoneMatch(Some(value.to_string()))
+  }
+
+  #[test]
+  fn test_matches_weakly_and_in_lists() {
+    let etag = ETag("W/\"2-5f-a1\"".to_string());
+
+    assert!(if_none_match("W/\"2-5f-a1\"").matches(&etag));
+    assert!(if_none_match("\"2-5f-a1\"").matches(&etag));
+    assert!(if_none_match("\"other\", W/\"2-5f-a1\"").matches(&etag));
+    assert!(if_none_match("*").matches(&etag));
+    assert!(!if_none_match("W/\"3-5f-a1\"").matches(&etag));
+    assert!(!IfNoneMatch::default().matches(&etag));
+  }
+}
@@ -0,0 +1,115 @@
+use crate::{
+  error::AppResult,
+  extractor::Authz,
+  models::{
+    ActorSummaryResponse, ListActorsQuery, PaginatedActorSummaryResponse, Redact,
+    RouteAuditResponse,
+  },
+  route_permissions,
+  route_permissions::Guard,
+  routes::Routes,
+  ApiDoc,
+};
+use application::{services::actor::ActorSummaryFilter, state::AppState};
+use axum::{
+  extract::{Query, State},
+  Json,
+};
+use domain::{types::PageRequest, Permission};
+use utoipa::OpenApi;
+
+/// Search users and guests
+///
+/// Users first, then guests, with how many wallets each holds and their
+/// combined balance. Served from summaries the server rebuilds shortly after
+/// every change, so a change can take a moment to show.
+#[utoipa::path(
+  get,
+  path = "/api/admin/actors",
+  params(ListActorsQuery),
+  responses(
+    (status = StatusCode::OK, description = "Page of actors; contact details are omitted without the matching read permission", body = PaginatedActorSummaryResponse),
+    (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+    (status = StatusCode::FORBIDDEN, description = "Forbidden", body = ErrorResponse),
+  ),
+  security(
+    ("session_cookie" = [])
+  )
+)]
+pub async fn list_actors(
+  State(state): State<AppState>,
+  authz: Authz,
+  Query(query): Query<ListActorsQuery>,
+) -> AppResult<Json<PaginatedActorSummaryResponse>> {
+  authz.require(Permission::ReadTransactions)?;
+
+  let filter = ActorSummaryFilter {
+    kind: query.kind,
+    search: query.search.filter(|s| !s.trim().is_empty()),
+  };
+  let page = PageRequest::new(query.page, query.per_page);
+
+  let actors = state
+    .actor_service
+    .list(filter, page)
+    .await?
+    .map(|actor| ActorSummaryResponse::from(actor).redact(&authz));
+
+  Ok(Json(actors.into()))
+}
+
+/// Audit which permission each route enforces
+///
+/// Lists every documented operation with the permissions it requires. Routes
+/// that declare none and are not deliberately public are flagged as
+/// unprotected; the same check runs at startup and in the test suite.
+#[utoipa::path(
+  get,
+  path = "/api/admin/route-audit",
+  responses(
+    (status = StatusCode::OK, description = "Every route and its guard, sorted by path", body = RouteAuditResponse),
+    (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+    (status = StatusCode::FORBIDDEN, description = "Forbidden", body = ErrorResponse),
+  ),
+  security(
+    ("session_cookie" = [])
+  )
+)]
+pub async fn route_audit(authz: Authz) -> AppResult<Json<RouteAuditResponse>> {
+  authz.require(Permission::ReadAuditLog)?;
+
+  let routes = route_permissions::coverage(&ApiDoc::openapi());
+
+  Ok(Json(routes.into()))
+}
+
+#[derive(OpenApi)]
+#[openapi(
+  paths(list_actors, route_audit,),
+  components(schemas(
+    ActorSummaryResponse,
+    PaginatedActorSummaryResponse,
+    RouteAuditResponse,
+    crate::models::RouteCoverageResponse,
+    crate::models::RequiredPermissionsResponse,
+  ))
+)]
+struct Api;
+
+pub fn openapi() -> utoipa::openapi::OpenApi {
+  Api::openapi()
+}
+
+pub fn router() -> Routes {
+  Routes::new()
+    .get(
+      "/actors",
+      list_actors,
+      Guard::All(&[Permission::ReadTransactions]),
+    )
+    .get(
+      "/route-audit",
+      route_audit,
+      Guard::All(&[Permission::ReadAuditLog]),
+    )
+}
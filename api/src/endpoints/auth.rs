@@ -1,17 +1,30 @@
+use std::net::SocketAddr;
+
 use axum::{
-  extract::State,
-  routing::{get, post},
-  Json, Router,
+  extract::{ConnectInfo, Path, State},
+  http::{header, HeaderMap},
+  Json,
 };
-use axum_extra::extract::cookie::{self, Cookie, CookieJar, SameSite};
+use axum_extra::extract::cookie::{Cookie, CookieJar, SameSite};
 
 use crate::{
   error::AppResult,
-  extractor::{Authn, ValidatedJson},
-  models::{LoginRequest, UserResponse},
+  extractor::{Audit, Authn, Authz, ValidatedJson},
+  models::{
+    AppTokenResponse, CheckedResource, DeviceNonceResponse, LoginRequest,
+    PasswordConfirmationResponse, PermissionCheck, PermissionCheckResult, PermissionChecksRequest,
+    PermissionChecksResponse, SessionResponse, UserResponse, VerifyPasswordRequest,
+  },
+  route_permissions::{Access, Guard},
+  routes::Routes,
+};
+use application::{
+  config::{Config, CookieSameSite},
+  error::AppError,
+  state::AppState,
 };
-use application::state::AppState;
-use domain::{Email, RawPassword};
+use domain::{AccessTokenId, AuditAction, Email, RawPassword, Session, SessionId};
+use utoipa::OpenApi;
 
 #[utoipa::path(
   post,
@@ -21,34 +34,157 @@ use domain::{Email, RawPassword};
     (status = StatusCode::OK, description = "Login successful", body = UserResponse),
     (status = StatusCode::BAD_REQUEST, description = "Validation error", body = ErrorResponse),
     (status = StatusCode::UNAUTHORIZED, description = "Invalid credentials", body = ErrorResponse),
+    (status = StatusCode::LOCKED, description = "Account locked after too many failed logins", body = ErrorResponse),
   )
 )]
 pub async fn login(
   State(state): State<AppState>,
+  ConnectInfo(addr): ConnectInfo<SocketAddr>,
+  audit: Audit,
+  headers: HeaderMap,
   jar: CookieJar,
   ValidatedJson(payload): ValidatedJson<LoginRequest>,
 ) -> AppResult<(CookieJar, Json<UserResponse>)> {
   let email = Email::new(payload.email);
   let password = RawPassword::new(payload.password);
 
-  let user = state.auth_service.login(email, password).await?;
-  let session = state.session_service.create_session(user.id).await?;
+  let user_agent = headers
+    .get(header::USER_AGENT)
+    .and_then(|v| v.to_str().ok())
+    .map(ToString::to_string);
+
+  let user = state
+    .auth_service
+    .login(email, password, Some(addr.ip().to_string()))
+    .await?;
+  let session = state
+    .session_service
+    .create_session(
+      user.id,
+      user_agent,
+      Some(addr.ip().to_string()),
+      payload.device_public_key,
+    )
+    .await?;
+
+  audit
+    .record(Some(user.actor_id), AuditAction::Login, user.id, None)
+    .await;
+
+  let cookie = lasting_session_cookie(&state, session);
+
+  Ok((jar.add(cookie), Json(user.into())))
+}
+
+/// Swap the session token for a new one
+///
+/// Also pushes the session's expiry out again as far as the idle timeout
+/// allows, but never past the longest a session may last after signing in.
+/// The old token stops working right away; the new one comes as a cookie.
+#[utoipa::path(
+  post,
+  path = "/api/auth/refresh",
+  responses(
+    (status = StatusCode::OK, description = "Session renewed", body = SessionResponse),
+    (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+  ),
+  security(
+    ("session_cookie" = [])
+  )
+)]
+pub async fn refresh(
+  State(state): State<AppState>,
+  jar: CookieJar,
+) -> AppResult<(CookieJar, Json<SessionResponse>)> {
+  let token = jar
+    .get(&state.config.session_cookie_name)
+    .map(|c| c.value().to_string())
+    .ok_or(AppError::Authentication)?;
+
+  let session = state.session_service.refresh_session(&token).await?;
+  let response = SessionResponse::new(session.clone(), Some(&session.token));
 
-  // TODO: Control cookie attributes based on environment (e.g., Secure in production)
-  let cookie = Cookie::build((state.config.session_cookie_name.clone(), session.token))
+  Ok((
+    jar.add(lasting_session_cookie(&state, session)),
+    Json(response),
+  ))
+}
+
+/// End the current session
+///
+/// Also clears the session cookie, even if the session was already gone.
+#[utoipa::path(
+  post,
+  path = "/api/auth/logout",
+  responses(
+    (status = StatusCode::OK, description = "Logged out"),
+  )
+)]
+pub async fn logout(
+  State(state): State<AppState>,
+  authn: Option<Authn>,
+  audit: Audit,
+  jar: CookieJar,
+) -> AppResult<CookieJar> {
+  if let Some(cookie) = jar.get(&state.config.session_cookie_name) {
+    state.session_service.end_session(cookie.value()).await?;
+  }
+
+  if let Some(Authn(user)) = authn {
+    audit
+      .record(Some(user.actor_id), AuditAction::Logout, user.id, None)
+      .await;
+  }
+
+  let mut removal = session_cookie(&state.config, String::new());
+  removal.set_max_age(time::Duration::ZERO);
+  removal.set_expires(time::OffsetDateTime::UNIX_EPOCH);
+
+  Ok(jar.add(removal))
+}
+
+/// The session cookie with the configured attributes, so setting and
+/// clearing it address the same cookie.
+fn session_cookie(config: &Config, token: String) -> Cookie<'static> {
+  let same_site = match config.session_cookie_same_site {
+    CookieSameSite::Strict => SameSite::Strict,
+    CookieSameSite::Lax => SameSite::Lax,
+    CookieSameSite::None => SameSite::None,
+  };
+
+  let mut cookie = Cookie::build((config.session_cookie_name.clone(), token))
     .path("/")
     .http_only(true)
-    .same_site(SameSite::Strict)
-    .expires(cookie::Expiration::DateTime(
-      time::OffsetDateTime::now_utc()
-        .checked_add(time::Duration::milliseconds(
-          session.expires_in.num_milliseconds(),
-        ))
-        .unwrap(),
-    ))
+    .secure(config.session_cookie_secure)
+    .same_site(same_site)
     .build();
+  if let Some(domain) = config
+    .session_cookie_domain
+    .clone()
+    .filter(|domain| !domain.is_empty())
+  {
+    cookie.set_domain(domain);
+  }
 
-  Ok((jar.add(cookie), Json(user.into())))
+  cookie
+}
+
+/// The cookie for `session`, kept by the browser until the session ends at
+/// the latest, as renewals may keep it going that long.
+fn lasting_session_cookie(state: &AppState, session: Session) -> Cookie<'static> {
+  let remaining = state.session_service.ends_at(&session) - chrono::Utc::now();
+  let ttl = match state.config.session_cookie_ttl_minutes {
+    Some(0) => None,
+    Some(minutes) => Some(chrono::Duration::minutes(minutes).min(remaining)),
+    None => Some(remaining),
+  };
+
+  let mut cookie = session_cookie(&state.config, session.token);
+  if let Some(ttl) = ttl {
+    cookie.set_max_age(time::Duration::seconds(ttl.num_seconds()));
+  }
+
+  cookie
 }
 
 #[utoipa::path(
@@ -66,8 +202,326 @@ pub async fn me(Authn(user): Authn) -> AppResult<Json<UserResponse>> {
   Ok(Json(user.into()))
 }
 
-pub fn router() -> Router<AppState> {
-  Router::new()
-    .route("/login", post(login))
-    .route("/me", get(me))
+/// Whether the signed-in user may take each of several actions, so screens
+/// can show only what works instead of finding out through 403s.
+///
+/// Evaluated like the endpoints do: the user's permissions, with owners of a
+/// shop allowed to manage it and read its reports, and owners of a wallet
+/// allowed to read it, regardless. Cash top-ups, withdrawals and legal holds
+/// need the permission even on one's own wallet.
+#[utoipa::path(
+  post,
+  path = "/api/auth/can",
+  request_body = PermissionChecksRequest,
+  responses(
+    (status = StatusCode::OK, description = "One result per check, in request order", body = PermissionChecksResponse),
+    (status = StatusCode::BAD_REQUEST, description = "Validation error", body = ErrorResponse),
+    (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+  ),
+  security(
+    ("session_cookie" = [])
+  )
+)]
+pub async fn can(
+  State(state): State<AppState>,
+  authz: Authz,
+  ValidatedJson(payload): ValidatedJson<PermissionChecksRequest>,
+) -> AppResult<Json<PermissionChecksResponse>> {
+  let mut results = Vec::with_capacity(payload.checks.len());
+  for check in payload.checks {
+    results.push(PermissionCheckResult {
+      allowed: is_allowed(&state, &authz, check).await?,
+      check,
+    });
+  }
+
+  Ok(Json(PermissionChecksResponse { results }))
+}
+
+async fn is_allowed(state: &AppState, authz: &Authz, check: PermissionCheck) -> AppResult<bool> {
+  Ok(match check.resource {
+    None => authz.has(check.action),
+    Some(CheckedResource::Shop { id }) => state
+      .shop_service
+      .get_by_id(id)
+      .await?
+      .is_some_and(|shop| authz.has_on_shop(&shop, check.action)),
+    Some(CheckedResource::Wallet { id }) => state
+      .wallet_service
+      .get_details(id)
+      .await?
+      .is_some_and(|wallet| authz.has_on_wallet_details(&wallet, check.action)),
+  })
+}
+
+#[utoipa::path(
+  post,
+  path = "/api/auth/verify-password",
+  request_body = VerifyPasswordRequest,
+  responses(
+    (status = StatusCode::OK, description = "Password confirmed, step-up token issued", body = PasswordConfirmationResponse),
+    (status = StatusCode::BAD_REQUEST, description = "Validation error", body = ErrorResponse),
+    (status = StatusCode::UNAUTHORIZED, description = "Unauthorized or wrong password", body = ErrorResponse),
+    (status = StatusCode::LOCKED, description = "Account locked after too many wrong passwords", body = ErrorResponse),
+  ),
+  security(
+    ("session_cookie" = [])
+  )
+)]
+pub async fn verify_password(
+  State(state): State<AppState>,
+  ConnectInfo(addr): ConnectInfo<SocketAddr>,
+  jar: CookieJar,
+  Authn(user): Authn,
+  audit: Audit,
+  ValidatedJson(payload): ValidatedJson<VerifyPasswordRequest>,
+) -> AppResult<Json<PasswordConfirmationResponse>> {
+  let session_token = jar
+    .get(&state.config.session_cookie_name)
+    .map(|c| c.value().to_string())
+    .ok_or(AppError::Authentication)?;
+
+  state
+    .auth_service
+    .verify_password(
+      &user,
+      RawPassword::new(payload.password),
+      Some(addr.ip().to_string()),
+    )
+    .await?;
+
+  let confirmation = state
+    .session_service
+    .issue_password_confirmation(&session_token)
+    .await?;
+
+  audit
+    .record(
+      Some(user.actor_id),
+      AuditAction::PasswordConfirmed,
+      user.id,
+      None,
+    )
+    .await;
+
+  Ok(Json(confirmation.into()))
+}
+
+/// Issue a device nonce
+///
+/// For sessions bound to a device key. The nonce is good for one sensitive
+/// request within a minute.
+#[utoipa::path(
+  post,
+  path = "/api/auth/device-nonce",
+  responses(
+    (status = StatusCode::OK, description = "Nonce issued", body = DeviceNonceResponse),
+    (status = StatusCode::BAD_REQUEST, description = "Session is not bound to a device", body = ErrorResponse),
+    (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+  ),
+  security(
+    ("session_cookie" = [])
+  )
+)]
+pub async fn issue_device_nonce(
+  State(state): State<AppState>,
+  jar: CookieJar,
+  _: Authn,
+) -> AppResult<Json<DeviceNonceResponse>> {
+  let session_token = jar
+    .get(&state.config.session_cookie_name)
+    .map(|c| c.value().to_string())
+    .ok_or(AppError::Authentication)?;
+
+  let nonce = state
+    .session_service
+    .issue_device_nonce(&session_token)
+    .await?;
+
+  Ok(Json(nonce.into()))
+}
+
+#[utoipa::path(
+  get,
+  path = "/api/auth/sessions",
+  responses(
+    (status = StatusCode::OK, description = "Active sessions of the current user", body = [SessionResponse]),
+    (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+  ),
+  security(
+    ("session_cookie" = [])
+  )
+)]
+pub async fn list_sessions(
+  State(state): State<AppState>,
+  jar: CookieJar,
+  Authn(user): Authn,
+) -> AppResult<Json<Vec<SessionResponse>>> {
+  let current_token = jar
+    .get(&state.config.session_cookie_name)
+    .map(|c| c.value().to_string());
+
+  let sessions = state.session_service.list_active(user.id).await?;
+  let response = sessions
+    .into_iter()
+    .map(|s| SessionResponse::new(s, current_token.as_deref()))
+    .collect();
+
+  Ok(Json(response))
+}
+
+#[utoipa::path(
+  delete,
+  path = "/api/auth/sessions/{session_id}",
+  params(
+    ("session_id" = Uuid, Path, description = "Session id")
+  ),
+  responses(
+    (status = StatusCode::OK, description = "Session revoked"),
+    (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+    (status = StatusCode::NOT_FOUND, description = "Session not found", body = ErrorResponse),
+  ),
+  security(
+    ("session_cookie" = [])
+  )
+)]
+pub async fn revoke_session(
+  State(state): State<AppState>,
+  Authn(user): Authn,
+  audit: Audit,
+  Path(session_id): Path<SessionId>,
+) -> AppResult<()> {
+  state
+    .session_service
+    .revoke_session(user.id, session_id)
+    .await?;
+
+  audit
+    .record(
+      Some(user.actor_id),
+      AuditAction::SessionRevoked,
+      session_id,
+      None,
+    )
+    .await;
+
+  Ok(())
+}
+
+#[utoipa::path(
+  get,
+  path = "/api/auth/app-tokens",
+  responses(
+    (status = StatusCode::OK, description = "Client apps the current user approved, newest first", body = [AppTokenResponse]),
+    (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+  ),
+  security(
+    ("session_cookie" = [])
+  )
+)]
+pub async fn list_app_tokens(
+  State(state): State<AppState>,
+  Authn(user): Authn,
+) -> AppResult<Json<Vec<AppTokenResponse>>> {
+  let tokens = state.client_app_service.list_tokens(user.id).await?;
+
+  Ok(Json(tokens.into_iter().map(Into::into).collect()))
+}
+
+#[utoipa::path(
+  delete,
+  path = "/api/auth/app-tokens/{token_id}",
+  params(
+    ("token_id" = Uuid, Path, description = "App token id")
+  ),
+  responses(
+    (status = StatusCode::OK, description = "Token revoked"),
+    (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+    (status = StatusCode::NOT_FOUND, description = "Token not found", body = ErrorResponse),
+  ),
+  security(
+    ("session_cookie" = [])
+  )
+)]
+pub async fn revoke_app_token(
+  State(state): State<AppState>,
+  Authn(user): Authn,
+  audit: Audit,
+  Path(token_id): Path<AccessTokenId>,
+) -> AppResult<()> {
+  state
+    .client_app_service
+    .revoke_token(user.id, token_id)
+    .await?;
+
+  audit
+    .record(
+      Some(user.actor_id),
+      AuditAction::AppTokenRevoked,
+      token_id,
+      None,
+    )
+    .await;
+
+  Ok(())
+}
+
+#[derive(OpenApi)]
+#[openapi(
+  paths(
+    login,
+    logout,
+    refresh,
+    me,
+    can,
+    verify_password,
+    issue_device_nonce,
+    list_sessions,
+    revoke_session,
+    list_app_tokens,
+    revoke_app_token,
+  ),
+  components(schemas(
+    LoginRequest,
+    VerifyPasswordRequest,
+    PermissionChecksRequest,
+    PermissionCheck,
+    CheckedResource,
+    PermissionCheckResult,
+    PermissionChecksResponse,
+    PasswordConfirmationResponse,
+    DeviceNonceResponse,
+    SessionResponse,
+    AppTokenResponse,
+    domain::DevicePublicKey,
+  ))
+)]
+struct Api;
+
+pub fn openapi() -> utoipa::openapi::OpenApi {
+  Api::openapi()
+}
+
+pub fn router() -> Routes {
+  Routes::new()
+    .post("/login", login, Access::Public)
+    // Clears the cookie even if the session is gone
+    .post("/logout", logout, Access::Public)
+    .post("/refresh", refresh, Guard::Authenticated)
+    .get("/me", me, Guard::Authenticated)
+    .post("/can", can, Guard::Authenticated)
+    .post("/verify-password", verify_password, Guard::Authenticated)
+    .post("/device-nonce", issue_device_nonce, Guard::Authenticated)
+    .get("/sessions", list_sessions, Guard::Authenticated)
+    .delete(
+      "/sessions/:session_id",
+      revoke_session,
+      Guard::Authenticated,
+    )
+    .get("/app-tokens", list_app_tokens, Guard::Authenticated)
+    .delete(
+      "/app-tokens/:token_id",
+      revoke_app_token,
+      Guard::Authenticated,
+    )
 }
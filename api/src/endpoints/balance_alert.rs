@@ -0,0 +1,161 @@
+use crate::{
+  error::AppResult,
+  extractor::{Audit, Authz, ValidatedJson},
+  models::{BalanceAlertResponse, CreateBalanceAlertRequest, CreatedBalanceAlertResponse},
+  route_permissions::Guard,
+  routes::Routes,
+};
+use application::state::AppState;
+use axum::{
+  extract::{Path, State},
+  http::StatusCode,
+  Json,
+};
+use domain::{AuditAction, BalanceAlertId, Permission};
+use serde_json::json;
+use utoipa::OpenApi;
+
+/// List balance alerts
+#[utoipa::path(
+  get,
+  path = "/api/balance-alerts",
+  responses(
+    (status = StatusCode::OK, description = "Balance alerts, oldest first", body = [BalanceAlertResponse]),
+    (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+    (status = StatusCode::FORBIDDEN, description = "Forbidden", body = ErrorResponse),
+  ),
+  security(
+    ("session_cookie" = [])
+  )
+)]
+pub async fn list_balance_alerts(
+  State(state): State<AppState>,
+  authz: Authz,
+) -> AppResult<Json<Vec<BalanceAlertResponse>>> {
+  authz.require(Permission::ConfigureSettings)?;
+
+  let alerts = state.balance_alert_service.list().await?;
+
+  Ok(Json(alerts.into_iter().map(Into::into).collect()))
+}
+
+/// Add a balance alert
+///
+/// Notifies by email and/or webhook once a system wallet, or the money owed to
+/// members, crosses the threshold. Balances are checked every few minutes.
+#[utoipa::path(
+  post,
+  path = "/api/balance-alerts",
+  request_body = CreateBalanceAlertRequest,
+  responses(
+    (status = StatusCode::CREATED, description = "Alert added; the webhook secret is only shown now", body = CreatedBalanceAlertResponse),
+    (status = StatusCode::BAD_REQUEST, description = "Validation error or nobody to notify", body = ErrorResponse),
+    (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+    (status = StatusCode::FORBIDDEN, description = "Forbidden", body = ErrorResponse),
+  ),
+  security(
+    ("session_cookie" = [])
+  )
+)]
+pub async fn create_balance_alert(
+  State(state): State<AppState>,
+  authz: Authz,
+  audit: Audit,
+  ValidatedJson(payload): ValidatedJson<CreateBalanceAlertRequest>,
+) -> AppResult<(StatusCode, Json<CreatedBalanceAlertResponse>)> {
+  authz.require(Permission::ConfigureSettings)?;
+
+  let alert = state
+    .balance_alert_service
+    .create(authz.0.actor_id, payload.into())
+    .await?;
+
+  audit
+    .record(
+      Some(authz.0.actor_id),
+      AuditAction::BalanceAlertCreated,
+      alert.id,
+      Some(json!({
+        "wallet_label": alert.wallet_label,
+        "direction": alert.direction,
+        "threshold_cents": alert.threshold_cents,
+      })),
+    )
+    .await;
+
+  Ok((StatusCode::CREATED, Json(alert.into())))
+}
+
+/// Remove a balance alert
+#[utoipa::path(
+  delete,
+  path = "/api/balance-alerts/{alert_id}",
+  params(
+    ("alert_id" = Uuid, Path, description = "Balance alert id")
+  ),
+  responses(
+    (status = StatusCode::NO_CONTENT, description = "Alert removed"),
+    (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+    (status = StatusCode::FORBIDDEN, description = "Forbidden", body = ErrorResponse),
+    (status = StatusCode::NOT_FOUND, description = "Alert not found", body = ErrorResponse),
+  ),
+  security(
+    ("session_cookie" = [])
+  )
+)]
+pub async fn remove_balance_alert(
+  State(state): State<AppState>,
+  authz: Authz,
+  audit: Audit,
+  Path(alert_id): Path<BalanceAlertId>,
+) -> AppResult<StatusCode> {
+  authz.require(Permission::ConfigureSettings)?;
+
+  let alert = state.balance_alert_service.remove(alert_id).await?;
+
+  audit
+    .record(
+      Some(authz.0.actor_id),
+      AuditAction::BalanceAlertRemoved,
+      alert.id,
+      None,
+    )
+    .await;
+
+  Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(OpenApi)]
+#[openapi(
+  paths(list_balance_alerts, create_balance_alert, remove_balance_alert,),
+  components(schemas(
+    CreateBalanceAlertRequest,
+    BalanceAlertResponse,
+    CreatedBalanceAlertResponse,
+    domain::BalanceAlertDirection,
+  ))
+)]
+struct Api;
+
+pub fn openapi() -> utoipa::openapi::OpenApi {
+  Api::openapi()
+}
+
+pub fn router() -> Routes {
+  Routes::new()
+    .get(
+      "/",
+      list_balance_alerts,
+      Guard::All(&[Permission::ConfigureSettings]),
+    )
+    .post(
+      "/",
+      create_balance_alert,
+      Guard::All(&[Permission::ConfigureSettings]),
+    )
+    .delete(
+      "/:alert_id",
+      remove_balance_alert,
+      Guard::All(&[Permission::ConfigureSettings]),
+    )
+}
@@ -0,0 +1,122 @@
+use crate::{
+  error::AppResult,
+  extractor::{Audit, Authz, ValidatedJson},
+  models::{
+    ChargebackReportQuery, ChargebackReportResponse, ChargebackResponse, ResolveChargebackRequest,
+  },
+  route_permissions::Guard,
+  routes::Routes,
+};
+use application::state::AppState;
+use axum::{
+  extract::{Path, Query, State},
+  Json,
+};
+use domain::{AuditAction, ChargebackId, Permission};
+use utoipa::OpenApi;
+
+/// Chargeback report
+///
+/// Online top-ups disputed by the payer's bank, with totals by outcome.
+#[utoipa::path(
+  get,
+  path = "/api/chargebacks",
+  params(ChargebackReportQuery),
+  responses(
+    (status = StatusCode::OK, description = "Chargebacks with totals", body = ChargebackReportResponse),
+    (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+    (status = StatusCode::FORBIDDEN, description = "Forbidden", body = ErrorResponse),
+  ),
+  security(
+    ("session_cookie" = [])
+  )
+)]
+pub async fn chargeback_report(
+  State(state): State<AppState>,
+  authz: Authz,
+  Query(query): Query<ChargebackReportQuery>,
+) -> AppResult<Json<ChargebackReportResponse>> {
+  authz.require(Permission::ReadReports)?;
+
+  let chargebacks = state.chargeback_service.list(query.unresolved).await?;
+
+  Ok(Json(chargebacks.into()))
+}
+
+/// Resolve a chargeback
+///
+/// Lets the wallet be topped up online again, e.g. once the member paid the
+/// missing amount.
+#[utoipa::path(
+  post,
+  path = "/api/chargebacks/{chargeback_id}/resolve",
+  request_body = ResolveChargebackRequest,
+  params(
+    ("chargeback_id" = Uuid, Path, description = "Chargeback id")
+  ),
+  responses(
+    (status = StatusCode::OK, description = "Chargeback resolved", body = ChargebackResponse),
+    (status = StatusCode::BAD_REQUEST, description = "Validation error or chargeback already resolved", body = ErrorResponse),
+    (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+    (status = StatusCode::FORBIDDEN, description = "Forbidden", body = ErrorResponse),
+    (status = StatusCode::NOT_FOUND, description = "Chargeback not found", body = ErrorResponse),
+  ),
+  security(
+    ("session_cookie" = [])
+  )
+)]
+pub async fn resolve_chargeback(
+  State(state): State<AppState>,
+  authz: Authz,
+  audit: Audit,
+  Path(chargeback_id): Path<ChargebackId>,
+  ValidatedJson(payload): ValidatedJson<ResolveChargebackRequest>,
+) -> AppResult<Json<ChargebackResponse>> {
+  authz.require(Permission::ReviewSuspiciousActivity)?;
+
+  let chargeback = state
+    .chargeback_service
+    .resolve(authz.0.actor_id, chargeback_id, payload.note)
+    .await?;
+
+  audit
+    .record(
+      Some(authz.0.actor_id),
+      AuditAction::ChargebackResolved,
+      chargeback_id,
+      None,
+    )
+    .await;
+
+  Ok(Json(chargeback.into()))
+}
+
+#[derive(OpenApi)]
+#[openapi(
+  paths(chargeback_report, resolve_chargeback,),
+  components(schemas(
+    ChargebackResponse,
+    ChargebackReportResponse,
+    ResolveChargebackRequest,
+    domain::ChargebackStatus,
+  ))
+)]
+struct Api;
+
+pub fn openapi() -> utoipa::openapi::OpenApi {
+  Api::openapi()
+}
+
+pub fn router() -> Routes {
+  Routes::new()
+    .get(
+      "/",
+      chargeback_report,
+      Guard::All(&[Permission::ReadReports]),
+    )
+    .post(
+      "/:chargeback_id/resolve",
+      resolve_chargeback,
+      Guard::All(&[Permission::ReviewSuspiciousActivity]),
+    )
+}
@@ -0,0 +1,300 @@
+use crate::{
+  error::AppResult,
+  extractor::{Audit, Authn, Authz, ValidatedJson},
+  models::{
+    AccessTokenRequest, AccessTokenResponse, AuthorizationResponse, AuthorizeRequest,
+    ClientAppResponse, CreateClientAppRequest,
+  },
+  route_permissions::{Access, Guard},
+  routes::Routes,
+};
+use application::{error::AppError, state::AppState};
+use axum::{
+  extract::{Path, State},
+  http::StatusCode,
+  Json,
+};
+use domain::{AuditAction, ClientAppId, Permission};
+use serde_json::json;
+use utoipa::OpenApi;
+
+/// List client apps
+#[utoipa::path(
+  get,
+  path = "/api/client-apps",
+  responses(
+    (status = StatusCode::OK, description = "Registered client apps, by name", body = [ClientAppResponse]),
+    (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+    (status = StatusCode::FORBIDDEN, description = "Forbidden", body = ErrorResponse),
+  ),
+  security(
+    ("session_cookie" = [])
+  )
+)]
+pub async fn list_client_apps(
+  State(state): State<AppState>,
+  authz: Authz,
+) -> AppResult<Json<Vec<ClientAppResponse>>> {
+  authz.require(Permission::ConfigureSettings)?;
+
+  let apps = state.client_app_service.list().await?;
+
+  Ok(Json(apps.into_iter().map(Into::into).collect()))
+}
+
+/// Register a client app
+///
+/// Lets a third-party app, such as a companion app or a kiosk, ask users for
+/// scoped access to their account.
+#[utoipa::path(
+  post,
+  path = "/api/client-apps",
+  request_body = CreateClientAppRequest,
+  responses(
+    (status = StatusCode::CREATED, description = "App registered", body = ClientAppResponse),
+    (status = StatusCode::BAD_REQUEST, description = "Validation error", body = ErrorResponse),
+    (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+    (status = StatusCode::FORBIDDEN, description = "Forbidden", body = ErrorResponse),
+  ),
+  security(
+    ("session_cookie" = [])
+  )
+)]
+pub async fn create_client_app(
+  State(state): State<AppState>,
+  authz: Authz,
+  audit: Audit,
+  ValidatedJson(payload): ValidatedJson<CreateClientAppRequest>,
+) -> AppResult<(StatusCode, Json<ClientAppResponse>)> {
+  authz.require(Permission::ConfigureSettings)?;
+
+  let app = state
+    .client_app_service
+    .register(authz.0.actor_id, payload.into())
+    .await?;
+
+  audit
+    .record(
+      Some(authz.0.actor_id),
+      AuditAction::ClientAppRegistered,
+      app.id,
+      Some(json!({
+        "name": app.name,
+        "redirect_uris": app.redirect_uris,
+        "allowed_scopes": app.allowed_scopes,
+      })),
+    )
+    .await;
+
+  Ok((StatusCode::CREATED, Json(app.into())))
+}
+
+/// Get a client app
+///
+/// For showing users what they are about to approve.
+#[utoipa::path(
+  get,
+  path = "/api/client-apps/{client_app_id}",
+  params(
+    ("client_app_id" = Uuid, Path, description = "Client app id")
+  ),
+  responses(
+    (status = StatusCode::OK, description = "The app", body = ClientAppResponse),
+    (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+    (status = StatusCode::NOT_FOUND, description = "App not found", body = ErrorResponse),
+  ),
+  security(
+    ("session_cookie" = [])
+  )
+)]
+pub async fn get_client_app(
+  State(state): State<AppState>,
+  _: Authn,
+  Path(client_app_id): Path<ClientAppId>,
+) -> AppResult<Json<ClientAppResponse>> {
+  let app = state.client_app_service.get(client_app_id).await?;
+
+  Ok(Json(app.into()))
+}
+
+/// Remove a client app
+///
+/// Also revokes every token issued to it.
+#[utoipa::path(
+  delete,
+  path = "/api/client-apps/{client_app_id}",
+  params(
+    ("client_app_id" = Uuid, Path, description = "Client app id")
+  ),
+  responses(
+    (status = StatusCode::NO_CONTENT, description = "App removed"),
+    (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+    (status = StatusCode::FORBIDDEN, description = "Forbidden", body = ErrorResponse),
+    (status = StatusCode::NOT_FOUND, description = "App not found", body = ErrorResponse),
+  ),
+  security(
+    ("session_cookie" = [])
+  )
+)]
+pub async fn remove_client_app(
+  State(state): State<AppState>,
+  authz: Authz,
+  audit: Audit,
+  Path(client_app_id): Path<ClientAppId>,
+) -> AppResult<StatusCode> {
+  authz.require(Permission::ConfigureSettings)?;
+
+  state.client_app_service.remove(client_app_id).await?;
+
+  audit
+    .record(
+      Some(authz.0.actor_id),
+      AuditAction::ClientAppRemoved,
+      client_app_id,
+      None,
+    )
+    .await;
+
+  Ok(StatusCode::NO_CONTENT)
+}
+
+/// Approve a client app
+///
+/// Grants the app the requested scopes on the signed-in user's account. Send
+/// the user back to `redirect_uri` with the returned code, which the app
+/// exchanges at `POST /api/oauth/token` within ten minutes.
+#[utoipa::path(
+  post,
+  path = "/api/oauth/authorize",
+  request_body = AuthorizeRequest,
+  responses(
+    (status = StatusCode::OK, description = "Approved", body = AuthorizationResponse),
+    (status = StatusCode::BAD_REQUEST, description = "Validation error, unregistered redirect URI or scopes the app may not ask for", body = ErrorResponse),
+    (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+    (status = StatusCode::NOT_FOUND, description = "App not found", body = ErrorResponse),
+  ),
+  security(
+    ("session_cookie" = [])
+  )
+)]
+pub async fn authorize(
+  State(state): State<AppState>,
+  Authn(user): Authn,
+  audit: Audit,
+  ValidatedJson(payload): ValidatedJson<AuthorizeRequest>,
+) -> AppResult<Json<AuthorizationResponse>> {
+  let code = state
+    .client_app_service
+    .authorize(&user, payload.into())
+    .await?;
+
+  audit
+    .record(
+      Some(user.actor_id),
+      AuditAction::ClientAppAuthorized,
+      code.client_app_id,
+      Some(json!({ "scopes": code.scopes })),
+    )
+    .await;
+
+  Ok(Json(code.into()))
+}
+
+/// Exchange a code for an access token
+///
+/// Called by the app with the code from its redirect and the PKCE code
+/// verifier. Each code works once.
+#[utoipa::path(
+  post,
+  path = "/api/oauth/token",
+  request_body = AccessTokenRequest,
+  responses(
+    (status = StatusCode::OK, description = "Token issued", body = AccessTokenResponse),
+    (status = StatusCode::BAD_REQUEST, description = "Validation error or unsupported grant type", body = ErrorResponse),
+    (status = StatusCode::UNAUTHORIZED, description = "Code unknown, used, expired or not matching the request", body = ErrorResponse),
+  )
+)]
+pub async fn issue_token(
+  State(state): State<AppState>,
+  audit: Audit,
+  ValidatedJson(payload): ValidatedJson<AccessTokenRequest>,
+) -> AppResult<Json<AccessTokenResponse>> {
+  if payload.grant_type != "authorization_code" {
+    return Err(
+      AppError::BadRequest(format!("Unsupported grant type {}", payload.grant_type)).into(),
+    );
+  }
+
+  let token = state
+    .client_app_service
+    .exchange_code(payload.into())
+    .await?;
+
+  // The app acts for the user who approved it
+  let user = state.user_service.get_by_id(token.user_id).await?;
+  audit
+    .record(
+      user.map(|user| user.actor_id),
+      AuditAction::AppTokenIssued,
+      token.id,
+      Some(json!({
+        "client_app_id": token.client_app_id,
+        "scopes": token.scopes,
+      })),
+    )
+    .await;
+
+  Ok(Json(token.into()))
+}
+
+#[derive(OpenApi)]
+#[openapi(
+  paths(
+    list_client_apps,
+    create_client_app,
+    get_client_app,
+    remove_client_app,
+    authorize,
+    issue_token,
+  ),
+  components(schemas(
+    CreateClientAppRequest,
+    ClientAppResponse,
+    AuthorizeRequest,
+    AuthorizationResponse,
+    AccessTokenRequest,
+    AccessTokenResponse,
+  ))
+)]
+struct Api;
+
+pub fn openapi() -> utoipa::openapi::OpenApi {
+  Api::openapi()
+}
+
+pub fn router() -> Routes {
+  Routes::new()
+    .get(
+      "/client-apps",
+      list_client_apps,
+      Guard::All(&[Permission::ConfigureSettings]),
+    )
+    .post(
+      "/client-apps",
+      create_client_app,
+      Guard::All(&[Permission::ConfigureSettings]),
+    )
+    .get(
+      "/client-apps/:client_app_id",
+      get_client_app,
+      Guard::Authenticated,
+    )
+    .delete(
+      "/client-apps/:client_app_id",
+      remove_client_app,
+      Guard::All(&[Permission::ConfigureSettings]),
+    )
+    .post("/oauth/authorize", authorize, Guard::Authenticated)
+    // The authorization code and PKCE verifier are the credential
+    .post("/oauth/token", issue_token, Access::Public)
+}
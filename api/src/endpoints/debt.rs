@@ -0,0 +1,68 @@
+use crate::{
+  error::AppResult,
+  extractor::Authz,
+  models::{
+    DebtAgeResponse, DebtCurrencyResponse, DebtorReportQuery, DebtorReportResponse, DebtorResponse,
+    Redact,
+  },
+  route_permissions::Guard,
+  routes::Routes,
+};
+use application::state::AppState;
+use axum::{
+  extract::{Query, State},
+  Json,
+};
+use domain::Permission;
+use utoipa::OpenApi;
+
+/// Debtors report
+///
+/// Member wallets below zero with how long they have been, totalled per age.
+/// Wallets are checked hourly: those below zero for longer than the grace
+/// period are flagged, and their owners are emailed payment reminders.
+#[utoipa::path(
+  get,
+  path = "/api/debts",
+  params(DebtorReportQuery),
+  responses(
+    (status = StatusCode::OK, description = "Open debts with totals per currency and age; emails are omitted without permission to read the owner's details", body = DebtorReportResponse),
+    (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+    (status = StatusCode::FORBIDDEN, description = "Forbidden", body = ErrorResponse),
+  ),
+  security(
+    ("session_cookie" = [])
+  )
+)]
+pub async fn debtor_report(
+  State(state): State<AppState>,
+  authz: Authz,
+  Query(query): Query<DebtorReportQuery>,
+) -> AppResult<Json<DebtorReportResponse>> {
+  authz.require(Permission::ReadReports)?;
+
+  let debtors = state.debt_service.list_debtors(query.flagged).await?;
+
+  Ok(Json(DebtorReportResponse::from(debtors).redact(&authz)))
+}
+
+#[derive(OpenApi)]
+#[openapi(
+  paths(debtor_report),
+  components(schemas(
+    DebtorReportResponse,
+    DebtorResponse,
+    DebtCurrencyResponse,
+    DebtAgeResponse,
+    domain::DebtAge
+  ))
+)]
+struct Api;
+
+pub fn openapi() -> utoipa::openapi::OpenApi {
+  Api::openapi()
+}
+
+pub fn router() -> Routes {
+  Routes::new().get("/", debtor_report, Guard::All(&[Permission::ReadReports]))
+}
@@ -0,0 +1,84 @@
+use crate::{
+  error::AppResult,
+  extractor::Authz,
+  models::{EventLogQuery, EventLogResponse, LoggedEventResponse},
+  route_permissions::Guard,
+  routes::Routes,
+};
+use application::state::AppState;
+use axum::{
+  extract::{Query, State},
+  Json,
+};
+use domain::Permission;
+use utoipa::OpenApi;
+
+const DEFAULT_LOG_PAGE: i64 = 100;
+const MAX_LOG_PAGE: i64 = 1000;
+
+/// Read the event log
+///
+/// Everything that happened, oldest first, for keeping an external system
+/// such as a data warehouse in sync without access to the database. Pass the
+/// returned cursor to the next request; events are never reordered or
+/// inserted before one already returned, so reading from any cursor again
+/// replays the same events. A cursor no event has, such as one from another
+/// deployment, is refused; start over from 0. Client apps need the `events`
+/// scope.
+#[utoipa::path(
+  get,
+  path = "/api/events/log",
+  params(EventLogQuery),
+  responses(
+    (status = StatusCode::OK, description = "Events after the cursor, possibly none", body = EventLogResponse),
+    (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+    (status = StatusCode::FORBIDDEN, description = "Forbidden, or an access token without the events scope", body = ErrorResponse),
+    (status = StatusCode::GONE, description = "No event has the cursor", body = ErrorResponse),
+  ),
+  security(
+    ("session_cookie" = []),
+    ("app_token" = ["events"])
+  )
+)]
+pub async fn read_event_log(
+  State(state): State<AppState>,
+  authz: Authz,
+  Query(query): Query<EventLogQuery>,
+) -> AppResult<Json<EventLogResponse>> {
+  authz.require(Permission::ExportData)?;
+
+  let since = query.since.unwrap_or(0).max(0);
+  let limit = query
+    .limit
+    .unwrap_or(DEFAULT_LOG_PAGE)
+    .clamp(1, MAX_LOG_PAGE);
+
+  let records = state.domain_event_service.log_after(since, limit).await?;
+  let has_more = records.len() as i64 == limit;
+  let cursor = records.last().map_or(since, |record| record.sequence);
+
+  Ok(Json(EventLogResponse {
+    events: records.into_iter().map(LoggedEventResponse::from).collect(),
+    cursor,
+    has_more,
+  }))
+}
+
+#[derive(OpenApi)]
+#[openapi(
+  paths(read_event_log),
+  components(schemas(EventLogResponse, LoggedEventResponse))
+)]
+struct Api;
+
+pub fn openapi() -> utoipa::openapi::OpenApi {
+  Api::openapi()
+}
+
+pub fn router() -> Routes {
+  Routes::new().get(
+    "/events/log",
+    read_event_log,
+    Guard::All(&[Permission::ExportData]),
+  )
+}
@@ -0,0 +1,108 @@
+use crate::{
+  error::AppResult, extractor::Authz, models::ShopOfferingResponse, route_permissions::Guard,
+  routes::Routes,
+};
+use application::state::AppState;
+use axum::{
+  extract::{Path, State},
+  http::StatusCode,
+  Json,
+};
+use domain::ShopOfferingId;
+use utoipa::OpenApi;
+
+/// List the caller's favorite offerings
+#[utoipa::path(
+  get,
+  path = "/api/favorites",
+  responses(
+    (status = StatusCode::OK, description = "Favorites at their current price, most recently added first", body = [ShopOfferingResponse]),
+    (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+  ),
+  security(
+    ("session_cookie" = [])
+  )
+)]
+pub async fn list_favorites(
+  State(state): State<AppState>,
+  authz: Authz,
+) -> AppResult<Json<Vec<ShopOfferingResponse>>> {
+  let offerings = state.shop_service.favorites(authz.0.id).await?;
+
+  Ok(Json(offerings.into_iter().map(Into::into).collect()))
+}
+
+/// Add an offering to the caller's favorites
+#[utoipa::path(
+  put,
+  path = "/api/favorites/{offering_id}",
+  params(
+    ("offering_id" = Uuid, Path, description = "Offering id")
+  ),
+  responses(
+    (status = StatusCode::OK, description = "Offering is a favorite", body = ShopOfferingResponse),
+    (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+    (status = StatusCode::NOT_FOUND, description = "Offering not found", body = ErrorResponse),
+  ),
+  security(
+    ("session_cookie" = [])
+  )
+)]
+pub async fn add_favorite(
+  State(state): State<AppState>,
+  authz: Authz,
+  Path(offering_id): Path<ShopOfferingId>,
+) -> AppResult<Json<ShopOfferingResponse>> {
+  let offering = state
+    .shop_service
+    .add_favorite(authz.0.id, offering_id)
+    .await?;
+
+  Ok(Json(offering.into()))
+}
+
+/// Remove an offering from the caller's favorites
+#[utoipa::path(
+  delete,
+  path = "/api/favorites/{offering_id}",
+  params(
+    ("offering_id" = Uuid, Path, description = "Offering id")
+  ),
+  responses(
+    (status = StatusCode::NO_CONTENT, description = "Offering is no favorite"),
+    (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+  ),
+  security(
+    ("session_cookie" = [])
+  )
+)]
+pub async fn remove_favorite(
+  State(state): State<AppState>,
+  authz: Authz,
+  Path(offering_id): Path<ShopOfferingId>,
+) -> AppResult<StatusCode> {
+  state
+    .shop_service
+    .remove_favorite(authz.0.id, offering_id)
+    .await?;
+
+  Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(OpenApi)]
+#[openapi(
+  paths(list_favorites, add_favorite, remove_favorite,),
+  components(schemas(ShopOfferingResponse,))
+)]
+struct Api;
+
+pub fn openapi() -> utoipa::openapi::OpenApi {
+  Api::openapi()
+}
+
+pub fn router() -> Routes {
+  Routes::new()
+    .get("/", list_favorites, Guard::Authenticated)
+    .put("/:offering_id", add_favorite, Guard::Authenticated)
+    .delete("/:offering_id", remove_favorite, Guard::Authenticated)
+}
@@ -1,29 +1,457 @@
-use crate::{error::AppResult, extractor::Authz, models::GuestResponse};
-use application::state::AppState;
-use axum::{extract::State, routing::get, Json, Router};
-use domain::Permission;
+use crate::{
+  conditional::{Conditional, ETag, IfNoneMatch},
+  error::AppResult,
+  extractor::{Audit, Authn, Authz, ValidatedJson},
+  models::{
+    AccountNoteRequest, AccountNoteResponse, AssignIdentifierRequest, ClaimGuestRequest,
+    CreateGuestRequest, CreatedGuestResponse, GuestClaimResponse, GuestLookupResponse,
+    GuestResponse, ListGuestsQuery, PaginatedGuestResponse, Redact,
+  },
+  route_permissions::Guard,
+  routes::Routes,
+};
+use application::{
+  error::AppError,
+  services::{guest::GuestFilter, note::NoteSubject},
+  state::AppState,
+};
+use axum::{
+  extract::{Path, Query, State},
+  http::StatusCode,
+  Json,
+};
+use domain::{
+  types::{Money, PageRequest},
+  AuditAction, Email, GuestId, Permission,
+};
+use serde_json::json;
+use utoipa::OpenApi;
 
+/// Add a guest
+///
+/// Refused while users or guests with a similar email are likely the same
+/// person, listing them so staff can hand out the existing account instead.
+/// Sent with `force`, the guest is added anyway.
+#[utoipa::path(
+    post,
+    path = "/api/guests",
+    request_body = CreateGuestRequest,
+    responses(
+        (status = StatusCode::CREATED, description = "Guest and wallet created, with the possible duplicates it was forced past", body = CreatedGuestResponse),
+        (status = StatusCode::BAD_REQUEST, description = "Validation error", body = ErrorResponse),
+        (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+        (status = StatusCode::FORBIDDEN, description = "Forbidden", body = ErrorResponse),
+        (status = StatusCode::CONFLICT, description = "The person likely has an account; details list the candidates", body = ErrorResponse),
+    ),
+    security(
+        ("session_cookie" = [])
+    )
+)]
+pub async fn create_guest(
+  State(state): State<AppState>,
+  authz: Authz,
+  audit: Audit,
+  ValidatedJson(payload): ValidatedJson<CreateGuestRequest>,
+) -> AppResult<(StatusCode, Json<CreatedGuestResponse>)> {
+  authz.require(Permission::CreateGuest)?;
+
+  let email = payload.email.map(Email::new);
+  let possible_duplicates = match &email {
+    Some(email) => state.actor_service.find_duplicates(email).await?,
+    None => Vec::new(),
+  };
+  if !payload.force && !possible_duplicates.is_empty() {
+    return Err(AppError::PossibleDuplicates(possible_duplicates).into());
+  }
+
+  let guest = state.guest_service.create_guest(email).await?;
+
+  audit
+    .record(
+      Some(authz.0.actor_id),
+      AuditAction::GuestCreated,
+      guest.id,
+      None,
+    )
+    .await;
+
+  Ok((
+    StatusCode::CREATED,
+    Json(CreatedGuestResponse {
+      guest: guest.into(),
+      possible_duplicates: possible_duplicates.into_iter().map(Into::into).collect(),
+    }),
+  ))
+}
+
+/// List guests
+///
+/// Oldest first; balances are those of the guests' wallets.
 #[utoipa::path(
     get,
     path = "/api/guests",
+    params(
+        ListGuestsQuery,
+        ("If-None-Match" = Option<String>, Header, description = "ETag of the copy the client has")
+    ),
     responses(
-        (status = StatusCode::OK, description = "List of all guests", body = Vec<GuestResponse>),
+        (status = StatusCode::OK, description = "Page of guests; contact details and identifiers are omitted without ReadGuestDetails", body = PaginatedGuestResponse),
+        (status = StatusCode::NOT_MODIFIED, description = "The client's copy is still current"),
         (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
         (status = StatusCode::FORBIDDEN, description = "Forbidden", body = ErrorResponse),
+    ),
+    security(
+        ("session_cookie" = [])
     )
 )]
 pub async fn list_guests(
   State(state): State<AppState>,
   authz: Authz,
-) -> AppResult<Json<Vec<GuestResponse>>> {
+  Query(query): Query<ListGuestsQuery>,
+  if_none_match: IfNoneMatch,
+) -> AppResult<Conditional<Json<PaginatedGuestResponse>>> {
+  authz.require_any(&[Permission::ListGuests, Permission::ReadGuestDetails])?;
+
+  let currency = match query.currency {
+    Some(currency) => currency,
+    None => state.settings_service.get().await?.currency,
+  };
+  let filter = GuestFilter {
+    currency,
+    min_balance: query
+      .min_balance
+      .or(query.min_balance_cents.map(Money::from_minor)),
+    max_balance: query
+      .max_balance
+      .or(query.max_balance_cents.map(Money::from_minor)),
+    card_bound: query.card_bound,
+  };
+  let page = PageRequest::new(query.page, query.per_page);
+
+  let version = state.guest_service.list_version(&filter).await?;
+  let etag = ETag::for_listing(version, &authz);
+  if if_none_match.matches(&etag) {
+    return Ok(Conditional::NotModified(etag));
+  }
+
+  let guests = state
+    .guest_service
+    .list(filter, page)
+    .await?
+    .map(|guest| GuestResponse::from(guest).redact(&authz));
+
+  Ok(Conditional::Modified(etag, Json(guests.into())))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/guests/{guest_id}/identifier",
+    request_body = AssignIdentifierRequest,
+    params(
+        ("guest_id" = Uuid, Path, description = "Guest id")
+    ),
+    responses(
+        (status = StatusCode::OK, description = "Identifier bound to the guest", body = GuestResponse),
+        (status = StatusCode::BAD_REQUEST, description = "Validation error", body = ErrorResponse),
+        (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+        (status = StatusCode::FORBIDDEN, description = "Forbidden", body = ErrorResponse),
+        (status = StatusCode::NOT_FOUND, description = "Guest not found", body = ErrorResponse),
+        (status = StatusCode::CONFLICT, description = "Identifier bound to another guest", body = ErrorResponse),
+    ),
+    security(
+        ("session_cookie" = [])
+    )
+)]
+pub async fn assign_identifier(
+  State(state): State<AppState>,
+  authz: Authz,
+  audit: Audit,
+  Path(guest_id): Path<GuestId>,
+  ValidatedJson(payload): ValidatedJson<AssignIdentifierRequest>,
+) -> AppResult<Json<GuestResponse>> {
+  authz.require(Permission::ManageGuestIdentifiers)?;
+
+  let guest = state
+    .guest_service
+    .assign_identifier(guest_id, payload.identifier)
+    .await?;
+
+  audit
+    .record(
+      Some(authz.0.actor_id),
+      AuditAction::GuestIdentifierAssigned,
+      guest.id,
+      None,
+    )
+    .await;
+
+  Ok(Json(guest.into()))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/guests/{guest_id}/identifier/rotate",
+    params(
+        ("guest_id" = Uuid, Path, description = "Guest id")
+    ),
+    responses(
+        (status = StatusCode::OK, description = "New identifier generated", body = GuestResponse),
+        (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+        (status = StatusCode::FORBIDDEN, description = "Forbidden", body = ErrorResponse),
+        (status = StatusCode::NOT_FOUND, description = "Guest not found", body = ErrorResponse),
+    ),
+    security(
+        ("session_cookie" = [])
+    )
+)]
+pub async fn rotate_identifier(
+  State(state): State<AppState>,
+  authz: Authz,
+  audit: Audit,
+  Path(guest_id): Path<GuestId>,
+) -> AppResult<Json<GuestResponse>> {
+  authz.require(Permission::ManageGuestIdentifiers)?;
+
+  let guest = state.guest_service.rotate_identifier(guest_id).await?;
+
+  audit
+    .record(
+      Some(authz.0.actor_id),
+      AuditAction::GuestIdentifierRotated,
+      guest.id,
+      None,
+    )
+    .await;
+
+  Ok(Json(guest.into()))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/guests/by-identifier/{identifier}",
+    params(
+        ("identifier" = String, Path, description = "NFC card UID or QR code payload")
+    ),
+    responses(
+        (status = StatusCode::OK, description = "Guest and wallet bound to the identifier", body = GuestLookupResponse),
+        (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+        (status = StatusCode::FORBIDDEN, description = "Forbidden", body = ErrorResponse),
+        (status = StatusCode::NOT_FOUND, description = "No guest with this identifier", body = ErrorResponse),
+    ),
+    security(
+        ("session_cookie" = [])
+    )
+)]
+pub async fn lookup_by_identifier(
+  State(state): State<AppState>,
+  authz: Authz,
+  Path(identifier): Path<String>,
+) -> AppResult<Json<GuestLookupResponse>> {
   authz.require(Permission::ReadGuestDetails)?;
 
-  let guests = state.guest_service.get_all().await?;
-  let response: Vec<GuestResponse> = guests.into_iter().map(Into::into).collect();
+  let found = state
+    .guest_service
+    .find_by_identifier(&identifier)
+    .await?
+    .ok_or(AppError::NotFound)?;
 
-  Ok(Json(response))
+  Ok(Json(found.into()))
 }
 
-pub fn router() -> Router<AppState> {
-  Router::new().route("/", get(list_guests))
+/// Take over a guest's wallet balance
+///
+/// Moves the guest's balance into the caller's wallet, or the caller takes
+/// over the guest's debt, and retires the guest. The caller proves to be the
+/// guest with the guest's identifier, or by being registered with the email
+/// the guest verified.
+#[utoipa::path(
+    post,
+    path = "/api/guests/{guest_id}/claim",
+    request_body = ClaimGuestRequest,
+    params(
+        ("guest_id" = Uuid, Path, description = "Guest id")
+    ),
+    responses(
+        (status = StatusCode::CREATED, description = "Guest claimed and retired", body = GuestClaimResponse),
+        (status = StatusCode::BAD_REQUEST, description = "Validation error", body = ErrorResponse),
+        (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+        (status = StatusCode::FORBIDDEN, description = "Neither identifier nor email match the guest", body = ErrorResponse),
+        (status = StatusCode::NOT_FOUND, description = "Guest or a wallet not found", body = ErrorResponse),
+        (status = StatusCode::CONFLICT, description = "Guest already claimed", body = ErrorResponse),
+        (status = StatusCode::UNPROCESSABLE_ENTITY, description = "Caller's wallet cannot cover the guest's debt", body = ErrorResponse),
+        (status = StatusCode::LOCKED, description = "A wallet is frozen or under legal hold", body = ErrorResponse),
+    ),
+    security(
+        ("session_cookie" = [])
+    )
+)]
+pub async fn claim_guest(
+  State(state): State<AppState>,
+  Authn(user): Authn,
+  audit: Audit,
+  Path(guest_id): Path<GuestId>,
+  ValidatedJson(payload): ValidatedJson<ClaimGuestRequest>,
+) -> AppResult<(StatusCode, Json<GuestClaimResponse>)> {
+  let claim = state
+    .guest_service
+    .claim(&user, guest_id, payload.identifier.as_deref())
+    .await?;
+
+  audit
+    .record(
+      Some(user.actor_id),
+      AuditAction::GuestClaimed,
+      claim.guest_id,
+      Some(json!({
+        "amount_cents": claim.amount.as_minor(),
+        "transaction_id": claim.transaction_id,
+      })),
+    )
+    .await;
+
+  Ok((StatusCode::CREATED, Json(claim.into())))
+}
+
+/// List staff notes on a guest
+#[utoipa::path(
+    get,
+    path = "/api/guests/{guest_id}/notes",
+    params(
+        ("guest_id" = Uuid, Path, description = "Guest id")
+    ),
+    responses(
+        (status = StatusCode::OK, description = "Notes, newest first", body = [AccountNoteResponse]),
+        (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+        (status = StatusCode::FORBIDDEN, description = "Forbidden", body = ErrorResponse),
+        (status = StatusCode::NOT_FOUND, description = "Guest not found", body = ErrorResponse),
+    ),
+    security(
+        ("session_cookie" = [])
+    )
+)]
+pub async fn list_guest_notes(
+  State(state): State<AppState>,
+  authz: Authz,
+  Path(guest_id): Path<GuestId>,
+) -> AppResult<Json<Vec<AccountNoteResponse>>> {
+  authz.require(Permission::ManageAccountNotes)?;
+
+  let notes = state
+    .account_note_service
+    .list(NoteSubject::Guest(guest_id))
+    .await?;
+
+  Ok(Json(notes.into_iter().map(Into::into).collect()))
+}
+
+/// Add a staff note on a guest
+///
+/// For support history only staff allowed to manage notes see.
+#[utoipa::path(
+    post,
+    path = "/api/guests/{guest_id}/notes",
+    request_body = AccountNoteRequest,
+    params(
+        ("guest_id" = Uuid, Path, description = "Guest id")
+    ),
+    responses(
+        (status = StatusCode::CREATED, description = "Note added", body = AccountNoteResponse),
+        (status = StatusCode::BAD_REQUEST, description = "Validation error", body = ErrorResponse),
+        (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+        (status = StatusCode::FORBIDDEN, description = "Forbidden", body = ErrorResponse),
+        (status = StatusCode::NOT_FOUND, description = "Guest not found", body = ErrorResponse),
+    ),
+    security(
+        ("session_cookie" = [])
+    )
+)]
+pub async fn add_guest_note(
+  State(state): State<AppState>,
+  authz: Authz,
+  audit: Audit,
+  Path(guest_id): Path<GuestId>,
+  ValidatedJson(payload): ValidatedJson<AccountNoteRequest>,
+) -> AppResult<(StatusCode, Json<AccountNoteResponse>)> {
+  authz.require(Permission::ManageAccountNotes)?;
+
+  let note = state
+    .account_note_service
+    .add(authz.0.actor_id, NoteSubject::Guest(guest_id), payload.body)
+    .await?;
+
+  audit
+    .record(
+      Some(authz.0.actor_id),
+      AuditAction::AccountNoteAdded,
+      guest_id,
+      Some(json!({ "note_id": note.id })),
+    )
+    .await;
+
+  Ok((StatusCode::CREATED, Json(note.into())))
+}
+
+#[derive(OpenApi)]
+#[openapi(
+  paths(
+    list_guests,
+    create_guest,
+    assign_identifier,
+    rotate_identifier,
+    lookup_by_identifier,
+    claim_guest,
+    list_guest_notes,
+    add_guest_note,
+  ),
+  components(schemas(
+    GuestResponse,
+    CreatedGuestResponse,
+    CreateGuestRequest,
+    AssignIdentifierRequest,
+    GuestLookupResponse,
+    ClaimGuestRequest,
+    GuestClaimResponse,
+    PaginatedGuestResponse,
+  ))
+)]
+struct Api;
+
+pub fn openapi() -> utoipa::openapi::OpenApi {
+  Api::openapi()
+}
+
+pub fn router() -> Routes {
+  Routes::new()
+    .get(
+      "/",
+      list_guests,
+      Guard::Any(&[Permission::ListGuests, Permission::ReadGuestDetails]),
+    )
+    .post("/", create_guest, Guard::All(&[Permission::CreateGuest]))
+    .get(
+      "/by-identifier/:identifier",
+      lookup_by_identifier,
+      Guard::All(&[Permission::ReadGuestDetails]),
+    )
+    .put(
+      "/:guest_id/identifier",
+      assign_identifier,
+      Guard::All(&[Permission::ManageGuestIdentifiers]),
+    )
+    .post(
+      "/:guest_id/identifier/rotate",
+      rotate_identifier,
+      Guard::All(&[Permission::ManageGuestIdentifiers]),
+    )
+    .post("/:guest_id/claim", claim_guest, Guard::Authenticated)
+    .get(
+      "/:guest_id/notes",
+      list_guest_notes,
+      Guard::All(&[Permission::ManageAccountNotes]),
+    )
+    .post(
+      "/:guest_id/notes",
+      add_guest_note,
+      Guard::All(&[Permission::ManageAccountNotes]),
+    )
 }
@@ -1,7 +1,17 @@
-use crate::models::HealthResponse;
+use crate::{
+  middleware::panic,
+  models::{BuildInfo, HealthResponse, ReadinessResponse},
+  route_permissions::Access,
+  routes::Routes,
+};
 use application::AppState;
-use axum::{response::IntoResponse, routing::get, Json, Router};
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+use utoipa::OpenApi;
 
+/// Liveness
+///
+/// Answers as long as the process serves requests, without touching any
+/// dependency. Also served at `/api/health/live`.
 #[utoipa::path(
   get,
   path = "/api/health",
@@ -12,9 +22,73 @@ use axum::{response::IntoResponse, routing::get, Json, Router};
 pub async fn health_check() -> impl IntoResponse {
   Json(HealthResponse {
     status: "ok".to_string(),
+    build: BuildInfo::current(),
+    panics: panic::panic_count(),
   })
 }
 
-pub fn router() -> Router<AppState> {
-  Router::new().route("/health", get(health_check))
+/// Liveness
+///
+/// Answers as long as the process serves requests, without touching any
+/// dependency. Restart the server if this fails.
+#[utoipa::path(
+  get,
+  path = "/api/health/live",
+  responses(
+    (status = 200, description = "Server is alive", body = HealthResponse)
+  )
+)]
+pub async fn liveness() -> impl IntoResponse {
+  health_check().await
+}
+
+/// Readiness
+///
+/// Pings the database and checks that it has every migration this build
+/// ships with; optionally also reports on the SMTP server. Route traffic
+/// elsewhere while this fails.
+#[utoipa::path(
+  get,
+  path = "/api/health/ready",
+  responses(
+    (status = 200, description = "Server can take traffic", body = ReadinessResponse),
+    (status = 503, description = "A required dependency is down", body = ReadinessResponse),
+  )
+)]
+pub async fn readiness(State(state): State<AppState>) -> impl IntoResponse {
+  let readiness = state
+    .health_service
+    .readiness(BuildInfo::current().migration_version)
+    .await;
+
+  let status = if readiness.is_ready() {
+    StatusCode::OK
+  } else {
+    StatusCode::SERVICE_UNAVAILABLE
+  };
+
+  (status, Json(ReadinessResponse::from(readiness)))
+}
+
+#[derive(OpenApi)]
+#[openapi(
+  paths(health_check, liveness, readiness,),
+  components(schemas(
+    HealthResponse,
+    BuildInfo,
+    ReadinessResponse,
+    crate::models::DependencyResponse,
+  ))
+)]
+struct Api;
+
+pub fn openapi() -> utoipa::openapi::OpenApi {
+  Api::openapi()
+}
+
+pub fn router() -> Routes {
+  Routes::new()
+    .get("/health", health_check, Access::Public)
+    .get("/health/live", liveness, Access::Public)
+    .get("/health/ready", readiness, Access::Public)
 }
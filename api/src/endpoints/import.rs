@@ -0,0 +1,92 @@
+use crate::{
+  error::AppResult,
+  extractor::{Audit, Authz},
+  models::{MemberImportQuery, MemberImportResponse},
+  route_permissions::Guard,
+  routes::Routes,
+};
+use application::state::AppState;
+use axum::{
+  extract::{Query, State},
+  Json,
+};
+use domain::{AuditAction, Permission, WalletLabel};
+use serde_json::json;
+use utoipa::OpenApi;
+
+/// Import members with their balances from a legacy system
+///
+/// Takes a CSV with the columns `email`, `first_name`, `last_name`, `role`,
+/// `identifier` and `balance_cents`. Rows without a role become guests, rows
+/// with one become users of that role, who are emailed a token to choose
+/// their password with. Every member gets a wallet with the
+/// balance booked as a transfer from the `legacy_import` system wallet. Bad
+/// rows are skipped and reported; the rest are imported.
+#[utoipa::path(
+  post,
+  path = "/api/imports/members",
+  params(MemberImportQuery),
+  request_body(content = String, content_type = "text/csv"),
+  responses(
+    (status = StatusCode::OK, description = "Imported and skipped rows", body = MemberImportResponse),
+    (status = StatusCode::BAD_REQUEST, description = "Empty file or missing columns", body = ErrorResponse),
+    (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+    (status = StatusCode::FORBIDDEN, description = "Forbidden", body = ErrorResponse),
+  ),
+  security(
+    ("session_cookie" = [])
+  )
+)]
+pub async fn import_members(
+  State(state): State<AppState>,
+  authz: Authz,
+  audit: Audit,
+  Query(query): Query<MemberImportQuery>,
+  csv: String,
+) -> AppResult<Json<MemberImportResponse>> {
+  authz.require(Permission::ImportMembers)?;
+
+  let report = state
+    .import_service
+    .import_members(authz.0.actor_id, authz.0.role, &csv, query.dry_run)
+    .await?;
+
+  if !report.dry_run {
+    audit
+      .record(
+        Some(authz.0.actor_id),
+        AuditAction::MembersImported,
+        WalletLabel::LegacyImport,
+        Some(json!({
+          "imported": report.imported.len(),
+          "failed": report.failed.len(),
+        })),
+      )
+      .await;
+  }
+
+  Ok(Json(report.into()))
+}
+
+#[derive(OpenApi)]
+#[openapi(
+  paths(import_members,),
+  components(schemas(
+    MemberImportResponse,
+    crate::models::ImportedMemberResponse,
+    crate::models::MemberImportErrorResponse,
+  ))
+)]
+struct Api;
+
+pub fn openapi() -> utoipa::openapi::OpenApi {
+  Api::openapi()
+}
+
+pub fn router() -> Routes {
+  Routes::new().post(
+    "/members",
+    import_members,
+    Guard::All(&[Permission::ImportMembers]),
+  )
+}
@@ -1,26 +1,39 @@
 use crate::{
+  conditional::{Conditional, ETag, IfNoneMatch},
   error::AppResult,
-  extractor::{Authz, ValidatedJson},
-  models::{AcceptInviteRequest, InviteRequest, InviteResponse},
+  extractor::{Audit, Authz, Tx, ValidatedJson},
+  models::{
+    AcceptInviteRequest, CreatedInviteResponse, InviteRequest, InviteResponse, ListInvitesQuery,
+    PaginatedInviteResponse,
+  },
+  route_permissions::{Access, Guard},
+  routes::Routes,
 };
-use application::state::AppState;
+use application::{error::AppError, services::invite::InviteFilter, state::AppState};
 use axum::{
-  extract::{Path, State},
-  http::Request,
-  routing::{get, post},
-  Json, Router,
+  extract::{Path, Query, State},
+  Json,
 };
-use domain::{Email, Permission, RawPassword};
+use domain::{types::PageRequest, AuditAction, Email, InviteId, Permission, RawPassword};
+use serde_json::json;
+use utoipa::OpenApi;
 
+/// Invite someone
+///
+/// Refused while users or guests are likely the same person, e.g. by a
+/// similar address or name, listing them so the inviter can check they aren't
+/// creating a second account for someone. Sent with `force`, the invite goes
+/// out anyway.
 #[utoipa::path(
   post,
   path = "/api/invites",
   request_body = InviteRequest,
   responses(
-    (status = StatusCode::OK, description = "Invite sent successfully"),
+    (status = StatusCode::OK, description = "Invite sent, with the possible duplicates it was forced past", body = CreatedInviteResponse),
     (status = StatusCode::BAD_REQUEST, description = "Validation error", body = ErrorResponse),
     (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
-    (status = StatusCode::FORBIDDEN, description = "Forbidden", body = ErrorResponse),
+    (status = StatusCode::FORBIDDEN, description = "Forbidden, or the address's domain may not be invited with the role", body = ErrorResponse),
+    (status = StatusCode::CONFLICT, description = "A pending invite exists for the address, or the person likely has an account; details carry the expiry or the candidates", body = ErrorResponse),
   ),
   security(
     ("session_cookie" = [])
@@ -29,27 +42,58 @@ use domain::{Email, Permission, RawPassword};
 pub async fn create_invite(
   State(state): State<AppState>,
   authz: Authz,
+  audit: Audit,
   ValidatedJson(payload): ValidatedJson<InviteRequest>,
-) -> AppResult<()> {
+) -> AppResult<Json<CreatedInviteResponse>> {
   authz.require(Permission::SendInvite)?;
   authz.can_assign(payload.role)?;
 
   let email = Email::new(payload.email);
   let user = authz.0;
 
-  state
+  let possible_duplicates = state.actor_service.find_duplicates(&email).await?;
+  if !payload.force && !possible_duplicates.is_empty() {
+    return Err(AppError::PossibleDuplicates(possible_duplicates).into());
+  }
+
+  let invite = state
     .invite_service
-    .create_invite(user.id, email, payload.role)
+    .create_invite(
+      user.id,
+      email,
+      payload.role,
+      payload.locale.unwrap_or(user.locale),
+    )
     .await?;
 
-  Ok(())
+  audit
+    .record(
+      Some(user.actor_id),
+      AuditAction::InviteSent,
+      invite.id,
+      Some(json!({ "role": invite.role })),
+    )
+    .await;
+
+  Ok(Json(CreatedInviteResponse {
+    invite: invite.into(),
+    possible_duplicates: possible_duplicates.into_iter().map(Into::into).collect(),
+  }))
 }
 
+/// List invites
+///
+/// Newest first.
 #[utoipa::path(
   get,
   path = "/api/invites",
+  params(
+    ListInvitesQuery,
+    ("If-None-Match" = Option<String>, Header, description = "ETag of the copy the client has")
+  ),
   responses(
-    (status = StatusCode::OK, description = "List of invites", body = [InviteResponse]),
+    (status = StatusCode::OK, description = "Page of invites", body = PaginatedInviteResponse),
+    (status = StatusCode::NOT_MODIFIED, description = "The client's copy is still current"),
     (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
     (status = StatusCode::FORBIDDEN, description = "Forbidden", body = ErrorResponse),
   ),
@@ -61,17 +105,33 @@ pub async fn create_invite(
 pub async fn get_invites(
   State(state): State<AppState>,
   authz: Authz,
-) -> AppResult<Json<Vec<InviteResponse>>> {
+  Query(query): Query<ListInvitesQuery>,
+  if_none_match: IfNoneMatch,
+) -> AppResult<Conditional<Json<PaginatedInviteResponse>>> {
   authz.require(Permission::ViewInvite)?;
 
-  // Get list of invites
-  let invites = state.invite_service.get_all().await?;
-  let response = invites
-    .into_iter()
-    .map(InviteResponse::from)
-    .collect::<Vec<InviteResponse>>();
+  let filter = InviteFilter {
+    status: query.status,
+    email_prefix: query.email_prefix.filter(|s| !s.trim().is_empty()),
+    invitor: query.invitor,
+    created_from: query.created_from,
+    created_to: query.created_to,
+  };
+  let page = PageRequest::new(query.page, query.per_page);
 
-  Ok(Json(response))
+  let version = state.invite_service.list_version(&filter).await?;
+  let etag = ETag::for_listing(version, &authz);
+  if if_none_match.matches(&etag) {
+    return Ok(Conditional::NotModified(etag));
+  }
+
+  let invites = state
+    .invite_service
+    .list(filter, page)
+    .await?
+    .map(InviteResponse::from);
+
+  Ok(Conditional::Modified(etag, Json(invites.into())))
 }
 
 #[utoipa::path(
@@ -89,25 +149,171 @@ pub async fn get_invites(
 )]
 pub async fn accept_invite(
   State(state): State<AppState>,
+  mut tx: Tx,
+  audit: Audit,
   Path(token): Path<String>,
   ValidatedJson(payload): ValidatedJson<AcceptInviteRequest>,
 ) -> AppResult<()> {
-  state
+  let user = state
     .invite_service
     .accept_invite(
+      &mut tx,
       &token,
       RawPassword::new(payload.password),
       payload.first_name,
       payload.last_name,
     )
     .await?;
+  tx.commit().await?;
+
+  audit
+    .record(
+      Some(user.actor_id),
+      AuditAction::InviteAccepted,
+      user.id,
+      Some(json!({ "role": user.role })),
+    )
+    .await;
 
   Ok(())
 }
 
-pub fn router() -> Router<AppState> {
-  Router::new()
-    .route("/", post(create_invite))
-    .route("/", get(get_invites))
-    .route("/:token/accept", post(accept_invite))
+#[utoipa::path(
+  delete,
+  path = "/api/invites/{invite_id}",
+  params(
+    ("invite_id" = Uuid, Path, description = "Invite id")
+  ),
+  responses(
+    (status = StatusCode::OK, description = "Invite revoked", body = InviteResponse),
+    (status = StatusCode::BAD_REQUEST, description = "Invite is no longer pending", body = ErrorResponse),
+    (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+    (status = StatusCode::FORBIDDEN, description = "Forbidden", body = ErrorResponse),
+    (status = StatusCode::NOT_FOUND, description = "Invite not found", body = ErrorResponse),
+  ),
+  security(
+    ("session_cookie" = [])
+  )
+)]
+pub async fn revoke_invite(
+  State(state): State<AppState>,
+  authz: Authz,
+  audit: Audit,
+  Path(invite_id): Path<InviteId>,
+) -> AppResult<Json<InviteResponse>> {
+  authz.require(Permission::RevokeInvite)?;
+
+  let invite = state
+    .invite_service
+    .get_by_id(invite_id)
+    .await?
+    .ok_or(AppError::NotFound)?;
+
+  // Only allow pulling back invites the caller could have sent themselves
+  authz.can_assign(invite.role)?;
+
+  let invite = state.invite_service.revoke_invite(invite_id).await?;
+
+  audit
+    .record(
+      Some(authz.0.actor_id),
+      AuditAction::InviteRevoked,
+      invite.id,
+      None,
+    )
+    .await;
+
+  Ok(Json(invite.into()))
+}
+
+#[utoipa::path(
+  post,
+  path = "/api/invites/{invite_id}/resend",
+  params(
+    ("invite_id" = Uuid, Path, description = "Invite id")
+  ),
+  responses(
+    (status = StatusCode::OK, description = "Invite re-sent with a new token", body = InviteResponse),
+    (status = StatusCode::BAD_REQUEST, description = "Invite is no longer pending", body = ErrorResponse),
+    (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+    (status = StatusCode::FORBIDDEN, description = "Forbidden", body = ErrorResponse),
+    (status = StatusCode::NOT_FOUND, description = "Invite not found", body = ErrorResponse),
+  ),
+  security(
+    ("session_cookie" = [])
+  )
+)]
+pub async fn resend_invite(
+  State(state): State<AppState>,
+  authz: Authz,
+  audit: Audit,
+  Path(invite_id): Path<InviteId>,
+) -> AppResult<Json<InviteResponse>> {
+  authz.require(Permission::SendInvite)?;
+
+  let invite = state
+    .invite_service
+    .get_by_id(invite_id)
+    .await?
+    .ok_or(AppError::NotFound)?;
+
+  authz.can_assign(invite.role)?;
+
+  let invite = state
+    .invite_service
+    .resend_invite(authz.0.id, invite_id)
+    .await?;
+
+  audit
+    .record(
+      Some(authz.0.actor_id),
+      AuditAction::InviteResent,
+      invite.id,
+      None,
+    )
+    .await;
+
+  Ok(Json(invite.into()))
+}
+
+#[derive(OpenApi)]
+#[openapi(
+  paths(
+    create_invite,
+    accept_invite,
+    get_invites,
+    revoke_invite,
+    resend_invite,
+  ),
+  components(schemas(
+    InviteRequest,
+    InviteResponse,
+    CreatedInviteResponse,
+    AcceptInviteRequest,
+    PaginatedInviteResponse,
+    domain::InviteStatus,
+  ))
+)]
+struct Api;
+
+pub fn openapi() -> utoipa::openapi::OpenApi {
+  Api::openapi()
+}
+
+pub fn router() -> Routes {
+  Routes::new()
+    .post("/", create_invite, Guard::All(&[Permission::SendInvite]))
+    .get("/", get_invites, Guard::All(&[Permission::ViewInvite]))
+    .delete(
+      "/:invite_id",
+      revoke_invite,
+      Guard::All(&[Permission::RevokeInvite]),
+    )
+    .post(
+      "/:invite_id/resend",
+      resend_invite,
+      Guard::All(&[Permission::SendInvite]),
+    )
+    // The invite token is the credential
+    .post("/:token/accept", accept_invite, Access::Public)
 }
@@ -1,5 +1,28 @@
+pub mod admin;
+pub mod audit;
 pub mod auth;
+pub mod balance_alert;
+pub mod chargeback;
+pub mod client_app;
+pub mod debt;
+pub mod event_log;
+pub mod favorite;
 pub mod guest;
 pub mod health;
+pub mod import;
 pub mod invites;
+pub mod order;
+pub mod owner_transfer;
+pub mod payout;
+pub mod psp;
+pub mod report;
+pub mod retention;
+pub mod review;
+pub mod settings;
+pub mod shop;
+pub mod stream;
+pub mod transaction;
+pub mod transfer;
 pub mod user;
+pub mod wallet;
+pub mod webhook;
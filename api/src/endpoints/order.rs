@@ -0,0 +1,715 @@
+use std::convert::Infallible;
+
+use crate::{
+  error::AppResult,
+  extractor::{Audit, Authz, ValidatedJson},
+  models::{
+    CheckoutRequest, ListOrdersQuery, OrderResponse, OrderStatusEvent, PaginatedOrderResponse,
+    PreorderRequest, PurchaserResponse, ReorderResponse, UpdateOrderStatusRequest,
+  },
+  route_permissions::Guard,
+  routes::Routes,
+};
+use application::{
+  error::AppError,
+  services::order::{OrderFilter, Payer},
+  state::AppState,
+};
+use axum::{
+  extract::{Path, Query, State},
+  http::{HeaderMap, StatusCode},
+  response::sse::{Event, KeepAlive, Sse},
+  Json,
+};
+use domain::{
+  types::PageRequest, AuditAction, MetadataSource, Order, OrderEvent, OrderId, OrderStatusChange,
+  Permission, ShopId, TransactionMetadata,
+};
+use futures_util::{stream, Stream, StreamExt};
+use serde_json::json;
+use tokio::sync::broadcast::{self, error::RecvError};
+use utoipa::OpenApi;
+
+/// Header in which guests without a session pass the identifier of their card
+/// or QR code. Kept out of the URL so it doesn't end up in access logs.
+pub const GUEST_IDENTIFIER_HEADER: &str = "x-guest-identifier";
+
+/// Anyone who may read the ledger sees every order; shop staff see the orders
+/// of their own shop.
+async fn require_order_access(state: &AppState, authz: &Authz, shop_id: ShopId) -> AppResult<()> {
+  if authz.has(Permission::ReadTransactions) {
+    return Ok(());
+  }
+
+  let shop = state
+    .shop_service
+    .get_by_id(shop_id)
+    .await?
+    .ok_or(AppError::NotFound)?;
+
+  if state.shop_service.is_staff(&shop, authz.0.id).await? {
+    Ok(())
+  } else {
+    Err(AppError::Authorization.into())
+  }
+}
+
+#[utoipa::path(
+  post,
+  path = "/api/shops/{shop_id}/orders",
+  request_body = CheckoutRequest,
+  params(
+    ("shop_id" = Uuid, Path, description = "Shop id")
+  ),
+  responses(
+    (status = StatusCode::CREATED, description = "Order placed and paid", body = OrderResponse),
+    (status = StatusCode::BAD_REQUEST, description = "Validation error or offering not sold by this shop", body = ErrorResponse),
+    (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+    (status = StatusCode::FORBIDDEN, description = "Not on the shop's staff", body = ErrorResponse),
+    (status = StatusCode::NOT_FOUND, description = "Shop or payer not found", body = ErrorResponse),
+    (status = StatusCode::UNPROCESSABLE_ENTITY, description = "Insufficient funds", body = ErrorResponse),
+    (status = StatusCode::LOCKED, description = "Payer wallet is frozen or under legal hold", body = ErrorResponse),
+    (status = StatusCode::SERVICE_UNAVAILABLE, description = "Charging guests is switched off", body = ErrorResponse),
+  ),
+  security(
+    ("session_cookie" = [])
+  )
+)]
+pub async fn checkout(
+  State(state): State<AppState>,
+  authz: Authz,
+  audit: Audit,
+  Path(shop_id): Path<ShopId>,
+  ValidatedJson(payload): ValidatedJson<CheckoutRequest>,
+) -> AppResult<(StatusCode, Json<OrderResponse>)> {
+  let shop = state
+    .shop_service
+    .get_by_id(shop_id)
+    .await?
+    .ok_or(AppError::NotFound)?;
+
+  if !state.shop_service.is_staff(&shop, authz.0.id).await? {
+    return Err(AppError::Authorization.into());
+  }
+
+  let pickup = payload.pickup();
+  let metadata = TransactionMetadata::new(MetadataSource::Pos, payload.metadata)
+    .map_err(AppError::Validation)?;
+  let order = state
+    .order_service
+    .checkout(
+      authz.0.actor_id,
+      shop.id,
+      payload.payer.into(),
+      payload.items.into_iter().map(Into::into).collect(),
+      pickup,
+      metadata,
+    )
+    .await?;
+
+  audit
+    .record(
+      Some(authz.0.actor_id),
+      AuditAction::OrderPlaced,
+      order.0.id,
+      Some(json!({
+        "shop_id": shop.id,
+        "total_cents": order.0.total.as_minor(),
+      })),
+    )
+    .await;
+
+  Ok((StatusCode::CREATED, Json(order.into())))
+}
+
+/// Book an order into a pickup slot
+///
+/// Signed-in users pre-order for themselves; the shop's staff may pre-order
+/// for any payer.
+#[utoipa::path(
+  post,
+  path = "/api/shops/{shop_id}/preorders",
+  request_body = PreorderRequest,
+  params(
+    ("shop_id" = Uuid, Path, description = "Shop id")
+  ),
+  responses(
+    (status = StatusCode::CREATED, description = "Pre-order booked, paid unless paid at pickup", body = OrderResponse),
+    (status = StatusCode::BAD_REQUEST, description = "Validation error, pickup time not a future slot, or shop takes no pre-orders", body = ErrorResponse),
+    (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+    (status = StatusCode::FORBIDDEN, description = "Paying for someone else without being on the shop's staff", body = ErrorResponse),
+    (status = StatusCode::NOT_FOUND, description = "Shop or payer not found", body = ErrorResponse),
+    (status = StatusCode::CONFLICT, description = "Pickup slot fully booked", body = ErrorResponse),
+    (status = StatusCode::UNPROCESSABLE_ENTITY, description = "Insufficient funds", body = ErrorResponse),
+    (status = StatusCode::LOCKED, description = "Payer wallet is frozen or under legal hold", body = ErrorResponse),
+    (status = StatusCode::SERVICE_UNAVAILABLE, description = "Charging guests is switched off", body = ErrorResponse),
+  ),
+  security(
+    ("session_cookie" = [])
+  )
+)]
+pub async fn preorder(
+  State(state): State<AppState>,
+  authz: Authz,
+  audit: Audit,
+  Path(shop_id): Path<ShopId>,
+  ValidatedJson(payload): ValidatedJson<PreorderRequest>,
+) -> AppResult<(StatusCode, Json<OrderResponse>)> {
+  let shop = state
+    .shop_service
+    .get_by_id(shop_id)
+    .await?
+    .ok_or(AppError::NotFound)?;
+
+  let payer = match payload.payer.map(Payer::from) {
+    Some(Payer::User(user_id)) if user_id == authz.0.id => Payer::User(user_id),
+    Some(payer) => {
+      if !state.shop_service.is_staff(&shop, authz.0.id).await? {
+        return Err(AppError::Authorization.into());
+      }
+      payer
+    }
+    None => Payer::User(authz.0.id),
+  };
+
+  let order = state
+    .order_service
+    .preorder(
+      authz.0.actor_id,
+      shop.id,
+      payer,
+      payload.items.into_iter().map(Into::into).collect(),
+      payload.pickup_at,
+      !payload.pay_at_pickup,
+    )
+    .await?;
+
+  audit
+    .record(
+      Some(authz.0.actor_id),
+      AuditAction::OrderPlaced,
+      order.0.id,
+      Some(json!({
+        "shop_id": shop.id,
+        "total_cents": order.0.total.as_minor(),
+        "pickup_at": order.0.pickup_at,
+        "pay_at_pickup": payload.pay_at_pickup,
+      })),
+    )
+    .await;
+
+  Ok((StatusCode::CREATED, Json(order.into())))
+}
+
+/// Charge a pre-order paid at pickup
+#[utoipa::path(
+  post,
+  path = "/api/orders/{order_id}/payment",
+  params(
+    ("order_id" = Uuid, Path, description = "Order id")
+  ),
+  responses(
+    (status = StatusCode::OK, description = "Order paid", body = OrderResponse),
+    (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+    (status = StatusCode::FORBIDDEN, description = "Not on the shop's staff", body = ErrorResponse),
+    (status = StatusCode::NOT_FOUND, description = "Order not found", body = ErrorResponse),
+    (status = StatusCode::CONFLICT, description = "Order already paid", body = ErrorResponse),
+    (status = StatusCode::UNPROCESSABLE_ENTITY, description = "Insufficient funds", body = ErrorResponse),
+    (status = StatusCode::LOCKED, description = "Payer wallet is frozen or under legal hold", body = ErrorResponse),
+    (status = StatusCode::SERVICE_UNAVAILABLE, description = "Charging guests is switched off", body = ErrorResponse),
+  ),
+  security(
+    ("session_cookie" = [])
+  )
+)]
+pub async fn pay_order(
+  State(state): State<AppState>,
+  authz: Authz,
+  audit: Audit,
+  Path(order_id): Path<OrderId>,
+) -> AppResult<Json<OrderResponse>> {
+  let (order, _) = state
+    .order_service
+    .get_by_id(order_id)
+    .await?
+    .ok_or(AppError::NotFound)?;
+
+  let shop = state
+    .shop_service
+    .get_by_id(order.shop_id)
+    .await?
+    .ok_or(AppError::NotFound)?;
+
+  if !state.shop_service.is_staff(&shop, authz.0.id).await? {
+    return Err(AppError::Authorization.into());
+  }
+
+  let order = state.order_service.pay(authz.0.actor_id, order.id).await?;
+
+  audit
+    .record(
+      Some(authz.0.actor_id),
+      AuditAction::OrderPaid,
+      order.0.id,
+      Some(json!({
+        "transaction_id": order.0.transaction_id,
+        "total_cents": order.0.total.as_minor(),
+      })),
+    )
+    .await;
+
+  Ok(Json(order.into()))
+}
+
+#[utoipa::path(
+  get,
+  path = "/api/orders",
+  params(ListOrdersQuery),
+  responses(
+    (status = StatusCode::OK, description = "Page of orders with their line items, newest first", body = PaginatedOrderResponse),
+    (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+    (status = StatusCode::FORBIDDEN, description = "Without ReadTransactions, shop_id must name a shop the caller works at", body = ErrorResponse),
+    (status = StatusCode::NOT_FOUND, description = "Shop not found", body = ErrorResponse),
+  ),
+  security(
+    ("session_cookie" = [])
+  )
+)]
+pub async fn list_orders(
+  State(state): State<AppState>,
+  authz: Authz,
+  Query(query): Query<ListOrdersQuery>,
+) -> AppResult<Json<PaginatedOrderResponse>> {
+  match query.shop_id {
+    Some(shop_id) => require_order_access(&state, &authz, shop_id).await?,
+    None => authz.require(Permission::ReadTransactions)?,
+  }
+
+  let filter = OrderFilter {
+    shop_id: query.shop_id,
+    payer_wallet_id: query.payer_wallet_id,
+    from: query.from,
+    to: query.to,
+  };
+  let page = PageRequest::new(query.page, query.per_page);
+
+  let orders = state.order_service.list(filter, page).await?;
+
+  Ok(Json(orders.into()))
+}
+
+#[utoipa::path(
+  get,
+  path = "/api/orders/{order_id}",
+  params(
+    ("order_id" = Uuid, Path, description = "Order id")
+  ),
+  responses(
+    (status = StatusCode::OK, description = "Order with line items as captured at checkout", body = OrderResponse),
+    (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+    (status = StatusCode::FORBIDDEN, description = "Forbidden", body = ErrorResponse),
+    (status = StatusCode::NOT_FOUND, description = "Order not found", body = ErrorResponse),
+  ),
+  security(
+    ("session_cookie" = [])
+  )
+)]
+pub async fn get_order(
+  State(state): State<AppState>,
+  authz: Authz,
+  Path(order_id): Path<OrderId>,
+) -> AppResult<Json<OrderResponse>> {
+  let order = state
+    .order_service
+    .get_by_id(order_id)
+    .await?
+    .ok_or(AppError::NotFound)?;
+
+  require_order_access(&state, &authz, order.0.shop_id).await?;
+
+  Ok(Json(order.into()))
+}
+
+/// Get who placed an order
+///
+/// Only the first name and last initial, so shop staff can call out orders
+/// without being allowed to read user details.
+#[utoipa::path(
+  get,
+  path = "/api/orders/{order_id}/purchaser",
+  params(
+    ("order_id" = Uuid, Path, description = "Order id")
+  ),
+  responses(
+    (status = StatusCode::OK, description = "Purchaser of the order", body = PurchaserResponse),
+    (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+    (status = StatusCode::FORBIDDEN, description = "Neither on the shop's staff nor allowed to read user details", body = ErrorResponse),
+    (status = StatusCode::NOT_FOUND, description = "Order not found", body = ErrorResponse),
+  ),
+  security(
+    ("session_cookie" = [])
+  )
+)]
+pub async fn get_purchaser(
+  State(state): State<AppState>,
+  authz: Authz,
+  Path(order_id): Path<OrderId>,
+) -> AppResult<Json<PurchaserResponse>> {
+  let (order, _) = state
+    .order_service
+    .get_by_id(order_id)
+    .await?
+    .ok_or(AppError::NotFound)?;
+
+  if !authz.has(Permission::ReadUserDetails) {
+    let shop = state
+      .shop_service
+      .get_by_id(order.shop_id)
+      .await?
+      .ok_or(AppError::NotFound)?;
+
+    if !state.shop_service.is_staff(&shop, authz.0.id).await? {
+      return Err(AppError::Authorization.into());
+    }
+  }
+
+  let purchaser = state
+    .order_service
+    .get_purchaser(order.id)
+    .await?
+    .ok_or(AppError::NotFound)?;
+
+  Ok(Json(purchaser.into()))
+}
+
+/// Rebuild the cart of a past order
+///
+/// Prices are today's; items the shop no longer sells are flagged. Nothing is
+/// charged, the cart is checked out as a new order.
+#[utoipa::path(
+  post,
+  path = "/api/orders/{order_id}/reorder",
+  params(
+    ("order_id" = Uuid, Path, description = "Order id")
+  ),
+  responses(
+    (status = StatusCode::OK, description = "Cart with current prices", body = ReorderResponse),
+    (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+    (status = StatusCode::FORBIDDEN, description = "Caller did not pay for the order and does not work at the shop", body = ErrorResponse),
+    (status = StatusCode::NOT_FOUND, description = "Order not found", body = ErrorResponse),
+  ),
+  security(
+    ("session_cookie" = [])
+  )
+)]
+pub async fn reorder(
+  State(state): State<AppState>,
+  authz: Authz,
+  Path(order_id): Path<OrderId>,
+) -> AppResult<Json<ReorderResponse>> {
+  let (order, _) = state
+    .order_service
+    .get_by_id(order_id)
+    .await?
+    .ok_or(AppError::NotFound)?;
+
+  if !state
+    .order_service
+    .is_paid_by(&order, &Payer::User(authz.0.id))
+    .await?
+  {
+    require_order_access(&state, &authz, order.shop_id).await?;
+  }
+
+  let lines = state.order_service.reorder(&order).await?;
+
+  Ok(Json((order, lines).into()))
+}
+
+/// What the bar still has to prepare or hand out, for the screen behind the
+/// counter.
+#[utoipa::path(
+  get,
+  path = "/api/shops/{shop_id}/queue",
+  params(
+    ("shop_id" = Uuid, Path, description = "Shop id")
+  ),
+  responses(
+    (status = StatusCode::OK, description = "Orders not collected yet, oldest first", body = [OrderResponse]),
+    (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+    (status = StatusCode::FORBIDDEN, description = "Not on the shop's staff", body = ErrorResponse),
+    (status = StatusCode::NOT_FOUND, description = "Shop not found", body = ErrorResponse),
+  ),
+  security(
+    ("session_cookie" = [])
+  )
+)]
+pub async fn order_queue(
+  State(state): State<AppState>,
+  authz: Authz,
+  Path(shop_id): Path<ShopId>,
+) -> AppResult<Json<Vec<OrderResponse>>> {
+  let shop = state
+    .shop_service
+    .get_by_id(shop_id)
+    .await?
+    .ok_or(AppError::NotFound)?;
+
+  if !state.shop_service.is_staff(&shop, authz.0.id).await? {
+    return Err(AppError::Authorization.into());
+  }
+
+  let orders = state.order_service.queue(shop.id).await?;
+
+  Ok(Json(orders.into_iter().map(Into::into).collect()))
+}
+
+/// Only the staff of the shop that sold the order may move it along.
+#[utoipa::path(
+  post,
+  path = "/api/orders/{order_id}/status",
+  request_body = UpdateOrderStatusRequest,
+  params(
+    ("order_id" = Uuid, Path, description = "Order id")
+  ),
+  responses(
+    (status = StatusCode::OK, description = "Order with its new status", body = OrderResponse),
+    (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+    (status = StatusCode::FORBIDDEN, description = "Not on the shop's staff", body = ErrorResponse),
+    (status = StatusCode::NOT_FOUND, description = "Order not found", body = ErrorResponse),
+    (status = StatusCode::CONFLICT, description = "Orders only move forward from placed over preparing and ready to collected, and are paid before being collected", body = ErrorResponse),
+  ),
+  security(
+    ("session_cookie" = [])
+  )
+)]
+pub async fn update_order_status(
+  State(state): State<AppState>,
+  authz: Authz,
+  audit: Audit,
+  Path(order_id): Path<OrderId>,
+  ValidatedJson(payload): ValidatedJson<UpdateOrderStatusRequest>,
+) -> AppResult<Json<OrderResponse>> {
+  let (order, _) = state
+    .order_service
+    .get_by_id(order_id)
+    .await?
+    .ok_or(AppError::NotFound)?;
+
+  let shop = state
+    .shop_service
+    .get_by_id(order.shop_id)
+    .await?
+    .ok_or(AppError::NotFound)?;
+
+  if !state.shop_service.is_staff(&shop, authz.0.id).await? {
+    return Err(AppError::Authorization.into());
+  }
+
+  let from = order.status;
+  let order = state
+    .order_service
+    .advance_status(order.id, payload.status)
+    .await?;
+
+  audit
+    .record(
+      Some(authz.0.actor_id),
+      AuditAction::OrderStatusChanged,
+      order.0.id,
+      Some(json!({
+        "from": from,
+        "to": order.0.status,
+      })),
+    )
+    .await;
+
+  Ok(Json(order.into()))
+}
+
+/// Live status of an order as server-sent events, for the purchaser's
+/// "your order is ready" screen.
+///
+/// Guests without a session prove they paid with the identifier of their card
+/// or QR code in the `x-guest-identifier` header. The first event carries the current status; the stream ends
+/// once the order is collected.
+#[utoipa::path(
+  get,
+  path = "/api/orders/{order_id}/events",
+  params(
+    ("order_id" = Uuid, Path, description = "Order id"),
+    ("x-guest-identifier" = Option<String>, Header, description = "Card or QR identifier of the guest who paid, for purchasers without a session"),
+  ),
+  responses(
+    (status = StatusCode::OK, description = "Stream of `status` events", body = OrderStatusEvent, content_type = "text/event-stream"),
+    (status = StatusCode::UNAUTHORIZED, description = "Neither a session nor an identifier", body = ErrorResponse),
+    (status = StatusCode::FORBIDDEN, description = "Caller did not pay for the order and does not work at the shop", body = ErrorResponse),
+    (status = StatusCode::NOT_FOUND, description = "Order not found", body = ErrorResponse),
+  ),
+  security(
+    (),
+    ("session_cookie" = [])
+  )
+)]
+pub async fn order_events(
+  State(state): State<AppState>,
+  authz: Option<Authz>,
+  Path(order_id): Path<OrderId>,
+  headers: HeaderMap,
+) -> AppResult<Sse<impl Stream<Item = Result<Event, Infallible>>>> {
+  let (order, _) = state
+    .order_service
+    .get_by_id(order_id)
+    .await?
+    .ok_or(AppError::NotFound)?;
+
+  let identifier = headers
+    .get(GUEST_IDENTIFIER_HEADER)
+    .and_then(|v| v.to_str().ok())
+    .map(ToString::to_string);
+
+  let payer = match (&authz, identifier) {
+    (_, Some(identifier)) => Payer::Guest(identifier),
+    (Some(authz), None) => Payer::User(authz.0.id),
+    (None, None) => return Err(AppError::Authentication.into()),
+  };
+
+  if !state.order_service.is_paid_by(&order, &payer).await? {
+    match &authz {
+      Some(authz) => require_order_access(&state, authz, order.shop_id).await?,
+      None => return Err(AppError::Authorization.into()),
+    }
+  }
+
+  // Subscribe before reading the status so no change slips in between
+  let changes = state.event_bus.subscribe();
+  let current = state
+    .order_service
+    .get_by_id(order_id)
+    .await?
+    .ok_or(AppError::NotFound)?
+    .0;
+
+  Ok(Sse::new(status_events(current, changes)).keep_alive(KeepAlive::default()))
+}
+
+fn status_event(change: OrderStatusChange) -> Result<Event, Infallible> {
+  Ok(
+    Event::default()
+      .event("status")
+      .json_data(OrderStatusEvent::from(change))
+      .expect("status event serializes"),
+  )
+}
+
+/// The current status, then every change to the order until it is final.
+fn status_events(
+  order: Order,
+  changes: broadcast::Receiver<OrderEvent>,
+) -> impl Stream<Item = Result<Event, Infallible>> {
+  let first = OrderStatusChange {
+    order_id: order.id,
+    shop_id: order.shop_id,
+    status: order.status,
+    changed_at: order.updated_at.unwrap_or(order.created_at),
+  };
+  let finished = first.status.is_final();
+
+  let rest = stream::unfold(
+    (changes, finished),
+    move |(mut changes, finished)| async move {
+      if finished {
+        return None;
+      }
+
+      loop {
+        match changes.recv().await {
+          Ok(OrderEvent::StatusChanged(change)) if change.order_id == order.id => {
+            let finished = change.status.is_final();
+            return Some((change, (changes, finished)));
+          }
+          Ok(_) | Err(RecvError::Lagged(_)) => continue,
+          Err(RecvError::Closed) => return None,
+        }
+      }
+    },
+  );
+
+  stream::once(async move { first })
+    .chain(rest)
+    .map(status_event)
+}
+
+#[derive(OpenApi)]
+#[openapi(
+  paths(
+    checkout,
+    preorder,
+    pay_order,
+    list_orders,
+    order_queue,
+    get_order,
+    get_purchaser,
+    reorder,
+    update_order_status,
+    order_events,
+  ),
+  components(schemas(
+    crate::models::PayerRequest,
+    crate::models::CheckoutItemRequest,
+    CheckoutRequest,
+    crate::models::OrderItemResponse,
+    OrderResponse,
+    PaginatedOrderResponse,
+    UpdateOrderStatusRequest,
+    PreorderRequest,
+    ReorderResponse,
+    crate::models::ReorderItemResponse,
+    OrderStatusEvent,
+    PurchaserResponse,
+    domain::OrderStatus,
+  ))
+)]
+struct Api;
+
+pub fn openapi() -> utoipa::openapi::OpenApi {
+  Api::openapi()
+}
+
+pub fn router() -> Routes {
+  Routes::new()
+    .post("/shops/:shop_id/orders", checkout, Guard::ShopStaff)
+    .get("/shops/:shop_id/queue", order_queue, Guard::ShopStaff)
+    .post("/shops/:shop_id/preorders", preorder, Guard::Authenticated)
+    .get(
+      "/orders",
+      list_orders,
+      Guard::ShopStaffOr(&[Permission::ReadTransactions]),
+    )
+    .get(
+      "/orders/:order_id",
+      get_order,
+      Guard::ShopStaffOr(&[Permission::ReadTransactions]),
+    )
+    .get(
+      "/orders/:order_id/purchaser",
+      get_purchaser,
+      Guard::ShopStaffOr(&[Permission::ReadUserDetails]),
+    )
+    .post(
+      "/orders/:order_id/status",
+      update_order_status,
+      Guard::ShopStaff,
+    )
+    .post("/orders/:order_id/payment", pay_order, Guard::ShopStaff)
+    .post(
+      "/orders/:order_id/reorder",
+      reorder,
+      Guard::PayerOrShopStaffOr(&[Permission::ReadTransactions]),
+    )
+    .get(
+      "/orders/:order_id/events",
+      order_events,
+      Guard::PayerOrShopStaffOr(&[Permission::ReadTransactions]),
+    )
+}
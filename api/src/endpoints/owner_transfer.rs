@@ -0,0 +1,261 @@
+use crate::{
+  error::AppResult,
+  extractor::{Audit, Authn, StepUp, ValidatedJson},
+  models::{OwnerTransferRequest, OwnerTransferResponse},
+  route_permissions::Guard,
+  routes::Routes,
+};
+use application::{error::AppError, state::AppState};
+use axum::{
+  extract::{Path, State},
+  http::StatusCode,
+  Json,
+};
+use domain::{AuditAction, OwnerTransferId};
+use serde_json::json;
+use utoipa::OpenApi;
+
+/// Offer the Owner role to another user
+///
+/// Only an owner can offer it, and only one transfer can be pending. Nothing
+/// changes until the other user accepts within three days; then they become
+/// an owner and the caller steps down to `demote_to`.
+#[utoipa::path(
+    post,
+    path = "/api/owner-transfers",
+    request_body = OwnerTransferRequest,
+    responses(
+        (status = StatusCode::CREATED, description = "Transfer offered", body = OwnerTransferResponse),
+        (status = StatusCode::BAD_REQUEST, description = "Validation error, own account, already an owner, or a role owners can't step down to", body = ErrorResponse),
+        (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+        (status = StatusCode::FORBIDDEN, description = "Not an owner, or password confirmation or device signature required", body = ErrorResponse),
+        (status = StatusCode::NOT_FOUND, description = "User not found", body = ErrorResponse),
+        (status = StatusCode::CONFLICT, description = "Another transfer is pending", body = ErrorResponse),
+    ),
+    security(
+        ("session_cookie" = [], "confirmation_token" = [])
+    )
+)]
+pub async fn offer_owner_transfer(
+  State(state): State<AppState>,
+  Authn(user): Authn,
+  _: StepUp,
+  audit: Audit,
+  ValidatedJson(payload): ValidatedJson<OwnerTransferRequest>,
+) -> AppResult<(StatusCode, Json<OwnerTransferResponse>)> {
+  let transfer = state
+    .owner_transfer_service
+    .offer(&user, payload.to_user_id, payload.demote_to)
+    .await?;
+
+  audit
+    .record(
+      Some(user.actor_id),
+      AuditAction::OwnerTransferOffered,
+      transfer.id,
+      Some(json!({
+        "to_user_id": transfer.to_user_id,
+        "demote_to": transfer.demote_to,
+        "expires_at": transfer.expires_at,
+      })),
+    )
+    .await;
+
+  Ok((StatusCode::CREATED, Json(transfer.into())))
+}
+
+/// Get the pending owner transfer
+///
+/// Only the owner who offered it and the user it is offered to see it.
+#[utoipa::path(
+    get,
+    path = "/api/owner-transfers/current",
+    responses(
+        (status = StatusCode::OK, description = "Pending transfer", body = OwnerTransferResponse),
+        (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+        (status = StatusCode::NOT_FOUND, description = "No transfer involving the caller is pending", body = ErrorResponse),
+    ),
+    security(
+        ("session_cookie" = [])
+    )
+)]
+pub async fn get_current_owner_transfer(
+  State(state): State<AppState>,
+  Authn(user): Authn,
+) -> AppResult<Json<OwnerTransferResponse>> {
+  let transfer = state
+    .owner_transfer_service
+    .pending_for(user.id)
+    .await?
+    .ok_or(AppError::NotFound)?;
+
+  Ok(Json(transfer.into()))
+}
+
+/// Accept the Owner role
+///
+/// The caller becomes an owner and the owner who offered it steps down, in
+/// one go. Both are signed out everywhere and sign in again with their new
+/// roles.
+#[utoipa::path(
+    post,
+    path = "/api/owner-transfers/{transfer_id}/accept",
+    params(
+        ("transfer_id" = Uuid, Path, description = "Owner transfer id")
+    ),
+    responses(
+        (status = StatusCode::OK, description = "Transfer accepted", body = OwnerTransferResponse),
+        (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+        (status = StatusCode::FORBIDDEN, description = "Password confirmation or device signature required", body = ErrorResponse),
+        (status = StatusCode::NOT_FOUND, description = "No such transfer offered to the caller", body = ErrorResponse),
+        (status = StatusCode::CONFLICT, description = "Transfer is no longer pending, ran out, or its owner no longer is one", body = ErrorResponse),
+    ),
+    security(
+        ("session_cookie" = [], "confirmation_token" = [])
+    )
+)]
+pub async fn accept_owner_transfer(
+  State(state): State<AppState>,
+  Authn(user): Authn,
+  _: StepUp,
+  audit: Audit,
+  Path(transfer_id): Path<OwnerTransferId>,
+) -> AppResult<Json<OwnerTransferResponse>> {
+  let transfer = state
+    .owner_transfer_service
+    .accept(user.id, transfer_id)
+    .await?;
+
+  audit
+    .record(
+      Some(user.actor_id),
+      AuditAction::OwnerTransferAccepted,
+      transfer.id,
+      Some(json!({
+        "from_user_id": transfer.from_user_id,
+        "demoted_to": transfer.demote_to,
+        "previous_role": user.role,
+      })),
+    )
+    .await;
+
+  Ok(Json(transfer.into()))
+}
+
+/// Decline the Owner role
+#[utoipa::path(
+    post,
+    path = "/api/owner-transfers/{transfer_id}/decline",
+    params(
+        ("transfer_id" = Uuid, Path, description = "Owner transfer id")
+    ),
+    responses(
+        (status = StatusCode::OK, description = "Transfer declined", body = OwnerTransferResponse),
+        (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+        (status = StatusCode::NOT_FOUND, description = "No such transfer offered to the caller", body = ErrorResponse),
+        (status = StatusCode::CONFLICT, description = "Transfer is no longer pending", body = ErrorResponse),
+    ),
+    security(
+        ("session_cookie" = [])
+    )
+)]
+pub async fn decline_owner_transfer(
+  State(state): State<AppState>,
+  Authn(user): Authn,
+  audit: Audit,
+  Path(transfer_id): Path<OwnerTransferId>,
+) -> AppResult<Json<OwnerTransferResponse>> {
+  let transfer = state
+    .owner_transfer_service
+    .decline(user.id, transfer_id)
+    .await?;
+
+  audit
+    .record(
+      Some(user.actor_id),
+      AuditAction::OwnerTransferDeclined,
+      transfer.id,
+      Some(json!({ "from_user_id": transfer.from_user_id })),
+    )
+    .await;
+
+  Ok(Json(transfer.into()))
+}
+
+/// Withdraw an owner transfer
+#[utoipa::path(
+    delete,
+    path = "/api/owner-transfers/{transfer_id}",
+    params(
+        ("transfer_id" = Uuid, Path, description = "Owner transfer id")
+    ),
+    responses(
+        (status = StatusCode::NO_CONTENT, description = "Transfer withdrawn"),
+        (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+        (status = StatusCode::NOT_FOUND, description = "No such transfer offered by the caller", body = ErrorResponse),
+        (status = StatusCode::CONFLICT, description = "Transfer is no longer pending", body = ErrorResponse),
+    ),
+    security(
+        ("session_cookie" = [])
+    )
+)]
+pub async fn cancel_owner_transfer(
+  State(state): State<AppState>,
+  Authn(user): Authn,
+  audit: Audit,
+  Path(transfer_id): Path<OwnerTransferId>,
+) -> AppResult<StatusCode> {
+  let transfer = state
+    .owner_transfer_service
+    .cancel(user.id, transfer_id)
+    .await?;
+
+  audit
+    .record(
+      Some(user.actor_id),
+      AuditAction::OwnerTransferCancelled,
+      transfer.id,
+      Some(json!({ "to_user_id": transfer.to_user_id })),
+    )
+    .await;
+
+  Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(OpenApi)]
+#[openapi(
+  paths(
+    offer_owner_transfer,
+    get_current_owner_transfer,
+    accept_owner_transfer,
+    decline_owner_transfer,
+    cancel_owner_transfer
+  ),
+  components(schemas(
+    OwnerTransferRequest,
+    OwnerTransferResponse,
+    domain::OwnerTransferStatus
+  ))
+)]
+struct Api;
+
+pub fn openapi() -> utoipa::openapi::OpenApi {
+  Api::openapi()
+}
+
+pub fn router() -> Routes {
+  Routes::new()
+    .post("/", offer_owner_transfer, Guard::Authenticated)
+    .get("/current", get_current_owner_transfer, Guard::Authenticated)
+    .delete("/:transfer_id", cancel_owner_transfer, Guard::Authenticated)
+    .post(
+      "/:transfer_id/accept",
+      accept_owner_transfer,
+      Guard::Authenticated,
+    )
+    .post(
+      "/:transfer_id/decline",
+      decline_owner_transfer,
+      Guard::Authenticated,
+    )
+}
@@ -0,0 +1,247 @@
+use crate::{
+  error::AppResult,
+  extractor::{Audit, Authz},
+  models::{ListPayoutsQuery, PayoutResponse},
+  route_permissions::Guard,
+  routes::Routes,
+};
+use application::state::AppState;
+use axum::{
+  extract::{Path, Query, State},
+  http::{header, StatusCode},
+  response::{IntoResponse, Response},
+  Json,
+};
+use domain::{AuditAction, PayoutBatchId, PayoutId, Permission};
+use serde_json::json;
+use utoipa::OpenApi;
+
+/// List bank payouts
+#[utoipa::path(
+  get,
+  path = "/api/payouts",
+  params(ListPayoutsQuery),
+  responses(
+    (status = StatusCode::OK, description = "Payouts, oldest first", body = [PayoutResponse]),
+    (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+    (status = StatusCode::FORBIDDEN, description = "Forbidden", body = ErrorResponse),
+  ),
+  security(
+    ("session_cookie" = [])
+  )
+)]
+pub async fn list_payouts(
+  State(state): State<AppState>,
+  authz: Authz,
+  Query(query): Query<ListPayoutsQuery>,
+) -> AppResult<Json<Vec<PayoutResponse>>> {
+  authz.require(Permission::ManagePayouts)?;
+
+  let payouts = state.payout_service.list(query.status).await?;
+
+  Ok(Json(payouts.into_iter().map(Into::into).collect()))
+}
+
+/// Export requested payouts for the bank
+///
+/// Moves every requested payout into a new batch and returns it as a SEPA
+/// credit transfer file (`pain.001.001.03`) to upload to the bank.
+#[utoipa::path(
+  post,
+  path = "/api/payouts/batches",
+  responses(
+    (status = StatusCode::CREATED, description = "SEPA file of the new batch", content_type = "application/xml", body = String),
+    (status = StatusCode::BAD_REQUEST, description = "No payouts are waiting", body = ErrorResponse),
+    (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+    (status = StatusCode::FORBIDDEN, description = "Forbidden", body = ErrorResponse),
+    (status = StatusCode::SERVICE_UNAVAILABLE, description = "No account to pay out from is configured", body = ErrorResponse),
+  ),
+  security(
+    ("session_cookie" = [])
+  )
+)]
+pub async fn export_batch(
+  State(state): State<AppState>,
+  authz: Authz,
+  audit: Audit,
+) -> AppResult<Response> {
+  authz.require(Permission::ManagePayouts)?;
+
+  let (batch, document) = state.payout_service.export_batch(authz.0.actor_id).await?;
+
+  audit
+    .record(
+      Some(authz.0.actor_id),
+      AuditAction::PayoutBatchExported,
+      batch.id,
+      None,
+    )
+    .await;
+
+  Ok((StatusCode::CREATED, sepa_file(batch.id, document)).into_response())
+}
+
+/// Download the SEPA file of an earlier batch
+#[utoipa::path(
+  get,
+  path = "/api/payouts/batches/{batch_id}",
+  params(
+    ("batch_id" = Uuid, Path, description = "Payout batch id")
+  ),
+  responses(
+    (status = StatusCode::OK, description = "SEPA file of the batch", content_type = "application/xml", body = String),
+    (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+    (status = StatusCode::FORBIDDEN, description = "Forbidden", body = ErrorResponse),
+    (status = StatusCode::NOT_FOUND, description = "Batch not found", body = ErrorResponse),
+    (status = StatusCode::SERVICE_UNAVAILABLE, description = "No account to pay out from is configured", body = ErrorResponse),
+  ),
+  security(
+    ("session_cookie" = [])
+  )
+)]
+pub async fn get_batch(
+  State(state): State<AppState>,
+  authz: Authz,
+  Path(batch_id): Path<PayoutBatchId>,
+) -> AppResult<Response> {
+  authz.require(Permission::ManagePayouts)?;
+
+  let document = state.payout_service.get_batch_document(batch_id).await?;
+
+  Ok(sepa_file(batch_id, document).into_response())
+}
+
+/// Mark a payout as carried out by the bank
+#[utoipa::path(
+  post,
+  path = "/api/payouts/{payout_id}/settle",
+  params(
+    ("payout_id" = Uuid, Path, description = "Payout id")
+  ),
+  responses(
+    (status = StatusCode::OK, description = "Payout settled", body = PayoutResponse),
+    (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+    (status = StatusCode::FORBIDDEN, description = "Forbidden", body = ErrorResponse),
+    (status = StatusCode::NOT_FOUND, description = "Payout not found", body = ErrorResponse),
+    (status = StatusCode::CONFLICT, description = "Payout has not been exported or is settled already", body = ErrorResponse),
+  ),
+  security(
+    ("session_cookie" = [])
+  )
+)]
+pub async fn settle_payout(
+  State(state): State<AppState>,
+  authz: Authz,
+  audit: Audit,
+  Path(payout_id): Path<PayoutId>,
+) -> AppResult<Json<PayoutResponse>> {
+  authz.require(Permission::ManagePayouts)?;
+
+  let payout = state.payout_service.settle(payout_id).await?;
+
+  audit
+    .record(
+      Some(authz.0.actor_id),
+      AuditAction::PayoutSettled,
+      payout.id,
+      None,
+    )
+    .await;
+
+  Ok(Json(payout.into()))
+}
+
+/// Reject a payout
+///
+/// Books the amount back into the wallet, e.g. after the bank returned the
+/// transfer.
+#[utoipa::path(
+  post,
+  path = "/api/payouts/{payout_id}/reject",
+  params(
+    ("payout_id" = Uuid, Path, description = "Payout id")
+  ),
+  responses(
+    (status = StatusCode::OK, description = "Payout rejected and refunded", body = PayoutResponse),
+    (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+    (status = StatusCode::FORBIDDEN, description = "Forbidden", body = ErrorResponse),
+    (status = StatusCode::NOT_FOUND, description = "Payout not found", body = ErrorResponse),
+    (status = StatusCode::CONFLICT, description = "Payout is settled or rejected already", body = ErrorResponse),
+  ),
+  security(
+    ("session_cookie" = [])
+  )
+)]
+pub async fn reject_payout(
+  State(state): State<AppState>,
+  authz: Authz,
+  audit: Audit,
+  Path(payout_id): Path<PayoutId>,
+) -> AppResult<Json<PayoutResponse>> {
+  authz.require(Permission::ManagePayouts)?;
+
+  let payout = state
+    .payout_service
+    .reject(authz.0.actor_id, payout_id)
+    .await?;
+
+  audit
+    .record(
+      Some(authz.0.actor_id),
+      AuditAction::PayoutRejected,
+      payout.id,
+      Some(json!({ "refund_transaction_id": payout.refund_transaction_id })),
+    )
+    .await;
+
+  Ok(Json(payout.into()))
+}
+
+fn sepa_file(batch_id: PayoutBatchId, document: String) -> impl IntoResponse {
+  (
+    [
+      (
+        header::CONTENT_TYPE,
+        "application/xml; charset=utf-8".to_string(),
+      ),
+      (
+        header::CONTENT_DISPOSITION,
+        format!("attachment; filename=\"payouts-{}.xml\"", batch_id),
+      ),
+    ],
+    document,
+  )
+}
+
+#[derive(OpenApi)]
+#[openapi(paths(list_payouts, export_batch, get_batch, settle_payout, reject_payout,))]
+struct Api;
+
+pub fn openapi() -> utoipa::openapi::OpenApi {
+  Api::openapi()
+}
+
+pub fn router() -> Routes {
+  Routes::new()
+    .get("/", list_payouts, Guard::All(&[Permission::ManagePayouts]))
+    .post(
+      "/batches",
+      export_batch,
+      Guard::All(&[Permission::ManagePayouts]),
+    )
+    .get(
+      "/batches/:batch_id",
+      get_batch,
+      Guard::All(&[Permission::ManagePayouts]),
+    )
+    .post(
+      "/:payout_id/settle",
+      settle_payout,
+      Guard::All(&[Permission::ManagePayouts]),
+    )
+    .post(
+      "/:payout_id/reject",
+      reject_payout,
+      Guard::All(&[Permission::ManagePayouts]),
+    )
+}
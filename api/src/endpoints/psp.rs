@@ -0,0 +1,53 @@
+use crate::{error::AppResult, route_permissions::Access, routes::Routes};
+use application::{error::AppError, services::top_up::SIGNATURE_HEADER, state::AppState};
+use axum::{
+  extract::State,
+  http::{HeaderMap, StatusCode},
+};
+use utoipa::OpenApi;
+
+/// Receive events from the payment provider
+///
+/// Called by the provider, not by clients. Events are authenticated by their
+/// signature and may be delivered more than once.
+#[utoipa::path(
+  post,
+  path = "/api/psp/webhook",
+  request_body(content = String, content_type = "application/json"),
+  responses(
+    (status = StatusCode::NO_CONTENT, description = "Event handled or ignored"),
+    (status = StatusCode::BAD_REQUEST, description = "Invalid signature or malformed event", body = ErrorResponse),
+    (status = StatusCode::SERVICE_UNAVAILABLE, description = "No payment provider configured", body = ErrorResponse),
+  )
+)]
+pub async fn provider_webhook(
+  State(state): State<AppState>,
+  headers: HeaderMap,
+  body: String,
+) -> AppResult<StatusCode> {
+  let signature = headers
+    .get(SIGNATURE_HEADER)
+    .and_then(|value| value.to_str().ok())
+    .ok_or_else(|| AppError::BadRequest("Missing signature".to_string()))?;
+
+  state
+    .top_up_service
+    .handle_provider_event(signature, &body)
+    .await?;
+
+  Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(OpenApi)]
+#[openapi(paths(provider_webhook,))]
+struct Api;
+
+pub fn openapi() -> utoipa::openapi::OpenApi {
+  Api::openapi()
+}
+
+pub fn router() -> Routes {
+  Routes::new()
+    // Signed by the payment service provider
+    .post("/webhook", provider_webhook, Access::Public)
+}
@@ -0,0 +1,197 @@
+use std::{convert::Infallible, time::Duration};
+
+use crate::{
+  error::AppResult,
+  extractor::Authz,
+  middleware::locale::{current_currency, current_locale, in_locale},
+  models::{LiveShopStatsEvent, SalesReportQuery, SalesReportResponse},
+  route_permissions::Guard,
+  routes::Routes,
+};
+use application::{error::AppError, state::AppState};
+use axum::{
+  extract::{Path, Query, State},
+  response::sse::{Event, KeepAlive, Sse},
+  Json,
+};
+use chrono::Utc;
+use domain::{Currency, LiveShopStats, Locale, OrderEvent, Permission, ShopId};
+use futures_util::{stream, Stream};
+use tokio::{
+  sync::broadcast::{self, error::RecvError},
+  time::{interval, Interval, MissedTickBehavior},
+};
+use utoipa::OpenApi;
+
+/// How often the stats are pushed even without new orders, so orders per
+/// minute decays on screen
+const LIVE_STATS_INTERVAL: Duration = Duration::from_secs(5);
+
+#[utoipa::path(
+  get,
+  path = "/api/shops/{shop_id}/reports/sales",
+  params(
+    ("shop_id" = Uuid, Path, description = "Shop id"),
+    SalesReportQuery
+  ),
+  responses(
+    (status = StatusCode::OK, description = "Sales per offering and per day with gross revenue", body = SalesReportResponse),
+    (status = StatusCode::BAD_REQUEST, description = "`from` is not before `to`", body = ErrorResponse),
+    (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+    (status = StatusCode::FORBIDDEN, description = "Neither shop owner nor allowed to read reports", body = ErrorResponse),
+    (status = StatusCode::NOT_FOUND, description = "Shop not found", body = ErrorResponse),
+  ),
+  security(
+    ("session_cookie" = [])
+  )
+)]
+pub async fn sales_report(
+  State(state): State<AppState>,
+  authz: Authz,
+  Path(shop_id): Path<ShopId>,
+  Query(query): Query<SalesReportQuery>,
+) -> AppResult<Json<SalesReportResponse>> {
+  let shop = state
+    .shop_service
+    .get_by_id(shop_id)
+    .await?
+    .ok_or(AppError::NotFound)?;
+
+  if !authz.has_on_shop(&shop, Permission::ReadReports) {
+    return Err(AppError::Authorization.into());
+  }
+
+  let report = state
+    .order_service
+    .sales_report(shop.id, query.from, query.to)
+    .await?;
+
+  Ok(Json(report.into()))
+}
+
+/// Today's figures of a shop as server-sent events, for the screen behind the
+/// bar.
+///
+/// Stats are pushed on every payment or refund of the shop and every few
+/// seconds in between. Days run at the venue (`VENUE_TIMEZONE`).
+#[utoipa::path(
+  get,
+  path = "/api/shops/{shop_id}/reports/live",
+  params(
+    ("shop_id" = Uuid, Path, description = "Shop id"),
+  ),
+  responses(
+    (status = StatusCode::OK, description = "Stream of `stats` events", body = LiveShopStatsEvent, content_type = "text/event-stream"),
+    (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+    (status = StatusCode::FORBIDDEN, description = "Neither shop staff nor allowed to read reports", body = ErrorResponse),
+    (status = StatusCode::NOT_FOUND, description = "Shop not found", body = ErrorResponse),
+  ),
+  security(
+    ("session_cookie" = [])
+  )
+)]
+pub async fn live_stats(
+  State(state): State<AppState>,
+  authz: Authz,
+  Path(shop_id): Path<ShopId>,
+) -> AppResult<Sse<impl Stream<Item = Result<Event, Infallible>>>> {
+  let shop = state
+    .shop_service
+    .get_by_id(shop_id)
+    .await?
+    .ok_or(AppError::NotFound)?;
+
+  if !state.shop_service.is_staff(&shop, authz.0.id).await? {
+    authz.require(Permission::ReadReports)?;
+  }
+
+  // Subscribe before taking the snapshot so no order slips in between
+  let events = state.event_bus.subscribe();
+  let stats = state.order_service.live_stats(shop.id).await?;
+
+  let mut ticks = interval(LIVE_STATS_INTERVAL);
+  ticks.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+  Ok(
+    Sse::new(stats_events(
+      shop.id,
+      current_locale(),
+      current_currency(),
+      stats,
+      events,
+      ticks,
+    ))
+    .keep_alive(KeepAlive::default()),
+  )
+}
+
+/// The stats right away, then again on every payment or refund of the shop and
+/// on every tick, with amounts formatted in `locale` and `currency`.
+fn stats_events(
+  shop_id: ShopId,
+  locale: Locale,
+  currency: Currency,
+  stats: LiveShopStats,
+  events: broadcast::Receiver<OrderEvent>,
+  ticks: Interval,
+) -> impl Stream<Item = Result<Event, Infallible>> {
+  stream::unfold(
+    (stats, events, ticks),
+    move |(mut stats, mut events, mut ticks)| async move {
+      loop {
+        tokio::select! {
+          _ = ticks.tick() => break,
+          event = events.recv() => match event {
+            Ok(event @ (OrderEvent::Paid { .. } | OrderEvent::Refunded { .. }))
+              if event.shop_id() == shop_id =>
+            {
+              stats.apply(&event);
+              break;
+            }
+            Ok(_) | Err(RecvError::Lagged(_)) => continue,
+            Err(RecvError::Closed) => return None,
+          },
+        }
+      }
+
+      let event = in_locale(locale, currency, || {
+        Event::default()
+          .event("stats")
+          .json_data(LiveShopStatsEvent::new(&mut stats, Utc::now()))
+          .expect("stats event serializes")
+      });
+
+      Some((Ok(event), (stats, events, ticks)))
+    },
+  )
+}
+
+#[derive(OpenApi)]
+#[openapi(
+  paths(sales_report, live_stats,),
+  components(schemas(
+    crate::models::OfferingSalesResponse,
+    crate::models::DailySalesResponse,
+    SalesReportResponse,
+    LiveShopStatsEvent,
+  ))
+)]
+struct Api;
+
+pub fn openapi() -> utoipa::openapi::OpenApi {
+  Api::openapi()
+}
+
+pub fn router() -> Routes {
+  Routes::new()
+    .get(
+      "/shops/:shop_id/reports/sales",
+      sales_report,
+      Guard::OwnerOr(&[Permission::ReadReports]),
+    )
+    .get(
+      "/shops/:shop_id/reports/live",
+      live_stats,
+      Guard::ShopStaffOr(&[Permission::ReadReports]),
+    )
+}
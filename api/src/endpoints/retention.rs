@@ -0,0 +1,54 @@
+use crate::{
+  error::AppResult, extractor::Authz, models::RetentionOutcomeResponse, route_permissions::Guard,
+  routes::Routes,
+};
+use application::state::AppState;
+use axum::{extract::State, Json};
+use domain::Permission;
+use utoipa::OpenApi;
+
+/// Preview the data retention rules
+///
+/// Reports what the next scheduled run would delete or anonymize, without
+/// changing anything. Rules configured to keep data forever are left out.
+#[utoipa::path(
+  get,
+  path = "/api/retention/report",
+  responses(
+    (status = StatusCode::OK, description = "Rows each enabled rule would affect now", body = [RetentionOutcomeResponse]),
+    (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+    (status = StatusCode::FORBIDDEN, description = "Forbidden", body = ErrorResponse),
+  ),
+  security(
+    ("session_cookie" = [])
+  )
+)]
+pub async fn retention_report(
+  State(state): State<AppState>,
+  authz: Authz,
+) -> AppResult<Json<Vec<RetentionOutcomeResponse>>> {
+  authz.require(Permission::ConfigureSettings)?;
+
+  let outcomes = state.retention_service.apply(true).await?;
+
+  Ok(Json(outcomes.into_iter().map(Into::into).collect()))
+}
+
+#[derive(OpenApi)]
+#[openapi(
+  paths(retention_report,),
+  components(schemas(RetentionOutcomeResponse, domain::RetentionRule,))
+)]
+struct Api;
+
+pub fn openapi() -> utoipa::openapi::OpenApi {
+  Api::openapi()
+}
+
+pub fn router() -> Routes {
+  Routes::new().get(
+    "/report",
+    retention_report,
+    Guard::All(&[Permission::ConfigureSettings]),
+  )
+}
@@ -0,0 +1,123 @@
+use crate::{
+  error::AppResult,
+  extractor::{Audit, Authz, ValidatedJson},
+  models::{ListReviewsQuery, ResolveReviewRequest, ReviewItemResponse},
+  route_permissions::Guard,
+  routes::Routes,
+};
+use application::state::AppState;
+use axum::{
+  extract::{Path, Query, State},
+  Json,
+};
+use domain::{AuditAction, Permission, ReviewItemId};
+use serde_json::json;
+use utoipa::OpenApi;
+
+#[utoipa::path(
+  get,
+  path = "/api/reviews",
+  params(ListReviewsQuery),
+  responses(
+    (status = StatusCode::OK, description = "Review queue, oldest first", body = [ReviewItemResponse]),
+    (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+    (status = StatusCode::FORBIDDEN, description = "Forbidden", body = ErrorResponse),
+  ),
+  security(
+    ("session_cookie" = [])
+  )
+)]
+pub async fn list_reviews(
+  State(state): State<AppState>,
+  authz: Authz,
+  Query(query): Query<ListReviewsQuery>,
+) -> AppResult<Json<Vec<ReviewItemResponse>>> {
+  authz.require(Permission::ReviewSuspiciousActivity)?;
+
+  let items = state
+    .risk_service
+    .list_reviews(query.status.unwrap_or_default())
+    .await?;
+
+  Ok(Json(items.into_iter().map(Into::into).collect()))
+}
+
+#[utoipa::path(
+  post,
+  path = "/api/reviews/{review_id}/resolve",
+  request_body = ResolveReviewRequest,
+  params(
+    ("review_id" = Uuid, Path, description = "Review item id")
+  ),
+  responses(
+    (status = StatusCode::OK, description = "Review resolved", body = ReviewItemResponse),
+    (status = StatusCode::BAD_REQUEST, description = "Validation error or review already resolved", body = ErrorResponse),
+    (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+    (status = StatusCode::FORBIDDEN, description = "Forbidden", body = ErrorResponse),
+    (status = StatusCode::NOT_FOUND, description = "Review item not found", body = ErrorResponse),
+  ),
+  security(
+    ("session_cookie" = [])
+  )
+)]
+pub async fn resolve_review(
+  State(state): State<AppState>,
+  authz: Authz,
+  audit: Audit,
+  Path(review_id): Path<ReviewItemId>,
+  ValidatedJson(payload): ValidatedJson<ResolveReviewRequest>,
+) -> AppResult<Json<ReviewItemResponse>> {
+  authz.require(Permission::ReviewSuspiciousActivity)?;
+
+  let item = state
+    .risk_service
+    .resolve_review(
+      review_id,
+      authz.0.actor_id,
+      payload.status,
+      payload.note,
+      payload.unfreeze_wallet,
+    )
+    .await?;
+
+  audit
+    .record(
+      Some(authz.0.actor_id),
+      AuditAction::ReviewResolved,
+      review_id,
+      Some(json!({ "status": item.status })),
+    )
+    .await;
+
+  Ok(Json(item.into()))
+}
+
+#[derive(OpenApi)]
+#[openapi(
+  paths(list_reviews, resolve_review,),
+  components(schemas(
+    ReviewItemResponse,
+    ResolveReviewRequest,
+    domain::ReviewStatus,
+    domain::RiskSignal,
+  ))
+)]
+struct Api;
+
+pub fn openapi() -> utoipa::openapi::OpenApi {
+  Api::openapi()
+}
+
+pub fn router() -> Routes {
+  Routes::new()
+    .get(
+      "/",
+      list_reviews,
+      Guard::All(&[Permission::ReviewSuspiciousActivity]),
+    )
+    .post(
+      "/:review_id/resolve",
+      resolve_review,
+      Guard::All(&[Permission::ReviewSuspiciousActivity]),
+    )
+}
@@ -0,0 +1,299 @@
+use crate::{
+  error::AppResult,
+  extractor::{Audit, Authz, ValidatedJson},
+  models::{
+    FeatureSwitchResponse, InviteDomainsResponse, SettingsResponse, UpdateFeatureSwitchRequest,
+    UpdateInviteDomainsRequest, UpdateSettingsRequest,
+  },
+  route_permissions::Guard,
+  routes::Routes,
+};
+use application::{error::AppError, state::AppState};
+use axum::{
+  extract::{Path, State},
+  Json,
+};
+use domain::{AuditAction, Feature, Permission};
+use serde_json::json;
+use utoipa::OpenApi;
+
+/// Get the runtime settings
+///
+/// The configured defaults until an owner changes them.
+#[utoipa::path(
+  get,
+  path = "/api/settings",
+  responses(
+    (status = StatusCode::OK, description = "Settings in effect", body = SettingsResponse),
+    (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+    (status = StatusCode::FORBIDDEN, description = "Forbidden", body = ErrorResponse),
+  ),
+  security(
+    ("session_cookie" = [])
+  )
+)]
+pub async fn get_settings(
+  State(state): State<AppState>,
+  authz: Authz,
+) -> AppResult<Json<SettingsResponse>> {
+  authz.require(Permission::ConfigureSettings)?;
+
+  let settings = state.settings_service.get().await?;
+
+  Ok(Json(settings.into()))
+}
+
+/// Replace the runtime settings
+///
+/// Other instances of the server follow within a few seconds. Invites
+/// already sent and wallets already opened keep what they were created
+/// with.
+#[utoipa::path(
+  put,
+  path = "/api/settings",
+  request_body = UpdateSettingsRequest,
+  responses(
+    (status = StatusCode::OK, description = "Settings replaced", body = SettingsResponse),
+    (status = StatusCode::BAD_REQUEST, description = "Validation error", body = ErrorResponse),
+    (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+    (status = StatusCode::FORBIDDEN, description = "Forbidden", body = ErrorResponse),
+  ),
+  security(
+    ("session_cookie" = [])
+  )
+)]
+pub async fn update_settings(
+  State(state): State<AppState>,
+  authz: Authz,
+  audit: Audit,
+  ValidatedJson(payload): ValidatedJson<UpdateSettingsRequest>,
+) -> AppResult<Json<SettingsResponse>> {
+  authz.require(Permission::ConfigureSettings)?;
+
+  let settings = state
+    .settings_service
+    .update(payload.into_settings(), authz.0.actor_id)
+    .await?;
+
+  audit
+    .record(
+      Some(authz.0.actor_id),
+      AuditAction::SettingsChanged,
+      "settings",
+      Some(json!({
+        "invite_expiration_days": settings.invite_expiration_days,
+        "allow_overdraft_by_default": settings.allow_overdraft_by_default,
+        "currency": settings.currency,
+        "email_sender_name": settings.email_sender_name,
+      })),
+    )
+    .await;
+
+  Ok(Json(settings.into()))
+}
+
+/// Get which email domains may be invited
+#[utoipa::path(
+  get,
+  path = "/api/settings/invite-domains",
+  responses(
+    (status = StatusCode::OK, description = "Rules of which domains may be invited", body = InviteDomainsResponse),
+    (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+    (status = StatusCode::FORBIDDEN, description = "Forbidden", body = ErrorResponse),
+  ),
+  security(
+    ("session_cookie" = [])
+  )
+)]
+pub async fn get_invite_domains(
+  State(state): State<AppState>,
+  authz: Authz,
+) -> AppResult<Json<InviteDomainsResponse>> {
+  authz.require(Permission::ConfigureSettings)?;
+
+  let policy = state.invite_service.domain_policy().await?;
+
+  Ok(Json(policy.into()))
+}
+
+/// Replace which email domains may be invited
+///
+/// Applies to new invites and member imports; invites already sent stay
+/// valid.
+#[utoipa::path(
+  put,
+  path = "/api/settings/invite-domains",
+  request_body = UpdateInviteDomainsRequest,
+  responses(
+    (status = StatusCode::OK, description = "Rules replaced", body = InviteDomainsResponse),
+    (status = StatusCode::BAD_REQUEST, description = "Not a domain, or a domain with two rules for a role", body = ErrorResponse),
+    (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+    (status = StatusCode::FORBIDDEN, description = "Forbidden", body = ErrorResponse),
+  ),
+  security(
+    ("session_cookie" = [])
+  )
+)]
+pub async fn update_invite_domains(
+  State(state): State<AppState>,
+  authz: Authz,
+  audit: Audit,
+  ValidatedJson(payload): ValidatedJson<UpdateInviteDomainsRequest>,
+) -> AppResult<Json<InviteDomainsResponse>> {
+  authz.require(Permission::ConfigureSettings)?;
+
+  let rules = payload.into_rules().map_err(AppError::BadRequest)?;
+  let policy = state.invite_service.set_domain_policy(rules).await?;
+  let response = InviteDomainsResponse::from(policy);
+
+  audit
+    .record(
+      Some(authz.0.actor_id),
+      AuditAction::SettingsChanged,
+      "invite_domains",
+      Some(json!({ "rules": response.rules })),
+    )
+    .await;
+
+  Ok(Json(response))
+}
+
+/// List the feature switches
+///
+/// Every feature that can be switched off at runtime, whether it is on, and
+/// who switched it last.
+#[utoipa::path(
+  get,
+  path = "/api/settings/features",
+  responses(
+    (status = StatusCode::OK, description = "Switches of all features", body = [FeatureSwitchResponse]),
+    (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+    (status = StatusCode::FORBIDDEN, description = "Forbidden", body = ErrorResponse),
+  ),
+  security(
+    ("session_cookie" = [])
+  )
+)]
+pub async fn list_feature_switches(
+  State(state): State<AppState>,
+  authz: Authz,
+) -> AppResult<Json<Vec<FeatureSwitchResponse>>> {
+  authz.require(Permission::ConfigureSettings)?;
+
+  let switches = state.feature_switch_service.list().await?;
+
+  Ok(Json(switches.into_iter().map(Into::into).collect()))
+}
+
+/// Switch a feature on or off
+///
+/// While a feature is off, requests using it fail with `503` and the code
+/// `feature_disabled`, carrying the reason. Other instances of the server
+/// follow within a few seconds.
+#[utoipa::path(
+  put,
+  path = "/api/settings/features/{feature}",
+  request_body = UpdateFeatureSwitchRequest,
+  params(
+    ("feature" = Feature, Path, description = "Feature to switch")
+  ),
+  responses(
+    (status = StatusCode::OK, description = "Feature switched", body = FeatureSwitchResponse),
+    (status = StatusCode::BAD_REQUEST, description = "Validation error or unknown feature", body = ErrorResponse),
+    (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+    (status = StatusCode::FORBIDDEN, description = "Forbidden", body = ErrorResponse),
+  ),
+  security(
+    ("session_cookie" = [])
+  )
+)]
+pub async fn update_feature_switch(
+  State(state): State<AppState>,
+  authz: Authz,
+  audit: Audit,
+  Path(feature): Path<Feature>,
+  ValidatedJson(payload): ValidatedJson<UpdateFeatureSwitchRequest>,
+) -> AppResult<Json<FeatureSwitchResponse>> {
+  authz.require(Permission::ConfigureSettings)?;
+
+  let switch = state
+    .feature_switch_service
+    .set(feature, payload.enabled, payload.reason, authz.0.actor_id)
+    .await?;
+
+  audit
+    .record(
+      Some(authz.0.actor_id),
+      AuditAction::SettingsChanged,
+      "features",
+      Some(json!({
+        "feature": switch.feature,
+        "enabled": switch.enabled,
+        "reason": switch.reason,
+      })),
+    )
+    .await;
+
+  Ok(Json(switch.into()))
+}
+
+#[derive(OpenApi)]
+#[openapi(
+  paths(
+    get_settings,
+    update_settings,
+    get_invite_domains,
+    update_invite_domains,
+    list_feature_switches,
+    update_feature_switch,
+  ),
+  components(schemas(
+    UpdateSettingsRequest,
+    SettingsResponse,
+    crate::models::InviteDomainRuleBody,
+    UpdateInviteDomainsRequest,
+    InviteDomainsResponse,
+    Feature,
+    UpdateFeatureSwitchRequest,
+    FeatureSwitchResponse,
+  ))
+)]
+struct Api;
+
+pub fn openapi() -> utoipa::openapi::OpenApi {
+  Api::openapi()
+}
+
+pub fn router() -> Routes {
+  Routes::new()
+    .get(
+      "/",
+      get_settings,
+      Guard::All(&[Permission::ConfigureSettings]),
+    )
+    .put(
+      "/",
+      update_settings,
+      Guard::All(&[Permission::ConfigureSettings]),
+    )
+    .get(
+      "/invite-domains",
+      get_invite_domains,
+      Guard::All(&[Permission::ConfigureSettings]),
+    )
+    .put(
+      "/invite-domains",
+      update_invite_domains,
+      Guard::All(&[Permission::ConfigureSettings]),
+    )
+    .get(
+      "/features",
+      list_feature_switches,
+      Guard::All(&[Permission::ConfigureSettings]),
+    )
+    .put(
+      "/features/:feature",
+      update_feature_switch,
+      Guard::All(&[Permission::ConfigureSettings]),
+    )
+}
@@ -0,0 +1,486 @@
+use crate::{
+  error::AppResult,
+  extractor::{Audit, Authz, ValidatedJson},
+  models::{
+    AddShopMemberRequest, CreateWebhookRequest, CreatedWebhookResponse, ListPickupSlotsQuery,
+    PickupSlotResponse, PickupSlotsRequest, PickupSlotsResponse, Redact, ShopMemberResponse,
+    WebhookResponse,
+  },
+  route_permissions::Guard,
+  routes::Routes,
+};
+use application::{error::AppError, state::AppState};
+use axum::{
+  extract::{Path, Query, State},
+  http::StatusCode,
+  Json,
+};
+use chrono::{Duration, Utc};
+use domain::{AuditAction, Id, Permission, Shop, ShopId, User, Webhook};
+use serde_json::json;
+use utoipa::OpenApi;
+
+/// Only the shop's owner, or whoever holds `permission` for every shop, gets
+/// past this.
+pub(crate) async fn require_shop_owner_or(
+  state: &AppState,
+  authz: &Authz,
+  shop_id: ShopId,
+  permission: Permission,
+) -> AppResult<()> {
+  let shop = state
+    .shop_service
+    .get_by_id(shop_id)
+    .await?
+    .ok_or(AppError::NotFound)?;
+
+  if authz.has_on_shop(&shop, permission) {
+    Ok(())
+  } else {
+    Err(AppError::Authorization.into())
+  }
+}
+
+#[utoipa::path(
+  post,
+  path = "/api/shops/{shop_id}/members",
+  request_body = AddShopMemberRequest,
+  params(
+    ("shop_id" = Uuid, Path, description = "Shop id")
+  ),
+  responses(
+    (status = StatusCode::CREATED, description = "User added to the shop's staff", body = ShopMemberResponse),
+    (status = StatusCode::BAD_REQUEST, description = "Validation error", body = ErrorResponse),
+    (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+    (status = StatusCode::FORBIDDEN, description = "Neither shop owner nor allowed to manage shop members", body = ErrorResponse),
+    (status = StatusCode::NOT_FOUND, description = "Shop or user not found", body = ErrorResponse),
+    (status = StatusCode::CONFLICT, description = "User is already a member", body = ErrorResponse),
+  ),
+  security(
+    ("session_cookie" = [])
+  )
+)]
+pub async fn add_member(
+  State(state): State<AppState>,
+  authz: Authz,
+  audit: Audit,
+  Path(shop_id): Path<ShopId>,
+  ValidatedJson(payload): ValidatedJson<AddShopMemberRequest>,
+) -> AppResult<(StatusCode, Json<ShopMemberResponse>)> {
+  require_shop_owner_or(&state, &authz, shop_id, Permission::ManageShopMembers).await?;
+
+  let member = state
+    .shop_service
+    .add_member(shop_id, payload.user_id)
+    .await?;
+
+  audit
+    .record(
+      Some(authz.0.actor_id),
+      AuditAction::ShopMemberAdded,
+      shop_id,
+      Some(json!({ "user_id": member.user_id })),
+    )
+    .await;
+  let user = state
+    .user_service
+    .get_by_id(member.user_id)
+    .await?
+    .ok_or(AppError::NotFound)?;
+
+  let response = ShopMemberResponse::from((member, user)).redact(&authz);
+
+  Ok((StatusCode::CREATED, Json(response)))
+}
+
+#[utoipa::path(
+  delete,
+  path = "/api/shops/{shop_id}/members/{user_id}",
+  params(
+    ("shop_id" = Uuid, Path, description = "Shop id"),
+    ("user_id" = Uuid, Path, description = "User id of the member")
+  ),
+  responses(
+    (status = StatusCode::NO_CONTENT, description = "User removed from the shop's staff"),
+    (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+    (status = StatusCode::FORBIDDEN, description = "Neither shop owner nor allowed to manage shop members", body = ErrorResponse),
+    (status = StatusCode::NOT_FOUND, description = "Shop not found or user is not a member", body = ErrorResponse),
+  ),
+  security(
+    ("session_cookie" = [])
+  )
+)]
+pub async fn remove_member(
+  State(state): State<AppState>,
+  authz: Authz,
+  audit: Audit,
+  Path((shop_id, user_id)): Path<(Id<Shop>, Id<User>)>,
+) -> AppResult<StatusCode> {
+  require_shop_owner_or(&state, &authz, shop_id, Permission::ManageShopMembers).await?;
+
+  state.shop_service.remove_member(shop_id, user_id).await?;
+
+  audit
+    .record(
+      Some(authz.0.actor_id),
+      AuditAction::ShopMemberRemoved,
+      shop_id,
+      Some(json!({ "user_id": user_id })),
+    )
+    .await;
+
+  Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+  get,
+  path = "/api/shops/{shop_id}/members",
+  params(
+    ("shop_id" = Uuid, Path, description = "Shop id")
+  ),
+  responses(
+    (status = StatusCode::OK, description = "Members of the shop; contact details are omitted without ReadUserDetails", body = Vec<ShopMemberResponse>),
+    (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+    (status = StatusCode::FORBIDDEN, description = "Neither shop owner nor allowed to manage shop members", body = ErrorResponse),
+    (status = StatusCode::NOT_FOUND, description = "Shop not found", body = ErrorResponse),
+  ),
+  security(
+    ("session_cookie" = [])
+  )
+)]
+pub async fn list_members(
+  State(state): State<AppState>,
+  authz: Authz,
+  Path(shop_id): Path<ShopId>,
+) -> AppResult<Json<Vec<ShopMemberResponse>>> {
+  require_shop_owner_or(&state, &authz, shop_id, Permission::ManageShopMembers).await?;
+
+  let members = state.shop_service.list_members(shop_id).await?;
+  let response = members
+    .into_iter()
+    .map(|member| ShopMemberResponse::from(member).redact(&authz))
+    .collect();
+
+  Ok(Json(response))
+}
+
+#[utoipa::path(
+  post,
+  path = "/api/shops/{shop_id}/webhooks",
+  request_body = CreateWebhookRequest,
+  params(
+    ("shop_id" = Uuid, Path, description = "Shop id")
+  ),
+  responses(
+    (status = StatusCode::CREATED, description = "Webhook registered; the signing secret is only shown now", body = CreatedWebhookResponse),
+    (status = StatusCode::BAD_REQUEST, description = "Validation error or events that don't happen at shops", body = ErrorResponse),
+    (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+    (status = StatusCode::FORBIDDEN, description = "Neither shop owner nor allowed to manage webhooks", body = ErrorResponse),
+    (status = StatusCode::NOT_FOUND, description = "Shop not found", body = ErrorResponse),
+  ),
+  security(
+    ("session_cookie" = [])
+  )
+)]
+pub async fn create_webhook(
+  State(state): State<AppState>,
+  authz: Authz,
+  audit: Audit,
+  Path(shop_id): Path<ShopId>,
+  ValidatedJson(payload): ValidatedJson<CreateWebhookRequest>,
+) -> AppResult<(StatusCode, Json<CreatedWebhookResponse>)> {
+  require_shop_owner_or(&state, &authz, shop_id, Permission::ManageWebhooks).await?;
+
+  let webhook = state
+    .webhook_service
+    .register_for_shop(shop_id, authz.0.actor_id, payload.url, payload.events)
+    .await?;
+
+  audit
+    .record(
+      Some(authz.0.actor_id),
+      AuditAction::WebhookRegistered,
+      webhook.id,
+      Some(json!({ "shop_id": shop_id, "url": webhook.url })),
+    )
+    .await;
+
+  Ok((StatusCode::CREATED, Json(webhook.into())))
+}
+
+#[utoipa::path(
+  get,
+  path = "/api/shops/{shop_id}/webhooks",
+  params(
+    ("shop_id" = Uuid, Path, description = "Shop id")
+  ),
+  responses(
+    (status = StatusCode::OK, description = "Webhooks registered for the shop", body = Vec<WebhookResponse>),
+    (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+    (status = StatusCode::FORBIDDEN, description = "Neither shop owner nor allowed to manage webhooks", body = ErrorResponse),
+    (status = StatusCode::NOT_FOUND, description = "Shop not found", body = ErrorResponse),
+  ),
+  security(
+    ("session_cookie" = [])
+  )
+)]
+pub async fn list_webhooks(
+  State(state): State<AppState>,
+  authz: Authz,
+  Path(shop_id): Path<ShopId>,
+) -> AppResult<Json<Vec<WebhookResponse>>> {
+  require_shop_owner_or(&state, &authz, shop_id, Permission::ManageWebhooks).await?;
+
+  let webhooks = state.webhook_service.list_for_shop(shop_id).await?;
+
+  Ok(Json(webhooks.into_iter().map(Into::into).collect()))
+}
+
+#[utoipa::path(
+  delete,
+  path = "/api/shops/{shop_id}/webhooks/{webhook_id}",
+  params(
+    ("shop_id" = Uuid, Path, description = "Shop id"),
+    ("webhook_id" = Uuid, Path, description = "Webhook id")
+  ),
+  responses(
+    (status = StatusCode::NO_CONTENT, description = "Webhook removed"),
+    (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+    (status = StatusCode::FORBIDDEN, description = "Neither shop owner nor allowed to manage webhooks", body = ErrorResponse),
+    (status = StatusCode::NOT_FOUND, description = "Shop or webhook not found", body = ErrorResponse),
+  ),
+  security(
+    ("session_cookie" = [])
+  )
+)]
+pub async fn remove_webhook(
+  State(state): State<AppState>,
+  authz: Authz,
+  audit: Audit,
+  Path((shop_id, webhook_id)): Path<(Id<Shop>, Id<Webhook>)>,
+) -> AppResult<StatusCode> {
+  require_shop_owner_or(&state, &authz, shop_id, Permission::ManageWebhooks).await?;
+
+  let webhook = state
+    .webhook_service
+    .remove_from_shop(shop_id, webhook_id)
+    .await?;
+
+  audit
+    .record(
+      Some(authz.0.actor_id),
+      AuditAction::WebhookRemoved,
+      webhook.id,
+      Some(json!({ "shop_id": shop_id })),
+    )
+    .await;
+
+  Ok(StatusCode::NO_CONTENT)
+}
+
+/// Open the shop for pre-orders
+#[utoipa::path(
+  put,
+  path = "/api/shops/{shop_id}/pickup-slots",
+  request_body = PickupSlotsRequest,
+  params(
+    ("shop_id" = Uuid, Path, description = "Shop id")
+  ),
+  responses(
+    (status = StatusCode::OK, description = "Pickup slots set; booked pre-orders are kept", body = PickupSlotsResponse),
+    (status = StatusCode::BAD_REQUEST, description = "Validation error", body = ErrorResponse),
+    (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+    (status = StatusCode::FORBIDDEN, description = "Neither shop owner nor allowed to configure settings", body = ErrorResponse),
+    (status = StatusCode::NOT_FOUND, description = "Shop not found", body = ErrorResponse),
+  ),
+  security(
+    ("session_cookie" = [])
+  )
+)]
+pub async fn set_pickup_slots(
+  State(state): State<AppState>,
+  authz: Authz,
+  audit: Audit,
+  Path(shop_id): Path<ShopId>,
+  ValidatedJson(payload): ValidatedJson<PickupSlotsRequest>,
+) -> AppResult<Json<PickupSlotsResponse>> {
+  require_shop_owner_or(&state, &authz, shop_id, Permission::ConfigureSettings).await?;
+
+  let shop = state
+    .shop_service
+    .set_pickup_slots(shop_id, Some(payload.into()))
+    .await?;
+  let slots = shop.pickup_slots.ok_or(AppError::NotFound)?;
+
+  audit
+    .record(
+      Some(authz.0.actor_id),
+      AuditAction::PickupSlotsChanged,
+      shop.id,
+      Some(json!({
+        "slot_minutes": slots.length.num_minutes(),
+        "capacity": slots.capacity,
+      })),
+    )
+    .await;
+
+  Ok(Json(slots.into()))
+}
+
+/// Stop taking pre-orders
+#[utoipa::path(
+  delete,
+  path = "/api/shops/{shop_id}/pickup-slots",
+  params(
+    ("shop_id" = Uuid, Path, description = "Shop id")
+  ),
+  responses(
+    (status = StatusCode::NO_CONTENT, description = "Pre-ordering turned off; booked pre-orders are kept"),
+    (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+    (status = StatusCode::FORBIDDEN, description = "Neither shop owner nor allowed to configure settings", body = ErrorResponse),
+    (status = StatusCode::NOT_FOUND, description = "Shop not found", body = ErrorResponse),
+  ),
+  security(
+    ("session_cookie" = [])
+  )
+)]
+pub async fn clear_pickup_slots(
+  State(state): State<AppState>,
+  authz: Authz,
+  audit: Audit,
+  Path(shop_id): Path<ShopId>,
+) -> AppResult<StatusCode> {
+  require_shop_owner_or(&state, &authz, shop_id, Permission::ConfigureSettings).await?;
+
+  let shop = state.shop_service.set_pickup_slots(shop_id, None).await?;
+
+  audit
+    .record(
+      Some(authz.0.actor_id),
+      AuditAction::PickupSlotsChanged,
+      shop.id,
+      None,
+    )
+    .await;
+
+  Ok(StatusCode::NO_CONTENT)
+}
+
+/// Pickup slots open for pre-orders
+#[utoipa::path(
+  get,
+  path = "/api/shops/{shop_id}/pickup-slots",
+  params(
+    ("shop_id" = Uuid, Path, description = "Shop id"),
+    ListPickupSlotsQuery,
+  ),
+  responses(
+    (status = StatusCode::OK, description = "Slots within the range, empty if the shop takes no pre-orders", body = [PickupSlotResponse]),
+    (status = StatusCode::BAD_REQUEST, description = "Range reversed or longer than a week", body = ErrorResponse),
+    (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+    (status = StatusCode::NOT_FOUND, description = "Shop not found", body = ErrorResponse),
+  ),
+  security(
+    ("session_cookie" = [])
+  )
+)]
+pub async fn list_pickup_slots(
+  State(state): State<AppState>,
+  _authz: Authz,
+  Path(shop_id): Path<ShopId>,
+  Query(query): Query<ListPickupSlotsQuery>,
+) -> AppResult<Json<Vec<PickupSlotResponse>>> {
+  let from = query.from.unwrap_or_else(Utc::now);
+  let to = query.to.unwrap_or(from + Duration::days(1));
+  if to <= from || to - from > Duration::weeks(1) {
+    return Err(
+      AppError::Validation("`to` must be after `from` and at most a week later".to_string()).into(),
+    );
+  }
+
+  let shop = state
+    .shop_service
+    .get_by_id(shop_id)
+    .await?
+    .ok_or(AppError::NotFound)?;
+
+  let slots = state.order_service.available_slots(&shop, from, to).await?;
+
+  Ok(Json(slots.into_iter().map(Into::into).collect()))
+}
+
+#[derive(OpenApi)]
+#[openapi(
+  paths(
+    add_member,
+    remove_member,
+    list_members,
+    create_webhook,
+    list_webhooks,
+    remove_webhook,
+    list_pickup_slots,
+    set_pickup_slots,
+    clear_pickup_slots,
+  ),
+  components(schemas(
+    AddShopMemberRequest,
+    ShopMemberResponse,
+    PickupSlotsRequest,
+    PickupSlotsResponse,
+    PickupSlotResponse,
+  ))
+)]
+struct Api;
+
+pub fn openapi() -> utoipa::openapi::OpenApi {
+  Api::openapi()
+}
+
+pub fn router() -> Routes {
+  Routes::new()
+    .get(
+      "/:shop_id/members",
+      list_members,
+      Guard::OwnerOr(&[Permission::ManageShopMembers]),
+    )
+    .post(
+      "/:shop_id/members",
+      add_member,
+      Guard::OwnerOr(&[Permission::ManageShopMembers]),
+    )
+    .delete(
+      "/:shop_id/members/:user_id",
+      remove_member,
+      Guard::OwnerOr(&[Permission::ManageShopMembers]),
+    )
+    .get(
+      "/:shop_id/webhooks",
+      list_webhooks,
+      Guard::OwnerOr(&[Permission::ManageWebhooks]),
+    )
+    .post(
+      "/:shop_id/webhooks",
+      create_webhook,
+      Guard::OwnerOr(&[Permission::ManageWebhooks]),
+    )
+    .delete(
+      "/:shop_id/webhooks/:webhook_id",
+      remove_webhook,
+      Guard::OwnerOr(&[Permission::ManageWebhooks]),
+    )
+    .get(
+      "/:shop_id/pickup-slots",
+      list_pickup_slots,
+      Guard::Authenticated,
+    )
+    .put(
+      "/:shop_id/pickup-slots",
+      set_pickup_slots,
+      Guard::OwnerOr(&[Permission::ConfigureSettings]),
+    )
+    .delete(
+      "/:shop_id/pickup-slots",
+      clear_pickup_slots,
+      Guard::OwnerOr(&[Permission::ConfigureSettings]),
+    )
+}
@@ -0,0 +1,319 @@
+use std::{convert::Infallible, time::Duration};
+
+use crate::{
+  error::AppResult,
+  extractor::Authz,
+  middleware::locale::{current_currency, current_locale, in_locale},
+  models::{
+    BalanceStreamEvent, PollQuery, PollResponse, PolledEvent, StreamQuery, TransactionStreamEvent,
+  },
+  route_permissions::Guard,
+  routes::Routes,
+};
+use application::{error::AppError, services::WalletService, state::AppState};
+use axum::{
+  extract::{Query, State},
+  response::sse::{Event, KeepAlive, Sse},
+  Json,
+};
+use domain::{ActorId, Currency, Locale, Permission, TransactionEventData, UserId, WalletId};
+use futures_util::{stream, Stream, StreamExt};
+use tokio::{
+  sync::broadcast::{self, error::RecvError},
+  time::{timeout_at, Instant},
+};
+use utoipa::OpenApi;
+
+/// How long a poll waits for something to happen by default
+const DEFAULT_POLL_WAIT: Duration = Duration::from_secs(25);
+/// Longest a poll may wait, below common proxy timeouts
+const MAX_POLL_WAIT: Duration = Duration::from_secs(30);
+/// Transactions looked at per query while catching up
+const POLL_BATCH: i64 = 100;
+/// Most queries one poll makes while catching up before it hands back the
+/// cursor it reached
+const MAX_POLL_BATCHES: usize = 10;
+
+/// Live balances and new transactions as server-sent events, for POS
+/// displays and customer apps that would otherwise poll.
+///
+/// Every transaction touching a wallet the caller may read is pushed as a
+/// `transaction` event, followed by a `balance` event per such wallet. Those
+/// are the caller's own wallets, or every wallet with permission to read
+/// transactions.
+///
+/// When the caller is signed out everywhere, for instance because their role
+/// changed, a `logout` event is sent and the stream ends.
+#[utoipa::path(
+  get,
+  path = "/api/stream",
+  params(StreamQuery),
+  responses(
+    (status = StatusCode::OK, description = "Stream of `transaction`, `balance` and `logout` events", body = TransactionStreamEvent, content_type = "text/event-stream"),
+    (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+    (status = StatusCode::FORBIDDEN, description = "Neither the wallet's owner, allowed to read transactions, nor a guest's wallet and allowed to read guest details", body = ErrorResponse),
+    (status = StatusCode::NOT_FOUND, description = "Wallet not found", body = ErrorResponse),
+  ),
+  security(
+    ("session_cookie" = [])
+  )
+)]
+pub async fn stream_updates(
+  State(state): State<AppState>,
+  authz: Authz,
+  Query(query): Query<StreamQuery>,
+) -> AppResult<Sse<impl Stream<Item = Result<Event, Infallible>>>> {
+  let scope = WalletScope::new(&state, &authz, query.wallet_id).await?;
+  let transactions = state.event_bus.subscribe_transactions();
+  let logouts = state.event_bus.subscribe_logouts();
+
+  Ok(
+    Sse::new(wallet_events(
+      state.wallet_service.clone(),
+      scope,
+      current_locale(),
+      current_currency(),
+      transactions,
+      logouts,
+    ))
+    .keep_alive(KeepAlive::default()),
+  )
+}
+
+/// The wallets a stream reports on.
+#[derive(Clone, Copy)]
+struct WalletScope {
+  user_id: UserId,
+  actor_id: ActorId,
+  /// Every wallet, not only the caller's own
+  read_all: bool,
+  /// Only this wallet, already checked to be readable
+  wallet_id: Option<WalletId>,
+}
+
+impl WalletScope {
+  /// Checks the caller may read `wallet_id`, if given.
+  async fn new(state: &AppState, authz: &Authz, wallet_id: Option<WalletId>) -> AppResult<Self> {
+    if let Some(wallet_id) = wallet_id {
+      let wallet = state
+        .wallet_service
+        .get_details(wallet_id)
+        .await?
+        .ok_or(AppError::NotFound)?;
+
+      if !authz.has_on_wallet_details(&wallet, Permission::ReadTransactions) {
+        return Err(AppError::Authorization.into());
+      }
+    }
+
+    Ok(Self {
+      user_id: authz.0.id,
+      actor_id: authz.0.actor_id,
+      read_all: authz.has(Permission::ReadTransactions),
+      wallet_id,
+    })
+  }
+
+  /// The wallet and owner to read transactions of, `None` for any.
+  fn filter(&self) -> (Option<WalletId>, Option<ActorId>) {
+    match self.wallet_id {
+      Some(wallet_id) => (Some(wallet_id), None),
+      None if self.read_all => (None, None),
+      None => (None, Some(self.actor_id)),
+    }
+  }
+
+  /// The wallets of `transaction` in scope, with their current balance.
+  async fn balances(
+    &self,
+    wallet_service: &WalletService,
+    transaction: &TransactionEventData,
+  ) -> Result<Vec<BalanceStreamEvent>, AppError> {
+    let mut wallet_ids = vec![transaction.source_wallet_id];
+    if transaction.destination_wallet_id != transaction.source_wallet_id {
+      wallet_ids.push(transaction.destination_wallet_id);
+    }
+    if let Some(wallet_id) = self.wallet_id {
+      wallet_ids.retain(|id| *id == wallet_id);
+    }
+
+    let mut balances = Vec::new();
+    for wallet_id in wallet_ids {
+      let Some(details) = wallet_service.get_details(wallet_id).await? else {
+        continue;
+      };
+
+      if self.wallet_id.is_some() || self.read_all || details.wallet.owner == Some(self.actor_id) {
+        balances.push((details, transaction.transaction_id).into());
+      }
+    }
+
+    Ok(balances)
+  }
+}
+
+/// Events for every transaction in scope, with amounts formatted in `locale`,
+/// until the caller is signed out.
+fn wallet_events(
+  wallet_service: WalletService,
+  scope: WalletScope,
+  locale: Locale,
+  currency: Currency,
+  transactions: broadcast::Receiver<TransactionEventData>,
+  logouts: broadcast::Receiver<UserId>,
+) -> impl Stream<Item = Result<Event, Infallible>> {
+  stream::unfold(Some((transactions, logouts)), move |receivers| {
+    let wallet_service = wallet_service.clone();
+    async move {
+      let (mut transactions, mut logouts) = receivers?;
+      loop {
+        let transaction = tokio::select! {
+          received = transactions.recv() => match received {
+            Ok(transaction) => transaction,
+            Err(RecvError::Lagged(_)) => continue,
+            Err(RecvError::Closed) => return None,
+          },
+          received = logouts.recv() => match received {
+            Ok(user_id) if user_id == scope.user_id => {
+              return Some((vec![Event::default().event("logout").data("{}")], None));
+            }
+            Ok(_) | Err(RecvError::Lagged(_)) => continue,
+            Err(RecvError::Closed) => return None,
+          },
+        };
+
+        let balances = match scope.balances(&wallet_service, &transaction).await {
+          Ok(balances) if balances.is_empty() => continue,
+          Ok(balances) => balances,
+          Err(e) => {
+            tracing::warn!("Failed to stream a transaction: {}", e);
+            continue;
+          }
+        };
+
+        let events = in_locale(locale, currency, || {
+          let mut events = vec![Event::default()
+            .event("transaction")
+            .json_data(TransactionStreamEvent::from(transaction))
+            .expect("transaction event serializes")];
+          events.extend(balances.into_iter().map(|balance| {
+            Event::default()
+              .event("balance")
+              .json_data(balance)
+              .expect("balance event serializes")
+          }));
+          events
+        });
+
+        return Some((events, Some((transactions, logouts))));
+      }
+    }
+  })
+  .flat_map(|events| stream::iter(events.into_iter().map(Ok)))
+}
+
+/// The updates of `GET /api/stream` for clients that cannot keep a stream
+/// open, such as old kiosk browsers.
+///
+/// Returns the events after the `since` cursor right away, or waits up to
+/// `wait` seconds for the next ones. Passing the returned cursor to the next
+/// request picks up where this one left off, so nothing is missed between
+/// polls. A client far behind may get no events but a later cursor, and
+/// should poll again right away. A cursor no event has is refused rather
+/// than waited on.
+#[utoipa::path(
+  get,
+  path = "/api/events/poll",
+  params(PollQuery),
+  responses(
+    (status = StatusCode::OK, description = "Events after the cursor, possibly none", body = PollResponse),
+    (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+    (status = StatusCode::FORBIDDEN, description = "Neither the wallet's owner, allowed to read transactions, nor a guest's wallet and allowed to read guest details", body = ErrorResponse),
+    (status = StatusCode::NOT_FOUND, description = "Wallet not found", body = ErrorResponse),
+    (status = StatusCode::GONE, description = "No event has the cursor", body = ErrorResponse),
+  ),
+  security(
+    ("session_cookie" = [])
+  )
+)]
+pub async fn poll_updates(
+  State(state): State<AppState>,
+  authz: Authz,
+  Query(query): Query<PollQuery>,
+) -> AppResult<Json<PollResponse>> {
+  let scope = WalletScope::new(&state, &authz, query.wallet_id).await?;
+  let wait = query
+    .wait
+    .map_or(DEFAULT_POLL_WAIT, Duration::from_secs)
+    .min(MAX_POLL_WAIT);
+  let deadline = Instant::now() + wait;
+
+  // Subscribe before reading so no transaction slips in between
+  let mut transactions = state.event_bus.subscribe_transactions();
+  let mut cursor = match query.since {
+    Some(since) => since,
+    None => state.domain_event_service.latest_sequence().await?,
+  };
+
+  let (wallet_id, owner) = scope.filter();
+  let mut events = Vec::new();
+  let mut batches = 0;
+  loop {
+    let page = state
+      .domain_event_service
+      .transactions_after(cursor, POLL_BATCH, wallet_id, owner)
+      .await?;
+    cursor = page.cursor;
+    batches += 1;
+
+    for transaction in page.transactions {
+      let balances = scope.balances(&state.wallet_service, &transaction).await?;
+      if !balances.is_empty() {
+        events.push(PolledEvent::Transaction(transaction.into()));
+        events.extend(balances.into_iter().map(PolledEvent::Balance));
+      }
+    }
+
+    if !events.is_empty() {
+      break;
+    }
+    if !page.exhausted {
+      // Far behind: hand back how far this poll got rather than reading on
+      if batches >= MAX_POLL_BATCHES || Instant::now() >= deadline {
+        break;
+      }
+      continue;
+    }
+    match timeout_at(deadline, transactions.recv()).await {
+      Ok(Ok(_) | Err(RecvError::Lagged(_))) => {}
+      Ok(Err(RecvError::Closed)) | Err(_) => break,
+    }
+  }
+
+  Ok(Json(PollResponse { cursor, events }))
+}
+
+#[derive(OpenApi)]
+#[openapi(
+  paths(stream_updates, poll_updates,),
+  components(schemas(TransactionStreamEvent, BalanceStreamEvent, PollResponse, PolledEvent,))
+)]
+struct Api;
+
+pub fn openapi() -> utoipa::openapi::OpenApi {
+  Api::openapi()
+}
+
+pub fn router() -> Routes {
+  Routes::new()
+    .get(
+      "/stream",
+      stream_updates,
+      Guard::OwnerOrGuestReaderOr(&[Permission::ReadTransactions]),
+    )
+    .get(
+      "/events/poll",
+      poll_updates,
+      Guard::OwnerOrGuestReaderOr(&[Permission::ReadTransactions]),
+    )
+}
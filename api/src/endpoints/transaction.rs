@@ -0,0 +1,176 @@
+use crate::{
+  error::AppResult,
+  export::{csv_text, ExportMask},
+  extractor::{Audit, Authz, ValidatedJson},
+  models::{ExportFormat, ExportQuery, RefundRequest, TransactionResponse},
+  route_permissions::Guard,
+  routes::Routes,
+};
+use application::state::AppState;
+use axum::{
+  body::Body,
+  extract::{Path, Query, State},
+  http::{header, StatusCode},
+  response::{IntoResponse, Response},
+  Json,
+};
+use domain::{AuditAction, LedgerLine, Permission, TransactionId};
+use futures_util::{stream, StreamExt};
+use utoipa::OpenApi;
+
+const CSV_HEADER: &str = "id,created_at,source_wallet_id,source_label,destination_wallet_id,destination_label,executor_name,amount_cents,description,reversal_of,metadata\r\n";
+
+#[utoipa::path(
+  post,
+  path = "/api/transactions/{transaction_id}/refund",
+  request_body = RefundRequest,
+  params(
+    ("transaction_id" = Uuid, Path, description = "Transaction id")
+  ),
+  responses(
+    (status = StatusCode::CREATED, description = "Compensating transaction created", body = TransactionResponse),
+    (status = StatusCode::BAD_REQUEST, description = "Validation error or transaction is a refund", body = ErrorResponse),
+    (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+    (status = StatusCode::FORBIDDEN, description = "Forbidden", body = ErrorResponse),
+    (status = StatusCode::NOT_FOUND, description = "Transaction not found", body = ErrorResponse),
+    (status = StatusCode::CONFLICT, description = "Transaction already refunded", body = ErrorResponse),
+    (status = StatusCode::UNPROCESSABLE_ENTITY, description = "Insufficient funds", body = ErrorResponse),
+    (status = StatusCode::LOCKED, description = "Wallet is frozen", body = ErrorResponse),
+  ),
+  security(
+    ("session_cookie" = [])
+  )
+)]
+pub async fn refund_transaction(
+  State(state): State<AppState>,
+  authz: Authz,
+  audit: Audit,
+  Path(transaction_id): Path<TransactionId>,
+  ValidatedJson(payload): ValidatedJson<RefundRequest>,
+) -> AppResult<(StatusCode, Json<TransactionResponse>)> {
+  authz.require(Permission::RefundTransaction)?;
+
+  let refund = state
+    .transaction_service
+    .refund(authz.0.actor_id, transaction_id, payload.description)
+    .await?;
+
+  audit
+    .record(
+      Some(authz.0.actor_id),
+      AuditAction::TransactionRefunded,
+      transaction_id,
+      Some(serde_json::json!({ "refund_id": refund.id })),
+    )
+    .await;
+
+  Ok((StatusCode::CREATED, Json(refund.into())))
+}
+
+/// Export the ledger
+///
+/// Every transaction as CSV. Callers not allowed to read user details get
+/// the executor's actor id instead of their name.
+#[utoipa::path(
+  get,
+  path = "/api/transactions/export",
+  params(ExportQuery),
+  responses(
+    (status = StatusCode::OK, description = "Every transaction, oldest first", content_type = "text/csv", body = String),
+    (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+    (status = StatusCode::FORBIDDEN, description = "Forbidden", body = ErrorResponse),
+  ),
+  security(
+    ("session_cookie" = [])
+  )
+)]
+pub async fn export_transactions(
+  State(state): State<AppState>,
+  authz: Authz,
+  Query(query): Query<ExportQuery>,
+) -> AppResult<Response> {
+  authz.require(Permission::ExportData)?;
+
+  let ExportFormat::Csv = query.format.unwrap_or_default();
+  let mask = ExportMask::for_caller(&authz);
+
+  // Headers are already sent once rows stream, so a failing batch can only
+  // cut the download short
+  let rows = state.transaction_service.export_ledger().map(move |batch| {
+    batch
+      .map(|lines| {
+        lines
+          .iter()
+          .map(|line| csv_row(line, mask))
+          .collect::<String>()
+      })
+      .map_err(|err| {
+        tracing::error!("Transaction export aborted: {err}");
+        std::io::Error::other(err.to_string())
+      })
+  });
+  let body = stream::once(async { Ok(CSV_HEADER.to_string()) }).chain(rows);
+
+  Ok(
+    (
+      [
+        (header::CONTENT_TYPE, "text/csv; charset=utf-8"),
+        (
+          header::CONTENT_DISPOSITION,
+          "attachment; filename=\"transactions.csv\"",
+        ),
+      ],
+      Body::from_stream(body),
+    )
+      .into_response(),
+  )
+}
+
+fn csv_row(line: &LedgerLine, mask: ExportMask) -> String {
+  let tx = &line.transaction;
+  let fields = [
+    tx.id.to_string(),
+    tx.created_at.to_rfc3339(),
+    tx.source.to_string(),
+    csv_text(line.source_label.as_ref().map(|l| l.to_string())),
+    tx.destination.to_string(),
+    csv_text(line.destination_label.as_ref().map(|l| l.to_string())),
+    csv_text(mask.user_name(tx.executor, line.executor_name.clone())),
+    tx.amount.as_minor().to_string(),
+    csv_text(tx.description.clone()),
+    tx.reversal_of.map(|id| id.to_string()).unwrap_or_default(),
+    csv_text(
+      (!tx.metadata.is_empty())
+        .then(|| serde_json::to_string(&tx.metadata).expect("metadata serializes")),
+    ),
+  ];
+
+  let mut row = fields.join(",");
+  row.push_str("\r\n");
+  row
+}
+
+#[derive(OpenApi)]
+#[openapi(
+  paths(refund_transaction, export_transactions,),
+  components(schemas(RefundRequest,))
+)]
+struct Api;
+
+pub fn openapi() -> utoipa::openapi::OpenApi {
+  Api::openapi()
+}
+
+pub fn router() -> Routes {
+  Routes::new()
+    .get(
+      "/export",
+      export_transactions,
+      Guard::All(&[Permission::ExportData]),
+    )
+    .post(
+      "/:transaction_id/refund",
+      refund_transaction,
+      Guard::All(&[Permission::RefundTransaction]),
+    )
+}
@@ -0,0 +1,87 @@
+use crate::{
+  error::AppResult,
+  extractor::{Audit, Authn, DeviceProof, ValidatedJson},
+  models::TransferRequest,
+  route_permissions::Guard,
+  routes::Routes,
+};
+use application::state::AppState;
+use axum::{extract::State, http::StatusCode};
+use domain::{AuditAction, Email};
+use serde_json::json;
+use utoipa::OpenApi;
+
+/// Send money to another user
+///
+/// Moves the amount from the caller's wallet into the wallet of the user
+/// registered with the given email. Single transfers are capped by the
+/// server's configured limit.
+///
+/// The answer is the same whether or not anyone is registered with the
+/// email; nothing is sent if nobody is, or if their wallet is in another
+/// currency.
+#[utoipa::path(
+  post,
+  path = "/api/transfers",
+  request_body = TransferRequest,
+  responses(
+    (status = StatusCode::ACCEPTED, description = "Money sent if the email belongs to a user"),
+    (status = StatusCode::BAD_REQUEST, description = "Validation error, limit exceeded or transfer to oneself", body = ErrorResponse),
+    (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+    (status = StatusCode::FORBIDDEN, description = "Device signature required", body = ErrorResponse),
+    (status = StatusCode::NOT_FOUND, description = "The caller has no wallet", body = ErrorResponse),
+    (status = StatusCode::UNPROCESSABLE_ENTITY, description = "Insufficient funds", body = ErrorResponse),
+    (status = StatusCode::LOCKED, description = "Wallet is frozen", body = ErrorResponse),
+    (status = StatusCode::SERVICE_UNAVAILABLE, description = "Transfers are switched off", body = ErrorResponse),
+  ),
+  security(
+    ("session_cookie" = [])
+  )
+)]
+pub async fn send_transfer(
+  State(state): State<AppState>,
+  Authn(user): Authn,
+  _: DeviceProof,
+  audit: Audit,
+  ValidatedJson(payload): ValidatedJson<TransferRequest>,
+) -> AppResult<StatusCode> {
+  let sent = state
+    .transfer_service
+    .send(
+      &user,
+      &Email::new(payload.recipient_email),
+      payload.amount,
+      payload.description,
+      payload.notify_recipient,
+    )
+    .await?;
+
+  if let Some(transaction) = sent {
+    audit
+      .record(
+        Some(user.actor_id),
+        AuditAction::TransferSent,
+        transaction.id,
+        Some(json!({
+          "source_wallet_id": transaction.source,
+          "destination_wallet_id": transaction.destination,
+          "amount_cents": transaction.amount.as_minor(),
+        })),
+      )
+      .await;
+  }
+
+  Ok(StatusCode::ACCEPTED)
+}
+
+#[derive(OpenApi)]
+#[openapi(paths(send_transfer,), components(schemas(TransferRequest,)))]
+struct Api;
+
+pub fn openapi() -> utoipa::openapi::OpenApi {
+  Api::openapi()
+}
+
+pub fn router() -> Routes {
+  Routes::new().post("/", send_transfer, Guard::Authenticated)
+}
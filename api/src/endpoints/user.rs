@@ -1,30 +1,656 @@
-use crate::{error::AppResult, extractor::Authz, models::UserResponse};
-use application::state::AppState;
-use axum::{extract::State, routing::get, Json, Router};
-use domain::Permission;
+use crate::{
+  conditional::{Conditional, ETag, IfNoneMatch},
+  error::AppResult,
+  extractor::{Audit, Authz, StepUp, ValidatedJson},
+  models::{
+    AccountNoteRequest, AccountNoteResponse, ChangeRoleRequest, ListUsersQuery,
+    PaginatedUserResponse, PasswordSetupRequest, Redact, UpdatePermissionsRequest,
+    UpdateUserRequest, UpdateUserResponse, UserPermissionsResponse, UserResponse,
+  },
+  route_permissions::{Access, Guard},
+  routes::Routes,
+};
+use application::{
+  error::AppError,
+  services::{note::NoteSubject, user::UserFilter},
+  state::AppState,
+};
+use axum::{
+  extract::{Path, Query, State},
+  http::StatusCode,
+  Json,
+};
+use domain::{types::PageRequest, AuditAction, Email, Permission, RawPassword, UserId};
+use serde_json::json;
+use utoipa::OpenApi;
 
-/// List all users
+/// List users
 #[utoipa::path(
     get,
     path = "/api/users",
+    params(
+        ListUsersQuery,
+        ("If-None-Match" = Option<String>, Header, description = "ETag of the copy the client has")
+    ),
     responses(
-        (status = StatusCode::OK, description = "List of all users", body = Vec<UserResponse>),
+        (status = StatusCode::OK, description = "Page of users; emails are omitted without ReadUserDetails", body = PaginatedUserResponse),
+        (status = StatusCode::NOT_MODIFIED, description = "The client's copy is still current"),
         (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
         (status = StatusCode::FORBIDDEN, description = "Forbidden", body = ErrorResponse),
+    ),
+    security(
+        ("session_cookie" = [])
     )
 )]
 pub async fn list_users(
   State(state): State<AppState>,
   authz: Authz,
-) -> AppResult<Json<Vec<UserResponse>>> {
-  authz.require(Permission::ReadUserDetails)?;
+  Query(query): Query<ListUsersQuery>,
+  if_none_match: IfNoneMatch,
+) -> AppResult<Conditional<Json<PaginatedUserResponse>>> {
+  authz.require_any(&[Permission::ListUsers, Permission::ReadUserDetails])?;
 
-  let users = state.user_service.get_all().await?;
-  let response: Vec<UserResponse> = users.into_iter().map(Into::into).collect();
+  let filter = UserFilter {
+    role: query.role,
+    search: query.search.filter(|s| !s.trim().is_empty()),
+    sort_by: query.sort_by.unwrap_or_default(),
+    sort_order: query.sort_order.unwrap_or_default(),
+  };
+  let page = PageRequest::new(query.page, query.per_page);
 
-  Ok(Json(response))
+  let version = state.user_service.list_version(&filter).await?;
+  let etag = ETag::for_listing(version, &authz);
+  if if_none_match.matches(&etag) {
+    return Ok(Conditional::NotModified(etag));
+  }
+
+  let users = state
+    .user_service
+    .list(filter, page)
+    .await?
+    .map(|user| UserResponse::from(user).redact(&authz));
+
+  Ok(Conditional::Modified(etag, Json(users.into())))
+}
+
+/// Update a user's profile
+///
+/// Users may update their own; changing anyone else requires ManageUsers. A
+/// new email address only replaces the current one once confirmed with the
+/// token emailed to it.
+#[utoipa::path(
+    patch,
+    path = "/api/users/{user_id}",
+    request_body = UpdateUserRequest,
+    params(
+        ("user_id" = Uuid, Path, description = "User id")
+    ),
+    responses(
+        (status = StatusCode::OK, description = "Profile updated", body = UpdateUserResponse),
+        (status = StatusCode::BAD_REQUEST, description = "Validation error", body = ErrorResponse),
+        (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+        (status = StatusCode::FORBIDDEN, description = "Forbidden, or role above the caller's", body = ErrorResponse),
+        (status = StatusCode::NOT_FOUND, description = "User not found", body = ErrorResponse),
+        (status = StatusCode::CONFLICT, description = "Email address is already in use", body = ErrorResponse),
+    ),
+    security(
+        ("session_cookie" = [])
+    )
+)]
+pub async fn update_user(
+  State(state): State<AppState>,
+  authz: Authz,
+  audit: Audit,
+  Path(user_id): Path<UserId>,
+  ValidatedJson(payload): ValidatedJson<UpdateUserRequest>,
+) -> AppResult<Json<UpdateUserResponse>> {
+  if user_id != authz.0.id {
+    authz.require(Permission::ManageUsers)?;
+
+    let user = state
+      .user_service
+      .get_by_id(user_id)
+      .await?
+      .ok_or(AppError::NotFound)?;
+    authz.can_assign(user.role)?;
+  }
+
+  let details = json!({
+    "first_name": payload.first_name.is_some(),
+    "last_name": payload.last_name.is_some(),
+    "locale": payload.locale.is_some(),
+    "email": payload.email.is_some(),
+  });
+
+  let (user, change) = state
+    .user_service
+    .update_profile(
+      user_id,
+      payload.first_name,
+      payload.last_name,
+      payload.locale,
+      payload.email.map(Email::new),
+    )
+    .await?;
+
+  audit
+    .record(
+      Some(authz.0.actor_id),
+      AuditAction::UserUpdated,
+      user.id,
+      Some(details),
+    )
+    .await;
+
+  Ok(Json((user, change).into()))
+}
+
+/// Change a user's role
+///
+/// The caller must be allowed to assign both the user's current role and the
+/// new one. The last owner keeps their role. A user whose role changes is
+/// signed out everywhere.
+#[utoipa::path(
+    patch,
+    path = "/api/users/{user_id}/role",
+    request_body = ChangeRoleRequest,
+    params(
+        ("user_id" = Uuid, Path, description = "User id")
+    ),
+    responses(
+        (status = StatusCode::OK, description = "Role changed", body = UserResponse),
+        (status = StatusCode::BAD_REQUEST, description = "Validation error", body = ErrorResponse),
+        (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+        (status = StatusCode::FORBIDDEN, description = "Forbidden, or either role above the caller's", body = ErrorResponse),
+        (status = StatusCode::NOT_FOUND, description = "User not found", body = ErrorResponse),
+        (status = StatusCode::CONFLICT, description = "User is the last owner", body = ErrorResponse),
+    ),
+    security(
+        ("session_cookie" = [])
+    )
+)]
+pub async fn change_role(
+  State(state): State<AppState>,
+  authz: Authz,
+  audit: Audit,
+  Path(user_id): Path<UserId>,
+  ValidatedJson(payload): ValidatedJson<ChangeRoleRequest>,
+) -> AppResult<Json<UserResponse>> {
+  authz.require(Permission::ManageUsers)?;
+
+  let user = state
+    .user_service
+    .get_by_id(user_id)
+    .await?
+    .ok_or(AppError::NotFound)?;
+  authz.can_assign(user.role)?;
+  authz.can_assign(payload.role)?;
+
+  let (previous, user) = state
+    .user_service
+    .change_role(user.id, payload.role)
+    .await?;
+
+  audit
+    .record(
+      Some(authz.0.actor_id),
+      AuditAction::RoleChanged,
+      user.id,
+      Some(json!({ "from": previous, "to": user.role })),
+    )
+    .await;
+
+  Ok(Json(user.into()))
+}
+
+/// Confirm a new email address
+///
+/// Takes the token emailed to the address and makes it the user's email.
+#[utoipa::path(
+    post,
+    path = "/api/users/email-confirmations/{token}",
+    params(
+        ("token" = String, Path, description = "Token emailed to the new address")
+    ),
+    responses(
+        (status = StatusCode::OK, description = "Email address changed", body = UserResponse),
+        (status = StatusCode::BAD_REQUEST, description = "Token expired", body = ErrorResponse),
+        (status = StatusCode::NOT_FOUND, description = "Unknown token", body = ErrorResponse),
+        (status = StatusCode::CONFLICT, description = "Email address is already in use", body = ErrorResponse),
+    ),
+)]
+pub async fn confirm_email_change(
+  State(state): State<AppState>,
+  audit: Audit,
+  Path(token): Path<String>,
+) -> AppResult<Json<UserResponse>> {
+  let user = state.user_service.confirm_email_change(&token).await?;
+
+  audit
+    .record(
+      Some(user.actor_id),
+      AuditAction::EmailChanged,
+      user.id,
+      None,
+    )
+    .await;
+
+  Ok(Json(user.into()))
+}
+
+/// Choose the password of a new account
+///
+/// Takes the token emailed to a user whose account was created for them,
+/// e.g. by a member import, and sets their password.
+#[utoipa::path(
+    post,
+    path = "/api/users/password-setups/{token}",
+    request_body = PasswordSetupRequest,
+    params(
+        ("token" = String, Path, description = "Token emailed to the user")
+    ),
+    responses(
+        (status = StatusCode::OK, description = "Password set", body = UserResponse),
+        (status = StatusCode::BAD_REQUEST, description = "Validation error or token expired", body = ErrorResponse),
+        (status = StatusCode::NOT_FOUND, description = "Unknown token", body = ErrorResponse),
+    ),
+)]
+pub async fn set_up_password(
+  State(state): State<AppState>,
+  audit: Audit,
+  Path(token): Path<String>,
+  ValidatedJson(payload): ValidatedJson<PasswordSetupRequest>,
+) -> AppResult<Json<UserResponse>> {
+  let user = state
+    .user_service
+    .set_up_password(&token, RawPassword::new(payload.password))
+    .await?;
+
+  audit
+    .record(
+      Some(user.actor_id),
+      AuditAction::PasswordSetUp,
+      user.id,
+      None,
+    )
+    .await;
+
+  Ok(Json(user.into()))
+}
+
+/// Get a user's permission overrides
+#[utoipa::path(
+    get,
+    path = "/api/users/{user_id}/permissions",
+    params(
+        ("user_id" = Uuid, Path, description = "User id")
+    ),
+    responses(
+        (status = StatusCode::OK, description = "Grants, revocations and resulting permissions", body = UserPermissionsResponse),
+        (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+        (status = StatusCode::FORBIDDEN, description = "Forbidden", body = ErrorResponse),
+        (status = StatusCode::NOT_FOUND, description = "User not found", body = ErrorResponse),
+    ),
+    security(
+        ("session_cookie" = [])
+    )
+)]
+pub async fn get_permissions(
+  State(state): State<AppState>,
+  authz: Authz,
+  Path(user_id): Path<UserId>,
+) -> AppResult<Json<UserPermissionsResponse>> {
+  authz.require(Permission::ManagePermissions)?;
+
+  let user = state
+    .user_service
+    .get_by_id(user_id)
+    .await?
+    .ok_or(AppError::NotFound)?;
+  let overrides = state.user_service.permission_overrides(user.id).await?;
+
+  Ok(Json((user, overrides).into()))
+}
+
+/// Replace a user's permission overrides
+///
+/// Only users whose role the caller may assign can be changed, and only
+/// permissions the caller holds can be granted. A user whose overrides
+/// change is signed out everywhere.
+#[utoipa::path(
+    put,
+    path = "/api/users/{user_id}/permissions",
+    request_body = UpdatePermissionsRequest,
+    params(
+        ("user_id" = Uuid, Path, description = "User id")
+    ),
+    responses(
+        (status = StatusCode::OK, description = "Overrides replaced", body = UserPermissionsResponse),
+        (status = StatusCode::BAD_REQUEST, description = "Permission both granted and revoked, or own permissions", body = ErrorResponse),
+        (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+        (status = StatusCode::FORBIDDEN, description = "Forbidden, user's role not assignable by the caller, or permission granted the caller does not hold", body = ErrorResponse),
+        (status = StatusCode::NOT_FOUND, description = "User not found", body = ErrorResponse),
+    ),
+    security(
+        ("session_cookie" = [])
+    )
+)]
+pub async fn update_permissions(
+  State(state): State<AppState>,
+  authz: Authz,
+  audit: Audit,
+  Path(user_id): Path<UserId>,
+  ValidatedJson(payload): ValidatedJson<UpdatePermissionsRequest>,
+) -> AppResult<Json<UserPermissionsResponse>> {
+  authz.require(Permission::ManagePermissions)?;
+
+  // Keeps an owner from locking themselves out
+  if user_id == authz.0.id {
+    return Err(AppError::BadRequest("Cannot change your own permissions".to_string()).into());
+  }
+
+  if payload
+    .grants
+    .iter()
+    .any(|p| payload.revocations.contains(p))
+  {
+    return Err(
+      AppError::BadRequest("A permission cannot be both granted and revoked".to_string()).into(),
+    );
+  }
+
+  let user = state
+    .user_service
+    .get_by_id(user_id)
+    .await?
+    .ok_or(AppError::NotFound)?;
+  authz.can_assign(user.role)?;
+  // Nobody hands out more than they hold themselves
+  authz.require_all(&payload.grants)?;
+
+  let details = json!({
+    "grants": payload.grants,
+    "revocations": payload.revocations,
+  });
+
+  let overrides = state
+    .user_service
+    .set_permission_overrides(user.id, payload.into_overrides())
+    .await?;
+
+  audit
+    .record(
+      Some(authz.0.actor_id),
+      AuditAction::PermissionsChanged,
+      user.id,
+      Some(details),
+    )
+    .await;
+
+  Ok(Json((user, overrides).into()))
+}
+
+/// Unlock an account locked by repeated failed logins
+#[utoipa::path(
+    post,
+    path = "/api/users/{user_id}/unlock",
+    params(
+        ("user_id" = Uuid, Path, description = "User id")
+    ),
+    responses(
+        (status = StatusCode::NO_CONTENT, description = "Account unlocked; earlier failed logins no longer count"),
+        (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+        (status = StatusCode::FORBIDDEN, description = "Forbidden", body = ErrorResponse),
+        (status = StatusCode::NOT_FOUND, description = "User not found", body = ErrorResponse),
+    ),
+    security(
+        ("session_cookie" = [])
+    )
+)]
+pub async fn unlock_user(
+  State(state): State<AppState>,
+  authz: Authz,
+  audit: Audit,
+  Path(user_id): Path<UserId>,
+) -> AppResult<StatusCode> {
+  authz.require(Permission::UnlockAccounts)?;
+
+  state.auth_service.unlock(user_id).await?;
+
+  audit
+    .record(
+      Some(authz.0.actor_id),
+      AuditAction::AccountUnlocked,
+      user_id,
+      None,
+    )
+    .await;
+
+  Ok(StatusCode::NO_CONTENT)
 }
 
-pub fn router() -> Router<AppState> {
-  Router::new().route("/", get(list_users))
+/// Delete a user
+///
+/// Anonymizes the account rather than removing it, so the ledger and audit
+/// log keep their references: name and email are replaced, and sessions,
+/// permission overrides, shop memberships, favorites and the bank account are
+/// removed. The user's wallet stays.
+#[utoipa::path(
+    delete,
+    path = "/api/users/{user_id}",
+    params(
+        ("user_id" = Uuid, Path, description = "User id")
+    ),
+    responses(
+        (status = StatusCode::NO_CONTENT, description = "User deleted"),
+        (status = StatusCode::BAD_REQUEST, description = "Own account", body = ErrorResponse),
+        (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+        (status = StatusCode::FORBIDDEN, description = "Forbidden, role above the caller's, or password confirmation or device signature required", body = ErrorResponse),
+        (status = StatusCode::NOT_FOUND, description = "User not found", body = ErrorResponse),
+        (status = StatusCode::LOCKED, description = "User's wallet is under legal hold", body = ErrorResponse),
+    ),
+    security(
+        ("session_cookie" = [], "confirmation_token" = [])
+    )
+)]
+pub async fn delete_user(
+  State(state): State<AppState>,
+  authz: Authz,
+  _: StepUp,
+  audit: Audit,
+  Path(user_id): Path<UserId>,
+) -> AppResult<StatusCode> {
+  authz.require(Permission::RemoveUser)?;
+
+  if user_id == authz.0.id {
+    return Err(AppError::BadRequest("Cannot delete your own account".to_string()).into());
+  }
+
+  let user = state
+    .user_service
+    .get_by_id(user_id)
+    .await?
+    .ok_or(AppError::NotFound)?;
+  authz.can_assign(user.role)?;
+
+  state.user_service.delete(user.id).await?;
+
+  audit
+    .record(
+      Some(authz.0.actor_id),
+      AuditAction::UserDeleted,
+      user.id,
+      Some(json!({ "role": user.role })),
+    )
+    .await;
+
+  Ok(StatusCode::NO_CONTENT)
+}
+
+/// List staff notes on a user
+#[utoipa::path(
+    get,
+    path = "/api/users/{user_id}/notes",
+    params(
+        ("user_id" = Uuid, Path, description = "User id")
+    ),
+    responses(
+        (status = StatusCode::OK, description = "Notes, newest first", body = [AccountNoteResponse]),
+        (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+        (status = StatusCode::FORBIDDEN, description = "Forbidden", body = ErrorResponse),
+        (status = StatusCode::NOT_FOUND, description = "User not found", body = ErrorResponse),
+    ),
+    security(
+        ("session_cookie" = [])
+    )
+)]
+pub async fn list_user_notes(
+  State(state): State<AppState>,
+  authz: Authz,
+  Path(user_id): Path<UserId>,
+) -> AppResult<Json<Vec<AccountNoteResponse>>> {
+  authz.require(Permission::ManageAccountNotes)?;
+
+  let notes = state
+    .account_note_service
+    .list(NoteSubject::User(user_id))
+    .await?;
+
+  Ok(Json(notes.into_iter().map(Into::into).collect()))
+}
+
+/// Add a staff note on a user
+///
+/// For support history only staff allowed to manage notes see.
+#[utoipa::path(
+    post,
+    path = "/api/users/{user_id}/notes",
+    request_body = AccountNoteRequest,
+    params(
+        ("user_id" = Uuid, Path, description = "User id")
+    ),
+    responses(
+        (status = StatusCode::CREATED, description = "Note added", body = AccountNoteResponse),
+        (status = StatusCode::BAD_REQUEST, description = "Validation error", body = ErrorResponse),
+        (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+        (status = StatusCode::FORBIDDEN, description = "Forbidden", body = ErrorResponse),
+        (status = StatusCode::NOT_FOUND, description = "User not found", body = ErrorResponse),
+    ),
+    security(
+        ("session_cookie" = [])
+    )
+)]
+pub async fn add_user_note(
+  State(state): State<AppState>,
+  authz: Authz,
+  audit: Audit,
+  Path(user_id): Path<UserId>,
+  ValidatedJson(payload): ValidatedJson<AccountNoteRequest>,
+) -> AppResult<(StatusCode, Json<AccountNoteResponse>)> {
+  authz.require(Permission::ManageAccountNotes)?;
+
+  let note = state
+    .account_note_service
+    .add(authz.0.actor_id, NoteSubject::User(user_id), payload.body)
+    .await?;
+
+  audit
+    .record(
+      Some(authz.0.actor_id),
+      AuditAction::AccountNoteAdded,
+      user_id,
+      Some(json!({ "note_id": note.id })),
+    )
+    .await;
+
+  Ok((StatusCode::CREATED, Json(note.into())))
+}
+
+#[derive(OpenApi)]
+#[openapi(
+  paths(
+    list_users,
+    get_permissions,
+    update_permissions,
+    unlock_user,
+    list_user_notes,
+    add_user_note,
+    update_user,
+    change_role,
+    confirm_email_change,
+    set_up_password,
+    delete_user,
+  ),
+  components(schemas(
+    PaginatedUserResponse,
+    UpdatePermissionsRequest,
+    UserPermissionsResponse,
+    UpdateUserRequest,
+    UpdateUserResponse,
+    PasswordSetupRequest,
+    ChangeRoleRequest,
+    domain::UserSortField,
+  ))
+)]
+struct Api;
+
+pub fn openapi() -> utoipa::openapi::OpenApi {
+  Api::openapi()
+}
+
+pub fn router() -> Routes {
+  Routes::new()
+    .get(
+      "/",
+      list_users,
+      Guard::Any(&[Permission::ListUsers, Permission::ReadUserDetails]),
+    )
+    .patch(
+      "/:user_id",
+      update_user,
+      Guard::OwnerOr(&[Permission::ManageUsers]),
+    )
+    .delete(
+      "/:user_id",
+      delete_user,
+      Guard::All(&[Permission::RemoveUser]),
+    )
+    .patch(
+      "/:user_id/role",
+      change_role,
+      Guard::All(&[Permission::ManageUsers]),
+    )
+    // The token emailed to the new address is the credential
+    .post(
+      "/email-confirmations/:token",
+      confirm_email_change,
+      Access::Public,
+    )
+    // So is the one emailed to a user whose account was created for them
+    .post("/password-setups/:token", set_up_password, Access::Public)
+    .get(
+      "/:user_id/permissions",
+      get_permissions,
+      Guard::All(&[Permission::ManagePermissions]),
+    )
+    .put(
+      "/:user_id/permissions",
+      update_permissions,
+      Guard::All(&[Permission::ManagePermissions]),
+    )
+    .post(
+      "/:user_id/unlock",
+      unlock_user,
+      Guard::All(&[Permission::UnlockAccounts]),
+    )
+    .get(
+      "/:user_id/notes",
+      list_user_notes,
+      Guard::All(&[Permission::ManageAccountNotes]),
+    )
+    .post(
+      "/:user_id/notes",
+      add_user_note,
+      Guard::All(&[Permission::ManageAccountNotes]),
+    )
 }
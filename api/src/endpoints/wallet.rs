@@ -0,0 +1,840 @@
+use crate::{
+  conditional::{Conditional, ETag, IfNoneMatch},
+  error::AppResult,
+  extractor::{Audit, Authz, DeviceProof, StepUp, ValidatedJson},
+  models::{
+    BudgetEnvelopeRequest, BudgetEnvelopeResponse, LegalHoldEventResponse, LegalHoldRequest,
+    ListWalletsQuery, OnlineTopUpRequest, OnlineTopUpResponse, PaginatedWalletResponse,
+    PayoutRequest, PayoutResponse, Redact, TopUpRequest, TransactionResponse,
+    WalletAppearanceRequest, WalletDetailsResponse, WalletResponse, WithdrawRequest,
+  },
+  route_permissions::Guard,
+  routes::Routes,
+};
+use application::{
+  error::AppError,
+  services::{payout::PayoutDestination, wallet::WalletFilter},
+  state::AppState,
+};
+use axum::{
+  extract::{Path, Query, State},
+  http::StatusCode,
+  Json,
+};
+use domain::{
+  types::PageRequest, AuditAction, BudgetEnvelope, Id, MetadataSource, Permission,
+  TransactionMetadata, Wallet, WalletAppearance, WalletId,
+};
+use serde_json::json;
+use utoipa::OpenApi;
+
+/// List wallets
+///
+/// Oldest first, with each wallet's balance and the user or guest owning it.
+#[utoipa::path(
+  get,
+  path = "/api/wallets",
+  params(
+    ListWalletsQuery,
+    ("If-None-Match" = Option<String>, Header, description = "ETag of the copy the client has")
+  ),
+  responses(
+    (status = StatusCode::OK, description = "Page of wallets; owner contact details are omitted without the matching read permission", body = PaginatedWalletResponse),
+    (status = StatusCode::NOT_MODIFIED, description = "The client's copy is still current"),
+    (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+    (status = StatusCode::FORBIDDEN, description = "Not allowed to read transactions, or to read user details when searching", body = ErrorResponse),
+  ),
+  security(
+    ("session_cookie" = [])
+  )
+)]
+pub async fn list_wallets(
+  State(state): State<AppState>,
+  authz: Authz,
+  Query(query): Query<ListWalletsQuery>,
+  if_none_match: IfNoneMatch,
+) -> AppResult<Conditional<Json<PaginatedWalletResponse>>> {
+  authz.require(Permission::ReadTransactions)?;
+
+  let search = query.search.filter(|s| !s.trim().is_empty());
+  if search.is_some() {
+    // Matches reveal contact details the page itself may leave out
+    authz.require(Permission::ReadUserDetails)?;
+  }
+
+  let filter = WalletFilter {
+    system: query.system,
+    search,
+  };
+  let page = PageRequest::new(query.page, query.per_page);
+
+  let version = state.wallet_service.list_version(&filter).await?;
+  let etag = ETag::for_listing(version, &authz);
+  if if_none_match.matches(&etag) {
+    return Ok(Conditional::NotModified(etag));
+  }
+
+  let wallets = state
+    .wallet_service
+    .list(filter, page)
+    .await?
+    .map(|wallet| WalletDetailsResponse::from(wallet).redact(&authz));
+
+  Ok(Conditional::Modified(etag, Json(wallets.into())))
+}
+
+/// Get a wallet
+///
+/// With its balance and the user or guest owning it. Staff allowed to read
+/// guest details, such as cashiers, read guests' wallets too.
+#[utoipa::path(
+  get,
+  path = "/api/wallets/{wallet_id}",
+  params(
+    ("wallet_id" = Uuid, Path, description = "Wallet id")
+  ),
+  responses(
+    (status = StatusCode::OK, description = "Wallet found", body = WalletDetailsResponse),
+    (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+    (status = StatusCode::FORBIDDEN, description = "Neither the wallet's owner, allowed to read transactions, nor a guest's wallet and allowed to read guest details", body = ErrorResponse),
+    (status = StatusCode::NOT_FOUND, description = "Wallet not found", body = ErrorResponse),
+  ),
+  security(
+    ("session_cookie" = [])
+  )
+)]
+pub async fn get_wallet(
+  State(state): State<AppState>,
+  authz: Authz,
+  Path(wallet_id): Path<WalletId>,
+) -> AppResult<Json<WalletDetailsResponse>> {
+  let wallet = state
+    .wallet_service
+    .get_details(wallet_id)
+    .await?
+    .ok_or(AppError::NotFound)?;
+
+  if wallet.wallet.owner == Some(authz.0.actor_id) {
+    let envelopes = state.budget_service.list(wallet_id).await?;
+    let mut response = WalletDetailsResponse::from(wallet);
+    response.envelopes = Some(envelopes.into_iter().map(Into::into).collect());
+    return Ok(Json(response));
+  }
+  if !authz.has_on_wallet_details(&wallet, Permission::ReadTransactions) {
+    return Err(AppError::Authorization.into());
+  }
+
+  Ok(Json(WalletDetailsResponse::from(wallet).redact(&authz)))
+}
+
+#[utoipa::path(
+  post,
+  path = "/api/wallets/{wallet_id}/topup",
+  request_body = TopUpRequest,
+  params(
+    ("wallet_id" = Uuid, Path, description = "Wallet id")
+  ),
+  responses(
+    (status = StatusCode::CREATED, description = "Cash deposit recorded", body = TransactionResponse),
+    (status = StatusCode::BAD_REQUEST, description = "Validation error or system wallet", body = ErrorResponse),
+    (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+    (status = StatusCode::FORBIDDEN, description = "Forbidden", body = ErrorResponse),
+    (status = StatusCode::NOT_FOUND, description = "Wallet not found", body = ErrorResponse),
+  ),
+  security(
+    ("session_cookie" = [])
+  )
+)]
+pub async fn top_up(
+  State(state): State<AppState>,
+  authz: Authz,
+  audit: Audit,
+  Path(wallet_id): Path<WalletId>,
+  ValidatedJson(payload): ValidatedJson<TopUpRequest>,
+) -> AppResult<(StatusCode, Json<TransactionResponse>)> {
+  authz.require(Permission::TopUpWallet)?;
+
+  let transaction = state
+    .wallet_service
+    .top_up(
+      authz.0.actor_id,
+      wallet_id,
+      payload.amount,
+      payload.description,
+      TransactionMetadata::new(MetadataSource::Pos, payload.metadata)
+        .map_err(AppError::Validation)?,
+    )
+    .await?;
+
+  audit
+    .record(
+      Some(authz.0.actor_id),
+      AuditAction::WalletToppedUp,
+      wallet_id,
+      Some(json!({
+        "transaction_id": transaction.id,
+        "amount_cents": transaction.amount.as_minor(),
+      })),
+    )
+    .await;
+
+  Ok((StatusCode::CREATED, Json(transaction.into())))
+}
+
+/// Top up a wallet online
+///
+/// Opens a hosted checkout at the payment provider. The wallet is credited
+/// once the provider reports the payment as successful.
+#[utoipa::path(
+  post,
+  path = "/api/wallets/{wallet_id}/topup/online",
+  request_body = OnlineTopUpRequest,
+  params(
+    ("wallet_id" = Uuid, Path, description = "Wallet id")
+  ),
+  responses(
+    (status = StatusCode::CREATED, description = "Checkout opened; send the payer to `checkout_url`", body = OnlineTopUpResponse),
+    (status = StatusCode::BAD_REQUEST, description = "Validation error or system wallet", body = ErrorResponse),
+    (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+    (status = StatusCode::FORBIDDEN, description = "Neither the wallet's owner nor allowed to top up wallets", body = ErrorResponse),
+    (status = StatusCode::NOT_FOUND, description = "Wallet not found", body = ErrorResponse),
+    (status = StatusCode::CONFLICT, description = "Wallet has an unresolved chargeback", body = ErrorResponse),
+    (status = StatusCode::BAD_GATEWAY, description = "Payment provider unavailable", body = ErrorResponse),
+    (status = StatusCode::SERVICE_UNAVAILABLE, description = "No payment provider configured, or online top-ups are switched off", body = ErrorResponse),
+  ),
+  security(
+    ("session_cookie" = [])
+  )
+)]
+pub async fn top_up_online(
+  State(state): State<AppState>,
+  authz: Authz,
+  audit: Audit,
+  Path(wallet_id): Path<WalletId>,
+  ValidatedJson(payload): ValidatedJson<OnlineTopUpRequest>,
+) -> AppResult<(StatusCode, Json<OnlineTopUpResponse>)> {
+  let wallet = state
+    .wallet_service
+    .get_by_id(wallet_id)
+    .await?
+    .ok_or(AppError::NotFound)?;
+
+  if wallet.owner != Some(authz.0.actor_id) {
+    authz.require(Permission::TopUpWallet)?;
+  }
+
+  let top_up = state
+    .top_up_service
+    .start(authz.0.actor_id, wallet.id, payload.amount)
+    .await?;
+
+  audit
+    .record(
+      Some(authz.0.actor_id),
+      AuditAction::OnlineTopUpStarted,
+      wallet.id,
+      Some(json!({
+        "top_up_id": top_up.0.id,
+        "amount_cents": top_up.0.amount.as_minor(),
+      })),
+    )
+    .await;
+
+  Ok((StatusCode::CREATED, Json(top_up.into())))
+}
+
+#[utoipa::path(
+  post,
+  path = "/api/wallets/{wallet_id}/withdraw",
+  request_body = WithdrawRequest,
+  params(
+    ("wallet_id" = Uuid, Path, description = "Wallet id")
+  ),
+  responses(
+    (status = StatusCode::CREATED, description = "Cash payout recorded", body = TransactionResponse),
+    (status = StatusCode::BAD_REQUEST, description = "Validation error or system wallet", body = ErrorResponse),
+    (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+    (status = StatusCode::FORBIDDEN, description = "Forbidden or device signature required", body = ErrorResponse),
+    (status = StatusCode::NOT_FOUND, description = "Wallet not found", body = ErrorResponse),
+    (status = StatusCode::UNPROCESSABLE_ENTITY, description = "Insufficient funds", body = ErrorResponse),
+    (status = StatusCode::LOCKED, description = "Wallet is frozen", body = ErrorResponse),
+  ),
+  security(
+    ("session_cookie" = [])
+  )
+)]
+pub async fn withdraw(
+  State(state): State<AppState>,
+  authz: Authz,
+  _: DeviceProof,
+  audit: Audit,
+  Path(wallet_id): Path<WalletId>,
+  ValidatedJson(payload): ValidatedJson<WithdrawRequest>,
+) -> AppResult<(StatusCode, Json<TransactionResponse>)> {
+  authz.require(Permission::WithdrawFromWallet)?;
+
+  let transaction = state
+    .wallet_service
+    .withdraw(
+      authz.0.actor_id,
+      wallet_id,
+      payload.amount,
+      payload.description,
+      TransactionMetadata::new(MetadataSource::Pos, payload.metadata)
+        .map_err(AppError::Validation)?,
+    )
+    .await?;
+
+  audit
+    .record(
+      Some(authz.0.actor_id),
+      AuditAction::WalletWithdrawn,
+      wallet_id,
+      Some(json!({
+        "transaction_id": transaction.id,
+        "amount_cents": transaction.amount.as_minor(),
+      })),
+    )
+    .await;
+
+  Ok((StatusCode::CREATED, Json(transaction.into())))
+}
+
+/// Request a payout to a bank account
+///
+/// Takes the amount out of the wallet right away and queues the transfer for
+/// the next SEPA batch. Without an IBAN, the owner's account on file is used.
+/// An IBAN given by the owner replaces their account on file; one given by
+/// staff is used for this payout only.
+#[utoipa::path(
+  post,
+  path = "/api/wallets/{wallet_id}/payouts",
+  request_body = PayoutRequest,
+  params(
+    ("wallet_id" = Uuid, Path, description = "Wallet id")
+  ),
+  responses(
+    (status = StatusCode::CREATED, description = "Payout queued", body = PayoutResponse),
+    (status = StatusCode::BAD_REQUEST, description = "Validation error, no bank account or system wallet", body = ErrorResponse),
+    (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+    (status = StatusCode::FORBIDDEN, description = "Neither the wallet's owner nor allowed to withdraw from wallets, or device signature required", body = ErrorResponse),
+    (status = StatusCode::NOT_FOUND, description = "Wallet not found", body = ErrorResponse),
+    (status = StatusCode::UNPROCESSABLE_ENTITY, description = "Insufficient funds", body = ErrorResponse),
+    (status = StatusCode::LOCKED, description = "Wallet is frozen", body = ErrorResponse),
+    (status = StatusCode::SERVICE_UNAVAILABLE, description = "No account to pay out from is configured", body = ErrorResponse),
+  ),
+  security(
+    ("session_cookie" = [])
+  )
+)]
+pub async fn request_payout(
+  State(state): State<AppState>,
+  authz: Authz,
+  _: DeviceProof,
+  audit: Audit,
+  Path(wallet_id): Path<WalletId>,
+  ValidatedJson(payload): ValidatedJson<PayoutRequest>,
+) -> AppResult<(StatusCode, Json<PayoutResponse>)> {
+  let wallet = state
+    .wallet_service
+    .get_by_id(wallet_id)
+    .await?
+    .ok_or(AppError::NotFound)?;
+
+  let requested_by_owner = wallet.owner == Some(authz.0.actor_id);
+  if !requested_by_owner {
+    authz.require(Permission::WithdrawFromWallet)?;
+  }
+
+  let destination = match (payload.iban, payload.holder_name) {
+    (Some(iban), Some(holder_name)) => Some(PayoutDestination { iban, holder_name }),
+    (None, None) => None,
+    _ => {
+      return Err(AppError::Validation("`iban` and `holder_name` go together".to_string()).into())
+    }
+  };
+
+  let iban_given = destination.is_some();
+  let payout = state
+    .payout_service
+    .request(authz.0.actor_id, wallet.id, payload.amount, destination)
+    .await?;
+
+  audit
+    .record(
+      Some(authz.0.actor_id),
+      AuditAction::PayoutRequested,
+      wallet_id,
+      Some(json!({
+        "payout_id": payout.id,
+        "amount_cents": payout.amount.as_minor(),
+        "iban": payout.iban.masked(),
+        "iban_given": iban_given,
+        "bank_account_updated": iban_given && requested_by_owner,
+      })),
+    )
+    .await;
+
+  Ok((StatusCode::CREATED, Json(payout.into())))
+}
+
+#[utoipa::path(
+  post,
+  path = "/api/wallets/{wallet_id}/legal-hold",
+  request_body = LegalHoldRequest,
+  params(
+    ("wallet_id" = Uuid, Path, description = "Wallet id")
+  ),
+  responses(
+    (status = StatusCode::OK, description = "Legal hold placed", body = WalletResponse),
+    (status = StatusCode::BAD_REQUEST, description = "Validation error or wallet already held", body = ErrorResponse),
+    (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+    (status = StatusCode::FORBIDDEN, description = "Forbidden", body = ErrorResponse),
+    (status = StatusCode::NOT_FOUND, description = "Wallet not found", body = ErrorResponse),
+  ),
+  security(
+    ("session_cookie" = [])
+  )
+)]
+pub async fn place_legal_hold(
+  State(state): State<AppState>,
+  authz: Authz,
+  audit: Audit,
+  Path(wallet_id): Path<WalletId>,
+  ValidatedJson(payload): ValidatedJson<LegalHoldRequest>,
+) -> AppResult<Json<WalletResponse>> {
+  authz.require(Permission::ManageLegalHold)?;
+
+  let wallet = state
+    .wallet_service
+    .place_legal_hold(authz.0.actor_id, wallet_id, payload.reason)
+    .await?;
+
+  audit
+    .record(
+      Some(authz.0.actor_id),
+      AuditAction::LegalHoldPlaced,
+      wallet_id,
+      None,
+    )
+    .await;
+
+  Ok(Json(wallet.into()))
+}
+
+#[utoipa::path(
+  post,
+  path = "/api/wallets/{wallet_id}/legal-hold/release",
+  request_body = LegalHoldRequest,
+  params(
+    ("wallet_id" = Uuid, Path, description = "Wallet id")
+  ),
+  responses(
+    (status = StatusCode::OK, description = "Legal hold released", body = WalletResponse),
+    (status = StatusCode::BAD_REQUEST, description = "Validation error or wallet not held", body = ErrorResponse),
+    (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+    (status = StatusCode::FORBIDDEN, description = "Forbidden, or password confirmation or device signature required", body = ErrorResponse),
+    (status = StatusCode::NOT_FOUND, description = "Wallet not found", body = ErrorResponse),
+  ),
+  security(
+    ("session_cookie" = [], "confirmation_token" = [])
+  )
+)]
+pub async fn release_legal_hold(
+  State(state): State<AppState>,
+  authz: Authz,
+  _: StepUp,
+  audit: Audit,
+  Path(wallet_id): Path<WalletId>,
+  ValidatedJson(payload): ValidatedJson<LegalHoldRequest>,
+) -> AppResult<Json<WalletResponse>> {
+  authz.require(Permission::ManageLegalHold)?;
+
+  let wallet = state
+    .wallet_service
+    .release_legal_hold(authz.0.actor_id, wallet_id, payload.reason)
+    .await?;
+
+  audit
+    .record(
+      Some(authz.0.actor_id),
+      AuditAction::LegalHoldReleased,
+      wallet_id,
+      None,
+    )
+    .await;
+
+  Ok(Json(wallet.into()))
+}
+
+#[utoipa::path(
+  get,
+  path = "/api/wallets/{wallet_id}/legal-hold",
+  params(
+    ("wallet_id" = Uuid, Path, description = "Wallet id")
+  ),
+  responses(
+    (status = StatusCode::OK, description = "Legal hold history, newest first", body = [LegalHoldEventResponse]),
+    (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+    (status = StatusCode::FORBIDDEN, description = "Forbidden", body = ErrorResponse),
+    (status = StatusCode::NOT_FOUND, description = "Wallet not found", body = ErrorResponse),
+  ),
+  security(
+    ("session_cookie" = [])
+  )
+)]
+pub async fn get_legal_hold_history(
+  State(state): State<AppState>,
+  authz: Authz,
+  Path(wallet_id): Path<WalletId>,
+) -> AppResult<Json<Vec<LegalHoldEventResponse>>> {
+  authz.require(Permission::ManageLegalHold)?;
+
+  let events = state
+    .wallet_service
+    .get_legal_hold_history(wallet_id)
+    .await?;
+
+  Ok(Json(events.into_iter().map(Into::into).collect()))
+}
+
+/// Set a wallet's appearance
+///
+/// Name, color and icon the app shows for one of the caller's own wallets.
+/// Wallets under legal hold keep their appearance until the hold is released.
+#[utoipa::path(
+  put,
+  path = "/api/wallets/{wallet_id}/appearance",
+  request_body = WalletAppearanceRequest,
+  params(
+    ("wallet_id" = Uuid, Path, description = "Wallet id")
+  ),
+  responses(
+    (status = StatusCode::OK, description = "Appearance replaced", body = WalletResponse),
+    (status = StatusCode::BAD_REQUEST, description = "Validation error or system wallet", body = ErrorResponse),
+    (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+    (status = StatusCode::NOT_FOUND, description = "Wallet not found or not the caller's", body = ErrorResponse),
+    (status = StatusCode::LOCKED, description = "Wallet is under legal hold", body = ErrorResponse),
+  ),
+  security(
+    ("session_cookie" = [])
+  )
+)]
+pub async fn set_wallet_appearance(
+  State(state): State<AppState>,
+  authz: Authz,
+  audit: Audit,
+  Path(wallet_id): Path<WalletId>,
+  ValidatedJson(payload): ValidatedJson<WalletAppearanceRequest>,
+) -> AppResult<Json<WalletResponse>> {
+  let appearance = WalletAppearance::new(payload.display_name, payload.color, payload.icon)
+    .map_err(AppError::Validation)?;
+
+  let wallet = state
+    .wallet_service
+    .set_appearance(authz.0.actor_id, wallet_id, appearance)
+    .await?;
+
+  audit
+    .record(
+      Some(authz.0.actor_id),
+      AuditAction::WalletAppearanceChanged,
+      wallet.id,
+      None,
+    )
+    .await;
+
+  Ok(Json(wallet.into()))
+}
+
+/// List a wallet's budget envelopes
+///
+/// By name, each with what was spent against it so far.
+#[utoipa::path(
+  get,
+  path = "/api/wallets/{wallet_id}/envelopes",
+  params(
+    ("wallet_id" = Uuid, Path, description = "Wallet id")
+  ),
+  responses(
+    (status = StatusCode::OK, description = "Envelopes of the wallet", body = [BudgetEnvelopeResponse]),
+    (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+    (status = StatusCode::FORBIDDEN, description = "Neither the wallet's owner, allowed to read transactions, nor a guest's wallet and allowed to read guest details", body = ErrorResponse),
+    (status = StatusCode::NOT_FOUND, description = "Wallet not found", body = ErrorResponse),
+  ),
+  security(
+    ("session_cookie" = [])
+  )
+)]
+pub async fn list_budget_envelopes(
+  State(state): State<AppState>,
+  authz: Authz,
+  Path(wallet_id): Path<WalletId>,
+) -> AppResult<Json<Vec<BudgetEnvelopeResponse>>> {
+  let wallet = state
+    .wallet_service
+    .get_details(wallet_id)
+    .await?
+    .ok_or(AppError::NotFound)?;
+  if !authz.has_on_wallet_details(&wallet, Permission::ReadTransactions) {
+    return Err(AppError::Authorization.into());
+  }
+
+  let envelopes = state.budget_service.list(wallet_id).await?;
+
+  Ok(Json(envelopes.into_iter().map(Into::into).collect()))
+}
+
+/// Open a budget envelope
+///
+/// Earmarks part of one of the caller's own wallets for spending at some
+/// shops. Payments to them count against it from now on, refunds of those
+/// payments back. Purely informational: payments past it go through.
+#[utoipa::path(
+  post,
+  path = "/api/wallets/{wallet_id}/envelopes",
+  request_body = BudgetEnvelopeRequest,
+  params(
+    ("wallet_id" = Uuid, Path, description = "Wallet id")
+  ),
+  responses(
+    (status = StatusCode::CREATED, description = "Envelope opened", body = BudgetEnvelopeResponse),
+    (status = StatusCode::BAD_REQUEST, description = "Validation error, name taken, unknown shop, too many envelopes or system wallet", body = ErrorResponse),
+    (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+    (status = StatusCode::NOT_FOUND, description = "Wallet not found or not the caller's", body = ErrorResponse),
+  ),
+  security(
+    ("session_cookie" = [])
+  )
+)]
+pub async fn create_budget_envelope(
+  State(state): State<AppState>,
+  authz: Authz,
+  audit: Audit,
+  Path(wallet_id): Path<WalletId>,
+  ValidatedJson(payload): ValidatedJson<BudgetEnvelopeRequest>,
+) -> AppResult<(StatusCode, Json<BudgetEnvelopeResponse>)> {
+  let plan = payload.into_plan().map_err(AppError::Validation)?;
+
+  let envelope = state
+    .budget_service
+    .create(authz.0.actor_id, wallet_id, plan)
+    .await?;
+
+  audit
+    .record(
+      Some(authz.0.actor_id),
+      AuditAction::BudgetEnvelopeCreated,
+      envelope.id,
+      Some(json!({ "wallet_id": wallet_id })),
+    )
+    .await;
+
+  Ok((StatusCode::CREATED, Json(envelope.into())))
+}
+
+/// Replace a budget envelope
+///
+/// Spending so far stays counted, also against shops no longer covered,
+/// unless `reset_spent` is set.
+#[utoipa::path(
+  put,
+  path = "/api/wallets/{wallet_id}/envelopes/{envelope_id}",
+  request_body = BudgetEnvelopeRequest,
+  params(
+    ("wallet_id" = Uuid, Path, description = "Wallet id"),
+    ("envelope_id" = Uuid, Path, description = "Envelope id")
+  ),
+  responses(
+    (status = StatusCode::OK, description = "Envelope replaced", body = BudgetEnvelopeResponse),
+    (status = StatusCode::BAD_REQUEST, description = "Validation error, name taken or unknown shop", body = ErrorResponse),
+    (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+    (status = StatusCode::NOT_FOUND, description = "Wallet or envelope not found, or not the caller's", body = ErrorResponse),
+  ),
+  security(
+    ("session_cookie" = [])
+  )
+)]
+pub async fn update_budget_envelope(
+  State(state): State<AppState>,
+  authz: Authz,
+  audit: Audit,
+  Path((wallet_id, envelope_id)): Path<(Id<Wallet>, Id<BudgetEnvelope>)>,
+  ValidatedJson(payload): ValidatedJson<BudgetEnvelopeRequest>,
+) -> AppResult<Json<BudgetEnvelopeResponse>> {
+  let reset = payload.reset_spent;
+  let plan = payload.into_plan().map_err(AppError::Validation)?;
+
+  let envelope = state
+    .budget_service
+    .update(authz.0.actor_id, wallet_id, envelope_id, plan, reset)
+    .await?;
+
+  audit
+    .record(
+      Some(authz.0.actor_id),
+      AuditAction::BudgetEnvelopeUpdated,
+      envelope.id,
+      Some(json!({ "wallet_id": wallet_id, "reset_spent": reset })),
+    )
+    .await;
+
+  Ok(Json(envelope.into()))
+}
+
+/// Remove a budget envelope
+#[utoipa::path(
+  delete,
+  path = "/api/wallets/{wallet_id}/envelopes/{envelope_id}",
+  params(
+    ("wallet_id" = Uuid, Path, description = "Wallet id"),
+    ("envelope_id" = Uuid, Path, description = "Envelope id")
+  ),
+  responses(
+    (status = StatusCode::NO_CONTENT, description = "Envelope removed"),
+    (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+    (status = StatusCode::NOT_FOUND, description = "Wallet or envelope not found, or not the caller's", body = ErrorResponse),
+  ),
+  security(
+    ("session_cookie" = [])
+  )
+)]
+pub async fn remove_budget_envelope(
+  State(state): State<AppState>,
+  authz: Authz,
+  audit: Audit,
+  Path((wallet_id, envelope_id)): Path<(Id<Wallet>, Id<BudgetEnvelope>)>,
+) -> AppResult<StatusCode> {
+  state
+    .budget_service
+    .delete(authz.0.actor_id, wallet_id, envelope_id)
+    .await?;
+
+  audit
+    .record(
+      Some(authz.0.actor_id),
+      AuditAction::BudgetEnvelopeRemoved,
+      envelope_id,
+      Some(json!({ "wallet_id": wallet_id })),
+    )
+    .await;
+
+  Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(OpenApi)]
+#[openapi(
+  paths(
+    list_wallets,
+    get_wallet,
+    set_wallet_appearance,
+    list_budget_envelopes,
+    create_budget_envelope,
+    update_budget_envelope,
+    remove_budget_envelope,
+    top_up,
+    top_up_online,
+    withdraw,
+    request_payout,
+    place_legal_hold,
+    release_legal_hold,
+    get_legal_hold_history,
+  ),
+  components(schemas(
+    WalletResponse,
+    PaginatedWalletResponse,
+    WalletDetailsResponse,
+    crate::models::WalletOwnerResponse,
+    WalletAppearanceRequest,
+    BudgetEnvelopeRequest,
+    BudgetEnvelopeResponse,
+    TopUpRequest,
+    OnlineTopUpRequest,
+    OnlineTopUpResponse,
+    domain::TopUpStatus,
+    WithdrawRequest,
+    PayoutRequest,
+    LegalHoldRequest,
+    LegalHoldEventResponse,
+    domain::LegalHoldAction,
+  ))
+)]
+struct Api;
+
+pub fn openapi() -> utoipa::openapi::OpenApi {
+  Api::openapi()
+}
+
+pub fn router() -> Routes {
+  Routes::new()
+    .get(
+      "/",
+      list_wallets,
+      Guard::All(&[Permission::ReadTransactions]),
+    )
+    .get(
+      "/:wallet_id",
+      get_wallet,
+      Guard::OwnerOrGuestReaderOr(&[Permission::ReadTransactions]),
+    )
+    .put(
+      "/:wallet_id/appearance",
+      set_wallet_appearance,
+      Guard::Authenticated,
+    )
+    .get(
+      "/:wallet_id/envelopes",
+      list_budget_envelopes,
+      Guard::OwnerOrGuestReaderOr(&[Permission::ReadTransactions]),
+    )
+    .post(
+      "/:wallet_id/envelopes",
+      create_budget_envelope,
+      Guard::Authenticated,
+    )
+    .put(
+      "/:wallet_id/envelopes/:envelope_id",
+      update_budget_envelope,
+      Guard::Authenticated,
+    )
+    .delete(
+      "/:wallet_id/envelopes/:envelope_id",
+      remove_budget_envelope,
+      Guard::Authenticated,
+    )
+    .post(
+      "/:wallet_id/topup",
+      top_up,
+      Guard::All(&[Permission::TopUpWallet]),
+    )
+    .post(
+      "/:wallet_id/topup/online",
+      top_up_online,
+      Guard::OwnerOr(&[Permission::TopUpWallet]),
+    )
+    .post(
+      "/:wallet_id/withdraw",
+      withdraw,
+      Guard::All(&[Permission::WithdrawFromWallet]),
+    )
+    .post(
+      "/:wallet_id/payouts",
+      request_payout,
+      Guard::OwnerOr(&[Permission::WithdrawFromWallet]),
+    )
+    .post(
+      "/:wallet_id/legal-hold",
+      place_legal_hold,
+      Guard::All(&[Permission::ManageLegalHold]),
+    )
+    .get(
+      "/:wallet_id/legal-hold",
+      get_legal_hold_history,
+      Guard::All(&[Permission::ManageLegalHold]),
+    )
+    .post(
+      "/:wallet_id/legal-hold/release",
+      release_legal_hold,
+      Guard::All(&[Permission::ManageLegalHold]),
+    )
+}
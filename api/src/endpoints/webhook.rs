@@ -0,0 +1,206 @@
+use crate::{
+  endpoints::shop::require_shop_owner_or,
+  error::AppResult,
+  extractor::{Audit, Authz, ValidatedJson},
+  models::{
+    CreateWebhookRequest, CreatedWebhookResponse, WebhookDeliveryResponse, WebhookResponse,
+  },
+  route_permissions::Guard,
+  routes::Routes,
+};
+use application::state::AppState;
+use axum::{
+  extract::{Path, State},
+  http::StatusCode,
+  Json,
+};
+use domain::{AuditAction, Permission, WebhookId};
+use serde_json::json;
+use utoipa::OpenApi;
+
+/// List account-wide webhooks
+///
+/// Webhooks registered for a single shop are listed under the shop.
+#[utoipa::path(
+  get,
+  path = "/api/webhooks",
+  responses(
+    (status = StatusCode::OK, description = "Webhooks not tied to a shop", body = Vec<WebhookResponse>),
+    (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+    (status = StatusCode::FORBIDDEN, description = "Forbidden", body = ErrorResponse),
+  ),
+  security(
+    ("session_cookie" = [])
+  )
+)]
+pub async fn list_webhooks(
+  State(state): State<AppState>,
+  authz: Authz,
+) -> AppResult<Json<Vec<WebhookResponse>>> {
+  authz.require(Permission::ManageWebhooks)?;
+
+  let webhooks = state.webhook_service.list().await?;
+
+  Ok(Json(webhooks.into_iter().map(Into::into).collect()))
+}
+
+/// Register an account-wide webhook
+///
+/// Receives the subscribed events of every shop as well as those concerning
+/// no shop, such as `transaction.created`. Each delivery is a signed JSON
+/// `POST`, retried with backoff for about five hours until the endpoint
+/// answers with a 2xx status.
+#[utoipa::path(
+  post,
+  path = "/api/webhooks",
+  request_body = CreateWebhookRequest,
+  responses(
+    (status = StatusCode::CREATED, description = "Webhook registered; the signing secret is only shown now", body = CreatedWebhookResponse),
+    (status = StatusCode::BAD_REQUEST, description = "Validation error", body = ErrorResponse),
+    (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+    (status = StatusCode::FORBIDDEN, description = "Forbidden", body = ErrorResponse),
+  ),
+  security(
+    ("session_cookie" = [])
+  )
+)]
+pub async fn create_webhook(
+  State(state): State<AppState>,
+  authz: Authz,
+  audit: Audit,
+  ValidatedJson(payload): ValidatedJson<CreateWebhookRequest>,
+) -> AppResult<(StatusCode, Json<CreatedWebhookResponse>)> {
+  authz.require(Permission::ManageWebhooks)?;
+
+  let webhook = state
+    .webhook_service
+    .register(authz.0.actor_id, payload.url, payload.events)
+    .await?;
+
+  audit
+    .record(
+      Some(authz.0.actor_id),
+      AuditAction::WebhookRegistered,
+      webhook.id,
+      Some(json!({ "url": webhook.url, "events": webhook.events })),
+    )
+    .await;
+
+  Ok((StatusCode::CREATED, Json(webhook.into())))
+}
+
+/// Remove an account-wide webhook
+///
+/// Pending deliveries are dropped along with its delivery log.
+#[utoipa::path(
+  delete,
+  path = "/api/webhooks/{webhook_id}",
+  params(
+    ("webhook_id" = Uuid, Path, description = "Webhook id")
+  ),
+  responses(
+    (status = StatusCode::NO_CONTENT, description = "Webhook removed"),
+    (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+    (status = StatusCode::FORBIDDEN, description = "Forbidden", body = ErrorResponse),
+    (status = StatusCode::NOT_FOUND, description = "No account-wide webhook with this id", body = ErrorResponse),
+  ),
+  security(
+    ("session_cookie" = [])
+  )
+)]
+pub async fn remove_webhook(
+  State(state): State<AppState>,
+  authz: Authz,
+  audit: Audit,
+  Path(webhook_id): Path<WebhookId>,
+) -> AppResult<StatusCode> {
+  authz.require(Permission::ManageWebhooks)?;
+
+  let webhook = state.webhook_service.remove(webhook_id).await?;
+
+  audit
+    .record(
+      Some(authz.0.actor_id),
+      AuditAction::WebhookRemoved,
+      webhook.id,
+      None,
+    )
+    .await;
+
+  Ok(StatusCode::NO_CONTENT)
+}
+
+/// List a webhook's deliveries
+///
+/// The latest hundred, newest first. Delivered events are purged after a
+/// week; failed ones stay until the webhook is removed. Shop owners can see
+/// the log of their shop's webhooks.
+#[utoipa::path(
+  get,
+  path = "/api/webhooks/{webhook_id}/deliveries",
+  params(
+    ("webhook_id" = Uuid, Path, description = "Webhook id")
+  ),
+  responses(
+    (status = StatusCode::OK, description = "Delivery log", body = Vec<WebhookDeliveryResponse>),
+    (status = StatusCode::UNAUTHORIZED, description = "Unauthorized", body = ErrorResponse),
+    (status = StatusCode::FORBIDDEN, description = "Neither owner of the webhook's shop nor allowed to manage webhooks", body = ErrorResponse),
+    (status = StatusCode::NOT_FOUND, description = "Webhook not found", body = ErrorResponse),
+  ),
+  security(
+    ("session_cookie" = [])
+  )
+)]
+pub async fn list_deliveries(
+  State(state): State<AppState>,
+  authz: Authz,
+  Path(webhook_id): Path<WebhookId>,
+) -> AppResult<Json<Vec<WebhookDeliveryResponse>>> {
+  let webhook = state.webhook_service.get(webhook_id).await?;
+
+  match webhook.shop_id {
+    Some(shop_id) => {
+      require_shop_owner_or(&state, &authz, shop_id, Permission::ManageWebhooks).await?
+    }
+    None => authz.require(Permission::ManageWebhooks)?,
+  }
+
+  let deliveries = state.webhook_service.list_deliveries(webhook.id).await?;
+
+  Ok(Json(deliveries.into_iter().map(Into::into).collect()))
+}
+
+#[derive(OpenApi)]
+#[openapi(
+  paths(list_webhooks, create_webhook, remove_webhook, list_deliveries,),
+  components(schemas(WebhookDeliveryResponse, domain::WebhookDeliveryStatus,))
+)]
+struct Api;
+
+pub fn openapi() -> utoipa::openapi::OpenApi {
+  Api::openapi()
+}
+
+pub fn router() -> Routes {
+  Routes::new()
+    .get(
+      "/",
+      list_webhooks,
+      Guard::All(&[Permission::ManageWebhooks]),
+    )
+    .post(
+      "/",
+      create_webhook,
+      Guard::All(&[Permission::ManageWebhooks]),
+    )
+    .delete(
+      "/:webhook_id",
+      remove_webhook,
+      Guard::All(&[Permission::ManageWebhooks]),
+    )
+    .get(
+      "/:webhook_id/deliveries",
+      list_deliveries,
+      Guard::OwnerOr(&[Permission::ManageWebhooks]),
+    )
+}
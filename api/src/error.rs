@@ -1,3 +1,4 @@
+use crate::{middleware::request_id::current_request_id, models::DuplicateCandidateResponse};
 use application::error::AppError;
 use axum::{
   http::StatusCode,
@@ -5,7 +6,6 @@ use axum::{
   Json,
 };
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
 use utoipa::ToSchema;
 
 #[derive(Debug)]
@@ -19,80 +19,419 @@ impl From<AppError> for ApiError {
 
 pub type AppResult<T> = Result<T, ApiError>;
 
+/// Version of the error body format; raised only when a field changes
+/// meaning or goes away.
+pub const ERROR_FORMAT_VERSION: u32 = 1;
+
+/// Body of every error response.
 #[derive(Serialize, Deserialize, Debug, ToSchema)]
 pub struct ErrorResponse {
+  /// Format version, see [`ERROR_FORMAT_VERSION`]
+  #[schema(example = 1)]
+  pub version: u32,
+  pub code: ErrorCode,
+  /// Human-readable explanation; wording may change between releases
   pub message: String,
+  /// Further facts about the error, depending on the code
+  #[serde(skip_serializing_if = "Option::is_none")]
+  #[schema(value_type = Option<Object>)]
+  pub details: Option<serde_json::Value>,
+  /// Correlates the response with server logs
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub trace_id: Option<String>,
+  /// Same as `trace_id`, kept for clients that read the older name
   #[serde(skip_serializing_if = "Option::is_none")]
-  pub details: Option<HashMap<String, Vec<String>>>,
+  #[schema(deprecated)]
+  pub request_id: Option<String>,
+}
+
+impl ErrorResponse {
+  /// Builds the body for the current request, filling in its trace ID.
+  pub fn new(code: ErrorCode, message: String, details: Option<serde_json::Value>) -> Self {
+    let trace_id = current_request_id();
+    Self {
+      version: ERROR_FORMAT_VERSION,
+      code,
+      message,
+      details,
+      request_id: trace_id.clone(),
+      trace_id,
+    }
+  }
+}
+
+/// What went wrong, for clients to branch on. Codes are never renamed or
+/// reused; new ones may be added.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+  /// Something failed on the server; the trace ID helps finding out what
+  Internal,
+  /// The request is malformed
+  BadRequest,
+  /// The request body failed validation
+  ValidationFailed,
+  /// No session or invalid credentials
+  Unauthenticated,
+  /// The caller lacks a permission
+  Forbidden,
+  /// The password has to be confirmed again first
+  PasswordConfirmationRequired,
+  /// The session is bound to a device, which has to sign the request
+  DeviceSignatureRequired,
+  /// The resource does not exist or is hidden from the caller
+  NotFound,
+  /// The resource does not support the HTTP method
+  MethodNotAllowed,
+  /// The cursor matches no event in the log; read again from the start or
+  /// the latest cursor
+  UnknownCursor,
+  /// The request body is too large
+  PayloadTooLarge,
+  /// The request body is not of a supported content type
+  UnsupportedMediaType,
+  /// Too many requests; retry after the `Retry-After` header
+  RateLimited,
+  /// Too many failed logins
+  AccountLocked,
+  UserAlreadyExists,
+  EmailInUse,
+  /// The person likely has an account already; `details.candidates` lists
+  /// who. Repeat the request with `force` to go ahead anyway
+  PossibleDuplicate,
+  /// The only owner cannot be demoted
+  LastOwner,
+  /// Another owner transfer awaits acceptance; `details.expires_at` says
+  /// until when
+  OwnerTransferPending,
+  /// The owner transfer was decided, ran out, or its owner no longer is one
+  OwnerTransferClosed,
+  /// A pending invite exists; `details.expires_at` says when it frees up
+  InviteAlreadySent,
+  InviteExpired,
+  InviteRevoked,
+  /// The address's domain may not be invited with the role
+  InviteDomainNotAllowed,
+  /// The card or wristband identifier belongs to another guest
+  IdentifierInUse,
+  GuestAlreadyClaimed,
+  AlreadyRefunded,
+  AlreadyShopMember,
+  InvalidOrderTransition,
+  InvalidPayoutTransition,
+  OrderUnpaid,
+  OrderAlreadyPaid,
+  PickupSlotFull,
+  InsufficientFunds,
+  /// The wallet is under legal hold
+  LegalHold,
+  WalletFrozen,
+  /// Money can only move between wallets of the same currency
+  CurrencyMismatch,
+  ChargebackUnresolved,
+  /// The payment service provider failed to respond
+  PaymentProviderUnavailable,
+  OnlinePaymentsDisabled,
+  BankPayoutsDisabled,
+  /// An operator switched the feature off for now; `details.feature` says
+  /// which and `details.reason` may say why
+  FeatureDisabled,
+}
+
+impl ErrorCode {
+  /// Best fit for a response the application did not produce itself, such
+  /// as an extractor rejection.
+  pub fn from_status(status: StatusCode) -> Self {
+    match status {
+      StatusCode::BAD_REQUEST => ErrorCode::BadRequest,
+      StatusCode::UNPROCESSABLE_ENTITY => ErrorCode::ValidationFailed,
+      StatusCode::UNAUTHORIZED => ErrorCode::Unauthenticated,
+      StatusCode::FORBIDDEN => ErrorCode::Forbidden,
+      StatusCode::NOT_FOUND => ErrorCode::NotFound,
+      StatusCode::METHOD_NOT_ALLOWED => ErrorCode::MethodNotAllowed,
+      StatusCode::PAYLOAD_TOO_LARGE => ErrorCode::PayloadTooLarge,
+      StatusCode::UNSUPPORTED_MEDIA_TYPE => ErrorCode::UnsupportedMediaType,
+      StatusCode::TOO_MANY_REQUESTS => ErrorCode::RateLimited,
+      status if status.is_client_error() => ErrorCode::BadRequest,
+      _ => ErrorCode::Internal,
+    }
+  }
+}
+
+impl ApiError {
+  fn details(&self) -> Option<serde_json::Value> {
+    match &self.0 {
+      AppError::InviteAlreadySent { expires_at }
+      | AppError::OwnerTransferPending { expires_at } => {
+        Some(serde_json::json!({ "expires_at": expires_at }))
+      }
+      AppError::FeatureDisabled { feature, reason } => {
+        Some(serde_json::json!({ "feature": feature, "reason": reason }))
+      }
+      AppError::PossibleDuplicates(candidates) => {
+        let candidates: Vec<_> = candidates
+          .iter()
+          .cloned()
+          .map(DuplicateCandidateResponse::from)
+          .collect();
+        Some(serde_json::json!({ "candidates": candidates }))
+      }
+      _ => None,
+    }
+  }
 }
 
 impl IntoResponse for ApiError {
   fn into_response(self) -> Response {
-    let (status, message, details) = match self.0 {
+    let details = self.details();
+    let (status, code, message) = match self.0 {
       AppError::Database(e) => {
         tracing::error!("Database error: {:?}", e);
         (
           StatusCode::INTERNAL_SERVER_ERROR,
+          ErrorCode::Internal,
           "Internal server error".to_string(),
-          None,
         )
       }
       AppError::NotFound => (
         StatusCode::NOT_FOUND,
+        ErrorCode::NotFound,
         "Resource not found".to_string(),
-        None,
       ),
       AppError::Authentication => (
         StatusCode::UNAUTHORIZED,
+        ErrorCode::Unauthenticated,
         "Authentication failed".to_string(),
-        None,
       ),
-      AppError::Authorization => (StatusCode::FORBIDDEN, "Permission denied".to_string(), None),
+      AppError::Authorization => (
+        StatusCode::FORBIDDEN,
+        ErrorCode::Forbidden,
+        "Permission denied".to_string(),
+      ),
+      AppError::AccountLocked(until) => (
+        StatusCode::LOCKED,
+        ErrorCode::AccountLocked,
+        format!(
+          "Account is locked after too many failed logins until {}",
+          until.to_rfc3339()
+        ),
+      ),
       AppError::UserAlreadyExists => (
         StatusCode::CONFLICT,
+        ErrorCode::UserAlreadyExists,
         "User already exists".to_string(),
-        None,
       ),
-      AppError::InviteAlreadySent => (
+      AppError::InviteAlreadySent { expires_at } => (
         StatusCode::CONFLICT,
-        "Invite already sent".to_string(),
-        None,
+        ErrorCode::InviteAlreadySent,
+        format!(
+          "Invite already sent; it expires at {}",
+          expires_at.to_rfc3339()
+        ),
       ),
       AppError::InvitorMissing(user_id) => {
         tracing::error!("Invitor missing: {:?}", user_id);
         (
           StatusCode::INTERNAL_SERVER_ERROR,
+          ErrorCode::Internal,
           "Internal server error".to_string(),
-          None,
         )
       }
-      AppError::InviteExpired => (StatusCode::BAD_REQUEST, "Invite expired".to_string(), None),
+      AppError::InviteExpired => (
+        StatusCode::BAD_REQUEST,
+        ErrorCode::InviteExpired,
+        "Invite expired".to_string(),
+      ),
+      AppError::InviteRevoked => (
+        StatusCode::BAD_REQUEST,
+        ErrorCode::InviteRevoked,
+        "Invite revoked".to_string(),
+      ),
+      AppError::InviteDomainNotAllowed(role) => (
+        StatusCode::FORBIDDEN,
+        ErrorCode::InviteDomainNotAllowed,
+        format!("Addresses at this domain cannot be invited as {}", role),
+      ),
       AppError::Email(e) => {
         tracing::error!("Email error: {:?}", e);
         (
           StatusCode::INTERNAL_SERVER_ERROR,
+          ErrorCode::Internal,
           "Internal server error".to_string(),
-          None,
         )
       }
-      AppError::Validation(msg) => (StatusCode::BAD_REQUEST, msg, None),
-      AppError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg, None),
+      AppError::Webhook(e) => {
+        tracing::error!("Webhook error: {:?}", e);
+        (
+          StatusCode::INTERNAL_SERVER_ERROR,
+          ErrorCode::Internal,
+          "Internal server error".to_string(),
+        )
+      }
+      AppError::Psp(e) => {
+        tracing::error!("Payment provider error: {:?}", e);
+        (
+          StatusCode::BAD_GATEWAY,
+          ErrorCode::PaymentProviderUnavailable,
+          "Payment provider unavailable".to_string(),
+        )
+      }
+      AppError::PspDisabled => (
+        StatusCode::SERVICE_UNAVAILABLE,
+        ErrorCode::OnlinePaymentsDisabled,
+        "Online payments are not available".to_string(),
+      ),
+      AppError::ChargebackUnresolved => (
+        StatusCode::CONFLICT,
+        ErrorCode::ChargebackUnresolved,
+        "Wallet has an unresolved chargeback".to_string(),
+      ),
+      AppError::SepaDisabled => (
+        StatusCode::SERVICE_UNAVAILABLE,
+        ErrorCode::BankPayoutsDisabled,
+        "Bank payouts are not available".to_string(),
+      ),
+      AppError::FeatureDisabled { feature, .. } => (
+        StatusCode::SERVICE_UNAVAILABLE,
+        ErrorCode::FeatureDisabled,
+        format!("{} is switched off for now", feature.describe()),
+      ),
+      AppError::Validation(msg) => (StatusCode::BAD_REQUEST, ErrorCode::ValidationFailed, msg),
+      AppError::BadRequest(msg) => (StatusCode::BAD_REQUEST, ErrorCode::BadRequest, msg),
+      AppError::UnknownCursor(sequence) => (
+        StatusCode::GONE,
+        ErrorCode::UnknownCursor,
+        format!("No event has the cursor {sequence}; read again from the start"),
+      ),
+      AppError::PayloadTooLarge => (
+        StatusCode::PAYLOAD_TOO_LARGE,
+        ErrorCode::PayloadTooLarge,
+        "Request body exceeds the size limit".to_string(),
+      ),
       AppError::InternalServerError => (
         StatusCode::INTERNAL_SERVER_ERROR,
+        ErrorCode::Internal,
         "Internal server error".to_string(),
-        None,
       ),
       AppError::PasswordHash(e) => {
         tracing::error!("Password hash error: {:?}", e);
         (
           StatusCode::INTERNAL_SERVER_ERROR,
+          ErrorCode::Internal,
           "Internal server error".to_string(),
-          None,
         )
       }
+      AppError::PasswordConfirmationRequired => (
+        StatusCode::FORBIDDEN,
+        ErrorCode::PasswordConfirmationRequired,
+        "Password confirmation required".to_string(),
+      ),
+      AppError::DeviceSignatureRequired => (
+        StatusCode::FORBIDDEN,
+        ErrorCode::DeviceSignatureRequired,
+        "Device signature required".to_string(),
+      ),
+      AppError::GuestIdentifierInUse => (
+        StatusCode::CONFLICT,
+        ErrorCode::IdentifierInUse,
+        "Identifier is already bound to another guest".to_string(),
+      ),
+      AppError::EmailInUse => (
+        StatusCode::CONFLICT,
+        ErrorCode::EmailInUse,
+        "Email address is already in use".to_string(),
+      ),
+      AppError::PossibleDuplicates(_) => (
+        StatusCode::CONFLICT,
+        ErrorCode::PossibleDuplicate,
+        "The person likely has an account already".to_string(),
+      ),
+      AppError::LastOwner => (
+        StatusCode::CONFLICT,
+        ErrorCode::LastOwner,
+        "The last owner must stay an owner".to_string(),
+      ),
+      AppError::OwnerTransferPending { expires_at } => (
+        StatusCode::CONFLICT,
+        ErrorCode::OwnerTransferPending,
+        format!(
+          "An owner transfer is already pending; it expires at {}",
+          expires_at.to_rfc3339()
+        ),
+      ),
+      AppError::OwnerTransferClosed => (
+        StatusCode::CONFLICT,
+        ErrorCode::OwnerTransferClosed,
+        "The owner transfer is no longer pending".to_string(),
+      ),
+      AppError::GuestAlreadyClaimed => (
+        StatusCode::CONFLICT,
+        ErrorCode::GuestAlreadyClaimed,
+        "Guest has already been claimed".to_string(),
+      ),
+      AppError::AlreadyRefunded => (
+        StatusCode::CONFLICT,
+        ErrorCode::AlreadyRefunded,
+        "Transaction has already been refunded".to_string(),
+      ),
+      AppError::AlreadyShopMember => (
+        StatusCode::CONFLICT,
+        ErrorCode::AlreadyShopMember,
+        "User is already a member of this shop".to_string(),
+      ),
+      AppError::InvalidOrderTransition { from, to } => (
+        StatusCode::CONFLICT,
+        ErrorCode::InvalidOrderTransition,
+        format!("Order cannot move from {} to {}", from, to),
+      ),
+      AppError::InvalidPayoutTransition { from, to } => (
+        StatusCode::CONFLICT,
+        ErrorCode::InvalidPayoutTransition,
+        format!("Payout cannot move from {} to {}", from, to),
+      ),
+      AppError::OrderUnpaid => (
+        StatusCode::CONFLICT,
+        ErrorCode::OrderUnpaid,
+        "Order has not been paid yet".to_string(),
+      ),
+      AppError::OrderAlreadyPaid => (
+        StatusCode::CONFLICT,
+        ErrorCode::OrderAlreadyPaid,
+        "Order has already been paid".to_string(),
+      ),
+      AppError::PickupSlotFull => (
+        StatusCode::CONFLICT,
+        ErrorCode::PickupSlotFull,
+        "Pickup slot is fully booked".to_string(),
+      ),
+      AppError::InsufficientFunds => (
+        StatusCode::UNPROCESSABLE_ENTITY,
+        ErrorCode::InsufficientFunds,
+        "Insufficient funds".to_string(),
+      ),
+      AppError::LegalHold => (
+        StatusCode::LOCKED,
+        ErrorCode::LegalHold,
+        "Wallet is under legal hold".to_string(),
+      ),
+      AppError::WalletFrozen => (
+        StatusCode::LOCKED,
+        ErrorCode::WalletFrozen,
+        "Wallet is frozen".to_string(),
+      ),
+      AppError::CurrencyMismatch => (
+        StatusCode::CONFLICT,
+        ErrorCode::CurrencyMismatch,
+        "Wallets hold different currencies; convert the amount first".to_string(),
+      ),
+      AppError::RateLimited => (
+        StatusCode::TOO_MANY_REQUESTS,
+        ErrorCode::RateLimited,
+        "Too many requests".to_string(),
+      ),
     };
 
-    let body = Json(ErrorResponse { message, details });
+    let body = Json(ErrorResponse::new(code, message, details));
 
     (status, body).into_response()
   }
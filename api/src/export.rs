@@ -0,0 +1,115 @@
+use domain::{ActorId, Permission};
+
+use crate::extractor::Authz;
+
+/// What a download may reveal about the people in it, decided once per
+/// export from the caller's permissions.
+///
+/// Whatever [`crate::models::Redact`] strips from the matching JSON response
+/// stays out of the download too. Names go further: where JSON responses
+/// still name users, a download leaves the app, so callers not allowed to
+/// read user details get the actor id as a pseudonym instead.
+#[derive(Debug, Clone, Copy)]
+pub struct ExportMask {
+  user_details: bool,
+}
+
+impl ExportMask {
+  pub fn for_caller(authz: &Authz) -> Self {
+    Self {
+      user_details: authz.has(Permission::ReadUserDetails),
+    }
+  }
+
+  /// A user's name, or their actor id as a pseudonym for callers not allowed
+  /// to read user details.
+  pub fn user_name(&self, actor_id: Option<ActorId>, name: Option<String>) -> Option<String> {
+    if self.user_details {
+      name
+    } else {
+      actor_id.map(|id| id.to_string())
+    }
+  }
+
+  /// What is only for callers allowed to read user details, such as where
+  /// someone acted from.
+  pub fn user_details<T>(&self, value: Option<T>) -> Option<T> {
+    value.filter(|_| self.user_details)
+  }
+}
+
+/// Escapes free text for CSV and defuses values a spreadsheet would
+/// otherwise evaluate as a formula.
+pub fn csv_text(value: Option<String>) -> String {
+  let Some(mut value) = value else {
+    return String::new();
+  };
+
+  if value.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+    value.insert(0, '\'');
+  }
+
+  if value.contains([',', '"', '\r', '\n']) {
+    format!("\"{}\"", value.replace('"', "\"\""))
+  } else {
+    value
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::test_support::create_user;
+  use domain::{Id, Role};
+
+  fn authz(role: Role) -> Authz {
+    Authz::new(create_user(role), &[])
+  }
+
+  #[test]
+  fn test_export_mask_pseudonymizes_users_without_permission() {
+    let actor_id: ActorId = Id::new();
+    let name = || Some("Ada Lovelace".to_string());
+
+    let auditor = ExportMask::for_caller(&authz(Role::Auditor));
+    assert_eq!(auditor.user_name(Some(actor_id), name()), name());
+
+    let cashier = ExportMask::for_caller(&authz(Role::Cashier));
+    assert_eq!(
+      cashier.user_name(Some(actor_id), name()),
+      Some(actor_id.to_string())
+    );
+    assert_eq!(cashier.user_name(None, None), None);
+  }
+
+  #[test]
+  fn test_export_mask_withholds_user_details_without_permission() {
+    let ip = || Some("192.0.2.1".to_string());
+
+    let auditor = ExportMask::for_caller(&authz(Role::Auditor));
+    assert_eq!(auditor.user_details(ip()), ip());
+
+    let cashier = ExportMask::for_caller(&authz(Role::Cashier));
+    assert_eq!(cashier.user_details(ip()), None);
+  }
+
+  #[test]
+  fn test_csv_text_passes_plain_values() {
+    assert_eq!(csv_text(None), "");
+    assert_eq!(csv_text(Some("Beer".into())), "Beer");
+  }
+
+  #[test]
+  fn test_csv_text_quotes_separators() {
+    assert_eq!(csv_text(Some("a,b".into())), "\"a,b\"");
+    assert_eq!(csv_text(Some("say \"hi\"".into())), "\"say \"\"hi\"\"\"");
+    assert_eq!(csv_text(Some("two\nlines".into())), "\"two\nlines\"");
+  }
+
+  #[test]
+  fn test_csv_text_defuses_formulas() {
+    assert_eq!(csv_text(Some("=1+1".into())), "'=1+1");
+    assert_eq!(csv_text(Some("@SUM(A1)".into())), "'@SUM(A1)");
+    assert_eq!(csv_text(Some("=A1,B1".into())), "\"'=A1,B1\"");
+  }
+}
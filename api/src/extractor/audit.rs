@@ -0,0 +1,66 @@
+use std::{convert::Infallible, fmt::Display, net::SocketAddr};
+
+use axum::{
+  async_trait,
+  extract::{ConnectInfo, FromRequestParts},
+  http::request::Parts,
+};
+use serde_json::Value;
+
+use application::{
+  services::{audit::AuditEntryCreation, AuditService},
+  state::AppState,
+};
+use domain::{ActorId, AuditAction};
+
+/// Records what a handler did in the audit log, tagged with the caller's IP.
+///
+/// Recording is best effort: the action already happened by the time it is
+/// logged, so failures are only traced and never fail the request.
+pub struct Audit {
+  service: AuditService,
+  ip_address: Option<String>,
+}
+
+impl Audit {
+  pub async fn record(
+    &self,
+    actor_id: Option<ActorId>,
+    action: AuditAction,
+    target: impl Display,
+    details: Option<Value>,
+  ) {
+    let entry = AuditEntryCreation {
+      actor_id,
+      action: action.to_string(),
+      target: Some(target.to_string()),
+      ip_address: self.ip_address.clone(),
+      details,
+      personal_data: None,
+    };
+
+    if let Err(e) = self.service.record(entry).await {
+      tracing::warn!("Failed to record audit entry for {}: {}", action, e);
+    }
+  }
+}
+
+#[async_trait]
+impl FromRequestParts<AppState> for Audit {
+  type Rejection = Infallible;
+
+  async fn from_request_parts(
+    parts: &mut Parts,
+    state: &AppState,
+  ) -> Result<Self, Self::Rejection> {
+    let ip_address = parts
+      .extensions
+      .get::<ConnectInfo<SocketAddr>>()
+      .map(|ConnectInfo(addr)| addr.ip().to_string());
+
+    Ok(Audit {
+      service: state.audit_service.clone(),
+      ip_address,
+    })
+  }
+}
@@ -3,10 +3,13 @@ use axum_extra::extract::CookieJar;
 use std::ops::Deref;
 
 use application::{error::AppError, state::AppState};
-use domain::User;
+use domain::{AccessToken, Principal, User};
 
 use crate::error::ApiError;
 
+/// The signed-in user, by session cookie or by a client app's access token.
+/// Tokens only get this far on routes within their scopes, see
+/// [`crate::middleware::authenticate_app_token`].
 pub struct Authn(pub User);
 
 impl Deref for Authn {
@@ -25,28 +28,38 @@ impl FromRequestParts<AppState> for Authn {
     parts: &mut Parts,
     state: &AppState,
   ) -> Result<Self, Self::Rejection> {
-    let jar = parts
-      .extract::<CookieJar>()
-      .await
-      .map_err(|_| AppError::Authentication)?;
-
-    let session_cookie = jar
-      .get(&state.config.session_cookie_name)
-      .ok_or(AppError::Authentication)?;
-    let token = session_cookie.value();
-
-    let session = state
-      .session_service
-      .get_session(token)
-      .await?
-      .ok_or(AppError::Authentication)?;
-
-    let user = state
-      .user_service
-      .get_by_id(session.user_id)
-      .await?
-      .ok_or(AppError::Authentication)?;
-
-    Ok(Authn(user))
+    Ok(Authn(principal(parts, state).await?.user))
   }
 }
+
+/// The signed-in user with what they may do, looked up once per request
+/// however many extractors ask.
+pub(crate) async fn principal(parts: &mut Parts, state: &AppState) -> Result<Principal, ApiError> {
+  if let Some(principal) = parts.extensions.get::<Principal>() {
+    return Ok(principal.clone());
+  }
+
+  let principal = match parts.extensions.get::<AccessToken>() {
+    Some(access_token) => state.user_service.principal(access_token.user_id).await?,
+    None => {
+      let jar = parts
+        .extract::<CookieJar>()
+        .await
+        .map_err(|_| AppError::Authentication)?;
+
+      let session_cookie = jar
+        .get(&state.config.session_cookie_name)
+        .ok_or(AppError::Authentication)?;
+
+      state
+        .session_service
+        .principal(session_cookie.value())
+        .await?
+    }
+  }
+  .ok_or(AppError::Authentication)?;
+
+  parts.extensions.insert(principal.clone());
+
+  Ok(principal)
+}
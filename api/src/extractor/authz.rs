@@ -1,13 +1,45 @@
-use crate::extractor::Authn;
+use crate::extractor::authn::principal;
 use application::{error::AppError, state::AppState};
 use axum::{async_trait, extract::FromRequestParts, http::request::Parts};
-use domain::{Permission, Role, User};
+use domain::{
+  ActorDetails, Permission, PermissionOverride, Role, Shop, User, Wallet, WalletDetails,
+};
 
 use crate::error::ApiError;
 
-pub struct Authz(pub User);
+/// What a shop's owner may do on it without holding the permission, as the
+/// shop endpoints allow.
+const SHOP_OWNER_PERMISSIONS: &[Permission] = &[
+  Permission::ManageShopMembers,
+  Permission::ManageWebhooks,
+  Permission::ConfigureSettings,
+  Permission::ReadReports,
+];
+
+/// What a wallet's owner may do on it without holding the permission, as the
+/// wallet endpoints allow. Owners also top up online and request payouts
+/// from their own wallets without any permission, but the cash top-ups and
+/// withdrawals behind the same permissions are for staff only.
+const WALLET_OWNER_PERMISSIONS: &[Permission] = &[Permission::ReadTransactions];
+
+/// What staff reading guest details may do on a guest's wallet without
+/// holding the permission, such as cashiers checking a balance at the till.
+const GUEST_WALLET_PERMISSIONS: &[Permission] = &[Permission::ReadTransactions];
+
+/// The signed-in user together with what they may do: their role's
+/// permissions adjusted by any per-user grants and revocations.
+pub struct Authz(pub User, Vec<Permission>);
 
 impl Authz {
+  pub fn new(user: User, overrides: &[PermissionOverride]) -> Self {
+    let permissions = user.role.permissions_with(overrides);
+    Self(user, permissions)
+  }
+
+  pub fn permissions(&self) -> &[Permission] {
+    &self.1
+  }
+
   pub fn can_assign(&self, target_role: Role) -> Result<(), AppError> {
     if self.0.role.can_assign_role(target_role) {
       Ok(())
@@ -16,8 +48,34 @@ impl Authz {
     }
   }
 
+  pub fn has(&self, perm: Permission) -> bool {
+    self.1.contains(&perm)
+  }
+
+  /// Owners of a shop manage it and read its reports without holding
+  /// `perm`.
+  pub fn has_on_shop(&self, shop: &Shop, perm: Permission) -> bool {
+    (shop.is_owned_by(&self.0.id) && SHOP_OWNER_PERMISSIONS.contains(&perm)) || self.has(perm)
+  }
+
+  /// Owners of a wallet read it without holding `perm`.
+  pub fn has_on_wallet(&self, wallet: &Wallet, perm: Permission) -> bool {
+    (wallet.owner == Some(self.0.actor_id) && WALLET_OWNER_PERMISSIONS.contains(&perm))
+      || self.has(perm)
+  }
+
+  /// Like [`Self::has_on_wallet`], and staff reading guest details also read
+  /// guests' wallets.
+  pub fn has_on_wallet_details(&self, details: &WalletDetails, perm: Permission) -> bool {
+    let guest_owned = matches!(details.owner, Some(ActorDetails::Guest(_)));
+    (guest_owned
+      && self.has(Permission::ReadGuestDetails)
+      && GUEST_WALLET_PERMISSIONS.contains(&perm))
+      || self.has_on_wallet(&details.wallet, perm)
+  }
+
   pub fn require(&self, perm: Permission) -> Result<(), AppError> {
-    if self.0.role.has_permission(perm) {
+    if self.has(perm) {
       Ok(())
     } else {
       Err(AppError::Authorization)
@@ -25,7 +83,7 @@ impl Authz {
   }
 
   pub fn require_any(&self, perms: &[Permission]) -> Result<(), AppError> {
-    if perms.iter().any(|p| self.0.role.has_permission(*p)) {
+    if perms.iter().any(|p| self.has(*p)) {
       Ok(())
     } else {
       Err(AppError::Authorization)
@@ -33,7 +91,7 @@ impl Authz {
   }
 
   pub fn require_all(&self, perms: &[Permission]) -> Result<(), AppError> {
-    if perms.iter().all(|p| self.0.role.has_permission(*p)) {
+    if perms.iter().all(|p| self.has(*p)) {
       Ok(())
     } else {
       Err(AppError::Authorization)
@@ -49,55 +107,43 @@ impl FromRequestParts<AppState> for Authz {
     parts: &mut Parts,
     state: &AppState,
   ) -> Result<Self, Self::Rejection> {
-    let user = Authn::from_request_parts(parts, state).await?.0;
-    Ok(Authz(user))
+    let principal = principal(parts, state).await?;
+
+    Ok(Authz::new(principal.user, &principal.overrides))
   }
 }
 
 #[cfg(test)]
 mod tests {
   use super::*;
+  use crate::test_support::create_user;
   use chrono::Utc;
-  use domain::{Email, HashedPassword, Id};
-
-  fn create_user(role: Role) -> User {
-    User {
-      id: Id::new(),
-      actor_id: Id::new(),
-      email: Email::new("test@example.com".to_string()),
-      password: HashedPassword::new("hash".to_string()),
-      first_name: "Test".to_string(),
-      last_name: "User".to_string(),
-      role,
-      created_at: Utc::now(),
-      updated_at: None,
-    }
-  }
+  use domain::{types::Money, Currency, Guest, Id, WalletAppearance};
 
   #[test]
   fn test_authz_can_assign() {
-    let owner = Authz(create_user(Role::Owner));
+    let owner = Authz::new(create_user(Role::Owner), &[]);
     assert!(owner.can_assign(Role::Admin).is_ok());
     assert!(owner.can_assign(Role::Owner).is_ok());
 
-    let admin = Authz(create_user(Role::Admin));
+    let admin = Authz::new(create_user(Role::Admin), &[]);
     assert!(admin.can_assign(Role::Admin).is_ok());
     assert!(admin.can_assign(Role::Owner).is_err());
   }
 
   #[test]
   fn test_authz_require() {
-    let owner = Authz(create_user(Role::Owner));
+    let owner = Authz::new(create_user(Role::Owner), &[]);
     assert!(owner.require(Permission::SendInvite).is_ok());
 
-    let admin = Authz(create_user(Role::Admin));
+    let admin = Authz::new(create_user(Role::Admin), &[]);
     assert!(admin.require(Permission::SendInvite).is_ok());
     assert!(admin.require(Permission::ConfigureSettings).is_err());
   }
 
   #[test]
   fn test_authz_require_any() {
-    let admin = Authz(create_user(Role::Admin));
+    let admin = Authz::new(create_user(Role::Admin), &[]);
     assert!(admin
       .require_any(&[Permission::SendInvite, Permission::ConfigureSettings])
       .is_ok());
@@ -106,15 +152,139 @@ mod tests {
 
   #[test]
   fn test_authz_require_all() {
-    let owner = Authz(create_user(Role::Owner));
+    let owner = Authz::new(create_user(Role::Owner), &[]);
     assert!(owner
       .require_all(&[Permission::SendInvite, Permission::ConfigureSettings])
       .is_ok());
 
-    let admin = Authz(create_user(Role::Admin));
+    let admin = Authz::new(create_user(Role::Admin), &[]);
     assert!(admin
       .require_all(&[Permission::SendInvite, Permission::ConfigureSettings])
       .is_err());
     assert!(admin.require_all(&[Permission::SendInvite]).is_ok());
   }
+
+  #[test]
+  fn test_authz_applies_overrides() {
+    let admin = Authz::new(
+      create_user(Role::Admin),
+      &[
+        PermissionOverride {
+          permission: Permission::RefundTransaction,
+          granted: false,
+        },
+        PermissionOverride {
+          permission: Permission::ManageLegalHold,
+          granted: true,
+        },
+      ],
+    );
+
+    assert!(admin.require(Permission::RefundTransaction).is_err());
+    assert!(admin.require(Permission::ManageLegalHold).is_ok());
+    assert!(admin
+      .require_any(&[Permission::RefundTransaction, Permission::TopUpWallet])
+      .is_ok());
+  }
+
+  #[test]
+  fn test_authz_owners_need_no_permission() {
+    let member = Authz::new(create_user(Role::Undefined), &[]);
+    let shop = |owner| Shop {
+      id: Id::new(),
+      owner,
+      name: "Bar".to_string(),
+      wallet_id: Id::new(),
+      pickup_slots: None,
+      created_at: Utc::now(),
+      updated_at: None,
+    };
+    let wallet = |owner| Wallet {
+      id: Id::new(),
+      owner,
+      label: None,
+      currency: Currency::EUR,
+      allow_overdraft: false,
+      legal_hold: false,
+      frozen: false,
+      appearance: WalletAppearance::default(),
+      created_at: Utc::now(),
+      updated_at: None,
+    };
+
+    assert!(member.has_on_shop(&shop(Some(member.0.id)), Permission::ManageWebhooks));
+    assert!(member.has_on_shop(&shop(Some(member.0.id)), Permission::ReadReports));
+    assert!(!member.has_on_shop(&shop(None), Permission::ManageWebhooks));
+    assert!(member.has_on_wallet(
+      &wallet(Some(member.0.actor_id)),
+      Permission::ReadTransactions
+    ));
+    assert!(!member.has_on_wallet(&wallet(Some(Id::new())), Permission::ReadTransactions));
+
+    let auditor = Authz::new(create_user(Role::Auditor), &[]);
+    assert!(auditor.has_on_wallet(&wallet(None), Permission::ReadTransactions));
+  }
+
+  #[test]
+  fn test_authz_owners_need_permission_for_staff_actions() {
+    let member = Authz::new(create_user(Role::Undefined), &[]);
+    let wallet = Wallet {
+      id: Id::new(),
+      owner: Some(member.0.actor_id),
+      label: None,
+      currency: Currency::EUR,
+      allow_overdraft: false,
+      legal_hold: false,
+      frozen: false,
+      appearance: WalletAppearance::default(),
+      created_at: Utc::now(),
+      updated_at: None,
+    };
+
+    // Cash top-ups, withdrawals and legal holds check the permission alone
+    assert!(!member.has_on_wallet(&wallet, Permission::TopUpWallet));
+    assert!(!member.has_on_wallet(&wallet, Permission::WithdrawFromWallet));
+    assert!(!member.has_on_wallet(&wallet, Permission::ManageLegalHold));
+  }
+
+  #[test]
+  fn test_authz_guest_readers_read_guest_wallets() {
+    let cashier = Authz::new(create_user(Role::Cashier), &[]);
+    let guest = Guest {
+      id: Id::new(),
+      actor_id: Id::new(),
+      email: None,
+      verified: false,
+      identifier: Some("04A224B2C35E80".to_string()),
+      created_at: Utc::now(),
+      updated_at: None,
+    };
+    let details = |owner| WalletDetails {
+      wallet: Wallet {
+        id: Id::new(),
+        owner: Some(guest.actor_id),
+        label: None,
+        currency: Currency::EUR,
+        allow_overdraft: false,
+        legal_hold: false,
+        frozen: false,
+        appearance: WalletAppearance::default(),
+        created_at: Utc::now(),
+        updated_at: None,
+      },
+      owner,
+      balance: Money::from_minor(1000),
+    };
+
+    let guest_wallet = details(Some(ActorDetails::Guest(guest.clone())));
+    assert!(cashier.has_on_wallet_details(&guest_wallet, Permission::ReadTransactions));
+    assert!(!cashier.has_on_wallet_details(&guest_wallet, Permission::WithdrawFromWallet));
+
+    let user_wallet = details(Some(ActorDetails::User(create_user(Role::Undefined))));
+    assert!(!cashier.has_on_wallet_details(&user_wallet, Permission::ReadTransactions));
+    assert!(!cashier.has_on_wallet_details(&details(None), Permission::ReadTransactions));
+
+    let nobody = Authz::new(create_user(Role::Undefined), &[]);
+    assert!(!nobody.has_on_wallet_details(&guest_wallet, Permission::ReadTransactions));
+  }
 }
@@ -0,0 +1,67 @@
+use axum::{async_trait, extract::FromRequestParts, http::request::Parts, RequestPartsExt};
+use axum_extra::extract::CookieJar;
+
+use application::{error::AppError, state::AppState};
+
+use crate::error::ApiError;
+
+/// Header carrying the nonce issued by `POST /api/auth/device-nonce`.
+pub const DEVICE_NONCE_HEADER: &str = "x-device-nonce";
+/// Header carrying base64 of the device's DER-encoded ECDSA P-256 signature
+/// over the nonce.
+pub const DEVICE_SIGNATURE_HEADER: &str = "x-device-signature";
+
+/// Proof that a sensitive request comes from the device its session is bound
+/// to. Sessions not bound to a device, and client apps' access tokens, pass
+/// without one.
+pub struct DeviceProof;
+
+#[async_trait]
+impl FromRequestParts<AppState> for DeviceProof {
+  type Rejection = ApiError;
+
+  async fn from_request_parts(
+    parts: &mut Parts,
+    state: &AppState,
+  ) -> Result<Self, Self::Rejection> {
+    let jar = parts
+      .extract::<CookieJar>()
+      .await
+      .map_err(|_| AppError::Authentication)?;
+    let Some(session_token) = jar
+      .get(&state.config.session_cookie_name)
+      .map(|c| c.value().to_string())
+    else {
+      return Ok(DeviceProof);
+    };
+
+    let session = state
+      .session_service
+      .get_session(&session_token)
+      .await?
+      .ok_or(AppError::Authentication)?;
+    if !session.is_device_bound() {
+      return Ok(DeviceProof);
+    }
+
+    let header = |name: &str| {
+      parts
+        .headers
+        .get(name)
+        .and_then(|v| v.to_str().ok())
+        .ok_or(AppError::DeviceSignatureRequired)
+    };
+    let nonce = header(DEVICE_NONCE_HEADER)?;
+    let signature = header(DEVICE_SIGNATURE_HEADER)?;
+
+    if !state
+      .session_service
+      .verify_device_signature(&session_token, nonce, signature)
+      .await?
+    {
+      return Err(AppError::DeviceSignatureRequired.into());
+    }
+
+    Ok(DeviceProof)
+  }
+}
@@ -1,7 +1,15 @@
+pub mod audit;
 pub mod authn;
 pub mod authz;
+pub mod device_proof;
+pub mod step_up;
+pub mod tx;
 pub mod validated_json;
 
+pub use audit::Audit;
 pub use authn::Authn;
 pub use authz::Authz;
+pub use device_proof::DeviceProof;
+pub use step_up::StepUp;
+pub use tx::Tx;
 pub use validated_json::ValidatedJson;
@@ -0,0 +1,66 @@
+use axum::{async_trait, extract::FromRequestParts, http::request::Parts, RequestPartsExt};
+use axum_extra::extract::CookieJar;
+use std::ops::Deref;
+
+use application::{error::AppError, state::AppState};
+use domain::User;
+
+use crate::{
+  error::ApiError,
+  extractor::{Authn, DeviceProof},
+};
+
+/// Header carrying the token issued by `POST /api/auth/verify-password`.
+pub const CONFIRMATION_TOKEN_HEADER: &str = "x-confirmation-token";
+
+/// Authenticated user who recently re-entered their password in this session,
+/// on the session's device if it is bound to one.
+pub struct StepUp(pub User);
+
+impl Deref for StepUp {
+  type Target = User;
+
+  fn deref(&self) -> &Self::Target {
+    &self.0
+  }
+}
+
+#[async_trait]
+impl FromRequestParts<AppState> for StepUp {
+  type Rejection = ApiError;
+
+  async fn from_request_parts(
+    parts: &mut Parts,
+    state: &AppState,
+  ) -> Result<Self, Self::Rejection> {
+    let user = Authn::from_request_parts(parts, state).await?.0;
+
+    let jar = parts
+      .extract::<CookieJar>()
+      .await
+      .map_err(|_| AppError::Authentication)?;
+    let session_token = jar
+      .get(&state.config.session_cookie_name)
+      .ok_or(AppError::Authentication)?
+      .value()
+      .to_string();
+
+    let confirmation_token = parts
+      .headers
+      .get(CONFIRMATION_TOKEN_HEADER)
+      .and_then(|v| v.to_str().ok())
+      .ok_or(AppError::PasswordConfirmationRequired)?;
+
+    if !state
+      .session_service
+      .is_password_confirmed(&session_token, confirmation_token)
+      .await?
+    {
+      return Err(AppError::PasswordConfirmationRequired.into());
+    }
+
+    DeviceProof::from_request_parts(parts, state).await?;
+
+    Ok(StepUp(user))
+  }
+}
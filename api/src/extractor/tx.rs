@@ -0,0 +1,87 @@
+use std::{
+  ops::{Deref, DerefMut},
+  sync::{Arc, Mutex},
+};
+
+use axum::{async_trait, extract::FromRequestParts, http::request::Parts};
+use sqlx::{PgConnection, Postgres, Transaction};
+
+use crate::error::ApiError;
+use application::{error::AppError, state::AppState};
+
+/// Where a handler's transaction waits between the handler returning and
+/// [`crate::middleware::finish_transaction`] settling it.
+#[derive(Clone, Default)]
+pub struct TxSlot(Arc<Mutex<Option<Transaction<'static, Postgres>>>>);
+
+/// A database transaction spanning the whole request. Pass `&mut tx` to
+/// service and store calls that must succeed or fail together.
+///
+/// Committed once the handler returns a success response; rolled back on
+/// any error response. Handlers with effects outside the database, such as
+/// audit entries, [`Tx::commit`] before them instead. Requires
+/// [`crate::middleware::finish_transaction`].
+pub struct Tx {
+  tx: Option<Transaction<'static, Postgres>>,
+  slot: TxSlot,
+}
+
+impl TxSlot {
+  pub fn take(&self) -> Option<Transaction<'static, Postgres>> {
+    self.0.lock().expect("transaction slot poisoned").take()
+  }
+
+  fn put(&self, tx: Transaction<'static, Postgres>) {
+    *self.0.lock().expect("transaction slot poisoned") = Some(tx);
+  }
+}
+
+impl Tx {
+  pub async fn commit(mut self) -> Result<(), ApiError> {
+    let tx = self.tx.take().expect("transaction is present until drop");
+    tx.commit().await.map_err(AppError::from)?;
+
+    Ok(())
+  }
+}
+
+impl Deref for Tx {
+  type Target = PgConnection;
+
+  fn deref(&self) -> &Self::Target {
+    self.tx.as_ref().expect("transaction is present until drop")
+  }
+}
+
+impl DerefMut for Tx {
+  fn deref_mut(&mut self) -> &mut Self::Target {
+    self.tx.as_mut().expect("transaction is present until drop")
+  }
+}
+
+impl Drop for Tx {
+  fn drop(&mut self) {
+    if let Some(tx) = self.tx.take() {
+      self.slot.put(tx);
+    }
+  }
+}
+
+#[async_trait]
+impl FromRequestParts<AppState> for Tx {
+  type Rejection = ApiError;
+
+  async fn from_request_parts(
+    parts: &mut Parts,
+    state: &AppState,
+  ) -> Result<Self, Self::Rejection> {
+    let Some(slot) = parts.extensions.get::<TxSlot>().cloned() else {
+      tracing::error!("Tx extracted on a route without the transaction middleware");
+      return Err(AppError::InternalServerError.into());
+    };
+
+    let tx = state.pool.begin().await.map_err(AppError::from)?;
+
+    Ok(Tx { tx: Some(tx), slot })
+  }
+}
@@ -1,4 +1,9 @@
-use axum::{async_trait, extract::FromRequest, http::Request, Json};
+use axum::{
+  async_trait,
+  extract::FromRequest,
+  http::{Request, StatusCode},
+  Json,
+};
 use serde::de::DeserializeOwned;
 use validator::Validate;
 
@@ -22,7 +27,10 @@ where
   ) -> Result<Self, Self::Rejection> {
     let Json(value) = Json::<T>::from_request(req, state)
       .await
-      .map_err(|e| AppError::BadRequest(e.to_string()))?;
+      .map_err(|e| match e.status() {
+        StatusCode::PAYLOAD_TOO_LARGE => AppError::PayloadTooLarge,
+        _ => AppError::BadRequest(e.to_string()),
+      })?;
     value
       .validate()
       .map_err(|e| AppError::Validation(e.to_string()))?;
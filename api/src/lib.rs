@@ -1,55 +1,149 @@
+use std::sync::Arc;
+
 use application::AppState;
-use axum::Router;
+use axum::http::Method;
+use axum::{extract::DefaultBodyLimit, Router};
+use cache::CacheClass;
+use route_permissions::Access;
+use routes::Routes;
 use tower_http::trace::TraceLayer;
-use utoipa::openapi::security::{ApiKey, ApiKeyValue, SecurityScheme};
+use utoipa::openapi::security::{ApiKey, ApiKeyValue, HttpAuthScheme, HttpBuilder, SecurityScheme};
 use utoipa::OpenApi;
 use utoipa_swagger_ui::SwaggerUi;
 
+pub mod cache;
+pub mod conditional;
 pub mod endpoints;
 pub mod error;
+pub mod export;
 pub mod extractor;
+pub mod middleware;
 pub mod models;
+pub mod route_permissions;
+pub mod routes;
+#[cfg(test)]
+mod test_support;
+
+use endpoints::{
+  admin, audit, auth, balance_alert, chargeback, client_app, debt, event_log, favorite, guest,
+  health, import, invites, order, owner_transfer, payout, psp, report, retention, review, settings,
+  shop, stream, transaction, transfer, user, wallet, webhook,
+};
+
+/// A feature of the API, whose routes and docs are mounted together so
+/// neither can be left out.
+struct Feature {
+  /// Where its routes are nested under the API root; merged in at the root
+  /// if empty
+  mount: &'static str,
+  router: fn() -> Routes,
+  openapi: fn() -> utoipa::openapi::OpenApi,
+}
+
+macro_rules! feature {
+  ($module:ident) => {
+    feature!($module, "")
+  };
+  ($module:ident, $mount:literal) => {
+    Feature {
+      mount: $mount,
+      router: $module::router,
+      openapi: $module::openapi,
+    }
+  };
+}
 
-use endpoints::{auth, guest, health, invites, user};
+const FEATURES: &[Feature] = &[
+  feature!(health),
+  feature!(auth, "/auth"),
+  feature!(invites, "/invites"),
+  feature!(user, "/users"),
+  feature!(owner_transfer, "/owner-transfers"),
+  feature!(guest, "/guests"),
+  feature!(wallet, "/wallets"),
+  feature!(transaction, "/transactions"),
+  feature!(transfer, "/transfers"),
+  feature!(import, "/imports"),
+  feature!(payout, "/payouts"),
+  feature!(psp, "/psp"),
+  feature!(chargeback, "/chargebacks"),
+  feature!(debt, "/debts"),
+  feature!(balance_alert, "/balance-alerts"),
+  feature!(retention, "/retention"),
+  feature!(settings, "/settings"),
+  feature!(shop, "/shops"),
+  feature!(webhook, "/webhooks"),
+  feature!(favorite, "/favorites"),
+  feature!(client_app),
+  feature!(order),
+  feature!(report),
+  feature!(stream),
+  feature!(event_log),
+  feature!(review, "/reviews"),
+  feature!(audit, "/audit"),
+  feature!(admin, "/admin"),
+];
 
+/// Schemas shared between features, and the document the features' parts
+/// are merged into.
 #[derive(OpenApi)]
 #[openapi(
-    paths(
-        health::health_check,
-        auth::login,
-        auth::me,
-        invites::create_invite,
-        invites::accept_invite,
-        invites::get_invites,
-        user::list_users,
-        guest::list_guests,
-    ),
     components(
         schemas(
             crate::error::ErrorResponse,
+            crate::error::ErrorCode,
             domain::Id<()>,
             domain::Email,
             domain::RawPassword,
             domain::HashedPassword,
             domain::Role,
-            domain::InviteStatus,
+            domain::Permission,
+            domain::Locale,
+            domain::Currency,
+            domain::Scope,
+            domain::WalletLabel,
+            domain::WebhookEvent,
+            domain::ActorKind,
+            domain::DuplicateReason,
+            domain::types::SortOrder,
+            domain::types::Money,
+            models::AmountDisplay,
             models::UserResponse,
-            models::GuestResponse,
-            models::HealthResponse,
-            models::LoginRequest,
-            models::InviteRequest,
-            models::InviteResponse,
-            models::AcceptInviteRequest,
+            models::AccountNoteRequest,
+            models::AccountNoteResponse,
+            models::TransactionResponse,
+            models::ExportFormat,
+            models::PayoutResponse,
+            domain::PayoutStatus,
+            domain::Iban,
+            domain::TransactionMetadata,
+            models::CreateWebhookRequest,
+            models::WebhookResponse,
+            models::CreatedWebhookResponse,
+            models::DuplicateCandidateResponse,
         )
     ),
     tags(
         (name = "cayopay-server", description = "Cayopay Server API")
     )
 )]
+struct SharedDoc;
+
 pub struct ApiDoc;
 
+impl OpenApi for ApiDoc {
+  fn openapi() -> utoipa::openapi::OpenApi {
+    let mut openapi = SharedDoc::openapi();
+    for feature in FEATURES {
+      openapi.merge((feature.openapi)());
+    }
+
+    openapi
+  }
+}
+
 impl ApiDoc {
-  pub fn new(state: &AppState) -> utoipa::openapi::OpenApi {
+  pub fn build(state: &AppState) -> utoipa::openapi::OpenApi {
     let mut openapi = ApiDoc::openapi();
 
     if let Some(components) = openapi.components.as_mut() {
@@ -59,25 +153,260 @@ impl ApiDoc {
           state.config.session_cookie_name.clone(),
         ))),
       );
+      components.add_security_scheme(
+        "confirmation_token",
+        SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::with_description(
+          extractor::step_up::CONFIRMATION_TOKEN_HEADER,
+          "Step-up token from POST /api/auth/verify-password",
+        ))),
+      );
+      components.add_security_scheme(
+        "app_token",
+        SecurityScheme::Http(
+          HttpBuilder::new()
+            .scheme(HttpAuthScheme::Bearer)
+            .description(Some(
+              "Client app access token from POST /api/oauth/token, limited to the routes of its scopes",
+            ))
+            .build(),
+        ),
+      );
     }
 
+    route_permissions::annotate(&mut openapi);
+
     openapi
   }
 }
 
-pub fn router(state: AppState) -> Router {
-  let openapi = ApiDoc::new(&state);
+/// Where the API is served
+const API_ROOT: &str = "/api";
+/// Where the sandbox's copy of the API is served
+const SANDBOX_ROOT: &str = "/api/sandbox";
 
-  let api_router = Router::new()
-    .merge(health::router())
-    .nest("/auth", auth::router())
-    .nest("/invites", invites::router())
-    .nest("/users", user::router())
-    .nest("/guests", guest::router());
+/// A matched route relative to the API root, so it is the same in the
+/// sandbox.
+fn api_route(matched_path: &str) -> Option<&str> {
+  matched_path
+    .strip_prefix(SANDBOX_ROOT)
+    .or_else(|| matched_path.strip_prefix(API_ROOT))
+}
+
+/// The application's routes. With a sandbox state, a second copy of the
+/// API runs on it under [`SANDBOX_ROOT`].
+pub fn router(state: AppState, sandbox: Option<AppState>) -> Router {
+  let openapi = ApiDoc::build(&state);
+  route_permissions::check(&openapi);
+
+  let mut app =
+    Router::new().merge(SwaggerUi::new("/api/docs").url("/api/docs/openapi.json", openapi));
 
-  Router::new()
-    .merge(SwaggerUi::new("/api/docs").url("/api/docs/openapi.json", openapi))
-    .nest("/api", api_router)
+  if let Some(sandbox) = sandbox {
+    app = app.nest_service(SANDBOX_ROOT, api_router(&sandbox).with_state(sandbox));
+  }
+
+  app
+    .nest(API_ROOT, api_router(&state))
+    .layer(middleware::catch_panic_layer())
+    .layer(DefaultBodyLimit::max(state.config.max_request_body_bytes))
+    .layer(axum::middleware::from_fn(middleware::wrap_error_responses))
+    .layer(middleware::compression_layer(&state.config))
+    .layer(axum::middleware::from_fn_with_state(
+      state.clone(),
+      middleware::scope_locale,
+    ))
+    .layer(axum::middleware::from_fn(middleware::scope_request_id))
     .layer(TraceLayer::new_for_http())
     .with_state(state)
 }
+
+/// The routes of every feature, relative to the API root.
+fn feature_routes() -> Router<AppState> {
+  FEATURES.iter().fold(Router::new(), |router, feature| {
+    let routes = (feature.router)().into_router();
+    match feature.mount {
+      "" => router.merge(routes),
+      mount => router.nest(mount, routes),
+    }
+  })
+}
+
+/// Every route [`feature_routes`] mounts, with its method, axum path
+/// relative to the API root, e.g. `/users/:user_id`, and who may call it.
+pub(crate) fn mounted_routes() -> Vec<(Method, String, Access)> {
+  FEATURES
+    .iter()
+    .flat_map(|feature| {
+      (feature.router)()
+        .entries()
+        .iter()
+        .map(|entry| {
+          let path = match entry.path {
+            "/" if !feature.mount.is_empty() => feature.mount.to_string(),
+            path => format!("{}{}", feature.mount, path),
+          };
+          (entry.method.clone(), path, entry.access)
+        })
+        .collect::<Vec<_>>()
+    })
+    .collect()
+}
+
+/// A route relative to the API root as the spec keys it, e.g.
+/// `/users/:user_id` becomes `/api/users/{user_id}`.
+pub(crate) fn spec_path(route: &str) -> String {
+  let path = route
+    .split('/')
+    .map(|segment| match segment.strip_prefix(':') {
+      Some(param) => format!("{{{param}}}"),
+      None => segment.to_string(),
+    })
+    .collect::<Vec<_>>()
+    .join("/");
+
+  format!("{API_ROOT}{path}")
+}
+
+fn api_router(state: &AppState) -> Router<AppState> {
+  let api_router = feature_routes()
+    .layer(axum::middleware::from_fn(middleware::finish_transaction))
+    .layer(axum::middleware::from_fn_with_state(
+      state.clone(),
+      middleware::hold_shutdown,
+    ))
+    .layer(axum::middleware::from_fn_with_state(
+      state.clone(),
+      middleware::enforce_route_guard,
+    ));
+
+  // Inside the access token check, so audited requests already carry the
+  // token their principal is resolved from
+  let api_router = if state.config.audit_request_bodies {
+    api_router.layer(axum::middleware::from_fn_with_state(
+      state.clone(),
+      middleware::audit_request_body,
+    ))
+  } else {
+    api_router
+  };
+
+  // Inside the access token check too, so clients of an app are limited per
+  // user like those signed in by session
+  let api_router = if state.config.rate_limit_enabled {
+    api_router.layer(axum::middleware::from_fn_with_state(
+      Arc::new(middleware::RateLimiter::new(state)),
+      middleware::rate_limit,
+    ))
+  } else {
+    api_router
+  };
+
+  api_router
+    .layer(axum::middleware::from_fn_with_state(
+      state.clone(),
+      middleware::authenticate_app_token,
+    ))
+    .layer(CacheClass::NoStore.layer(&state.config))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::test_support::create_state;
+  use axum::{
+    body::{to_bytes, Body},
+    extract::{MatchedPath, Request},
+    middleware::Next,
+  };
+  use std::collections::BTreeSet;
+  use tower::ServiceExt;
+
+  /// Every mounted method and path is documented, and everything
+  /// documented is mounted.
+  #[test]
+  fn test_mounted_routes_match_the_spec() {
+    let openapi = ApiDoc::openapi();
+
+    let mounted: BTreeSet<(String, String)> = mounted_routes()
+      .into_iter()
+      .map(|(method, route, _)| (method.as_str().to_lowercase(), spec_path(&route)))
+      .collect();
+    let documented: BTreeSet<(String, String)> = openapi
+      .paths
+      .paths
+      .iter()
+      .flat_map(|(path, item)| {
+        item.operations.keys().map(move |method| {
+          let method = serde_json::to_value(method).unwrap();
+          (method.as_str().unwrap().to_string(), path.clone())
+        })
+      })
+      .collect();
+
+    let undocumented: Vec<_> = mounted.difference(&documented).collect();
+    assert!(
+      undocumented.is_empty(),
+      "Mounted but not documented: {:?}",
+      undocumented
+    );
+    let unmounted: Vec<_> = documented.difference(&mounted).collect();
+    assert!(
+      unmounted.is_empty(),
+      "Documented but not mounted: {:?}",
+      unmounted
+    );
+  }
+
+  #[tokio::test]
+  async fn test_mounted_routes_are_reachable() {
+    let router = feature_routes()
+      .route_layer(axum::middleware::from_fn(
+        |path: MatchedPath, _: Request, _: Next| async move { path.as_str().to_string() },
+      ))
+      .with_state(create_state());
+
+    for (method, route, _) in mounted_routes() {
+      let uri = route
+        .split('/')
+        .map(|segment| {
+          if segment.starts_with(':') {
+            "x"
+          } else {
+            segment
+          }
+        })
+        .collect::<Vec<_>>()
+        .join("/");
+      let request = Request::builder()
+        .method(method.clone())
+        .uri(&uri)
+        .body(Body::empty())
+        .unwrap();
+      let response = router.clone().oneshot(request).await.unwrap();
+      let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+
+      assert_eq!(body, route, "{} {} reaches another route", method, route);
+    }
+  }
+
+  #[test]
+  fn test_every_referenced_schema_is_registered() {
+    let openapi = serde_json::to_value(ApiDoc::openapi()).unwrap();
+    let schemas = &openapi["components"]["schemas"];
+
+    let mut pending = vec![&openapi];
+    while let Some(value) = pending.pop() {
+      match value {
+        serde_json::Value::Object(object) => {
+          if let Some(reference) = object.get("$ref").and_then(|r| r.as_str()) {
+            let name = reference.trim_start_matches("#/components/schemas/");
+            assert!(schemas.get(name).is_some(), "{} is not registered", name);
+          }
+          pending.extend(object.values());
+        }
+        serde_json::Value::Array(items) => pending.extend(items),
+        _ => {}
+      }
+    }
+  }
+}
@@ -0,0 +1,155 @@
+use axum::{
+  extract::{MatchedPath, Request, State},
+  http::{header, Method},
+  middleware::Next,
+  response::{IntoResponse, Response},
+};
+
+use application::{error::AppError, state::AppState};
+use domain::Scope;
+
+use crate::{api_route, error::ApiError};
+
+/// Routes client apps may call with an access token, relative to the API
+/// root, and the scope each needs. Tokens are refused everywhere else,
+/// including account settings and approving apps.
+pub const ROUTE_SCOPES: &[(Method, &str, Scope)] = &[
+  (Method::GET, "/auth/me", Scope::Profile),
+  (Method::GET, "/wallets/:wallet_id", Scope::Wallet),
+  (Method::GET, "/wallets/:wallet_id/envelopes", Scope::Wallet),
+  (Method::GET, "/favorites", Scope::Wallet),
+  (Method::GET, "/orders/:order_id/events", Scope::Wallet),
+  (Method::GET, "/stream", Scope::Wallet),
+  (Method::GET, "/events/poll", Scope::Wallet),
+  (Method::GET, "/events/log", Scope::Events),
+  (Method::POST, "/shops/:shop_id/preorders", Scope::Preorders),
+  (
+    Method::GET,
+    "/shops/:shop_id/pickup-slots",
+    Scope::Preorders,
+  ),
+  (Method::PUT, "/favorites/:offering_id", Scope::Preorders),
+  (Method::DELETE, "/favorites/:offering_id", Scope::Preorders),
+  (Method::POST, "/orders/:order_id/reorder", Scope::Preorders),
+  (Method::POST, "/transfers", Scope::Transfers),
+  (Method::GET, "/guests", Scope::Till),
+  (Method::POST, "/guests", Scope::Till),
+  (
+    Method::GET,
+    "/guests/by-identifier/:identifier",
+    Scope::Till,
+  ),
+  (Method::PUT, "/guests/:guest_id/identifier", Scope::Till),
+  (Method::POST, "/wallets/:wallet_id/topup", Scope::Till),
+  (Method::POST, "/shops/:shop_id/orders", Scope::Orders),
+  (Method::GET, "/shops/:shop_id/queue", Scope::Orders),
+  (Method::GET, "/orders", Scope::Orders),
+  (Method::GET, "/orders/:order_id", Scope::Orders),
+  (Method::GET, "/orders/:order_id/purchaser", Scope::Orders),
+  (Method::POST, "/orders/:order_id/status", Scope::Orders),
+  (Method::POST, "/orders/:order_id/payment", Scope::Orders),
+];
+
+/// The scope an access token needs for the route, if tokens may call it.
+pub fn required_scope(method: &Method, matched_path: Option<&str>) -> Option<Scope> {
+  let route = matched_path.and_then(api_route)?;
+
+  ROUTE_SCOPES
+    .iter()
+    .find(|(m, p, _)| m == method && *p == route)
+    .map(|(_, _, scope)| *scope)
+}
+
+/// Authenticates requests sent with `Authorization: Bearer` and a client
+/// app's access token, refusing those outside the token's scopes. The token
+/// is left in the request extensions for [`crate::extractor::Authn`].
+pub async fn authenticate_app_token(
+  State(state): State<AppState>,
+  mut request: Request,
+  next: Next,
+) -> Response {
+  let Some(authorization) = request.headers().get(header::AUTHORIZATION) else {
+    return next.run(request).await;
+  };
+  let Some(token) = authorization
+    .to_str()
+    .ok()
+    .and_then(|value| value.strip_prefix("Bearer "))
+  else {
+    return ApiError(AppError::Authentication).into_response();
+  };
+
+  let access_token = match state.client_app_service.authenticate(token).await {
+    Ok(Some(access_token)) => access_token,
+    Ok(None) => return ApiError(AppError::Authentication).into_response(),
+    Err(e) => return ApiError(e).into_response(),
+  };
+
+  let matched_path = request
+    .extensions()
+    .get::<MatchedPath>()
+    .map(MatchedPath::as_str);
+  let in_scope = required_scope(request.method(), matched_path)
+    .is_some_and(|scope| access_token.has_scope(scope));
+  if !in_scope {
+    return ApiError(AppError::Authorization).into_response();
+  }
+
+  request.extensions_mut().insert(access_token);
+
+  next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    route_permissions::{route_access, Access},
+    spec_path,
+  };
+  use utoipa::openapi::PathItemType;
+
+  fn spec_method(method: &Method) -> PathItemType {
+    match *method {
+      Method::GET => PathItemType::Get,
+      Method::POST => PathItemType::Post,
+      Method::PUT => PathItemType::Put,
+      Method::PATCH => PathItemType::Patch,
+      Method::DELETE => PathItemType::Delete,
+      _ => unreachable!("no scoped route uses {}", method),
+    }
+  }
+
+  #[test]
+  fn test_every_scoped_route_is_a_guarded_operation() {
+    for (method, route, _) in ROUTE_SCOPES {
+      let path = spec_path(route);
+      let guarded = route_access().iter().any(|(m, p, access)| {
+        *m == spec_method(method) && *p == path && matches!(access, Access::Guarded(_))
+      });
+
+      assert!(guarded, "{} {} is scoped but not guarded", method, path);
+    }
+  }
+
+  #[test]
+  fn test_required_scope() {
+    assert_eq!(
+      required_scope(&Method::GET, Some("/api/auth/me")),
+      Some(Scope::Profile)
+    );
+    assert_eq!(
+      required_scope(&Method::POST, Some("/api/sandbox/wallets/:wallet_id/topup")),
+      Some(Scope::Till)
+    );
+    assert_eq!(
+      required_scope(&Method::GET, Some("/api/auth/sessions")),
+      None
+    );
+    assert_eq!(
+      required_scope(&Method::POST, Some("/api/oauth/authorize")),
+      None
+    );
+    assert_eq!(required_scope(&Method::GET, None), None);
+  }
+}
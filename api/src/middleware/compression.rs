@@ -0,0 +1,32 @@
+use axum::http::{header, Extensions, HeaderMap, StatusCode, Version};
+use tower_http::compression::{
+  predicate::{Predicate, SizeAbove},
+  CompressionLayer,
+};
+
+use application::config::Config;
+
+/// Content types worth compressing; images are compressed already and event
+/// streams must reach clients as they are written.
+const COMPRESSIBLE: &[&str] = &["application/json", "text/csv"];
+
+/// Compresses JSON and CSV responses, e.g. long listings and exports, with
+/// gzip or brotli as the client accepts. Streamed bodies are compressed as
+/// they go. Turned off by `COMPRESSION_ENABLED=false`.
+pub fn compression_layer(config: &Config) -> CompressionLayer<impl Predicate> {
+  let enabled = config.compression_enabled;
+  let predicate = SizeAbove::new(config.compression_min_bytes).and(
+    move |_: StatusCode, _: Version, headers: &HeaderMap, _: &Extensions| {
+      enabled && is_compressible(headers)
+    },
+  );
+
+  CompressionLayer::new().compress_when(predicate)
+}
+
+fn is_compressible(headers: &HeaderMap) -> bool {
+  headers
+    .get(header::CONTENT_TYPE)
+    .and_then(|value| value.to_str().ok())
+    .is_some_and(|value| COMPRESSIBLE.iter().any(|t| value.starts_with(t)))
+}
@@ -0,0 +1,22 @@
+use axum::{
+  extract::{Request, State},
+  middleware::Next,
+  response::Response,
+};
+
+use application::state::AppState;
+
+/// Keeps a graceful shutdown waiting until mutations under way, such as
+/// payments and top-ups, have committed and answered. Reads are cut off.
+pub async fn hold_shutdown(
+  State(state): State<AppState>,
+  request: Request,
+  next: Next,
+) -> Response {
+  if request.method().is_safe() {
+    return next.run(request).await;
+  }
+
+  let _in_flight = state.shutdown.track();
+  next.run(request).await
+}
@@ -0,0 +1,114 @@
+use axum::{
+  body::{to_bytes, Body},
+  extract::Request,
+  http::{header, HeaderValue, StatusCode},
+  middleware::Next,
+  response::Response,
+};
+
+use crate::error::{ErrorCode, ErrorResponse};
+
+/// Longest plain-text error body kept as the message
+const MAX_MESSAGE_BYTES: usize = 4096;
+
+/// Oversized bodies are cut off wherever they are read, each with its own
+/// wording, so callers get this instead
+const PAYLOAD_TOO_LARGE_MESSAGE: &str = "Request body exceeds the size limit";
+
+/// Wraps error responses produced outside the handlers, such as extractor
+/// rejections and unknown routes, in the [`ErrorResponse`] envelope the
+/// handlers use. Must sit inside [`crate::middleware::scope_request_id`].
+pub async fn wrap_error_responses(request: Request, next: Next) -> Response {
+  let response = next.run(request).await;
+
+  let status = response.status();
+  if !(status.is_client_error() || status.is_server_error()) || is_json(&response) {
+    return response;
+  }
+
+  let (mut parts, body) = response.into_parts();
+  let message = if status == StatusCode::PAYLOAD_TOO_LARGE {
+    PAYLOAD_TOO_LARGE_MESSAGE.to_string()
+  } else {
+    to_bytes(body, MAX_MESSAGE_BYTES)
+      .await
+      .ok()
+      .map(|bytes| String::from_utf8_lossy(&bytes).trim().to_string())
+      .filter(|message| !message.is_empty())
+      .or_else(|| status.canonical_reason().map(ToString::to_string))
+      .unwrap_or_default()
+  };
+
+  let body = serde_json::to_vec(&ErrorResponse::new(
+    ErrorCode::from_status(status),
+    message,
+    None,
+  ))
+  .expect("error response serializes");
+
+  parts.headers.remove(header::CONTENT_LENGTH);
+  parts.headers.insert(
+    header::CONTENT_TYPE,
+    HeaderValue::from_static("application/json"),
+  );
+
+  Response::from_parts(parts, Body::from(body))
+}
+
+fn is_json(response: &Response) -> bool {
+  response
+    .headers()
+    .get(header::CONTENT_TYPE)
+    .and_then(|value| value.to_str().ok())
+    .is_some_and(|value| value.starts_with("application/json"))
+}
+
+#[cfg(test)]
+mod tests {
+  use axum::{
+    body::to_bytes,
+    extract::DefaultBodyLimit,
+    routing::{get, post},
+    Router,
+  };
+  use tower::ServiceExt;
+
+  use super::*;
+
+  #[tokio::test]
+  async fn test_rejections_get_envelope() {
+    let app = Router::new()
+      .route("/", get(|| async { "ok" }))
+      .layer(axum::middleware::from_fn(wrap_error_responses));
+
+    let response = app
+      .oneshot(Request::post("/").body(Body::empty()).unwrap())
+      .await
+      .unwrap();
+
+    assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let error: ErrorResponse = serde_json::from_slice(&body).unwrap();
+    assert_eq!(error.code, ErrorCode::MethodNotAllowed);
+    assert_eq!(error.message, "Method Not Allowed");
+  }
+
+  #[tokio::test]
+  async fn test_oversized_bodies_get_clean_message() {
+    let app = Router::new()
+      .route("/", post(|body: String| async move { body }))
+      .layer(DefaultBodyLimit::max(4))
+      .layer(axum::middleware::from_fn(wrap_error_responses));
+
+    let response = app
+      .oneshot(Request::post("/").body(Body::from("too long")).unwrap())
+      .await
+      .unwrap();
+
+    assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let error: ErrorResponse = serde_json::from_slice(&body).unwrap();
+    assert_eq!(error.code, ErrorCode::PayloadTooLarge);
+    assert_eq!(error.message, PAYLOAD_TOO_LARGE_MESSAGE);
+  }
+}
@@ -0,0 +1,176 @@
+use axum::{
+  extract::{MatchedPath, Request, State},
+  middleware::Next,
+  response::{IntoResponse, Response},
+};
+
+use application::{error::AppError, state::AppState};
+
+use crate::{
+  api_route, error::ApiError, extractor::authn::principal, route_permissions::guard_for,
+};
+
+/// Refuses what the matched route's guard rules out before the handler runs:
+/// guarded routes need a signed-in caller, and guards on permissions alone
+/// need those permissions. Guards involving ownership or shop staff depend on
+/// the addressed resource, which the handler checks once it loaded it. The
+/// principal is left in the extensions for the handler.
+pub async fn enforce_route_guard(
+  State(state): State<AppState>,
+  request: Request,
+  next: Next,
+) -> Response {
+  let guard = request
+    .extensions()
+    .get::<MatchedPath>()
+    .and_then(|path| api_route(path.as_str()))
+    .and_then(|route| guard_for(request.method(), route));
+  let Some(guard) = guard else {
+    return next.run(request).await;
+  };
+
+  let (mut parts, body) = request.into_parts();
+  let principal = match principal(&mut parts, &state).await {
+    Ok(principal) => principal,
+    Err(e) => return e.into_response(),
+  };
+  if guard.is_resource_independent() && !guard.granted_by(&principal.permissions()) {
+    return ApiError(AppError::Authorization).into_response();
+  }
+
+  next.run(Request::from_parts(parts, body)).await
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    feature_routes, mounted_routes,
+    route_permissions::{Access, Guard},
+    test_support::{create_state, create_user},
+    API_ROOT,
+  };
+  use axum::{
+    body::{to_bytes, Body},
+    http::StatusCode,
+    Router,
+  };
+  use domain::{Principal, Role};
+  use tower::ServiceExt;
+
+  /// The API behind the guard, with every handler replaced by one answering
+  /// the route it was reached on.
+  fn guarded_router() -> Router {
+    let state = create_state();
+    let routes = feature_routes()
+      .route_layer(axum::middleware::from_fn(
+        |path: MatchedPath, _: Request, _: Next| async move { path.as_str().to_string() },
+      ))
+      .layer(axum::middleware::from_fn_with_state(
+        state.clone(),
+        enforce_route_guard,
+      ));
+
+    Router::new().nest(API_ROOT, routes).with_state(state)
+  }
+
+  fn request(method: &axum::http::Method, route: &str, role: Option<Role>) -> Request {
+    let uri = route
+      .split('/')
+      .map(|segment| {
+        if segment.starts_with(':') {
+          "x"
+        } else {
+          segment
+        }
+      })
+      .collect::<Vec<_>>()
+      .join("/");
+    let mut request = Request::builder()
+      .method(method.clone())
+      .uri(format!("{API_ROOT}{uri}"))
+      .body(Body::empty())
+      .unwrap();
+    if let Some(role) = role {
+      request.extensions_mut().insert(Principal {
+        user: create_user(role),
+        overrides: vec![],
+      });
+    }
+    request
+  }
+
+  #[tokio::test]
+  async fn test_guarded_routes_refuse_roles_their_guard_rejects() {
+    let router = guarded_router();
+
+    for (method, route, access) in mounted_routes() {
+      let Access::Guarded(guard) = access else {
+        continue;
+      };
+
+      for role in Role::variants().iter().copied().chain([Role::Undefined]) {
+        let response = router
+          .clone()
+          .oneshot(request(&method, &route, Some(role)))
+          .await
+          .unwrap();
+
+        let refused = guard.is_resource_independent() && !guard.allows(role);
+        if refused {
+          assert_eq!(
+            response.status(),
+            StatusCode::FORBIDDEN,
+            "{} {} lets {} through",
+            method,
+            route,
+            role
+          );
+        } else {
+          let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+          assert_eq!(
+            body,
+            format!("{API_ROOT}{route}"),
+            "{} {} refuses {}",
+            method,
+            route,
+            role
+          );
+        }
+      }
+    }
+  }
+
+  #[tokio::test]
+  async fn test_guarded_routes_need_a_signed_in_caller() {
+    let router = guarded_router();
+
+    for (method, route, access) in mounted_routes() {
+      let status = router
+        .clone()
+        .oneshot(request(&method, &route, None))
+        .await
+        .unwrap()
+        .status();
+
+      match access {
+        Access::Guarded(_) => assert_eq!(
+          status,
+          StatusCode::UNAUTHORIZED,
+          "{} {} lets anonymous callers through",
+          method,
+          route
+        ),
+        Access::Public => assert_eq!(status, StatusCode::OK, "{} {}", method, route),
+      }
+    }
+  }
+
+  #[test]
+  fn test_only_permission_guards_are_resource_independent() {
+    assert!(Guard::Authenticated.is_resource_independent());
+    assert!(Guard::All(&[]).is_resource_independent());
+    assert!(!Guard::OwnerOr(&[]).is_resource_independent());
+    assert!(!Guard::ShopStaff.is_resource_independent());
+  }
+}
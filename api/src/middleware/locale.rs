@@ -0,0 +1,108 @@
+use application::state::AppState;
+use axum::{
+  extract::{Request, State},
+  http::{header, HeaderMap, HeaderValue},
+  middleware::Next,
+  response::Response,
+};
+use domain::{Currency, Locale};
+
+tokio::task_local! {
+  static LOCALE: Locale;
+  static CURRENCY: Currency;
+}
+
+/// Locale the response is written in, outside of [`scope_locale`] the
+/// default one.
+pub fn current_locale() -> Locale {
+  LOCALE.try_with(|locale| *locale).unwrap_or_default()
+}
+
+/// Currency of amounts that belong to no wallet in particular, such as
+/// prices and report totals; outside of [`scope_locale`] the default one.
+pub fn current_currency() -> Currency {
+  CURRENCY.try_with(|currency| *currency).unwrap_or_default()
+}
+
+/// Runs `f` in `locale` and `currency`, for writing responses that outlive
+/// the request's scope, such as server-sent events.
+pub fn in_locale<R>(locale: Locale, currency: Currency, f: impl FnOnce() -> R) -> R {
+  LOCALE.sync_scope(locale, || CURRENCY.sync_scope(currency, f))
+}
+
+/// Writes formatted values in the response, such as amounts, in the
+/// caller's preferred language of those `Accept-Language` lists that the
+/// API speaks, and in the venue's currency unless they belong to a wallet.
+pub async fn scope_locale(State(state): State<AppState>, request: Request, next: Next) -> Response {
+  let locale = preferred_locale(request.headers()).unwrap_or_default();
+  let currency = match state.settings_service.get().await {
+    Ok(settings) => settings.currency,
+    Err(e) => {
+      tracing::warn!("Failed to read the venue's currency: {}", e);
+      Currency::default()
+    }
+  };
+
+  let mut response = LOCALE
+    .scope(locale, CURRENCY.scope(currency, next.run(request)))
+    .await;
+  response
+    .headers_mut()
+    .append(header::VARY, HeaderValue::from_static("accept-language"));
+
+  response
+}
+
+/// The supported locale with the highest quality in `Accept-Language`;
+/// earlier ones win ties.
+fn preferred_locale(headers: &HeaderMap) -> Option<Locale> {
+  let header = headers.get(header::ACCEPT_LANGUAGE)?.to_str().ok()?;
+
+  let mut best: Option<(Locale, f32)> = None;
+  for range in header.split(',') {
+    let mut parts = range.split(';').map(str::trim);
+    let tag = parts.next().unwrap_or_default();
+    let quality = parts
+      .find_map(|param| param.strip_prefix("q="))
+      .map_or(Some(1.0), |q| q.parse::<f32>().ok())
+      .unwrap_or(0.0);
+
+    let language = tag.split('-').next().unwrap_or_default();
+    let Some(locale) = Locale::variants()
+      .iter()
+      .copied()
+      .find(|locale| locale.code().eq_ignore_ascii_case(language))
+    else {
+      continue;
+    };
+
+    if quality > 0.0 && !best.is_some_and(|(_, q)| q >= quality) {
+      best = Some((locale, quality));
+    }
+  }
+
+  best.map(|(locale, _)| locale)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn locale_for(accept_language: &str) -> Option<Locale> {
+    let mut headers = HeaderMap::new();
+    headers.insert(
+      header::ACCEPT_LANGUAGE,
+      HeaderValue::from_str(accept_language).unwrap(),
+    );
+    preferred_locale(&headers)
+  }
+
+  #[test]
+  fn test_preferred_locale_weighs_quality() {
+    assert_eq!(locale_for("de-AT"), Some(Locale::De));
+    assert_eq!(locale_for("fr, en;q=0.5, de;q=0.8"), Some(Locale::De));
+    assert_eq!(locale_for("en, de"), Some(Locale::En));
+    assert_eq!(locale_for("de;q=0, fr"), None);
+    assert_eq!(preferred_locale(&HeaderMap::new()), None);
+  }
+}
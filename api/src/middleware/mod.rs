@@ -0,0 +1,23 @@
+pub mod app_token;
+pub mod body_audit;
+pub mod compression;
+pub mod drain;
+pub mod error_envelope;
+pub mod guard;
+pub mod locale;
+pub mod panic;
+pub mod rate_limit;
+pub mod request_id;
+pub mod transaction;
+
+pub use app_token::authenticate_app_token;
+pub use body_audit::audit_request_body;
+pub use compression::compression_layer;
+pub use drain::hold_shutdown;
+pub use error_envelope::wrap_error_responses;
+pub use guard::enforce_route_guard;
+pub use locale::scope_locale;
+pub use panic::catch_panic_layer;
+pub use rate_limit::{rate_limit, RateLimiter};
+pub use request_id::scope_request_id;
+pub use transaction::finish_transaction;
@@ -0,0 +1,104 @@
+use std::{
+  any::Any,
+  sync::atomic::{AtomicU64, Ordering},
+};
+
+use axum::{
+  http::StatusCode,
+  response::{IntoResponse, Response},
+  Json,
+};
+use tower_http::catch_panic::CatchPanicLayer;
+
+use super::request_id::current_request_id;
+use crate::error::{ErrorCode, ErrorResponse};
+
+/// Panics seen anywhere in the process since startup.
+static PANICS: AtomicU64 = AtomicU64::new(0);
+
+pub fn panic_count() -> u64 {
+  PANICS.load(Ordering::Relaxed)
+}
+
+/// Replaces the default panic hook, which only writes to stderr, with one
+/// that logs through tracing and counts the panic. Covers request handlers
+/// as well as spawned tasks.
+pub fn install_hook() {
+  std::panic::set_hook(Box::new(|info| {
+    PANICS.fetch_add(1, Ordering::Relaxed);
+
+    let location = info
+      .location()
+      .map(|l| format!("{}:{}", l.file(), l.line()))
+      .unwrap_or_else(|| "unknown location".to_string());
+    let request_id = current_request_id();
+
+    tracing::error!(
+      request_id = request_id.as_deref(),
+      "Panic at {}: {}",
+      location,
+      panic_message(info.payload())
+    );
+  }));
+}
+
+/// Turns a panicking handler into a 500 response instead of dropping the
+/// connection. Must sit inside [`super::scope_request_id`].
+pub fn catch_panic_layer() -> CatchPanicLayer<fn(Box<dyn Any + Send + 'static>) -> Response> {
+  CatchPanicLayer::custom(panic_response as fn(Box<dyn Any + Send + 'static>) -> Response)
+}
+
+fn panic_response(_payload: Box<dyn Any + Send + 'static>) -> Response {
+  // The hook has already logged the payload
+  (
+    StatusCode::INTERNAL_SERVER_ERROR,
+    Json(ErrorResponse::new(
+      ErrorCode::Internal,
+      "Internal server error".to_string(),
+      None,
+    )),
+  )
+    .into_response()
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+  if let Some(message) = payload.downcast_ref::<&str>() {
+    message
+  } else if let Some(message) = payload.downcast_ref::<String>() {
+    message
+  } else {
+    "<non-string panic payload>"
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use axum::body::to_bytes;
+
+  use super::super::request_id::REQUEST_ID;
+  use super::*;
+  use crate::error::ERROR_FORMAT_VERSION;
+
+  #[test]
+  fn test_panic_message() {
+    assert_eq!(panic_message(&"boom"), "boom");
+    assert_eq!(panic_message(&"boom".to_string()), "boom");
+    assert_eq!(panic_message(&42), "<non-string panic payload>");
+  }
+
+  #[tokio::test]
+  async fn test_panic_response_carries_request_id() {
+    let response = REQUEST_ID
+      .scope("abc".to_string(), async {
+        panic_response(Box::new("boom"))
+      })
+      .await;
+
+    assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let error: ErrorResponse = serde_json::from_slice(&body).unwrap();
+    assert_eq!(error.version, ERROR_FORMAT_VERSION);
+    assert_eq!(error.trace_id.as_deref(), Some("abc"));
+    assert_eq!(error.request_id.as_deref(), Some("abc"));
+  }
+}
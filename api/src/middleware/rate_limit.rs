@@ -0,0 +1,417 @@
+use std::{
+  collections::HashMap,
+  net::SocketAddr,
+  sync::{Arc, Mutex},
+  time::{Duration, Instant},
+};
+
+use axum::{
+  extract::{ConnectInfo, MatchedPath, Request, State},
+  http::{header, request::Parts, HeaderValue, Method},
+  middleware::Next,
+  response::{IntoResponse, Response},
+};
+use tokio::sync::Semaphore;
+
+use application::{error::AppError, state::AppState};
+
+use crate::{api_route, error::ApiError, extractor::authn::principal};
+
+/// Routes driven by point-of-sale terminals, relative to the API root so they
+/// match in the sandbox too. Everything else counts as dashboard traffic.
+const TERMINAL_ROUTES: &[(Method, &str)] = &[
+  (Method::POST, "/shops/:shop_id/orders"),
+  (Method::POST, "/orders/:order_id/payment"),
+  (Method::POST, "/wallets/:wallet_id/topup"),
+  (Method::POST, "/wallets/:wallet_id/withdraw"),
+  (Method::GET, "/guests/by-identifier/:identifier"),
+];
+
+/// Buckets are dropped once they have been idle for this long. Budgets are
+/// per minute, so by then a bucket is full again and dropping it forgets
+/// nothing.
+const BUCKET_IDLE_TTL: Duration = Duration::from_secs(60);
+
+/// How often idle buckets are dropped.
+const PRUNE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Clients tracked at most. Beyond that, new clients share one bucket until
+/// pruning makes room, so memory stays bounded however many addresses or
+/// accounts send requests.
+const MAX_BUCKETS: usize = 100_000;
+
+/// Key of the bucket shared by clients beyond [`MAX_BUCKETS`]
+const OVERFLOW_KEY: &str = "overflow";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TrafficClass {
+  /// Checkout line: larger budget, never queued behind dashboards
+  Terminal,
+  Dashboard,
+}
+
+impl TrafficClass {
+  pub fn classify(method: &Method, matched_path: Option<&str>) -> Self {
+    let route = matched_path.and_then(api_route);
+    let is_terminal = route.is_some_and(|path| {
+      TERMINAL_ROUTES
+        .iter()
+        .any(|(m, p)| m == method && *p == path)
+    });
+
+    if is_terminal {
+      TrafficClass::Terminal
+    } else {
+      TrafficClass::Dashboard
+    }
+  }
+}
+
+/// Token bucket that lets callers borrow against future tokens for up to
+/// `max_delay`, so short bursts are slowed down instead of rejected.
+#[derive(Debug, Clone)]
+struct Bucket {
+  tokens: f64,
+  updated_at: Instant,
+}
+
+impl Bucket {
+  fn new(capacity: f64, now: Instant) -> Self {
+    Self {
+      tokens: capacity,
+      updated_at: now,
+    }
+  }
+
+  /// Takes a token. `Ok` holds how long the caller has to wait before going
+  /// ahead, `Err` how long until a request would be admitted again.
+  fn take(
+    &mut self,
+    now: Instant,
+    capacity: f64,
+    per_second: f64,
+    max_delay: Duration,
+  ) -> Result<Duration, Duration> {
+    let elapsed = now.saturating_duration_since(self.updated_at).as_secs_f64();
+    self.tokens = (self.tokens + elapsed * per_second).min(capacity);
+    self.updated_at = now;
+
+    let remaining = self.tokens - 1.0;
+    let wait = Duration::from_secs_f64((-remaining).max(0.0) / per_second);
+
+    if wait > max_delay {
+      return Err(wait - max_delay);
+    }
+
+    self.tokens = remaining;
+    Ok(wait)
+  }
+}
+
+/// Buckets by client and traffic class.
+#[derive(Debug)]
+struct Buckets {
+  by_client: HashMap<(String, TrafficClass), Bucket>,
+  pruned_at: Instant,
+  max: usize,
+}
+
+impl Buckets {
+  fn new(max: usize, now: Instant) -> Self {
+    Self {
+      by_client: HashMap::new(),
+      pruned_at: now,
+      max,
+    }
+  }
+
+  fn take(
+    &mut self,
+    client: String,
+    class: TrafficClass,
+    now: Instant,
+    capacity: f64,
+    per_second: f64,
+    max_delay: Duration,
+  ) -> Result<Duration, Duration> {
+    if now.saturating_duration_since(self.pruned_at) >= PRUNE_INTERVAL {
+      self
+        .by_client
+        .retain(|_, bucket| now.saturating_duration_since(bucket.updated_at) < BUCKET_IDLE_TTL);
+      self.pruned_at = now;
+    }
+
+    let key = if self.by_client.len() >= self.max
+      && !self.by_client.contains_key(&(client.clone(), class))
+    {
+      (OVERFLOW_KEY.to_string(), class)
+    } else {
+      (client, class)
+    };
+
+    self
+      .by_client
+      .entry(key)
+      .or_insert_with(|| Bucket::new(capacity, now))
+      .take(now, capacity, per_second, max_delay)
+  }
+}
+
+pub struct RateLimiter {
+  /// Resolves who is calling, see [`principal`]
+  state: AppState,
+  terminal_per_minute: u32,
+  dashboard_per_minute: u32,
+  max_delay: Duration,
+  /// Bounds concurrent dashboard requests so they queue up under load while
+  /// terminal requests go straight through
+  dashboard_gate: Semaphore,
+  buckets: Mutex<Buckets>,
+}
+
+impl RateLimiter {
+  pub fn new(state: &AppState) -> Self {
+    let config = &state.config;
+    Self {
+      state: state.clone(),
+      terminal_per_minute: config.rate_limit_terminal_per_minute.max(1),
+      dashboard_per_minute: config.rate_limit_dashboard_per_minute.max(1),
+      max_delay: Duration::from_millis(config.rate_limit_max_delay_ms),
+      dashboard_gate: Semaphore::new(config.rate_limit_dashboard_concurrency.max(1)),
+      buckets: Mutex::new(Buckets::new(MAX_BUCKETS, Instant::now())),
+    }
+  }
+
+  fn acquire(&self, client: String, class: TrafficClass) -> Result<Duration, Duration> {
+    let per_minute = match class {
+      TrafficClass::Terminal => self.terminal_per_minute,
+      TrafficClass::Dashboard => self.dashboard_per_minute,
+    };
+    let capacity = f64::from(per_minute);
+    let per_second = capacity / 60.0;
+
+    self
+      .buckets
+      .lock()
+      .expect("rate limiter lock poisoned")
+      .take(
+        client,
+        class,
+        Instant::now(),
+        capacity,
+        per_second,
+        self.max_delay,
+      )
+  }
+
+  /// Signed-in users are limited per user, whether by session or a client
+  /// app's access token, everyone else per IP. Credentials only count once
+  /// they sign someone in, so made-up ones can't buy fresh budgets. The
+  /// principal is left in the extensions for the handler.
+  async fn client_key(&self, parts: &mut Parts, ip: String) -> String {
+    match principal(parts, &self.state).await {
+      Ok(principal) => format!("user:{}", principal.user.id),
+      Err(_) => ip,
+    }
+  }
+}
+
+/// Soft rate limiting: requests over budget are delayed up to
+/// `RATE_LIMIT_MAX_DELAY_MS` before being rejected with 429. Terminal traffic
+/// has its own, larger budget and skips the dashboard concurrency queue.
+pub async fn rate_limit(
+  State(limiter): State<Arc<RateLimiter>>,
+  request: Request,
+  next: Next,
+) -> Response {
+  let class = TrafficClass::classify(
+    request.method(),
+    request
+      .extensions()
+      .get::<MatchedPath>()
+      .map(MatchedPath::as_str),
+  );
+  let ip = request
+    .extensions()
+    .get::<ConnectInfo<SocketAddr>>()
+    .map(|ConnectInfo(addr)| format!("ip:{}", addr.ip()))
+    .unwrap_or_else(|| "unknown".to_string());
+  let (mut parts, body) = request.into_parts();
+  let client = limiter.client_key(&mut parts, ip).await;
+  let request = Request::from_parts(parts, body);
+
+  match limiter.acquire(client, class) {
+    Ok(wait) if !wait.is_zero() => tokio::time::sleep(wait).await,
+    Ok(_) => {}
+    Err(retry_after) => return too_many_requests(retry_after),
+  }
+
+  match class {
+    TrafficClass::Terminal => next.run(request).await,
+    TrafficClass::Dashboard => {
+      let _permit = limiter.dashboard_gate.acquire().await;
+      next.run(request).await
+    }
+  }
+}
+
+fn too_many_requests(retry_after: Duration) -> Response {
+  let mut response = ApiError(AppError::RateLimited).into_response();
+  let seconds = retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0);
+  response
+    .headers_mut()
+    .insert(header::RETRY_AFTER, HeaderValue::from(seconds.max(1)));
+  response
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_classify() {
+    assert_eq!(
+      TrafficClass::classify(&Method::POST, Some("/api/shops/:shop_id/orders")),
+      TrafficClass::Terminal
+    );
+    assert_eq!(
+      TrafficClass::classify(&Method::POST, Some("/api/sandbox/shops/:shop_id/orders")),
+      TrafficClass::Terminal
+    );
+    assert_eq!(
+      TrafficClass::classify(&Method::POST, Some("/api/orders/:order_id/payment")),
+      TrafficClass::Terminal
+    );
+    assert_eq!(
+      TrafficClass::classify(&Method::GET, Some("/api/orders/:order_id/payment")),
+      TrafficClass::Dashboard
+    );
+    assert_eq!(
+      TrafficClass::classify(&Method::POST, Some("/shops/:shop_id/orders")),
+      TrafficClass::Dashboard
+    );
+    assert_eq!(
+      TrafficClass::classify(&Method::GET, Some("/api/shops/:shop_id/members")),
+      TrafficClass::Dashboard
+    );
+    assert_eq!(
+      TrafficClass::classify(&Method::GET, Some("/api/wallets/:wallet_id/topup")),
+      TrafficClass::Dashboard
+    );
+    assert_eq!(
+      TrafficClass::classify(&Method::POST, None),
+      TrafficClass::Dashboard
+    );
+  }
+
+  #[test]
+  fn test_terminal_routes_are_mounted() {
+    let mounted = crate::mounted_routes();
+
+    for (method, path) in TERMINAL_ROUTES {
+      assert!(
+        mounted.iter().any(|(m, p, _)| m == method && p == path),
+        "{} {} is not mounted",
+        method,
+        path
+      );
+    }
+  }
+
+  #[test]
+  fn test_bucket_admits_burst_up_to_capacity() {
+    let now = Instant::now();
+    let mut bucket = Bucket::new(3.0, now);
+
+    for _ in 0..3 {
+      assert_eq!(
+        bucket.take(now, 3.0, 1.0, Duration::ZERO),
+        Ok(Duration::ZERO)
+      );
+    }
+    assert!(bucket.take(now, 3.0, 1.0, Duration::ZERO).is_err());
+  }
+
+  #[test]
+  fn test_bucket_delays_before_rejecting() {
+    let now = Instant::now();
+    let mut bucket = Bucket::new(1.0, now);
+    let max_delay = Duration::from_secs(2);
+
+    assert_eq!(bucket.take(now, 1.0, 1.0, max_delay), Ok(Duration::ZERO));
+    assert_eq!(
+      bucket.take(now, 1.0, 1.0, max_delay),
+      Ok(Duration::from_secs(1))
+    );
+    assert_eq!(
+      bucket.take(now, 1.0, 1.0, max_delay),
+      Ok(Duration::from_secs(2))
+    );
+    assert_eq!(
+      bucket.take(now, 1.0, 1.0, max_delay),
+      Err(Duration::from_secs(1))
+    );
+  }
+
+  #[test]
+  fn test_buckets_share_one_beyond_the_cap() {
+    let now = Instant::now();
+    let mut buckets = Buckets::new(2, now);
+    let mut take = |client: &str| {
+      buckets.take(
+        client.to_string(),
+        TrafficClass::Dashboard,
+        now,
+        1.0,
+        1.0,
+        Duration::ZERO,
+      )
+    };
+
+    assert!(take("ip:1").is_ok());
+    assert!(take("ip:2").is_ok());
+    // Both newcomers land in the overflow bucket, which only holds one token
+    assert!(take("ip:3").is_ok());
+    assert!(take("ip:4").is_err());
+    assert_eq!(buckets.by_client.len(), 3);
+  }
+
+  #[test]
+  fn test_buckets_drop_idle_clients() {
+    let now = Instant::now();
+    let mut buckets = Buckets::new(10, now);
+    for client in ["ip:1", "ip:2"] {
+      let _ = buckets.take(
+        client.to_string(),
+        TrafficClass::Dashboard,
+        now,
+        1.0,
+        1.0,
+        Duration::ZERO,
+      );
+    }
+
+    let later = now + PRUNE_INTERVAL.max(BUCKET_IDLE_TTL);
+    let _ = buckets.take(
+      "ip:3".to_string(),
+      TrafficClass::Dashboard,
+      later,
+      1.0,
+      1.0,
+      Duration::ZERO,
+    );
+    assert_eq!(buckets.by_client.len(), 1);
+  }
+
+  #[test]
+  fn test_bucket_refills_over_time() {
+    let now = Instant::now();
+    let mut bucket = Bucket::new(1.0, now);
+
+    assert!(bucket.take(now, 1.0, 1.0, Duration::ZERO).is_ok());
+    assert!(bucket.take(now, 1.0, 1.0, Duration::ZERO).is_err());
+
+    let later = now + Duration::from_secs(1);
+    assert!(bucket.take(later, 1.0, 1.0, Duration::ZERO).is_ok());
+  }
+}
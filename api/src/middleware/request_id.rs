@@ -0,0 +1,95 @@
+use axum::{
+  extract::Request,
+  http::{HeaderMap, HeaderValue},
+  middleware::Next,
+  response::Response,
+};
+use tracing::Instrument;
+use uuid::Uuid;
+
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Longest client-supplied request ID taken over
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+tokio::task_local! {
+  pub(super) static REQUEST_ID: String;
+}
+
+/// ID of the request being handled, outside of [`scope_request_id`] none.
+pub fn current_request_id() -> Option<String> {
+  REQUEST_ID.try_with(Clone::clone).ok()
+}
+
+/// Gives each request an ID, taken from `X-Request-Id` or freshly made,
+/// that is recorded on every log line written while handling it, returned
+/// in the `X-Request-Id` response header and put in error bodies.
+pub async fn scope_request_id(request: Request, next: Next) -> Response {
+  let request_id =
+    client_request_id(request.headers()).unwrap_or_else(|| Uuid::new_v4().to_string());
+  let span = tracing::info_span!("request", request_id = %request_id);
+
+  let mut response = REQUEST_ID
+    .scope(request_id.clone(), next.run(request).instrument(span))
+    .await;
+
+  if let Ok(value) = HeaderValue::from_str(&request_id) {
+    response.headers_mut().insert(REQUEST_ID_HEADER, value);
+  }
+
+  response
+}
+
+/// The caller's request ID, unless it could garble logs.
+fn client_request_id(headers: &HeaderMap) -> Option<String> {
+  headers
+    .get(REQUEST_ID_HEADER)
+    .and_then(|value| value.to_str().ok())
+    .filter(|id| {
+      !id.is_empty() && id.len() <= MAX_REQUEST_ID_LEN && id.bytes().all(|b| b.is_ascii_graphic())
+    })
+    .map(ToString::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+  use axum::{body::Body, routing::get, Router};
+  use tower::ServiceExt;
+
+  use super::*;
+
+  async fn echo_request_id() -> String {
+    current_request_id().unwrap_or_default()
+  }
+
+  #[tokio::test]
+  async fn test_request_id_is_propagated_or_assigned() {
+    let app = Router::new()
+      .route("/", get(echo_request_id))
+      .layer(axum::middleware::from_fn(scope_request_id));
+
+    let response = app
+      .clone()
+      .oneshot(
+        Request::get("/")
+          .header(REQUEST_ID_HEADER, "abc-123")
+          .body(Body::empty())
+          .unwrap(),
+      )
+      .await
+      .unwrap();
+    assert_eq!(response.headers()[REQUEST_ID_HEADER], "abc-123");
+
+    let response = app
+      .oneshot(
+        Request::get("/")
+          .header(REQUEST_ID_HEADER, "two words")
+          .body(Body::empty())
+          .unwrap(),
+      )
+      .await
+      .unwrap();
+    let assigned = response.headers()[REQUEST_ID_HEADER].to_str().unwrap();
+    assert!(Uuid::parse_str(assigned).is_ok());
+  }
+}
@@ -0,0 +1,31 @@
+use axum::{
+  extract::Request,
+  middleware::Next,
+  response::{IntoResponse, Response},
+};
+
+use crate::{error::ApiError, extractor::tx::TxSlot};
+use application::error::AppError;
+
+/// Settles the transaction of a handler taking [`crate::extractor::Tx`]:
+/// commits it if the response is a success, rolls it back otherwise.
+pub async fn finish_transaction(mut request: Request, next: Next) -> Response {
+  let slot = TxSlot::default();
+  request.extensions_mut().insert(slot.clone());
+
+  let response = next.run(request).await;
+
+  let Some(tx) = slot.take() else {
+    return response;
+  };
+
+  if response.status().is_success() || response.status().is_redirection() {
+    if let Err(e) = tx.commit().await {
+      return ApiError(AppError::from(e)).into_response();
+    }
+  } else if let Err(e) = tx.rollback().await {
+    tracing::warn!("Failed to roll back request transaction: {}", e);
+  }
+
+  response
+}
@@ -0,0 +1,102 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::models::AmountDisplay;
+use domain::{
+  Actor, ActorKind, ActorSummary, Currency, DuplicateCandidate, DuplicateReason, Email, Guest, Id,
+  Role, User,
+};
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ListActorsQuery {
+  /// Page number, starting at 1
+  pub page: Option<u32>,
+  /// Items per page (max 100)
+  pub per_page: Option<u32>,
+  #[param(inline)]
+  pub kind: Option<ActorKind>,
+  /// Case-insensitive match on name, email or guest identifier
+  pub search: Option<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ActorSummaryResponse {
+  pub actor_id: Id<Actor>,
+  pub kind: ActorKind,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub user_id: Option<Id<User>>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub guest_id: Option<Id<Guest>>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub display_name: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub email: Option<Email>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub identifier: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub role: Option<Role>,
+  pub wallet_count: i32,
+  /// What the balance is counted in; omitted without a wallet
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub currency: Option<Currency>,
+  /// Summed over the actor's wallets in `currency` as of the request
+  pub balance_cents: i64,
+  pub balance_display: AmountDisplay,
+  /// When the summary was last rebuilt; the balance is always current
+  pub refreshed_at: DateTime<Utc>,
+}
+
+impl From<ActorSummary> for ActorSummaryResponse {
+  fn from(summary: ActorSummary) -> Self {
+    Self {
+      actor_id: summary.actor_id,
+      kind: summary.kind,
+      user_id: summary.user_id,
+      guest_id: summary.guest_id,
+      display_name: summary.display_name,
+      email: summary.email,
+      identifier: summary.identifier,
+      role: summary.role,
+      wallet_count: summary.wallet_count,
+      currency: summary.currency,
+      balance_cents: summary.balance_cents,
+      balance_display: summary
+        .currency
+        .map_or(summary.balance_cents.into(), |currency| {
+          AmountDisplay::new(summary.balance_cents, currency)
+        }),
+      refreshed_at: summary.updated_at.unwrap_or(summary.created_at),
+    }
+  }
+}
+
+/// Someone who likely already exists, returned as a warning when inviting or
+/// adding a guest so their balance isn't split across two actors.
+#[derive(Serialize, ToSchema)]
+pub struct DuplicateCandidateResponse {
+  pub actor_id: Id<Actor>,
+  pub kind: ActorKind,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub user_id: Option<Id<User>>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub guest_id: Option<Id<Guest>>,
+  /// First name and last initial, e.g. "Ada L."
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub name: Option<String>,
+  pub reason: DuplicateReason,
+}
+
+impl From<DuplicateCandidate> for DuplicateCandidateResponse {
+  fn from(candidate: DuplicateCandidate) -> Self {
+    Self {
+      name: candidate.short_name(),
+      actor_id: candidate.actor_id,
+      kind: candidate.kind,
+      user_id: candidate.user_id,
+      guest_id: candidate.guest_id,
+      reason: candidate.reason,
+    }
+  }
+}
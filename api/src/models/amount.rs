@@ -0,0 +1,88 @@
+use serde::{Serialize, Serializer};
+use utoipa::openapi::{ObjectBuilder, RefOr, Schema, SchemaType};
+use utoipa::ToSchema;
+use validator::ValidationError;
+
+use domain::{types::Money, Currency};
+
+use crate::middleware::locale::{current_currency, current_locale};
+
+/// An amount of cents formatted for display in the locale the response is
+/// written in, e.g. "€12.50" or "12,50 €". Sent next to every `*_cents`
+/// field as its `*_display` counterpart, so clients need not format and
+/// round amounts themselves. Amounts of a wallet are written in its
+/// currency, all others in the venue's.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AmountDisplay {
+  cents: i64,
+  /// The venue's currency if unset
+  currency: Option<Currency>,
+}
+
+impl AmountDisplay {
+  /// `cents` of `currency` rather than of the venue's.
+  pub fn new(cents: impl Into<i64>, currency: Currency) -> Self {
+    Self {
+      cents: cents.into(),
+      currency: Some(currency),
+    }
+  }
+}
+
+impl<'s> ToSchema<'s> for AmountDisplay {
+  fn schema() -> (&'s str, RefOr<Schema>) {
+    (
+      "AmountDisplay",
+      ObjectBuilder::new()
+        .schema_type(SchemaType::String)
+        .description(Some(
+          "Amount formatted for display, e.g. \"€12.50\" or \"12,50 €\"",
+        ))
+        .example(Some("€12.50".into()))
+        .into(),
+    )
+  }
+}
+
+impl Serialize for AmountDisplay {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    let currency = self.currency.unwrap_or_else(current_currency);
+    serializer.serialize_str(&current_locale().format_cents(self.cents, currency))
+  }
+}
+
+impl From<Money> for AmountDisplay {
+  fn from(money: Money) -> Self {
+    Self {
+      cents: money.as_minor().into(),
+      currency: None,
+    }
+  }
+}
+
+impl From<i32> for AmountDisplay {
+  fn from(cents: i32) -> Self {
+    Self {
+      cents: cents.into(),
+      currency: None,
+    }
+  }
+}
+
+impl From<i64> for AmountDisplay {
+  fn from(cents: i64) -> Self {
+    Self {
+      cents,
+      currency: None,
+    }
+  }
+}
+
+/// Validates that a requested amount moves money, i.e. is at least one cent.
+pub fn positive_amount(amount: &Money) -> Result<(), ValidationError> {
+  if amount.is_positive() {
+    Ok(())
+  } else {
+    Err(ValidationError::new("range").with_message("must be at least 0.01".into()))
+  }
+}
@@ -0,0 +1,97 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use utoipa::{IntoParams, ToSchema};
+
+use domain::{Actor, AuditChainReport, AuditEntry, Id};
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ListAuditQuery {
+  /// Page number, starting at 1
+  pub page: Option<u32>,
+  /// Items per page (max 100)
+  pub per_page: Option<u32>,
+  pub actor_id: Option<Id<Actor>>,
+  /// Exact action, e.g. `invite.sent`
+  pub action: Option<String>,
+  /// Only entries recorded at or after this time
+  pub from: Option<DateTime<Utc>>,
+  /// Only entries recorded before this time
+  pub to: Option<DateTime<Utc>>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct AuditEntryResponse {
+  pub id: Id<AuditEntry>,
+  /// Position in the hash chain
+  pub seq: i64,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub actor_id: Option<Id<Actor>>,
+  #[schema(example = "invite.sent")]
+  pub action: String,
+  /// What the action was performed on, usually an id
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub target: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub ip_address: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  #[schema(value_type = Option<Object>)]
+  pub details: Option<Value>,
+  /// Such as the request body; not covered by the hash, and gone once the
+  /// user who acted was deleted
+  #[serde(skip_serializing_if = "Option::is_none")]
+  #[schema(value_type = Option<Object>)]
+  pub personal_data: Option<Value>,
+  /// Hash of the entry before
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub previous_hash: Option<String>,
+  pub hash: String,
+  pub created_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct AuditChainResponse {
+  /// Every entry matches its hash and links to the one before
+  pub intact: bool,
+  pub entries: i64,
+  /// Hash of the latest entry; keep it to notice entries removed from the
+  /// end later
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub head_hash: Option<String>,
+  /// Position of the first entry that doesn't match
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub broken_seq: Option<i64>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub broken_entry_id: Option<Id<AuditEntry>>,
+}
+
+impl From<AuditEntry> for AuditEntryResponse {
+  fn from(entry: AuditEntry) -> Self {
+    Self {
+      id: entry.id,
+      seq: entry.seq,
+      actor_id: entry.actor_id,
+      action: entry.action,
+      target: entry.target,
+      ip_address: entry.ip_address,
+      details: entry.details,
+      personal_data: entry.personal_data,
+      previous_hash: entry.previous_hash,
+      hash: entry.hash,
+      created_at: entry.created_at,
+    }
+  }
+}
+
+impl From<AuditChainReport> for AuditChainResponse {
+  fn from(report: AuditChainReport) -> Self {
+    Self {
+      intact: report.is_intact(),
+      entries: report.entries,
+      head_hash: report.head_hash,
+      broken_seq: report.first_broken.map(|(seq, _)| seq),
+      broken_entry_id: report.first_broken.map(|(_, id)| id),
+    }
+  }
+}
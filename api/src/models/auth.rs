@@ -1,7 +1,10 @@
-use serde::Deserialize;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 use validator::Validate;
 
+use domain::{DeviceNonce, DevicePublicKey, Id, PasswordConfirmation, Permission, Shop, Wallet};
+
 #[derive(Deserialize, Validate, ToSchema)]
 pub struct LoginRequest {
   #[validate(email)]
@@ -11,4 +14,85 @@ pub struct LoginRequest {
   #[validate(length(min = 1))]
   #[schema(example = "password123")]
   pub password: String,
+
+  /// Binds the session to a key pair in the device's secure hardware, as
+  /// base64 of the SEC1-encoded P-256 public key. Sensitive requests in the
+  /// session then need a signed nonce from `POST /api/auth/device-nonce`.
+  pub device_public_key: Option<DevicePublicKey>,
+}
+
+#[derive(Deserialize, Validate, ToSchema)]
+pub struct VerifyPasswordRequest {
+  #[validate(length(min = 1))]
+  #[schema(example = "password123")]
+  pub password: String,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct PasswordConfirmationResponse {
+  /// Send as `X-Confirmation-Token` on endpoints that require a step-up
+  pub token: String,
+  pub expires_at: DateTime<Utc>,
+}
+
+impl From<PasswordConfirmation> for PasswordConfirmationResponse {
+  fn from(confirmation: PasswordConfirmation) -> Self {
+    Self {
+      token: confirmation.token,
+      expires_at: confirmation.expires_at,
+    }
+  }
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct DeviceNonceResponse {
+  /// Send as `X-Device-Nonce`, along with the device key's signature over it
+  /// as `X-Device-Signature`, on one sensitive request
+  pub nonce: String,
+  pub expires_at: DateTime<Utc>,
+}
+
+impl From<DeviceNonce> for DeviceNonceResponse {
+  fn from(nonce: DeviceNonce) -> Self {
+    Self {
+      nonce: nonce.nonce,
+      expires_at: nonce.expires_at,
+    }
+  }
+}
+
+#[derive(Deserialize, Validate, ToSchema)]
+pub struct PermissionChecksRequest {
+  #[validate(length(min = 1, max = 100))]
+  pub checks: Vec<PermissionCheck>,
+}
+
+/// Whether the caller may do `action`, on `resource` if given.
+#[derive(Serialize, Deserialize, Clone, Copy, ToSchema)]
+pub struct PermissionCheck {
+  pub action: Permission,
+  pub resource: Option<CheckedResource>,
+}
+
+/// A resource whose owner may take some actions on it without holding the
+/// permission.
+#[derive(Serialize, Deserialize, Clone, Copy, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum CheckedResource {
+  Shop { id: Id<Shop> },
+  Wallet { id: Id<Wallet> },
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct PermissionCheckResult {
+  #[serde(flatten)]
+  pub check: PermissionCheck,
+  /// Unknown resources are denied
+  pub allowed: bool,
+}
+
+/// One result per check, in request order.
+#[derive(Serialize, ToSchema)]
+pub struct PermissionChecksResponse {
+  pub results: Vec<PermissionCheckResult>,
 }
@@ -0,0 +1,99 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use validator::Validate;
+
+use crate::models::AmountDisplay;
+use application::services::balance_alert::NewBalanceAlert;
+use domain::{types::Money, BalanceAlert, BalanceAlertDirection, Email, Id, WalletLabel};
+
+#[derive(Deserialize, Validate, ToSchema)]
+pub struct CreateBalanceAlertRequest {
+  /// System wallet to watch; omit to watch the money owed to members, the sum
+  /// of all positive member balances
+  pub wallet_label: Option<WalletLabel>,
+  pub direction: BalanceAlertDirection,
+  /// Threshold, as integer cents or a decimal string such as
+  /// `"5000.00"`; `threshold_cents` is accepted too
+  #[serde(alias = "threshold_cents")]
+  #[schema(example = "5000.00")]
+  pub threshold: Money,
+  #[validate(email)]
+  #[schema(example = "treasurer@example.com")]
+  pub notify_email: Option<String>,
+  /// Receives signed `balance.alert` events; an https URL on the public
+  /// internet, like any other webhook
+  #[validate(url, length(max = 2048))]
+  #[schema(example = "https://monitoring.example.com/cayopay")]
+  pub webhook_url: Option<String>,
+}
+
+impl From<CreateBalanceAlertRequest> for NewBalanceAlert {
+  fn from(request: CreateBalanceAlertRequest) -> Self {
+    Self {
+      wallet_label: request.wallet_label,
+      direction: request.direction,
+      threshold_cents: i64::from(request.threshold.as_minor()),
+      notify_email: request.notify_email.map(Email::new),
+      webhook_url: request.webhook_url,
+    }
+  }
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct BalanceAlertResponse {
+  pub id: Id<BalanceAlert>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub wallet_label: Option<WalletLabel>,
+  pub direction: BalanceAlertDirection,
+  /// Threshold in cents
+  pub threshold_cents: i64,
+  pub threshold_display: AmountDisplay,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub notify_email: Option<Email>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub webhook_url: Option<String>,
+  /// Whether the balance was past the threshold at the last check
+  pub breached: bool,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub last_triggered_at: Option<DateTime<Utc>>,
+  pub created_at: DateTime<Utc>,
+}
+
+/// Returned once on creation; the webhook secret can't be read back later.
+#[derive(Serialize, ToSchema)]
+pub struct CreatedBalanceAlertResponse {
+  #[serde(flatten)]
+  pub alert: BalanceAlertResponse,
+  /// Key for verifying the `X-Cayopay-Signature` header of webhook deliveries
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub webhook_secret: Option<String>,
+}
+
+impl From<BalanceAlert> for BalanceAlertResponse {
+  fn from(alert: BalanceAlert) -> Self {
+    Self {
+      id: alert.id,
+      wallet_label: alert.wallet_label,
+      direction: alert.direction,
+      threshold_cents: alert.threshold_cents,
+      threshold_display: alert.threshold_cents.into(),
+      notify_email: alert.notify_email,
+      webhook_url: alert.webhook_url,
+      breached: alert.breached,
+      last_triggered_at: alert.last_triggered_at,
+      created_at: alert.created_at,
+    }
+  }
+}
+
+impl From<BalanceAlert> for CreatedBalanceAlertResponse {
+  fn from(mut alert: BalanceAlert) -> Self {
+    let webhook_secret = alert.webhook_secret.take();
+
+    Self {
+      alert: alert.into(),
+      webhook_secret,
+    }
+  }
+}
@@ -0,0 +1,76 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use validator::Validate;
+
+use crate::models::{positive_amount, AmountDisplay};
+use domain::{types::Money, BudgetEnvelope, BudgetPlan, Id, Shop};
+
+#[derive(Deserialize, Validate, ToSchema)]
+pub struct BudgetEnvelopeRequest {
+  /// Up to 40 characters, unique within the wallet
+  #[schema(example = "Drinks")]
+  pub name: String,
+  /// Amount earmarked, as integer cents or a decimal string such as
+  /// `"30.00"`; `amount_cents` is accepted too
+  #[serde(alias = "amount_cents")]
+  #[validate(custom(function = "positive_amount"))]
+  #[schema(example = "30.00")]
+  pub amount: Money,
+  /// Shops whose payments count against the envelope
+  #[validate(length(min = 1, max = 50))]
+  pub shop_ids: Vec<Id<Shop>>,
+  /// Starts counting spending from zero again; ignored when opening one
+  #[serde(default)]
+  pub reset_spent: bool,
+}
+
+impl BudgetEnvelopeRequest {
+  pub fn into_plan(self) -> Result<BudgetPlan, String> {
+    BudgetPlan::new(self.name, self.amount, self.shop_ids)
+  }
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct BudgetEnvelopeResponse {
+  pub id: Id<BudgetEnvelope>,
+  #[schema(example = "Drinks")]
+  pub name: String,
+  pub amount_cents: i32,
+  pub amount_display: AmountDisplay,
+  /// Paid at the envelope's shops since `spent_since`, less refunds
+  pub spent_cents: i32,
+  pub spent_display: AmountDisplay,
+  /// Negative once overspent; payments are never refused for it
+  pub remaining_cents: i32,
+  pub remaining_display: AmountDisplay,
+  pub overspent: bool,
+  pub shop_ids: Vec<Id<Shop>>,
+  pub spent_since: DateTime<Utc>,
+  pub created_at: DateTime<Utc>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl From<BudgetEnvelope> for BudgetEnvelopeResponse {
+  fn from(envelope: BudgetEnvelope) -> Self {
+    let remaining = envelope.remaining();
+    let overspent = envelope.is_overspent();
+
+    Self {
+      id: envelope.id,
+      name: envelope.plan.name().to_string(),
+      amount_cents: envelope.plan.amount().as_minor(),
+      amount_display: envelope.plan.amount().into(),
+      spent_cents: envelope.spent.as_minor(),
+      spent_display: envelope.spent.into(),
+      remaining_cents: remaining.as_minor(),
+      remaining_display: remaining.into(),
+      overspent,
+      shop_ids: envelope.plan.shops().to_vec(),
+      spent_since: envelope.spent_since,
+      created_at: envelope.created_at,
+      updated_at: envelope.updated_at,
+    }
+  }
+}
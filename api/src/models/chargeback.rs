@@ -0,0 +1,94 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use validator::Validate;
+
+use crate::models::AmountDisplay;
+use domain::{Chargeback, ChargebackStatus, ChargebackTotals, Id, TopUp, Transaction, Wallet};
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ChargebackReportQuery {
+  /// Only chargebacks staff have yet to resolve
+  #[serde(default)]
+  pub unresolved: bool,
+}
+
+#[derive(Deserialize, Validate, ToSchema)]
+pub struct ResolveChargebackRequest {
+  #[validate(length(min = 1, max = 1024))]
+  #[schema(example = "Paid back in cash at the bar")]
+  pub note: String,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ChargebackResponse {
+  pub id: Id<Chargeback>,
+  pub top_up_id: Id<TopUp>,
+  pub wallet_id: Id<Wallet>,
+  /// Amount in cents
+  pub amount_cents: i32,
+  pub amount_display: AmountDisplay,
+  pub status: ChargebackStatus,
+  /// Debit of the wallet
+  pub transaction_id: Id<Transaction>,
+  /// Credit back to the wallet, for won disputes
+  pub reversal_transaction_id: Option<Id<Transaction>>,
+  pub resolution_note: Option<String>,
+  pub resolved_at: Option<DateTime<Utc>>,
+  pub created_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ChargebackReportResponse {
+  pub count: u32,
+  /// Wallets with these cannot be topped up online
+  pub unresolved_count: u32,
+  /// Taken back by the provider for good, in cents
+  pub lost_cents: i64,
+  pub lost_display: AmountDisplay,
+  /// Still disputed, in cents
+  pub open_cents: i64,
+  pub open_display: AmountDisplay,
+  /// Returned after won disputes, in cents
+  pub won_cents: i64,
+  pub won_display: AmountDisplay,
+  /// Newest first
+  pub chargebacks: Vec<ChargebackResponse>,
+}
+
+impl From<Chargeback> for ChargebackResponse {
+  fn from(chargeback: Chargeback) -> Self {
+    Self {
+      id: chargeback.id,
+      top_up_id: chargeback.top_up_id,
+      wallet_id: chargeback.wallet_id,
+      amount_cents: chargeback.amount.as_minor(),
+      amount_display: chargeback.amount.into(),
+      status: chargeback.status,
+      transaction_id: chargeback.transaction_id,
+      reversal_transaction_id: chargeback.reversal_transaction_id,
+      resolution_note: chargeback.resolution_note,
+      resolved_at: chargeback.resolved_at,
+      created_at: chargeback.created_at,
+    }
+  }
+}
+
+impl From<Vec<Chargeback>> for ChargebackReportResponse {
+  fn from(chargebacks: Vec<Chargeback>) -> Self {
+    let totals = ChargebackTotals::of(&chargebacks);
+
+    Self {
+      count: totals.count,
+      unresolved_count: totals.unresolved_count,
+      lost_cents: totals.lost_cents,
+      lost_display: totals.lost_cents.into(),
+      open_cents: totals.open_cents,
+      open_display: totals.open_cents.into(),
+      won_cents: totals.won_cents,
+      won_display: totals.won_cents.into(),
+      chargebacks: chargebacks.into_iter().map(Into::into).collect(),
+    }
+  }
+}
@@ -0,0 +1,171 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use validator::Validate;
+
+use application::services::client_app::{AuthorizationRequest, NewClientApp, TokenRequest};
+use domain::{AccessToken, AuthorizationCode, ClientApp, Id, Scope};
+
+#[derive(Deserialize, Validate, ToSchema)]
+pub struct CreateClientAppRequest {
+  #[validate(length(min = 1, max = 100))]
+  #[schema(example = "Bar kiosk")]
+  pub name: String,
+  /// Where users are sent back to with their approval; custom schemes such
+  /// as `cayopay://callback` work for mobile apps
+  #[validate(length(min = 1, max = 10))]
+  #[schema(example = json!(["https://kiosk.example.com/callback"]))]
+  pub redirect_uris: Vec<String>,
+  /// Scopes the app may ask users for
+  #[validate(length(min = 1))]
+  pub allowed_scopes: Vec<Scope>,
+}
+
+impl From<CreateClientAppRequest> for NewClientApp {
+  fn from(request: CreateClientAppRequest) -> Self {
+    Self {
+      name: request.name,
+      redirect_uris: request.redirect_uris,
+      allowed_scopes: request.allowed_scopes,
+    }
+  }
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ClientAppResponse {
+  /// Also the app's `client_id`
+  pub id: Id<ClientApp>,
+  pub name: String,
+  pub redirect_uris: Vec<String>,
+  pub allowed_scopes: Vec<Scope>,
+  pub created_at: DateTime<Utc>,
+}
+
+impl From<ClientApp> for ClientAppResponse {
+  fn from(app: ClientApp) -> Self {
+    Self {
+      id: app.id,
+      name: app.name,
+      redirect_uris: app.redirect_uris,
+      allowed_scopes: app.allowed_scopes,
+      created_at: app.created_at,
+    }
+  }
+}
+
+#[derive(Deserialize, Validate, ToSchema)]
+pub struct AuthorizeRequest {
+  pub client_id: Id<ClientApp>,
+  #[validate(length(min = 1, max = 2048))]
+  pub redirect_uri: String,
+  #[validate(length(min = 1))]
+  pub scopes: Vec<Scope>,
+  /// Base64url-encoded SHA-256 of the app's code verifier (PKCE `S256`)
+  #[validate(length(equal = 43))]
+  #[schema(example = "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM")]
+  pub code_challenge: String,
+}
+
+impl From<AuthorizeRequest> for AuthorizationRequest {
+  fn from(request: AuthorizeRequest) -> Self {
+    Self {
+      client_app_id: request.client_id,
+      redirect_uri: request.redirect_uri,
+      scopes: request.scopes,
+      code_challenge: request.code_challenge,
+    }
+  }
+}
+
+/// Where to send the user back to, with the code for the app.
+#[derive(Serialize, ToSchema)]
+pub struct AuthorizationResponse {
+  pub code: String,
+  pub redirect_uri: String,
+  pub expires_at: DateTime<Utc>,
+}
+
+impl From<AuthorizationCode> for AuthorizationResponse {
+  fn from(code: AuthorizationCode) -> Self {
+    Self {
+      code: code.code,
+      redirect_uri: code.redirect_uri,
+      expires_at: code.expires_at,
+    }
+  }
+}
+
+#[derive(Deserialize, Validate, ToSchema)]
+pub struct AccessTokenRequest {
+  /// Only `authorization_code` is supported
+  #[schema(example = "authorization_code")]
+  pub grant_type: String,
+  pub client_id: Id<ClientApp>,
+  #[validate(length(min = 1))]
+  pub code: String,
+  #[validate(length(min = 1, max = 2048))]
+  pub redirect_uri: String,
+  #[validate(length(min = 43, max = 128))]
+  #[schema(example = "dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk")]
+  pub code_verifier: String,
+}
+
+impl From<AccessTokenRequest> for TokenRequest {
+  fn from(request: AccessTokenRequest) -> Self {
+    Self {
+      client_app_id: request.client_id,
+      code: request.code,
+      redirect_uri: request.redirect_uri,
+      code_verifier: request.code_verifier,
+    }
+  }
+}
+
+/// Send the token as `Authorization: Bearer <access_token>`.
+#[derive(Serialize, ToSchema)]
+pub struct AccessTokenResponse {
+  pub access_token: String,
+  #[schema(example = "Bearer")]
+  pub token_type: String,
+  /// Seconds until the token expires
+  pub expires_in: i64,
+  pub scopes: Vec<Scope>,
+}
+
+impl From<AccessToken> for AccessTokenResponse {
+  fn from(token: AccessToken) -> Self {
+    Self {
+      expires_in: (token.expires_at - Utc::now()).num_seconds(),
+      access_token: token.token,
+      token_type: "Bearer".to_string(),
+      scopes: token.scopes,
+    }
+  }
+}
+
+/// An app the user approved, without the token itself.
+#[derive(Serialize, ToSchema)]
+pub struct AppTokenResponse {
+  pub id: Id<AccessToken>,
+  pub client_app_id: Id<ClientApp>,
+  pub client_app_name: String,
+  pub scopes: Vec<Scope>,
+  pub expires_at: DateTime<Utc>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub last_used_at: Option<DateTime<Utc>>,
+  pub created_at: DateTime<Utc>,
+}
+
+impl From<(AccessToken, ClientApp)> for AppTokenResponse {
+  fn from((token, app): (AccessToken, ClientApp)) -> Self {
+    Self {
+      id: token.id,
+      client_app_id: app.id,
+      client_app_name: app.name,
+      scopes: token.scopes,
+      expires_at: token.expires_at,
+      last_used_at: token.last_used_at,
+      created_at: token.created_at,
+    }
+  }
+}
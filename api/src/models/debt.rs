@@ -0,0 +1,136 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::models::AmountDisplay;
+use domain::{Actor, ActorKind, Currency, Debt, DebtAge, DebtAgeTotals, Debtor, Email, Id, Wallet};
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct DebtorReportQuery {
+  /// Only debts past the grace period, whose owners are being reminded
+  #[serde(default)]
+  pub flagged: bool,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct DebtorResponse {
+  pub id: Id<Debt>,
+  pub wallet_id: Id<Wallet>,
+  pub owner_actor_id: Id<Actor>,
+  pub kind: Option<ActorKind>,
+  pub display_name: Option<String>,
+  /// Omitted without permission to read the owner's details
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub email: Option<Email>,
+  /// Current balance in cents, below zero
+  pub balance_cents: i64,
+  pub balance_display: AmountDisplay,
+  /// What the wallet's balance is counted in
+  pub currency: Currency,
+  /// When the balance was first seen below zero
+  pub since: DateTime<Utc>,
+  pub age: DebtAge,
+  /// When the grace period ran out; unset until then
+  pub flagged_at: Option<DateTime<Utc>>,
+  pub reminders_sent: i32,
+  pub last_reminded_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct DebtAgeResponse {
+  pub age: DebtAge,
+  pub count: u32,
+  /// Owed in cents, as a positive amount
+  pub owed_cents: i64,
+  pub owed_display: AmountDisplay,
+}
+
+/// Totals of the debts owed in one currency.
+#[derive(Serialize, ToSchema)]
+pub struct DebtCurrencyResponse {
+  pub currency: Currency,
+  pub count: u32,
+  /// Owed in cents, as a positive amount
+  pub owed_cents: i64,
+  pub owed_display: AmountDisplay,
+  /// Totals per age, youngest first
+  pub ages: Vec<DebtAgeResponse>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct DebtorReportResponse {
+  pub count: u32,
+  /// Totals per currency owed in, as amounts in different currencies don't
+  /// add up
+  pub totals: Vec<DebtCurrencyResponse>,
+  /// Longest owed first
+  pub debtors: Vec<DebtorResponse>,
+}
+
+impl DebtorResponse {
+  fn new(debtor: Debtor, now: DateTime<Utc>) -> Self {
+    Self {
+      id: debtor.debt.id,
+      wallet_id: debtor.debt.wallet_id,
+      owner_actor_id: debtor.owner,
+      kind: debtor.kind,
+      display_name: debtor.display_name,
+      email: debtor.email,
+      balance_cents: debtor.balance_cents,
+      balance_display: AmountDisplay::new(debtor.balance_cents, debtor.currency),
+      currency: debtor.currency,
+      since: debtor.debt.since,
+      age: debtor.debt.age(now),
+      flagged_at: debtor.debt.flagged_at,
+      reminders_sent: debtor.debt.reminders_sent,
+      last_reminded_at: debtor.debt.last_reminded_at,
+    }
+  }
+}
+
+impl From<DebtAgeTotals> for DebtAgeResponse {
+  fn from(totals: DebtAgeTotals) -> Self {
+    Self {
+      age: totals.age,
+      count: totals.count,
+      owed_cents: totals.owed_cents,
+      owed_display: AmountDisplay::new(totals.owed_cents, totals.currency),
+    }
+  }
+}
+
+/// Expects the totals of one currency, as grouped by [`DebtAgeTotals::of`].
+impl From<&[DebtAgeTotals]> for DebtCurrencyResponse {
+  fn from(ages: &[DebtAgeTotals]) -> Self {
+    let currency = ages[0].currency;
+    let owed_cents = ages.iter().map(|age| age.owed_cents).sum::<i64>();
+
+    Self {
+      currency,
+      count: ages.iter().map(|age| age.count).sum(),
+      owed_cents,
+      owed_display: AmountDisplay::new(owed_cents, currency),
+      ages: ages.iter().copied().map(Into::into).collect(),
+    }
+  }
+}
+
+impl From<Vec<Debtor>> for DebtorReportResponse {
+  fn from(debtors: Vec<Debtor>) -> Self {
+    let now = Utc::now();
+    let totals: Vec<DebtCurrencyResponse> = DebtAgeTotals::of(&debtors, now)
+      .chunk_by(|a, b| a.currency == b.currency)
+      .map(Into::into)
+      .collect();
+
+    Self {
+      count: totals.iter().map(|totals| totals.count).sum(),
+      totals,
+      debtors: debtors
+        .into_iter()
+        .map(|debtor| DebtorResponse::new(debtor, now))
+        .collect(),
+    }
+  }
+}
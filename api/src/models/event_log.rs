@@ -0,0 +1,53 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use domain::DomainEventRecord;
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct EventLogQuery {
+  /// Cursor of the previous response, the sequence of the last event read;
+  /// without one, the log is read from its start
+  pub since: Option<i64>,
+  /// Events per page (default 100, max 1000)
+  pub limit: Option<i64>,
+}
+
+/// Something that happened, as recorded in the event log.
+#[derive(Serialize, ToSchema)]
+pub struct LoggedEventResponse {
+  /// Identifies the event in the log; never reused or changed. Events are
+  /// ordered by the transaction that recorded them, so sequences need not
+  /// increase from one event to the next.
+  pub sequence: i64,
+  #[serde(rename = "type")]
+  #[schema(example = "transaction.created")]
+  pub event_type: String,
+  /// Depends on the type, as in webhook payloads
+  #[schema(value_type = Object)]
+  pub data: serde_json::Value,
+  pub occurred_at: DateTime<Utc>,
+}
+
+/// A page of the event log.
+#[derive(Serialize, ToSchema)]
+pub struct EventLogResponse {
+  /// Oldest first
+  pub events: Vec<LoggedEventResponse>,
+  /// Where to continue from in the next request
+  pub cursor: i64,
+  /// Whether more events follow right away; otherwise poll again later
+  pub has_more: bool,
+}
+
+impl From<DomainEventRecord> for LoggedEventResponse {
+  fn from(record: DomainEventRecord) -> Self {
+    Self {
+      sequence: record.sequence,
+      event_type: record.event.name().to_string(),
+      data: record.event.data(),
+      occurred_at: record.occurred_at,
+    }
+  }
+}
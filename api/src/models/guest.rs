@@ -1,8 +1,67 @@
 use chrono::{DateTime, Utc};
-use serde::Serialize;
-use utoipa::ToSchema;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use validator::Validate;
 
-use domain::{Actor, Email, Guest, Id};
+use crate::models::{AmountDisplay, DuplicateCandidateResponse, WalletResponse};
+use domain::{
+  types::Money, Actor, Currency, Email, Guest, GuestClaim, Id, Transaction, User, Wallet,
+};
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ListGuestsQuery {
+  /// Page number, starting at 1
+  pub page: Option<u32>,
+  /// Items per page (max 100)
+  pub per_page: Option<u32>,
+  /// What the balance bounds are counted in, the venue's currency if
+  /// omitted; guests without a wallet in it never match them
+  #[param(value_type = Option<String>, example = "EUR")]
+  pub currency: Option<Currency>,
+  /// Lowest wallet balance, inclusive, as a decimal such as `12.50`
+  #[param(value_type = Option<String>, example = "12.50")]
+  pub min_balance: Option<Money>,
+  /// Highest wallet balance, inclusive, as a decimal such as `12.50`
+  #[param(value_type = Option<String>, example = "50.00")]
+  pub max_balance: Option<Money>,
+  /// Lowest wallet balance in cents, inclusive; `min_balance` takes
+  /// precedence
+  pub min_balance_cents: Option<i32>,
+  /// Highest wallet balance in cents, inclusive; `max_balance` takes
+  /// precedence
+  pub max_balance_cents: Option<i32>,
+  /// Only guests with a card or wristband bound if true, only those without
+  /// if false
+  pub card_bound: Option<bool>,
+}
+
+#[derive(Deserialize, Validate, ToSchema)]
+pub struct CreateGuestRequest {
+  #[validate(email)]
+  #[schema(example = "guest@example.com")]
+  pub email: Option<String>,
+  /// Adds the guest even if the person likely has an account already
+  #[serde(default)]
+  pub force: bool,
+}
+
+#[derive(Deserialize, Validate, ToSchema)]
+pub struct AssignIdentifierRequest {
+  /// NFC card UID or QR code payload
+  #[validate(length(min = 1, max = 255))]
+  #[schema(example = "04A224B2C35E80")]
+  pub identifier: String,
+}
+
+#[derive(Deserialize, Validate, ToSchema)]
+pub struct ClaimGuestRequest {
+  /// The guest's NFC card UID or QR code payload; not needed if the guest
+  /// verified the email the caller is registered with
+  #[validate(length(min = 1, max = 255))]
+  #[schema(example = "04A224B2C35E80")]
+  pub identifier: Option<String>,
+}
 
 #[derive(Serialize, ToSchema)]
 pub struct GuestResponse {
@@ -10,6 +69,8 @@ pub struct GuestResponse {
   pub actor_id: Id<Actor>,
   pub email: Option<Email>,
   pub verified: bool,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub identifier: Option<String>,
   pub created_at: DateTime<Utc>,
   #[serde(skip_serializing_if = "Option::is_none")]
   pub updated_at: Option<DateTime<Utc>>,
@@ -22,8 +83,60 @@ impl From<Guest> for GuestResponse {
       actor_id: guest.actor_id,
       email: guest.email,
       verified: guest.verified,
+      identifier: guest.identifier,
       created_at: guest.created_at,
       updated_at: guest.updated_at,
     }
   }
 }
+
+#[derive(Serialize, ToSchema)]
+pub struct CreatedGuestResponse {
+  #[serde(flatten)]
+  pub guest: GuestResponse,
+  /// Users and guests with a similar email who may be the same person, when
+  /// added with `force`
+  pub possible_duplicates: Vec<DuplicateCandidateResponse>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct GuestLookupResponse {
+  pub guest: GuestResponse,
+  pub wallet: WalletResponse,
+}
+
+impl From<(Guest, Wallet)> for GuestLookupResponse {
+  fn from((guest, wallet): (Guest, Wallet)) -> Self {
+    Self {
+      guest: guest.into(),
+      wallet: wallet.into(),
+    }
+  }
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct GuestClaimResponse {
+  pub id: Id<GuestClaim>,
+  pub guest_id: Id<Guest>,
+  pub user_id: Id<User>,
+  /// Balance moved into the caller's wallet; negative for a debt taken over
+  pub amount_cents: i32,
+  pub amount_display: AmountDisplay,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub transaction_id: Option<Id<Transaction>>,
+  pub created_at: DateTime<Utc>,
+}
+
+impl From<GuestClaim> for GuestClaimResponse {
+  fn from(claim: GuestClaim) -> Self {
+    Self {
+      id: claim.id,
+      guest_id: claim.guest_id,
+      user_id: claim.user_id,
+      amount_cents: claim.amount.as_minor(),
+      amount_display: claim.amount.into(),
+      transaction_id: claim.transaction_id,
+      created_at: claim.created_at,
+    }
+  }
+}
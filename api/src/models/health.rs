@@ -1,7 +1,99 @@
+use chrono::{DateTime, Utc};
 use serde::Serialize;
 use utoipa::ToSchema;
 
+use application::services::health::{Check, Readiness};
+
 #[derive(Serialize, ToSchema)]
 pub struct HealthResponse {
   pub status: String,
+  pub build: BuildInfo,
+  /// Panics caught since startup
+  pub panics: u64,
+}
+
+/// Identifies the build that is serving traffic.
+#[derive(Serialize, ToSchema)]
+pub struct BuildInfo {
+  #[schema(example = "0.1.0")]
+  pub version: String,
+  #[schema(example = "31dc146")]
+  pub git_commit: String,
+  pub build_timestamp: Option<DateTime<Utc>>,
+  /// Newest migration bundled with the binary
+  #[schema(example = 20261016120000_i64)]
+  pub migration_version: i64,
+}
+
+impl BuildInfo {
+  pub fn current() -> Self {
+    Self {
+      version: env!("CARGO_PKG_VERSION").to_string(),
+      git_commit: env!("CAYOPAY_GIT_COMMIT").to_string(),
+      build_timestamp: env!("CAYOPAY_BUILD_TIMESTAMP")
+        .parse()
+        .ok()
+        .and_then(|secs| DateTime::from_timestamp(secs, 0)),
+      migration_version: env!("CAYOPAY_MIGRATION_VERSION")
+        .parse()
+        .unwrap_or_default(),
+    }
+  }
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ReadinessResponse {
+  /// `ok`, `degraded` when only optional dependencies are down, or
+  /// `unavailable`
+  #[schema(example = "ok")]
+  pub status: String,
+  pub database: DependencyResponse,
+  /// Whether the database has every migration this build ships with
+  pub migrations: DependencyResponse,
+  /// Only checked when enabled in the configuration
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub smtp: Option<DependencyResponse>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct DependencyResponse {
+  #[schema(example = "up")]
+  pub status: String,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  #[schema(example = "timed out")]
+  pub detail: Option<String>,
+}
+
+impl From<Readiness> for ReadinessResponse {
+  fn from(readiness: Readiness) -> Self {
+    let status = if !readiness.is_ready() {
+      "unavailable"
+    } else if readiness.smtp.as_ref().is_some_and(|c| *c != Check::Up) {
+      "degraded"
+    } else {
+      "ok"
+    };
+
+    Self {
+      status: status.to_string(),
+      database: readiness.database.into(),
+      migrations: readiness.migrations.into(),
+      smtp: readiness.smtp.map(Into::into),
+    }
+  }
+}
+
+impl From<Check> for DependencyResponse {
+  fn from(check: Check) -> Self {
+    match check {
+      Check::Up => Self {
+        status: "up".to_string(),
+        detail: None,
+      },
+      Check::Down(detail) => Self {
+        status: "down".to_string(),
+        detail: Some(detail.to_string()),
+      },
+    }
+  }
 }
@@ -0,0 +1,74 @@
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use application::services::import::{ImportedMember, MemberImportReport};
+use domain::{Guest, Id, MemberImportError, Transaction, User, Wallet};
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct MemberImportQuery {
+  /// Check the file and report what would be imported without saving anything
+  #[serde(default)]
+  pub dry_run: bool,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ImportedMemberResponse {
+  /// Line of the row in the file, counting the header as line 1
+  pub line: usize,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub guest_id: Option<Id<Guest>>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub user_id: Option<Id<User>>,
+  pub wallet_id: Id<Wallet>,
+  /// Unset for members starting at zero
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub opening_transaction_id: Option<Id<Transaction>>,
+}
+
+impl From<ImportedMember> for ImportedMemberResponse {
+  fn from(member: ImportedMember) -> Self {
+    Self {
+      line: member.line,
+      guest_id: member.guest_id,
+      user_id: member.user_id,
+      wallet_id: member.wallet_id,
+      opening_transaction_id: member.opening_transaction_id,
+    }
+  }
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct MemberImportErrorResponse {
+  pub line: usize,
+  pub message: String,
+}
+
+impl From<MemberImportError> for MemberImportErrorResponse {
+  fn from(error: MemberImportError) -> Self {
+    Self {
+      line: error.line,
+      message: error.message,
+    }
+  }
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct MemberImportResponse {
+  /// Nothing was saved
+  pub dry_run: bool,
+  /// Rows that were imported, or would be on a dry run
+  pub imported: Vec<ImportedMemberResponse>,
+  /// Rows that were skipped, with the reason
+  pub failed: Vec<MemberImportErrorResponse>,
+}
+
+impl From<MemberImportReport> for MemberImportResponse {
+  fn from(report: MemberImportReport) -> Self {
+    Self {
+      dry_run: report.dry_run,
+      imported: report.imported.into_iter().map(Into::into).collect(),
+      failed: report.failed.into_iter().map(Into::into).collect(),
+    }
+  }
+}
@@ -1,9 +1,29 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use utoipa::ToSchema;
+use utoipa::{IntoParams, ToSchema};
 use validator::Validate;
 
-use domain::{Id, Invite, InviteStatus, Role, User};
+use crate::models::DuplicateCandidateResponse;
+use domain::{Id, Invite, InviteStatus, Locale, Role, User};
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ListInvitesQuery {
+  /// Page number, starting at 1
+  pub page: Option<u32>,
+  /// Items per page (max 100)
+  pub per_page: Option<u32>,
+  #[param(inline)]
+  pub status: Option<InviteStatus>,
+  /// Case-insensitive start of the invited address
+  pub email_prefix: Option<String>,
+  /// Only invites sent by this user
+  pub invitor: Option<Id<User>>,
+  /// Only invites sent at or after this time
+  pub created_from: Option<DateTime<Utc>>,
+  /// Only invites sent before this time
+  pub created_to: Option<DateTime<Utc>>,
+}
 
 #[derive(Deserialize, Validate, ToSchema)]
 pub struct InviteRequest {
@@ -12,6 +32,12 @@ pub struct InviteRequest {
   pub email: String,
 
   pub role: Role,
+  /// Language of the invite emails and of the account; defaults to the
+  /// inviting user's
+  pub locale: Option<Locale>,
+  /// Sends the invite even if the person likely has an account already
+  #[serde(default)]
+  pub force: bool,
 }
 
 #[derive(Deserialize, Validate, ToSchema)]
@@ -33,6 +59,7 @@ pub struct InviteResponse {
   pub invitor: Id<User>,
   pub email: String,
   pub role: Role,
+  pub locale: Locale,
   pub status: InviteStatus,
   pub expires_at: DateTime<Utc>,
   pub created_at: DateTime<Utc>,
@@ -40,15 +67,25 @@ pub struct InviteResponse {
   pub updated_at: Option<DateTime<Utc>>,
 }
 
+#[derive(Serialize, ToSchema)]
+pub struct CreatedInviteResponse {
+  #[serde(flatten)]
+  pub invite: InviteResponse,
+  /// Users and guests who may already be the invited person, when sent with
+  /// `force`
+  pub possible_duplicates: Vec<DuplicateCandidateResponse>,
+}
+
 impl From<Invite> for InviteResponse {
   fn from(invite: Invite) -> Self {
     Self {
+      expires_at: invite.expires_at(),
       id: invite.id,
       invitor: invite.invitor,
       email: invite.email.expose().to_string(),
       role: invite.role,
+      locale: invite.locale,
       status: invite.status,
-      expires_at: invite.created_at + invite.expires_in,
       created_at: invite.created_at,
       updated_at: invite.updated_at,
     }
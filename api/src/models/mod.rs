@@ -1,11 +1,67 @@
+pub mod actor;
+pub mod amount;
+pub mod audit;
 pub mod auth;
+pub mod balance_alert;
+pub mod budget;
+pub mod chargeback;
+pub mod client_app;
+pub mod debt;
+pub mod event_log;
 pub mod guest;
 pub mod health;
+pub mod import;
 pub mod invite;
+pub mod note;
+pub mod order;
+pub mod owner_transfer;
+pub mod page;
+pub mod payout;
+pub mod redact;
+pub mod report;
+pub mod retention;
+pub mod review;
+pub mod route_audit;
+pub mod session;
+pub mod settings;
+pub mod shop;
+pub mod stream;
+pub mod transaction;
+pub mod transfer;
 pub mod user;
+pub mod wallet;
+pub mod webhook;
 
+pub use actor::*;
+pub use amount::*;
+pub use audit::*;
 pub use auth::*;
+pub use balance_alert::*;
+pub use budget::*;
+pub use chargeback::*;
+pub use client_app::*;
+pub use debt::*;
+pub use event_log::*;
 pub use guest::*;
 pub use health::*;
+pub use import::*;
 pub use invite::*;
+pub use note::*;
+pub use order::*;
+pub use owner_transfer::*;
+pub use page::*;
+pub use payout::*;
+pub use redact::*;
+pub use report::*;
+pub use retention::*;
+pub use review::*;
+pub use route_audit::*;
+pub use session::*;
+pub use settings::*;
+pub use shop::*;
+pub use stream::*;
+pub use transaction::*;
+pub use transfer::*;
 pub use user::*;
+pub use wallet::*;
+pub use webhook::*;
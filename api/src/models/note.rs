@@ -0,0 +1,33 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use validator::Validate;
+
+use domain::{AccountNote, Actor, Id};
+
+#[derive(Deserialize, Validate, ToSchema)]
+pub struct AccountNoteRequest {
+  #[validate(length(min = 1, max = 2000))]
+  #[schema(example = "Refunded double charge on 2024-06-01")]
+  pub body: String,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct AccountNoteResponse {
+  pub id: Id<AccountNote>,
+  /// Staff member who wrote the note, unless their account is gone
+  pub author: Option<Id<Actor>>,
+  pub body: String,
+  pub created_at: DateTime<Utc>,
+}
+
+impl From<AccountNote> for AccountNoteResponse {
+  fn from(note: AccountNote) -> Self {
+    Self {
+      id: note.id,
+      author: note.author,
+      body: note.body,
+      created_at: note.created_at,
+    }
+  }
+}
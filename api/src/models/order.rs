@@ -0,0 +1,304 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use validator::Validate;
+
+use crate::models::AmountDisplay;
+use application::services::order::{CheckoutLine, Payer, Pickup};
+use domain::{
+  Actor, ActorKind, Id, Order, OrderItem, OrderStatus, OrderStatusChange, Purchaser, ReorderLine,
+  Shop, ShopOffering, Transaction, User, Wallet,
+};
+
+/// Who pays for the order.
+#[derive(Deserialize, ToSchema)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum PayerRequest {
+  /// A guest, by the NFC card UID or QR code payload bound to them
+  Guest {
+    identifier: String,
+  },
+  User {
+    user_id: Id<User>,
+  },
+}
+
+impl From<PayerRequest> for Payer {
+  fn from(payer: PayerRequest) -> Self {
+    match payer {
+      PayerRequest::Guest { identifier } => Payer::Guest(identifier),
+      PayerRequest::User { user_id } => Payer::User(user_id),
+    }
+  }
+}
+
+#[derive(Serialize, Deserialize, Validate, ToSchema)]
+pub struct CheckoutItemRequest {
+  pub offering_id: Id<ShopOffering>,
+  #[validate(range(min = 1, max = 1000))]
+  #[schema(example = 2)]
+  pub quantity: i32,
+}
+
+impl From<CheckoutItemRequest> for CheckoutLine {
+  fn from(item: CheckoutItemRequest) -> Self {
+    Self {
+      offering_id: item.offering_id,
+      quantity: item.quantity,
+    }
+  }
+}
+
+#[derive(Deserialize, Validate, ToSchema)]
+pub struct CheckoutRequest {
+  pub payer: PayerRequest,
+  #[validate(length(min = 1, max = 100), nested)]
+  pub items: Vec<CheckoutItemRequest>,
+  /// Table to serve the order to
+  #[validate(length(min = 1, max = 32))]
+  pub table_label: Option<String>,
+  /// Number called out when the order is ready
+  #[validate(range(min = 1, max = 9999))]
+  pub pickup_number: Option<i32>,
+  /// Context from the till: `terminal_id`, `receipt_number` or
+  /// `external_reference`
+  #[serde(default)]
+  #[schema(example = json!({"terminal_id": "bar-2"}))]
+  pub metadata: BTreeMap<String, String>,
+}
+
+#[derive(Deserialize, Validate, ToSchema)]
+pub struct PreorderRequest {
+  /// Defaults to the caller; only the shop's staff may pre-order for others
+  pub payer: Option<PayerRequest>,
+  #[validate(length(min = 1, max = 100), nested)]
+  pub items: Vec<CheckoutItemRequest>,
+  /// Start of a pickup slot of the shop
+  pub pickup_at: DateTime<Utc>,
+  /// Charge the payer when the order is picked up instead of now
+  #[serde(default)]
+  pub pay_at_pickup: bool,
+}
+
+impl CheckoutRequest {
+  pub fn pickup(&self) -> Pickup {
+    Pickup {
+      table_label: self.table_label.clone(),
+      pickup_number: self.pickup_number,
+    }
+  }
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ListOrdersQuery {
+  /// Page number, starting at 1
+  pub page: Option<u32>,
+  /// Items per page (max 100)
+  pub per_page: Option<u32>,
+  pub shop_id: Option<Id<Shop>>,
+  pub payer_wallet_id: Option<Id<Wallet>>,
+  /// Only orders placed at or after this time
+  pub from: Option<DateTime<Utc>>,
+  /// Only orders placed before this time
+  pub to: Option<DateTime<Utc>>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct OrderItemResponse {
+  pub id: Id<OrderItem>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub offering_id: Option<Id<ShopOffering>>,
+  /// Offering name at the time of the order
+  pub name: String,
+  /// Unit price in cents at the time of the order
+  pub unit_price_cents: i32,
+  pub unit_price_display: AmountDisplay,
+  pub quantity: i32,
+}
+
+impl From<OrderItem> for OrderItemResponse {
+  fn from(item: OrderItem) -> Self {
+    Self {
+      id: item.id,
+      offering_id: item.offering_id,
+      name: item.name,
+      unit_price_cents: item.unit_price.as_minor(),
+      unit_price_display: item.unit_price.into(),
+      quantity: item.quantity,
+    }
+  }
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct OrderResponse {
+  pub id: Id<Order>,
+  pub shop_id: Id<Shop>,
+  pub payer_wallet_id: Id<Wallet>,
+  /// Missing until a pre-order paid at pickup is paid
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub transaction_id: Option<Id<Transaction>>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub executor: Option<Id<Actor>>,
+  /// Total in cents
+  pub total_cents: i32,
+  pub total_display: AmountDisplay,
+  pub status: OrderStatus,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub table_label: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub pickup_number: Option<i32>,
+  /// Start of the pickup slot of a pre-order
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub pickup_at: Option<DateTime<Utc>>,
+  pub items: Vec<OrderItemResponse>,
+  pub created_at: DateTime<Utc>,
+}
+
+impl From<(Order, Vec<OrderItem>)> for OrderResponse {
+  fn from((order, items): (Order, Vec<OrderItem>)) -> Self {
+    Self {
+      id: order.id,
+      shop_id: order.shop_id,
+      payer_wallet_id: order.payer_wallet_id,
+      transaction_id: order.transaction_id,
+      executor: order.executor,
+      total_cents: order.total.as_minor(),
+      total_display: order.total.into(),
+      status: order.status,
+      table_label: order.table_label,
+      pickup_number: order.pickup_number,
+      pickup_at: order.pickup_at,
+      items: items.into_iter().map(Into::into).collect(),
+      created_at: order.created_at,
+    }
+  }
+}
+
+#[derive(Deserialize, Validate, ToSchema)]
+pub struct UpdateOrderStatusRequest {
+  pub status: OrderStatus,
+}
+
+/// Who placed an order, for staff calling out names at the counter.
+#[derive(Serialize, ToSchema)]
+pub struct PurchaserResponse {
+  pub order_id: Id<Order>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub kind: Option<ActorKind>,
+  /// First name and last initial of users; missing for guests
+  #[serde(skip_serializing_if = "Option::is_none")]
+  #[schema(example = "Ada L.")]
+  pub display_name: Option<String>,
+  /// For a placeholder avatar; missing for guests
+  #[serde(skip_serializing_if = "Option::is_none")]
+  #[schema(example = "AL")]
+  pub initials: Option<String>,
+}
+
+impl From<Purchaser> for PurchaserResponse {
+  fn from(purchaser: Purchaser) -> Self {
+    Self {
+      order_id: purchaser.order_id,
+      kind: purchaser.kind,
+      display_name: purchaser.display_name(),
+      initials: purchaser.initials(),
+    }
+  }
+}
+
+/// Data of the `status` events streamed to purchasers.
+#[derive(Serialize, ToSchema)]
+pub struct OrderStatusEvent {
+  pub order_id: Id<Order>,
+  pub status: OrderStatus,
+  pub changed_at: DateTime<Utc>,
+}
+
+impl From<OrderStatusChange> for OrderStatusEvent {
+  fn from(change: OrderStatusChange) -> Self {
+    Self {
+      order_id: change.order_id,
+      status: change.status,
+      changed_at: change.changed_at,
+    }
+  }
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ReorderItemResponse {
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub offering_id: Option<Id<ShopOffering>>,
+  /// Current name, or the name at the time of the past order if no longer sold
+  pub name: String,
+  pub quantity: i32,
+  /// Current price in cents; missing if no longer sold
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub unit_price_cents: Option<i32>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub unit_price_display: Option<AmountDisplay>,
+  /// Price in cents paid in the past order
+  pub previous_unit_price_cents: i32,
+  pub previous_unit_price_display: AmountDisplay,
+  /// Whether the shop still sells the offering
+  pub available: bool,
+  pub price_changed: bool,
+}
+
+impl From<ReorderLine> for ReorderItemResponse {
+  fn from(line: ReorderLine) -> Self {
+    let available = line.is_available();
+    let price_changed = line.price_changed();
+
+    Self {
+      offering_id: line.previous.offering_id,
+      name: line
+        .current
+        .as_ref()
+        .map_or(line.previous.name, |offering| offering.name.clone()),
+      quantity: line.previous.quantity,
+      unit_price_cents: line
+        .current
+        .as_ref()
+        .map(|offering| offering.price_cents.as_minor()),
+      unit_price_display: line.current.map(|offering| offering.price_cents.into()),
+      previous_unit_price_cents: line.previous.unit_price.as_minor(),
+      previous_unit_price_display: line.previous.unit_price.into(),
+      available,
+      price_changed,
+    }
+  }
+}
+
+/// A cart rebuilt from a past order, ready to be sent to checkout.
+#[derive(Serialize, ToSchema)]
+pub struct ReorderResponse {
+  pub shop_id: Id<Shop>,
+  pub items: Vec<ReorderItemResponse>,
+  /// Current total in cents of the items still available
+  pub total_cents: i64,
+  pub total_display: AmountDisplay,
+}
+
+impl From<(Order, Vec<ReorderLine>)> for ReorderResponse {
+  fn from((order, lines): (Order, Vec<ReorderLine>)) -> Self {
+    let items: Vec<ReorderItemResponse> = lines.into_iter().map(Into::into).collect();
+    let total_cents: i64 = items
+      .iter()
+      .filter_map(|item| {
+        item
+          .unit_price_cents
+          .map(|price| i64::from(price) * i64::from(item.quantity))
+      })
+      .sum();
+
+    Self {
+      shop_id: order.shop_id,
+      items,
+      total_cents,
+      total_display: total_cents.into(),
+    }
+  }
+}
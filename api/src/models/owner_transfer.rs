@@ -0,0 +1,47 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use validator::Validate;
+
+use domain::{Id, OwnerTransfer, OwnerTransferStatus, Role, User};
+
+#[derive(Deserialize, Validate, ToSchema)]
+pub struct OwnerTransferRequest {
+  /// User who becomes the owner once they accept
+  pub to_user_id: Id<User>,
+  /// Role the caller steps down to then
+  #[serde(default = "default_demote_to")]
+  #[schema(example = "admin")]
+  pub demote_to: Role,
+}
+
+fn default_demote_to() -> Role {
+  Role::Admin
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct OwnerTransferResponse {
+  pub id: Id<OwnerTransfer>,
+  pub from_user_id: Id<User>,
+  pub to_user_id: Id<User>,
+  pub demote_to: Role,
+  pub status: OwnerTransferStatus,
+  pub expires_at: DateTime<Utc>,
+  pub decided_at: Option<DateTime<Utc>>,
+  pub created_at: DateTime<Utc>,
+}
+
+impl From<OwnerTransfer> for OwnerTransferResponse {
+  fn from(transfer: OwnerTransfer) -> Self {
+    Self {
+      id: transfer.id,
+      from_user_id: transfer.from_user_id,
+      to_user_id: transfer.to_user_id,
+      demote_to: transfer.demote_to,
+      status: transfer.status,
+      expires_at: transfer.expires_at,
+      decided_at: transfer.decided_at,
+      created_at: transfer.created_at,
+    }
+  }
+}
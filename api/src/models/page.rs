@@ -0,0 +1,42 @@
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use domain::types::Page;
+
+use crate::models::{
+  ActorSummaryResponse, AuditEntryResponse, GuestResponse, InviteResponse, OrderResponse,
+  UserResponse, WalletDetailsResponse,
+};
+
+#[derive(Serialize, ToSchema)]
+#[aliases(
+  PaginatedUserResponse = PaginatedResponse<UserResponse>,
+  PaginatedOrderResponse = PaginatedResponse<OrderResponse>,
+  PaginatedAuditEntryResponse = PaginatedResponse<AuditEntryResponse>,
+  PaginatedWalletResponse = PaginatedResponse<WalletDetailsResponse>,
+  PaginatedActorSummaryResponse = PaginatedResponse<ActorSummaryResponse>,
+  PaginatedInviteResponse = PaginatedResponse<InviteResponse>,
+  PaginatedGuestResponse = PaginatedResponse<GuestResponse>
+)]
+pub struct PaginatedResponse<T> {
+  pub items: Vec<T>,
+  pub total: i64,
+  pub page: u32,
+  pub per_page: u32,
+  pub total_pages: i64,
+}
+
+impl<T, U> From<Page<U>> for PaginatedResponse<T>
+where
+  T: From<U>,
+{
+  fn from(page: Page<U>) -> Self {
+    Self {
+      total: page.total,
+      page: page.request.page(),
+      per_page: page.request.per_page(),
+      total_pages: page.total_pages(),
+      items: page.items.into_iter().map(T::from).collect(),
+    }
+  }
+}
@@ -0,0 +1,70 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use validator::Validate;
+
+use crate::models::{positive_amount, AmountDisplay};
+use domain::{
+  types::Money, Currency, Iban, Id, Payout, PayoutBatch, PayoutStatus, Transaction, Wallet,
+};
+
+#[derive(Deserialize, Validate, ToSchema)]
+pub struct PayoutRequest {
+  /// Amount, as integer cents or a decimal string such as
+  /// `"25.00"`; `amount_cents` is accepted too
+  #[serde(alias = "amount_cents")]
+  #[validate(custom(function = "positive_amount"))]
+  #[schema(example = "25.00")]
+  pub amount: Money,
+  /// Account to pay out to; remembered for the wallet's owner. Defaults to
+  /// the account on file.
+  pub iban: Option<Iban>,
+  /// Required along with `iban`
+  #[validate(length(min = 1, max = 70))]
+  #[schema(example = "Ann Smith")]
+  pub holder_name: Option<String>,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ListPayoutsQuery {
+  pub status: Option<PayoutStatus>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct PayoutResponse {
+  pub id: Id<Payout>,
+  pub wallet_id: Id<Wallet>,
+  /// Amount in euro cents, the only currency SEPA transfers are made in
+  pub amount_cents: i32,
+  pub amount_display: AmountDisplay,
+  pub iban: Iban,
+  pub holder_name: String,
+  pub status: PayoutStatus,
+  pub batch_id: Option<Id<PayoutBatch>>,
+  /// Debit of the wallet
+  pub transaction_id: Id<Transaction>,
+  /// Credit back to the wallet, for rejected payouts
+  pub refund_transaction_id: Option<Id<Transaction>>,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl From<Payout> for PayoutResponse {
+  fn from(payout: Payout) -> Self {
+    Self {
+      id: payout.id,
+      wallet_id: payout.wallet_id,
+      amount_cents: payout.amount.as_minor(),
+      amount_display: AmountDisplay::new(payout.amount.as_minor(), Currency::EUR),
+      iban: payout.iban,
+      holder_name: payout.holder_name,
+      status: payout.status,
+      batch_id: payout.batch_id,
+      transaction_id: payout.transaction_id,
+      refund_transaction_id: payout.refund_transaction_id,
+      created_at: payout.created_at,
+      updated_at: payout.updated_at,
+    }
+  }
+}
@@ -0,0 +1,197 @@
+use domain::{ActorKind, Permission};
+
+use crate::{
+  extractor::Authz,
+  models::{
+    ActorSummaryResponse, AuditEntryResponse, DebtorReportResponse, DebtorResponse, GuestResponse,
+    ShopMemberResponse, UserResponse, WalletDetailsResponse, WalletOwnerResponse,
+  },
+};
+
+/// Strips fields from a response that the caller is not allowed to read.
+///
+/// Listings use this to hand out partial records (e.g. names without emails)
+/// to roles that may see who exists but not the details.
+pub trait Redact {
+  fn redact(self, authz: &Authz) -> Self;
+}
+
+impl Redact for UserResponse {
+  fn redact(mut self, authz: &Authz) -> Self {
+    if !authz.has(Permission::ReadUserDetails) {
+      self.email = None;
+    }
+    self
+  }
+}
+
+impl Redact for GuestResponse {
+  fn redact(mut self, authz: &Authz) -> Self {
+    if !authz.has(Permission::ReadGuestDetails) {
+      self.email = None;
+      self.identifier = None;
+    }
+    self
+  }
+}
+
+impl Redact for ActorSummaryResponse {
+  fn redact(mut self, authz: &Authz) -> Self {
+    let details = match self.kind {
+      ActorKind::User => Permission::ReadUserDetails,
+      ActorKind::Guest => Permission::ReadGuestDetails,
+    };
+    if !authz.has(details) {
+      self.email = None;
+      self.identifier = None;
+    }
+    self
+  }
+}
+
+impl Redact for DebtorResponse {
+  fn redact(mut self, authz: &Authz) -> Self {
+    let details = match self.kind {
+      Some(ActorKind::User) => Permission::ReadUserDetails,
+      Some(ActorKind::Guest) | None => Permission::ReadGuestDetails,
+    };
+    if !authz.has(details) {
+      self.email = None;
+    }
+    self
+  }
+}
+
+impl Redact for DebtorReportResponse {
+  fn redact(mut self, authz: &Authz) -> Self {
+    self.debtors = self
+      .debtors
+      .into_iter()
+      .map(|debtor| debtor.redact(authz))
+      .collect();
+    self
+  }
+}
+
+impl Redact for AuditEntryResponse {
+  fn redact(mut self, authz: &Authz) -> Self {
+    if !authz.has(Permission::ReadUserDetails) {
+      self.ip_address = None;
+      self.details = None;
+      self.personal_data = None;
+    }
+    self
+  }
+}
+
+impl Redact for ShopMemberResponse {
+  fn redact(mut self, authz: &Authz) -> Self {
+    self.user = self.user.redact(authz);
+    self
+  }
+}
+
+impl Redact for WalletDetailsResponse {
+  fn redact(mut self, authz: &Authz) -> Self {
+    self.owner_details = self.owner_details.map(|owner| match owner {
+      WalletOwnerResponse::User(user) => WalletOwnerResponse::User(user.redact(authz)),
+      WalletOwnerResponse::Guest(guest) => WalletOwnerResponse::Guest(guest.redact(authz)),
+    });
+    self
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::test_support::create_user;
+  use chrono::Utc;
+  use domain::{
+    types::Money, ActorDetails, Currency, Email, Guest, Id, Role, Wallet, WalletAppearance,
+    WalletDetails,
+  };
+
+  #[test]
+  fn test_redact_user_keeps_details_with_permission() {
+    let owner = Authz::new(create_user(Role::Owner), &[]);
+    let response = UserResponse::from(create_user(Role::Admin)).redact(&owner);
+
+    assert!(response.email.is_some());
+  }
+
+  #[test]
+  fn test_redact_user_strips_details_without_permission() {
+    let nobody = Authz::new(create_user(Role::Undefined), &[]);
+    let response = UserResponse::from(create_user(Role::Admin)).redact(&nobody);
+
+    assert!(response.email.is_none());
+    assert_eq!(response.first_name, "Test");
+  }
+
+  #[test]
+  fn test_redact_wallet_strips_owner_details_without_permission() {
+    let guest = Guest {
+      id: Id::new(),
+      actor_id: Id::new(),
+      email: Some(Email::new("guest@example.com".to_string())),
+      verified: true,
+      identifier: Some("04A224B2C35E80".to_string()),
+      created_at: Utc::now(),
+      updated_at: None,
+    };
+    let details = WalletDetails {
+      wallet: Wallet {
+        id: Id::new(),
+        owner: Some(guest.actor_id),
+        label: None,
+        currency: Currency::EUR,
+        allow_overdraft: false,
+        legal_hold: false,
+        frozen: false,
+        appearance: WalletAppearance::default(),
+        created_at: Utc::now(),
+        updated_at: None,
+      },
+      owner: Some(ActorDetails::Guest(guest)),
+      balance: Money::from_minor(1000),
+    };
+    let nobody = Authz::new(create_user(Role::Undefined), &[]);
+    let response = WalletDetailsResponse::from(details).redact(&nobody);
+
+    let Some(WalletOwnerResponse::Guest(guest)) = response.owner_details else {
+      panic!("expected a guest owner");
+    };
+    assert!(guest.email.is_none());
+    assert!(guest.identifier.is_none());
+    assert_eq!(response.balance_cents, 1000);
+  }
+
+  #[test]
+  fn test_redact_actor_summary_strips_guest_details_only_without_permission() {
+    let summary = |kind| ActorSummaryResponse {
+      actor_id: Id::new(),
+      kind,
+      user_id: None,
+      guest_id: None,
+      display_name: None,
+      email: Some(Email::new("someone@example.com".to_string())),
+      identifier: Some("04A224B2C35E80".to_string()),
+      role: None,
+      wallet_count: 1,
+      currency: Some(Currency::EUR),
+      balance_cents: 1000,
+      balance_display: 1000.into(),
+      refreshed_at: Utc::now(),
+    };
+    let cashier = Authz::new(create_user(Role::Cashier), &[]);
+
+    let guest = summary(ActorKind::Guest).redact(&cashier);
+    assert!(guest.email.is_some());
+    assert!(guest.identifier.is_some());
+
+    let user = summary(ActorKind::User).redact(&cashier);
+    assert!(user.email.is_none());
+    assert!(user.identifier.is_none());
+    assert_eq!(user.balance_cents, 1000);
+  }
+}
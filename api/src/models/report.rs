@@ -0,0 +1,125 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::models::AmountDisplay;
+use domain::{DailySales, Id, LiveShopStats, OfferingSales, SalesReport, ShopOffering};
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SalesReportQuery {
+  /// Only orders placed at or after this time
+  pub from: Option<DateTime<Utc>>,
+  /// Only orders placed before this time
+  pub to: Option<DateTime<Utc>>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct OfferingSalesResponse {
+  /// Unset for offerings deleted since
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub offering_id: Option<Id<ShopOffering>>,
+  pub name: String,
+  pub quantity: i64,
+  pub revenue_cents: i64,
+  pub revenue_display: AmountDisplay,
+}
+
+impl From<OfferingSales> for OfferingSalesResponse {
+  fn from(sales: OfferingSales) -> Self {
+    Self {
+      offering_id: sales.offering_id,
+      name: sales.name,
+      quantity: sales.quantity,
+      revenue_cents: sales.revenue_cents,
+      revenue_display: sales.revenue_cents.into(),
+    }
+  }
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct DailySalesResponse {
+  /// Calendar day at the venue (`VENUE_TIMEZONE`)
+  pub day: NaiveDate,
+  pub order_count: i64,
+  pub revenue_cents: i64,
+  pub revenue_display: AmountDisplay,
+}
+
+impl From<DailySales> for DailySalesResponse {
+  fn from(sales: DailySales) -> Self {
+    Self {
+      day: sales.day,
+      order_count: sales.order_count,
+      revenue_cents: sales.revenue_cents,
+      revenue_display: sales.revenue_cents.into(),
+    }
+  }
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct SalesReportResponse {
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub from: Option<DateTime<Utc>>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub to: Option<DateTime<Utc>>,
+  pub order_count: i64,
+  /// Everything sold, including orders refunded since
+  pub gross_cents: i64,
+  pub gross_display: AmountDisplay,
+  pub refunded_cents: i64,
+  pub refunded_display: AmountDisplay,
+  pub net_cents: i64,
+  pub net_display: AmountDisplay,
+  pub by_offering: Vec<OfferingSalesResponse>,
+  pub by_day: Vec<DailySalesResponse>,
+}
+
+impl From<SalesReport> for SalesReportResponse {
+  fn from(report: SalesReport) -> Self {
+    Self {
+      net_cents: report.net_cents(),
+      net_display: report.net_cents().into(),
+      from: report.from,
+      to: report.to,
+      order_count: report.order_count,
+      gross_cents: report.gross_cents,
+      gross_display: report.gross_cents.into(),
+      refunded_cents: report.refunded_cents,
+      refunded_display: report.refunded_cents.into(),
+      by_offering: report.by_offering.into_iter().map(Into::into).collect(),
+      by_day: report.by_day.into_iter().map(Into::into).collect(),
+    }
+  }
+}
+
+/// Today's running figures of a shop, with days as at the venue, pushed to
+/// live dashboards.
+#[derive(Serialize, ToSchema)]
+pub struct LiveShopStatsEvent {
+  pub order_count: i64,
+  /// Paid minus refunded
+  pub revenue_cents: i64,
+  pub revenue_display: AmountDisplay,
+  pub average_ticket_cents: i64,
+  pub average_ticket_display: AmountDisplay,
+  /// Orders paid within the last minute
+  pub orders_per_minute: usize,
+  pub at: DateTime<Utc>,
+}
+
+impl LiveShopStatsEvent {
+  pub fn new(stats: &mut LiveShopStats, at: DateTime<Utc>) -> Self {
+    stats.roll_over(at);
+
+    Self {
+      order_count: stats.order_count,
+      revenue_cents: stats.revenue_cents,
+      revenue_display: stats.revenue_cents.into(),
+      average_ticket_cents: stats.average_ticket_cents(),
+      average_ticket_display: stats.average_ticket_cents().into(),
+      orders_per_minute: stats.orders_per_minute(at),
+      at,
+    }
+  }
+}
@@ -0,0 +1,26 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use domain::{RetentionOutcome, RetentionRule};
+
+#[derive(Serialize, ToSchema)]
+pub struct RetentionOutcomeResponse {
+  pub rule: RetentionRule,
+  pub retention_days: u32,
+  /// Data older than this is affected
+  pub cutoff: DateTime<Utc>,
+  /// Rows that would be deleted or anonymized
+  pub affected: u64,
+}
+
+impl From<RetentionOutcome> for RetentionOutcomeResponse {
+  fn from(outcome: RetentionOutcome) -> Self {
+    Self {
+      rule: outcome.rule,
+      retention_days: outcome.retention_days,
+      cutoff: outcome.cutoff,
+      affected: outcome.affected,
+    }
+  }
+}
@@ -0,0 +1,58 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use validator::Validate;
+
+use domain::{Actor, Id, ReviewItem, ReviewStatus, RiskSignal, Wallet};
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ListReviewsQuery {
+  /// Defaults to `open`
+  #[param(inline)]
+  pub status: Option<ReviewStatus>,
+}
+
+#[derive(Deserialize, Validate, ToSchema)]
+pub struct ResolveReviewRequest {
+  pub status: ReviewStatus,
+  #[validate(length(max = 1024))]
+  pub note: Option<String>,
+  /// Lift the freeze that was placed on the wallet when the review was opened
+  #[serde(default)]
+  pub unfreeze_wallet: bool,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ReviewItemResponse {
+  pub id: Id<ReviewItem>,
+  pub wallet_id: Id<Wallet>,
+  pub signal: RiskSignal,
+  pub details: String,
+  pub status: ReviewStatus,
+  pub wallet_frozen: bool,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub resolved_by: Option<Id<Actor>>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub resolution_note: Option<String>,
+  pub created_at: DateTime<Utc>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl From<ReviewItem> for ReviewItemResponse {
+  fn from(item: ReviewItem) -> Self {
+    Self {
+      id: item.id,
+      wallet_id: item.wallet_id,
+      signal: item.signal,
+      details: item.details,
+      status: item.status,
+      wallet_frozen: item.wallet_frozen,
+      resolved_by: item.resolved_by,
+      resolution_note: item.resolution_note,
+      created_at: item.created_at,
+      updated_at: item.updated_at,
+    }
+  }
+}
@@ -0,0 +1,65 @@
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::route_permissions::{Guard, RouteCoverage};
+use domain::{Permission, Role};
+
+#[derive(Serialize, ToSchema)]
+pub struct RequiredPermissionsResponse {
+  /// How the permissions combine, e.g. `all`, `any` or `owner_or_all`
+  pub mode: &'static str,
+  pub permissions: Vec<Permission>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct RouteCoverageResponse {
+  pub method: &'static str,
+  pub path: String,
+  /// Deliberately reachable without a session
+  pub public: bool,
+  pub required: Option<RequiredPermissionsResponse>,
+  /// Roles let through regardless of ownership or shop staff
+  pub allowed_roles: Vec<Role>,
+  /// Neither guarded nor deliberately public
+  pub unprotected: bool,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct RouteAuditResponse {
+  pub unprotected_count: usize,
+  pub routes: Vec<RouteCoverageResponse>,
+}
+
+impl From<Guard> for RequiredPermissionsResponse {
+  fn from(guard: Guard) -> Self {
+    Self {
+      mode: guard.mode(),
+      permissions: guard.permissions().to_vec(),
+    }
+  }
+}
+
+impl From<RouteCoverage> for RouteCoverageResponse {
+  fn from(route: RouteCoverage) -> Self {
+    Self {
+      method: route.method_name(),
+      unprotected: route.is_unprotected(),
+      public: route.public,
+      required: route.guard.map(Into::into),
+      allowed_roles: route
+        .guard
+        .map(|guard| guard.allowed_roles())
+        .unwrap_or_default(),
+      path: route.path,
+    }
+  }
+}
+
+impl From<Vec<RouteCoverage>> for RouteAuditResponse {
+  fn from(routes: Vec<RouteCoverage>) -> Self {
+    Self {
+      unprotected_count: routes.iter().filter(|r| r.is_unprotected()).count(),
+      routes: routes.into_iter().map(Into::into).collect(),
+    }
+  }
+}
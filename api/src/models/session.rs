@@ -0,0 +1,37 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use domain::{Id, Session};
+
+#[derive(Serialize, ToSchema)]
+pub struct SessionResponse {
+  pub id: Id<Session>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub user_agent: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub ip_address: Option<String>,
+  /// Whether this is the session the request was made with
+  pub current: bool,
+  /// Whether sensitive requests in this session must be signed by a device key
+  pub device_bound: bool,
+  pub expires_at: DateTime<Utc>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub last_used_at: Option<DateTime<Utc>>,
+  pub created_at: DateTime<Utc>,
+}
+
+impl SessionResponse {
+  pub fn new(session: Session, current_token: Option<&str>) -> Self {
+    Self {
+      id: session.id,
+      current: current_token == Some(session.token.as_str()),
+      device_bound: session.is_device_bound(),
+      expires_at: session.expires_at(),
+      user_agent: session.user_agent,
+      ip_address: session.ip_address,
+      last_used_at: session.last_used_at,
+      created_at: session.created_at,
+    }
+  }
+}
@@ -0,0 +1,160 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use validator::Validate;
+
+use domain::{
+  Actor, Currency, Feature, FeatureSwitch, Id, InviteDomainPolicy, InviteDomainRule, Role, Settings,
+};
+
+#[derive(Deserialize, Validate, ToSchema)]
+pub struct UpdateSettingsRequest {
+  /// Days an invite can be accepted for; applies to invites sent from now on
+  #[validate(range(min = 1, max = 90))]
+  #[schema(example = 7)]
+  pub invite_expiration_days: i32,
+  /// Whether wallets of new members and guests may go below zero
+  pub allow_overdraft_by_default: bool,
+  /// ISO 4217 code new wallets are opened in; those open keep theirs
+  pub currency: Currency,
+  /// Shown as the sender of emails; the bare address when unset
+  #[validate(length(min = 1, max = 100))]
+  #[schema(example = "Cayo Club")]
+  pub email_sender_name: Option<String>,
+}
+
+impl UpdateSettingsRequest {
+  pub fn into_settings(self) -> Settings {
+    Settings {
+      invite_expiration_days: self.invite_expiration_days,
+      allow_overdraft_by_default: self.allow_overdraft_by_default,
+      currency: self.currency,
+      email_sender_name: self.email_sender_name,
+      changed_by: None,
+      changed_at: None,
+    }
+  }
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct SettingsResponse {
+  pub invite_expiration_days: i32,
+  pub allow_overdraft_by_default: bool,
+  pub currency: Currency,
+  pub email_sender_name: Option<String>,
+  /// Actor who changed them last; unset while they are the defaults
+  pub changed_by: Option<Id<Actor>>,
+  pub changed_at: Option<DateTime<Utc>>,
+}
+
+impl From<Settings> for SettingsResponse {
+  fn from(settings: Settings) -> Self {
+    Self {
+      invite_expiration_days: settings.invite_expiration_days,
+      allow_overdraft_by_default: settings.allow_overdraft_by_default,
+      currency: settings.currency,
+      email_sender_name: settings.email_sender_name,
+      changed_by: settings.changed_by,
+      changed_at: settings.changed_at,
+    }
+  }
+}
+
+#[derive(Deserialize, Serialize, ToSchema)]
+pub struct InviteDomainRuleBody {
+  /// Domain of the addresses, e.g. `club.example`; covers its subdomains too
+  #[schema(example = "club.example")]
+  pub domain: String,
+  /// Allows the domain when set, denies it otherwise
+  pub allowed: bool,
+  /// Only invites with this role; every invite without one
+  pub role: Option<Role>,
+}
+
+#[derive(Deserialize, Validate, ToSchema)]
+pub struct UpdateInviteDomainsRequest {
+  #[validate(length(max = 100))]
+  pub rules: Vec<InviteDomainRuleBody>,
+}
+
+impl UpdateInviteDomainsRequest {
+  /// The rules with domains lowercased and without a leading `@`, or why
+  /// they are unusable.
+  pub fn into_rules(self) -> Result<Vec<InviteDomainRule>, String> {
+    let mut rules: Vec<InviteDomainRule> = Vec::with_capacity(self.rules.len());
+    for rule in self.rules {
+      let domain = rule.domain.trim().trim_start_matches('@').to_lowercase();
+      if domain.is_empty() || domain.contains(['@', ' ']) {
+        return Err(format!("`{}` is not a domain", rule.domain));
+      }
+      if rules
+        .iter()
+        .any(|r| r.domain == domain && r.role == rule.role)
+      {
+        return Err(format!("`{}` has more than one rule for a role", domain));
+      }
+
+      rules.push(InviteDomainRule {
+        domain,
+        allowed: rule.allowed,
+        role: rule.role,
+      });
+    }
+
+    Ok(rules)
+  }
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct InviteDomainsResponse {
+  /// Denying rules win; once a rule allows a domain for a role, only allowed
+  /// domains may be invited with it
+  pub rules: Vec<InviteDomainRuleBody>,
+}
+
+impl From<InviteDomainPolicy> for InviteDomainsResponse {
+  fn from(policy: InviteDomainPolicy) -> Self {
+    Self {
+      rules: policy
+        .rules
+        .into_iter()
+        .map(|rule| InviteDomainRuleBody {
+          domain: rule.domain,
+          allowed: rule.allowed,
+          role: rule.role,
+        })
+        .collect(),
+    }
+  }
+}
+
+#[derive(Deserialize, Validate, ToSchema)]
+pub struct UpdateFeatureSwitchRequest {
+  pub enabled: bool,
+  /// Told to callers while the feature is off, e.g. what is being done
+  #[validate(length(min = 1, max = 500))]
+  #[schema(example = "Payment provider outage")]
+  pub reason: Option<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct FeatureSwitchResponse {
+  pub feature: Feature,
+  pub enabled: bool,
+  pub reason: Option<String>,
+  /// Actor who switched it last; unset for features never switched
+  pub changed_by: Option<Id<Actor>>,
+  pub changed_at: Option<DateTime<Utc>>,
+}
+
+impl From<FeatureSwitch> for FeatureSwitchResponse {
+  fn from(switch: FeatureSwitch) -> Self {
+    Self {
+      feature: switch.feature,
+      enabled: switch.enabled,
+      reason: switch.reason,
+      changed_by: switch.changed_by,
+      changed_at: switch.changed_at,
+    }
+  }
+}
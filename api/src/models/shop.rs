@@ -0,0 +1,116 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use validator::Validate;
+
+use crate::models::{AmountDisplay, UserResponse};
+use domain::{Id, PickupSlots, Shop, ShopMember, ShopOffering, User};
+
+#[derive(Deserialize, Validate, ToSchema)]
+pub struct AddShopMemberRequest {
+  pub user_id: Id<User>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ShopMemberResponse {
+  pub id: Id<ShopMember>,
+  pub shop_id: Id<Shop>,
+  pub user: UserResponse,
+  /// When the user joined the shop
+  pub created_at: DateTime<Utc>,
+}
+
+impl From<(ShopMember, User)> for ShopMemberResponse {
+  fn from((member, user): (ShopMember, User)) -> Self {
+    Self {
+      id: member.id,
+      shop_id: member.shop_id,
+      user: user.into(),
+      created_at: member.created_at,
+    }
+  }
+}
+
+#[derive(Deserialize, Validate, ToSchema)]
+pub struct PickupSlotsRequest {
+  /// Length of a slot; slots start at multiples of it since midnight UTC
+  #[validate(range(min = 5, max = 240))]
+  pub slot_minutes: i32,
+  /// Pre-orders each slot takes
+  #[validate(range(min = 1, max = 10000))]
+  pub capacity: i32,
+}
+
+impl From<PickupSlotsRequest> for PickupSlots {
+  fn from(request: PickupSlotsRequest) -> Self {
+    Self {
+      length: Duration::minutes(request.slot_minutes.into()),
+      capacity: request.capacity,
+    }
+  }
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct PickupSlotsResponse {
+  pub slot_minutes: i64,
+  pub capacity: i32,
+}
+
+impl From<PickupSlots> for PickupSlotsResponse {
+  fn from(slots: PickupSlots) -> Self {
+    Self {
+      slot_minutes: slots.length.num_minutes(),
+      capacity: slots.capacity,
+    }
+  }
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ListPickupSlotsQuery {
+  /// Defaults to now
+  pub from: Option<DateTime<Utc>>,
+  /// Defaults to a day after `from`; at most a week after it
+  pub to: Option<DateTime<Utc>>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct PickupSlotResponse {
+  pub starts_at: DateTime<Utc>,
+  /// Pre-orders the slot can still take
+  pub remaining: i64,
+}
+
+impl From<(DateTime<Utc>, i64)> for PickupSlotResponse {
+  fn from((starts_at, remaining): (DateTime<Utc>, i64)) -> Self {
+    Self {
+      starts_at,
+      remaining,
+    }
+  }
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ShopOfferingResponse {
+  pub id: Id<ShopOffering>,
+  pub shop_id: Id<Shop>,
+  pub name: String,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub description: Option<String>,
+  /// Current price in cents
+  pub price_cents: i32,
+  pub price_display: AmountDisplay,
+}
+
+impl From<ShopOffering> for ShopOfferingResponse {
+  fn from(offering: ShopOffering) -> Self {
+    Self {
+      id: offering.id,
+      shop_id: offering.shop_id,
+      name: offering.name,
+      description: offering.description,
+      price_cents: offering.price_cents.as_minor(),
+      price_display: offering.price_cents.into(),
+    }
+  }
+}
@@ -0,0 +1,106 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::models::AmountDisplay;
+use domain::{
+  Currency, Id, Transaction, TransactionEventData, TransactionMetadata, Wallet, WalletDetails,
+};
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct StreamQuery {
+  /// Only updates of this wallet, e.g. for a till's customer display
+  pub wallet_id: Option<Id<Wallet>>,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct PollQuery {
+  /// Cursor of the previous response; without one, only what happens from
+  /// now on is returned
+  pub since: Option<i64>,
+  /// Seconds to wait for something to happen (default 25, max 30)
+  pub wait: Option<u64>,
+  /// Only updates of this wallet, e.g. for a till's customer display
+  pub wallet_id: Option<Id<Wallet>>,
+}
+
+/// What happened since the cursor of the request, for clients that cannot
+/// keep a stream open.
+#[derive(Serialize, ToSchema)]
+pub struct PollResponse {
+  /// Where to continue from in the next request
+  pub cursor: i64,
+  /// The same events `GET /api/stream` pushes, oldest first; empty if
+  /// nothing happened while waiting
+  pub events: Vec<PolledEvent>,
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(tag = "event", content = "data", rename_all = "snake_case")]
+pub enum PolledEvent {
+  Transaction(TransactionStreamEvent),
+  Balance(BalanceStreamEvent),
+}
+
+/// Data of the `transaction` events streamed to wallet readers.
+#[derive(Serialize, ToSchema)]
+pub struct TransactionStreamEvent {
+  pub id: Id<Transaction>,
+  pub source: Id<Wallet>,
+  pub destination: Id<Wallet>,
+  /// Amount in cents
+  pub amount_cents: i32,
+  pub amount_display: AmountDisplay,
+  pub currency: Currency,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub description: Option<String>,
+  /// Set on refunds, pointing at the transaction they compensate
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub reversal_of: Option<Id<Transaction>>,
+  pub metadata: TransactionMetadata,
+  pub created_at: DateTime<Utc>,
+}
+
+impl From<TransactionEventData> for TransactionStreamEvent {
+  fn from(transaction: TransactionEventData) -> Self {
+    Self {
+      id: transaction.transaction_id,
+      source: transaction.source_wallet_id,
+      destination: transaction.destination_wallet_id,
+      amount_cents: transaction.amount_cents,
+      amount_display: AmountDisplay::new(transaction.amount_cents, transaction.currency),
+      currency: transaction.currency,
+      description: transaction.description,
+      reversal_of: transaction.reversal_of,
+      metadata: transaction.metadata,
+      created_at: transaction.created_at,
+    }
+  }
+}
+
+/// Data of the `balance` events streamed to wallet readers, sent after each
+/// transaction touching the wallet.
+#[derive(Serialize, ToSchema)]
+pub struct BalanceStreamEvent {
+  pub wallet_id: Id<Wallet>,
+  /// Balance in cents
+  pub balance_cents: i32,
+  pub balance_display: AmountDisplay,
+  pub currency: Currency,
+  /// The transaction that changed it
+  pub transaction_id: Id<Transaction>,
+}
+
+impl From<(WalletDetails, Id<Transaction>)> for BalanceStreamEvent {
+  fn from((details, transaction_id): (WalletDetails, Id<Transaction>)) -> Self {
+    Self {
+      wallet_id: details.wallet.id,
+      balance_cents: details.balance.as_minor(),
+      balance_display: AmountDisplay::new(details.balance.as_minor(), details.wallet.currency),
+      currency: details.wallet.currency,
+      transaction_id,
+    }
+  }
+}
@@ -0,0 +1,68 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use validator::Validate;
+
+use crate::models::AmountDisplay;
+use domain::{Actor, Currency, Id, Transaction, TransactionMetadata, Wallet};
+
+#[derive(Deserialize, Validate, ToSchema)]
+pub struct RefundRequest {
+  #[validate(length(max = 255))]
+  #[schema(example = "Charged twice by mistake")]
+  pub description: Option<String>,
+}
+
+#[derive(Deserialize, Clone, Copy, Default, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+  #[default]
+  Csv,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ExportQuery {
+  /// Output format, defaults to `csv`
+  pub format: Option<ExportFormat>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct TransactionResponse {
+  pub id: Id<Transaction>,
+  pub source: Id<Wallet>,
+  pub destination: Id<Wallet>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub executor: Option<Id<Actor>>,
+  /// Amount in cents
+  pub amount_cents: i32,
+  pub amount_display: AmountDisplay,
+  /// That of both wallets
+  pub currency: Currency,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub description: Option<String>,
+  /// Set on refunds, pointing at the transaction they compensate
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub reversal_of: Option<Id<Transaction>>,
+  /// Context such as the terminal it was booked on
+  pub metadata: TransactionMetadata,
+  pub created_at: DateTime<Utc>,
+}
+
+impl From<Transaction> for TransactionResponse {
+  fn from(transaction: Transaction) -> Self {
+    Self {
+      id: transaction.id,
+      source: transaction.source,
+      destination: transaction.destination,
+      executor: transaction.executor,
+      amount_cents: transaction.amount.as_minor(),
+      amount_display: AmountDisplay::new(transaction.amount.as_minor(), transaction.currency),
+      currency: transaction.currency,
+      description: transaction.description,
+      reversal_of: transaction.reversal_of,
+      metadata: transaction.metadata,
+      created_at: transaction.created_at,
+    }
+  }
+}
@@ -0,0 +1,26 @@
+use serde::Deserialize;
+use utoipa::ToSchema;
+use validator::Validate;
+
+use crate::models::positive_amount;
+use domain::types::Money;
+
+#[derive(Deserialize, Validate, ToSchema)]
+pub struct TransferRequest {
+  /// Email the recipient is registered with
+  #[validate(email)]
+  #[schema(example = "friend@example.com")]
+  pub recipient_email: String,
+  /// Amount, as integer cents or a decimal string such as
+  /// `"5.00"`; `amount_cents` is accepted too
+  #[serde(alias = "amount_cents")]
+  #[validate(custom(function = "positive_amount"))]
+  #[schema(example = "5.00")]
+  pub amount: Money,
+  #[validate(length(max = 255))]
+  #[schema(example = "Drinks last night")]
+  pub description: Option<String>,
+  /// Let the recipient know by email
+  #[serde(default)]
+  pub notify_recipient: bool,
+}
@@ -1,17 +1,40 @@
 use chrono::{DateTime, Utc};
-use serde::Serialize;
-use utoipa::ToSchema;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use validator::Validate;
 
-use domain::{Actor, Email, Id, Role, User};
+use domain::{
+  types::SortOrder, Actor, Email, EmailChange, Id, Locale, Permission, PermissionOverride, Role,
+  User, UserSortField,
+};
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ListUsersQuery {
+  /// Page number, starting at 1
+  pub page: Option<u32>,
+  /// Items per page (max 100)
+  pub per_page: Option<u32>,
+  #[param(inline)]
+  pub sort_by: Option<UserSortField>,
+  #[param(inline)]
+  pub sort_order: Option<SortOrder>,
+  #[param(inline)]
+  pub role: Option<Role>,
+  /// Case-insensitive match on email, first or last name
+  pub search: Option<String>,
+}
 
 #[derive(Serialize, ToSchema)]
 pub struct UserResponse {
   pub id: Id<User>,
   pub actor_id: Id<Actor>,
-  pub email: Email,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub email: Option<Email>,
   pub first_name: String,
   pub last_name: String,
   pub role: Role,
+  pub locale: Locale,
   pub created_at: DateTime<Utc>,
   #[serde(skip_serializing_if = "Option::is_none")]
   pub updated_at: Option<DateTime<Utc>>,
@@ -22,12 +45,153 @@ impl From<User> for UserResponse {
     Self {
       id: user.id,
       actor_id: user.actor_id,
-      email: user.email,
+      email: Some(user.email),
       first_name: user.first_name,
       last_name: user.last_name,
       role: user.role,
+      locale: user.locale,
       created_at: user.created_at,
       updated_at: user.updated_at,
     }
   }
 }
+
+/// Fields left out stay as they are.
+#[derive(Deserialize, Validate, ToSchema)]
+pub struct UpdateUserRequest {
+  #[validate(length(min = 1, max = 127))]
+  #[schema(example = "John")]
+  pub first_name: Option<String>,
+  #[validate(length(min = 1, max = 127))]
+  #[schema(example = "Doe")]
+  pub last_name: Option<String>,
+  /// Language of the emails sent to the user
+  pub locale: Option<Locale>,
+  /// Takes effect once confirmed with the token emailed to the new address
+  #[validate(email)]
+  #[schema(example = "john.doe@example.com")]
+  pub email: Option<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct UpdateUserResponse {
+  pub user: UserResponse,
+  /// Address awaiting confirmation, if the update asked for a new one
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub pending_email: Option<Email>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub pending_email_expires_at: Option<DateTime<Utc>>,
+}
+
+impl From<(User, Option<EmailChange>)> for UpdateUserResponse {
+  fn from((user, change): (User, Option<EmailChange>)) -> Self {
+    Self {
+      user: user.into(),
+      pending_email_expires_at: change.as_ref().map(|c| c.expires_at),
+      pending_email: change.map(|c| c.email),
+    }
+  }
+}
+
+#[derive(Deserialize, Validate, ToSchema)]
+pub struct PasswordSetupRequest {
+  #[validate(length(min = 8, max = 127))]
+  #[schema(example = "password123")]
+  pub password: String,
+}
+
+#[derive(Deserialize, Validate, ToSchema)]
+pub struct ChangeRoleRequest {
+  pub role: Role,
+}
+
+/// Replaces every grant and revocation of the user.
+#[derive(Deserialize, Validate, ToSchema)]
+pub struct UpdatePermissionsRequest {
+  /// Permissions the user gets on top of their role
+  #[serde(default)]
+  pub grants: Vec<Permission>,
+  /// Permissions taken away from what their role allows
+  #[serde(default)]
+  pub revocations: Vec<Permission>,
+}
+
+impl UpdatePermissionsRequest {
+  /// One override per permission; repeats in either list are dropped.
+  pub fn into_overrides(self) -> Vec<PermissionOverride> {
+    let grants = self
+      .grants
+      .into_iter()
+      .map(|permission| PermissionOverride {
+        permission,
+        granted: true,
+      });
+    let revocations = self
+      .revocations
+      .into_iter()
+      .map(|permission| PermissionOverride {
+        permission,
+        granted: false,
+      });
+
+    let mut overrides: Vec<PermissionOverride> = Vec::new();
+    for o in grants.chain(revocations) {
+      if !overrides.iter().any(|seen| seen.permission == o.permission) {
+        overrides.push(o);
+      }
+    }
+    overrides
+  }
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct UserPermissionsResponse {
+  pub user_id: Id<User>,
+  pub role: Role,
+  pub grants: Vec<Permission>,
+  pub revocations: Vec<Permission>,
+  /// What the user may do after applying grants and revocations to the role
+  pub effective: Vec<Permission>,
+}
+
+impl From<(User, Vec<PermissionOverride>)> for UserPermissionsResponse {
+  fn from((user, overrides): (User, Vec<PermissionOverride>)) -> Self {
+    let pick = |granted: bool| {
+      overrides
+        .iter()
+        .filter(|o| o.granted == granted)
+        .map(|o| o.permission)
+        .collect()
+    };
+
+    Self {
+      user_id: user.id,
+      role: user.role,
+      grants: pick(true),
+      revocations: pick(false),
+      effective: user.role.permissions_with(&overrides),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_into_overrides_drops_repeats() {
+    let request = UpdatePermissionsRequest {
+      grants: vec![Permission::SendInvite, Permission::SendInvite],
+      revocations: vec![Permission::ReadAuditLog, Permission::ReadAuditLog],
+    };
+
+    let overrides = request.into_overrides();
+    assert_eq!(overrides.len(), 2);
+    assert!(overrides
+      .iter()
+      .any(|o| o.permission == Permission::SendInvite && o.granted));
+    assert!(overrides
+      .iter()
+      .any(|o| o.permission == Permission::ReadAuditLog && !o.granted));
+  }
+}
@@ -0,0 +1,270 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use validator::Validate;
+
+use crate::models::{
+  positive_amount, AmountDisplay, BudgetEnvelopeResponse, GuestResponse, UserResponse,
+};
+use domain::{
+  types::Money, Actor, ActorDetails, Currency, Id, LegalHoldAction, TopUp, TopUpStatus, Wallet,
+  WalletDetails, WalletLabel, WalletLegalHoldEvent,
+};
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ListWalletsQuery {
+  /// Page number, starting at 1
+  pub page: Option<u32>,
+  /// Items per page (max 100)
+  pub per_page: Option<u32>,
+  /// Only system wallets if true, only member wallets if false
+  pub system: Option<bool>,
+  /// Case-insensitive match on the owner's email, names or guest identifier.
+  /// Needs the permission to read user details.
+  pub search: Option<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct WalletResponse {
+  pub id: Id<Wallet>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub owner: Option<Id<Actor>>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub label: Option<WalletLabel>,
+  /// What the balance and every transaction of the wallet are in
+  pub currency: Currency,
+  pub allow_overdraft: bool,
+  pub legal_hold: bool,
+  pub frozen: bool,
+  /// Name the owner gave the wallet
+  #[serde(skip_serializing_if = "Option::is_none")]
+  #[schema(example = "Bar budget")]
+  pub display_name: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  #[schema(example = "#ff8800")]
+  pub color: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  #[schema(example = "beer")]
+  pub icon: Option<String>,
+  pub created_at: DateTime<Utc>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl From<Wallet> for WalletResponse {
+  fn from(wallet: Wallet) -> Self {
+    Self {
+      id: wallet.id,
+      owner: wallet.owner,
+      label: wallet.label,
+      currency: wallet.currency,
+      allow_overdraft: wallet.allow_overdraft,
+      legal_hold: wallet.legal_hold,
+      frozen: wallet.frozen,
+      display_name: wallet.appearance.display_name().map(ToString::to_string),
+      color: wallet.appearance.color().map(ToString::to_string),
+      icon: wallet.appearance.icon().map(ToString::to_string),
+      created_at: wallet.created_at,
+      updated_at: wallet.updated_at,
+    }
+  }
+}
+
+/// The user or guest a wallet belongs to.
+#[derive(Serialize, ToSchema)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum WalletOwnerResponse {
+  User(UserResponse),
+  Guest(GuestResponse),
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct WalletDetailsResponse {
+  pub id: Id<Wallet>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub owner: Option<Id<Actor>>,
+  /// The user or guest behind `owner`
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub owner_details: Option<WalletOwnerResponse>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub label: Option<WalletLabel>,
+  /// What the balance and every transaction of the wallet are in
+  pub currency: Currency,
+  pub allow_overdraft: bool,
+  pub legal_hold: bool,
+  pub frozen: bool,
+  /// Name the owner gave the wallet
+  #[serde(skip_serializing_if = "Option::is_none")]
+  #[schema(example = "Bar budget")]
+  pub display_name: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  #[schema(example = "#ff8800")]
+  pub color: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  #[schema(example = "beer")]
+  pub icon: Option<String>,
+  /// Balance in cents
+  pub balance_cents: i32,
+  pub balance_display: AmountDisplay,
+  /// Budget envelopes, shown to the owner only
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub envelopes: Option<Vec<BudgetEnvelopeResponse>>,
+  pub created_at: DateTime<Utc>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl From<ActorDetails> for WalletOwnerResponse {
+  fn from(details: ActorDetails) -> Self {
+    match details {
+      ActorDetails::User(user) => WalletOwnerResponse::User(user.into()),
+      ActorDetails::Guest(guest) => WalletOwnerResponse::Guest(guest.into()),
+    }
+  }
+}
+
+impl From<WalletDetails> for WalletDetailsResponse {
+  fn from(details: WalletDetails) -> Self {
+    let wallet = details.wallet;
+
+    Self {
+      id: wallet.id,
+      owner: wallet.owner,
+      owner_details: details.owner.map(Into::into),
+      label: wallet.label,
+      currency: wallet.currency,
+      allow_overdraft: wallet.allow_overdraft,
+      legal_hold: wallet.legal_hold,
+      frozen: wallet.frozen,
+      display_name: wallet.appearance.display_name().map(ToString::to_string),
+      color: wallet.appearance.color().map(ToString::to_string),
+      icon: wallet.appearance.icon().map(ToString::to_string),
+      balance_cents: details.balance.as_minor(),
+      balance_display: AmountDisplay::new(details.balance.as_minor(), wallet.currency),
+      envelopes: None,
+      created_at: wallet.created_at,
+      updated_at: wallet.updated_at,
+    }
+  }
+}
+
+#[derive(Deserialize, Validate, ToSchema)]
+pub struct LegalHoldRequest {
+  #[validate(length(min = 1, max = 1024))]
+  #[schema(example = "Pending investigation of disputed charges")]
+  pub reason: String,
+}
+
+/// Replaces the appearance as a whole; leave a field out to clear it.
+#[derive(Deserialize, Validate, ToSchema)]
+pub struct WalletAppearanceRequest {
+  /// Up to 40 characters
+  #[schema(example = "Bar budget")]
+  pub display_name: Option<String>,
+  /// `#rrggbb`
+  #[schema(example = "#ff8800")]
+  pub color: Option<String>,
+  /// Name of an icon in the app's set: lower case letters, digits and dashes
+  #[schema(example = "beer")]
+  pub icon: Option<String>,
+}
+
+#[derive(Deserialize, Validate, ToSchema)]
+pub struct TopUpRequest {
+  /// Amount, as integer cents or a decimal string such as
+  /// `"20.00"`; `amount_cents` is accepted too
+  #[serde(alias = "amount_cents")]
+  #[validate(custom(function = "positive_amount"))]
+  #[schema(example = "20.00")]
+  pub amount: Money,
+  #[validate(length(max = 255))]
+  #[schema(example = "Cash at entrance")]
+  pub description: Option<String>,
+  /// Context from the till: `terminal_id`, `receipt_number` or
+  /// `external_reference`
+  #[serde(default)]
+  #[schema(example = json!({"terminal_id": "bar-2"}))]
+  pub metadata: BTreeMap<String, String>,
+}
+
+#[derive(Deserialize, Validate, ToSchema)]
+pub struct OnlineTopUpRequest {
+  /// Amount, as integer cents or a decimal string such as
+  /// `"20.00"`; `amount_cents` is accepted too
+  #[serde(alias = "amount_cents")]
+  #[validate(custom(function = "positive_amount"))]
+  #[schema(example = "20.00")]
+  pub amount: Money,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct OnlineTopUpResponse {
+  pub id: Id<TopUp>,
+  pub wallet_id: Id<Wallet>,
+  /// Amount in cents
+  pub amount_cents: i32,
+  pub amount_display: AmountDisplay,
+  pub status: TopUpStatus,
+  /// Hosted payment page to send the payer to
+  pub checkout_url: String,
+  pub created_at: DateTime<Utc>,
+}
+
+impl From<(TopUp, String)> for OnlineTopUpResponse {
+  fn from((top_up, checkout_url): (TopUp, String)) -> Self {
+    Self {
+      id: top_up.id,
+      wallet_id: top_up.wallet_id,
+      amount_cents: top_up.amount.as_minor(),
+      amount_display: top_up.amount.into(),
+      status: top_up.status,
+      checkout_url,
+      created_at: top_up.created_at,
+    }
+  }
+}
+
+#[derive(Deserialize, Validate, ToSchema)]
+pub struct WithdrawRequest {
+  /// Amount, as integer cents or a decimal string such as
+  /// `"15.00"`; `amount_cents` is accepted too
+  #[serde(alias = "amount_cents")]
+  #[validate(custom(function = "positive_amount"))]
+  #[schema(example = "15.00")]
+  pub amount: Money,
+  #[validate(length(max = 255))]
+  #[schema(example = "Balance paid out at closing")]
+  pub description: Option<String>,
+  /// Context from the till: `terminal_id`, `receipt_number` or
+  /// `external_reference`
+  #[serde(default)]
+  #[schema(example = json!({"terminal_id": "bar-2"}))]
+  pub metadata: BTreeMap<String, String>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct LegalHoldEventResponse {
+  pub id: Id<WalletLegalHoldEvent>,
+  pub wallet_id: Id<Wallet>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub actor_id: Option<Id<Actor>>,
+  pub action: LegalHoldAction,
+  pub reason: String,
+  pub created_at: DateTime<Utc>,
+}
+
+impl From<WalletLegalHoldEvent> for LegalHoldEventResponse {
+  fn from(event: WalletLegalHoldEvent) -> Self {
+    Self {
+      id: event.id,
+      wallet_id: event.wallet_id,
+      actor_id: event.actor_id,
+      action: event.action,
+      reason: event.reason,
+      created_at: event.created_at,
+    }
+  }
+}
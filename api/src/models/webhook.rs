@@ -0,0 +1,93 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use utoipa::ToSchema;
+use validator::Validate;
+
+use domain::{Id, Shop, Webhook, WebhookDelivery, WebhookDeliveryStatus, WebhookEvent};
+
+#[derive(Deserialize, Validate, ToSchema)]
+pub struct CreateWebhookRequest {
+  /// An https URL on the public internet; loopback, private and link-local
+  /// addresses are refused
+  #[validate(url, length(max = 2048))]
+  #[schema(example = "https://kitchen.example.com/cayopay")]
+  pub url: String,
+  #[validate(length(min = 1))]
+  pub events: Vec<WebhookEvent>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct WebhookResponse {
+  pub id: Id<Webhook>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub shop_id: Option<Id<Shop>>,
+  pub url: String,
+  pub events: Vec<WebhookEvent>,
+  pub created_at: DateTime<Utc>,
+}
+
+/// Returned once on registration; the secret can't be read back later.
+#[derive(Serialize, ToSchema)]
+pub struct CreatedWebhookResponse {
+  #[serde(flatten)]
+  pub webhook: WebhookResponse,
+  /// Key for verifying the `X-Cayopay-Signature` header of deliveries
+  pub secret: String,
+}
+
+impl From<Webhook> for WebhookResponse {
+  fn from(webhook: Webhook) -> Self {
+    Self {
+      id: webhook.id,
+      shop_id: webhook.shop_id,
+      url: webhook.url,
+      events: webhook.events,
+      created_at: webhook.created_at,
+    }
+  }
+}
+
+impl From<Webhook> for CreatedWebhookResponse {
+  fn from(webhook: Webhook) -> Self {
+    Self {
+      secret: webhook.secret.clone(),
+      webhook: webhook.into(),
+    }
+  }
+}
+
+/// One event sent, or being sent, to a webhook.
+#[derive(Serialize, ToSchema)]
+pub struct WebhookDeliveryResponse {
+  pub id: Id<WebhookDelivery>,
+  pub event: WebhookEvent,
+  /// The JSON body posted
+  pub payload: Value,
+  pub status: WebhookDeliveryStatus,
+  pub attempts: i32,
+  /// HTTP status of the latest response, if the endpoint answered
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub response_status: Option<i32>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub last_error: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub delivered_at: Option<DateTime<Utc>>,
+  pub created_at: DateTime<Utc>,
+}
+
+impl From<WebhookDelivery> for WebhookDeliveryResponse {
+  fn from(delivery: WebhookDelivery) -> Self {
+    Self {
+      id: delivery.id,
+      event: delivery.event,
+      payload: delivery.payload,
+      status: delivery.status,
+      attempts: delivery.attempts,
+      response_status: delivery.response_status,
+      last_error: delivery.last_error,
+      delivered_at: delivery.delivered_at,
+      created_at: delivery.created_at,
+    }
+  }
+}
@@ -0,0 +1,382 @@
+use std::sync::LazyLock;
+
+use axum::http::Method;
+use serde_json::{json, Value};
+use utoipa::openapi::{OpenApi, PathItemType};
+
+use domain::{Permission, Role};
+
+use crate::{mounted_routes, spec_path};
+
+/// What an operation requires beyond a valid session.
+#[derive(Debug, Clone, Copy)]
+pub enum Guard {
+  /// Any signed-in user
+  Authenticated,
+  /// Every listed permission
+  All(&'static [Permission]),
+  /// At least one of the listed permissions
+  Any(&'static [Permission]),
+  /// The owner of the addressed resource, or every listed permission
+  OwnerOr(&'static [Permission]),
+  /// The owner of the addressed wallet, every listed permission, or on a
+  /// guest's wallet, whoever may read guest details
+  OwnerOrGuestReaderOr(&'static [Permission]),
+  /// The owner or a member of the addressed shop; no role grants this
+  ShopStaff,
+  /// Staff of the addressed shop, or every listed permission
+  ShopStaffOr(&'static [Permission]),
+  /// Whoever paid for the addressed order, its shop's staff, or every listed
+  /// permission
+  PayerOrShopStaffOr(&'static [Permission]),
+}
+
+impl Guard {
+  pub fn allows(&self, role: Role) -> bool {
+    self.granted_by(&role.permissions())
+  }
+
+  /// Whether `permissions` alone let the caller through, regardless of
+  /// ownership or shop staff. Readers of guest details count for
+  /// [`Guard::OwnerOrGuestReaderOr`], though only on guests' wallets.
+  pub fn granted_by(&self, permissions: &[Permission]) -> bool {
+    match self {
+      Guard::Authenticated => true,
+      Guard::OwnerOrGuestReaderOr(perms) => {
+        perms.iter().all(|p| permissions.contains(p))
+          || permissions.contains(&Permission::ReadGuestDetails)
+      }
+      Guard::All(perms)
+      | Guard::OwnerOr(perms)
+      | Guard::ShopStaffOr(perms)
+      | Guard::PayerOrShopStaffOr(perms) => perms.iter().all(|p| permissions.contains(p)),
+      Guard::Any(perms) => perms.iter().any(|p| permissions.contains(p)),
+      Guard::ShopStaff => false,
+    }
+  }
+
+  /// Whether the guard depends on the caller alone, not on the addressed
+  /// resource, so it can be checked before the handler loads anything.
+  pub fn is_resource_independent(&self) -> bool {
+    matches!(self, Guard::Authenticated | Guard::All(_) | Guard::Any(_))
+  }
+
+  pub fn mode(&self) -> &'static str {
+    match self {
+      Guard::Authenticated => "authenticated",
+      Guard::All(_) => "all",
+      Guard::Any(_) => "any",
+      Guard::OwnerOr(_) => "owner_or_all",
+      Guard::OwnerOrGuestReaderOr(_) => "owner_or_guest_reader_or_all",
+      Guard::ShopStaff => "shop_staff",
+      Guard::ShopStaffOr(_) => "shop_staff_or_all",
+      Guard::PayerOrShopStaffOr(_) => "payer_or_shop_staff_or_all",
+    }
+  }
+
+  pub fn permissions(&self) -> &'static [Permission] {
+    match self {
+      Guard::Authenticated | Guard::ShopStaff => &[],
+      Guard::All(perms)
+      | Guard::Any(perms)
+      | Guard::OwnerOr(perms)
+      | Guard::OwnerOrGuestReaderOr(perms)
+      | Guard::ShopStaffOr(perms)
+      | Guard::PayerOrShopStaffOr(perms) => perms,
+    }
+  }
+
+  /// Roles allowed through regardless of ownership or shop staff.
+  pub fn allowed_roles(&self) -> Vec<Role> {
+    Role::variants()
+      .iter()
+      .copied()
+      .filter(|role| self.allows(*role))
+      .collect()
+  }
+
+  fn to_extension(self) -> Value {
+    json!({ "mode": self.mode(), "permissions": self.permissions() })
+  }
+}
+
+/// How a documented operation is protected.
+#[derive(Clone)]
+pub struct RouteCoverage {
+  pub method: PathItemType,
+  pub path: String,
+  pub guard: Option<Guard>,
+  /// Registered as [`Access::Public`]
+  pub public: bool,
+}
+
+impl RouteCoverage {
+  /// Neither guarded nor deliberately public, e.g. a documented operation
+  /// no route serves.
+  pub fn is_unprotected(&self) -> bool {
+    self.guard.is_none() && !self.public
+  }
+
+  pub fn method_name(&self) -> &'static str {
+    match self.method {
+      PathItemType::Get => "GET",
+      PathItemType::Post => "POST",
+      PathItemType::Put => "PUT",
+      PathItemType::Delete => "DELETE",
+      PathItemType::Options => "OPTIONS",
+      PathItemType::Head => "HEAD",
+      PathItemType::Patch => "PATCH",
+      PathItemType::Trace => "TRACE",
+      PathItemType::Connect => "CONNECT",
+    }
+  }
+}
+
+/// Who may call a route, declared where the route is registered.
+#[derive(Debug, Clone, Copy)]
+pub enum Access {
+  /// Reachable without a session. The route authenticates the caller some
+  /// other way, if at all.
+  Public,
+  Guarded(Guard),
+}
+
+impl From<Guard> for Access {
+  fn from(guard: Guard) -> Self {
+    Access::Guarded(guard)
+  }
+}
+
+/// How every mounted operation is protected, keyed like the spec. Read off
+/// the feature routers, so a route can't be mounted without a guard.
+///
+/// [`crate::middleware::enforce_route_guard`] enforces the guards as far as
+/// they don't depend on the addressed resource; handlers check the rest
+/// through `Authz` once they loaded it.
+static ROUTE_ACCESS: LazyLock<Vec<(PathItemType, String, Access)>> = LazyLock::new(|| {
+  mounted_routes()
+    .into_iter()
+    .filter_map(|(method, route, access)| Some((spec_method(&method)?, spec_path(&route), access)))
+    .collect()
+});
+
+pub fn route_access() -> &'static [(PathItemType, String, Access)] {
+  &ROUTE_ACCESS
+}
+
+/// The guard of a protected operation, keyed like the spec.
+fn find_guard(method: &PathItemType, path: &str) -> Option<Guard> {
+  route_access()
+    .iter()
+    .find(|(m, p, _)| m == method && p == path)
+    .and_then(|(_, _, access)| match access {
+      Access::Guarded(guard) => Some(*guard),
+      Access::Public => None,
+    })
+}
+
+fn is_public(method: &PathItemType, path: &str) -> bool {
+  route_access()
+    .iter()
+    .any(|(m, p, access)| m == method && p == path && matches!(access, Access::Public))
+}
+
+fn spec_method(method: &Method) -> Option<PathItemType> {
+  match *method {
+    Method::GET => Some(PathItemType::Get),
+    Method::POST => Some(PathItemType::Post),
+    Method::PUT => Some(PathItemType::Put),
+    Method::PATCH => Some(PathItemType::Patch),
+    Method::DELETE => Some(PathItemType::Delete),
+    _ => None,
+  }
+}
+
+/// The guard of the operation a request was routed to, from its method and
+/// the axum route it matched relative to the API root, e.g. `/users/:id`.
+pub fn guard_for(method: &Method, route: &str) -> Option<Guard> {
+  find_guard(&spec_method(method)?, &spec_path(route))
+}
+
+/// Lists every documented operation along with its guard, sorted by path.
+pub fn coverage(openapi: &OpenApi) -> Vec<RouteCoverage> {
+  let mut routes: Vec<RouteCoverage> = openapi
+    .paths
+    .paths
+    .iter()
+    .flat_map(|(path, item)| {
+      item.operations.keys().map(move |method| RouteCoverage {
+        method: method.clone(),
+        path: path.clone(),
+        guard: find_guard(method, path),
+        public: is_public(method, path),
+      })
+    })
+    .collect();
+
+  routes.sort_by(|a, b| (&a.path, &a.method).cmp(&(&b.path, &b.method)));
+  routes
+}
+
+/// Logs every operation that is neither guarded nor deliberately public.
+/// Returns how many there are.
+pub fn check(openapi: &OpenApi) -> usize {
+  let unprotected: Vec<RouteCoverage> = coverage(openapi)
+    .into_iter()
+    .filter(RouteCoverage::is_unprotected)
+    .collect();
+
+  for route in &unprotected {
+    tracing::error!(
+      "{} {} declares no permission and is not listed as public",
+      route.method_name(),
+      route.path
+    );
+  }
+
+  unprotected.len()
+}
+
+/// Annotates each protected operation with `x-required-permissions` and
+/// `x-allowed-roles` so clients and gateway policies can be generated from
+/// the spec. For guards involving ownership or shop staff the roles listed are
+/// those allowed regardless of it.
+pub fn annotate(openapi: &mut OpenApi) {
+  for (method, path, access) in route_access() {
+    let Access::Guarded(guard) = access else {
+      continue;
+    };
+    let Some(operation) = openapi
+      .paths
+      .paths
+      .get_mut(path)
+      .and_then(|item| item.operations.get_mut(method))
+    else {
+      continue;
+    };
+
+    let roles = guard.allowed_roles();
+
+    let extensions = operation.extensions.get_or_insert_with(Default::default);
+    extensions.insert("x-required-permissions".to_string(), guard.to_extension());
+    extensions.insert("x-allowed-roles".to_string(), json!(roles));
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::ApiDoc;
+  use utoipa::OpenApi as _;
+
+  #[test]
+  fn test_every_secured_operation_declares_its_guard() {
+    let openapi = ApiDoc::openapi();
+
+    for (path, item) in &openapi.paths.paths {
+      for (method, operation) in &item.operations {
+        if operation.security.is_some() {
+          assert!(
+            find_guard(method, path).is_some(),
+            "{} is secured but no route declares its guard",
+            path
+          );
+        }
+      }
+    }
+  }
+
+  #[test]
+  fn test_every_guard_matches_a_documented_operation() {
+    let openapi = ApiDoc::openapi();
+
+    for (method, path, access) in route_access() {
+      let Access::Guarded(_) = access else {
+        continue;
+      };
+      let operation = openapi
+        .paths
+        .paths
+        .get(path)
+        .and_then(|item| item.operations.get(method));
+
+      assert!(
+        operation.is_some_and(|o| o.security.is_some()),
+        "{} is not a secured operation in the spec",
+        path
+      );
+    }
+  }
+
+  #[test]
+  fn test_no_operation_is_unprotected() {
+    let unprotected: Vec<String> = coverage(&ApiDoc::openapi())
+      .into_iter()
+      .filter(RouteCoverage::is_unprotected)
+      .map(|route| format!("{} {}", route.method_name(), route.path))
+      .collect();
+
+    assert!(
+      unprotected.is_empty(),
+      "Unprotected operations: {:?}",
+      unprotected
+    );
+  }
+
+  #[test]
+  fn test_every_public_route_is_documented_without_session() {
+    let openapi = ApiDoc::openapi();
+
+    for (method, path, access) in route_access() {
+      let Access::Public = access else {
+        continue;
+      };
+      let operation = openapi
+        .paths
+        .paths
+        .get(path)
+        .and_then(|item| item.operations.get(method));
+
+      assert!(
+        operation.is_some_and(|o| o.security.is_none()),
+        "{} is registered as public but is not an unsecured operation in the spec",
+        path
+      );
+    }
+  }
+
+  #[test]
+  fn test_guard_allows() {
+    let refund = Guard::All(&[Permission::RefundTransaction]);
+    assert!(refund.allows(Role::Admin));
+    assert!(!refund.allows(Role::Auditor));
+    assert!(!refund.allows(Role::Cashier));
+
+    let top_up = Guard::All(&[Permission::TopUpWallet]);
+    assert!(top_up.allows(Role::Cashier));
+
+    let list = Guard::Any(&[Permission::ListUsers, Permission::ReadUserDetails]);
+    assert!(list.allows(Role::Auditor));
+    assert!(!list.allows(Role::Undefined));
+
+    let events = Guard::PayerOrShopStaffOr(&[Permission::ReadTransactions]);
+    assert!(events.allows(Role::Auditor));
+    assert!(!events.allows(Role::Cashier));
+
+    let wallet = Guard::OwnerOrGuestReaderOr(&[Permission::ReadTransactions]);
+    assert!(wallet.allows(Role::Auditor));
+    assert!(wallet.allows(Role::Cashier));
+    assert!(!wallet.allows(Role::Undefined));
+  }
+
+  #[test]
+  fn test_guard_for_matched_route() {
+    let unlock = guard_for(&Method::POST, "/users/:user_id/unlock");
+    assert!(matches!(
+      unlock,
+      Some(Guard::All([Permission::UnlockAccounts]))
+    ));
+    assert!(guard_for(&Method::GET, "/users/:user_id/unlock").is_none());
+    assert!(guard_for(&Method::POST, "/nope").is_none());
+  }
+}
@@ -0,0 +1,97 @@
+use application::AppState;
+use axum::{
+  handler::Handler,
+  http::Method,
+  routing::{delete, get, patch, post, put, MethodRouter},
+  Router,
+};
+
+use crate::route_permissions::Access;
+
+/// A route as registered, relative to where its feature is mounted.
+#[derive(Debug, Clone)]
+pub struct RouteEntry {
+  pub method: Method,
+  /// In axum's syntax, e.g. `/:user_id`
+  pub path: &'static str,
+  pub access: Access,
+}
+
+/// A feature's routes. Handlers are registered one method at a time, each
+/// with who may call it, so the routes and their guards can be listed, which
+/// an axum [`Router`] can't do.
+#[derive(Default)]
+pub struct Routes {
+  router: Router<AppState>,
+  entries: Vec<RouteEntry>,
+}
+
+impl Routes {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  pub fn get<H, T>(self, path: &'static str, handler: H, access: impl Into<Access>) -> Self
+  where
+    H: Handler<T, AppState>,
+    T: 'static,
+  {
+    self.on(Method::GET, path, get(handler), access.into())
+  }
+
+  pub fn post<H, T>(self, path: &'static str, handler: H, access: impl Into<Access>) -> Self
+  where
+    H: Handler<T, AppState>,
+    T: 'static,
+  {
+    self.on(Method::POST, path, post(handler), access.into())
+  }
+
+  pub fn put<H, T>(self, path: &'static str, handler: H, access: impl Into<Access>) -> Self
+  where
+    H: Handler<T, AppState>,
+    T: 'static,
+  {
+    self.on(Method::PUT, path, put(handler), access.into())
+  }
+
+  pub fn patch<H, T>(self, path: &'static str, handler: H, access: impl Into<Access>) -> Self
+  where
+    H: Handler<T, AppState>,
+    T: 'static,
+  {
+    self.on(Method::PATCH, path, patch(handler), access.into())
+  }
+
+  pub fn delete<H, T>(self, path: &'static str, handler: H, access: impl Into<Access>) -> Self
+  where
+    H: Handler<T, AppState>,
+    T: 'static,
+  {
+    self.on(Method::DELETE, path, delete(handler), access.into())
+  }
+
+  fn on(
+    mut self,
+    method: Method,
+    path: &'static str,
+    route: MethodRouter<AppState>,
+    access: Access,
+  ) -> Self {
+    self.router = self.router.route(path, route);
+    self.entries.push(RouteEntry {
+      method,
+      path,
+      access,
+    });
+    self
+  }
+
+  pub fn entries(&self) -> &[RouteEntry] {
+    &self.entries
+  }
+
+  pub fn into_router(self) -> Router<AppState> {
+    self.router
+  }
+}
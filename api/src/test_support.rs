@@ -0,0 +1,43 @@
+use application::{config::Config, AppState};
+use chrono::Utc;
+use domain::{Email, HashedPassword, Id, Locale, Role, User};
+use sqlx::postgres::PgPoolOptions;
+
+/// A user with the role, for tests that only care about permissions.
+pub fn create_user(role: Role) -> User {
+  User {
+    id: Id::new(),
+    actor_id: Id::new(),
+    email: Email::new("test@example.com".to_string()),
+    password: HashedPassword::new("hash".to_string()),
+    first_name: "Test".to_string(),
+    last_name: "User".to_string(),
+    role,
+    locale: Locale::default(),
+    created_at: Utc::now(),
+    updated_at: None,
+  }
+}
+
+/// State with the default config and a pool that never connects, for tests
+/// that don't reach the database. Needs a Tokio runtime.
+pub fn create_state() -> AppState {
+  let config = Config::from_sources(
+    None,
+    [
+      ("DATABASE_URL", "postgres://localhost/unused"),
+      ("SMTP_HOST", "localhost"),
+      ("SMTP_PORT", "25"),
+      ("SMTP_USERNAME", "mailer@example.com"),
+      ("SMTP_PASSWORD", "password"),
+      ("SMTP_FROM", "noreply@example.com"),
+    ]
+    .map(|(key, value)| (key.to_string(), value.to_string())),
+  )
+  .unwrap();
+  let pool = PgPoolOptions::new()
+    .connect_lazy(config.database_url.expose())
+    .unwrap();
+
+  AppState::new(&config, pool)
+}
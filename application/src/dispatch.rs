@@ -0,0 +1,25 @@
+use std::time::Duration;
+
+use sqlx::PgPool;
+
+use crate::{listen::listen, services::DomainEventService};
+
+/// Postgres channel recording a domain event wakes the dispatcher on
+const CHANNEL: &str = "domain_events";
+/// Catches up even without notifications, e.g. while reconnecting
+const POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Hands domain events to their subscribers until the process exits: right
+/// after the database reports new ones, and every so often regardless.
+pub async fn run(domain_event_service: DomainEventService, pool: PgPool) {
+  listen(&pool, CHANNEL, "domain events", Some(POLL_INTERVAL), |_| {
+    dispatch(&domain_event_service)
+  })
+  .await;
+}
+
+async fn dispatch(domain_event_service: &DomainEventService) {
+  if let Err(e) = domain_event_service.dispatch_pending().await {
+    tracing::warn!("Failed to dispatch domain events: {}", e);
+  }
+}
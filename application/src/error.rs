@@ -1,4 +1,7 @@
-use domain::UserId;
+use domain::{Feature, UserId};
+use infra::stores::{
+  is_currency_mismatch_violation, is_legal_hold_violation, is_wallet_frozen_violation,
+};
 use thiserror::Error;
 
 pub type AppResult<T> = Result<T, AppError>;
@@ -6,7 +9,7 @@ pub type AppResult<T> = Result<T, AppError>;
 #[derive(Debug, Error)]
 pub enum AppError {
   #[error("Database error: {0}")]
-  Database(#[from] sqlx::Error),
+  Database(sqlx::Error),
 
   #[error("Entity not found")]
   NotFound,
@@ -17,30 +20,157 @@ pub enum AppError {
   #[error("Authorization failed")]
   Authorization,
 
+  #[error("Account is locked until {0}")]
+  AccountLocked(chrono::DateTime<chrono::Utc>),
+
   #[error("User already exists")]
   UserAlreadyExists,
 
-  #[error("Invite already sent")]
-  InviteAlreadySent,
+  #[error("Email address is already in use")]
+  EmailInUse,
+
+  #[error("The person likely has an account already")]
+  PossibleDuplicates(Vec<domain::DuplicateCandidate>),
+
+  #[error("The last owner must stay an owner")]
+  LastOwner,
+
+  #[error("An owner transfer is already pending, expiring at {expires_at}")]
+  OwnerTransferPending {
+    expires_at: chrono::DateTime<chrono::Utc>,
+  },
+
+  #[error("The owner transfer is no longer pending")]
+  OwnerTransferClosed,
+
+  #[error("Invite already sent, expiring at {expires_at}")]
+  InviteAlreadySent {
+    expires_at: chrono::DateTime<chrono::Utc>,
+  },
 
   #[error("Invite expired")]
   InviteExpired,
 
+  #[error("Invite revoked")]
+  InviteRevoked,
+
+  #[error("Addresses at this domain cannot be invited with the role {0}")]
+  InviteDomainNotAllowed(domain::Role),
+
   #[error("Invitor with user id '{0}' does not exist")]
   InvitorMissing(UserId),
 
   #[error("Email error: {0}")]
   Email(#[from] infra::services::EmailError),
 
+  #[error("Payment provider error: {0}")]
+  Psp(#[from] infra::services::PspError),
+
+  #[error("Webhook error: {0}")]
+  Webhook(#[from] infra::services::WebhookError),
+
+  #[error("Online payments are not configured")]
+  PspDisabled,
+
+  #[error("Bank payouts are not configured")]
+  SepaDisabled,
+
+  #[error("Feature {feature} is switched off")]
+  FeatureDisabled {
+    feature: Feature,
+    reason: Option<String>,
+  },
+
+  #[error("Wallet has an unresolved chargeback")]
+  ChargebackUnresolved,
+
   #[error("Validation error: {0}")]
   Validation(String),
 
   #[error("Bad request: {0}")]
   BadRequest(String),
 
+  #[error("No event has the cursor {0}")]
+  UnknownCursor(i64),
+
+  #[error("Request body exceeds the size limit")]
+  PayloadTooLarge,
+
+  #[error("Too many requests")]
+  RateLimited,
+
   #[error("Internal server error")]
   InternalServerError,
 
   #[error("Password hashing error: {0}")]
   PasswordHash(#[from] argon2::password_hash::Error),
+
+  #[error("Password confirmation required")]
+  PasswordConfirmationRequired,
+
+  #[error("Device signature required")]
+  DeviceSignatureRequired,
+
+  #[error("Identifier is already bound to another guest")]
+  GuestIdentifierInUse,
+
+  #[error("Guest has already been claimed")]
+  GuestAlreadyClaimed,
+
+  #[error("Transaction has already been refunded")]
+  AlreadyRefunded,
+
+  #[error("User is already a member of this shop")]
+  AlreadyShopMember,
+
+  #[error("Order cannot move from {from} to {to}")]
+  InvalidOrderTransition {
+    from: domain::OrderStatus,
+    to: domain::OrderStatus,
+  },
+
+  #[error("Payout cannot move from {from} to {to}")]
+  InvalidPayoutTransition {
+    from: domain::PayoutStatus,
+    to: domain::PayoutStatus,
+  },
+
+  #[error("Order has not been paid yet")]
+  OrderUnpaid,
+
+  #[error("Order has already been paid")]
+  OrderAlreadyPaid,
+
+  #[error("Pickup slot is fully booked")]
+  PickupSlotFull,
+
+  #[error("Insufficient funds")]
+  InsufficientFunds,
+
+  #[error("Wallet is under legal hold")]
+  LegalHold,
+
+  #[error("Wallet is frozen")]
+  WalletFrozen,
+
+  #[error("Wallets hold different currencies")]
+  CurrencyMismatch,
+}
+
+impl From<sqlx::Error> for AppError {
+  fn from(err: sqlx::Error) -> Self {
+    if is_legal_hold_violation(&err) {
+      return AppError::LegalHold;
+    }
+
+    if is_wallet_frozen_violation(&err) {
+      return AppError::WalletFrozen;
+    }
+
+    if is_currency_mismatch_violation(&err) {
+      return AppError::CurrencyMismatch;
+    }
+
+    AppError::Database(err)
+  }
 }
@@ -0,0 +1,283 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+use domain::{
+  types::Money, OrderEvent, OrderId, OrderStatus, OrderStatusChange, ShopId, TransactionEventData,
+  UserId,
+};
+use infra::stores::NotificationStore;
+
+use crate::listen::listen;
+
+/// Events buffered per subscriber before slow ones start missing some
+const EVENT_BUFFER: usize = 256;
+/// Postgres channel events are relayed between instances on
+const CHANNEL: &str = "order_events";
+
+/// Fan-out of order events, new transactions and sign-outs to live views such
+/// as status streams, shop dashboards and wallet streams. Publishing never
+/// blocks; subscribers that fall too far behind skip ahead.
+///
+/// Events reach subscribers on every instance once [`relay`] runs; otherwise
+/// only those of the publishing one.
+#[derive(Clone)]
+pub struct EventBus {
+  /// Events for subscribers, from this instance and others
+  sender: broadcast::Sender<OrderEvent>,
+  /// Transactions for subscribers, from this instance and others
+  transactions: broadcast::Sender<TransactionEventData>,
+  /// Users signed out everywhere, from this instance and others
+  logouts: broadcast::Sender<UserId>,
+  /// Events published on this instance, to relay to others
+  outgoing: broadcast::Sender<WireEvent>,
+  /// Tells this instance's notifications apart from those of others
+  origin: Uuid,
+}
+
+/// An event as sent between instances.
+#[derive(Serialize, Deserialize)]
+struct Envelope {
+  origin: Uuid,
+  event: WireEvent,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum WireEvent {
+  Paid {
+    order_id: OrderId,
+    shop_id: ShopId,
+    amount_cents: i32,
+    at: DateTime<Utc>,
+  },
+  Refunded {
+    order_id: OrderId,
+    shop_id: ShopId,
+    amount_cents: i32,
+    at: DateTime<Utc>,
+  },
+  StatusChanged {
+    order_id: OrderId,
+    shop_id: ShopId,
+    status: OrderStatus,
+    changed_at: DateTime<Utc>,
+  },
+  TransactionCreated(TransactionEventData),
+  SessionsRevoked {
+    user_id: UserId,
+  },
+}
+
+impl EventBus {
+  pub fn new() -> Self {
+    let (sender, _) = broadcast::channel(EVENT_BUFFER);
+    let (transactions, _) = broadcast::channel(EVENT_BUFFER);
+    let (logouts, _) = broadcast::channel(EVENT_BUFFER);
+    let (outgoing, _) = broadcast::channel(EVENT_BUFFER);
+
+    Self {
+      sender,
+      transactions,
+      logouts,
+      outgoing,
+      origin: Uuid::new_v4(),
+    }
+  }
+
+  pub fn publish(&self, event: OrderEvent) {
+    // Nobody listening is fine
+    let _ = self.outgoing.send(event.clone().into());
+    let _ = self.sender.send(event);
+  }
+
+  /// Publishes a transaction once it is committed.
+  pub fn publish_transaction(&self, transaction: TransactionEventData) {
+    let _ = self
+      .outgoing
+      .send(WireEvent::TransactionCreated(transaction.clone()));
+    let _ = self.transactions.send(transaction);
+  }
+
+  /// Publishes that a user's sessions were revoked, so their open streams
+  /// end.
+  pub fn publish_logout(&self, user_id: UserId) {
+    let _ = self.outgoing.send(WireEvent::SessionsRevoked { user_id });
+    let _ = self.logouts.send(user_id);
+  }
+
+  /// Every event published from now on.
+  pub fn subscribe(&self) -> broadcast::Receiver<OrderEvent> {
+    self.sender.subscribe()
+  }
+
+  /// Every transaction published from now on.
+  pub fn subscribe_transactions(&self) -> broadcast::Receiver<TransactionEventData> {
+    self.transactions.subscribe()
+  }
+
+  /// Every user signed out from now on.
+  pub fn subscribe_logouts(&self) -> broadcast::Receiver<UserId> {
+    self.logouts.subscribe()
+  }
+
+  /// Hands an event of another instance to local subscribers.
+  fn deliver(&self, event: WireEvent) {
+    // Nobody listening is fine
+    match OrderEvent::try_from(event) {
+      Ok(event) => {
+        let _ = self.sender.send(event);
+      }
+      Err(WireEvent::TransactionCreated(transaction)) => {
+        let _ = self.transactions.send(transaction);
+      }
+      Err(WireEvent::SessionsRevoked { user_id }) => {
+        let _ = self.logouts.send(user_id);
+      }
+      Err(_) => {}
+    }
+  }
+}
+
+impl Default for EventBus {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+/// Shares events with the other server instances through Postgres
+/// `LISTEN`/`NOTIFY` until the process exits: sends those published here and
+/// hands those of others to local subscribers.
+pub async fn relay(bus: EventBus, pool: PgPool) {
+  tokio::join!(send_outgoing(&bus, &pool), receive_incoming(&bus, &pool));
+}
+
+async fn send_outgoing(bus: &EventBus, pool: &PgPool) {
+  let mut outgoing = bus.outgoing.subscribe();
+
+  loop {
+    let event = match outgoing.recv().await {
+      Ok(event) => event,
+      Err(broadcast::error::RecvError::Lagged(missed)) => {
+        tracing::warn!("Other instances missed {} events", missed);
+        continue;
+      }
+      Err(broadcast::error::RecvError::Closed) => return,
+    };
+
+    let envelope = Envelope {
+      origin: bus.origin,
+      event,
+    };
+    let payload = serde_json::to_string(&envelope).expect("event serializes");
+
+    if let Err(e) = NotificationStore::notify(pool, CHANNEL, &payload).await {
+      tracing::warn!("Failed to relay an event to other instances: {}", e);
+    }
+  }
+}
+
+async fn receive_incoming(bus: &EventBus, pool: &PgPool) {
+  // Events sent while the connection is down are lost
+  listen(
+    pool,
+    CHANNEL,
+    "events of other instances",
+    None,
+    |notification| async move {
+      let Some(notification) = notification else {
+        return;
+      };
+
+      match serde_json::from_str::<Envelope>(notification.payload()) {
+        Ok(envelope) if envelope.origin == bus.origin => {}
+        Ok(envelope) => bus.deliver(envelope.event),
+        Err(e) => tracing::warn!("Ignoring malformed event: {}", e),
+      }
+    },
+  )
+  .await;
+}
+
+impl From<OrderEvent> for WireEvent {
+  fn from(event: OrderEvent) -> Self {
+    match event {
+      OrderEvent::Paid {
+        order_id,
+        shop_id,
+        amount,
+        at,
+      } => WireEvent::Paid {
+        order_id,
+        shop_id,
+        amount_cents: amount.as_minor(),
+        at,
+      },
+      OrderEvent::Refunded {
+        order_id,
+        shop_id,
+        amount,
+        at,
+      } => WireEvent::Refunded {
+        order_id,
+        shop_id,
+        amount_cents: amount.as_minor(),
+        at,
+      },
+      OrderEvent::StatusChanged(change) => WireEvent::StatusChanged {
+        order_id: change.order_id,
+        shop_id: change.shop_id,
+        status: change.status,
+        changed_at: change.changed_at,
+      },
+    }
+  }
+}
+
+/// Fails with the event itself if it is not about an order.
+impl TryFrom<WireEvent> for OrderEvent {
+  type Error = WireEvent;
+
+  fn try_from(event: WireEvent) -> Result<Self, Self::Error> {
+    Ok(match event {
+      WireEvent::Paid {
+        order_id,
+        shop_id,
+        amount_cents,
+        at,
+      } => OrderEvent::Paid {
+        order_id,
+        shop_id,
+        amount: Money::from_minor(amount_cents),
+        at,
+      },
+      WireEvent::Refunded {
+        order_id,
+        shop_id,
+        amount_cents,
+        at,
+      } => OrderEvent::Refunded {
+        order_id,
+        shop_id,
+        amount: Money::from_minor(amount_cents),
+        at,
+      },
+      WireEvent::StatusChanged {
+        order_id,
+        shop_id,
+        status,
+        changed_at,
+      } => OrderEvent::StatusChanged(OrderStatusChange {
+        order_id,
+        shop_id,
+        status,
+        changed_at,
+      }),
+      event @ (WireEvent::TransactionCreated(_) | WireEvent::SessionsRevoked { .. }) => {
+        return Err(event)
+      }
+    })
+  }
+}
@@ -0,0 +1,33 @@
+use std::time::Duration;
+
+use crate::{services::JobService, shutdown::Shutdown};
+
+/// How often the queue is checked for due jobs
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Runs queued background jobs until shutdown. Then waits for the requests
+/// still under way and runs once more, so that emails they queued go out too;
+/// the caller cuts that short at its deadline. Every replica runs this loop;
+/// each job is claimed by one of them at a time.
+pub async fn run(job_service: JobService, shutdown: Shutdown) {
+  let mut interval = tokio::time::interval(POLL_INTERVAL);
+  interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+  loop {
+    tokio::select! {
+      _ = interval.tick() => {}
+      _ = shutdown.triggered() => break,
+    }
+
+    run_due(&job_service).await;
+  }
+
+  shutdown.drained().await;
+  run_due(&job_service).await;
+}
+
+async fn run_due(job_service: &JobService) {
+  if let Err(e) = job_service.run_due().await {
+    tracing::warn!("Failed to run background jobs: {}", e);
+  }
+}
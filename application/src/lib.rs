@@ -1,8 +1,18 @@
 pub mod config;
+pub mod dispatch;
 pub mod error;
+pub mod events;
+pub mod jobs;
+mod listen;
+pub mod projection;
+pub mod sandbox;
+pub mod scheduler;
 pub mod services;
+pub mod shutdown;
 pub mod state;
 
 pub use config::Config;
 pub use error::{AppError, AppResult};
+pub use events::EventBus;
+pub use shutdown::Shutdown;
 pub use state::AppState;
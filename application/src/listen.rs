@@ -0,0 +1,69 @@
+use std::{future::Future, time::Duration};
+
+use sqlx::{
+  postgres::{PgListener, PgNotification},
+  PgPool,
+};
+
+/// Wait before listening again after losing the connection
+const RELISTEN_DELAY: Duration = Duration::from_secs(5);
+
+/// Hands every notification on `channel` to `handle` until the process exits,
+/// listening again whenever the connection drops; `subject` names what is
+/// listened for in the log.
+///
+/// With a `poll_interval`, `handle` is also called without a notification
+/// before connecting, once listening started and whenever the interval passes
+/// quietly, so whatever was missed meanwhile is still caught up on.
+pub async fn listen<F, Fut>(
+  pool: &PgPool,
+  channel: &str,
+  subject: &str,
+  poll_interval: Option<Duration>,
+  mut handle: F,
+) where
+  F: FnMut(Option<PgNotification>) -> Fut,
+  Fut: Future<Output = ()>,
+{
+  loop {
+    if poll_interval.is_some() {
+      handle(None).await;
+    }
+
+    let mut listener = match PgListener::connect_with(pool).await {
+      Ok(listener) => listener,
+      Err(e) => {
+        tracing::warn!("Failed to connect for {}: {}", subject, e);
+        tokio::time::sleep(RELISTEN_DELAY).await;
+        continue;
+      }
+    };
+    if let Err(e) = listener.listen(channel).await {
+      tracing::warn!("Failed to listen for {}: {}", subject, e);
+      tokio::time::sleep(RELISTEN_DELAY).await;
+      continue;
+    }
+
+    // Whatever happened before listening started went unnoticed
+    if poll_interval.is_some() {
+      handle(None).await;
+    }
+
+    loop {
+      let received = match poll_interval {
+        Some(interval) => tokio::time::timeout(interval, listener.recv()).await.ok(),
+        None => Some(listener.recv().await),
+      };
+
+      match received {
+        Some(Ok(notification)) => handle(Some(notification)).await,
+        None => handle(None).await,
+        Some(Err(e)) => {
+          tracing::warn!("Lost {}: {}", subject, e);
+          tokio::time::sleep(RELISTEN_DELAY).await;
+          break;
+        }
+      }
+    }
+  }
+}
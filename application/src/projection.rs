@@ -0,0 +1,31 @@
+use std::time::Duration;
+
+use sqlx::PgPool;
+
+use crate::{listen::listen, services::ActorService};
+
+/// Postgres channel the outbox triggers wake the projector on
+const CHANNEL: &str = "actor_summaries";
+/// Catches up even without notifications, e.g. while reconnecting
+const POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Keeps the actor summaries up to date until the process exits: projects
+/// whatever the outbox holds whenever the database reports a change, and
+/// every so often regardless.
+pub async fn run(actor_service: ActorService, pool: PgPool) {
+  listen(
+    &pool,
+    CHANNEL,
+    "actor summary changes",
+    Some(POLL_INTERVAL),
+    |_| project(&actor_service),
+  )
+  .await;
+}
+
+async fn project(actor_service: &ActorService) {
+  match actor_service.project_pending().await {
+    Ok(_) => {}
+    Err(e) => tracing::warn!("Failed to project actor summaries: {}", e),
+  }
+}
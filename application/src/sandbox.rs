@@ -0,0 +1,106 @@
+use domain::{types::Money, TransactionMetadata};
+use infra::stores::{
+  models::{
+    shop::{ShopCreation, ShopOfferingCreation},
+    WalletCreation,
+  },
+  ShopOfferingStore, ShopStore, UserStore, WalletStore,
+};
+
+use crate::{
+  error::{AppError, AppResult},
+  state::AppState,
+};
+
+/// Card identifiers of the seeded guests; the first one also tells whether
+/// the sandbox was seeded already
+const GUEST_IDENTIFIERS: &[&str] = &["SANDBOX-0001", "SANDBOX-0002", "SANDBOX-0003"];
+/// Opening balance of every seeded guest
+const GUEST_BALANCE_CENTS: i32 = 5000;
+const SHOP_NAME: &str = "Sandbox Bar";
+/// Name and price in cents of the seeded shop's offerings
+const OFFERINGS: &[(&str, i32)] = &[("Beer", 350), ("Soft drink", 250), ("Fries", 400)];
+
+/// Fills a fresh sandbox with fake data to integrate against: a shop owned
+/// by the sandbox owner with a few offerings, and guests with cards and
+/// money on their wallets. Does nothing once the sandbox was seeded.
+///
+/// Expects the sandbox owner and the labelled wallets to exist.
+pub async fn seed(state: &AppState) -> AppResult<()> {
+  if state
+    .guest_service
+    .find_by_identifier(GUEST_IDENTIFIERS[0])
+    .await?
+    .is_some()
+  {
+    return Ok(());
+  }
+
+  let owner = UserStore::find_by_email(&state.pool, &state.config.owner_email)
+    .await?
+    .ok_or(AppError::NotFound)?;
+
+  let settings = state.settings_service.get().await?;
+
+  let mut tx = state.pool.begin().await?;
+  let wallet = WalletStore::create(
+    &mut *tx,
+    &WalletCreation {
+      owner: None,
+      label: None,
+      currency: settings.currency,
+      allow_overdraft: false,
+    },
+  )
+  .await?;
+  let shop = ShopStore::create(
+    &mut *tx,
+    &ShopCreation {
+      owner: Some(owner.id),
+      name: SHOP_NAME.to_string(),
+      wallet_id: wallet.id,
+    },
+  )
+  .await?;
+  for (name, price_cents) in OFFERINGS {
+    ShopOfferingStore::create(
+      &mut *tx,
+      &shop.id,
+      &ShopOfferingCreation {
+        name: name.to_string(),
+        description: None,
+        price: Money::from_minor(*price_cents),
+      },
+    )
+    .await?;
+  }
+  tx.commit().await?;
+
+  for identifier in GUEST_IDENTIFIERS {
+    let guest = state.guest_service.create_guest(None).await?;
+    state
+      .guest_service
+      .assign_identifier(guest.id, identifier.to_string())
+      .await?;
+    let (_, wallet) = state
+      .guest_service
+      .find_by_identifier(identifier)
+      .await?
+      .ok_or(AppError::NotFound)?;
+
+    state
+      .wallet_service
+      .top_up(
+        owner.actor_id,
+        wallet.id,
+        Money::from_minor(GUEST_BALANCE_CENTS),
+        Some("Sandbox opening balance".to_string()),
+        TransactionMetadata::default(),
+      )
+      .await?;
+  }
+
+  tracing::info!("Seeded the sandbox with fake data");
+
+  Ok(())
+}
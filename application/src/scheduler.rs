@@ -0,0 +1,128 @@
+use std::time::Duration;
+
+use crate::state::AppState;
+use infra::stores::ScheduledJobStore;
+
+/// How often the scheduled jobs run
+const TICK: Duration = Duration::from_secs(60);
+/// Ticks between two checks of the running wallet balances
+const BALANCE_CHECK_TICKS: u64 = 60;
+/// Ticks between two checks of the balance alerts
+const BALANCE_ALERT_TICKS: u64 = 5;
+/// Ticks between two rounds of debt recovery
+const DEBT_RECOVERY_TICKS: u64 = 60;
+/// Ticks between two runs of the data retention rules
+const RETENTION_TICKS: u64 = 24 * 60;
+/// Ticks between two purges of succeeded background jobs
+const JOB_PURGE_TICKS: u64 = 24 * 60;
+
+/// Runs the periodic background jobs until shutdown, finishing the tick under
+/// way. Failures are logged and retried on the next tick.
+///
+/// Every replica runs this loop; each job only runs on the replica that
+/// claims it first in its period.
+pub async fn run(state: AppState) {
+  let mut interval = tokio::time::interval(TICK);
+  interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+  let mut ticks: u64 = 0;
+  loop {
+    tokio::select! {
+      _ = interval.tick() => {}
+      _ = state.shutdown.triggered() => return,
+    }
+    ticks += 1;
+
+    if claim(&state, "release_preorders", 1).await {
+      match state.order_service.release_due_preorders().await {
+        Ok(0) => {}
+        Ok(released) => tracing::info!("Released {} pre-orders into shop queues", released),
+        Err(e) => tracing::warn!("Failed to release due pre-orders: {}", e),
+      }
+    }
+
+    if ticks % BALANCE_ALERT_TICKS == 1
+      && claim(&state, "balance_alerts", BALANCE_ALERT_TICKS).await
+    {
+      match state.balance_alert_service.check().await {
+        Ok(0) => {}
+        Ok(fired) => tracing::info!("Sent {} balance alerts", fired),
+        Err(e) => tracing::warn!("Failed to check balance alerts: {}", e),
+      }
+    }
+
+    if ticks % BALANCE_CHECK_TICKS == 1 && claim(&state, "balance_check", BALANCE_CHECK_TICKS).await
+    {
+      match state.wallet_service.check_balances().await {
+        Ok(drifts) => {
+          for drift in drifts {
+            tracing::error!(
+              "Balance of wallet {} drifted: stored {} cents, ledger {} cents",
+              drift.wallet_id,
+              drift.stored_cents,
+              drift.ledger_cents
+            );
+          }
+        }
+        Err(e) => tracing::warn!("Failed to check wallet balances: {}", e),
+      }
+    }
+
+    if ticks % DEBT_RECOVERY_TICKS == 1 && claim(&state, "debt_recovery", DEBT_RECOVERY_TICKS).await
+    {
+      match state.debt_service.run().await {
+        Ok(run) => {
+          if run.flagged > 0 || run.reminded > 0 {
+            tracing::info!(
+              "Flagged {} overdue debts and sent {} payment reminders",
+              run.flagged,
+              run.reminded
+            );
+          }
+        }
+        Err(e) => tracing::warn!("Failed to run debt recovery: {}", e),
+      }
+    }
+
+    if ticks % RETENTION_TICKS == 1 && claim(&state, "retention", RETENTION_TICKS).await {
+      match state.retention_service.apply(false).await {
+        Ok(outcomes) => {
+          for outcome in outcomes.iter().filter(|outcome| outcome.affected > 0) {
+            tracing::info!(
+              "Retention rule {:?} cleaned up {} rows older than {}",
+              outcome.rule,
+              outcome.affected,
+              outcome.cutoff
+            );
+          }
+        }
+        Err(e) => tracing::warn!("Failed to apply data retention rules: {}", e),
+      }
+    }
+
+    if ticks % JOB_PURGE_TICKS == 1 && claim(&state, "purge_jobs", JOB_PURGE_TICKS).await {
+      match state.job_service.purge_succeeded().await {
+        Ok(0) => {}
+        Ok(purged) => tracing::info!("Purged {} succeeded background jobs", purged),
+        Err(e) => tracing::warn!("Failed to purge succeeded background jobs: {}", e),
+      }
+    }
+  }
+}
+
+/// Whether this replica gets to run `job`, which is due every `ticks` ticks.
+/// Half a tick of slack keeps the replica's own next run from being refused
+/// when its timer fires a little early. Fails closed, so jobs are skipped
+/// while the database is unreachable.
+async fn claim(state: &AppState, job: &str, ticks: u64) -> bool {
+  let period = TICK * ticks as u32 - TICK / 2;
+  let period = chrono::Duration::from_std(period).unwrap_or(chrono::Duration::MAX);
+
+  match ScheduledJobStore::try_claim(&state.pool, job, period).await {
+    Ok(claimed) => claimed,
+    Err(e) => {
+      tracing::warn!("Failed to claim scheduled job {}: {}", job, e);
+      false
+    }
+  }
+}
@@ -0,0 +1,73 @@
+use sqlx::PgPool;
+
+use crate::error::AppResult;
+use domain::{
+  types::{Page, PageRequest},
+  ActorSummary, DuplicateCandidate, Email,
+};
+use infra::stores::{ActorSummaryOutboxStore, ActorSummaryStore};
+
+pub use infra::stores::models::ActorSummaryFilter;
+
+/// Actors projected per summary
+const PROJECTION_BATCH: i64 = 500;
+/// Likely duplicates reported when someone is about to get an account
+const DUPLICATE_CANDIDATES: i64 = 5;
+
+#[derive(Clone)]
+pub struct ActorService {
+  pool: PgPool,
+}
+
+impl ActorService {
+  pub fn new(pool: PgPool) -> Self {
+    Self { pool }
+  }
+
+  /// Searches the actor summaries, which may trail the latest changes by a
+  /// moment; balances are current.
+  pub async fn list(
+    &self,
+    filter: ActorSummaryFilter,
+    page: PageRequest,
+  ) -> AppResult<Page<ActorSummary>> {
+    let items = ActorSummaryStore::list_paginated(&self.pool, &filter, &page).await?;
+    let total = ActorSummaryStore::count(&self.pool, &filter).await?;
+
+    Ok(Page {
+      items,
+      total,
+      request: page,
+    })
+  }
+
+  /// Users and guests who are likely the person behind `email`, best
+  /// matches first. Based on the summaries, so someone added a moment ago may
+  /// be missed.
+  pub async fn find_duplicates(&self, email: &Email) -> AppResult<Vec<DuplicateCandidate>> {
+    Ok(ActorSummaryStore::find_duplicates(&self.pool, email, DUPLICATE_CANDIDATES).await?)
+  }
+
+  /// Rebuilds the summaries of every actor queued in the outbox and returns
+  /// how many actors it went through.
+  pub async fn project_pending(&self) -> AppResult<u64> {
+    let mut projected = 0;
+
+    loop {
+      let mut tx = self.pool.begin().await?;
+
+      let actor_ids = ActorSummaryOutboxStore::lock_batch(&mut *tx, PROJECTION_BATCH).await?;
+      if actor_ids.is_empty() {
+        return Ok(projected);
+      }
+
+      ActorSummaryStore::refresh(&mut *tx, &actor_ids).await?;
+      ActorSummaryStore::delete_orphaned(&mut *tx, &actor_ids).await?;
+      ActorSummaryOutboxStore::delete(&mut *tx, &actor_ids).await?;
+
+      tx.commit().await?;
+
+      projected += actor_ids.len() as u64;
+    }
+  }
+}
@@ -0,0 +1,71 @@
+use futures_util::{stream, Stream};
+use sqlx::PgPool;
+
+use crate::error::AppResult;
+use domain::{
+  types::{Page, PageRequest},
+  AuditChainReport, AuditEntry,
+};
+use infra::stores::AuditStore;
+
+/// Entries fetched per query while exporting the audit log
+const EXPORT_BATCH_SIZE: i64 = 500;
+
+pub use infra::stores::models::{AuditEntryCreation, AuditFilter};
+
+#[derive(Clone)]
+pub struct AuditService {
+  pool: PgPool,
+}
+
+impl AuditService {
+  pub fn new(pool: PgPool) -> Self {
+    Self { pool }
+  }
+
+  pub async fn record(&self, entry: AuditEntryCreation) -> AppResult<AuditEntry> {
+    Ok(AuditStore::create(&self.pool, &entry).await?)
+  }
+
+  /// Newest entries first
+  pub async fn list(&self, filter: AuditFilter, page: PageRequest) -> AppResult<Page<AuditEntry>> {
+    let items = AuditStore::list_paginated(&self.pool, &filter, &page).await?;
+    let total = AuditStore::count(&self.pool, &filter).await?;
+
+    Ok(Page {
+      items,
+      total,
+      request: page,
+    })
+  }
+
+  /// Recomputes the hash chain over the whole audit log.
+  pub async fn verify(&self) -> AppResult<AuditChainReport> {
+    Ok(AuditStore::verify_chain(&self.pool).await?)
+  }
+
+  /// Streams the whole audit log in chain order, in batches so the export
+  /// never holds more than one batch in memory.
+  pub fn export(&self) -> impl Stream<Item = AppResult<Vec<AuditEntry>>> + Send + 'static {
+    let pool = self.pool.clone();
+
+    // `None` once the last batch has been handed out
+    stream::try_unfold(Some(0), move |cursor| {
+      let pool = pool.clone();
+      async move {
+        let Some(after_seq) = cursor else {
+          return Ok(None);
+        };
+
+        let batch = AuditStore::list_chain_page(&pool, after_seq, EXPORT_BATCH_SIZE).await?;
+        let next = if (batch.len() as i64) < EXPORT_BATCH_SIZE {
+          None
+        } else {
+          batch.last().map(|entry| entry.seq)
+        };
+
+        Ok(Some((batch, next)))
+      }
+    })
+  }
+}
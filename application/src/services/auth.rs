@@ -1,55 +1,197 @@
-use sqlx::PgPool;
+use std::sync::LazyLock;
 
-use crate::error::{AppError, AppResult};
-use domain::{Email, RawPassword, Role, User};
+use chrono::{DateTime, Utc};
+use sqlx::{PgConnection, PgPool};
+
+use crate::{
+  error::{AppError, AppResult},
+  services::{domain_event::emit, SettingsService},
+};
+use domain::{
+  DomainEvent, Email, HashedPassword, Locale, LoginLockout, RawPassword, Role, User, UserId,
+};
 use infra::stores::{
-  models::{UserCreation, WalletCreation},
-  ActorStore, UserStore, WalletStore,
+  models::{LoginAttemptCreation, UserCreation, WalletCreation},
+  ActorStore, LoginAttemptStore, LoginLockoutStore, UserStore, WalletStore,
 };
 
+/// Checked against when nobody has the address, so that unknown addresses
+/// can't be told apart by how quickly they fail.
+static DECOY_PASSWORD: LazyLock<HashedPassword> = LazyLock::new(|| {
+  RawPassword::new(uuid::Uuid::new_v4().to_string())
+    .hash()
+    .expect("hash decoy password")
+});
+
 #[derive(Clone)]
 pub struct AuthService {
   pool: PgPool,
+  lockout: LoginLockout,
+  settings_service: SettingsService,
 }
 
 impl AuthService {
-  pub fn new(pool: PgPool) -> Self {
-    Self { pool }
+  pub fn new(pool: PgPool, lockout: LoginLockout, settings_service: SettingsService) -> Self {
+    Self {
+      pool,
+      lockout,
+      settings_service,
+    }
+  }
+
+  /// Checks the credentials and records the attempt.
+  ///
+  /// Too many wrong passwords in a row lock the address for a while; locked
+  /// addresses are refused even with the right password. Addresses without an
+  /// account fail and lock the same way, so neither reveals which are in use.
+  pub async fn login(
+    &self,
+    email: Email,
+    password: RawPassword,
+    ip_address: Option<String>,
+  ) -> AppResult<User> {
+    let locked_until = self.check_lockout(&email).await?;
+
+    let user = UserStore::find_by_email(&self.pool, &email).await?;
+    let succeeded = match &user {
+      Some(user) => user.password.verify(&password)?,
+      None => {
+        // Takes as long as a wrong password would
+        DECOY_PASSWORD.verify(&password)?;
+        false
+      }
+    };
+
+    self
+      .record_attempt(
+        &email,
+        user.as_ref().map(|user| user.id),
+        succeeded,
+        ip_address,
+        locked_until,
+      )
+      .await?;
+
+    user.ok_or(AppError::Authentication)
   }
 
-  pub async fn login(&self, email: Email, password: RawPassword) -> AppResult<User> {
-    let user = UserStore::find_by_email(&self.pool, &email)
+  /// Lifts a lockout early; failed logins before now no longer count.
+  pub async fn unlock(&self, user_id: UserId) -> AppResult<()> {
+    let user = UserStore::find_by_id(&self.pool, &user_id)
       .await?
-      .ok_or(AppError::Authentication)?;
+      .ok_or(AppError::NotFound)?;
+
+    LoginLockoutStore::set_locked_until(&self.pool, &user.email, Utc::now()).await?;
+
+    Ok(())
+  }
+
+  /// Re-checks the password of an already authenticated user. Wrong
+  /// passwords count towards the same lockout as failed logins.
+  pub async fn verify_password(
+    &self,
+    user: &User,
+    password: RawPassword,
+    ip_address: Option<String>,
+  ) -> AppResult<()> {
+    let locked_until = self.check_lockout(&user.email).await?;
+    let succeeded = user.password.verify(&password)?;
 
-    if !user.password.verify(&password)? {
-      return Err(AppError::Authentication);
+    self
+      .record_attempt(
+        &user.email,
+        Some(user.id),
+        succeeded,
+        ip_address,
+        locked_until,
+      )
+      .await
+  }
+
+  /// Refuses locked addresses. Returns when the last lock ended, if any.
+  async fn check_lockout(&self, email: &Email) -> AppResult<Option<DateTime<Utc>>> {
+    let locked_until = LoginLockoutStore::find_locked_until(&self.pool, email).await?;
+    if let Some(until) = locked_until.filter(|until| *until > Utc::now()) {
+      return Err(AppError::AccountLocked(until));
     }
 
-    Ok(user)
+    Ok(locked_until)
   }
 
+  /// Records the attempt and fails unless it succeeded, locking the address
+  /// once too many attempts failed.
+  async fn record_attempt(
+    &self,
+    email: &Email,
+    user_id: Option<UserId>,
+    succeeded: bool,
+    ip_address: Option<String>,
+    locked_until: Option<DateTime<Utc>>,
+  ) -> AppResult<()> {
+    LoginAttemptStore::create(
+      &self.pool,
+      &LoginAttemptCreation {
+        email: email.clone(),
+        user_id,
+        succeeded,
+        ip_address,
+      },
+    )
+    .await?;
+
+    if succeeded {
+      return Ok(());
+    }
+
+    // Failures from before the last lock ended or was lifted don't count
+    let now = Utc::now();
+    let since = locked_until.map_or(now - self.lockout.window, |until| {
+      until.max(now - self.lockout.window)
+    });
+    let failures = LoginAttemptStore::list_failure_times_since(&self.pool, email, since).await?;
+
+    if let Some(until) = self.lockout.locked_until(&failures, now) {
+      LoginLockoutStore::set_locked_until(&self.pool, email, until).await?;
+      tracing::warn!(
+        "Locked {} until {} after {} failed logins",
+        user_id.map_or("an unknown address".to_string(), |id| format!(
+          "user {}",
+          id
+        )),
+        until,
+        failures.len()
+      );
+      return Err(AppError::AccountLocked(until));
+    }
+
+    Err(AppError::Authentication)
+  }
+
+  /// Creates the user with their actor and wallet on the caller's
+  /// transaction.
+  #[allow(clippy::too_many_arguments)]
   pub async fn register(
     &self,
+    conn: &mut PgConnection,
     email: Email,
     password: RawPassword,
     first_name: String,
     last_name: String,
     role: Role,
+    locale: Locale,
   ) -> AppResult<User> {
-    if UserStore::find_by_email(&self.pool, &email)
+    if UserStore::find_by_email(&mut *conn, &email)
       .await?
       .is_some()
     {
       return Err(AppError::UserAlreadyExists);
     }
 
-    let mut tx = self.pool.begin().await?;
-
-    let actor = ActorStore::create(&mut *tx).await?;
+    let settings = self.settings_service.get().await?;
+    let actor = ActorStore::create(&mut *conn).await?;
 
     let user = UserStore::create(
-      &mut *tx,
+      &mut *conn,
       &UserCreation {
         actor_id: actor,
         email,
@@ -57,21 +199,30 @@ impl AuthService {
         first_name,
         last_name,
         role,
+        locale,
       },
     )
     .await?;
 
     WalletStore::create(
-      &mut *tx,
+      &mut *conn,
       &WalletCreation {
         owner: Some(actor),
         label: None,
-        allow_overdraft: false,
+        currency: settings.currency,
+        allow_overdraft: settings.allow_overdraft_by_default,
       },
     )
     .await?;
 
-    tx.commit().await?;
+    emit(
+      &mut *conn,
+      DomainEvent::UserRegistered {
+        user_id: user.id,
+        role: user.role,
+      },
+    )
+    .await?;
 
     Ok(user)
   }
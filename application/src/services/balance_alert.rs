@@ -0,0 +1,218 @@
+use chrono::Utc;
+use serde_json::json;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{
+  error::{AppError, AppResult},
+  services::SettingsService,
+};
+use domain::{
+  ActorId, BalanceAlert, BalanceAlertDirection, BalanceAlertId, Currency, Email, Locale,
+  WalletLabel,
+};
+use infra::{
+  services::{check_url, EmailService, EmailTemplate, EmailTemplates, WebhookClient},
+  stores::{models::BalanceAlertCreation, BalanceAlertStore, WalletStore},
+};
+
+/// Event name of webhook deliveries for crossed thresholds
+const WEBHOOK_EVENT: &str = "balance.alert";
+
+/// What to watch and whom to tell.
+#[derive(Debug, Clone)]
+pub struct NewBalanceAlert {
+  /// System wallet to watch; the money owed to members when `None`
+  pub wallet_label: Option<WalletLabel>,
+  pub direction: BalanceAlertDirection,
+  pub threshold_cents: i64,
+  pub notify_email: Option<Email>,
+  pub webhook_url: Option<String>,
+}
+
+/// Thresholds on system wallets and on the money owed to members, so the
+/// organisers notice when their float runs low or grows too large.
+#[derive(Clone)]
+pub struct BalanceAlertService {
+  pool: PgPool,
+  email_service: EmailService,
+  email_templates: EmailTemplates,
+  webhook_client: WebhookClient,
+  settings_service: SettingsService,
+}
+
+impl BalanceAlertService {
+  pub fn new(
+    pool: PgPool,
+    email_service: EmailService,
+    email_templates: EmailTemplates,
+    webhook_client: WebhookClient,
+    settings_service: SettingsService,
+  ) -> Self {
+    Self {
+      pool,
+      email_service,
+      email_templates,
+      webhook_client,
+      settings_service,
+    }
+  }
+
+  /// Adds an alert, with a freshly generated signing secret if it notifies a
+  /// webhook. Webhooks follow the same address rules as other webhooks.
+  pub async fn create(
+    &self,
+    created_by: ActorId,
+    alert: NewBalanceAlert,
+  ) -> AppResult<BalanceAlert> {
+    if alert.notify_email.is_none() && alert.webhook_url.is_none() {
+      return Err(AppError::Validation(
+        "An alert needs `notify_email` or `webhook_url`".to_string(),
+      ));
+    }
+    if let Some(url) = &alert.webhook_url {
+      check_url(url).map_err(|e| AppError::Validation(format!("webhook_url: {}", e)))?;
+    }
+
+    let creation = BalanceAlertCreation {
+      wallet_label: alert.wallet_label,
+      direction: alert.direction,
+      threshold_cents: alert.threshold_cents,
+      notify_email: alert.notify_email,
+      webhook_secret: alert
+        .webhook_url
+        .as_ref()
+        .map(|_| format!("whsec_{}", Uuid::new_v4().simple())),
+      webhook_url: alert.webhook_url,
+      created_by: Some(created_by),
+    };
+
+    Ok(BalanceAlertStore::create(&self.pool, &creation).await?)
+  }
+
+  pub async fn list(&self) -> AppResult<Vec<BalanceAlert>> {
+    Ok(BalanceAlertStore::list(&self.pool).await?)
+  }
+
+  pub async fn remove(&self, id: BalanceAlertId) -> AppResult<BalanceAlert> {
+    let alert = BalanceAlertStore::find_by_id(&self.pool, &id)
+      .await?
+      .ok_or(AppError::NotFound)?;
+
+    BalanceAlertStore::delete_by_id(&self.pool, &alert.id).await?;
+
+    Ok(alert)
+  }
+
+  /// Compares every alert against the current balances and notifies about
+  /// newly crossed thresholds. Returns how many alerts fired. Thresholds are
+  /// in the venue's currency, so are the balances they're compared with.
+  pub async fn check(&self) -> AppResult<usize> {
+    let currency = self.settings_service.get().await?.currency;
+    let mut fired = 0;
+
+    for alert in BalanceAlertStore::list(&self.pool).await? {
+      let Some(balance_cents) = self
+        .balance_of(alert.wallet_label.as_ref(), currency)
+        .await?
+      else {
+        continue;
+      };
+
+      let breached = alert.is_breached_by(balance_cents);
+      if breached == alert.breached {
+        continue;
+      }
+
+      BalanceAlertStore::set_breached(&self.pool, &alert.id, breached).await?;
+
+      if breached {
+        self.notify(&alert, balance_cents, currency).await;
+        fired += 1;
+      }
+    }
+
+    Ok(fired)
+  }
+
+  async fn balance_of(
+    &self,
+    label: Option<&WalletLabel>,
+    currency: Currency,
+  ) -> AppResult<Option<i64>> {
+    let Some(label) = label else {
+      return Ok(Some(
+        WalletStore::sum_member_balances(&self.pool, currency).await?,
+      ));
+    };
+
+    let Some(wallet) = WalletStore::find_by_label(&self.pool, label, currency).await? else {
+      tracing::warn!(
+        "System wallet {} watched by a balance alert is missing",
+        label
+      );
+      return Ok(None);
+    };
+
+    Ok(Some(
+      WalletStore::find_balance(&self.pool, &wallet.id)
+        .await?
+        .as_minor()
+        .into(),
+    ))
+  }
+
+  async fn notify(&self, alert: &BalanceAlert, balance_cents: i64, currency: Currency) {
+    if let Some(email) = &alert.notify_email {
+      // Alert addresses belong to no user, so there is no locale to pick
+      let template = EmailTemplate::BalanceAlert {
+        wallet_label: alert.wallet_label.as_ref().map(ToString::to_string),
+        direction: alert.direction.to_string(),
+        threshold_cents: alert.threshold_cents,
+        balance_cents,
+        currency,
+      };
+      // An alert is better sent from the bare address than not at all
+      let sender_name = match self.settings_service.get().await {
+        Ok(settings) => settings.email_sender_name,
+        Err(_) => None,
+      };
+      let sent = match self.email_templates.render(&template, Locale::default()) {
+        Ok(content) => {
+          self
+            .email_service
+            .send(email, &content, sender_name.as_deref())
+            .await
+        }
+        Err(e) => Err(e),
+      };
+      if let Err(e) = sent {
+        tracing::warn!("Failed to email balance alert {}: {}", alert.id, e);
+      }
+    }
+
+    if let (Some(url), Some(secret)) = (&alert.webhook_url, &alert.webhook_secret) {
+      let body = json!({
+        "id": Uuid::new_v4(),
+        "type": WEBHOOK_EVENT,
+        "created_at": Utc::now(),
+        "data": {
+          "alert_id": alert.id,
+          "wallet_label": alert.wallet_label,
+          "direction": alert.direction,
+          "threshold_cents": alert.threshold_cents,
+          "balance_cents": balance_cents,
+        },
+      })
+      .to_string();
+
+      if let Err(e) = self
+        .webhook_client
+        .deliver(url, secret, WEBHOOK_EVENT, body)
+        .await
+      {
+        tracing::warn!("Failed to deliver balance alert {}: {}", alert.id, e);
+      }
+    }
+  }
+}
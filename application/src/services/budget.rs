@@ -0,0 +1,154 @@
+use sqlx::PgPool;
+
+use crate::error::{AppError, AppResult};
+use domain::{ActorId, BudgetEnvelope, BudgetEnvelopeId, BudgetPlan, Wallet, WalletId};
+use infra::stores::{models::BudgetEnvelopeCreation, BudgetEnvelopeStore, WalletStore};
+
+/// Most envelopes one wallet can have
+const MAX_ENVELOPES_PER_WALLET: i64 = 20;
+
+/// Budget envelopes members keep within their own wallets. Their spending is
+/// counted by the database as payments are booked, so nothing here touches
+/// money.
+#[derive(Clone)]
+pub struct BudgetService {
+  pool: PgPool,
+}
+
+impl BudgetService {
+  pub fn new(pool: PgPool) -> Self {
+    Self { pool }
+  }
+
+  pub async fn list(&self, wallet_id: WalletId) -> AppResult<Vec<BudgetEnvelope>> {
+    Ok(BudgetEnvelopeStore::list_by_wallet_id(&self.pool, &wallet_id).await?)
+  }
+
+  /// Opens an envelope in one of the owner's personal wallets. Spending is
+  /// counted from now on.
+  pub async fn create(
+    &self,
+    owner: ActorId,
+    wallet_id: WalletId,
+    plan: BudgetPlan,
+  ) -> AppResult<BudgetEnvelope> {
+    let wallet = self.own_wallet(owner, wallet_id).await?;
+
+    let mut tx = self.pool.begin().await?;
+
+    // Serializes concurrent creates for the wallet, so they can't all pass
+    // the count check below before any of them inserts.
+    WalletStore::find_by_id_for_update(&mut *tx, &wallet.id)
+      .await?
+      .ok_or(AppError::NotFound)?;
+
+    if BudgetEnvelopeStore::count_by_wallet_id(&mut *tx, &wallet.id).await?
+      >= MAX_ENVELOPES_PER_WALLET
+    {
+      return Err(AppError::BadRequest(format!(
+        "A wallet has at most {} envelopes",
+        MAX_ENVELOPES_PER_WALLET
+      )));
+    }
+
+    let id = BudgetEnvelopeStore::create(
+      &mut *tx,
+      &BudgetEnvelopeCreation {
+        wallet_id: wallet.id,
+        plan: plan.clone(),
+      },
+    )
+    .await
+    .map_err(envelope_conflict)?;
+    BudgetEnvelopeStore::set_shops(&mut *tx, &id, plan.shops())
+      .await
+      .map_err(envelope_conflict)?;
+    let envelope = BudgetEnvelopeStore::find_by_id(&mut *tx, &id)
+      .await?
+      .ok_or(AppError::NotFound)?;
+
+    tx.commit().await?;
+
+    Ok(envelope)
+  }
+
+  /// Replaces the envelope's name, amount and shops. Spending so far stays
+  /// counted unless `reset`, e.g. at the start of a new event.
+  pub async fn update(
+    &self,
+    owner: ActorId,
+    wallet_id: WalletId,
+    id: BudgetEnvelopeId,
+    plan: BudgetPlan,
+    reset: bool,
+  ) -> AppResult<BudgetEnvelope> {
+    let wallet = self.own_wallet(owner, wallet_id).await?;
+
+    let mut tx = self.pool.begin().await?;
+
+    BudgetEnvelopeStore::find_by_id(&mut *tx, &id)
+      .await?
+      .filter(|envelope| envelope.wallet_id == wallet.id)
+      .ok_or(AppError::NotFound)?;
+
+    BudgetEnvelopeStore::update(&mut *tx, &id, &plan, reset)
+      .await
+      .map_err(envelope_conflict)?;
+    BudgetEnvelopeStore::set_shops(&mut *tx, &id, plan.shops())
+      .await
+      .map_err(envelope_conflict)?;
+    let envelope = BudgetEnvelopeStore::find_by_id(&mut *tx, &id)
+      .await?
+      .ok_or(AppError::NotFound)?;
+
+    tx.commit().await?;
+
+    Ok(envelope)
+  }
+
+  pub async fn delete(
+    &self,
+    owner: ActorId,
+    wallet_id: WalletId,
+    id: BudgetEnvelopeId,
+  ) -> AppResult<()> {
+    let wallet = self.own_wallet(owner, wallet_id).await?;
+
+    BudgetEnvelopeStore::find_by_id(&self.pool, &id)
+      .await?
+      .filter(|envelope| envelope.wallet_id == wallet.id)
+      .ok_or(AppError::NotFound)?;
+
+    BudgetEnvelopeStore::delete_by_id(&self.pool, &id).await?;
+
+    Ok(())
+  }
+
+  /// Other actors' wallets are reported as missing, like wallets that are.
+  async fn own_wallet(&self, owner: ActorId, wallet_id: WalletId) -> AppResult<Wallet> {
+    let wallet = WalletStore::find_by_id(&self.pool, &wallet_id)
+      .await?
+      .filter(|wallet| wallet.owner == Some(owner))
+      .ok_or(AppError::NotFound)?;
+
+    if wallet.label.is_some() {
+      return Err(AppError::BadRequest(
+        "System wallets have no envelopes".to_string(),
+      ));
+    }
+
+    Ok(wallet)
+  }
+}
+
+fn envelope_conflict(e: sqlx::Error) -> AppError {
+  match e {
+    sqlx::Error::Database(ref db_err) if db_err.is_unique_violation() => {
+      AppError::BadRequest("The wallet already has an envelope with this name".to_string())
+    }
+    sqlx::Error::Database(ref db_err) if db_err.is_foreign_key_violation() => {
+      AppError::BadRequest("Envelope covers a shop that does not exist".to_string())
+    }
+    e => e.into(),
+  }
+}
@@ -0,0 +1,208 @@
+use std::collections::BTreeMap;
+
+use sqlx::PgPool;
+
+use crate::{
+  error::{AppError, AppResult},
+  services::{domain_event::emit, top_up::find_clearing_wallet},
+};
+use domain::{
+  types::Money, ActorId, Chargeback, ChargebackId, ChargebackStatus, DomainEvent, MetadataSource,
+  TransactionMetadata, WalletId,
+};
+use infra::stores::{
+  models::{ChargebackCreation, TransactionCreation},
+  ChargebackStore, TopUpStore, TransactionStore, WalletStore,
+};
+
+/// Online top-ups disputed by the payer's bank and taken back by the payment
+/// service provider.
+#[derive(Clone)]
+pub struct ChargebackService {
+  pool: PgPool,
+}
+
+impl ChargebackService {
+  pub fn new(pool: PgPool) -> Self {
+    Self { pool }
+  }
+
+  /// Takes a disputed top-up back out of its wallet, regardless of the
+  /// balance or the wallet being frozen, and queues a notice to the owner.
+  /// Repeated reports of the same dispute are ignored; disputes over more
+  /// than earlier disputes left of the top-up are refused.
+  pub async fn open(&self, dispute_id: &str, payment_id: &str, amount_cents: i64) -> AppResult<()> {
+    let amount = i32::try_from(amount_cents)
+      .ok()
+      .map(Money::from_minor)
+      .filter(Money::is_positive)
+      .ok_or_else(|| AppError::BadRequest(format!("Invalid dispute amount {}", amount_cents)))?;
+
+    let mut tx = self.pool.begin().await?;
+
+    let Some(top_up) =
+      TopUpStore::find_by_provider_payment_id_for_update(&mut *tx, payment_id).await?
+    else {
+      tracing::warn!("Dispute {} for unknown payment {}", dispute_id, payment_id);
+      return Ok(());
+    };
+    if ChargebackStore::find_by_provider_dispute_id_for_update(&mut *tx, dispute_id)
+      .await?
+      .is_some()
+    {
+      return Ok(());
+    }
+    // The top-up's row lock keeps other disputes of it from counting alongside
+    let taken_back = ChargebackStore::sum_taken_back_by_top_up_id(&mut *tx, &top_up.id).await?;
+    if taken_back + i64::from(amount.as_minor()) > i64::from(top_up.amount.as_minor()) {
+      return Err(AppError::BadRequest(format!(
+        "Dispute {} is over {}, more than is left of the top-up of {}",
+        dispute_id, amount, top_up.amount
+      )));
+    }
+    // Lets the transaction out of a frozen wallet, see `enforce_wallet_not_frozen`
+    let metadata = TransactionMetadata::new(
+      MetadataSource::Chargeback,
+      BTreeMap::from([("provider_dispute_id".to_string(), dispute_id.to_string())]),
+    )
+    .map_err(|e| AppError::BadRequest(format!("Invalid dispute {}: {}", dispute_id, e)))?;
+
+    let wallet = WalletStore::find_by_id(&mut *tx, &top_up.wallet_id)
+      .await?
+      .ok_or(AppError::NotFound)?;
+    let clearing = find_clearing_wallet(&mut tx, wallet.currency).await?;
+    let (wallet, _) = WalletStore::find_pair_for_update(&mut *tx, &wallet.id, &clearing.id).await?;
+    let wallet = wallet.ok_or(AppError::NotFound)?;
+
+    // No balance check: the provider has the money back either way
+    let transaction = TransactionStore::create(
+      &mut *tx,
+      &TransactionCreation {
+        source: wallet.id,
+        destination: clearing.id,
+        executor: None,
+        amount,
+        description: Some("Chargeback of online top-up".to_string()),
+        reversal_of: None,
+        metadata,
+      },
+    )
+    .await?;
+    emit(
+      &mut tx,
+      DomainEvent::TransactionCreated((&transaction).into()),
+    )
+    .await?;
+
+    let chargeback = ChargebackStore::create(
+      &mut *tx,
+      &ChargebackCreation {
+        top_up_id: top_up.id,
+        wallet_id: wallet.id,
+        amount,
+        provider_dispute_id: dispute_id.to_string(),
+        transaction_id: transaction.id,
+      },
+    )
+    .await?;
+
+    let balance = WalletStore::find_balance(&mut *tx, &wallet.id).await?;
+    emit(
+      &mut tx,
+      DomainEvent::ChargebackOpened {
+        chargeback_id: chargeback.id,
+        wallet_id: wallet.id,
+        amount_cents: amount.as_minor(),
+        balance_cents: balance.as_minor(),
+      },
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(())
+  }
+
+  /// Applies the provider's decision on a dispute. Won disputes return the
+  /// amount to the wallet.
+  pub async fn close(&self, dispute_id: &str, won: bool) -> AppResult<()> {
+    let mut tx = self.pool.begin().await?;
+
+    let Some(chargeback) =
+      ChargebackStore::find_by_provider_dispute_id_for_update(&mut *tx, dispute_id).await?
+    else {
+      tracing::warn!("Closing unknown dispute {}", dispute_id);
+      return Ok(());
+    };
+    if chargeback.status != ChargebackStatus::Open {
+      return Ok(());
+    }
+
+    let (status, reversal_id) = if won {
+      let wallet = WalletStore::find_by_id(&mut *tx, &chargeback.wallet_id)
+        .await?
+        .ok_or(AppError::NotFound)?;
+      let clearing = find_clearing_wallet(&mut tx, wallet.currency).await?;
+      let reversal = TransactionStore::create(
+        &mut *tx,
+        &TransactionCreation {
+          source: clearing.id,
+          destination: chargeback.wallet_id,
+          executor: None,
+          amount: chargeback.amount,
+          description: Some("Chargeback reversed".to_string()),
+          reversal_of: Some(chargeback.transaction_id),
+          metadata: TransactionMetadata::default(),
+        },
+      )
+      .await?;
+      emit(&mut tx, DomainEvent::TransactionCreated((&reversal).into())).await?;
+      (ChargebackStatus::Won, Some(reversal.id))
+    } else {
+      (ChargebackStatus::Lost, None)
+    };
+
+    ChargebackStore::close(&mut *tx, &chargeback.id, status, reversal_id).await?;
+
+    tx.commit().await?;
+
+    Ok(())
+  }
+
+  pub async fn list(&self, unresolved: bool) -> AppResult<Vec<Chargeback>> {
+    Ok(ChargebackStore::list(&self.pool, unresolved).await?)
+  }
+
+  /// Whether the wallet has a chargeback staff have yet to resolve.
+  pub async fn has_unresolved(&self, wallet_id: WalletId) -> AppResult<bool> {
+    Ok(ChargebackStore::has_unresolved(&self.pool, &wallet_id).await?)
+  }
+
+  /// Marks a chargeback as dealt with, e.g. once the member paid the missing
+  /// amount in cash, which lets the wallet be topped up online again.
+  pub async fn resolve(
+    &self,
+    resolved_by: ActorId,
+    id: ChargebackId,
+    note: String,
+  ) -> AppResult<Chargeback> {
+    let mut tx = self.pool.begin().await?;
+
+    let chargeback = ChargebackStore::find_by_id_for_update(&mut *tx, &id)
+      .await?
+      .ok_or(AppError::NotFound)?;
+    if chargeback.is_resolved() {
+      return Err(AppError::BadRequest(
+        "Chargeback is already resolved".to_string(),
+      ));
+    }
+
+    let chargeback = ChargebackStore::resolve(&mut *tx, &id, resolved_by, &note)
+      .await?
+      .ok_or(AppError::NotFound)?;
+
+    tx.commit().await?;
+
+    Ok(chargeback)
+  }
+}
@@ -0,0 +1,217 @@
+use chrono::Duration;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::error::{AppError, AppResult};
+use domain::{
+  AccessToken, AccessTokenId, ActorId, AuthorizationCode, ClientApp, ClientAppId, Scope, User,
+  UserId,
+};
+use infra::stores::{
+  models::{AccessTokenCreation, AuthorizationCodeCreation, ClientAppCreation},
+  AccessTokenStore, AuthorizationCodeStore, ClientAppStore,
+};
+
+/// Codes are exchanged right after the redirect, so they needn't last long
+const AUTHORIZATION_CODE_MINUTES: i64 = 10;
+
+/// What an app is registered with.
+#[derive(Debug, Clone)]
+pub struct NewClientApp {
+  pub name: String,
+  pub redirect_uris: Vec<String>,
+  pub allowed_scopes: Vec<Scope>,
+}
+
+/// A user's approval of an app, as the app requests it.
+#[derive(Debug, Clone)]
+pub struct AuthorizationRequest {
+  pub client_app_id: ClientAppId,
+  pub redirect_uri: String,
+  pub scopes: Vec<Scope>,
+  /// PKCE `S256` challenge
+  pub code_challenge: String,
+}
+
+/// A code being exchanged for an access token, as the app sends it.
+#[derive(Debug, Clone)]
+pub struct TokenRequest {
+  pub client_app_id: ClientAppId,
+  pub code: String,
+  pub redirect_uri: String,
+  pub code_verifier: String,
+}
+
+/// Third-party apps users grant scoped access to their account, in the style
+/// of OAuth's authorization code flow with PKCE: the signed-in user approves
+/// the app, which exchanges the code it is redirected with for a bearer
+/// token.
+#[derive(Clone)]
+pub struct ClientAppService {
+  pool: PgPool,
+  token_expiration_days: i64,
+}
+
+impl ClientAppService {
+  pub fn new(pool: PgPool, token_expiration_days: i64) -> Self {
+    Self {
+      pool,
+      token_expiration_days,
+    }
+  }
+
+  pub async fn register(&self, created_by: ActorId, app: NewClientApp) -> AppResult<ClientApp> {
+    if app.redirect_uris.is_empty() {
+      return Err(AppError::BadRequest(
+        "At least one redirect URI is required".to_string(),
+      ));
+    }
+    if app.allowed_scopes.is_empty() {
+      return Err(AppError::BadRequest(
+        "At least one scope is required".to_string(),
+      ));
+    }
+
+    Ok(
+      ClientAppStore::create(
+        &self.pool,
+        &ClientAppCreation {
+          name: app.name,
+          redirect_uris: app.redirect_uris,
+          allowed_scopes: app.allowed_scopes,
+          created_by: Some(created_by),
+        },
+      )
+      .await?,
+    )
+  }
+
+  pub async fn list(&self) -> AppResult<Vec<ClientApp>> {
+    Ok(ClientAppStore::list(&self.pool).await?)
+  }
+
+  pub async fn get(&self, id: ClientAppId) -> AppResult<ClientApp> {
+    ClientAppStore::find_by_id(&self.pool, &id)
+      .await?
+      .ok_or(AppError::NotFound)
+  }
+
+  /// Removes the app and signs it out of every account.
+  pub async fn remove(&self, id: ClientAppId) -> AppResult<()> {
+    if !ClientAppStore::delete_by_id(&self.pool, &id).await? {
+      return Err(AppError::NotFound);
+    }
+
+    Ok(())
+  }
+
+  /// Records the user's approval and returns the code to redirect the app
+  /// with. The redirect URI must be registered and the scopes allowed.
+  pub async fn authorize(
+    &self,
+    user: &User,
+    request: AuthorizationRequest,
+  ) -> AppResult<AuthorizationCode> {
+    let app = self.get(request.client_app_id).await?;
+
+    if !app.has_redirect_uri(&request.redirect_uri) {
+      return Err(AppError::BadRequest(
+        "Redirect URI is not registered for this app".to_string(),
+      ));
+    }
+    if request.scopes.is_empty() || !app.allows_scopes(&request.scopes) {
+      return Err(AppError::BadRequest(
+        "Scopes are missing or not allowed for this app".to_string(),
+      ));
+    }
+
+    AuthorizationCodeStore::delete_expired(&self.pool).await?;
+
+    let code = AuthorizationCodeStore::create(
+      &self.pool,
+      &AuthorizationCodeCreation {
+        client_app_id: app.id,
+        user_id: user.id,
+        code: Uuid::new_v4().to_string(),
+        redirect_uri: request.redirect_uri,
+        scopes: request.scopes,
+        code_challenge: request.code_challenge,
+        expires_in: Duration::minutes(AUTHORIZATION_CODE_MINUTES),
+      },
+    )
+    .await?;
+
+    Ok(code)
+  }
+
+  /// Exchanges a code for an access token. Codes work once, even when the
+  /// exchange fails, so a leaked one can't be tried repeatedly.
+  pub async fn exchange_code(&self, request: TokenRequest) -> AppResult<AccessToken> {
+    let code = AuthorizationCodeStore::take_by_code(&self.pool, &request.code)
+      .await?
+      .ok_or(AppError::Authentication)?;
+
+    if code.is_expired()
+      || code.client_app_id != request.client_app_id
+      || code.redirect_uri != request.redirect_uri
+      || !code.is_verified_by(&request.code_verifier)
+    {
+      return Err(AppError::Authentication);
+    }
+
+    let token = AccessTokenStore::create(
+      &self.pool,
+      &AccessTokenCreation {
+        client_app_id: code.client_app_id,
+        user_id: code.user_id,
+        token: Uuid::new_v4().to_string(),
+        scopes: code.scopes,
+        expires_in: Duration::days(self.token_expiration_days),
+      },
+    )
+    .await?;
+
+    Ok(token)
+  }
+
+  /// The unexpired token with this value, if any.
+  pub async fn authenticate(&self, token: &str) -> AppResult<Option<AccessToken>> {
+    let access_token = AccessTokenStore::find_by_token(&self.pool, token).await?;
+
+    if let Some(ref t) = access_token {
+      if t.is_expired() {
+        AccessTokenStore::delete_by_token(&self.pool, token).await?;
+        return Ok(None);
+      }
+
+      AccessTokenStore::touch_by_token(&self.pool, token).await?;
+    }
+
+    Ok(access_token)
+  }
+
+  /// Tokens of the apps the user approved, with the app of each.
+  pub async fn list_tokens(&self, user_id: UserId) -> AppResult<Vec<(AccessToken, ClientApp)>> {
+    let tokens = AccessTokenStore::list_by_user_id(&self.pool, &user_id).await?;
+    let apps = ClientAppStore::list(&self.pool).await?;
+
+    Ok(
+      tokens
+        .into_iter()
+        .filter(|t| !t.is_expired())
+        .filter_map(|t| {
+          let app = apps.iter().find(|a| a.id == t.client_app_id)?.clone();
+          Some((t, app))
+        })
+        .collect(),
+    )
+  }
+
+  pub async fn revoke_token(&self, user_id: UserId, id: AccessTokenId) -> AppResult<()> {
+    if !AccessTokenStore::delete_by_id_and_user_id(&self.pool, &id, &user_id).await? {
+      return Err(AppError::NotFound);
+    }
+
+    Ok(())
+  }
+}
@@ -0,0 +1,111 @@
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+
+use crate::{
+  error::AppResult,
+  services::job::{actor_contact, enqueue_email},
+};
+use domain::{Debt, DebtRecoveryPolicy, Debtor};
+use infra::{
+  services::{EmailTemplate, EmailTemplates},
+  stores::{DebtStore, WalletStore},
+};
+
+/// What one round of debt recovery did.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct DebtRecoveryRun {
+  pub opened: u64,
+  pub settled: u64,
+  pub flagged: u64,
+  pub reminded: u64,
+}
+
+/// Keeps track of member wallets below zero: flags those that stay there
+/// past the grace period and reminds their owners to settle up.
+#[derive(Clone)]
+pub struct DebtService {
+  pool: PgPool,
+  policy: DebtRecoveryPolicy,
+  email_templates: EmailTemplates,
+}
+
+impl DebtService {
+  pub fn new(pool: PgPool, policy: DebtRecoveryPolicy, email_templates: EmailTemplates) -> Self {
+    Self {
+      pool,
+      policy,
+      email_templates,
+    }
+  }
+
+  /// Opens debts for wallets that went below zero, settles those back at
+  /// zero, flags overdue ones and emails the reminders that are due.
+  pub async fn run(&self) -> AppResult<DebtRecoveryRun> {
+    let now = Utc::now();
+
+    let settled = DebtStore::settle_repaid(&self.pool, now).await?;
+    let opened = DebtStore::open_new(&self.pool, now).await?;
+    let flagged = DebtStore::flag_overdue(&self.pool, self.policy.flag_cutoff(now), now).await?;
+
+    let cutoff = self.policy.reminder_cutoff(now);
+    let max_reminders = i32::try_from(self.policy.max_reminders).unwrap_or(i32::MAX);
+    let mut reminded = 0;
+    for debt in DebtStore::list_due_reminders(&self.pool, cutoff, max_reminders).await? {
+      if self.remind(&debt, cutoff, now).await? {
+        reminded += 1;
+      }
+    }
+
+    Ok(DebtRecoveryRun {
+      opened,
+      settled,
+      flagged,
+      reminded,
+    })
+  }
+
+  /// Open debts, longest owed first; only flagged ones if `flagged_only`.
+  /// Up to date as of the last [`Self::run`].
+  pub async fn list_debtors(&self, flagged_only: bool) -> AppResult<Vec<Debtor>> {
+    Ok(DebtStore::list_open_debtors(&self.pool, flagged_only).await?)
+  }
+
+  /// Queues a reminder to the wallet's owner and counts it. Skipped for
+  /// owners without an email address.
+  async fn remind(
+    &self,
+    debt: &Debt,
+    cutoff: DateTime<Utc>,
+    now: DateTime<Utc>,
+  ) -> AppResult<bool> {
+    let mut tx = self.pool.begin().await?;
+
+    let Some((owner, currency)) = WalletStore::find_by_id(&mut *tx, &debt.wallet_id)
+      .await?
+      .and_then(|wallet| Some((wallet.owner?, wallet.currency)))
+    else {
+      return Ok(false);
+    };
+    let Some((email, locale)) = actor_contact(&mut tx, &owner).await? else {
+      return Ok(false);
+    };
+    let Some(debt) = DebtStore::record_reminder(&mut *tx, &debt.id, cutoff, now).await? else {
+      return Ok(false);
+    };
+    let balance = WalletStore::find_balance(&mut *tx, &debt.wallet_id).await?;
+
+    let content = self.email_templates.render(
+      &EmailTemplate::DebtReminder {
+        days: (now - debt.since).num_days(),
+        balance_cents: balance.as_minor().into(),
+        currency,
+      },
+      locale,
+    )?;
+    enqueue_email(&mut tx, &email, content).await?;
+
+    tx.commit().await?;
+
+    Ok(true)
+  }
+}
@@ -0,0 +1,164 @@
+use sqlx::{PgConnection, PgPool};
+
+use crate::{
+  error::{AppError, AppResult},
+  events::EventBus,
+  services::{job, webhook},
+};
+use domain::{ActorId, DomainEvent, DomainEventRecord, TransactionEventData, WalletId};
+use infra::stores::DomainEventStore;
+
+/// Events dispatched per transaction
+const DISPATCH_BATCH: i64 = 100;
+
+/// A page of transactions read from the event log.
+#[derive(Debug, Clone)]
+pub struct TransactionPage {
+  /// Transactions in scope, in log order
+  pub transactions: Vec<TransactionEventData>,
+  /// Sequence of the last event read, in scope or not, to continue from
+  pub cursor: i64,
+  /// Whether the log had nothing more to read
+  pub exhausted: bool,
+}
+
+/// Hands the events features [`emit`] to every subscriber.
+#[derive(Clone)]
+pub struct DomainEventService {
+  pool: PgPool,
+  events: EventBus,
+}
+
+/// Records `event` in the outbox on the caller's transaction. Subscribers
+/// react once it commits, and never if it rolls back.
+pub(crate) async fn emit(conn: &mut PgConnection, event: DomainEvent) -> AppResult<()> {
+  DomainEventStore::create(&mut *conn, &event).await?;
+
+  Ok(())
+}
+
+/// Everything reacting to domain events. Subscribers only queue work on the
+/// dispatching transaction, so each event is handled exactly once and a slow
+/// email server or webhook endpoint never holds up the others.
+async fn handle(conn: &mut PgConnection, record: &DomainEventRecord) -> AppResult<()> {
+  webhook::on_event(&mut *conn, record).await?;
+  job::on_event(&mut *conn, &record.event).await?;
+
+  Ok(())
+}
+
+impl DomainEventService {
+  pub fn new(pool: PgPool, events: EventBus) -> Self {
+    Self { pool, events }
+  }
+
+  /// Cursor of the latest event in the log, where to start reading from for
+  /// what happens next. Events still being committed come after it.
+  pub async fn latest_sequence(&self) -> AppResult<i64> {
+    Ok(DomainEventStore::latest_committed_sequence(&self.pool).await?)
+  }
+
+  /// The next `limit` transactions after the event with `sequence` in the
+  /// log, read like [`Self::log_after`] so no transaction committing late is
+  /// ever passed over. Only those moving money in or out of `wallet_id` or a
+  /// wallet of `owner` are returned; the page's cursor still moves past the
+  /// others.
+  pub async fn transactions_after(
+    &self,
+    sequence: i64,
+    limit: i64,
+    wallet_id: Option<WalletId>,
+    owner: Option<ActorId>,
+  ) -> AppResult<TransactionPage> {
+    let records = DomainEventStore::list_committed_transactions_after(
+      &self.pool,
+      sequence,
+      limit,
+      wallet_id.as_ref(),
+      owner.as_ref(),
+    )
+    .await?;
+    if records.is_empty() {
+      self.ensure_known(sequence).await?;
+    }
+
+    let exhausted = (records.len() as i64) < limit;
+    let cursor = records
+      .last()
+      .map_or(sequence, |(record, _)| record.sequence);
+    let transactions = records
+      .into_iter()
+      .filter(|(_, in_scope)| *in_scope)
+      .filter_map(|(record, _)| match record.event {
+        DomainEvent::TransactionCreated(transaction) => Some(transaction),
+        _ => None,
+      })
+      .collect();
+
+    Ok(TransactionPage {
+      transactions,
+      cursor,
+      exhausted,
+    })
+  }
+
+  /// The log of every event after the one with `sequence`, for replicating
+  /// it elsewhere. Sequences never change and the log never gains events
+  /// before one it returned, so the last sequence is a safe cursor.
+  pub async fn log_after(&self, sequence: i64, limit: i64) -> AppResult<Vec<DomainEventRecord>> {
+    let records = DomainEventStore::list_committed_after(&self.pool, None, sequence, limit).await?;
+    if records.is_empty() {
+      self.ensure_known(sequence).await?;
+    }
+
+    Ok(records)
+  }
+
+  /// Fails for a cursor no event has, which would otherwise read as a log
+  /// with nothing new forever. Only worth asking once a page comes back
+  /// empty: any event after the cursor proves it exists.
+  async fn ensure_known(&self, sequence: i64) -> AppResult<()> {
+    if sequence == 0 || DomainEventStore::exists(&self.pool, sequence).await? {
+      return Ok(());
+    }
+
+    Err(AppError::UnknownCursor(sequence))
+  }
+
+  /// Dispatches every event recorded so far, oldest first, and returns how
+  /// many it went through.
+  pub async fn dispatch_pending(&self) -> AppResult<u64> {
+    let mut dispatched = 0;
+
+    loop {
+      let mut tx = self.pool.begin().await?;
+
+      let records = DomainEventStore::lock_undispatched(&mut *tx, DISPATCH_BATCH).await?;
+      if records.is_empty() {
+        return Ok(dispatched);
+      }
+
+      for record in &records {
+        handle(&mut tx, record).await?;
+      }
+
+      let ids: Vec<_> = records.iter().map(|record| record.id).collect();
+      DomainEventStore::mark_dispatched(&mut *tx, &ids).await?;
+
+      tx.commit().await?;
+
+      // Live views only hear of what is committed
+      for record in records {
+        match record.event {
+          DomainEvent::TransactionCreated(transaction) => {
+            self.events.publish_transaction(transaction)
+          }
+          DomainEvent::SessionsRevoked { user_id } => self.events.publish_logout(user_id),
+          _ => {}
+        }
+      }
+
+      dispatched += ids.len() as u64;
+    }
+  }
+}
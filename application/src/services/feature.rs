@@ -0,0 +1,119 @@
+use std::{
+  sync::{Arc, Mutex},
+  time::{Duration, Instant},
+};
+
+use sqlx::PgPool;
+
+use crate::error::{AppError, AppResult};
+use domain::{ActorId, Feature, FeatureSwitch};
+use infra::stores::{models::FeatureSwitchUpdate, FeatureSwitchStore};
+
+/// How long other instances may take to notice a switch
+const FEATURE_SWITCH_SYNC: Duration = Duration::from_secs(5);
+
+/// Switches as last read from the database.
+struct CachedSwitches {
+  read_at: Instant,
+  switches: Vec<FeatureSwitch>,
+}
+
+/// Runtime switches for capabilities operators may turn off without a
+/// redeploy, e.g. during an incident with the payment provider.
+#[derive(Clone)]
+pub struct FeatureSwitchService {
+  pool: PgPool,
+  cached: Arc<Mutex<Option<CachedSwitches>>>,
+}
+
+impl FeatureSwitchService {
+  pub fn new(pool: PgPool) -> Self {
+    Self {
+      pool,
+      cached: Arc::default(),
+    }
+  }
+
+  /// Every feature's switch, including those nobody switched yet.
+  pub async fn list(&self) -> AppResult<Vec<FeatureSwitch>> {
+    let switched = FeatureSwitchStore::list(&self.pool).await?;
+
+    Ok(
+      Feature::variants()
+        .iter()
+        .map(|feature| {
+          switched
+            .iter()
+            .find(|switch| switch.feature == *feature)
+            .cloned()
+            .unwrap_or_else(|| FeatureSwitch::untouched(*feature))
+        })
+        .collect(),
+    )
+  }
+
+  /// Turns a feature on or off; `reason` is shown to callers while it is
+  /// off. Every instance notices within a few seconds.
+  pub async fn set(
+    &self,
+    feature: Feature,
+    enabled: bool,
+    reason: Option<String>,
+    changed_by: ActorId,
+  ) -> AppResult<FeatureSwitch> {
+    let switch = FeatureSwitchStore::upsert(
+      &self.pool,
+      &FeatureSwitchUpdate {
+        feature,
+        enabled,
+        reason,
+        changed_by: Some(changed_by),
+      },
+    )
+    .await?;
+
+    *self.cached.lock().expect("feature switches lock") = None;
+
+    Ok(switch)
+  }
+
+  /// Fails with [`AppError::FeatureDisabled`] while `feature` is off.
+  pub async fn require(&self, feature: Feature) -> AppResult<()> {
+    match self.find(feature).await? {
+      Some(switch) if !switch.enabled => Err(AppError::FeatureDisabled {
+        feature,
+        reason: switch.reason,
+      }),
+      _ => Ok(()),
+    }
+  }
+
+  /// The switch of `feature`, if it was ever switched. Read from the
+  /// database at most every few seconds.
+  async fn find(&self, feature: Feature) -> AppResult<Option<FeatureSwitch>> {
+    let fresh = |cached: &Option<CachedSwitches>| {
+      cached
+        .as_ref()
+        .filter(|cached| cached.read_at.elapsed() < FEATURE_SWITCH_SYNC)
+        .map(|cached| {
+          cached
+            .switches
+            .iter()
+            .find(|s| s.feature == feature)
+            .cloned()
+        })
+    };
+
+    if let Some(switch) = fresh(&self.cached.lock().expect("feature switches lock")) {
+      return Ok(switch);
+    }
+
+    let read_at = Instant::now();
+    let switches = FeatureSwitchStore::list(&self.pool).await?;
+    let switch = switches.iter().find(|s| s.feature == feature).cloned();
+    *self.cached.lock().expect("feature switches lock") =
+      Some(CachedSwitches { read_at, switches });
+
+    Ok(switch)
+  }
+}
@@ -1,20 +1,269 @@
-use sqlx::PgPool;
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
 
-use crate::error::AppResult;
-use domain::Guest;
-use infra::stores::GuestStore;
+use crate::{
+  error::{AppError, AppResult},
+  services::{domain_event::emit, RiskService, SettingsService},
+};
+use domain::{
+  types::{ListVersion, Page, PageRequest},
+  ActorId, DomainEvent, Email, Guest, GuestClaim, GuestId, TransactionMetadata, User, Wallet,
+};
+use infra::stores::{
+  models::{GuestClaimCreation, GuestCreation, TransactionCreation, WalletCreation},
+  ActorStore, GuestClaimStore, GuestStore, TransactionStore, WalletStore,
+};
+
+pub use infra::stores::models::GuestFilter;
 
 #[derive(Clone)]
 pub struct GuestService {
   pool: PgPool,
+  risk_service: RiskService,
+  settings_service: SettingsService,
 }
 
 impl GuestService {
-  pub fn new(pool: PgPool) -> Self {
-    Self { pool }
+  pub fn new(pool: PgPool, risk_service: RiskService, settings_service: SettingsService) -> Self {
+    Self {
+      pool,
+      risk_service,
+      settings_service,
+    }
+  }
+
+  /// Creates the guest together with its actor and personal wallet.
+  pub async fn create_guest(&self, email: Option<Email>) -> AppResult<Guest> {
+    let settings = self.settings_service.get().await?;
+    let mut tx = self.pool.begin().await?;
+
+    let actor = ActorStore::create(&mut *tx).await?;
+
+    let guest = GuestStore::create(
+      &mut *tx,
+      &GuestCreation {
+        actor_id: actor,
+        email,
+        verified: false,
+      },
+    )
+    .await?;
+
+    WalletStore::create(
+      &mut *tx,
+      &WalletCreation {
+        owner: Some(actor),
+        label: None,
+        currency: settings.currency,
+        allow_overdraft: settings.allow_overdraft_by_default,
+      },
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(guest)
+  }
+
+  /// Binds a physical token (e.g. an NFC card UID) to the guest, replacing
+  /// the previous one.
+  pub async fn assign_identifier(&self, id: GuestId, identifier: String) -> AppResult<Guest> {
+    if let Some(holder) = GuestStore::find_by_identifier(&self.pool, &identifier).await? {
+      if holder.id != id {
+        return Err(AppError::GuestIdentifierInUse);
+      }
+      return Ok(holder);
+    }
+
+    self.bind_identifier(id, &identifier).await
+  }
+
+  /// Replaces the guest's identifier with a freshly generated one, e.g. for
+  /// a new QR code after the old one leaked.
+  pub async fn rotate_identifier(&self, id: GuestId) -> AppResult<Guest> {
+    let identifier = Uuid::new_v4().simple().to_string();
+
+    self.bind_identifier(id, &identifier).await
+  }
+
+  /// Resolves a scanned token to the guest and their wallet.
+  pub async fn find_by_identifier(&self, identifier: &str) -> AppResult<Option<(Guest, Wallet)>> {
+    let Some(guest) = GuestStore::find_by_identifier(&self.pool, identifier).await? else {
+      return Ok(None);
+    };
+
+    let wallet = WalletStore::find_by_owner(&self.pool, &guest.actor_id)
+      .await?
+      .ok_or(AppError::NotFound)?;
+
+    Ok(Some((guest, wallet)))
+  }
+
+  pub async fn list(&self, filter: GuestFilter, page: PageRequest) -> AppResult<Page<Guest>> {
+    let items = GuestStore::list_paginated(&self.pool, &filter, &page).await?;
+    let total = GuestStore::count(&self.pool, &filter).await?;
+
+    Ok(Page {
+      items,
+      total,
+      request: page,
+    })
+  }
+
+  /// Changes whenever [`Self::list`] with the same filter would list
+  /// different guests, on any page.
+  pub async fn list_version(&self, filter: &GuestFilter) -> AppResult<ListVersion> {
+    Ok(GuestStore::version(&self.pool, filter).await?)
+  }
+
+  /// Moves the guest's balance into the user's wallet and retires the guest,
+  /// whose wallet keeps its history.
+  ///
+  /// The user proves to be the guest with the guest's identifier, or by being
+  /// registered with the guest's verified email. A guest in debt passes it on,
+  /// so the user's wallet has to be able to cover it.
+  pub async fn claim(
+    &self,
+    user: &User,
+    id: GuestId,
+    identifier: Option<&str>,
+  ) -> AppResult<GuestClaim> {
+    let mut tx = self.pool.begin().await?;
+
+    let guest = GuestStore::find_by_id_for_update(&mut *tx, &id)
+      .await?
+      .ok_or(AppError::NotFound)?;
+    if GuestClaimStore::find_by_guest_id(&mut *tx, &id)
+      .await?
+      .is_some()
+    {
+      return Err(AppError::GuestAlreadyClaimed);
+    }
+
+    let holds_identifier = identifier.is_some() && guest.identifier.as_deref() == identifier;
+    let shares_email = guest.verified
+      && guest
+        .email
+        .as_ref()
+        .is_some_and(|email| email.expose().eq_ignore_ascii_case(user.email.expose()));
+    if !holds_identifier && !shares_email {
+      if identifier.is_some() {
+        tx.rollback().await?;
+        self.record_wrong_identifier(&guest).await?;
+      }
+      return Err(AppError::Authorization);
+    }
+
+    let (guest_wallet, user_wallet) =
+      find_wallets_for_update(&mut tx, &guest.actor_id, &user.actor_id).await?;
+
+    let balance = WalletStore::find_balance(&mut *tx, &guest_wallet.id).await?;
+    let transfer = if balance.is_positive() {
+      Some(TransactionCreation {
+        source: guest_wallet.id,
+        destination: user_wallet.id,
+        executor: Some(user.actor_id),
+        amount: balance,
+        description: Some("Balance claimed from guest".to_string()),
+        reversal_of: None,
+        metadata: TransactionMetadata::default(),
+      })
+    } else if balance.is_negative() {
+      let debt = balance.abs();
+      let user_balance = WalletStore::find_balance(&mut *tx, &user_wallet.id).await?;
+      if !user_wallet.can_send(user_balance, debt) {
+        return Err(AppError::InsufficientFunds);
+      }
+
+      Some(TransactionCreation {
+        source: user_wallet.id,
+        destination: guest_wallet.id,
+        executor: Some(user.actor_id),
+        amount: debt,
+        description: Some("Debt taken over from guest".to_string()),
+        reversal_of: None,
+        metadata: TransactionMetadata::default(),
+      })
+    } else {
+      None
+    };
+
+    let transaction_id = match transfer {
+      Some(creation) => {
+        let transaction = TransactionStore::create(&mut *tx, &creation).await?;
+        emit(
+          &mut tx,
+          DomainEvent::TransactionCreated((&transaction).into()),
+        )
+        .await?;
+        Some(transaction.id)
+      }
+      None => None,
+    };
+
+    let claim = GuestClaimStore::create(
+      &mut *tx,
+      &GuestClaimCreation {
+        guest_id: guest.id,
+        user_id: user.id,
+        amount: balance,
+        transaction_id,
+      },
+    )
+    .await?;
+
+    GuestStore::retire_by_id(&mut *tx, &guest.id).await?;
+
+    tx.commit().await?;
+
+    Ok(claim)
+  }
+
+  /// A wrong identifier counts against the guest's card like a wrong PIN.
+  async fn record_wrong_identifier(&self, guest: &Guest) -> AppResult<()> {
+    if let Some(wallet) = WalletStore::find_by_owner(&self.pool, &guest.actor_id).await? {
+      self.risk_service.record_failed_pin(wallet.id).await?;
+    }
+
+    Ok(())
   }
 
-  pub async fn get_all(&self) -> AppResult<Vec<Guest>> {
-    Ok(GuestStore::list_all(&self.pool).await?)
+  async fn bind_identifier(&self, id: GuestId, identifier: &str) -> AppResult<Guest> {
+    let guest = GuestStore::set_identifier_by_id(&self.pool, &id, Some(identifier))
+      .await
+      .map_err(|e| match e {
+        // Lost a race against another guest binding the same identifier
+        sqlx::Error::Database(ref db_err) if db_err.is_unique_violation() => {
+          AppError::GuestIdentifierInUse
+        }
+        e => e.into(),
+      })?
+      .ok_or(AppError::NotFound)?;
+
+    if let Some(wallet) = WalletStore::find_by_owner(&self.pool, &guest.actor_id).await? {
+      self.risk_service.record_card_bound(wallet.id).await?;
+    }
+
+    Ok(guest)
+  }
+}
+
+/// The wallets of `a` and `b`, locked together as transfers between them
+/// lock them.
+async fn find_wallets_for_update(
+  conn: &mut PgConnection,
+  a: &ActorId,
+  b: &ActorId,
+) -> AppResult<(Wallet, Wallet)> {
+  let a = WalletStore::find_by_owner(&mut *conn, a)
+    .await?
+    .ok_or(AppError::NotFound)?;
+  let b = WalletStore::find_by_owner(&mut *conn, b)
+    .await?
+    .ok_or(AppError::NotFound)?;
+
+  match WalletStore::find_pair_for_update(&mut *conn, &a.id, &b.id).await? {
+    (Some(a), Some(b)) => Ok((a, b)),
+    _ => Err(AppError::NotFound),
   }
 }
@@ -0,0 +1,118 @@
+use std::{future::Future, time::Duration};
+
+use sqlx::PgPool;
+
+use infra::{services::EmailService, stores::HealthStore};
+
+/// Longest a single dependency may take to answer
+const CHECK_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Outcome of probing one dependency.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Check {
+  Up,
+  /// Why it is considered down, safe to show to anyone
+  Down(&'static str),
+}
+
+/// Whether this instance can serve traffic, per dependency.
+#[derive(Debug, Clone)]
+pub struct Readiness {
+  pub database: Check,
+  pub migrations: Check,
+  /// Only probed when configured; mail failing does not make the server
+  /// unready
+  pub smtp: Option<Check>,
+}
+
+impl Readiness {
+  pub fn is_ready(&self) -> bool {
+    self.database == Check::Up && self.migrations == Check::Up
+  }
+}
+
+#[derive(Clone)]
+pub struct HealthService {
+  pool: PgPool,
+  email_service: EmailService,
+  check_smtp: bool,
+}
+
+impl HealthService {
+  pub fn new(pool: PgPool, email_service: EmailService, check_smtp: bool) -> Self {
+    Self {
+      pool,
+      email_service,
+      check_smtp,
+    }
+  }
+
+  /// Probes the dependencies concurrently. `expected_migration` is the
+  /// newest migration the build ships with; the database must have it.
+  pub async fn readiness(&self, expected_migration: i64) -> Readiness {
+    let (database, migrations, smtp) = tokio::join!(
+      self.check_database(),
+      self.check_migrations(expected_migration),
+      self.check_smtp(),
+    );
+
+    Readiness {
+      database,
+      migrations,
+      smtp,
+    }
+  }
+
+  async fn check_database(&self) -> Check {
+    probe("database", HealthStore::ping(&self.pool), |_| Check::Up).await
+  }
+
+  async fn check_migrations(&self, expected: i64) -> Check {
+    probe(
+      "migrations",
+      HealthStore::latest_migration(&self.pool),
+      |applied| match applied {
+        Some(version) if version >= expected => Check::Up,
+        _ => Check::Down("pending migrations"),
+      },
+    )
+    .await
+  }
+
+  async fn check_smtp(&self) -> Option<Check> {
+    if !self.check_smtp {
+      return None;
+    }
+
+    Some(
+      probe(
+        "SMTP",
+        self.email_service.test_connection(),
+        |accepted| match accepted {
+          true => Check::Up,
+          false => Check::Down("connection test rejected"),
+        },
+      )
+      .await,
+    )
+  }
+}
+
+/// Runs a probe under [`CHECK_TIMEOUT`], logging why it failed.
+async fn probe<T, E: std::fmt::Display>(
+  name: &str,
+  probe: impl Future<Output = Result<T, E>>,
+  judge: impl FnOnce(T) -> Check,
+) -> Check {
+  match tokio::time::timeout(CHECK_TIMEOUT, probe).await {
+    Ok(Ok(value)) => judge(value),
+    Ok(Err(e)) => {
+      tracing::warn!("Readiness check of {} failed: {}", name, e);
+      Check::Down("unreachable")
+    }
+    Err(_) => {
+      tracing::warn!("Readiness check of {} timed out", name);
+      Check::Down("timed out")
+    }
+  }
+}
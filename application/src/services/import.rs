@@ -0,0 +1,341 @@
+use std::collections::BTreeMap;
+
+use chrono::Duration;
+use sqlx::{Acquire, PgConnection, PgPool};
+use uuid::Uuid;
+
+use crate::{
+  error::{AppError, AppResult},
+  services::{domain_event::emit, job::enqueue_email, SettingsService},
+};
+use domain::{
+  parse_member_csv, ActorId, Currency, DomainEvent, GuestId, InviteDomainPolicy, Locale,
+  MemberImportError, MemberImportRow, MetadataSource, RawPassword, Role, TransactionId,
+  TransactionMetadata, UserId, Wallet, WalletId, WalletLabel,
+};
+use infra::{
+  services::{EmailTemplate, EmailTemplates},
+  stores::{
+    models::{
+      GuestCreation, PasswordSetupCreation, TransactionCreation, UserCreation, WalletCreation,
+    },
+    ActorStore, GuestStore, InviteDomainRuleStore, PasswordSetupStore, TransactionStore, UserStore,
+    WalletStore,
+  },
+};
+
+/// A member brought over by an import.
+#[derive(Debug, Clone)]
+pub struct ImportedMember {
+  pub line: usize,
+  pub guest_id: Option<GuestId>,
+  pub user_id: Option<UserId>,
+  pub wallet_id: WalletId,
+  /// Unset for members starting at zero
+  pub opening_transaction_id: Option<TransactionId>,
+}
+
+#[derive(Debug, Clone)]
+pub struct MemberImportReport {
+  pub dry_run: bool,
+  pub imported: Vec<ImportedMember>,
+  pub failed: Vec<MemberImportError>,
+}
+
+#[derive(Clone)]
+pub struct ImportService {
+  pool: PgPool,
+  email_templates: EmailTemplates,
+  settings_service: SettingsService,
+}
+
+impl ImportService {
+  pub fn new(
+    pool: PgPool,
+    email_templates: EmailTemplates,
+    settings_service: SettingsService,
+  ) -> Self {
+    Self {
+      pool,
+      email_templates,
+      settings_service,
+    }
+  }
+
+  /// Creates an actor, guest or user and wallet for every row of a member CSV
+  /// and books its balance as a transfer from the
+  /// [`WalletLabel::LegacyImport`] system wallet.
+  ///
+  /// Users' addresses must be at domains that may be invited with their
+  /// role. They have no password in the legacy system and are emailed a
+  /// token to choose one with, valid as long as an invite. Rows are imported
+  /// one by one; a bad row is reported and skipped without affecting the
+  /// others. A dry run goes through exactly the same steps and rolls
+  /// everything back at the end, emails included.
+  pub async fn import_members(
+    &self,
+    executor: ActorId,
+    executor_role: Role,
+    csv: &str,
+    dry_run: bool,
+  ) -> AppResult<MemberImportReport> {
+    let rows = parse_member_csv(csv).map_err(AppError::Validation)?;
+    let settings = self.settings_service.get().await?;
+    let expires_in = Duration::days(settings.invite_expiration_days.into());
+
+    let mut tx = self.pool.begin().await?;
+    let source = find_import_wallet(&mut tx, settings.currency).await?;
+    let policy = InviteDomainPolicy {
+      rules: InviteDomainRuleStore::list(&mut *tx).await?,
+    };
+
+    let mut imported = Vec::new();
+    let mut failed = Vec::new();
+    for row in rows {
+      let row = match row {
+        Ok(row) => row,
+        Err(e) => {
+          failed.push(e);
+          continue;
+        }
+      };
+      let line = row.line;
+
+      // A savepoint per row, so a failed row leaves no trace
+      let mut savepoint = (&mut *tx).begin().await?;
+      match import_member(
+        &mut savepoint,
+        executor,
+        executor_role,
+        &policy,
+        &source,
+        settings.allow_overdraft_by_default,
+        row,
+      )
+      .await
+      {
+        Ok(member) => {
+          if let Some(user_id) = member.user_id {
+            self
+              .send_password_setup(&mut savepoint, user_id, expires_in)
+              .await?;
+          }
+          savepoint.commit().await?;
+          imported.push(member);
+        }
+        Err(AppError::Validation(message)) => {
+          savepoint.rollback().await?;
+          failed.push(MemberImportError { line, message });
+        }
+        Err(e) => return Err(e),
+      }
+    }
+
+    if dry_run {
+      tx.rollback().await?;
+    } else {
+      tx.commit().await?;
+    }
+
+    Ok(MemberImportReport {
+      dry_run,
+      imported,
+      failed,
+    })
+  }
+
+  /// Queues an email to the imported user with a token to choose their
+  /// password with.
+  async fn send_password_setup(
+    &self,
+    conn: &mut PgConnection,
+    user_id: UserId,
+    expires_in: Duration,
+  ) -> AppResult<()> {
+    let user = UserStore::find_by_id(&mut *conn, &user_id)
+      .await?
+      .ok_or(AppError::NotFound)?;
+    let setup = PasswordSetupStore::replace(
+      &mut *conn,
+      &PasswordSetupCreation {
+        user_id,
+        token: Uuid::new_v4().to_string(),
+        expires_in,
+      },
+    )
+    .await?;
+
+    let content = self.email_templates.render(
+      &EmailTemplate::PasswordSetup { token: setup.token },
+      user.locale,
+    )?;
+    enqueue_email(conn, &user.email, content).await
+  }
+}
+
+/// Rejections that only concern the row come back as
+/// [`AppError::Validation`].
+async fn import_member(
+  conn: &mut PgConnection,
+  executor: ActorId,
+  executor_role: Role,
+  policy: &InviteDomainPolicy,
+  source: &Wallet,
+  allow_overdraft: bool,
+  row: MemberImportRow,
+) -> AppResult<ImportedMember> {
+  let actor = ActorStore::create(&mut *conn).await?;
+
+  let mut guest_id = None;
+  let mut user_id = None;
+  match row.role {
+    Some(role) => {
+      if !executor_role.can_assign_role(role) {
+        return Err(AppError::Validation(format!(
+          "You cannot create users with the role `{}`",
+          role
+        )));
+      }
+
+      // Checked by `parse_member_csv` for rows with a role
+      let (Some(email), Some(first_name), Some(last_name)) =
+        (row.email, row.first_name, row.last_name)
+      else {
+        return Err(AppError::Validation(
+          "Users need an email, first name and last name".to_string(),
+        ));
+      };
+
+      if !policy.permits(&email, role) {
+        return Err(AppError::Validation(format!(
+          "Addresses at this domain cannot be given the role `{}`",
+          role
+        )));
+      }
+
+      if UserStore::find_by_email(&mut *conn, &email)
+        .await?
+        .is_some()
+      {
+        return Err(AppError::Validation(
+          "A user with this email already exists".to_string(),
+        ));
+      }
+
+      // Unknown to anyone; the user chooses their own with the token emailed
+      // by `send_password_setup`
+      let password = RawPassword::new(Uuid::new_v4().simple().to_string());
+      let user = UserStore::create(
+        &mut *conn,
+        &UserCreation {
+          actor_id: actor,
+          email,
+          password: password.hash()?,
+          first_name,
+          last_name,
+          role,
+          locale: Locale::default(),
+        },
+      )
+      .await?;
+      emit(
+        &mut *conn,
+        DomainEvent::UserRegistered {
+          user_id: user.id,
+          role: user.role,
+        },
+      )
+      .await?;
+
+      user_id = Some(user.id);
+    }
+    None => {
+      if let Some(identifier) = &row.identifier {
+        if GuestStore::find_by_identifier(&mut *conn, identifier)
+          .await?
+          .is_some()
+        {
+          return Err(AppError::Validation(format!(
+            "Identifier `{}` is already bound to a guest",
+            identifier
+          )));
+        }
+      }
+
+      let guest = GuestStore::create(
+        &mut *conn,
+        &GuestCreation {
+          actor_id: actor,
+          email: row.email,
+          verified: false,
+        },
+      )
+      .await?;
+
+      // Bound directly rather than through the guest service: a bulk import
+      // is not the card churn its risk signal looks out for
+      if let Some(identifier) = &row.identifier {
+        GuestStore::set_identifier_by_id(&mut *conn, &guest.id, Some(identifier)).await?;
+      }
+
+      guest_id = Some(guest.id);
+    }
+  }
+
+  let wallet = WalletStore::create(
+    &mut *conn,
+    &WalletCreation {
+      owner: Some(actor),
+      label: None,
+      currency: source.currency,
+      allow_overdraft,
+    },
+  )
+  .await?;
+
+  let opening_transaction_id = if row.opening_balance.is_positive() {
+    let transaction = TransactionStore::create(
+      &mut *conn,
+      &TransactionCreation {
+        source: source.id,
+        destination: wallet.id,
+        executor: Some(executor),
+        amount: row.opening_balance,
+        description: Some("Opening balance".to_string()),
+        reversal_of: None,
+        metadata: TransactionMetadata::new(
+          MetadataSource::Import,
+          BTreeMap::from([("import_line".to_string(), row.line.to_string())]),
+        )
+        .map_err(AppError::Validation)?,
+      },
+    )
+    .await?;
+    emit(
+      &mut *conn,
+      DomainEvent::TransactionCreated((&transaction).into()),
+    )
+    .await?;
+    Some(transaction.id)
+  } else {
+    None
+  };
+
+  Ok(ImportedMember {
+    line: row.line,
+    guest_id,
+    user_id,
+    wallet_id: wallet.id,
+    opening_transaction_id,
+  })
+}
+
+/// The system wallet opening balances in `currency` are paid from.
+async fn find_import_wallet(conn: &mut PgConnection, currency: Currency) -> AppResult<Wallet> {
+  WalletStore::find_by_label(&mut *conn, &WalletLabel::LegacyImport, currency)
+    .await?
+    .ok_or_else(|| {
+      tracing::error!("System wallet {} is missing", WalletLabel::LegacyImport);
+      AppError::InternalServerError
+    })
+}
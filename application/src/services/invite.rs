@@ -1,52 +1,81 @@
-use chrono::Duration;
-use sqlx::PgPool;
+use chrono::{Duration, Utc};
+use sqlx::{PgConnection, PgPool};
 use uuid::Uuid;
 
 use crate::{
   error::{AppError, AppResult},
-  services::auth::AuthService,
+  services::{auth::AuthService, domain_event::emit, job::enqueue_email, SettingsService},
+};
+use domain::{
+  types::{ListVersion, Page, PageRequest},
+  DomainEvent, Email, Invite, InviteDomainPolicy, InviteDomainRule, InviteId, InviteStatus, Locale,
+  RawPassword, Role, User, UserId,
 };
-use domain::{Email, Invite, RawPassword, Role, User, UserId};
 use infra::{
-  services::EmailService,
-  stores::{models::InviteCreation, InviteStore, UserStore},
+  services::{EmailContent, EmailTemplate, EmailTemplates},
+  stores::{
+    models::{InviteCreation, InviteUpdate},
+    InviteDomainRuleStore, InviteStore, UserStore,
+  },
 };
 
+pub use infra::stores::models::InviteFilter;
+
 #[derive(Clone)]
 pub struct InviteService {
   pool: PgPool,
-  email_service: EmailService,
   auth_service: AuthService,
+  email_templates: EmailTemplates,
+  settings_service: SettingsService,
 }
 
 impl InviteService {
-  pub fn new(pool: PgPool, email_service: EmailService, auth_service: AuthService) -> Self {
+  pub fn new(
+    pool: PgPool,
+    auth_service: AuthService,
+    email_templates: EmailTemplates,
+    settings_service: SettingsService,
+  ) -> Self {
     Self {
       pool,
-      email_service,
       auth_service,
+      email_templates,
+      settings_service,
     }
   }
 
+  /// Creates the invite and queues its email, in the invitee's locale, which
+  /// is delivered in the background. The address's domain must be allowed
+  /// for the role.
   pub async fn create_invite(
     &self,
     invitor: UserId,
     email: Email,
     role: Role,
+    locale: Locale,
   ) -> AppResult<Invite> {
-    if let Some(invite) = InviteStore::find_by_email(&self.pool, &email).await? {
-      if invite.is_expired() {
-        InviteStore::delete_by_id(&self.pool, &invite.id).await?;
+    let inviter_name = self.inviter_name(invitor).await?;
+    let expires_in = self.expires_in().await?;
+
+    let mut tx = self.pool.begin().await?;
+
+    let policy = InviteDomainPolicy {
+      rules: InviteDomainRuleStore::list(&mut *tx).await?,
+    };
+    if !policy.permits(&email, role) {
+      return Err(AppError::InviteDomainNotAllowed(role));
+    }
+
+    if let Some(invite) = InviteStore::find_by_email(&mut *tx, &email).await? {
+      if invite.is_expired() || !invite.is_pending() {
+        InviteStore::delete_by_id(&mut *tx, &invite.id).await?;
       } else {
-        return Err(AppError::InviteAlreadySent);
+        return Err(AppError::InviteAlreadySent {
+          expires_at: invite.expires_at(),
+        });
       }
     }
 
-    let inviter_name = UserStore::find_by_id(&self.pool, &invitor)
-      .await?
-      .map(|u| format!("{} {}", u.first_name, u.last_name))
-      .ok_or(AppError::InvitorMissing(invitor))?;
-
     let token = Uuid::new_v4().to_string();
 
     let new_invite = InviteCreation {
@@ -54,30 +83,39 @@ impl InviteService {
       email: email.clone(),
       token: token.clone(),
       role,
-      expires_in: Duration::days(7),
+      locale,
+      expires_in,
     };
 
-    let invite = InviteStore::create(&self.pool, &new_invite).await?;
+    let invite = InviteStore::create(&mut *tx, &new_invite).await?;
+    let content = self.invite_email(inviter_name, token, locale)?;
+    enqueue_email(&mut tx, &email, content).await?;
 
-    self
-      .email_service
-      .send_invite(&email, &token, &inviter_name)
-      .await?;
+    tx.commit().await?;
 
     Ok(invite)
   }
 
+  /// Registers the invited user and consumes the invite, both on the
+  /// caller's transaction.
   pub async fn accept_invite(
     &self,
+    conn: &mut PgConnection,
     token: &str,
     password: RawPassword,
     first_name: String,
     last_name: String,
   ) -> AppResult<User> {
-    let invite = InviteStore::find_by_token(&self.pool, token)
+    // Held until the request's transaction ends, so a concurrent accept of
+    // the same invite waits and then finds it gone.
+    let invite = InviteStore::find_by_token_for_update(&mut *conn, token)
       .await?
       .ok_or(AppError::NotFound)?;
 
+    if invite.status == InviteStatus::Revoked {
+      return Err(AppError::InviteRevoked);
+    }
+
     if invite.is_expired() {
       return Err(AppError::InviteExpired);
     }
@@ -85,20 +123,161 @@ impl InviteService {
     let user = self
       .auth_service
       .register(
+        &mut *conn,
         invite.email.clone(),
         password,
         first_name,
         last_name,
         invite.role,
+        invite.locale,
       )
       .await?;
 
-    InviteStore::delete_by_id(&self.pool, &invite.id).await?;
+    InviteStore::delete_by_id(&mut *conn, &invite.id).await?;
+
+    emit(
+      conn,
+      DomainEvent::InviteAccepted {
+        invite_id: invite.id,
+        user_id: user.id,
+        role: user.role,
+        invited_by: invite.invitor,
+        accepted_at: Utc::now(),
+      },
+    )
+    .await?;
 
     Ok(user)
   }
 
-  pub async fn get_all(&self) -> AppResult<Vec<Invite>> {
-    Ok(InviteStore::list_all(&self.pool).await?)
+  pub async fn domain_policy(&self) -> AppResult<InviteDomainPolicy> {
+    let rules = InviteDomainRuleStore::list(&self.pool).await?;
+
+    Ok(InviteDomainPolicy { rules })
+  }
+
+  /// Replaces all rules of which domains may be invited. Pending invites are
+  /// left as they are.
+  pub async fn set_domain_policy(
+    &self,
+    rules: Vec<InviteDomainRule>,
+  ) -> AppResult<InviteDomainPolicy> {
+    let mut tx = self.pool.begin().await?;
+
+    InviteDomainRuleStore::delete_all(&mut *tx).await?;
+    for rule in &rules {
+      InviteDomainRuleStore::create(&mut *tx, rule).await?;
+    }
+    let rules = InviteDomainRuleStore::list(&mut *tx).await?;
+
+    tx.commit().await?;
+
+    Ok(InviteDomainPolicy { rules })
+  }
+
+  pub async fn get_by_id(&self, id: InviteId) -> AppResult<Option<Invite>> {
+    Ok(InviteStore::find_by_id(&self.pool, &id).await?)
+  }
+
+  pub async fn revoke_invite(&self, id: InviteId) -> AppResult<Invite> {
+    let invite = InviteStore::find_by_id(&self.pool, &id)
+      .await?
+      .ok_or(AppError::NotFound)?;
+
+    if !invite.is_pending() {
+      return Err(AppError::BadRequest(format!(
+        "Invite is already {}",
+        invite.status
+      )));
+    }
+
+    InviteStore::update_by_id(
+      &self.pool,
+      &id,
+      &InviteUpdate {
+        status: Some(InviteStatus::Revoked),
+      },
+    )
+    .await?
+    .ok_or(AppError::NotFound)
+  }
+
+  /// Issues a fresh token with a new expiry window and queues another
+  /// email with it.
+  ///
+  /// The previous token stops working immediately.
+  pub async fn resend_invite(&self, resender: UserId, id: InviteId) -> AppResult<Invite> {
+    let inviter_name = self.inviter_name(resender).await?;
+    let expires_in = self.expires_in().await?;
+
+    let mut tx = self.pool.begin().await?;
+
+    let invite = InviteStore::find_by_id(&mut *tx, &id)
+      .await?
+      .ok_or(AppError::NotFound)?;
+
+    if !invite.is_pending() {
+      return Err(AppError::BadRequest(format!(
+        "Invite is already {}",
+        invite.status
+      )));
+    }
+
+    let token = Uuid::new_v4().to_string();
+
+    let invite = InviteStore::rotate_token_by_id(&mut *tx, &id, &token, expires_in)
+      .await?
+      .ok_or(AppError::NotFound)?;
+
+    let content = self.invite_email(inviter_name, token, invite.locale)?;
+    enqueue_email(&mut tx, &invite.email, content).await?;
+
+    tx.commit().await?;
+
+    Ok(invite)
+  }
+
+  pub async fn list(&self, filter: InviteFilter, page: PageRequest) -> AppResult<Page<Invite>> {
+    let items = InviteStore::list_paginated(&self.pool, &filter, &page).await?;
+    let total = InviteStore::count(&self.pool, &filter).await?;
+
+    Ok(Page {
+      items,
+      total,
+      request: page,
+    })
+  }
+
+  /// Changes whenever [`Self::list`] with the same filter would list
+  /// different invites, on any page.
+  pub async fn list_version(&self, filter: &InviteFilter) -> AppResult<ListVersion> {
+    Ok(InviteStore::version(&self.pool, filter).await?)
+  }
+
+  fn invite_email(
+    &self,
+    inviter_name: String,
+    token: String,
+    locale: Locale,
+  ) -> AppResult<EmailContent> {
+    Ok(self.email_templates.render(
+      &EmailTemplate::Invite {
+        inviter_name,
+        token,
+      },
+      locale,
+    )?)
+  }
+
+  async fn inviter_name(&self, invitor: UserId) -> AppResult<String> {
+    UserStore::find_by_id(&self.pool, &invitor)
+      .await?
+      .map(|u| format!("{} {}", u.first_name, u.last_name))
+      .ok_or(AppError::InvitorMissing(invitor))
+  }
+
+  async fn expires_in(&self) -> AppResult<Duration> {
+    let settings = self.settings_service.get().await?;
+    Ok(Duration::days(settings.invite_expiration_days.into()))
   }
 }
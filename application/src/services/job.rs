@@ -0,0 +1,405 @@
+use chrono::{Duration, Utc};
+use sqlx::{PgConnection, PgPool};
+
+use crate::{
+  error::{AppError, AppResult},
+  services::SettingsService,
+};
+use domain::{
+  types::Money, ActorId, Currency, DomainEvent, Email, Job, JobTask, Locale, OutboundEmailId,
+  OutboundEmailStatus, UserId, WalletId, WebhookDeliveryId, WebhookDeliveryStatus,
+};
+use infra::{
+  services::{
+    EmailContent, EmailService, EmailTemplate, EmailTemplates, WebhookClient, WebhookError,
+  },
+  stores::{
+    models::{JobCreation, OutboundEmailCreation},
+    GuestStore, JobStore, OutboundEmailStore, UserStore, WalletStore, WebhookDeliveryStore,
+    WebhookStore,
+  },
+};
+
+/// Attempts before a job is given up on; with the backoff about five hours
+const MAX_ATTEMPTS: i32 = 8;
+/// How long a worker may take before its job is handed to another one
+const LEASE_SECS: i64 = 5 * 60;
+/// Days succeeded jobs, sent emails and delivered webhook events are kept
+/// around for inspection
+const SUCCEEDED_RETENTION_DAYS: i64 = 7;
+
+/// Runs the jobs queued with [`enqueue`].
+#[derive(Clone)]
+pub struct JobService {
+  pool: PgPool,
+  email_service: EmailService,
+  email_templates: EmailTemplates,
+  webhook_client: WebhookClient,
+  settings_service: SettingsService,
+}
+
+/// Queues `task` to run in the background once the surrounding transaction
+/// commits.
+pub(crate) async fn enqueue(conn: &mut PgConnection, task: JobTask) -> AppResult<()> {
+  JobStore::create(
+    &mut *conn,
+    &JobCreation {
+      task,
+      max_attempts: MAX_ATTEMPTS,
+    },
+  )
+  .await?;
+
+  Ok(())
+}
+
+/// Stores the email in the outbox and queues its delivery once the
+/// surrounding transaction commits.
+pub(crate) async fn enqueue_email(
+  conn: &mut PgConnection,
+  recipient: &Email,
+  content: EmailContent,
+) -> AppResult<()> {
+  let email = OutboundEmailStore::create(
+    &mut *conn,
+    &OutboundEmailCreation {
+      recipient: recipient.clone(),
+      subject: content.subject,
+      text_body: content.text_body,
+      html_body: content.html_body,
+    },
+  )
+  .await?;
+
+  enqueue(conn, JobTask::SendEmail { email_id: email.id }).await
+}
+
+/// Where to email the user or guest behind an actor, if anywhere. Guests
+/// are written to in the default locale.
+pub(crate) async fn actor_contact(
+  conn: &mut PgConnection,
+  actor_id: &ActorId,
+) -> AppResult<Option<(Email, Locale)>> {
+  if let Some(user) = UserStore::find_by_actor_id(&mut *conn, actor_id).await? {
+    return Ok(Some((user.email, user.locale)));
+  }
+
+  Ok(
+    GuestStore::find_by_actor_id(&mut *conn, actor_id)
+      .await?
+      .and_then(|guest| guest.email)
+      .map(|email| (email, Locale::default())),
+  )
+}
+
+/// Subscriber queueing the notices an event calls for.
+pub(crate) async fn on_event(conn: &mut PgConnection, event: &DomainEvent) -> AppResult<()> {
+  match event {
+    DomainEvent::TransferSent {
+      sender_id,
+      recipient_id,
+      amount_cents,
+      currency,
+      notify_recipient: true,
+      ..
+    } => {
+      let Some(sender) = UserStore::find_by_id(&mut *conn, sender_id).await? else {
+        return Ok(());
+      };
+
+      enqueue(
+        conn,
+        JobTask::TransferNotice {
+          recipient: *recipient_id,
+          sender_name: format!("{} {}", sender.first_name, sender.last_name),
+          amount_cents: *amount_cents,
+          currency: *currency,
+        },
+      )
+      .await
+    }
+    DomainEvent::ChargebackOpened {
+      wallet_id,
+      amount_cents,
+      balance_cents,
+      ..
+    } => {
+      enqueue(
+        conn,
+        JobTask::ChargebackNotice {
+          wallet_id: *wallet_id,
+          amount_cents: *amount_cents,
+          balance_cents: *balance_cents,
+        },
+      )
+      .await
+    }
+    _ => Ok(()),
+  }
+}
+
+impl JobService {
+  pub fn new(
+    pool: PgPool,
+    email_service: EmailService,
+    email_templates: EmailTemplates,
+    webhook_client: WebhookClient,
+    settings_service: SettingsService,
+  ) -> Self {
+    Self {
+      pool,
+      email_service,
+      email_templates,
+      webhook_client,
+      settings_service,
+    }
+  }
+
+  /// Works through the jobs that are due, one at a time, and returns how
+  /// many it ran. Failed attempts are scheduled again with backoff.
+  pub async fn run_due(&self) -> AppResult<u64> {
+    let exhausted = JobStore::fail_exhausted(&self.pool).await?;
+    if exhausted > 0 {
+      tracing::error!("{exhausted} jobs lost their worker on the last attempt and failed for good");
+    }
+
+    let mut ran = 0;
+
+    while let Some(job) = JobStore::claim_due(&self.pool, Duration::seconds(LEASE_SECS)).await? {
+      ran += 1;
+
+      let Err(e) = self.perform(&job).await else {
+        JobStore::succeed(&self.pool, &job.id).await?;
+        continue;
+      };
+
+      let retry_at = job.can_retry().then(|| Utc::now() + job.retry_delay());
+      match retry_at {
+        Some(retry_at) => tracing::warn!(
+          "Job {} ({}) failed on attempt {}, retrying at {}: {}",
+          job.id,
+          job.task.name(),
+          job.attempts,
+          retry_at,
+          e
+        ),
+        None => tracing::error!(
+          "Job {} ({}) failed for good after {} attempts: {}",
+          job.id,
+          job.task.name(),
+          job.attempts,
+          e
+        ),
+      }
+      JobStore::fail(&self.pool, &job.id, &e.to_string(), retry_at).await?;
+    }
+
+    Ok(ran)
+  }
+
+  /// Deletes jobs that succeeded, emails that were sent and webhook events
+  /// that were delivered a while ago, and returns how many rows went.
+  pub async fn purge_succeeded(&self) -> AppResult<u64> {
+    let cutoff = Utc::now() - Duration::days(SUCCEEDED_RETENTION_DAYS);
+
+    let jobs = JobStore::delete_succeeded_before(&self.pool, cutoff).await?;
+    let emails = OutboundEmailStore::delete_sent_before(&self.pool, cutoff).await?;
+    let deliveries = WebhookDeliveryStore::delete_delivered_before(&self.pool, cutoff).await?;
+
+    Ok(jobs + emails + deliveries)
+  }
+
+  async fn perform(&self, job: &Job) -> AppResult<()> {
+    match &job.task {
+      JobTask::TransferNotice {
+        recipient,
+        sender_name,
+        amount_cents,
+        currency,
+      } => {
+        self
+          .send_transfer_notice(
+            *recipient,
+            sender_name,
+            Money::from_minor(*amount_cents),
+            *currency,
+          )
+          .await
+      }
+      JobTask::ChargebackNotice {
+        wallet_id,
+        amount_cents,
+        balance_cents,
+      } => {
+        self
+          .send_chargeback_notice(
+            *wallet_id,
+            Money::from_minor(*amount_cents),
+            Money::from_minor(*balance_cents),
+          )
+          .await
+      }
+      JobTask::SendEmail { email_id } => self.send_email(*email_id, job.can_retry()).await,
+      JobTask::DeliverWebhook { delivery_id } => {
+        self.deliver_webhook(*delivery_id, job.can_retry()).await
+      }
+    }
+  }
+
+  /// Delivers an email from the outbox and records the outcome on it.
+  /// Skipped once it was sent or purged.
+  async fn send_email(&self, email_id: OutboundEmailId, will_retry: bool) -> AppResult<()> {
+    let Some(email) = OutboundEmailStore::find_by_id(&self.pool, &email_id).await? else {
+      return Ok(());
+    };
+    if email.status == OutboundEmailStatus::Sent {
+      return Ok(());
+    }
+
+    let content = EmailContent {
+      subject: email.subject,
+      text_body: email.text_body,
+      html_body: email.html_body,
+    };
+
+    let settings = self.settings_service.get().await?;
+    let sent = self
+      .email_service
+      .send(
+        &email.recipient,
+        &content,
+        settings.email_sender_name.as_deref(),
+      )
+      .await;
+    match sent {
+      Ok(()) => {
+        OutboundEmailStore::mark_sent(&self.pool, &email_id).await?;
+        Ok(())
+      }
+      Err(e) => {
+        OutboundEmailStore::mark_failed(&self.pool, &email_id, &e.to_string(), !will_retry).await?;
+        Err(e.into())
+      }
+    }
+  }
+
+  /// Posts an event from a webhook's delivery log and records the outcome on
+  /// it. Skipped once it was delivered or its webhook removed.
+  async fn deliver_webhook(
+    &self,
+    delivery_id: WebhookDeliveryId,
+    will_retry: bool,
+  ) -> AppResult<()> {
+    let Some(delivery) = WebhookDeliveryStore::find_by_id(&self.pool, &delivery_id).await? else {
+      return Ok(());
+    };
+    if delivery.status == WebhookDeliveryStatus::Delivered {
+      return Ok(());
+    }
+    let Some(webhook) = WebhookStore::find_by_id(&self.pool, &delivery.webhook_id).await? else {
+      return Ok(());
+    };
+
+    let result = self
+      .webhook_client
+      .deliver(
+        &webhook.url,
+        &webhook.secret,
+        &delivery.event.to_string(),
+        delivery.payload.to_string(),
+      )
+      .await;
+
+    match result {
+      Ok(status) => {
+        WebhookDeliveryStore::mark_delivered(&self.pool, &delivery_id, i32::from(status)).await?;
+        Ok(())
+      }
+      // Retrying won't change where the webhook points
+      Err(e @ WebhookError::Forbidden(_)) => {
+        WebhookDeliveryStore::mark_failed(&self.pool, &delivery_id, None, &e.to_string(), true)
+          .await?;
+        Ok(())
+      }
+      Err(e) => {
+        let status = match e {
+          WebhookError::Status(status) => Some(i32::from(status)),
+          _ => None,
+        };
+        WebhookDeliveryStore::mark_failed(
+          &self.pool,
+          &delivery_id,
+          status,
+          &e.to_string(),
+          !will_retry,
+        )
+        .await?;
+        Err(e.into())
+      }
+    }
+  }
+
+  /// Skipped once the recipient's account is gone.
+  async fn send_transfer_notice(
+    &self,
+    recipient: UserId,
+    sender_name: &str,
+    amount: Money,
+    currency: Currency,
+  ) -> AppResult<()> {
+    let Some(user) = UserStore::find_by_id(&self.pool, &recipient).await? else {
+      return Ok(());
+    };
+
+    let content = self.email_templates.render(
+      &EmailTemplate::TransferNotice {
+        sender_name: sender_name.to_string(),
+        amount,
+        currency,
+      },
+      user.locale,
+    )?;
+    let settings = self.settings_service.get().await?;
+    self
+      .email_service
+      .send(&user.email, &content, settings.email_sender_name.as_deref())
+      .await?;
+
+    Ok(())
+  }
+
+  /// Skipped for owners without an email address. Guests get it in the
+  /// default locale.
+  async fn send_chargeback_notice(
+    &self,
+    wallet_id: WalletId,
+    amount: Money,
+    balance: Money,
+  ) -> AppResult<()> {
+    let wallet = WalletStore::find_by_id(&self.pool, &wallet_id)
+      .await?
+      .ok_or(AppError::NotFound)?;
+    let Some(owner) = wallet.owner else {
+      return Ok(());
+    };
+
+    let recipient = actor_contact(&mut *self.pool.acquire().await?, &owner).await?;
+    if let Some((email, locale)) = recipient {
+      let content = self.email_templates.render(
+        &EmailTemplate::ChargebackNotice {
+          amount,
+          balance,
+          currency: wallet.currency,
+        },
+        locale,
+      )?;
+      let settings = self.settings_service.get().await?;
+      self
+        .email_service
+        .send(&email, &content, settings.email_sender_name.as_deref())
+        .await?;
+    }
+
+    Ok(())
+  }
+}
@@ -1,11 +1,61 @@
+pub mod actor;
+pub mod audit;
 pub mod auth;
+pub mod balance_alert;
+pub mod budget;
+pub mod chargeback;
+pub mod client_app;
+pub mod debt;
+pub mod domain_event;
+pub mod feature;
 pub mod guest;
+pub mod health;
+pub mod import;
 pub mod invite;
+pub mod job;
+pub mod note;
+pub mod order;
+pub mod owner_transfer;
+pub mod payout;
+pub mod retention;
+pub mod risk;
 pub mod session;
+pub mod settings;
+pub mod shop;
+pub mod top_up;
+pub mod transaction;
+pub mod transfer;
 pub mod user;
+pub mod wallet;
+pub mod webhook;
 
+pub use actor::ActorService;
+pub use audit::AuditService;
 pub use auth::AuthService;
+pub use balance_alert::BalanceAlertService;
+pub use budget::BudgetService;
+pub use chargeback::ChargebackService;
+pub use client_app::ClientAppService;
+pub use debt::DebtService;
+pub use domain_event::DomainEventService;
+pub use feature::FeatureSwitchService;
 pub use guest::GuestService;
+pub use health::HealthService;
+pub use import::ImportService;
 pub use invite::InviteService;
+pub use job::JobService;
+pub use note::AccountNoteService;
+pub use order::OrderService;
+pub use owner_transfer::OwnerTransferService;
+pub use payout::PayoutService;
+pub use retention::RetentionService;
+pub use risk::RiskService;
 pub use session::SessionService;
+pub use settings::SettingsService;
+pub use shop::ShopService;
+pub use top_up::TopUpService;
+pub use transaction::TransactionService;
+pub use transfer::TransferService;
 pub use user::UserService;
+pub use wallet::WalletService;
+pub use webhook::WebhookService;
@@ -0,0 +1,69 @@
+use sqlx::{PgConnection, PgPool};
+
+use crate::error::{AppError, AppResult};
+use domain::{AccountNote, ActorId, GuestId, UserId};
+use infra::stores::{models::AccountNoteCreation, AccountNoteStore, GuestStore, UserStore};
+
+/// Account a note is about.
+#[derive(Debug, Clone, Copy)]
+pub enum NoteSubject {
+  User(UserId),
+  Guest(GuestId),
+}
+
+/// Internal support history on users and guests.
+#[derive(Clone)]
+pub struct AccountNoteService {
+  pool: PgPool,
+}
+
+impl AccountNoteService {
+  pub fn new(pool: PgPool) -> Self {
+    Self { pool }
+  }
+
+  /// Newest first.
+  pub async fn list(&self, subject: NoteSubject) -> AppResult<Vec<AccountNote>> {
+    let mut conn = self.pool.acquire().await?;
+
+    let actor_id = find_actor(&mut conn, subject).await?;
+
+    Ok(AccountNoteStore::list_by_actor_id(&mut *conn, &actor_id).await?)
+  }
+
+  pub async fn add(
+    &self,
+    author: ActorId,
+    subject: NoteSubject,
+    body: String,
+  ) -> AppResult<AccountNote> {
+    let mut conn = self.pool.acquire().await?;
+
+    let actor_id = find_actor(&mut conn, subject).await?;
+
+    let note = AccountNoteStore::create(
+      &mut *conn,
+      &AccountNoteCreation {
+        actor_id,
+        author,
+        body,
+      },
+    )
+    .await?;
+
+    Ok(note)
+  }
+}
+
+async fn find_actor(conn: &mut PgConnection, subject: NoteSubject) -> AppResult<ActorId> {
+  let actor_id = match subject {
+    NoteSubject::User(id) => UserStore::find_by_id(&mut *conn, &id)
+      .await?
+      .map(|user| user.actor_id),
+    NoteSubject::Guest(id) => GuestStore::find_by_id(&mut *conn, &id)
+      .await?
+      .map(|guest| guest.actor_id),
+  };
+
+  actor_id.ok_or(AppError::NotFound)
+}
@@ -0,0 +1,650 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use sqlx::{PgConnection, PgPool};
+
+use crate::{
+  error::{AppError, AppResult},
+  events::EventBus,
+  services::{domain_event::emit, FeatureSwitchService, RiskService},
+};
+use domain::{
+  types::{Money, Page, PageRequest, VenueTimezone},
+  ActorId, DomainEvent, Feature, LiveShopStats, Order, OrderEvent, OrderEventData, OrderId,
+  OrderItem, OrderStatus, OrderStatusChange, Purchaser, ReorderLine, SalesReport, Shop, ShopId,
+  ShopOfferingId, Transaction, TransactionMetadata, UserId, Wallet, WalletId,
+};
+use infra::stores::{
+  models::{OrderCreation, OrderItemCreation, TransactionCreation},
+  GuestStore, OrderItemStore, OrderStore, SalesReportStore, ShopOfferingStore, ShopStore,
+  TransactionStore, UserStore, WalletStore,
+};
+
+pub use infra::stores::models::OrderFilter;
+
+/// Whose personal wallet pays for an order.
+#[derive(Debug, Clone)]
+pub enum Payer {
+  /// A guest, by the identifier on their card or QR code
+  Guest(String),
+  User(UserId),
+}
+
+#[derive(Debug, Clone)]
+pub struct CheckoutLine {
+  pub offering_id: ShopOfferingId,
+  pub quantity: i32,
+}
+
+/// Where the purchaser receives the order, if the shop serves or calls out
+/// orders rather than handing them over at the till.
+#[derive(Debug, Clone, Default)]
+pub struct Pickup {
+  pub table_label: Option<String>,
+  pub pickup_number: Option<i32>,
+}
+
+/// An order as checked out or pre-ordered, for [`OrderService::place`].
+struct Placement {
+  executor: ActorId,
+  shop_id: ShopId,
+  payer: Payer,
+  lines: Vec<CheckoutLine>,
+  pickup: Pickup,
+  /// Slot booked by a pre-order
+  pickup_at: Option<DateTime<Utc>>,
+  pay_now: bool,
+  metadata: TransactionMetadata,
+}
+
+#[derive(Clone)]
+pub struct OrderService {
+  pool: PgPool,
+  risk_service: RiskService,
+  events: EventBus,
+  feature_switch_service: FeatureSwitchService,
+  timezone: VenueTimezone,
+}
+
+impl OrderService {
+  pub fn new(
+    pool: PgPool,
+    risk_service: RiskService,
+    events: EventBus,
+    feature_switch_service: FeatureSwitchService,
+    timezone: VenueTimezone,
+  ) -> Self {
+    Self {
+      pool,
+      risk_service,
+      events,
+      feature_switch_service,
+      timezone,
+    }
+  }
+
+  pub async fn advance_status(
+    &self,
+    id: OrderId,
+    next: OrderStatus,
+  ) -> AppResult<(Order, Vec<OrderItem>)> {
+    let order = OrderStore::find_by_id(&self.pool, &id)
+      .await?
+      .ok_or(AppError::NotFound)?;
+
+    if !order.status.can_advance_to(next) {
+      return Err(AppError::InvalidOrderTransition {
+        from: order.status,
+        to: next,
+      });
+    }
+
+    if next.is_final() && order.transaction_id.is_none() {
+      return Err(AppError::OrderUnpaid);
+    }
+
+    // Someone else moved the order on in the meantime
+    let order = OrderStore::update_status(&self.pool, &id, order.status, next)
+      .await?
+      .ok_or(AppError::InvalidOrderTransition {
+        from: order.status,
+        to: next,
+      })?;
+
+    self
+      .events
+      .publish(OrderEvent::StatusChanged(OrderStatusChange {
+        order_id: order.id,
+        shop_id: order.shop_id,
+        status: order.status,
+        changed_at: order.updated_at.unwrap_or_else(Utc::now),
+      }));
+
+    let items = OrderItemStore::list_by_order_id(&self.pool, &order.id).await?;
+
+    Ok((order, items))
+  }
+
+  /// Whether `payer` is who paid for the order.
+  pub async fn is_paid_by(&self, order: &Order, payer: &Payer) -> AppResult<bool> {
+    let mut conn = self.pool.acquire().await?;
+
+    match find_payer_wallet(&mut conn, payer).await {
+      Ok(wallet) => Ok(wallet.id == order.payer_wallet_id),
+      Err(AppError::NotFound) => Ok(false),
+      Err(e) => Err(e),
+    }
+  }
+
+  pub async fn get_by_id(&self, id: OrderId) -> AppResult<Option<(Order, Vec<OrderItem>)>> {
+    let Some(order) = OrderStore::find_by_id(&self.pool, &id).await? else {
+      return Ok(None);
+    };
+    let items = OrderItemStore::list_by_order_id(&self.pool, &order.id).await?;
+
+    Ok(Some((order, items)))
+  }
+
+  /// Who paid for the order, as much as the shop's staff may know.
+  pub async fn get_purchaser(&self, id: OrderId) -> AppResult<Option<Purchaser>> {
+    Ok(OrderStore::find_purchaser(&self.pool, &id).await?)
+  }
+
+  /// Lists orders with their line items, newest first.
+  pub async fn list(
+    &self,
+    filter: OrderFilter,
+    page: PageRequest,
+  ) -> AppResult<Page<(Order, Vec<OrderItem>)>> {
+    let orders = OrderStore::list_paginated(&self.pool, &filter, &page).await?;
+    let total = OrderStore::count(&self.pool, &filter).await?;
+
+    Ok(Page {
+      items: self.with_items(orders).await?,
+      total,
+      request: page,
+    })
+  }
+
+  /// The shop's orders that still have to be prepared or picked up, oldest
+  /// first.
+  pub async fn queue(&self, shop_id: ShopId) -> AppResult<Vec<(Order, Vec<OrderItem>)>> {
+    let orders = OrderStore::list_open_by_shop_id(&self.pool, &shop_id).await?;
+
+    self.with_items(orders).await
+  }
+
+  async fn with_items(&self, orders: Vec<Order>) -> AppResult<Vec<(Order, Vec<OrderItem>)>> {
+    let ids: Vec<OrderId> = orders.iter().map(|order| order.id).collect();
+    let mut items: HashMap<OrderId, Vec<OrderItem>> = HashMap::new();
+    for item in OrderItemStore::list_by_order_ids(&self.pool, &ids).await? {
+      items.entry(item.order_id).or_default().push(item);
+    }
+
+    Ok(
+      orders
+        .into_iter()
+        .map(|order| {
+          let lines = items.remove(&order.id).unwrap_or_default();
+          (order, lines)
+        })
+        .collect(),
+    )
+  }
+
+  /// Aggregates the shop's orders placed within `[from, to)`, by day at the
+  /// venue.
+  pub async fn sales_report(
+    &self,
+    shop_id: ShopId,
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+  ) -> AppResult<SalesReport> {
+    if let (Some(from), Some(to)) = (from, to) {
+      if from >= to {
+        return Err(AppError::Validation(
+          "`from` must be before `to`".to_string(),
+        ));
+      }
+    }
+
+    let totals = SalesReportStore::totals(&self.pool, &shop_id, from, to).await?;
+    let by_offering = SalesReportStore::by_offering(&self.pool, &shop_id, from, to).await?;
+    let by_day = SalesReportStore::by_day(&self.pool, &shop_id, from, to, self.timezone).await?;
+
+    Ok(SalesReport {
+      from,
+      to,
+      order_count: totals.order_count,
+      gross_cents: totals.gross_cents,
+      refunded_cents: totals.refunded_cents,
+      by_offering,
+      by_day,
+    })
+  }
+
+  /// Sells the given offerings to `payer`.
+  ///
+  /// Prices are taken from the current catalogue and captured on the order.
+  /// The order, its items and the transfer into the shop's wallet are
+  /// written in one database transaction.
+  pub async fn checkout(
+    &self,
+    executor: ActorId,
+    shop_id: ShopId,
+    payer: Payer,
+    lines: Vec<CheckoutLine>,
+    pickup: Pickup,
+    metadata: TransactionMetadata,
+  ) -> AppResult<(Order, Vec<OrderItem>)> {
+    self
+      .place(Placement {
+        executor,
+        shop_id,
+        payer,
+        lines,
+        pickup,
+        pickup_at: None,
+        pay_now: true,
+        metadata,
+      })
+      .await
+  }
+
+  /// Books an order into one of the shop's pickup slots.
+  ///
+  /// Unless `pay_now` is set, the payer is only charged at pickup through
+  /// [`OrderService::pay`].
+  pub async fn preorder(
+    &self,
+    executor: ActorId,
+    shop_id: ShopId,
+    payer: Payer,
+    lines: Vec<CheckoutLine>,
+    pickup_at: DateTime<Utc>,
+    pay_now: bool,
+  ) -> AppResult<(Order, Vec<OrderItem>)> {
+    self
+      .place(Placement {
+        executor,
+        shop_id,
+        payer,
+        lines,
+        pickup: Pickup::default(),
+        pickup_at: Some(pickup_at),
+        pay_now,
+        metadata: TransactionMetadata::default(),
+      })
+      .await
+  }
+
+  async fn place(&self, placement: Placement) -> AppResult<(Order, Vec<OrderItem>)> {
+    let Placement {
+      executor,
+      shop_id,
+      payer,
+      lines,
+      pickup,
+      pickup_at,
+      pay_now,
+      metadata,
+    } = placement;
+
+    if lines.is_empty() {
+      return Err(AppError::Validation(
+        "An order needs at least one item".to_string(),
+      ));
+    }
+    if pay_now && matches!(payer, Payer::Guest(_)) {
+      self
+        .feature_switch_service
+        .require(Feature::GuestCharges)
+        .await?;
+    }
+
+    let mut tx = self.pool.begin().await?;
+
+    let shop = match pickup_at {
+      // Serializes bookings so slots don't overfill
+      Some(_) => ShopStore::find_by_id_for_update(&mut *tx, &shop_id).await?,
+      None => ShopStore::find_by_id(&mut *tx, &shop_id).await?,
+    }
+    .ok_or(AppError::NotFound)?;
+
+    if let Some(at) = pickup_at {
+      let slots = shop
+        .pickup_slots
+        .ok_or_else(|| AppError::BadRequest("This shop does not take pre-orders".to_string()))?;
+
+      if at <= Utc::now() {
+        return Err(AppError::Validation(
+          "Pickup time must be in the future".to_string(),
+        ));
+      }
+      if !slots.is_slot_start(at) {
+        return Err(AppError::Validation(format!(
+          "Pickup time must be the start of a {} minute slot",
+          slots.length.num_minutes()
+        )));
+      }
+
+      let booked = OrderStore::count_by_pickup_at(&mut *tx, &shop.id, at, at + slots.length)
+        .await?
+        .iter()
+        .map(|(_, count)| count)
+        .sum::<i64>();
+      if booked >= slots.capacity.into() {
+        return Err(AppError::PickupSlotFull);
+      }
+    }
+
+    let mut priced = Vec::with_capacity(lines.len());
+    let mut total = Money::ZERO;
+    for line in lines {
+      if line.quantity <= 0 {
+        return Err(AppError::Validation(
+          "Quantities must be positive".to_string(),
+        ));
+      }
+
+      let offering = ShopOfferingStore::find_by_id(&mut *tx, &line.offering_id)
+        .await?
+        .filter(|offering| offering.shop_id == shop.id)
+        .ok_or_else(|| {
+          AppError::BadRequest(format!(
+            "Offering {} is not sold by this shop",
+            line.offering_id
+          ))
+        })?;
+
+      total = offering
+        .price_cents
+        .checked_mul(line.quantity)
+        .and_then(|subtotal| total.checked_add(subtotal))
+        .ok_or_else(|| AppError::Validation("Order total is too large".to_string()))?;
+
+      priced.push((offering, line.quantity));
+    }
+
+    let wallet = find_payer_wallet(&mut tx, &payer).await?;
+    let transaction = if pay_now {
+      Some(charge(&mut tx, &wallet, &shop, total, executor, metadata).await?)
+    } else {
+      None
+    };
+
+    let order = OrderStore::create(
+      &mut *tx,
+      &OrderCreation {
+        shop_id: shop.id,
+        payer_wallet_id: wallet.id,
+        transaction_id: transaction.as_ref().map(|t| t.id),
+        executor: Some(executor),
+        total,
+        table_label: pickup.table_label,
+        pickup_number: pickup.pickup_number,
+        pickup_at,
+      },
+    )
+    .await?;
+
+    let mut items = Vec::with_capacity(priced.len());
+    for (offering, quantity) in priced {
+      let item = OrderItemStore::create(
+        &mut *tx,
+        &OrderItemCreation {
+          order_id: order.id,
+          offering_id: Some(offering.id),
+          name: offering.name,
+          unit_price: offering.price_cents,
+          quantity,
+        },
+      )
+      .await?;
+      items.push(item);
+    }
+
+    let mut data = OrderEventData::new(&order, &items);
+    if let Some(transaction) = &transaction {
+      data.transaction_metadata = transaction.metadata.clone();
+    }
+    emit(&mut tx, DomainEvent::OrderCreated(data)).await?;
+
+    tx.commit().await?;
+
+    if let Some(transaction) = transaction {
+      self.publish_paid(&order, &transaction);
+      self.assess_payer(wallet.id).await;
+    }
+
+    Ok((order, items))
+  }
+
+  /// Puts the items of a past order back into a cart at today's prices,
+  /// flagging what the shop no longer sells. Nothing is charged; the cart is
+  /// meant to be checked out as a new order.
+  pub async fn reorder(&self, order: &Order) -> AppResult<Vec<ReorderLine>> {
+    let items = OrderItemStore::list_by_order_id(&self.pool, &order.id).await?;
+
+    let mut lines = Vec::with_capacity(items.len());
+    for item in items {
+      let current = match item.offering_id {
+        Some(offering_id) => ShopOfferingStore::find_by_id(&self.pool, &offering_id)
+          .await?
+          .filter(|offering| offering.shop_id == order.shop_id),
+        None => None,
+      };
+
+      lines.push(ReorderLine {
+        previous: item,
+        current,
+      });
+    }
+
+    Ok(lines)
+  }
+
+  /// Pickup slots of the shop starting within `[from, to)` with the number of
+  /// pre-orders each can still take.
+  pub async fn available_slots(
+    &self,
+    shop: &Shop,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+  ) -> AppResult<Vec<(DateTime<Utc>, i64)>> {
+    let Some(slots) = shop.pickup_slots else {
+      return Ok(Vec::new());
+    };
+
+    let booked: HashMap<_, _> = OrderStore::count_by_pickup_at(&self.pool, &shop.id, from, to)
+      .await?
+      .into_iter()
+      .collect();
+
+    Ok(
+      slots
+        .starts_between(from, to)
+        .into_iter()
+        .map(|at| {
+          let taken = booked.get(&at).copied().unwrap_or(0);
+          (at, (i64::from(slots.capacity) - taken).max(0))
+        })
+        .collect(),
+    )
+  }
+
+  /// Charges the payer of a pre-order that is paid at pickup.
+  pub async fn pay(&self, executor: ActorId, id: OrderId) -> AppResult<(Order, Vec<OrderItem>)> {
+    let mut tx = self.pool.begin().await?;
+
+    let order = OrderStore::find_by_id_for_update(&mut *tx, &id)
+      .await?
+      .ok_or(AppError::NotFound)?;
+    if order.transaction_id.is_some() {
+      return Err(AppError::OrderAlreadyPaid);
+    }
+
+    let shop = ShopStore::find_by_id(&mut *tx, &order.shop_id)
+      .await?
+      .ok_or(AppError::NotFound)?;
+    let wallet = WalletStore::find_by_id(&mut *tx, &order.payer_wallet_id)
+      .await?
+      .ok_or(AppError::NotFound)?;
+    if let Some(owner) = &wallet.owner {
+      if GuestStore::find_by_actor_id(&mut *tx, owner)
+        .await?
+        .is_some()
+      {
+        self
+          .feature_switch_service
+          .require(Feature::GuestCharges)
+          .await?;
+      }
+    }
+
+    let transaction = charge(
+      &mut tx,
+      &wallet,
+      &shop,
+      order.total,
+      executor,
+      TransactionMetadata::default(),
+    )
+    .await?;
+    let order = OrderStore::set_transaction_id(&mut *tx, &order.id, &transaction.id)
+      .await?
+      .ok_or(AppError::OrderAlreadyPaid)?;
+
+    tx.commit().await?;
+
+    self.publish_paid(&order, &transaction);
+    self.assess_payer(wallet.id).await;
+
+    let items = OrderItemStore::list_by_order_id(&self.pool, &order.id).await?;
+
+    Ok((order, items))
+  }
+
+  /// Queues pre-orders whose pickup slot is coming up and tells the shops
+  /// to start preparing them. Run periodically by the scheduler.
+  pub async fn release_due_preorders(&self) -> AppResult<usize> {
+    let mut tx = self.pool.begin().await?;
+
+    let released = OrderStore::release_due(&mut *tx, Utc::now()).await?;
+
+    for order in &released {
+      let items = OrderItemStore::list_by_order_id(&mut *tx, &order.id).await?;
+      emit(
+        &mut tx,
+        DomainEvent::OrderDue(OrderEventData::new(order, &items)),
+      )
+      .await?;
+    }
+
+    tx.commit().await?;
+
+    Ok(released.len())
+  }
+
+  /// Today's running figures of the shop, as a starting point for
+  /// [`LiveShopStats::apply`].
+  pub async fn live_stats(&self, shop_id: ShopId) -> AppResult<LiveShopStats> {
+    let now = Utc::now();
+    let midnight = self.timezone.start_of_day(self.timezone.day_of(now));
+
+    let totals = SalesReportStore::totals(&self.pool, &shop_id, Some(midnight), None).await?;
+    let recent =
+      OrderStore::list_paid_at_since(&self.pool, &shop_id, now - chrono::Duration::minutes(1))
+        .await?;
+
+    Ok(LiveShopStats::new(
+      now,
+      self.timezone,
+      totals.order_count,
+      totals.gross_cents - totals.refunded_cents,
+      recent,
+    ))
+  }
+
+  fn publish_paid(&self, order: &Order, transaction: &Transaction) {
+    self.events.publish(OrderEvent::Paid {
+      order_id: order.id,
+      shop_id: order.shop_id,
+      amount: transaction.amount,
+      at: transaction.created_at,
+    });
+  }
+
+  async fn assess_payer(&self, wallet_id: WalletId) {
+    if let Err(e) = self.risk_service.assess_wallet(wallet_id).await {
+      tracing::warn!(
+        "Failed to assess wallet {} after checkout: {}",
+        wallet_id,
+        e
+      );
+    }
+  }
+}
+
+/// Moves `total` from the payer's wallet into the shop's, refusing overdrafts.
+async fn charge(
+  conn: &mut PgConnection,
+  wallet: &Wallet,
+  shop: &Shop,
+  total: Money,
+  executor: ActorId,
+  metadata: TransactionMetadata,
+) -> AppResult<Transaction> {
+  let (wallet, _) =
+    WalletStore::find_pair_for_update(&mut *conn, &wallet.id, &shop.wallet_id).await?;
+  let wallet = wallet.ok_or(AppError::NotFound)?;
+
+  let balance = WalletStore::find_balance(&mut *conn, &wallet.id).await?;
+  if !wallet.can_send(balance, total) {
+    return Err(AppError::InsufficientFunds);
+  }
+
+  let transaction = TransactionStore::create(
+    &mut *conn,
+    &TransactionCreation {
+      source: wallet.id,
+      destination: shop.wallet_id,
+      executor: Some(executor),
+      amount: total,
+      description: Some(format!("Order at {}", shop.name)),
+      reversal_of: None,
+      metadata,
+    },
+  )
+  .await?;
+  emit(
+    &mut *conn,
+    DomainEvent::TransactionCreated((&transaction).into()),
+  )
+  .await?;
+
+  Ok(transaction)
+}
+
+/// The wallet of the guest bound to the identifier or of the user paying,
+/// not found if either has none.
+async fn find_payer_wallet(conn: &mut PgConnection, payer: &Payer) -> AppResult<Wallet> {
+  let actor = match payer {
+    Payer::Guest(identifier) => {
+      GuestStore::find_by_identifier(&mut *conn, identifier)
+        .await?
+        .ok_or(AppError::NotFound)?
+        .actor_id
+    }
+    Payer::User(user_id) => {
+      UserStore::find_by_id(&mut *conn, user_id)
+        .await?
+        .ok_or(AppError::NotFound)?
+        .actor_id
+    }
+  };
+
+  WalletStore::find_by_owner(&mut *conn, &actor)
+    .await?
+    .ok_or(AppError::NotFound)
+}
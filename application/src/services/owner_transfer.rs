@@ -0,0 +1,201 @@
+use chrono::{Duration, Utc};
+use sqlx::{PgConnection, PgPool};
+
+use crate::{
+  error::{AppError, AppResult},
+  services::session::revoke_all,
+};
+use domain::{OwnerTransfer, OwnerTransferId, OwnerTransferStatus, Role, User, UserId};
+use infra::stores::{
+  models::{OwnerTransferCreation, UserUpdate},
+  OwnerTransferStore, UserStore,
+};
+
+/// How long the new owner has to accept
+const OWNER_TRANSFER_HOURS: i64 = 72;
+
+#[derive(Clone)]
+pub struct OwnerTransferService {
+  pool: PgPool,
+}
+
+impl OwnerTransferService {
+  pub fn new(pool: PgPool) -> Self {
+    Self { pool }
+  }
+
+  /// Offers the Owner role of `from` to another user, with `from` stepping
+  /// down to `demote_to` once it is accepted. Only one transfer can be
+  /// pending at a time.
+  pub async fn offer(
+    &self,
+    from: &User,
+    to_user_id: UserId,
+    demote_to: Role,
+  ) -> AppResult<OwnerTransfer> {
+    if !OwnerTransfer::can_demote_to(demote_to) {
+      return Err(AppError::BadRequest(format!(
+        "Cannot step down to the role {}",
+        demote_to
+      )));
+    }
+    if to_user_id == from.id {
+      return Err(AppError::BadRequest(
+        "Cannot transfer ownership to yourself".to_string(),
+      ));
+    }
+
+    let mut tx = self.pool.begin().await?;
+
+    // Locked first, so offers and role changes of owners take turns
+    let owners = UserStore::lock_ids_by_role(&mut *tx, Role::Owner).await?;
+    if !owners.contains(&from.id) {
+      return Err(AppError::Authorization);
+    }
+
+    let to = UserStore::find_by_id(&mut *tx, &to_user_id)
+      .await?
+      .ok_or(AppError::NotFound)?;
+    if to.role == Role::Owner {
+      return Err(AppError::BadRequest("User is already an owner".to_string()));
+    }
+
+    let now = Utc::now();
+    if let Some(pending) = OwnerTransferStore::find_pending(&mut *tx).await? {
+      if pending.is_open() {
+        return Err(AppError::OwnerTransferPending {
+          expires_at: pending.expires_at,
+        });
+      }
+      OwnerTransferStore::close(&mut *tx, &pending.id, OwnerTransferStatus::Expired, now).await?;
+    }
+
+    let transfer = OwnerTransferStore::create(
+      &mut *tx,
+      &OwnerTransferCreation {
+        from_user_id: from.id,
+        to_user_id,
+        demote_to,
+        expires_at: now + Duration::hours(OWNER_TRANSFER_HOURS),
+      },
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(transfer)
+  }
+
+  /// The transfer waiting to be accepted, if the user offered it or is
+  /// offered it.
+  pub async fn pending_for(&self, user_id: UserId) -> AppResult<Option<OwnerTransfer>> {
+    let pending = OwnerTransferStore::find_pending(&self.pool).await?;
+
+    Ok(pending.filter(|transfer| {
+      transfer.is_open() && (transfer.from_user_id == user_id || transfer.to_user_id == user_id)
+    }))
+  }
+
+  /// Makes the user offered the transfer an owner and steps the offering
+  /// owner down, at once. Both are signed out everywhere, so their sessions
+  /// pick up the new roles.
+  pub async fn accept(&self, user_id: UserId, id: OwnerTransferId) -> AppResult<OwnerTransfer> {
+    let mut tx = self.pool.begin().await?;
+
+    let owners = UserStore::lock_ids_by_role(&mut *tx, Role::Owner).await?;
+    let transfer = OwnerTransferStore::find_by_id_for_update(&mut *tx, &id)
+      .await?
+      .filter(|transfer| transfer.to_user_id == user_id)
+      .ok_or(AppError::NotFound)?;
+
+    if transfer.status != OwnerTransferStatus::Pending {
+      return Err(AppError::OwnerTransferClosed);
+    }
+    // Also when the offering owner has since lost the role themselves
+    if !transfer.is_open() || !owners.contains(&transfer.from_user_id) {
+      OwnerTransferStore::close(&mut *tx, &id, OwnerTransferStatus::Expired, Utc::now()).await?;
+      tx.commit().await?;
+      return Err(AppError::OwnerTransferClosed);
+    }
+
+    // The new owner is promoted before the old one steps down, so there is
+    // an owner at every point
+    set_role(&mut tx, transfer.to_user_id, Role::Owner).await?;
+    set_role(&mut tx, transfer.from_user_id, transfer.demote_to).await?;
+    revoke_all(&mut tx, transfer.to_user_id).await?;
+    revoke_all(&mut tx, transfer.from_user_id).await?;
+
+    let accepted =
+      OwnerTransferStore::close(&mut *tx, &id, OwnerTransferStatus::Accepted, Utc::now())
+        .await?
+        .ok_or(AppError::OwnerTransferClosed)?;
+
+    tx.commit().await?;
+
+    Ok(accepted)
+  }
+
+  /// Turns the transfer down, as the user offered it.
+  pub async fn decline(&self, user_id: UserId, id: OwnerTransferId) -> AppResult<OwnerTransfer> {
+    self
+      .close(id, OwnerTransferStatus::Declined, |transfer| {
+        transfer.to_user_id == user_id
+      })
+      .await
+  }
+
+  /// Withdraws the transfer, as the owner who offered it.
+  pub async fn cancel(&self, user_id: UserId, id: OwnerTransferId) -> AppResult<OwnerTransfer> {
+    self
+      .close(id, OwnerTransferStatus::Cancelled, |transfer| {
+        transfer.from_user_id == user_id
+      })
+      .await
+  }
+
+  /// Closes an open transfer `party` is involved in; others don't see it.
+  async fn close(
+    &self,
+    id: OwnerTransferId,
+    status: OwnerTransferStatus,
+    party: impl Fn(&OwnerTransfer) -> bool,
+  ) -> AppResult<OwnerTransfer> {
+    let mut tx = self.pool.begin().await?;
+
+    let transfer = OwnerTransferStore::find_by_id_for_update(&mut *tx, &id)
+      .await?
+      .filter(|transfer| party(transfer))
+      .ok_or(AppError::NotFound)?;
+
+    if !transfer.is_open() {
+      return Err(AppError::OwnerTransferClosed);
+    }
+
+    let closed = OwnerTransferStore::close(&mut *tx, &id, status, Utc::now())
+      .await?
+      .ok_or(AppError::OwnerTransferClosed)?;
+
+    tx.commit().await?;
+
+    Ok(closed)
+  }
+}
+
+async fn set_role(conn: &mut PgConnection, id: UserId, role: Role) -> AppResult<()> {
+  UserStore::update_by_id(
+    &mut *conn,
+    &id,
+    &UserUpdate {
+      email: None,
+      password: None,
+      first_name: None,
+      last_name: None,
+      role: Some(role),
+      locale: None,
+    },
+  )
+  .await?
+  .ok_or(AppError::NotFound)?;
+
+  Ok(())
+}
@@ -0,0 +1,274 @@
+use sqlx::{PgConnection, PgPool};
+
+use crate::{
+  error::{AppError, AppResult},
+  services::{domain_event::emit, RiskService},
+};
+use domain::{
+  types::Money, ActorId, Currency, DomainEvent, Iban, Payout, PayoutBatch, PayoutBatchId, PayoutId,
+  PayoutStatus, SepaDebtor, TransactionMetadata, Wallet, WalletId, WalletLabel,
+};
+use infra::stores::{
+  models::{BankAccountCreation, PayoutCreation, TransactionCreation},
+  BankAccountStore, PayoutBatchStore, PayoutStore, TransactionStore, WalletStore,
+};
+
+/// The account a payout goes to, as given with the request.
+#[derive(Debug, Clone)]
+pub struct PayoutDestination {
+  pub iban: Iban,
+  pub holder_name: String,
+}
+
+/// Withdrawals to external bank accounts, handed to the bank in SEPA batches.
+#[derive(Clone)]
+pub struct PayoutService {
+  pool: PgPool,
+  risk_service: RiskService,
+  /// Unset when no account to pay out from is configured
+  debtor: Option<SepaDebtor>,
+}
+
+impl PayoutService {
+  pub fn new(pool: PgPool, risk_service: RiskService, debtor: Option<SepaDebtor>) -> Self {
+    Self {
+      pool,
+      risk_service,
+      debtor,
+    }
+  }
+
+  /// Queues a payout from `wallet_id`, taking the amount out of the wallet
+  /// right away.
+  ///
+  /// A destination given by the wallet's owner is remembered as their bank
+  /// account; one given by staff is used for this payout only. Without one,
+  /// the account on file is used.
+  pub async fn request(
+    &self,
+    requested_by: ActorId,
+    wallet_id: WalletId,
+    amount: Money,
+    destination: Option<PayoutDestination>,
+  ) -> AppResult<Payout> {
+    self.debtor.as_ref().ok_or(AppError::SepaDisabled)?;
+
+    if !amount.is_positive() {
+      return Err(AppError::Validation(
+        "Payout amount must be positive".to_string(),
+      ));
+    }
+
+    let mut tx = self.pool.begin().await?;
+
+    let clearing = find_payouts_wallet(&mut tx).await?;
+
+    let (wallet, _) = WalletStore::find_pair_for_update(&mut *tx, &wallet_id, &clearing.id).await?;
+    let wallet = wallet.ok_or(AppError::NotFound)?;
+
+    if wallet.label.is_some() {
+      return Err(AppError::BadRequest(
+        "System wallets cannot be paid out".to_string(),
+      ));
+    }
+    if wallet.currency != Currency::EUR {
+      return Err(AppError::BadRequest(
+        "Bank payouts are only made from euro wallets".to_string(),
+      ));
+    }
+
+    let destination = match (destination, wallet.owner) {
+      (Some(destination), Some(owner)) if owner == requested_by => {
+        BankAccountStore::upsert(
+          &mut *tx,
+          &BankAccountCreation {
+            actor_id: owner,
+            iban: destination.iban.clone(),
+            holder_name: destination.holder_name.clone(),
+          },
+        )
+        .await?;
+        destination
+      }
+      (Some(destination), _) => destination,
+      (None, owner) => {
+        let account = match owner {
+          Some(owner) => BankAccountStore::find_by_actor_id(&mut *tx, &owner).await?,
+          None => None,
+        };
+        let account = account.ok_or_else(|| {
+          AppError::Validation("No bank account on file; an IBAN is required".to_string())
+        })?;
+        PayoutDestination {
+          iban: account.iban,
+          holder_name: account.holder_name,
+        }
+      }
+    };
+
+    let balance = WalletStore::find_balance(&mut *tx, &wallet.id).await?;
+    if !wallet.can_pay_out(balance, amount) {
+      return Err(AppError::InsufficientFunds);
+    }
+
+    let transaction = TransactionStore::create(
+      &mut *tx,
+      &TransactionCreation {
+        source: wallet.id,
+        destination: clearing.id,
+        executor: Some(requested_by),
+        amount,
+        description: Some(format!("Bank payout to {}", destination.iban.masked())),
+        reversal_of: None,
+        metadata: TransactionMetadata::default(),
+      },
+    )
+    .await?;
+    emit(
+      &mut tx,
+      DomainEvent::TransactionCreated((&transaction).into()),
+    )
+    .await?;
+
+    let payout = PayoutStore::create(
+      &mut *tx,
+      &PayoutCreation {
+        wallet_id: wallet.id,
+        amount,
+        iban: destination.iban,
+        holder_name: destination.holder_name,
+        transaction_id: transaction.id,
+        requested_by: Some(requested_by),
+      },
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    if let Err(e) = self.risk_service.assess_wallet(wallet_id).await {
+      tracing::warn!(
+        "Failed to assess wallet {} after payout request: {}",
+        wallet_id,
+        e
+      );
+    }
+
+    Ok(payout)
+  }
+
+  pub async fn list(&self, status: Option<PayoutStatus>) -> AppResult<Vec<Payout>> {
+    Ok(PayoutStore::list(&self.pool, status).await?)
+  }
+
+  /// Puts every requested payout into a new batch and returns it with its
+  /// SEPA file. Fails with [`AppError::BadRequest`] if nothing is queued.
+  pub async fn export_batch(&self, created_by: ActorId) -> AppResult<(PayoutBatch, String)> {
+    let debtor = self.debtor.as_ref().ok_or(AppError::SepaDisabled)?;
+
+    let mut tx = self.pool.begin().await?;
+
+    let batch = PayoutBatchStore::create(&mut *tx, Some(created_by)).await?;
+    let payouts = PayoutStore::export_requested(&mut *tx, &batch.id).await?;
+    if payouts.is_empty() {
+      return Err(AppError::BadRequest("No payouts are waiting".to_string()));
+    }
+
+    tx.commit().await?;
+
+    let document = batch.to_pain001(debtor, &payouts);
+    Ok((batch, document))
+  }
+
+  /// The SEPA file of an earlier batch, e.g. when the first download got lost.
+  pub async fn get_batch_document(&self, batch_id: PayoutBatchId) -> AppResult<String> {
+    let debtor = self.debtor.as_ref().ok_or(AppError::SepaDisabled)?;
+
+    let batch = PayoutBatchStore::find_by_id(&self.pool, &batch_id)
+      .await?
+      .ok_or(AppError::NotFound)?;
+    let payouts = PayoutStore::list_by_batch_id(&self.pool, &batch.id).await?;
+
+    Ok(batch.to_pain001(debtor, &payouts))
+  }
+
+  /// Records that the bank carried out the transfer.
+  pub async fn settle(&self, payout_id: PayoutId) -> AppResult<Payout> {
+    let mut tx = self.pool.begin().await?;
+
+    let payout = find_for_transition(&mut tx, payout_id, PayoutStatus::Settled).await?;
+    let payout = PayoutStore::set_status(&mut *tx, &payout.id, PayoutStatus::Settled, None)
+      .await?
+      .ok_or(AppError::NotFound)?;
+
+    tx.commit().await?;
+
+    Ok(payout)
+  }
+
+  /// Gives up on a payout, e.g. after the bank bounced it, and books the
+  /// amount back into the wallet.
+  pub async fn reject(&self, executor: ActorId, payout_id: PayoutId) -> AppResult<Payout> {
+    let mut tx = self.pool.begin().await?;
+
+    let payout = find_for_transition(&mut tx, payout_id, PayoutStatus::Rejected).await?;
+    let clearing = find_payouts_wallet(&mut tx).await?;
+
+    let refund = TransactionStore::create(
+      &mut *tx,
+      &TransactionCreation {
+        source: clearing.id,
+        destination: payout.wallet_id,
+        executor: Some(executor),
+        amount: payout.amount,
+        description: Some("Bank payout rejected".to_string()),
+        reversal_of: Some(payout.transaction_id),
+        metadata: TransactionMetadata::default(),
+      },
+    )
+    .await?;
+    emit(&mut tx, DomainEvent::TransactionCreated((&refund).into())).await?;
+
+    let payout = PayoutStore::set_status(
+      &mut *tx,
+      &payout.id,
+      PayoutStatus::Rejected,
+      Some(refund.id),
+    )
+    .await?
+    .ok_or(AppError::NotFound)?;
+
+    tx.commit().await?;
+
+    Ok(payout)
+  }
+}
+
+async fn find_for_transition(
+  conn: &mut PgConnection,
+  payout_id: PayoutId,
+  next: PayoutStatus,
+) -> AppResult<Payout> {
+  let payout = PayoutStore::find_by_id_for_update(&mut *conn, &payout_id)
+    .await?
+    .ok_or(AppError::NotFound)?;
+
+  if !payout.status.can_transition_to(next) {
+    return Err(AppError::InvalidPayoutTransition {
+      from: payout.status,
+      to: next,
+    });
+  }
+
+  Ok(payout)
+}
+
+/// The system wallet requested payouts wait in. SEPA transfers are made in
+/// euros only, so there is just the one.
+async fn find_payouts_wallet(conn: &mut PgConnection) -> AppResult<Wallet> {
+  WalletStore::find_by_label(&mut *conn, &WalletLabel::BankPayouts, Currency::EUR)
+    .await?
+    .ok_or_else(|| {
+      tracing::error!("System wallet {} is missing", WalletLabel::BankPayouts);
+      AppError::InternalServerError
+    })
+}
@@ -0,0 +1,66 @@
+use chrono::Utc;
+use sqlx::PgPool;
+
+use crate::error::AppResult;
+use domain::{RetentionOutcome, RetentionPolicy, RetentionRule};
+use infra::stores::{
+  AuditStore, GuestStore, InviteStore, LoginAttemptStore, LoginLockoutStore,
+  SessionRevocationStore, SessionStore,
+};
+
+/// Deletes or anonymizes data once it is older than the policy allows.
+#[derive(Clone)]
+pub struct RetentionService {
+  pool: PgPool,
+  policy: RetentionPolicy,
+}
+
+impl RetentionService {
+  pub fn new(pool: PgPool, policy: RetentionPolicy) -> Self {
+    Self { pool, policy }
+  }
+
+  /// Applies every enabled rule. With `dry_run`, the changes are rolled back
+  /// and only reported.
+  pub async fn apply(&self, dry_run: bool) -> AppResult<Vec<RetentionOutcome>> {
+    let mut tx = self.pool.begin().await?;
+    let mut outcomes = Vec::new();
+
+    for (rule, retention_days, cutoff) in self.policy.cutoffs(Utc::now()) {
+      let affected = match rule {
+        RetentionRule::ExpiredSessions => {
+          SessionStore::delete_expired_before(&mut *tx, cutoff).await?
+            + SessionRevocationStore::delete_expired_before(&mut *tx, cutoff).await?
+        }
+        RetentionRule::ExpiredInvites => {
+          InviteStore::delete_expired_before(&mut *tx, cutoff).await?
+        }
+        RetentionRule::LoginAttempts => {
+          LoginAttemptStore::delete_before(&mut *tx, cutoff).await?
+            + LoginLockoutStore::delete_expired_before(&mut *tx, cutoff).await?
+        }
+        RetentionRule::InactiveGuests => {
+          GuestStore::anonymize_inactive_since(&mut *tx, cutoff).await?
+        }
+        RetentionRule::AuditIpAddresses => {
+          AuditStore::clear_ip_addresses_before(&mut *tx, cutoff).await?
+        }
+      };
+
+      outcomes.push(RetentionOutcome {
+        rule,
+        retention_days,
+        cutoff,
+        affected,
+      });
+    }
+
+    if dry_run {
+      tx.rollback().await?;
+    } else {
+      tx.commit().await?;
+    }
+
+    Ok(outcomes)
+  }
+}
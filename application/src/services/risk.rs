@@ -0,0 +1,232 @@
+use chrono::Utc;
+use sqlx::PgPool;
+
+use crate::{
+  error::{AppError, AppResult},
+  services::domain_event::emit,
+};
+use domain::{
+  ActorId, DomainEvent, ReviewItem, ReviewItemId, ReviewStatus, RiskSignal, RiskThresholds,
+  WalletId, WalletSecurityEventKind,
+};
+use infra::stores::{
+  models::{ReviewItemCreation, ReviewItemResolution},
+  ReviewItemStore, TransactionStore, WalletSecurityEventStore, WalletStore,
+};
+
+/// Flags suspicious wallet activity for review by admins.
+///
+/// Money-moving and card/PIN flows call [`RiskService::assess_wallet`] (or one
+/// of the `record_*` helpers) after the fact; each heuristic that fires opens
+/// at most one review item per wallet until an admin resolves it.
+#[derive(Clone)]
+pub struct RiskService {
+  pool: PgPool,
+  thresholds: RiskThresholds,
+  auto_freeze: bool,
+}
+
+impl RiskService {
+  pub fn new(pool: PgPool, thresholds: RiskThresholds, auto_freeze: bool) -> Self {
+    Self {
+      pool,
+      thresholds,
+      auto_freeze,
+    }
+  }
+
+  pub async fn record_card_bound(&self, wallet_id: WalletId) -> AppResult<Vec<ReviewItem>> {
+    WalletSecurityEventStore::create(&self.pool, &wallet_id, &WalletSecurityEventKind::CardBound)
+      .await?;
+    self.assess_wallet(wallet_id).await
+  }
+
+  pub async fn record_failed_pin(&self, wallet_id: WalletId) -> AppResult<Vec<ReviewItem>> {
+    WalletSecurityEventStore::create(&self.pool, &wallet_id, &WalletSecurityEventKind::PinFailed)
+      .await?;
+    self.assess_wallet(wallet_id).await
+  }
+
+  /// Runs every heuristic against the wallet and returns newly opened review items.
+  pub async fn assess_wallet(&self, wallet_id: WalletId) -> AppResult<Vec<ReviewItem>> {
+    let signals = self.detect_signals(wallet_id).await?;
+    let mut opened = Vec::new();
+
+    for (signal, details) in signals {
+      let mut tx = self.pool.begin().await?;
+
+      let Some(item) = ReviewItemStore::create(
+        &mut *tx,
+        &ReviewItemCreation {
+          wallet_id,
+          signal,
+          details,
+          wallet_frozen: self.auto_freeze,
+        },
+      )
+      .await?
+      else {
+        continue;
+      };
+
+      if self.auto_freeze {
+        WalletStore::set_frozen(&mut *tx, &wallet_id, true).await?;
+        emit(
+          &mut tx,
+          DomainEvent::WalletFrozen {
+            wallet_id,
+            review_item_id: item.id,
+            signal: item.signal,
+          },
+        )
+        .await?;
+      }
+
+      tx.commit().await?;
+
+      tracing::warn!(
+        "Suspicious activity ({}) flagged on wallet {}",
+        signal,
+        wallet_id
+      );
+      opened.push(item);
+    }
+
+    Ok(opened)
+  }
+
+  pub async fn list_reviews(&self, status: ReviewStatus) -> AppResult<Vec<ReviewItem>> {
+    Ok(ReviewItemStore::list_by_status(&self.pool, &status).await?)
+  }
+
+  pub async fn resolve_review(
+    &self,
+    id: ReviewItemId,
+    resolved_by: ActorId,
+    status: ReviewStatus,
+    note: Option<String>,
+    unfreeze_wallet: bool,
+  ) -> AppResult<ReviewItem> {
+    if status == ReviewStatus::Open {
+      return Err(AppError::BadRequest(
+        "A review can only be resolved as dismissed or confirmed".to_string(),
+      ));
+    }
+
+    let mut tx = self.pool.begin().await?;
+
+    let item = ReviewItemStore::find_by_id(&mut *tx, &id)
+      .await?
+      .ok_or(AppError::NotFound)?;
+
+    if item.status != ReviewStatus::Open {
+      return Err(AppError::BadRequest(format!(
+        "Review is already {}",
+        item.status
+      )));
+    }
+
+    let item = ReviewItemStore::resolve_by_id(
+      &mut *tx,
+      &id,
+      &ReviewItemResolution {
+        status,
+        resolved_by,
+        note,
+      },
+    )
+    .await?
+    .ok_or(AppError::NotFound)?;
+
+    if unfreeze_wallet {
+      let was_frozen = WalletStore::find_by_id(&mut *tx, &item.wallet_id)
+        .await?
+        .is_some_and(|wallet| wallet.frozen);
+      WalletStore::set_frozen(&mut *tx, &item.wallet_id, false).await?;
+
+      if was_frozen {
+        emit(
+          &mut tx,
+          DomainEvent::WalletUnfrozen {
+            wallet_id: item.wallet_id,
+            review_item_id: item.id,
+            resolved_by,
+          },
+        )
+        .await?;
+      }
+    }
+
+    tx.commit().await?;
+
+    Ok(item)
+  }
+
+  async fn detect_signals(&self, wallet_id: WalletId) -> AppResult<Vec<(RiskSignal, String)>> {
+    let now = Utc::now();
+    let t = &self.thresholds;
+    let mut signals = Vec::new();
+
+    let charges = TransactionStore::list_outgoing_times_since(
+      &self.pool,
+      &wallet_id,
+      now - t.rapid_charge_window,
+    )
+    .await?;
+    if t.is_rapid_charging(&charges, now) {
+      signals.push((
+        RiskSignal::RapidRepeatedCharges,
+        format!(
+          "{} outgoing transactions within {} minutes",
+          charges.len(),
+          t.rapid_charge_window.num_minutes()
+        ),
+      ));
+    }
+
+    let bindings = WalletSecurityEventStore::list_times_since(
+      &self.pool,
+      &wallet_id,
+      &WalletSecurityEventKind::CardBound,
+      now - t.drain_window,
+    )
+    .await?;
+    if let Some(bound_at) = bindings.first().copied() {
+      let balance =
+        TransactionStore::calculate_wallet_balance_at(&self.pool, &wallet_id, bound_at).await?;
+      let outgoing = TransactionStore::sum_outgoing_since(&self.pool, &wallet_id, bound_at).await?;
+
+      if t.is_drained_after_binding(bound_at, balance, outgoing, now) {
+        signals.push((
+          RiskSignal::DrainedAfterCardBinding,
+          format!(
+            "{} of {} spent within {} minutes of binding a card",
+            outgoing,
+            balance,
+            (now - bound_at).num_minutes()
+          ),
+        ));
+      }
+    }
+
+    let failures = WalletSecurityEventStore::list_times_since(
+      &self.pool,
+      &wallet_id,
+      &WalletSecurityEventKind::PinFailed,
+      now - t.failed_pin_window,
+    )
+    .await?;
+    if t.has_repeated_failed_pins(&failures, now) {
+      signals.push((
+        RiskSignal::RepeatedFailedPinAttempts,
+        format!(
+          "{} failed PIN attempts within {} minutes",
+          failures.len(),
+          t.failed_pin_window.num_minutes()
+        ),
+      ));
+    }
+
+    Ok(signals)
+  }
+}
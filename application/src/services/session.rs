@@ -1,34 +1,354 @@
-use chrono::Duration;
-use infra::stores::{models::SessionCreation, SessionStore};
-use sqlx::PgPool;
+use std::{
+  collections::HashMap,
+  sync::{Arc, Mutex},
+  time::Instant,
+};
+
+use chrono::{DateTime, Duration, Utc};
+use infra::{
+  services::SessionTokenSigner,
+  stores::{
+    models::{PasswordConfirmationCreation, SessionCreation},
+    PasswordConfirmationStore, SessionRevocationStore, SessionStore,
+  },
+};
+use sqlx::{PgConnection, PgPool};
+use tokio::sync::broadcast::{self, error::TryRecvError};
 use uuid::Uuid;
 
-use crate::error::AppResult;
-use domain::{Session, UserId};
+use crate::{
+  error::{AppError, AppResult},
+  events::EventBus,
+  services::{domain_event::emit, user::load_principal},
+};
+use domain::{
+  DeviceNonce, DevicePublicKey, DomainEvent, PasswordConfirmation, Principal, Session,
+  SessionClaims, SessionId, SessionRevocation, SessionTimeouts, UserId,
+};
+
+const PASSWORD_CONFIRMATION_MINUTES: i64 = 5;
+/// Nonces are signed right before the request they are for
+const DEVICE_NONCE_SECONDS: i64 = 60;
+/// How long other instances may take to reject a revoked signed token
+const REVOCATION_SYNC_SECONDS: i64 = 5;
+/// Overlap between syncs, for revocations committed after they were made
+const REVOCATION_SYNC_OVERLAP_SECONDS: i64 = 60;
+
+/// Signs the user out everywhere on the caller's transaction, so a cookie
+/// issued under old permissions is of no further use. Their open streams end
+/// once it commits.
+pub(crate) async fn revoke_all(conn: &mut PgConnection, user_id: UserId) -> AppResult<()> {
+  SessionStore::delete_by_user_id(&mut *conn, &user_id).await?;
+  emit(&mut *conn, DomainEvent::SessionsRevoked { user_id }).await?;
+
+  Ok(())
+}
+
+/// Revocations of signed tokens as this instance knows them.
+#[derive(Default)]
+struct Revocations {
+  by_session: HashMap<SessionId, SessionRevocation>,
+  synced_at: Option<DateTime<Utc>>,
+}
+
+/// Principals of recently used tokens, so a burst of requests doesn't look
+/// up the same session and user over and over.
+struct Principals {
+  ttl: std::time::Duration,
+  by_token: HashMap<String, CachedPrincipal>,
+  /// When users were signed out everywhere, for lookups already under way
+  signed_out: HashMap<UserId, Instant>,
+  /// When sessions ended on this instance, likewise
+  ended: HashMap<SessionId, Instant>,
+  /// Everything looked up before this may be outdated
+  cleared_at: Option<Instant>,
+  pruned_at: Instant,
+  logouts: broadcast::Receiver<UserId>,
+}
+
+struct CachedPrincipal {
+  session_id: SessionId,
+  principal: Principal,
+  /// When the lookup started
+  looked_up_at: Instant,
+}
+
+impl Principals {
+  fn new(ttl: std::time::Duration, logouts: broadcast::Receiver<UserId>) -> Self {
+    Self {
+      ttl,
+      by_token: HashMap::new(),
+      signed_out: HashMap::new(),
+      ended: HashMap::new(),
+      cleared_at: None,
+      pruned_at: Instant::now(),
+      logouts,
+    }
+  }
+
+  fn get(&mut self, token: &str) -> Option<Principal> {
+    self.catch_up();
+
+    self
+      .by_token
+      .get(token)
+      .filter(|cached| cached.looked_up_at.elapsed() < self.ttl)
+      .map(|cached| cached.principal.clone())
+  }
+
+  /// Remembers a lookup unless the user or session was signed out while it
+  /// ran.
+  fn insert(&mut self, token: &str, cached: CachedPrincipal) {
+    self.catch_up();
+
+    let outdated_since = [
+      self.signed_out.get(&cached.principal.user.id).copied(),
+      self.ended.get(&cached.session_id).copied(),
+      self.cleared_at,
+    ];
+    if outdated_since
+      .into_iter()
+      .flatten()
+      .any(|at| at >= cached.looked_up_at)
+    {
+      return;
+    }
+
+    self.by_token.insert(token.to_string(), cached);
+  }
+
+  fn end_session(&mut self, session_id: SessionId) {
+    self.ended.insert(session_id, Instant::now());
+    self
+      .by_token
+      .retain(|_, cached| cached.session_id != session_id);
+  }
+
+  fn end_token(&mut self, token: &str) {
+    if let Some(cached) = self.by_token.remove(token) {
+      self.end_session(cached.session_id);
+    }
+  }
+
+  /// Forgets the users signed out since the last call and, every so often,
+  /// whatever expired.
+  fn catch_up(&mut self) {
+    loop {
+      match self.logouts.try_recv() {
+        Ok(user_id) => {
+          self.signed_out.insert(user_id, Instant::now());
+          self
+            .by_token
+            .retain(|_, cached| cached.principal.user.id != user_id);
+        }
+        // Some sign-outs were missed, so none of it can be trusted
+        Err(TryRecvError::Lagged(_)) => {
+          self.cleared_at = Some(Instant::now());
+          self.by_token.clear();
+        }
+        Err(TryRecvError::Empty | TryRecvError::Closed) => break,
+      }
+    }
+
+    if self.pruned_at.elapsed() >= self.ttl {
+      let ttl = self.ttl;
+      self.by_token.retain(|_, c| c.looked_up_at.elapsed() < ttl);
+      self.signed_out.retain(|_, at| at.elapsed() < ttl);
+      self.ended.retain(|_, at| at.elapsed() < ttl);
+      self.pruned_at = Instant::now();
+    }
+  }
+}
 
 #[derive(Clone)]
 pub struct SessionService {
   pool: PgPool,
-  expiration_days: i64,
+  timeouts: SessionTimeouts,
+  /// Signs new tokens, if sessions are checked by signature
+  signer: Option<SessionTokenSigner>,
+  revocations: Arc<Mutex<Revocations>>,
+  /// Recent lookups, unless every request looks its user up
+  principals: Option<Arc<Mutex<Principals>>>,
 }
 
 impl SessionService {
-  pub fn new(pool: PgPool, expiration_days: i64) -> Self {
+  /// Remembers who is signed in with a token for `principal_ttl`; zero looks
+  /// them up on every request.
+  pub fn new(
+    pool: PgPool,
+    timeouts: SessionTimeouts,
+    signer: Option<SessionTokenSigner>,
+    principal_ttl: std::time::Duration,
+    event_bus: EventBus,
+  ) -> Self {
+    let principals = (!principal_ttl.is_zero()).then(|| {
+      Arc::new(Mutex::new(Principals::new(
+        principal_ttl,
+        event_bus.subscribe_logouts(),
+      )))
+    });
+
     Self {
       pool,
-      expiration_days,
+      timeouts,
+      signer,
+      revocations: Arc::default(),
+      principals,
     }
   }
 
-  pub async fn create_session(&self, user_id: UserId) -> AppResult<Session> {
-    let token = Uuid::new_v4().to_string();
+  /// The user signed in with `token` and what they may do.
+  ///
+  /// Lookups are remembered for a few seconds. Signing out, on this instance
+  /// or everywhere, forgets them right away; anything else about the user,
+  /// and sessions ended on other instances, may take that long to show.
+  pub async fn principal(&self, token: &str) -> AppResult<Option<Principal>> {
+    if let Some(principals) = &self.principals {
+      let cached = principals.lock().expect("principals lock").get(token);
+      if cached.is_some() {
+        return Ok(cached);
+      }
+    }
+
+    let looked_up_at = Instant::now();
+    let Some((session_id, user_id)) = self.authenticate(token).await? else {
+      return Ok(None);
+    };
+    let Some(principal) = load_principal(&self.pool, user_id).await? else {
+      return Ok(None);
+    };
+
+    if let Some(principals) = &self.principals {
+      principals.lock().expect("principals lock").insert(
+        token,
+        CachedPrincipal {
+          session_id,
+          principal: principal.clone(),
+          looked_up_at,
+        },
+      );
+    }
+
+    Ok(Some(principal))
+  }
+
+  /// The session and user of `token`. Signed tokens are checked without
+  /// looking the session up, so they neither slide its expiry nor record
+  /// its use; database tokens keep working alongside.
+  async fn authenticate(&self, token: &str) -> AppResult<Option<(SessionId, UserId)>> {
+    let Some(signer) = self
+      .signer
+      .as_ref()
+      .filter(|_| SessionTokenSigner::is_signed(token))
+    else {
+      let session = self.get_session(token).await?;
+      return Ok(session.map(|s| (s.id, s.user_id)));
+    };
+
+    let Some(claims) = signer.verify(token).filter(|c| !c.is_expired()) else {
+      return Ok(None);
+    };
+    if self.is_revoked(&claims).await? {
+      return Ok(None);
+    }
+
+    Ok(Some((claims.session_id, claims.user_id)))
+  }
+
+  /// Stops lookups of the session being reused.
+  fn forget_session(&self, session_id: SessionId) {
+    if let Some(principals) = &self.principals {
+      principals
+        .lock()
+        .expect("principals lock")
+        .end_session(session_id);
+    }
+  }
+
+  /// Checks `claims` against the revocations, fetching those made since the
+  /// last check every few seconds.
+  async fn is_revoked(&self, claims: &SessionClaims) -> AppResult<bool> {
+    let now = Utc::now();
+    let synced_at = {
+      let revocations = self.revocations.lock().expect("revocations lock");
+      if revocations
+        .synced_at
+        .is_some_and(|at| now - at < Duration::seconds(REVOCATION_SYNC_SECONDS))
+      {
+        return Ok(revoked(&revocations, claims));
+      }
+      revocations.synced_at
+    };
+
+    let since = synced_at.map(|at| at - Duration::seconds(REVOCATION_SYNC_OVERLAP_SECONDS));
+    let changed = SessionRevocationStore::list_changed_since(&self.pool, since).await?;
+
+    let mut revocations = self.revocations.lock().expect("revocations lock");
+    revocations.by_session.retain(|_, r| r.expires_at > now);
+    for revocation in changed {
+      revocations
+        .by_session
+        .insert(revocation.session_id, revocation);
+    }
+    revocations.synced_at = Some(now);
+
+    Ok(revoked(&revocations, claims))
+  }
+
+  /// Makes this instance reject the revoked tokens right away, rather than
+  /// after its next sync.
+  fn remember_revocation(&self, revocation: SessionRevocation) {
+    let mut revocations = self.revocations.lock().expect("revocations lock");
+    revocations
+      .by_session
+      .insert(revocation.session_id, revocation);
+  }
+
+  /// A new token for the session: signed, or random for the database.
+  fn issue_token(
+    &self,
+    session_id: SessionId,
+    user_id: UserId,
+    issued_at: DateTime<Utc>,
+    expires_at: DateTime<Utc>,
+  ) -> String {
+    match &self.signer {
+      Some(signer) => signer.sign(&SessionClaims {
+        session_id,
+        user_id,
+        issued_at,
+        expires_at,
+      }),
+      None => Uuid::new_v4().to_string(),
+    }
+  }
+
+  /// Latest the session can last until, however often it is renewed.
+  pub fn ends_at(&self, session: &Session) -> DateTime<Utc> {
+    self.timeouts.ends_at(session.created_at)
+  }
+
+  /// Starts a session, bound to the device holding the private half of
+  /// `device_public_key` if given.
+  pub async fn create_session(
+    &self,
+    user_id: UserId,
+    user_agent: Option<String>,
+    ip_address: Option<String>,
+    device_public_key: Option<DevicePublicKey>,
+  ) -> AppResult<Session> {
+    let id = SessionId::from(Uuid::now_v7());
+    let now = token_time(Utc::now());
+    let expires_at = self.timeouts.expires_at(now, now);
 
     let new_session = SessionCreation {
-      user_id: user_id.into(),
-      token,
-      user_agent: None,
-      ip_address: None,
-      expires_in: Duration::days(self.expiration_days),
+      id,
+      user_id,
+      token: self.issue_token(id, user_id, now, expires_at),
+      user_agent,
+      ip_address,
+      device_public_key,
+      expires_in: expires_at - now,
     };
 
     let session = SessionStore::create(&self.pool, &new_session).await?;
@@ -44,13 +364,181 @@ impl SessionService {
         SessionStore::delete_by_token(&self.pool, token).await?;
         return Ok(None);
       }
+
+      SessionStore::touch_by_token(&self.pool, token).await?;
+
+      let now = Utc::now();
+      if self.timeouts.is_due_for_renewal(s, now) {
+        let expires_at = self.timeouts.expires_at(s.created_at, now);
+        SessionStore::extend_by_token(&self.pool, token, expires_at).await?;
+      }
     }
 
     Ok(session)
   }
 
+  /// Swaps the session's token for a new one and pushes its expiry out as
+  /// far as the timeouts allow.
+  pub async fn refresh_session(&self, token: &str) -> AppResult<Session> {
+    let session = SessionStore::find_by_token(&self.pool, token)
+      .await?
+      .ok_or(AppError::Authentication)?;
+
+    let now = token_time(Utc::now());
+    let expires_at = self.timeouts.expires_at(session.created_at, now);
+    let new_token = self.issue_token(session.id, session.user_id, now, expires_at);
+
+    let mut tx = self.pool.begin().await?;
+    let renewed = SessionStore::rotate_token(&mut *tx, token, &new_token, expires_at)
+      .await?
+      .ok_or(AppError::Authentication)?;
+
+    // The old token is signed too and would otherwise stay good
+    let revocation = self.signer.is_some().then_some(SessionRevocation {
+      session_id: session.id,
+      issued_before: Some(now),
+      expires_at: session.expires_at(),
+    });
+    if let Some(revocation) = revocation {
+      SessionRevocationStore::revoke_issued_before(
+        &mut *tx,
+        &revocation.session_id,
+        now,
+        revocation.expires_at,
+      )
+      .await?;
+    }
+    tx.commit().await?;
+
+    if let Some(revocation) = revocation {
+      self.remember_revocation(revocation);
+    }
+    self.forget_session(session.id);
+
+    Ok(renewed)
+  }
+
+  pub async fn list_active(&self, user_id: UserId) -> AppResult<Vec<Session>> {
+    let sessions = SessionStore::list_by_user_id(&self.pool, &user_id).await?;
+
+    Ok(sessions.into_iter().filter(|s| !s.is_expired()).collect())
+  }
+
+  pub async fn revoke_session(&self, user_id: UserId, session_id: SessionId) -> AppResult<()> {
+    let session = SessionStore::find_by_id(&self.pool, &session_id)
+      .await?
+      .filter(|s| s.user_id == user_id)
+      .ok_or(AppError::NotFound)?;
+
+    SessionStore::delete_by_id(&self.pool, &session.id).await?;
+    self.remember_revocation(SessionRevocation {
+      session_id: session.id,
+      issued_before: None,
+      expires_at: session.expires_at(),
+    });
+    self.forget_session(session.id);
+
+    Ok(())
+  }
+
+  /// Issues a step-up token for the session. Callers must have verified the
+  /// password beforehand.
+  pub async fn issue_password_confirmation(
+    &self,
+    session_token: &str,
+  ) -> AppResult<PasswordConfirmation> {
+    let session = SessionStore::find_by_token(&self.pool, session_token)
+      .await?
+      .ok_or(AppError::Authentication)?;
+
+    PasswordConfirmationStore::delete_expired(&self.pool).await?;
+
+    let confirmation = PasswordConfirmationStore::create(
+      &self.pool,
+      &PasswordConfirmationCreation {
+        session_id: session.id,
+        token: Uuid::new_v4().to_string(),
+        expires_in: Duration::minutes(PASSWORD_CONFIRMATION_MINUTES),
+      },
+    )
+    .await?;
+
+    Ok(confirmation)
+  }
+
+  /// Whether `token` is an unexpired step-up token of the given session.
+  pub async fn is_password_confirmed(&self, session_token: &str, token: &str) -> AppResult<bool> {
+    let confirmation =
+      PasswordConfirmationStore::find_by_token_and_session_token(&self.pool, token, session_token)
+        .await?;
+
+    Ok(confirmation.is_some_and(|c| !c.is_expired()))
+  }
+
+  /// Issues a nonce for the device-bound session to sign, replacing any
+  /// earlier one.
+  pub async fn issue_device_nonce(&self, session_token: &str) -> AppResult<DeviceNonce> {
+    let nonce = DeviceNonce {
+      nonce: Uuid::new_v4().simple().to_string(),
+      expires_at: Utc::now() + Duration::seconds(DEVICE_NONCE_SECONDS),
+    };
+
+    if !SessionStore::set_device_nonce(&self.pool, session_token, &nonce.nonce, nonce.expires_at)
+      .await?
+    {
+      return Err(AppError::BadRequest(
+        "Session is not bound to a device".to_string(),
+      ));
+    }
+
+    Ok(nonce)
+  }
+
+  /// Whether `signature` is the session's device signing its current nonce,
+  /// which is used up either way.
+  pub async fn verify_device_signature(
+    &self,
+    session_token: &str,
+    nonce: &str,
+    signature: &str,
+  ) -> AppResult<bool> {
+    let session = SessionStore::take_device_nonce(&self.pool, session_token, nonce).await?;
+
+    Ok(
+      session
+        .and_then(|s| s.device_public_key)
+        .is_some_and(|key| key.verifies(nonce.as_bytes(), signature)),
+    )
+  }
+
   pub async fn end_session(&self, token: &str) -> AppResult<()> {
     SessionStore::delete_by_token(&self.pool, token).await?;
+
+    if let Some(principals) = &self.principals {
+      principals.lock().expect("principals lock").end_token(token);
+    }
+
+    if let Some(claims) = self.signer.as_ref().and_then(|s| s.verify(token)) {
+      self.remember_revocation(SessionRevocation {
+        session_id: claims.session_id,
+        issued_before: None,
+        expires_at: claims.expires_at,
+      });
+      self.forget_session(claims.session_id);
+    }
+
     Ok(())
   }
 }
+
+fn revoked(revocations: &Revocations, claims: &SessionClaims) -> bool {
+  revocations
+    .by_session
+    .get(&claims.session_id)
+    .is_some_and(|r| r.revokes(claims))
+}
+
+/// `time` to the millisecond, as signed tokens carry it.
+fn token_time(time: DateTime<Utc>) -> DateTime<Utc> {
+  DateTime::from_timestamp_millis(time.timestamp_millis()).unwrap_or(time)
+}
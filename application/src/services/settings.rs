@@ -0,0 +1,92 @@
+use std::{
+  sync::{Arc, Mutex},
+  time::{Duration, Instant},
+};
+
+use sqlx::PgPool;
+
+use crate::error::{AppError, AppResult};
+use domain::{ActorId, Settings, WalletLabel};
+use infra::stores::{models::SettingsUpdate, SettingsStore, WalletStore};
+
+/// How long other instances may take to notice a change
+const SETTINGS_SYNC: Duration = Duration::from_secs(5);
+
+/// Settings as last read from the database.
+struct CachedSettings {
+  read_at: Instant,
+  settings: Settings,
+}
+
+/// Settings operators change at runtime, read through a snapshot kept for a
+/// few seconds and dropped on every change.
+#[derive(Clone)]
+pub struct SettingsService {
+  pool: PgPool,
+  defaults: Settings,
+  cached: Arc<Mutex<Option<CachedSettings>>>,
+}
+
+impl SettingsService {
+  pub fn new(pool: PgPool, defaults: Settings) -> Self {
+    Self {
+      pool,
+      defaults,
+      cached: Arc::default(),
+    }
+  }
+
+  /// The settings in effect.
+  pub async fn get(&self) -> AppResult<Settings> {
+    if let Some(cached) = self
+      .cached
+      .lock()
+      .expect("settings lock")
+      .as_ref()
+      .filter(|cached| cached.read_at.elapsed() < SETTINGS_SYNC)
+    {
+      return Ok(cached.settings.clone());
+    }
+
+    let read_at = Instant::now();
+    let settings = SettingsStore::find(&self.pool)
+      .await?
+      .unwrap_or_else(|| self.defaults.clone());
+    *self.cached.lock().expect("settings lock") = Some(CachedSettings {
+      read_at,
+      settings: settings.clone(),
+    });
+
+    Ok(settings)
+  }
+
+  /// Replaces the settings; every instance applies them within a few
+  /// seconds. Opens the system wallets of a currency new wallets haven't been
+  /// in before.
+  pub async fn update(&self, settings: Settings, changed_by: ActorId) -> AppResult<Settings> {
+    settings.check().map_err(AppError::Validation)?;
+
+    let mut tx = self.pool.begin().await?;
+
+    let settings = SettingsStore::upsert(
+      &mut *tx,
+      &SettingsUpdate {
+        invite_expiration_days: settings.invite_expiration_days,
+        allow_overdraft_by_default: settings.allow_overdraft_by_default,
+        currency: settings.currency,
+        email_sender_name: settings.email_sender_name,
+        changed_by: Some(changed_by),
+      },
+    )
+    .await?;
+    for label in WalletLabel::variants() {
+      WalletStore::create_system(&mut *tx, label, settings.currency).await?;
+    }
+
+    tx.commit().await?;
+
+    *self.cached.lock().expect("settings lock") = None;
+
+    Ok(settings)
+  }
+}
@@ -0,0 +1,128 @@
+use std::collections::HashMap;
+
+use sqlx::PgPool;
+
+use crate::error::{AppError, AppResult};
+use domain::{PickupSlots, Shop, ShopId, ShopMember, ShopOffering, ShopOfferingId, User, UserId};
+use infra::stores::{
+  FavoriteOfferingStore, ShopMemberStore, ShopOfferingStore, ShopStore, UserStore,
+};
+
+#[derive(Clone)]
+pub struct ShopService {
+  pool: PgPool,
+}
+
+impl ShopService {
+  pub fn new(pool: PgPool) -> Self {
+    Self { pool }
+  }
+
+  pub async fn get_by_id(&self, id: ShopId) -> AppResult<Option<Shop>> {
+    Ok(ShopStore::find_by_id(&self.pool, &id).await?)
+  }
+
+  /// Opens the shop for pre-orders with the given slots, or closes it with
+  /// `None`. Pre-orders already booked are kept.
+  pub async fn set_pickup_slots(
+    &self,
+    shop_id: ShopId,
+    slots: Option<PickupSlots>,
+  ) -> AppResult<Shop> {
+    ShopStore::update_pickup_slots(&self.pool, &shop_id, slots.as_ref())
+      .await?
+      .ok_or(AppError::NotFound)
+  }
+
+  /// Whether the user may sell on behalf of the shop, i.e. owns it or is a
+  /// member of its staff.
+  pub async fn is_staff(&self, shop: &Shop, user_id: UserId) -> AppResult<bool> {
+    if shop.is_owned_by(&user_id) {
+      return Ok(true);
+    }
+
+    Ok(
+      ShopMemberStore::find_by_shop_and_user_id(&self.pool, &shop.id, &user_id)
+        .await?
+        .is_some(),
+    )
+  }
+
+  /// Lets the user sell on behalf of the shop.
+  pub async fn add_member(&self, shop_id: ShopId, user_id: UserId) -> AppResult<ShopMember> {
+    UserStore::find_by_id(&self.pool, &user_id)
+      .await?
+      .ok_or(AppError::NotFound)?;
+
+    if ShopMemberStore::find_by_shop_and_user_id(&self.pool, &shop_id, &user_id)
+      .await?
+      .is_some()
+    {
+      return Err(AppError::AlreadyShopMember);
+    }
+
+    ShopMemberStore::create(&self.pool, &shop_id, &user_id)
+      .await
+      .map_err(|e| match e {
+        sqlx::Error::Database(ref db_err) if db_err.is_unique_violation() => {
+          AppError::AlreadyShopMember
+        }
+        e => e.into(),
+      })
+  }
+
+  pub async fn remove_member(&self, shop_id: ShopId, user_id: UserId) -> AppResult<()> {
+    ShopMemberStore::find_by_shop_and_user_id(&self.pool, &shop_id, &user_id)
+      .await?
+      .ok_or(AppError::NotFound)?;
+
+    ShopMemberStore::delete_by_shop_and_user_id(&self.pool, &shop_id, &user_id).await?;
+
+    Ok(())
+  }
+
+  /// Returns the shop's members together with the users behind them.
+  pub async fn list_members(&self, shop_id: ShopId) -> AppResult<Vec<(ShopMember, User)>> {
+    let members = ShopMemberStore::list_by_shop_id(&self.pool, &shop_id).await?;
+    let mut users: HashMap<UserId, User> = UserStore::list_by_shop_id(&self.pool, &shop_id)
+      .await?
+      .into_iter()
+      .map(|user| (user.id, user))
+      .collect();
+
+    Ok(
+      members
+        .into_iter()
+        .filter_map(|member| users.remove(&member.user_id).map(|user| (member, user)))
+        .collect(),
+    )
+  }
+
+  pub async fn favorites(&self, user_id: UserId) -> AppResult<Vec<ShopOffering>> {
+    Ok(FavoriteOfferingStore::list_by_user_id(&self.pool, &user_id).await?)
+  }
+
+  pub async fn add_favorite(
+    &self,
+    user_id: UserId,
+    offering_id: ShopOfferingId,
+  ) -> AppResult<ShopOffering> {
+    let offering = ShopOfferingStore::find_by_id(&self.pool, &offering_id)
+      .await?
+      .ok_or(AppError::NotFound)?;
+
+    FavoriteOfferingStore::create(&self.pool, &user_id, &offering.id).await?;
+
+    Ok(offering)
+  }
+
+  pub async fn remove_favorite(
+    &self,
+    user_id: UserId,
+    offering_id: ShopOfferingId,
+  ) -> AppResult<()> {
+    FavoriteOfferingStore::delete(&self.pool, &user_id, &offering_id).await?;
+
+    Ok(())
+  }
+}
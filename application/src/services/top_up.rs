@@ -0,0 +1,305 @@
+use std::collections::BTreeMap;
+
+use chrono::Utc;
+use serde::Deserialize;
+use sqlx::{PgConnection, PgPool};
+
+use crate::{
+  error::{AppError, AppResult},
+  services::{domain_event::emit, ChargebackService, FeatureSwitchService, RiskService},
+};
+use domain::{
+  types::Money, ActorId, Currency, DomainEvent, Feature, MetadataSource, TopUp, TopUpStatus,
+  TransactionMetadata, Wallet, WalletId, WalletLabel,
+};
+use infra::{
+  services::{psp::verify_signature, PspClient},
+  stores::{
+    models::{TopUpCreation, TransactionCreation},
+    TopUpStore, TransactionStore, WalletStore,
+  },
+};
+
+pub use infra::services::psp::SIGNATURE_HEADER;
+
+/// Where the provider sends the payer back to after the hosted checkout.
+#[derive(Debug, Clone)]
+pub struct TopUpReturnUrls {
+  pub success: String,
+  pub cancel: String,
+}
+
+#[derive(Deserialize)]
+struct ProviderEvent {
+  #[serde(rename = "type")]
+  kind: String,
+  data: ProviderEventData,
+}
+
+#[derive(Deserialize)]
+struct ProviderEventData {
+  /// Shape depends on the kind of event
+  object: serde_json::Value,
+}
+
+#[derive(Deserialize)]
+struct CheckoutSessionObject {
+  id: String,
+  payment_status: Option<String>,
+  payment_intent: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct DisputeObject {
+  id: String,
+  payment_intent: Option<String>,
+  amount: i64,
+  status: String,
+}
+
+/// Top-ups paid online through the payment service provider. Money received
+/// is booked from the [`WalletLabel::PspClearing`] system wallet.
+#[derive(Clone)]
+pub struct TopUpService {
+  pool: PgPool,
+  risk_service: RiskService,
+  /// Unset when no provider is configured
+  client: Option<PspClient>,
+  webhook_secret: Option<String>,
+  return_urls: TopUpReturnUrls,
+  chargeback_service: ChargebackService,
+  feature_switch_service: FeatureSwitchService,
+}
+
+impl TopUpService {
+  pub fn new(
+    pool: PgPool,
+    risk_service: RiskService,
+    client: Option<PspClient>,
+    webhook_secret: Option<String>,
+    return_urls: TopUpReturnUrls,
+    chargeback_service: ChargebackService,
+    feature_switch_service: FeatureSwitchService,
+  ) -> Self {
+    Self {
+      pool,
+      risk_service,
+      client,
+      webhook_secret,
+      return_urls,
+      chargeback_service,
+      feature_switch_service,
+    }
+  }
+
+  /// Opens a hosted checkout for topping up `wallet_id` and returns the
+  /// pending top-up with the URL to send the payer to. The wallet is only
+  /// credited once the provider confirms the payment.
+  pub async fn start(
+    &self,
+    requested_by: ActorId,
+    wallet_id: WalletId,
+    amount: Money,
+  ) -> AppResult<(TopUp, String)> {
+    let client = self.client.as_ref().ok_or(AppError::PspDisabled)?;
+    self
+      .feature_switch_service
+      .require(Feature::ExternalTopUps)
+      .await?;
+
+    if !amount.is_positive() {
+      return Err(AppError::Validation(
+        "Top-up amount must be positive".to_string(),
+      ));
+    }
+
+    let wallet = WalletStore::find_by_id(&self.pool, &wallet_id)
+      .await?
+      .ok_or(AppError::NotFound)?;
+    if wallet.label.is_some() {
+      return Err(AppError::BadRequest(
+        "System wallets cannot be topped up".to_string(),
+      ));
+    }
+    if self.chargeback_service.has_unresolved(wallet.id).await? {
+      return Err(AppError::ChargebackUnresolved);
+    }
+
+    let top_up = TopUpStore::create(
+      &self.pool,
+      &TopUpCreation {
+        wallet_id: wallet.id,
+        amount,
+        requested_by: Some(requested_by),
+      },
+    )
+    .await?;
+
+    let session = client
+      .create_checkout_session(
+        &top_up.id.to_string(),
+        "Wallet top-up",
+        amount.as_minor(),
+        wallet.currency.code(),
+        &self.return_urls.success,
+        &self.return_urls.cancel,
+      )
+      .await?;
+
+    let top_up = TopUpStore::set_provider_session_id(&self.pool, &top_up.id, &session.id)
+      .await?
+      .ok_or(AppError::NotFound)?;
+
+    Ok((top_up, session.url))
+  }
+
+  /// Handles a signed event from the provider.
+  ///
+  /// Providers deliver events at least once, so settling is idempotent: a
+  /// top-up is credited or failed only while pending, under a row lock.
+  pub async fn handle_provider_event(&self, signature: &str, body: &str) -> AppResult<()> {
+    let secret = self.webhook_secret.as_ref().ok_or(AppError::PspDisabled)?;
+    if !verify_signature(secret, signature, body, Utc::now().timestamp()) {
+      return Err(AppError::BadRequest("Invalid signature".to_string()));
+    }
+
+    let event: ProviderEvent = serde_json::from_str(body).map_err(malformed)?;
+
+    if event.kind.starts_with("charge.dispute.") {
+      let dispute: DisputeObject = serde_json::from_value(event.data.object).map_err(malformed)?;
+      return match event.kind.as_str() {
+        "charge.dispute.created" => {
+          let Some(payment_id) = dispute.payment_intent else {
+            tracing::warn!("Dispute {} without a payment", dispute.id);
+            return Ok(());
+          };
+          self
+            .chargeback_service
+            .open(&dispute.id, &payment_id, dispute.amount)
+            .await
+        }
+        "charge.dispute.closed" => {
+          // Inquiries closed without a chargeback keep the money with us too
+          let won = matches!(dispute.status.as_str(), "won" | "warning_closed");
+          self.chargeback_service.close(&dispute.id, won).await
+        }
+        _ => Ok(()),
+      };
+    }
+
+    let session = match event.kind.as_str() {
+      kind if kind.starts_with("checkout.session.") => {
+        serde_json::from_value::<CheckoutSessionObject>(event.data.object).map_err(malformed)?
+      }
+      _ => return Ok(()),
+    };
+
+    let status = match event.kind.as_str() {
+      "checkout.session.completed" if session.payment_status.as_deref() == Some("paid") => {
+        TopUpStatus::Succeeded
+      }
+      "checkout.session.async_payment_succeeded" => TopUpStatus::Succeeded,
+      "checkout.session.expired" | "checkout.session.async_payment_failed" => TopUpStatus::Failed,
+      // Bank transfers and the like complete later with their own event
+      _ => return Ok(()),
+    };
+
+    self
+      .settle(&session.id, status, session.payment_intent.as_deref())
+      .await
+  }
+
+  async fn settle(
+    &self,
+    session_id: &str,
+    status: TopUpStatus,
+    payment_id: Option<&str>,
+  ) -> AppResult<()> {
+    let mut tx = self.pool.begin().await?;
+
+    let Some(top_up) =
+      TopUpStore::find_by_provider_session_id_for_update(&mut *tx, session_id).await?
+    else {
+      tracing::warn!("Provider event for unknown checkout session {}", session_id);
+      return Ok(());
+    };
+    if top_up.status != TopUpStatus::Pending {
+      return Ok(());
+    }
+
+    let transaction_id = if status == TopUpStatus::Succeeded {
+      let wallet = WalletStore::find_by_id(&mut *tx, &top_up.wallet_id)
+        .await?
+        .ok_or(AppError::NotFound)?;
+      let clearing = find_clearing_wallet(&mut tx, wallet.currency).await?;
+
+      let transaction = TransactionStore::create(
+        &mut *tx,
+        &TransactionCreation {
+          source: clearing.id,
+          destination: top_up.wallet_id,
+          executor: top_up.requested_by,
+          amount: top_up.amount,
+          description: Some("Online top-up".to_string()),
+          reversal_of: None,
+          metadata: payment_metadata(payment_id),
+        },
+      )
+      .await?;
+      emit(
+        &mut tx,
+        DomainEvent::TransactionCreated((&transaction).into()),
+      )
+      .await?;
+      Some(transaction.id)
+    } else {
+      None
+    };
+
+    TopUpStore::settle(&mut *tx, &top_up.id, status, transaction_id, payment_id).await?;
+
+    tx.commit().await?;
+
+    if transaction_id.is_some() {
+      if let Err(e) = self.risk_service.assess_wallet(top_up.wallet_id).await {
+        tracing::warn!(
+          "Failed to assess wallet {} after online top-up: {}",
+          top_up.wallet_id,
+          e
+        );
+      }
+    }
+
+    Ok(())
+  }
+}
+
+fn malformed(e: serde_json::Error) -> AppError {
+  AppError::BadRequest(format!("Malformed event: {}", e))
+}
+
+/// The system wallet standing in for the provider's account in `currency`.
+pub(crate) async fn find_clearing_wallet(
+  conn: &mut PgConnection,
+  currency: Currency,
+) -> AppResult<Wallet> {
+  WalletStore::find_by_label(&mut *conn, &WalletLabel::PspClearing, currency)
+    .await?
+    .ok_or_else(|| {
+      tracing::error!("System wallet {} is missing", WalletLabel::PspClearing);
+      AppError::InternalServerError
+    })
+}
+
+/// Records the provider's reference to the payment on its transaction. An
+/// unexpected reference is left out rather than holding up the settlement.
+fn payment_metadata(payment_id: Option<&str>) -> TransactionMetadata {
+  let entries = payment_id
+    .map(|id| BTreeMap::from([("provider_payment_id".to_string(), id.to_string())]))
+    .unwrap_or_default();
+
+  TransactionMetadata::new(MetadataSource::OnlineTopUp, entries).unwrap_or_else(|e| {
+    tracing::warn!("Dropping metadata of an online top-up: {}", e);
+    TransactionMetadata::default()
+  })
+}
@@ -0,0 +1,164 @@
+use futures_util::{stream, Stream};
+use sqlx::PgPool;
+
+use crate::{
+  error::{AppError, AppResult},
+  events::EventBus,
+  services::{domain_event::emit, RiskService},
+};
+use domain::{
+  ActorId, DomainEvent, LedgerLine, OrderEvent, Transaction, TransactionId, TransactionMetadata,
+};
+use infra::stores::{models::TransactionCreation, OrderStore, TransactionStore, WalletStore};
+
+/// Lines fetched per query while exporting the ledger
+const EXPORT_BATCH_SIZE: i64 = 500;
+
+#[derive(Clone)]
+pub struct TransactionService {
+  pool: PgPool,
+  risk_service: RiskService,
+  events: EventBus,
+}
+
+impl TransactionService {
+  pub fn new(pool: PgPool, risk_service: RiskService, events: EventBus) -> Self {
+    Self {
+      pool,
+      risk_service,
+      events,
+    }
+  }
+
+  pub async fn get_by_id(&self, id: TransactionId) -> AppResult<Option<Transaction>> {
+    Ok(TransactionStore::find_by_id(&self.pool, &id).await?)
+  }
+
+  /// Streams the whole ledger, oldest first, in batches so the export never
+  /// holds more than one batch in memory.
+  pub fn export_ledger(&self) -> impl Stream<Item = AppResult<Vec<LedgerLine>>> + Send + 'static {
+    let pool = self.pool.clone();
+
+    // `None` once the last batch has been handed out
+    stream::try_unfold(Some(None), move |cursor| {
+      let pool = pool.clone();
+      async move {
+        let Some(after) = cursor else {
+          return Ok(None);
+        };
+
+        let batch = TransactionStore::list_ledger_page(&pool, after, EXPORT_BATCH_SIZE).await?;
+        let next = if (batch.len() as i64) < EXPORT_BATCH_SIZE {
+          None
+        } else {
+          batch
+            .last()
+            .map(|line| Some((line.transaction.created_at, line.transaction.id)))
+        };
+
+        Ok(Some((batch, next)))
+      }
+    })
+  }
+
+  /// Books a compensating transaction moving the full amount back from the
+  /// original destination to the original source.
+  ///
+  /// A transaction can only be refunded once, and refunds themselves cannot
+  /// be refunded.
+  pub async fn refund(
+    &self,
+    executor: ActorId,
+    id: TransactionId,
+    description: Option<String>,
+  ) -> AppResult<Transaction> {
+    let mut tx = self.pool.begin().await?;
+
+    let original = TransactionStore::find_by_id(&mut *tx, &id)
+      .await?
+      .ok_or(AppError::NotFound)?;
+
+    if original.reversal_of.is_some() {
+      return Err(AppError::BadRequest(
+        "Refunds cannot be refunded".to_string(),
+      ));
+    }
+
+    if TransactionStore::find_reversal_of(&mut *tx, &id)
+      .await?
+      .is_some()
+    {
+      return Err(AppError::AlreadyRefunded);
+    }
+
+    // The refund is paid by whoever received the original money
+    let (payer, _) =
+      WalletStore::find_pair_for_update(&mut *tx, &original.destination, &original.source).await?;
+    let payer = payer.ok_or(AppError::NotFound)?;
+    let balance = WalletStore::find_balance(&mut *tx, &payer.id).await?;
+    if !payer.can_send(balance, original.amount) {
+      return Err(AppError::InsufficientFunds);
+    }
+
+    let refund = TransactionStore::create(
+      &mut *tx,
+      &TransactionCreation {
+        source: original.destination,
+        destination: original.source,
+        executor: Some(executor),
+        amount: original.amount,
+        description: description.or_else(|| Some(format!("Refund of {}", original.id))),
+        reversal_of: Some(original.id),
+        metadata: TransactionMetadata::default(),
+      },
+    )
+    .await
+    .map_err(|e| match e {
+      // Lost a race against a concurrent refund of the same transaction
+      sqlx::Error::Database(ref db_err) if db_err.is_unique_violation() => {
+        AppError::AlreadyRefunded
+      }
+      e => e.into(),
+    })?;
+    emit(&mut tx, DomainEvent::TransactionCreated((&refund).into())).await?;
+
+    let order = OrderStore::find_by_transaction_id(&mut *tx, &original.id).await?;
+    if let Some(order) = &order {
+      emit(
+        &mut tx,
+        DomainEvent::OrderRefunded {
+          order_id: order.id,
+          shop_id: order.shop_id,
+          refund_transaction_id: refund.id,
+          amount_cents: refund.amount.as_minor(),
+          refunded_at: refund.created_at,
+        },
+      )
+      .await?;
+    }
+
+    tx.commit().await?;
+
+    tracing::info!(
+      "Transaction {} refunded as {} by actor {}",
+      original.id,
+      refund.id,
+      executor
+    );
+
+    if let Err(e) = self.risk_service.assess_wallet(payer.id).await {
+      tracing::warn!("Failed to assess wallet {} after refund: {}", payer.id, e);
+    }
+
+    if let Some(order) = order {
+      self.events.publish(OrderEvent::Refunded {
+        order_id: order.id,
+        shop_id: order.shop_id,
+        amount: refund.amount,
+        at: refund.created_at,
+      });
+    }
+
+    Ok(refund)
+  }
+}
@@ -0,0 +1,155 @@
+use sqlx::PgPool;
+
+use crate::{
+  error::{AppError, AppResult},
+  services::{domain_event::emit, FeatureSwitchService, RiskService},
+};
+use domain::{types::Money, DomainEvent, Email, Feature, Transaction, TransactionMetadata, User};
+use infra::stores::{models::TransactionCreation, TransactionStore, UserStore, WalletStore};
+
+/// Money sent from one user's wallet to another's.
+#[derive(Clone)]
+pub struct TransferService {
+  pool: PgPool,
+  risk_service: RiskService,
+  /// Largest single transfer; zero for no limit
+  limit: Money,
+  feature_switch_service: FeatureSwitchService,
+}
+
+impl TransferService {
+  pub fn new(
+    pool: PgPool,
+    risk_service: RiskService,
+    limit: Money,
+    feature_switch_service: FeatureSwitchService,
+  ) -> Self {
+    Self {
+      pool,
+      risk_service,
+      limit,
+      feature_switch_service,
+    }
+  }
+
+  /// Moves `amount` from the sender's wallet into the wallet of the user
+  /// registered with `recipient_email`.
+  ///
+  /// Refuses to take the sender's wallet below zero unless it allows
+  /// overdraft, and refuses frozen wallets before looking at the recipient.
+  /// Both wallets are locked before the balance check, see
+  /// [`WalletStore::find_pair_for_update`].
+  ///
+  /// Sends nothing and returns `None` if nobody is registered with the email
+  /// or their wallet is in another currency. Only checks on the sender's side
+  /// can fail, so the outcome doesn't tell which emails have accounts.
+  pub async fn send(
+    &self,
+    sender: &User,
+    recipient_email: &Email,
+    amount: Money,
+    description: Option<String>,
+    notify_recipient: bool,
+  ) -> AppResult<Option<Transaction>> {
+    self
+      .feature_switch_service
+      .require(Feature::P2pTransfers)
+      .await?;
+
+    if !amount.is_positive() {
+      return Err(AppError::Validation(
+        "Transfer amount must be positive".to_string(),
+      ));
+    }
+    if recipient_email == &sender.email {
+      return Err(AppError::BadRequest(
+        "Cannot transfer to yourself".to_string(),
+      ));
+    }
+    let mut tx = self.pool.begin().await?;
+
+    let source = WalletStore::find_by_owner(&mut *tx, &sender.actor_id)
+      .await?
+      .ok_or(AppError::NotFound)?;
+    if self.limit.is_positive() && amount > self.limit {
+      return Err(AppError::Validation(format!(
+        "Transfers are limited to {}",
+        self.limit.format_in(source.currency)
+      )));
+    }
+
+    if source.frozen {
+      return Err(AppError::WalletFrozen);
+    }
+
+    let recipient = UserStore::find_by_email(&mut *tx, recipient_email).await?;
+    let destination = match &recipient {
+      Some(recipient) => WalletStore::find_by_owner(&mut *tx, &recipient.actor_id)
+        .await?
+        .filter(|destination| destination.currency == source.currency),
+      None => None,
+    };
+
+    // Locked together with the destination, if any, before the balance check
+    let source = match &destination {
+      Some(destination) => {
+        WalletStore::find_pair_for_update(&mut *tx, &source.id, &destination.id)
+          .await?
+          .0
+      }
+      None => WalletStore::find_by_id_for_update(&mut *tx, &source.id).await?,
+    }
+    .ok_or(AppError::NotFound)?;
+    let balance = WalletStore::find_balance(&mut *tx, &source.id).await?;
+    if !source.can_send(balance, amount) {
+      return Err(AppError::InsufficientFunds);
+    }
+
+    let (Some(recipient), Some(destination)) = (recipient, destination) else {
+      return Ok(None);
+    };
+
+    let transaction = TransactionStore::create(
+      &mut *tx,
+      &TransactionCreation {
+        source: source.id,
+        destination: destination.id,
+        executor: Some(sender.actor_id),
+        amount,
+        description: description.or_else(|| Some(format!("Transfer to {}", recipient.first_name))),
+        reversal_of: None,
+        metadata: TransactionMetadata::default(),
+      },
+    )
+    .await?;
+    emit(
+      &mut tx,
+      DomainEvent::TransactionCreated((&transaction).into()),
+    )
+    .await?;
+    emit(
+      &mut tx,
+      DomainEvent::TransferSent {
+        transaction_id: transaction.id,
+        sender_id: sender.id,
+        recipient_id: recipient.id,
+        amount_cents: amount.as_minor(),
+        currency: transaction.currency,
+        notify_recipient,
+      },
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    if let Err(e) = self.risk_service.assess_wallet(source.id).await {
+      tracing::warn!(
+        "Failed to assess wallet {} after transfer: {}",
+        source.id,
+        e
+      );
+    }
+
+    Ok(Some(transaction))
+  }
+}
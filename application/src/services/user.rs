@@ -1,17 +1,60 @@
+use chrono::Duration;
 use sqlx::PgPool;
+use uuid::Uuid;
 
-use crate::error::AppResult;
-use domain::{User, UserId};
-use infra::stores::UserStore;
+use crate::{
+  error::{AppError, AppResult},
+  services::{session::revoke_all, SettingsService},
+};
+use domain::{
+  types::{ListVersion, Page, PageRequest},
+  Email, EmailChange, Locale, PermissionOverride, Principal, RawPassword, Role, User, UserId,
+};
+use infra::{
+  services::{EmailService, EmailTemplate, EmailTemplates},
+  stores::{
+    models::{EmailChangeCreation, UserUpdate},
+    AccountNoteStore, AuditStore, BankAccountStore, EmailChangeStore, FavoriteOfferingStore,
+    LoginAttemptStore, LoginLockoutStore, PasswordSetupStore, ShopMemberStore, UserPermissionStore,
+    UserStore, WalletStore,
+  },
+};
+
+pub use infra::stores::models::UserFilter;
+
+const EMAIL_CHANGE_EXPIRATION_HOURS: i64 = 24;
+
+/// The user with what they may do, or `None` if they no longer exist.
+pub(crate) async fn load_principal(pool: &PgPool, id: UserId) -> AppResult<Option<Principal>> {
+  let Some(user) = UserStore::find_by_id(pool, &id).await? else {
+    return Ok(None);
+  };
+  let overrides = UserPermissionStore::list_by_user_id(pool, &id).await?;
+
+  Ok(Some(Principal { user, overrides }))
+}
 
 #[derive(Clone)]
 pub struct UserService {
   pool: PgPool,
+  email_service: EmailService,
+  email_templates: EmailTemplates,
+  settings_service: SettingsService,
 }
 
 impl UserService {
-  pub fn new(pool: PgPool) -> Self {
-    Self { pool }
+  pub fn new(
+    pool: PgPool,
+    email_service: EmailService,
+    email_templates: EmailTemplates,
+    settings_service: SettingsService,
+  ) -> Self {
+    Self {
+      pool,
+      email_service,
+      email_templates,
+      settings_service,
+    }
   }
 
   pub async fn get_by_id(&self, id: UserId) -> AppResult<Option<User>> {
@@ -21,4 +64,300 @@ impl UserService {
   pub async fn get_all(&self) -> AppResult<Vec<User>> {
     Ok(UserStore::list_all(&self.pool).await?)
   }
+
+  pub async fn list(&self, filter: UserFilter, page: PageRequest) -> AppResult<Page<User>> {
+    let items = UserStore::list_paginated(&self.pool, &filter, &page).await?;
+    let total = UserStore::count(&self.pool, &filter).await?;
+
+    Ok(Page {
+      items,
+      total,
+      request: page,
+    })
+  }
+
+  /// Changes whenever [`Self::list`] with the same filter would list
+  /// different users, on any page.
+  pub async fn list_version(&self, filter: &UserFilter) -> AppResult<ListVersion> {
+    Ok(UserStore::version(&self.pool, filter).await?)
+  }
+
+  /// Gives the user a new role and returns the previous one. The last owner
+  /// cannot be given another role. A changed role signs the user out
+  /// everywhere.
+  pub async fn change_role(&self, id: UserId, role: Role) -> AppResult<(Role, User)> {
+    let mut tx = self.pool.begin().await?;
+
+    // Locked first, so two owners can't demote each other at the same time
+    let owners = UserStore::lock_ids_by_role(&mut *tx, Role::Owner).await?;
+
+    let user = UserStore::find_by_id(&mut *tx, &id)
+      .await?
+      .ok_or(AppError::NotFound)?;
+
+    if user.role == Role::Owner && role != Role::Owner && owners.len() <= 1 {
+      return Err(AppError::LastOwner);
+    }
+
+    let updated = UserStore::update_by_id(
+      &mut *tx,
+      &id,
+      &UserUpdate {
+        email: None,
+        password: None,
+        first_name: None,
+        last_name: None,
+        role: Some(role),
+        locale: None,
+      },
+    )
+    .await?
+    .ok_or(AppError::NotFound)?;
+
+    if user.role != role {
+      revoke_all(&mut tx, id).await?;
+    }
+
+    tx.commit().await?;
+
+    Ok((user.role, updated))
+  }
+
+  /// Changes the user's name and locale right away. A different email
+  /// address is only requested and emailed a confirmation token; see
+  /// [`Self::confirm_email_change`].
+  pub async fn update_profile(
+    &self,
+    id: UserId,
+    first_name: Option<String>,
+    last_name: Option<String>,
+    locale: Option<Locale>,
+    email: Option<Email>,
+  ) -> AppResult<(User, Option<EmailChange>)> {
+    let mut tx = self.pool.begin().await?;
+
+    let user = UserStore::find_by_id(&mut *tx, &id)
+      .await?
+      .ok_or(AppError::NotFound)?;
+
+    let email = email.filter(|email| *email != user.email);
+    if let Some(email) = &email {
+      if UserStore::find_by_email(&mut *tx, email).await?.is_some() {
+        return Err(AppError::EmailInUse);
+      }
+    }
+
+    let user = UserStore::update_by_id(
+      &mut *tx,
+      &id,
+      &UserUpdate {
+        email: None,
+        password: None,
+        first_name,
+        last_name,
+        role: None,
+        locale,
+      },
+    )
+    .await?
+    .ok_or(AppError::NotFound)?;
+
+    let change = match email {
+      Some(email) => Some(
+        EmailChangeStore::replace(
+          &mut *tx,
+          &EmailChangeCreation {
+            user_id: id,
+            email,
+            token: Uuid::new_v4().to_string(),
+            expires_in: Duration::hours(EMAIL_CHANGE_EXPIRATION_HOURS),
+          },
+        )
+        .await?,
+      ),
+      None => None,
+    };
+
+    tx.commit().await?;
+
+    if let Some(change) = &change {
+      let content = self.email_templates.render(
+        &EmailTemplate::EmailChange {
+          token: change.token.clone(),
+        },
+        user.locale,
+      )?;
+      let settings = self.settings_service.get().await?;
+      self
+        .email_service
+        .send(
+          &change.email,
+          &content,
+          settings.email_sender_name.as_deref(),
+        )
+        .await?;
+    }
+
+    Ok((user, change))
+  }
+
+  /// Switches the user over to the address the token was sent to.
+  pub async fn confirm_email_change(&self, token: &str) -> AppResult<User> {
+    let mut tx = self.pool.begin().await?;
+
+    let change = EmailChangeStore::find_by_token_for_update(&mut *tx, token)
+      .await?
+      .ok_or(AppError::NotFound)?;
+    if change.is_expired() {
+      return Err(AppError::BadRequest("Email change has expired".to_string()));
+    }
+    if UserStore::find_by_email(&mut *tx, &change.email)
+      .await?
+      .is_some()
+    {
+      return Err(AppError::EmailInUse);
+    }
+
+    let user = UserStore::update_by_id(
+      &mut *tx,
+      &change.user_id,
+      &UserUpdate {
+        email: Some(change.email.clone()),
+        password: None,
+        first_name: None,
+        last_name: None,
+        role: None,
+        locale: None,
+      },
+    )
+    .await
+    .map_err(|e| match e {
+      sqlx::Error::Database(ref db_err) if db_err.is_unique_violation() => AppError::EmailInUse,
+      e => e.into(),
+    })?
+    .ok_or(AppError::NotFound)?;
+
+    EmailChangeStore::delete_by_id(&mut *tx, &change.id).await?;
+
+    tx.commit().await?;
+
+    Ok(user)
+  }
+
+  /// Sets the password of a user created for them, e.g. by an import, with
+  /// the token emailed to them. The token works once.
+  pub async fn set_up_password(&self, token: &str, password: RawPassword) -> AppResult<User> {
+    let mut tx = self.pool.begin().await?;
+
+    let setup = PasswordSetupStore::find_by_token_for_update(&mut *tx, token)
+      .await?
+      .ok_or(AppError::NotFound)?;
+    if setup.is_expired() {
+      return Err(AppError::BadRequest(
+        "Password setup has expired".to_string(),
+      ));
+    }
+
+    let user = UserStore::update_by_id(
+      &mut *tx,
+      &setup.user_id,
+      &UserUpdate {
+        email: None,
+        password: Some(password.hash()?),
+        first_name: None,
+        last_name: None,
+        role: None,
+        locale: None,
+      },
+    )
+    .await?
+    .ok_or(AppError::NotFound)?;
+
+    PasswordSetupStore::delete_by_id(&mut *tx, &setup.id).await?;
+
+    tx.commit().await?;
+
+    Ok(user)
+  }
+
+  /// Deletes the user's account: their personal data is replaced with
+  /// placeholders and whatever only served them is removed, i.e. sessions,
+  /// login attempts, password setups, permission overrides, shop
+  /// memberships, favorites, bank account, staff notes on them and the
+  /// personal data and IP addresses the audit log keeps of their actions.
+  /// Their wallet and its history stay for the books, as do the hashed audit
+  /// entries.
+  ///
+  /// Users whose wallet is under legal hold cannot be deleted.
+  pub async fn delete(&self, id: UserId) -> AppResult<()> {
+    let mut tx = self.pool.begin().await?;
+
+    let user = UserStore::find_by_id(&mut *tx, &id)
+      .await?
+      .ok_or(AppError::NotFound)?;
+
+    if WalletStore::find_by_owner(&mut *tx, &user.actor_id)
+      .await?
+      .is_some_and(|wallet| wallet.legal_hold)
+    {
+      return Err(AppError::LegalHold);
+    }
+
+    UserStore::anonymize_by_id(&mut *tx, &id)
+      .await?
+      .ok_or(AppError::NotFound)?;
+    revoke_all(&mut tx, id).await?;
+    LoginAttemptStore::delete_by_user_id(&mut *tx, &id).await?;
+    LoginLockoutStore::delete_by_email(&mut *tx, &user.email).await?;
+    PasswordSetupStore::delete_by_user_id(&mut *tx, &id).await?;
+    UserPermissionStore::delete_by_user_id(&mut *tx, &id).await?;
+    ShopMemberStore::delete_by_user_id(&mut *tx, &id).await?;
+    FavoriteOfferingStore::delete_by_user_id(&mut *tx, &id).await?;
+    BankAccountStore::delete_by_actor_id(&mut *tx, &user.actor_id).await?;
+    AuditStore::erase_personal_data_by_actor_id(&mut *tx, &user.actor_id).await?;
+    AccountNoteStore::delete_by_actor_id(&mut *tx, &user.actor_id).await?;
+
+    tx.commit().await?;
+
+    Ok(())
+  }
+
+  /// The user with their grants and revocations, for authorizing requests.
+  pub async fn principal(&self, id: UserId) -> AppResult<Option<Principal>> {
+    load_principal(&self.pool, id).await
+  }
+
+  pub async fn permission_overrides(&self, id: UserId) -> AppResult<Vec<PermissionOverride>> {
+    Ok(UserPermissionStore::list_by_user_id(&self.pool, &id).await?)
+  }
+
+  /// Replaces all of the user's grants and revocations. Any change signs the
+  /// user out everywhere.
+  pub async fn set_permission_overrides(
+    &self,
+    id: UserId,
+    overrides: Vec<PermissionOverride>,
+  ) -> AppResult<Vec<PermissionOverride>> {
+    let mut tx = self.pool.begin().await?;
+
+    UserStore::find_by_id(&mut *tx, &id)
+      .await?
+      .ok_or(AppError::NotFound)?;
+
+    let previous = UserPermissionStore::list_by_user_id(&mut *tx, &id).await?;
+
+    UserPermissionStore::delete_by_user_id(&mut *tx, &id).await?;
+    for permission_override in &overrides {
+      UserPermissionStore::create(&mut *tx, &id, permission_override).await?;
+    }
+
+    let overrides = UserPermissionStore::list_by_user_id(&mut *tx, &id).await?;
+    if overrides != previous {
+      revoke_all(&mut tx, id).await?;
+    }
+
+    tx.commit().await?;
+
+    Ok(overrides)
+  }
 }
@@ -0,0 +1,314 @@
+use sqlx::{Executor, PgPool, Postgres};
+
+use crate::{
+  error::{AppError, AppResult},
+  services::{domain_event::emit, RiskService},
+};
+use domain::{
+  types::{ListVersion, Money, Page, PageRequest},
+  ActorId, BalanceDrift, Currency, DomainEvent, LegalHoldAction, Transaction, TransactionMetadata,
+  Wallet, WalletAppearance, WalletDetails, WalletId, WalletLabel, WalletLegalHoldEvent,
+};
+use infra::stores::{
+  models::{TransactionCreation, WalletLegalHoldEventCreation},
+  TransactionStore, WalletLegalHoldStore, WalletStore,
+};
+
+pub use infra::stores::models::WalletFilter;
+
+#[derive(Clone)]
+pub struct WalletService {
+  pool: PgPool,
+  risk_service: RiskService,
+}
+
+impl WalletService {
+  pub fn new(pool: PgPool, risk_service: RiskService) -> Self {
+    Self { pool, risk_service }
+  }
+
+  pub async fn get_by_id(&self, id: WalletId) -> AppResult<Option<Wallet>> {
+    Ok(WalletStore::find_by_id(&self.pool, &id).await?)
+  }
+
+  pub async fn get_details(&self, id: WalletId) -> AppResult<Option<WalletDetails>> {
+    Ok(WalletStore::find_details_by_id(&self.pool, &id).await?)
+  }
+
+  pub async fn list(
+    &self,
+    filter: WalletFilter,
+    page: PageRequest,
+  ) -> AppResult<Page<WalletDetails>> {
+    let items = WalletStore::list_details_paginated(&self.pool, &filter, &page).await?;
+    let total = WalletStore::count(&self.pool, &filter).await?;
+
+    Ok(Page {
+      items,
+      total,
+      request: page,
+    })
+  }
+
+  /// Changes whenever [`Self::list`] with the same filter would list
+  /// different wallets, on any page.
+  pub async fn list_version(&self, filter: &WalletFilter) -> AppResult<ListVersion> {
+    Ok(WalletStore::version(&self.pool, filter).await?)
+  }
+
+  /// Compares every running balance against the sum of the wallet's
+  /// transactions and returns the wallets that drifted.
+  pub async fn check_balances(&self) -> AppResult<Vec<BalanceDrift>> {
+    Ok(WalletStore::list_balance_drift(&self.pool).await?)
+  }
+
+  pub async fn place_legal_hold(
+    &self,
+    actor: ActorId,
+    wallet_id: WalletId,
+    reason: String,
+  ) -> AppResult<Wallet> {
+    self
+      .set_legal_hold(actor, wallet_id, LegalHoldAction::Placed, reason)
+      .await
+  }
+
+  pub async fn release_legal_hold(
+    &self,
+    actor: ActorId,
+    wallet_id: WalletId,
+    reason: String,
+  ) -> AppResult<Wallet> {
+    self
+      .set_legal_hold(actor, wallet_id, LegalHoldAction::Released, reason)
+      .await
+  }
+
+  /// Changes how one of the owner's personal wallets is shown to them. Held
+  /// wallets are left exactly as they were when the hold was placed.
+  pub async fn set_appearance(
+    &self,
+    owner: ActorId,
+    wallet_id: WalletId,
+    appearance: WalletAppearance,
+  ) -> AppResult<Wallet> {
+    let wallet = WalletStore::find_by_id(&self.pool, &wallet_id)
+      .await?
+      .filter(|wallet| wallet.owner == Some(owner))
+      .ok_or(AppError::NotFound)?;
+
+    if wallet.label.is_some() {
+      return Err(AppError::BadRequest(
+        "System wallets cannot be restyled".to_string(),
+      ));
+    }
+
+    if wallet.legal_hold {
+      return Err(AppError::LegalHold);
+    }
+
+    WalletStore::set_appearance(&self.pool, &wallet.id, &appearance)
+      .await?
+      .ok_or(AppError::NotFound)
+  }
+
+  /// Records cash handed in at the register as a transfer from the
+  /// [`WalletLabel::OutsideCash`] system wallet into `wallet_id`.
+  pub async fn top_up(
+    &self,
+    executor: ActorId,
+    wallet_id: WalletId,
+    amount: Money,
+    description: Option<String>,
+    metadata: TransactionMetadata,
+  ) -> AppResult<Transaction> {
+    if !amount.is_positive() {
+      return Err(AppError::Validation(
+        "Top-up amount must be positive".to_string(),
+      ));
+    }
+
+    let mut tx = self.pool.begin().await?;
+
+    let wallet = WalletStore::find_by_id(&mut *tx, &wallet_id)
+      .await?
+      .ok_or(AppError::NotFound)?;
+
+    if wallet.label.is_some() {
+      return Err(AppError::BadRequest(
+        "System wallets cannot be topped up".to_string(),
+      ));
+    }
+
+    let cash = find_cash_wallet(&mut *tx, wallet.currency).await?;
+
+    let transaction = TransactionStore::create(
+      &mut *tx,
+      &TransactionCreation {
+        source: cash.id,
+        destination: wallet.id,
+        executor: Some(executor),
+        amount,
+        description: description.or_else(|| Some("Cash top-up".to_string())),
+        reversal_of: None,
+        metadata,
+      },
+    )
+    .await?;
+    emit(
+      &mut tx,
+      DomainEvent::TransactionCreated((&transaction).into()),
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(transaction)
+  }
+
+  /// Pays out cash at the register as a transfer from `wallet_id` back into
+  /// the [`WalletLabel::OutsideCash`] system wallet.
+  ///
+  /// Refuses to take the wallet below zero unless it allows overdraft.
+  pub async fn withdraw(
+    &self,
+    executor: ActorId,
+    wallet_id: WalletId,
+    amount: Money,
+    description: Option<String>,
+    metadata: TransactionMetadata,
+  ) -> AppResult<Transaction> {
+    if !amount.is_positive() {
+      return Err(AppError::Validation(
+        "Withdrawal amount must be positive".to_string(),
+      ));
+    }
+
+    let mut tx = self.pool.begin().await?;
+
+    let wallet = WalletStore::find_by_id(&mut *tx, &wallet_id)
+      .await?
+      .ok_or(AppError::NotFound)?;
+
+    if wallet.label.is_some() {
+      return Err(AppError::BadRequest(
+        "System wallets cannot be withdrawn from".to_string(),
+      ));
+    }
+
+    let cash = find_cash_wallet(&mut *tx, wallet.currency).await?;
+
+    let (wallet, _) = WalletStore::find_pair_for_update(&mut *tx, &wallet.id, &cash.id).await?;
+    let wallet = wallet.ok_or(AppError::NotFound)?;
+    let balance = WalletStore::find_balance(&mut *tx, &wallet.id).await?;
+    if !wallet.can_send(balance, amount) {
+      return Err(AppError::InsufficientFunds);
+    }
+
+    let transaction = TransactionStore::create(
+      &mut *tx,
+      &TransactionCreation {
+        source: wallet.id,
+        destination: cash.id,
+        executor: Some(executor),
+        amount,
+        description: description.or_else(|| Some("Cash withdrawal".to_string())),
+        reversal_of: None,
+        metadata,
+      },
+    )
+    .await?;
+    emit(
+      &mut tx,
+      DomainEvent::TransactionCreated((&transaction).into()),
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    if let Err(e) = self.risk_service.assess_wallet(wallet_id).await {
+      tracing::warn!(
+        "Failed to assess wallet {} after withdrawal: {}",
+        wallet_id,
+        e
+      );
+    }
+
+    Ok(transaction)
+  }
+
+  pub async fn get_legal_hold_history(
+    &self,
+    wallet_id: WalletId,
+  ) -> AppResult<Vec<WalletLegalHoldEvent>> {
+    WalletStore::find_by_id(&self.pool, &wallet_id)
+      .await?
+      .ok_or(AppError::NotFound)?;
+
+    Ok(WalletLegalHoldStore::list_by_wallet_id(&self.pool, &wallet_id).await?)
+  }
+
+  async fn set_legal_hold(
+    &self,
+    actor: ActorId,
+    wallet_id: WalletId,
+    action: LegalHoldAction,
+    reason: String,
+  ) -> AppResult<Wallet> {
+    let hold = action == LegalHoldAction::Placed;
+
+    let mut tx = self.pool.begin().await?;
+
+    // Locked so two people placing or releasing at once can't both record
+    // it in the history
+    let wallet = WalletStore::find_by_id_for_update(&mut *tx, &wallet_id)
+      .await?
+      .ok_or(AppError::NotFound)?;
+
+    if wallet.legal_hold == hold {
+      return Err(AppError::BadRequest(format!(
+        "Legal hold is already {}",
+        if hold { "placed" } else { "released" }
+      )));
+    }
+
+    let wallet = WalletStore::set_legal_hold(&mut *tx, &wallet_id, hold)
+      .await?
+      .ok_or(AppError::NotFound)?;
+
+    WalletLegalHoldStore::create(
+      &mut *tx,
+      &WalletLegalHoldEventCreation {
+        wallet_id,
+        actor_id: Some(actor),
+        action,
+        reason,
+      },
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    tracing::info!(
+      "Legal hold on wallet {} {} by actor {}",
+      wallet_id,
+      action,
+      actor
+    );
+
+    Ok(wallet)
+  }
+}
+
+/// The system wallet standing in for the physical cash register's `currency`.
+async fn find_cash_wallet<'c, E>(executor: E, currency: Currency) -> AppResult<Wallet>
+where
+  E: Executor<'c, Database = Postgres>,
+{
+  WalletStore::find_by_label(executor, &WalletLabel::OutsideCash, currency)
+    .await?
+    .ok_or_else(|| {
+      tracing::error!("System wallet {} is missing", WalletLabel::OutsideCash);
+      AppError::InternalServerError
+    })
+}
@@ -0,0 +1,170 @@
+use serde_json::json;
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+
+use crate::{
+  error::{AppError, AppResult},
+  services::job,
+};
+use domain::{
+  ActorId, DomainEventRecord, JobTask, ShopId, Webhook, WebhookDelivery, WebhookEvent, WebhookId,
+};
+use infra::{
+  services::check_url,
+  stores::{
+    models::{WebhookCreation, WebhookDeliveryCreation},
+    WebhookDeliveryStore, WebhookStore,
+  },
+};
+
+/// Deliveries shown in a webhook's log
+const DELIVERY_LOG_LIMIT: i64 = 100;
+
+#[derive(Clone)]
+pub struct WebhookService {
+  pool: PgPool,
+}
+
+/// Subscriber logging the event for every webhook subscribed to it and
+/// queueing the deliveries. Payloads carry the event's id, which lets
+/// receivers tell retries from new events.
+pub(crate) async fn on_event(conn: &mut PgConnection, record: &DomainEventRecord) -> AppResult<()> {
+  let Some(event) = record.event.webhook_event() else {
+    return Ok(());
+  };
+
+  let shop_id = record.event.shop_id();
+  let webhooks = WebhookStore::list_subscribed(&mut *conn, shop_id.as_ref(), event).await?;
+
+  if webhooks.is_empty() {
+    return Ok(());
+  }
+
+  let payload = json!({
+    "id": record.id,
+    "type": event,
+    "created_at": record.occurred_at,
+    "data": record.event.data(),
+  });
+
+  for webhook in webhooks {
+    let delivery = WebhookDeliveryStore::create(
+      &mut *conn,
+      &WebhookDeliveryCreation {
+        webhook_id: webhook.id,
+        event,
+        payload: payload.clone(),
+      },
+    )
+    .await?;
+
+    job::enqueue(
+      &mut *conn,
+      JobTask::DeliverWebhook {
+        delivery_id: delivery.id,
+      },
+    )
+    .await?;
+  }
+
+  Ok(())
+}
+
+impl WebhookService {
+  pub fn new(pool: PgPool) -> Self {
+    Self { pool }
+  }
+
+  /// Registers a webhook for events of every shop and those concerning no
+  /// shop, with a freshly generated signing secret.
+  pub async fn register(
+    &self,
+    created_by: ActorId,
+    url: String,
+    events: Vec<WebhookEvent>,
+  ) -> AppResult<Webhook> {
+    self.create(None, created_by, url, events).await
+  }
+
+  /// Registers a webhook for the shop's events with a freshly generated
+  /// signing secret.
+  pub async fn register_for_shop(
+    &self,
+    shop_id: ShopId,
+    created_by: ActorId,
+    url: String,
+    events: Vec<WebhookEvent>,
+  ) -> AppResult<Webhook> {
+    if let Some(event) = events.iter().find(|e| !e.concerns_shop()) {
+      return Err(AppError::BadRequest(format!(
+        "Shop webhooks can't subscribe to {}",
+        event
+      )));
+    }
+
+    self.create(Some(shop_id), created_by, url, events).await
+  }
+
+  async fn create(
+    &self,
+    shop_id: Option<ShopId>,
+    created_by: ActorId,
+    url: String,
+    events: Vec<WebhookEvent>,
+  ) -> AppResult<Webhook> {
+    check_url(&url).map_err(|e| AppError::Validation(format!("url: {}", e)))?;
+
+    let creation = WebhookCreation {
+      shop_id,
+      url,
+      secret: format!("whsec_{}", Uuid::new_v4().simple()),
+      events,
+      created_by: Some(created_by),
+    };
+
+    Ok(WebhookStore::create(&self.pool, &creation).await?)
+  }
+
+  pub async fn get(&self, id: WebhookId) -> AppResult<Webhook> {
+    WebhookStore::find_by_id(&self.pool, &id)
+      .await?
+      .ok_or(AppError::NotFound)
+  }
+
+  /// Webhooks not tied to a shop.
+  pub async fn list(&self) -> AppResult<Vec<Webhook>> {
+    Ok(WebhookStore::list_account_wide(&self.pool).await?)
+  }
+
+  pub async fn list_for_shop(&self, shop_id: ShopId) -> AppResult<Vec<Webhook>> {
+    Ok(WebhookStore::list_by_shop_id(&self.pool, &shop_id).await?)
+  }
+
+  /// Removes a webhook not tied to a shop, along with its delivery log.
+  pub async fn remove(&self, id: WebhookId) -> AppResult<Webhook> {
+    let webhook = WebhookStore::find_by_id(&self.pool, &id)
+      .await?
+      .filter(|webhook| webhook.shop_id.is_none())
+      .ok_or(AppError::NotFound)?;
+
+    WebhookStore::delete_by_id(&self.pool, &webhook.id).await?;
+
+    Ok(webhook)
+  }
+
+  pub async fn remove_from_shop(&self, shop_id: ShopId, id: WebhookId) -> AppResult<Webhook> {
+    let webhook = WebhookStore::find_by_id(&self.pool, &id)
+      .await?
+      .filter(|webhook| webhook.shop_id == Some(shop_id))
+      .ok_or(AppError::NotFound)?;
+
+    WebhookStore::delete_by_id(&self.pool, &webhook.id).await?;
+
+    Ok(webhook)
+  }
+
+  /// The webhook's latest deliveries, newest first.
+  pub async fn list_deliveries(&self, id: WebhookId) -> AppResult<Vec<WebhookDelivery>> {
+    Ok(WebhookDeliveryStore::list_by_webhook_id(&self.pool, &id, DELIVERY_LOG_LIMIT).await?)
+  }
+}
@@ -0,0 +1,63 @@
+use std::sync::Arc;
+
+use tokio::sync::watch;
+
+/// Coordinates a graceful shutdown: tells the background loops to wind down
+/// and counts the work that should finish before the process exits.
+#[derive(Clone)]
+pub struct Shutdown {
+  triggered: Arc<watch::Sender<bool>>,
+  in_flight: Arc<watch::Sender<usize>>,
+}
+
+/// Work that holds off the shutdown until it is dropped.
+pub struct InFlight(Arc<watch::Sender<usize>>);
+
+impl Shutdown {
+  pub fn new() -> Self {
+    Self {
+      triggered: Arc::new(watch::Sender::new(false)),
+      in_flight: Arc::new(watch::Sender::new(0)),
+    }
+  }
+
+  /// Starts shutting down; every [`Self::triggered`] resolves.
+  pub fn trigger(&self) {
+    self.triggered.send_replace(true);
+  }
+
+  pub fn is_triggered(&self) -> bool {
+    *self.triggered.borrow()
+  }
+
+  /// Resolves once the shutdown started.
+  pub async fn triggered(&self) {
+    let mut triggered = self.triggered.subscribe();
+    // The sender lives as long as `self`, so this only ends once triggered
+    let _ = triggered.wait_for(|triggered| *triggered).await;
+  }
+
+  /// Marks work the shutdown waits for, e.g. a request moving money.
+  pub fn track(&self) -> InFlight {
+    self.in_flight.send_modify(|count| *count += 1);
+    InFlight(self.in_flight.clone())
+  }
+
+  /// Resolves once no tracked work is left.
+  pub async fn drained(&self) {
+    let mut in_flight = self.in_flight.subscribe();
+    let _ = in_flight.wait_for(|count| *count == 0).await;
+  }
+}
+
+impl Default for Shutdown {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl Drop for InFlight {
+  fn drop(&mut self) {
+    self.0.send_modify(|count| *count -= 1);
+  }
+}
@@ -1,17 +1,63 @@
+use chrono::Duration;
 use sqlx::PgPool;
 
-use crate::config::Config;
-use crate::services::{AuthService, GuestService, InviteService, SessionService, UserService};
-use infra::services::{EmailService, EmailServiceConfig};
+use crate::config::{Config, SessionBackend};
+use crate::events::EventBus;
+use crate::services::top_up::TopUpReturnUrls;
+use crate::services::{
+  AccountNoteService, ActorService, AuditService, AuthService, BalanceAlertService, BudgetService,
+  ChargebackService, ClientAppService, DebtService, DomainEventService, FeatureSwitchService,
+  GuestService, HealthService, ImportService, InviteService, JobService, OrderService,
+  OwnerTransferService, PayoutService, RetentionService, RiskService, SessionService,
+  SettingsService, ShopService, TopUpService, TransactionService, TransferService, UserService,
+  WalletService, WebhookService,
+};
+use crate::shutdown::Shutdown;
+use domain::{
+  types::Money, DebtRecoveryPolicy, LoginLockout, RetentionPolicy, RiskThresholds, SepaDebtor,
+  SessionTimeouts, Settings,
+};
+use infra::services::{
+  EmailService, EmailServiceConfig, EmailTemplates, PspClient, PspClientConfig, SessionTokenSigner,
+  WebhookClient,
+};
 
 #[derive(Clone)]
 pub struct AppState {
   pub config: Config,
   pub auth_service: AuthService,
   pub session_service: SessionService,
+  pub client_app_service: ClientAppService,
   pub invite_service: InviteService,
   pub user_service: UserService,
+  pub owner_transfer_service: OwnerTransferService,
   pub guest_service: GuestService,
+  pub health_service: HealthService,
+  pub import_service: ImportService,
+  pub wallet_service: WalletService,
+  pub shop_service: ShopService,
+  pub order_service: OrderService,
+  pub transaction_service: TransactionService,
+  pub transfer_service: TransferService,
+  pub top_up_service: TopUpService,
+  pub chargeback_service: ChargebackService,
+  pub debt_service: DebtService,
+  pub feature_switch_service: FeatureSwitchService,
+  pub settings_service: SettingsService,
+  pub payout_service: PayoutService,
+  pub risk_service: RiskService,
+  pub retention_service: RetentionService,
+  pub audit_service: AuditService,
+  pub actor_service: ActorService,
+  pub domain_event_service: DomainEventService,
+  pub account_note_service: AccountNoteService,
+  pub job_service: JobService,
+  pub balance_alert_service: BalanceAlertService,
+  pub budget_service: BudgetService,
+  pub webhook_service: WebhookService,
+  pub email_service: EmailService,
+  pub event_bus: EventBus,
+  pub shutdown: Shutdown,
   pub pool: PgPool,
 }
 
@@ -23,21 +69,186 @@ impl AppState {
       username: config.smtp_username.expose().to_string(),
       password: config.smtp_password.expose().to_string(),
       from: config.smtp_from.clone(),
+      suppressed: config.smtp_suppressed,
     };
 
+    let settings_service =
+      SettingsService::new(pool.clone(), Settings::defaults(config.psp_currency));
     let email_service = EmailService::new(email_config);
-    let auth_service = AuthService::new(pool.clone());
-    let user_service = UserService::new(pool.clone());
-    let guest_service = GuestService::new(pool.clone());
-    let invite_service = InviteService::new(pool.clone(), email_service, auth_service.clone());
+    let email_templates = EmailTemplates::load().expect("email templates should be valid");
+    let auth_service = AuthService::new(
+      pool.clone(),
+      LoginLockout {
+        max_failures: config.login_max_failures,
+        window: Duration::minutes(config.login_lockout_minutes),
+      },
+      settings_service.clone(),
+    );
+    let user_service = UserService::new(
+      pool.clone(),
+      email_service.clone(),
+      email_templates.clone(),
+      settings_service.clone(),
+    );
+    let risk_service = RiskService::new(
+      pool.clone(),
+      RiskThresholds::default(),
+      config.risk_auto_freeze,
+    );
+    let webhook_service = WebhookService::new(pool.clone());
+    let wallet_service = WalletService::new(pool.clone(), risk_service.clone());
+    let event_bus = EventBus::new();
+    let transaction_service =
+      TransactionService::new(pool.clone(), risk_service.clone(), event_bus.clone());
+    let session_signer = match config.session_backend {
+      SessionBackend::Database => None,
+      SessionBackend::Signed => {
+        let key = config
+          .session_signing_key
+          .as_ref()
+          .map(|key| key.expose())
+          .filter(|key| key.len() >= 32)
+          .expect("signed sessions need a SESSION_SIGNING_KEY of at least 32 characters");
+        Some(SessionTokenSigner::new(key))
+      }
+    };
+    let psp_client = config.psp_api_key.as_ref().map(|api_key| {
+      PspClient::new(PspClientConfig {
+        api_url: config.psp_api_url.clone(),
+        api_key: api_key.expose().to_string(),
+      })
+    });
+    let chargeback_service = ChargebackService::new(pool.clone());
+    let feature_switch_service = FeatureSwitchService::new(pool.clone());
+    let top_up_service = TopUpService::new(
+      pool.clone(),
+      risk_service.clone(),
+      psp_client,
+      config
+        .psp_webhook_secret
+        .as_ref()
+        .map(|secret| secret.expose().to_string()),
+      TopUpReturnUrls {
+        success: config.psp_success_url.clone(),
+        cancel: config.psp_cancel_url.clone(),
+      },
+      chargeback_service.clone(),
+      feature_switch_service.clone(),
+    );
+    let payout_service = PayoutService::new(
+      pool.clone(),
+      risk_service.clone(),
+      config.sepa_debtor_iban.clone().map(|iban| SepaDebtor {
+        name: config.sepa_debtor_name.clone(),
+        iban,
+        bic: config.sepa_debtor_bic.clone(),
+      }),
+    );
+    let guest_service =
+      GuestService::new(pool.clone(), risk_service.clone(), settings_service.clone());
+    let order_service = OrderService::new(
+      pool.clone(),
+      risk_service.clone(),
+      event_bus.clone(),
+      feature_switch_service.clone(),
+      config.venue_timezone,
+    );
+    let invite_service = InviteService::new(
+      pool.clone(),
+      auth_service.clone(),
+      email_templates.clone(),
+      settings_service.clone(),
+    );
 
     Self {
       config: config.clone(),
       auth_service,
-      session_service: SessionService::new(pool.clone(), config.session_expiration_days),
+      session_service: SessionService::new(
+        pool.clone(),
+        SessionTimeouts {
+          absolute: Duration::days(config.session_expiration_days),
+          idle: config
+            .session_idle_timeout_minutes
+            .filter(|minutes| *minutes > 0)
+            .map(Duration::minutes),
+        },
+        session_signer,
+        std::time::Duration::from_secs(config.session_cache_seconds),
+        event_bus.clone(),
+      ),
+      client_app_service: ClientAppService::new(pool.clone(), config.app_token_expiration_days),
       invite_service,
       user_service,
+      owner_transfer_service: OwnerTransferService::new(pool.clone()),
       guest_service,
+      health_service: HealthService::new(
+        pool.clone(),
+        email_service.clone(),
+        config.health_check_smtp,
+      ),
+      import_service: ImportService::new(
+        pool.clone(),
+        email_templates.clone(),
+        settings_service.clone(),
+      ),
+      wallet_service,
+      shop_service: ShopService::new(pool.clone()),
+      order_service,
+      transaction_service,
+      transfer_service: TransferService::new(
+        pool.clone(),
+        risk_service.clone(),
+        Money::from_minor(i32::try_from(config.transfer_limit_cents).unwrap_or(i32::MAX)),
+        feature_switch_service.clone(),
+      ),
+      top_up_service,
+      chargeback_service,
+      feature_switch_service,
+      debt_service: DebtService::new(
+        pool.clone(),
+        DebtRecoveryPolicy {
+          grace_days: config.debt_grace_days,
+          reminder_interval_days: config.debt_reminder_interval_days,
+          max_reminders: config.debt_max_reminders,
+        },
+        email_templates.clone(),
+      ),
+      payout_service,
+      risk_service,
+      retention_service: RetentionService::new(
+        pool.clone(),
+        RetentionPolicy {
+          expired_sessions_days: config.retention_expired_sessions_days,
+          expired_invites_days: config.retention_expired_invites_days,
+          login_attempts_days: config.retention_login_attempts_days,
+          inactive_guests_days: config.retention_inactive_guests_days,
+          audit_ip_addresses_days: config.retention_audit_ip_addresses_days,
+        },
+      ),
+      audit_service: AuditService::new(pool.clone()),
+      actor_service: ActorService::new(pool.clone()),
+      domain_event_service: DomainEventService::new(pool.clone(), event_bus.clone()),
+      account_note_service: AccountNoteService::new(pool.clone()),
+      job_service: JobService::new(
+        pool.clone(),
+        email_service.clone(),
+        email_templates.clone(),
+        WebhookClient::new(),
+        settings_service.clone(),
+      ),
+      balance_alert_service: BalanceAlertService::new(
+        pool.clone(),
+        email_service.clone(),
+        email_templates,
+        WebhookClient::new(),
+        settings_service.clone(),
+      ),
+      budget_service: BudgetService::new(pool.clone()),
+      settings_service,
+      webhook_service,
+      email_service,
+      event_bus,
+      shutdown: Shutdown::new(),
       pool,
     }
   }
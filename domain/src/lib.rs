@@ -2,4 +2,6 @@ pub mod models;
 pub mod types;
 
 pub use models::*;
-pub use types::{Email, HashedPassword, Id, RawPassword};
+pub use types::{
+  Currency, DevicePublicKey, Email, HashedPassword, Iban, Id, Locale, RawPassword, VenueTimezone,
+};
@@ -1,5 +1,135 @@
-use crate::Id;
+use std::fmt::Display;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::{types::Currency, Email, Guest, GuestId, Id, Role, User, UserId};
 
 pub type ActorId = Id<Actor>;
 
 pub struct Actor;
+
+/// The user or guest behind an actor.
+#[derive(Debug, Clone)]
+pub enum ActorDetails {
+  User(User),
+  Guest(Guest),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ActorKind {
+  User,
+  Guest,
+}
+
+/// Who an actor is and what they hold, kept up to date by the database
+/// shortly after every change rather than assembled per request. Only the
+/// balance is summed when read.
+#[derive(Debug, Clone)]
+pub struct ActorSummary {
+  pub actor_id: ActorId,
+  pub kind: ActorKind,
+  pub user_id: Option<UserId>,
+  pub guest_id: Option<GuestId>,
+  /// First and last name of users
+  pub display_name: Option<String>,
+  pub email: Option<Email>,
+  /// Card or wristband of guests
+  pub identifier: Option<String>,
+  pub role: Option<Role>,
+  pub wallet_count: i32,
+  /// What the balance is counted in, that of the actor's first wallet; unset
+  /// without one
+  pub currency: Option<Currency>,
+  /// Summed over the actor's wallets in `currency`; members have just the
+  /// one
+  pub balance_cents: i64,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// Why someone may be the person about to be invited or added.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DuplicateReason {
+  /// The very same address, up to case
+  SameEmail,
+  /// The same address once `+tags` and dots are ignored
+  SimilarEmail,
+  /// A user named like the address, e.g. Ada Lovelace for
+  /// `ada.lovelace@example.com`
+  SimilarName,
+}
+
+/// A user or guest who is likely the same person as someone about to get an
+/// account, so their money doesn't end up split across two.
+#[derive(Debug, Clone)]
+pub struct DuplicateCandidate {
+  pub actor_id: ActorId,
+  pub kind: ActorKind,
+  pub user_id: Option<UserId>,
+  pub guest_id: Option<GuestId>,
+  /// First and last name of users
+  pub display_name: Option<String>,
+  pub reason: DuplicateReason,
+}
+
+impl DuplicateCandidate {
+  /// The first name and last initial, e.g. "Ada L.", enough to tell people
+  /// apart without revealing who they are.
+  pub fn short_name(&self) -> Option<String> {
+    let mut names = self.display_name.as_deref()?.split_whitespace();
+    let first = names.next()?;
+
+    Some(match names.last().and_then(|last| last.chars().next()) {
+      Some(initial) => format!("{} {}.", first, initial.to_uppercase()),
+      None => first.to_string(),
+    })
+  }
+}
+
+impl Display for ActorKind {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let kind_str = match self {
+      ActorKind::User => "user",
+      ActorKind::Guest => "guest",
+    };
+    write!(f, "{}", kind_str)
+  }
+}
+
+impl From<&str> for ActorKind {
+  fn from(value: &str) -> Self {
+    match value {
+      "guest" => ActorKind::Guest,
+      _ => ActorKind::User,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use uuid::Uuid;
+
+  #[test]
+  fn test_duplicate_candidate_short_name() {
+    let candidate = |display_name: Option<&str>| DuplicateCandidate {
+      actor_id: Uuid::nil().into(),
+      kind: ActorKind::User,
+      user_id: None,
+      guest_id: None,
+      display_name: display_name.map(ToString::to_string),
+      reason: DuplicateReason::SimilarName,
+    };
+
+    assert_eq!(
+      candidate(Some("Ada King Lovelace")).short_name().as_deref(),
+      Some("Ada L.")
+    );
+    assert_eq!(candidate(Some("Ada")).short_name().as_deref(), Some("Ada"));
+    assert_eq!(candidate(None).short_name(), None);
+  }
+}
@@ -0,0 +1,185 @@
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use std::fmt::Display;
+
+use crate::{ActorId, Id};
+
+pub type AuditEntryId = Id<AuditEntry>;
+
+/// Business events recorded explicitly by the handlers performing them.
+///
+/// Entries written by the request body middleware use `METHOD /route` as
+/// their action instead.
+///
+/// Some mutating handlers record nothing: refreshing a session only swaps the
+/// token of a session whose login is recorded, favorites are the caller's own
+/// shortcuts, and rebuilding a past order's cart changes nothing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuditAction {
+  Login,
+  Logout,
+  PasswordConfirmed,
+  SessionRevoked,
+  InviteSent,
+  InviteResent,
+  InviteRevoked,
+  InviteAccepted,
+  GuestCreated,
+  GuestIdentifierAssigned,
+  GuestIdentifierRotated,
+  GuestClaimed,
+  WalletToppedUp,
+  OnlineTopUpStarted,
+  WalletWithdrawn,
+  TransferSent,
+  TransactionRefunded,
+  LegalHoldPlaced,
+  LegalHoldReleased,
+  WalletAppearanceChanged,
+  BudgetEnvelopeCreated,
+  BudgetEnvelopeUpdated,
+  BudgetEnvelopeRemoved,
+  AccountNoteAdded,
+  ReviewResolved,
+  ShopMemberAdded,
+  ShopMemberRemoved,
+  OrderPlaced,
+  OrderStatusChanged,
+  OrderPaid,
+  PickupSlotsChanged,
+  WebhookRegistered,
+  WebhookRemoved,
+  PermissionsChanged,
+  AccountUnlocked,
+  UserDeleted,
+  UserUpdated,
+  RoleChanged,
+  OwnerTransferOffered,
+  OwnerTransferAccepted,
+  OwnerTransferDeclined,
+  OwnerTransferCancelled,
+  EmailChanged,
+  PasswordSetUp,
+  MembersImported,
+  PayoutRequested,
+  PayoutBatchExported,
+  PayoutSettled,
+  PayoutRejected,
+  ChargebackResolved,
+  BalanceAlertCreated,
+  BalanceAlertRemoved,
+  ClientAppRegistered,
+  ClientAppRemoved,
+  ClientAppAuthorized,
+  AppTokenIssued,
+  AppTokenRevoked,
+  SettingsChanged,
+}
+
+impl Display for AuditAction {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let action_str = match self {
+      AuditAction::Login => "auth.login",
+      AuditAction::Logout => "auth.logout",
+      AuditAction::PasswordConfirmed => "auth.password_confirmed",
+      AuditAction::SessionRevoked => "session.revoked",
+      AuditAction::InviteSent => "invite.sent",
+      AuditAction::InviteResent => "invite.resent",
+      AuditAction::InviteRevoked => "invite.revoked",
+      AuditAction::InviteAccepted => "invite.accepted",
+      AuditAction::GuestCreated => "guest.created",
+      AuditAction::GuestIdentifierAssigned => "guest.identifier_assigned",
+      AuditAction::GuestIdentifierRotated => "guest.identifier_rotated",
+      AuditAction::GuestClaimed => "guest.claimed",
+      AuditAction::WalletToppedUp => "wallet.topped_up",
+      AuditAction::OnlineTopUpStarted => "wallet.online_top_up_started",
+      AuditAction::WalletWithdrawn => "wallet.withdrawn",
+      AuditAction::TransferSent => "transfer.sent",
+      AuditAction::TransactionRefunded => "transaction.refunded",
+      AuditAction::LegalHoldPlaced => "wallet.legal_hold_placed",
+      AuditAction::LegalHoldReleased => "wallet.legal_hold_released",
+      AuditAction::WalletAppearanceChanged => "wallet.appearance_changed",
+      AuditAction::BudgetEnvelopeCreated => "budget_envelope.created",
+      AuditAction::BudgetEnvelopeUpdated => "budget_envelope.updated",
+      AuditAction::BudgetEnvelopeRemoved => "budget_envelope.removed",
+      AuditAction::AccountNoteAdded => "account_note.added",
+      AuditAction::ReviewResolved => "review.resolved",
+      AuditAction::ShopMemberAdded => "shop.member_added",
+      AuditAction::ShopMemberRemoved => "shop.member_removed",
+      AuditAction::OrderPlaced => "order.placed",
+      AuditAction::OrderStatusChanged => "order.status_changed",
+      AuditAction::OrderPaid => "order.paid",
+      AuditAction::PickupSlotsChanged => "shop.pickup_slots_changed",
+      AuditAction::WebhookRegistered => "webhook.registered",
+      AuditAction::WebhookRemoved => "webhook.removed",
+      AuditAction::PermissionsChanged => "user.permissions_changed",
+      AuditAction::AccountUnlocked => "user.unlocked",
+      AuditAction::UserDeleted => "user.deleted",
+      AuditAction::UserUpdated => "user.updated",
+      AuditAction::RoleChanged => "user.role_changed",
+      AuditAction::OwnerTransferOffered => "owner_transfer.offered",
+      AuditAction::OwnerTransferAccepted => "owner_transfer.accepted",
+      AuditAction::OwnerTransferDeclined => "owner_transfer.declined",
+      AuditAction::OwnerTransferCancelled => "owner_transfer.cancelled",
+      AuditAction::EmailChanged => "user.email_changed",
+      AuditAction::PasswordSetUp => "user.password_set_up",
+      AuditAction::MembersImported => "members.imported",
+      AuditAction::PayoutRequested => "payout.requested",
+      AuditAction::PayoutBatchExported => "payout.batch_exported",
+      AuditAction::PayoutSettled => "payout.settled",
+      AuditAction::PayoutRejected => "payout.rejected",
+      AuditAction::ChargebackResolved => "chargeback.resolved",
+      AuditAction::BalanceAlertCreated => "balance_alert.created",
+      AuditAction::BalanceAlertRemoved => "balance_alert.removed",
+      AuditAction::ClientAppRegistered => "client_app.registered",
+      AuditAction::ClientAppRemoved => "client_app.removed",
+      AuditAction::ClientAppAuthorized => "client_app.authorized",
+      AuditAction::AppTokenIssued => "app_token.issued",
+      AuditAction::AppTokenRevoked => "app_token.revoked",
+      AuditAction::SettingsChanged => "settings.changed",
+    };
+    write!(f, "{}", action_str)
+  }
+}
+
+/// Who did what, to whom, when and from where.
+///
+/// Entries form a hash chain: each one's hash covers its content and the
+/// hash of the entry before it, so later changes are detectable.
+#[derive(Debug, Clone)]
+pub struct AuditEntry {
+  pub id: AuditEntryId,
+  /// Position in the chain, starting at 1
+  pub seq: i64,
+  pub actor_id: Option<ActorId>,
+  pub action: String,
+  pub target: Option<String>,
+  pub ip_address: Option<String>,
+  pub details: Option<Value>,
+  /// Not covered by the hash; `None` once the person it is about was erased
+  pub personal_data: Option<Value>,
+  /// Hash of the entry before, `None` for the first
+  pub previous_hash: Option<String>,
+  /// Hex SHA-256 over the entry and `previous_hash`; the IP address is left
+  /// out, as retention clears it
+  pub hash: String,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// Outcome of recomputing the audit log's hash chain.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditChainReport {
+  pub entries: i64,
+  /// Hash of the latest entry. Comparing it with one kept from an earlier
+  /// export also reveals entries removed from the end.
+  pub head_hash: Option<String>,
+  /// First entry whose hash or link doesn't match, or that follows a gap
+  pub first_broken: Option<(i64, AuditEntryId)>,
+}
+
+impl AuditChainReport {
+  pub fn is_intact(&self) -> bool {
+    self.first_broken.is_none()
+  }
+}
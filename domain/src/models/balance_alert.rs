@@ -0,0 +1,107 @@
+use std::fmt::Display;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::{ActorId, Email, Id, WalletLabel};
+
+pub type BalanceAlertId = Id<BalanceAlert>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum BalanceAlertDirection {
+  /// Fires when the balance rises above the threshold
+  Above,
+  /// Fires when the balance drops below the threshold
+  Below,
+}
+
+/// A threshold on a system wallet, or on the total of all member balances,
+/// that notifies the organisers once crossed.
+///
+/// An alert fires when the balance crosses the threshold and then stays quiet
+/// until the balance is back on the safe side.
+#[derive(Debug, Clone)]
+pub struct BalanceAlert {
+  pub id: BalanceAlertId,
+  /// System wallet watched; `None` watches the money owed to members, the sum
+  /// of all positive member balances
+  pub wallet_label: Option<WalletLabel>,
+  pub direction: BalanceAlertDirection,
+  pub threshold_cents: i64,
+  pub notify_email: Option<Email>,
+  pub webhook_url: Option<String>,
+  /// Key for the HMAC signature sent along with every webhook delivery
+  pub webhook_secret: Option<String>,
+  /// Whether the balance was past the threshold at the last check
+  pub breached: bool,
+  pub last_triggered_at: Option<DateTime<Utc>>,
+  pub created_by: Option<ActorId>,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl BalanceAlert {
+  /// Whether `balance_cents` is past the threshold.
+  pub fn is_breached_by(&self, balance_cents: i64) -> bool {
+    match self.direction {
+      BalanceAlertDirection::Above => balance_cents > self.threshold_cents,
+      BalanceAlertDirection::Below => balance_cents < self.threshold_cents,
+    }
+  }
+}
+
+impl Display for BalanceAlertDirection {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let direction_str = match self {
+      BalanceAlertDirection::Above => "above",
+      BalanceAlertDirection::Below => "below",
+    };
+    write!(f, "{}", direction_str)
+  }
+}
+
+impl From<&str> for BalanceAlertDirection {
+  fn from(value: &str) -> Self {
+    match value {
+      "below" => BalanceAlertDirection::Below,
+      _ => BalanceAlertDirection::Above,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use uuid::Uuid;
+
+  fn alert(direction: BalanceAlertDirection, threshold_cents: i64) -> BalanceAlert {
+    BalanceAlert {
+      id: Uuid::new_v4().into(),
+      wallet_label: None,
+      direction,
+      threshold_cents,
+      notify_email: None,
+      webhook_url: None,
+      webhook_secret: None,
+      breached: false,
+      last_triggered_at: None,
+      created_by: None,
+      created_at: Utc::now(),
+      updated_at: None,
+    }
+  }
+
+  #[test]
+  fn test_is_breached_by() {
+    let above = alert(BalanceAlertDirection::Above, 100_000);
+    assert!(!above.is_breached_by(100_000));
+    assert!(above.is_breached_by(100_001));
+
+    let below = alert(BalanceAlertDirection::Below, -5_000);
+    assert!(!below.is_breached_by(-5_000));
+    assert!(below.is_breached_by(-5_001));
+    assert!(!below.is_breached_by(0));
+  }
+}
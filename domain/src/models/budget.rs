@@ -0,0 +1,140 @@
+use chrono::{DateTime, Utc};
+
+use crate::{types::Money, Id, ShopId, WalletId};
+
+/// Longest envelope name, in characters
+const MAX_NAME_LEN: usize = 40;
+/// Most shops one envelope covers
+const MAX_SHOPS: usize = 50;
+
+pub type BudgetEnvelopeId = Id<BudgetEnvelope>;
+
+/// Part of a personal wallet's balance the owner earmarked for spending at
+/// some shops, e.g. "Drinks" at the bars. Purely informational: payments
+/// past it go through like any other.
+#[derive(Debug, Clone)]
+pub struct BudgetEnvelope {
+  pub id: BudgetEnvelopeId,
+  pub wallet_id: WalletId,
+  pub plan: BudgetPlan,
+  /// Paid at the envelope's shops since `spent_since`, less refunds
+  pub spent: Money,
+  pub spent_since: DateTime<Utc>,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl BudgetEnvelope {
+  /// What is left to spend; negative once overspent.
+  pub fn remaining(&self) -> Money {
+    self.plan.amount - self.spent
+  }
+
+  pub fn is_overspent(&self) -> bool {
+    self.spent > self.plan.amount
+  }
+}
+
+/// Name, amount and shops of an envelope. Only constructed through
+/// [`BudgetPlan::new`] or read back from the database.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BudgetPlan {
+  name: String,
+  amount: Money,
+  /// Sorted, without duplicates
+  shops: Vec<ShopId>,
+}
+
+impl BudgetPlan {
+  /// Trims the name and drops repeated shops.
+  pub fn new(name: String, amount: Money, mut shops: Vec<ShopId>) -> Result<Self, String> {
+    let name = name.trim().to_string();
+    if name.is_empty() || name.chars().count() > MAX_NAME_LEN {
+      return Err(format!(
+        "Envelope name must have 1 to {} characters",
+        MAX_NAME_LEN
+      ));
+    }
+    if !amount.is_positive() {
+      return Err("Envelope amount must be positive".to_string());
+    }
+
+    shops.sort_by_key(|shop| shop.into_inner());
+    shops.dedup();
+    if shops.is_empty() || shops.len() > MAX_SHOPS {
+      return Err(format!("Envelope must cover 1 to {} shops", MAX_SHOPS));
+    }
+
+    Ok(Self {
+      name,
+      amount,
+      shops,
+    })
+  }
+
+  /// Plan as stored, which was checked when it was written.
+  pub fn from_stored(name: String, amount: Money, shops: Vec<ShopId>) -> Self {
+    Self {
+      name,
+      amount,
+      shops,
+    }
+  }
+
+  pub fn name(&self) -> &str {
+    &self.name
+  }
+
+  pub fn amount(&self) -> Money {
+    self.amount
+  }
+
+  pub fn shops(&self) -> &[ShopId] {
+    &self.shops
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_plan_is_checked() {
+    let bar = ShopId::new();
+    let plan = BudgetPlan::new(
+      " Drinks ".to_string(),
+      Money::from_major(20),
+      vec![bar, bar],
+    )
+    .unwrap();
+    assert_eq!(plan.name(), "Drinks");
+    assert_eq!(plan.shops(), &[bar]);
+
+    assert!(BudgetPlan::new(" ".to_string(), Money::from_major(20), vec![bar]).is_err());
+    assert!(BudgetPlan::new("Drinks".to_string(), Money::ZERO, vec![bar]).is_err());
+    assert!(BudgetPlan::new("Drinks".to_string(), Money::from_major(20), vec![]).is_err());
+  }
+
+  #[test]
+  fn test_remaining_goes_negative_once_overspent() {
+    let mut envelope = BudgetEnvelope {
+      id: BudgetEnvelopeId::new(),
+      wallet_id: WalletId::new(),
+      plan: BudgetPlan::from_stored(
+        "Food".to_string(),
+        Money::from_major(10),
+        vec![ShopId::new()],
+      ),
+      spent: Money::from_major(4),
+      spent_since: Utc::now(),
+      created_at: Utc::now(),
+      updated_at: None,
+    };
+    assert_eq!(envelope.remaining(), Money::from_major(6));
+    assert!(!envelope.is_overspent());
+
+    envelope.spent = Money::from_major(12);
+    assert_eq!(envelope.remaining(), Money::from_major(-2));
+    assert!(envelope.is_overspent());
+  }
+}
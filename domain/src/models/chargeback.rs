@@ -0,0 +1,149 @@
+use std::fmt::Display;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::{types::Money, ActorId, Id, TopUpId, TransactionId, WalletId};
+
+pub type ChargebackId = Id<Chargeback>;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ChargebackStatus {
+  /// Disputed at the provider; the amount has been taken back from the wallet
+  #[default]
+  Open,
+  /// Decided in the organiser's favour; the amount went back to the wallet
+  Won,
+  /// Decided in the payer's favour
+  Lost,
+}
+
+/// A payer disputing an online top-up with their bank. The provider takes the
+/// money back, so the wallet is debited right away, even into the negative.
+///
+/// Until staff resolve it, the wallet cannot be topped up online again.
+#[derive(Debug, Clone)]
+pub struct Chargeback {
+  pub id: ChargebackId,
+  pub top_up_id: TopUpId,
+  pub wallet_id: WalletId,
+  pub amount: Money,
+  pub provider_dispute_id: String,
+  pub status: ChargebackStatus,
+  /// Debit of the wallet
+  pub transaction_id: TransactionId,
+  /// Credit back to the wallet, for won disputes
+  pub reversal_transaction_id: Option<TransactionId>,
+  pub resolved_by: Option<ActorId>,
+  pub resolution_note: Option<String>,
+  pub resolved_at: Option<DateTime<Utc>>,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// Sums over a set of chargebacks.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ChargebackTotals {
+  pub count: u32,
+  pub unresolved_count: u32,
+  /// Taken back by the provider and not returned
+  pub lost_cents: i64,
+  /// Still disputed
+  pub open_cents: i64,
+  /// Returned after won disputes
+  pub won_cents: i64,
+}
+
+impl Chargeback {
+  pub fn is_resolved(&self) -> bool {
+    self.resolved_at.is_some()
+  }
+}
+
+impl ChargebackTotals {
+  pub fn of(chargebacks: &[Chargeback]) -> Self {
+    chargebacks
+      .iter()
+      .fold(Self::default(), |mut totals, chargeback| {
+        let cents = i64::from(chargeback.amount.as_minor());
+        totals.count += 1;
+        if !chargeback.is_resolved() {
+          totals.unresolved_count += 1;
+        }
+        match chargeback.status {
+          ChargebackStatus::Open => totals.open_cents += cents,
+          ChargebackStatus::Won => totals.won_cents += cents,
+          ChargebackStatus::Lost => totals.lost_cents += cents,
+        }
+        totals
+      })
+  }
+}
+
+impl Display for ChargebackStatus {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let status_str = match self {
+      ChargebackStatus::Open => "open",
+      ChargebackStatus::Won => "won",
+      ChargebackStatus::Lost => "lost",
+    };
+    write!(f, "{}", status_str)
+  }
+}
+
+impl From<&str> for ChargebackStatus {
+  fn from(value: &str) -> Self {
+    match value {
+      "won" => ChargebackStatus::Won,
+      "lost" => ChargebackStatus::Lost,
+      _ => ChargebackStatus::Open,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use uuid::Uuid;
+
+  fn chargeback(cents: i32, status: ChargebackStatus, resolved: bool) -> Chargeback {
+    Chargeback {
+      id: Uuid::new_v4().into(),
+      top_up_id: Uuid::new_v4().into(),
+      wallet_id: Uuid::new_v4().into(),
+      amount: Money::from_minor(cents),
+      provider_dispute_id: "dp_test".to_string(),
+      status,
+      transaction_id: Uuid::new_v4().into(),
+      reversal_transaction_id: None,
+      resolved_by: None,
+      resolution_note: None,
+      resolved_at: resolved.then(Utc::now),
+      created_at: Utc::now(),
+      updated_at: None,
+    }
+  }
+
+  #[test]
+  fn test_totals() {
+    let totals = ChargebackTotals::of(&[
+      chargeback(1000, ChargebackStatus::Open, false),
+      chargeback(500, ChargebackStatus::Lost, false),
+      chargeback(250, ChargebackStatus::Lost, true),
+      chargeback(2000, ChargebackStatus::Won, true),
+    ]);
+
+    assert_eq!(
+      totals,
+      ChargebackTotals {
+        count: 4,
+        unresolved_count: 2,
+        lost_cents: 750,
+        open_cents: 1000,
+        won_cents: 2000,
+      }
+    );
+  }
+}
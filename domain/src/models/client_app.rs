@@ -0,0 +1,218 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fmt::Display;
+use utoipa::ToSchema;
+
+use crate::{ActorId, Id, UserId};
+
+pub type ClientAppId = Id<ClientApp>;
+pub type AuthorizationCodeId = Id<AuthorizationCode>;
+pub type AccessTokenId = Id<AccessToken>;
+
+/// Part of a user's access an app can ask for. Requests with an access
+/// token only reach the routes of its scopes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+pub enum Scope {
+  /// Who the user is
+  #[serde(rename = "profile")]
+  Profile,
+  /// The user's wallet, orders and favorites
+  #[serde(rename = "wallet")]
+  Wallet,
+  /// Pre-order at shops and keep favorites
+  #[serde(rename = "preorders")]
+  Preorders,
+  /// Send money to other users
+  #[serde(rename = "transfers")]
+  Transfers,
+  /// Look up and register guests and top up wallets, as at a till
+  #[serde(rename = "till")]
+  Till,
+  /// Take, pay and advance orders at the user's shops
+  #[serde(rename = "orders")]
+  Orders,
+  /// Read the event log, e.g. to keep a data warehouse in sync
+  #[serde(rename = "events")]
+  Events,
+}
+
+/// A third-party application, such as a companion app or a kiosk, that
+/// users can grant scoped access to their account.
+#[derive(Debug, Clone)]
+pub struct ClientApp {
+  pub id: ClientAppId,
+  pub name: String,
+  /// Where codes may be sent back to; requests must match one exactly
+  pub redirect_uris: Vec<String>,
+  /// Scopes the app may ask users for
+  pub allowed_scopes: Vec<Scope>,
+  pub created_by: Option<ActorId>,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// A user's approval of an app, exchanged once for an [`AccessToken`].
+#[derive(Debug, Clone)]
+pub struct AuthorizationCode {
+  pub id: AuthorizationCodeId,
+  pub client_app_id: ClientAppId,
+  pub user_id: UserId,
+  pub code: String,
+  pub redirect_uri: String,
+  pub scopes: Vec<Scope>,
+  /// PKCE `S256` challenge the app sent along with the request
+  pub code_challenge: String,
+  pub expires_at: DateTime<Utc>,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// Bearer token acting for a user within the scopes they approved.
+#[derive(Debug, Clone)]
+pub struct AccessToken {
+  pub id: AccessTokenId,
+  pub client_app_id: ClientAppId,
+  pub user_id: UserId,
+  pub token: String,
+  pub scopes: Vec<Scope>,
+  pub expires_at: DateTime<Utc>,
+  pub last_used_at: Option<DateTime<Utc>>,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl Scope {
+  pub fn variants() -> &'static [Scope] {
+    &[
+      Scope::Profile,
+      Scope::Wallet,
+      Scope::Preorders,
+      Scope::Transfers,
+      Scope::Till,
+      Scope::Orders,
+      Scope::Events,
+    ]
+  }
+}
+
+impl Display for Scope {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let scope_str = match self {
+      Scope::Profile => "profile",
+      Scope::Wallet => "wallet",
+      Scope::Preorders => "preorders",
+      Scope::Transfers => "transfers",
+      Scope::Till => "till",
+      Scope::Orders => "orders",
+      Scope::Events => "events",
+    };
+    write!(f, "{}", scope_str)
+  }
+}
+
+impl From<&str> for Scope {
+  fn from(value: &str) -> Self {
+    match value {
+      "wallet" => Scope::Wallet,
+      "preorders" => Scope::Preorders,
+      "transfers" => Scope::Transfers,
+      "till" => Scope::Till,
+      "orders" => Scope::Orders,
+      "events" => Scope::Events,
+      _ => Scope::Profile,
+    }
+  }
+}
+
+impl ClientApp {
+  pub fn has_redirect_uri(&self, redirect_uri: &str) -> bool {
+    self.redirect_uris.iter().any(|uri| uri == redirect_uri)
+  }
+
+  pub fn allows_scopes(&self, scopes: &[Scope]) -> bool {
+    scopes.iter().all(|s| self.allowed_scopes.contains(s))
+  }
+}
+
+impl AuthorizationCode {
+  pub fn is_expired(&self) -> bool {
+    Utc::now() > self.expires_at
+  }
+
+  /// Whether the verifier is the one the challenge was derived from, per
+  /// PKCE's `S256` method.
+  pub fn is_verified_by(&self, code_verifier: &str) -> bool {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(code_verifier.as_bytes())) == self.code_challenge
+  }
+}
+
+impl AccessToken {
+  pub fn is_expired(&self) -> bool {
+    Utc::now() > self.expires_at
+  }
+
+  pub fn has_scope(&self, scope: Scope) -> bool {
+    self.scopes.contains(&scope)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use chrono::Duration;
+
+  fn code(code_challenge: &str) -> AuthorizationCode {
+    AuthorizationCode {
+      id: Id::new(),
+      client_app_id: Id::new(),
+      user_id: Id::new(),
+      code: "code".to_string(),
+      redirect_uri: "app://callback".to_string(),
+      scopes: vec![Scope::Profile],
+      code_challenge: code_challenge.to_string(),
+      expires_at: Utc::now() + Duration::minutes(10),
+      created_at: Utc::now(),
+      updated_at: None,
+    }
+  }
+
+  #[test]
+  fn test_scope_names_round_trip() {
+    for scope in Scope::variants() {
+      assert_eq!(Scope::from(scope.to_string().as_str()), *scope);
+      assert_eq!(
+        serde_json::to_value(scope).unwrap(),
+        serde_json::Value::String(scope.to_string())
+      );
+    }
+  }
+
+  #[test]
+  fn test_code_verifier_matches_s256_challenge() {
+    // Example from RFC 7636, appendix B
+    let code = code("E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM");
+
+    assert!(code.is_verified_by("dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk"));
+    assert!(!code.is_verified_by("dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXj"));
+  }
+
+  #[test]
+  fn test_client_app_checks_redirect_uris_and_scopes() {
+    let app = ClientApp {
+      id: Id::new(),
+      name: "Kiosk".to_string(),
+      redirect_uris: vec!["https://kiosk.example.com/callback".to_string()],
+      allowed_scopes: vec![Scope::Till, Scope::Profile],
+      created_by: None,
+      created_at: Utc::now(),
+      updated_at: None,
+    };
+
+    assert!(app.has_redirect_uri("https://kiosk.example.com/callback"));
+    assert!(!app.has_redirect_uri("https://kiosk.example.com/callback/"));
+    assert!(app.allows_scopes(&[Scope::Till]));
+    assert!(!app.allows_scopes(&[Scope::Till, Scope::Transfers]));
+  }
+}
@@ -0,0 +1,243 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::{types::Currency, ActorId, ActorKind, Email, Id, WalletId};
+
+pub type DebtId = Id<Debt>;
+
+/// A member's wallet below zero, e.g. after a chargeback, from when that was
+/// noticed until the balance is back at zero. Settled debts are kept as the
+/// history of who owed what.
+#[derive(Debug, Clone)]
+pub struct Debt {
+  pub id: DebtId,
+  pub wallet_id: WalletId,
+  /// When the balance was first seen below zero
+  pub since: DateTime<Utc>,
+  /// When the grace period ran out and reminders started
+  pub flagged_at: Option<DateTime<Utc>>,
+  pub reminders_sent: i32,
+  pub last_reminded_at: Option<DateTime<Utc>>,
+  /// When the balance was seen back at zero or above
+  pub settled_at: Option<DateTime<Utc>>,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// When debts are flagged and how often their owners are reminded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DebtRecoveryPolicy {
+  /// Days a wallet may stay below zero before it is flagged
+  pub grace_days: u32,
+  /// Days between two reminders
+  pub reminder_interval_days: u32,
+  /// Reminders sent at most per debt; 0 sends none
+  pub max_reminders: u32,
+}
+
+/// How long a debt has been open, in the usual 30 day steps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, ToSchema)]
+pub enum DebtAge {
+  #[serde(rename = "0-30")]
+  UpTo30Days,
+  #[serde(rename = "31-60")]
+  UpTo60Days,
+  #[serde(rename = "61-90")]
+  UpTo90Days,
+  #[serde(rename = "90+")]
+  Over90Days,
+}
+
+/// An open debt with the wallet's balance and who owes it.
+#[derive(Debug, Clone)]
+pub struct Debtor {
+  pub debt: Debt,
+  pub balance_cents: i64,
+  /// What the balance is counted in
+  pub currency: Currency,
+  pub owner: ActorId,
+  pub kind: Option<ActorKind>,
+  /// First and last name of users
+  pub display_name: Option<String>,
+  pub email: Option<Email>,
+}
+
+/// Sums over the debtors of one age who owe in one currency.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DebtAgeTotals {
+  pub age: DebtAge,
+  pub currency: Currency,
+  pub count: u32,
+  /// Owed, as a positive amount
+  pub owed_cents: i64,
+}
+
+impl Debt {
+  pub fn age(&self, now: DateTime<Utc>) -> DebtAge {
+    DebtAge::of(now - self.since)
+  }
+}
+
+impl DebtRecoveryPolicy {
+  /// Debts open since before this are flagged.
+  pub fn flag_cutoff(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+    now - Duration::days(i64::from(self.grace_days))
+  }
+
+  /// Debts last reminded before this are due another reminder.
+  pub fn reminder_cutoff(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+    now - Duration::days(i64::from(self.reminder_interval_days))
+  }
+}
+
+impl DebtAge {
+  pub fn variants() -> &'static [DebtAge] {
+    &[
+      DebtAge::UpTo30Days,
+      DebtAge::UpTo60Days,
+      DebtAge::UpTo90Days,
+      DebtAge::Over90Days,
+    ]
+  }
+
+  /// The age of a debt open for `open`; a started day counts as a full one.
+  pub fn of(open: Duration) -> Self {
+    let days = open.num_days() + i64::from(open.num_seconds() % 86_400 != 0);
+    match days {
+      ..=30 => DebtAge::UpTo30Days,
+      31..=60 => DebtAge::UpTo60Days,
+      61..=90 => DebtAge::UpTo90Days,
+      _ => DebtAge::Over90Days,
+    }
+  }
+}
+
+impl DebtAgeTotals {
+  /// Totals for every age in every currency somebody owes in, including ages
+  /// nobody is in. Grouped by currency, in the order of their codes.
+  pub fn of(debtors: &[Debtor], now: DateTime<Utc>) -> Vec<Self> {
+    let mut currencies: Vec<Currency> = Vec::new();
+    for debtor in debtors {
+      if !currencies.contains(&debtor.currency) {
+        currencies.push(debtor.currency);
+      }
+    }
+    currencies.sort_by(|a, b| a.code().cmp(b.code()));
+
+    currencies
+      .into_iter()
+      .flat_map(|currency| {
+        DebtAge::variants().iter().map(move |age| {
+          debtors
+            .iter()
+            .filter(|debtor| debtor.currency == currency && debtor.debt.age(now) == *age)
+            .fold(
+              Self {
+                age: *age,
+                currency,
+                count: 0,
+                owed_cents: 0,
+              },
+              |mut totals, debtor| {
+                totals.count += 1;
+                totals.owed_cents -= debtor.balance_cents.min(0);
+                totals
+              },
+            )
+        })
+      })
+      .collect()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use uuid::Uuid;
+
+  fn debtor(days_ago: i64, balance_cents: i64, now: DateTime<Utc>) -> Debtor {
+    debtor_in(days_ago, balance_cents, Currency::EUR, now)
+  }
+
+  fn debtor_in(
+    days_ago: i64,
+    balance_cents: i64,
+    currency: Currency,
+    now: DateTime<Utc>,
+  ) -> Debtor {
+    Debtor {
+      debt: Debt {
+        id: Uuid::new_v4().into(),
+        wallet_id: Uuid::new_v4().into(),
+        since: now - Duration::days(days_ago),
+        flagged_at: None,
+        reminders_sent: 0,
+        last_reminded_at: None,
+        settled_at: None,
+        created_at: now,
+        updated_at: None,
+      },
+      balance_cents,
+      currency,
+      owner: Uuid::new_v4().into(),
+      kind: Some(ActorKind::User),
+      display_name: None,
+      email: None,
+    }
+  }
+
+  #[test]
+  fn test_age_counts_started_days() {
+    assert_eq!(DebtAge::of(Duration::zero()), DebtAge::UpTo30Days);
+    assert_eq!(DebtAge::of(Duration::days(30)), DebtAge::UpTo30Days);
+    assert_eq!(
+      DebtAge::of(Duration::days(30) + Duration::minutes(1)),
+      DebtAge::UpTo60Days
+    );
+    assert_eq!(DebtAge::of(Duration::days(90)), DebtAge::UpTo90Days);
+    assert_eq!(DebtAge::of(Duration::days(91)), DebtAge::Over90Days);
+  }
+
+  #[test]
+  fn test_totals_cover_every_age() {
+    let now = Utc::now();
+    let debtors = [
+      debtor(3, -500, now),
+      debtor(10, -250, now),
+      debtor(120, -1000, now),
+    ];
+
+    let totals = DebtAgeTotals::of(&debtors, now);
+
+    assert_eq!(totals.len(), DebtAge::variants().len());
+    assert_eq!((totals[0].count, totals[0].owed_cents), (2, 750));
+    assert_eq!((totals[1].count, totals[1].owed_cents), (0, 0));
+    assert_eq!((totals[3].count, totals[3].owed_cents), (1, 1000));
+  }
+
+  #[test]
+  fn test_totals_keep_currencies_apart() {
+    let now = Utc::now();
+    let chf = Currency::parse("CHF").unwrap();
+    let debtors = [
+      debtor(3, -500, now),
+      debtor_in(5, -300, chf, now),
+      debtor_in(100, -200, chf, now),
+    ];
+
+    let totals = DebtAgeTotals::of(&debtors, now);
+
+    assert_eq!(totals.len(), 2 * DebtAge::variants().len());
+    assert!(totals[..4].iter().all(|totals| totals.currency == chf));
+    assert_eq!((totals[0].count, totals[0].owed_cents), (1, 300));
+    assert_eq!((totals[3].count, totals[3].owed_cents), (1, 200));
+    assert_eq!(totals[4].currency, Currency::EUR);
+    assert_eq!((totals[4].count, totals[4].owed_cents), (1, 500));
+  }
+
+  #[test]
+  fn test_totals_without_debtors_are_empty() {
+    assert!(DebtAgeTotals::of(&[], Utc::now()).is_empty());
+  }
+}
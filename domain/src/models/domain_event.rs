@@ -0,0 +1,301 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+  types::Currency, ActorId, ChargebackId, Id, InviteId, Order, OrderId, OrderItem, OrderStatus,
+  ReviewItemId, RiskSignal, Role, ShopId, ShopOfferingId, Transaction, TransactionId,
+  TransactionMetadata, UserId, WalletId, WebhookEvent,
+};
+
+pub type DomainEventId = Id<DomainEventRecord>;
+
+/// Something that happened, recorded in the transaction that made it happen.
+/// Subscribers such as webhooks and notices react to it once that
+/// transaction has committed, so features only need to say what happened.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data")]
+pub enum DomainEvent {
+  /// Someone got an account, through an invite or an import
+  #[serde(rename = "user.registered")]
+  UserRegistered { user_id: UserId, role: Role },
+  /// A user was signed out everywhere, e.g. because their role changed
+  #[serde(rename = "sessions.revoked")]
+  SessionsRevoked { user_id: UserId },
+  /// Someone joined through an invite
+  #[serde(rename = "invite.accepted")]
+  InviteAccepted {
+    invite_id: InviteId,
+    user_id: UserId,
+    role: Role,
+    invited_by: UserId,
+    accepted_at: DateTime<Utc>,
+  },
+  /// Money moved between two wallets, including refunds and reversals
+  #[serde(rename = "transaction.created")]
+  TransactionCreated(TransactionEventData),
+  /// A user sent money to another user
+  #[serde(rename = "transfer.sent")]
+  TransferSent {
+    transaction_id: TransactionId,
+    sender_id: UserId,
+    recipient_id: UserId,
+    amount_cents: i32,
+    /// Euros when missing, as in events recorded before wallets had one
+    #[serde(default)]
+    currency: Currency,
+    /// Whether the sender asked for the recipient to be told
+    notify_recipient: bool,
+  },
+  /// The provider took back the money of an online top-up
+  #[serde(rename = "chargeback.opened")]
+  ChargebackOpened {
+    chargeback_id: ChargebackId,
+    wallet_id: WalletId,
+    amount_cents: i32,
+    /// The wallet's balance right after
+    balance_cents: i32,
+  },
+  /// A wallet was frozen on suspicion of abuse
+  #[serde(rename = "wallet.frozen")]
+  WalletFrozen {
+    wallet_id: WalletId,
+    review_item_id: ReviewItemId,
+    signal: RiskSignal,
+  },
+  /// A frozen wallet was released after review
+  #[serde(rename = "wallet.unfrozen")]
+  WalletUnfrozen {
+    wallet_id: WalletId,
+    review_item_id: ReviewItemId,
+    resolved_by: ActorId,
+  },
+  #[serde(rename = "order.created")]
+  OrderCreated(OrderEventData),
+  /// A pre-order's pickup slot is coming up and it should be prepared
+  #[serde(rename = "order.due")]
+  OrderDue(OrderEventData),
+  #[serde(rename = "order.refunded")]
+  OrderRefunded {
+    order_id: OrderId,
+    shop_id: ShopId,
+    refund_transaction_id: TransactionId,
+    amount_cents: i32,
+    refunded_at: DateTime<Utc>,
+  },
+}
+
+/// A transaction as events describe it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TransactionEventData {
+  pub transaction_id: TransactionId,
+  pub source_wallet_id: WalletId,
+  pub destination_wallet_id: WalletId,
+  pub amount_cents: i32,
+  #[serde(default)]
+  pub currency: Currency,
+  pub description: Option<String>,
+  pub reversal_of: Option<TransactionId>,
+  pub metadata: TransactionMetadata,
+  pub created_at: DateTime<Utc>,
+}
+
+/// An order and its items as events describe them.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OrderEventData {
+  pub id: OrderId,
+  pub shop_id: ShopId,
+  pub payer_wallet_id: WalletId,
+  pub transaction_id: Option<TransactionId>,
+  pub total_cents: i32,
+  pub status: OrderStatus,
+  pub table_label: Option<String>,
+  pub pickup_number: Option<i32>,
+  pub items: Vec<OrderItemEventData>,
+  /// Metadata of the payment, if it was paid when placed
+  #[serde(default, skip_serializing_if = "TransactionMetadata::is_empty")]
+  pub transaction_metadata: TransactionMetadata,
+  pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OrderItemEventData {
+  pub offering_id: Option<ShopOfferingId>,
+  pub name: String,
+  pub unit_price_cents: i32,
+  pub quantity: i32,
+}
+
+/// A domain event as kept in the outbox.
+#[derive(Debug, Clone)]
+pub struct DomainEventRecord {
+  pub id: DomainEventId,
+  /// Position in the order events were recorded in
+  pub sequence: i64,
+  pub event: DomainEvent,
+  pub occurred_at: DateTime<Utc>,
+  /// When subscribers were handed the event; unset until then
+  pub dispatched_at: Option<DateTime<Utc>>,
+}
+
+impl DomainEvent {
+  /// Dotted name, as in the serialized `type`.
+  pub fn name(&self) -> &'static str {
+    match self {
+      DomainEvent::UserRegistered { .. } => "user.registered",
+      DomainEvent::SessionsRevoked { .. } => "sessions.revoked",
+      DomainEvent::InviteAccepted { .. } => "invite.accepted",
+      DomainEvent::TransactionCreated(_) => "transaction.created",
+      DomainEvent::TransferSent { .. } => "transfer.sent",
+      DomainEvent::ChargebackOpened { .. } => "chargeback.opened",
+      DomainEvent::WalletFrozen { .. } => "wallet.frozen",
+      DomainEvent::WalletUnfrozen { .. } => "wallet.unfrozen",
+      DomainEvent::OrderCreated(_) => "order.created",
+      DomainEvent::OrderDue(_) => "order.due",
+      DomainEvent::OrderRefunded { .. } => "order.refunded",
+    }
+  }
+
+  /// The shop the event happened at, if any.
+  pub fn shop_id(&self) -> Option<ShopId> {
+    match self {
+      DomainEvent::OrderCreated(order) | DomainEvent::OrderDue(order) => Some(order.shop_id),
+      DomainEvent::OrderRefunded { shop_id, .. } => Some(*shop_id),
+      _ => None,
+    }
+  }
+
+  /// The webhook event this is sent to webhooks as, if it is sent at all.
+  pub fn webhook_event(&self) -> Option<WebhookEvent> {
+    match self {
+      DomainEvent::UserRegistered { .. } => Some(WebhookEvent::UserRegistered),
+      DomainEvent::SessionsRevoked { .. } => None,
+      DomainEvent::InviteAccepted { .. } => Some(WebhookEvent::InviteAccepted),
+      DomainEvent::TransactionCreated(_) => Some(WebhookEvent::TransactionCreated),
+      // Already sent as the transaction they booked
+      DomainEvent::TransferSent { .. } | DomainEvent::ChargebackOpened { .. } => None,
+      DomainEvent::WalletFrozen { .. } => Some(WebhookEvent::WalletFrozen),
+      DomainEvent::WalletUnfrozen { .. } => Some(WebhookEvent::WalletUnfrozen),
+      DomainEvent::OrderCreated(_) => Some(WebhookEvent::OrderCreated),
+      DomainEvent::OrderDue(_) => Some(WebhookEvent::OrderDue),
+      DomainEvent::OrderRefunded { .. } => Some(WebhookEvent::OrderRefunded),
+    }
+  }
+
+  /// What happened, without the event's name.
+  pub fn data(&self) -> Value {
+    let mut value = serde_json::to_value(self).expect("domain event serializes");
+    value["data"].take()
+  }
+}
+
+impl From<&Transaction> for TransactionEventData {
+  fn from(transaction: &Transaction) -> Self {
+    Self {
+      transaction_id: transaction.id,
+      source_wallet_id: transaction.source,
+      destination_wallet_id: transaction.destination,
+      amount_cents: transaction.amount.as_minor(),
+      currency: transaction.currency,
+      description: transaction.description.clone(),
+      reversal_of: transaction.reversal_of,
+      metadata: transaction.metadata.clone(),
+      created_at: transaction.created_at,
+    }
+  }
+}
+
+impl OrderEventData {
+  pub fn new(order: &Order, items: &[OrderItem]) -> Self {
+    Self {
+      id: order.id,
+      shop_id: order.shop_id,
+      payer_wallet_id: order.payer_wallet_id,
+      transaction_id: order.transaction_id,
+      total_cents: order.total.as_minor(),
+      status: order.status,
+      table_label: order.table_label.clone(),
+      pickup_number: order.pickup_number,
+      items: items
+        .iter()
+        .map(|item| OrderItemEventData {
+          offering_id: item.offering_id,
+          name: item.name.clone(),
+          unit_price_cents: item.unit_price.as_minor(),
+          quantity: item.quantity,
+        })
+        .collect(),
+      transaction_metadata: TransactionMetadata::default(),
+      created_at: order.created_at,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use serde_json::json;
+  use uuid::Uuid;
+
+  fn order_event() -> DomainEvent {
+    DomainEvent::OrderDue(OrderEventData {
+      id: Uuid::nil().into(),
+      shop_id: Uuid::nil().into(),
+      payer_wallet_id: Uuid::nil().into(),
+      transaction_id: None,
+      total_cents: 350,
+      status: OrderStatus::default(),
+      table_label: None,
+      pickup_number: Some(7),
+      items: vec![],
+      transaction_metadata: TransactionMetadata::default(),
+      created_at: DateTime::UNIX_EPOCH,
+    })
+  }
+
+  #[test]
+  fn test_name_matches_serialized_type() {
+    let events = [
+      DomainEvent::UserRegistered {
+        user_id: Uuid::nil().into(),
+        role: Role::Cashier,
+      },
+      DomainEvent::ChargebackOpened {
+        chargeback_id: Uuid::nil().into(),
+        wallet_id: Uuid::nil().into(),
+        amount_cents: 100,
+        balance_cents: -100,
+      },
+      order_event(),
+    ];
+
+    for event in events {
+      let value = serde_json::to_value(&event).unwrap();
+      assert_eq!(value["type"], event.name());
+      assert_eq!(serde_json::from_value::<DomainEvent>(value).unwrap(), event);
+    }
+  }
+
+  #[test]
+  fn test_data_leaves_out_name_and_empty_metadata() {
+    let data = order_event().data();
+
+    assert_eq!(data["total_cents"], json!(350));
+    assert!(data.get("type").is_none());
+    assert!(data.get("transaction_metadata").is_none());
+  }
+
+  #[test]
+  fn test_only_shop_events_name_a_shop() {
+    let event = order_event();
+    assert!(event.webhook_event().is_some_and(|e| e.concerns_shop()));
+    assert!(event.shop_id().is_some());
+
+    let event = DomainEvent::UserRegistered {
+      user_id: Uuid::nil().into(),
+      role: Role::Cashier,
+    };
+    assert!(!event.webhook_event().is_some_and(|e| e.concerns_shop()));
+    assert!(event.shop_id().is_none());
+  }
+}
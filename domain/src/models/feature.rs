@@ -0,0 +1,104 @@
+use std::{fmt::Display, str::FromStr};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::ActorId;
+
+/// A capability operators can switch off at runtime, e.g. while an incident
+/// is under way.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Feature {
+  /// Topping up wallets through the payment provider
+  ExternalTopUps,
+  /// Members sending money to each other
+  P2pTransfers,
+  /// Charging guests for orders
+  GuestCharges,
+}
+
+/// Whether a feature is on, and who switched it last. Features nobody
+/// switched are on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeatureSwitch {
+  pub feature: Feature,
+  pub enabled: bool,
+  /// Shown to callers while the feature is off
+  pub reason: Option<String>,
+  pub changed_by: Option<ActorId>,
+  pub changed_at: Option<DateTime<Utc>>,
+}
+
+impl Feature {
+  pub fn variants() -> &'static [Feature] {
+    &[
+      Feature::ExternalTopUps,
+      Feature::P2pTransfers,
+      Feature::GuestCharges,
+    ]
+  }
+
+  /// What callers are told it is, e.g. in errors.
+  pub fn describe(&self) -> &'static str {
+    match self {
+      Feature::ExternalTopUps => "Topping up online",
+      Feature::P2pTransfers => "Sending money to other members",
+      Feature::GuestCharges => "Charging guests",
+    }
+  }
+}
+
+impl FeatureSwitch {
+  /// The switch of a feature nobody switched.
+  pub fn untouched(feature: Feature) -> Self {
+    Self {
+      feature,
+      enabled: true,
+      reason: None,
+      changed_by: None,
+      changed_at: None,
+    }
+  }
+}
+
+impl Display for Feature {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let feature_str = match self {
+      Feature::ExternalTopUps => "external_top_ups",
+      Feature::P2pTransfers => "p2p_transfers",
+      Feature::GuestCharges => "guest_charges",
+    };
+    write!(f, "{}", feature_str)
+  }
+}
+
+impl FromStr for Feature {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    Feature::variants()
+      .iter()
+      .find(|feature| feature.to_string() == s)
+      .copied()
+      .ok_or_else(|| format!("Unknown feature {}", s))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_names_round_trip() {
+    for feature in Feature::variants() {
+      assert_eq!(feature.to_string().parse::<Feature>(), Ok(*feature));
+      assert_eq!(
+        serde_json::to_value(feature).unwrap(),
+        serde_json::Value::String(feature.to_string())
+      );
+    }
+    assert!("payouts".parse::<Feature>().is_err());
+  }
+}
@@ -1,8 +1,9 @@
 use chrono::{DateTime, Utc};
 
-use crate::{actor::ActorId, Email, Id};
+use crate::{actor::ActorId, types::Money, Email, Id, TransactionId, UserId};
 
 pub type GuestId = Id<Guest>;
+pub type GuestClaimId = Id<GuestClaim>;
 
 #[derive(Debug, Clone)]
 pub struct Guest {
@@ -10,6 +11,24 @@ pub struct Guest {
   pub actor_id: ActorId,
   pub email: Option<Email>,
   pub verified: bool,
+  /// Physical token bound to the guest, e.g. an NFC card UID or QR payload
+  pub identifier: Option<String>,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// A registered user taking over a guest, e.g. after signing up with the card
+/// they used as a guest. The guest's balance moves into the user's wallet and
+/// the guest is retired.
+#[derive(Debug, Clone)]
+pub struct GuestClaim {
+  pub id: GuestClaimId,
+  pub guest_id: GuestId,
+  pub user_id: UserId,
+  /// Negative if the user took on the guest's debt
+  pub amount: Money,
+  /// Moves the balance; none if the guest's wallet was empty
+  pub transaction_id: Option<TransactionId>,
   pub created_at: DateTime<Utc>,
   pub updated_at: Option<DateTime<Utc>>,
 }
@@ -0,0 +1,240 @@
+use validator::ValidateEmail;
+
+use crate::{types::Money, Email, Role};
+
+/// Columns a member import must have, in any order. Other columns are ignored.
+pub const MEMBER_IMPORT_COLUMNS: &[&str] = &[
+  "email",
+  "first_name",
+  "last_name",
+  "role",
+  "identifier",
+  "balance_cents",
+];
+
+/// A member carried over from a legacy system, as read from one CSV row.
+///
+/// Rows without a role become guests; rows with one become users of that role.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MemberImportRow {
+  /// Line of the row in the file, counting the header as line 1
+  pub line: usize,
+  pub email: Option<Email>,
+  pub first_name: Option<String>,
+  pub last_name: Option<String>,
+  pub role: Option<Role>,
+  /// Card or QR code the member already carries, for guests
+  pub identifier: Option<String>,
+  /// Balance in the legacy system, booked as the opening balance
+  pub opening_balance: Money,
+}
+
+/// A row that could not be read, with the reason.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemberImportError {
+  pub line: usize,
+  pub message: String,
+}
+
+/// Reads a member import. Fails as a whole only if the header is unusable;
+/// bad rows are returned as errors next to the good ones, in file order.
+pub fn parse_member_csv(
+  input: &str,
+) -> Result<Vec<Result<MemberImportRow, MemberImportError>>, String> {
+  let mut records = csv_records(input).into_iter();
+
+  let Some((_, header)) = records.next() else {
+    return Err("The file is empty".to_string());
+  };
+  let header: Vec<String> = header.iter().map(|h| h.trim().to_lowercase()).collect();
+
+  let mut columns = Vec::with_capacity(MEMBER_IMPORT_COLUMNS.len());
+  for name in MEMBER_IMPORT_COLUMNS {
+    let index = header
+      .iter()
+      .position(|h| h == name)
+      .ok_or_else(|| format!("Missing column `{}`", name))?;
+    columns.push(index);
+  }
+
+  Ok(
+    records
+      .filter(|(_, fields)| fields.iter().any(|f| !f.trim().is_empty()))
+      .map(|(line, fields)| {
+        let field = |column: usize| {
+          fields
+            .get(columns[column])
+            .map(|f| f.trim())
+            .filter(|f| !f.is_empty())
+            .map(str::to_string)
+        };
+
+        parse_member_row(
+          line,
+          field(0),
+          field(1),
+          field(2),
+          field(3),
+          field(4),
+          field(5),
+        )
+        .map_err(|message| MemberImportError { line, message })
+      })
+      .collect(),
+  )
+}
+
+fn parse_member_row(
+  line: usize,
+  email: Option<String>,
+  first_name: Option<String>,
+  last_name: Option<String>,
+  role: Option<String>,
+  identifier: Option<String>,
+  balance_cents: Option<String>,
+) -> Result<MemberImportRow, String> {
+  if let Some(email) = &email {
+    if !email.validate_email() {
+      return Err(format!("`{}` is not a valid email address", email));
+    }
+  }
+
+  let role = match role {
+    Some(role) => match Role::from(role.to_lowercase()) {
+      Role::Undefined => return Err(format!("Unknown role `{}`", role)),
+      role => Some(role),
+    },
+    None => None,
+  };
+
+  if role.is_some() {
+    if email.is_none() || first_name.is_none() || last_name.is_none() {
+      return Err("Users need an email, first name and last name".to_string());
+    }
+    if identifier.is_some() {
+      return Err("Only guests can carry an identifier".to_string());
+    }
+  }
+
+  let opening_balance = match balance_cents {
+    Some(cents) => Money::from_minor(
+      cents
+        .parse()
+        .map_err(|_| format!("`{}` is not a whole number of cents", cents))?,
+    ),
+    None => Money::ZERO,
+  };
+  if opening_balance.is_negative() {
+    return Err("Opening balances cannot be negative".to_string());
+  }
+
+  Ok(MemberImportRow {
+    line,
+    email: email.map(Email::new),
+    first_name,
+    last_name,
+    role,
+    identifier,
+    opening_balance,
+  })
+}
+
+/// Splits CSV text into records with the line each starts on. Fields may be
+/// quoted to contain commas, line breaks or doubled quotes.
+fn csv_records(input: &str) -> Vec<(usize, Vec<String>)> {
+  let mut records = Vec::new();
+  let mut fields = Vec::new();
+  let mut field = String::new();
+  let mut quoted = false;
+  let mut line = 1;
+  let mut start = 1;
+  let mut chars = input.chars().peekable();
+
+  while let Some(c) = chars.next() {
+    match (quoted, c) {
+      (true, '"') if chars.peek() == Some(&'"') => {
+        chars.next();
+        field.push('"');
+      }
+      (true, '"') => quoted = false,
+      (false, '"') if field.is_empty() => quoted = true,
+      (false, ',') => fields.push(std::mem::take(&mut field)),
+      (false, '\r') if chars.peek() == Some(&'\n') => {}
+      (false, '\n') => {
+        fields.push(std::mem::take(&mut field));
+        records.push((start, std::mem::take(&mut fields)));
+        line += 1;
+        start = line;
+      }
+      (_, c) => {
+        if c == '\n' {
+          line += 1;
+        }
+        field.push(c);
+      }
+    }
+  }
+
+  if !field.is_empty() || !fields.is_empty() {
+    fields.push(field);
+    records.push((start, fields));
+  }
+
+  records
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const HEADER: &str = "email,first_name,last_name,role,identifier,balance_cents\n";
+
+  #[test]
+  fn test_csv_records_handles_quotes() {
+    let records = csv_records("a,\"b,c\"\r\n\"say \"\"hi\"\"\",\"two\nlines\"\nlast");
+
+    assert_eq!(
+      records,
+      vec![
+        (1, vec!["a".to_string(), "b,c".to_string()]),
+        (2, vec!["say \"hi\"".to_string(), "two\nlines".to_string()]),
+        (4, vec!["last".to_string()]),
+      ]
+    );
+  }
+
+  #[test]
+  fn test_parse_member_csv() {
+    let input = format!(
+      "{HEADER}ann@example.com,Ann,,,CARD7,1250\n\
+       ,,,,,\n\
+       bob@example.com,Bob,Smith,cashier,,\n\
+       carl@example.com,Carl,,admin,,\n\
+       ,,,,,-5\n"
+    );
+
+    let rows = parse_member_csv(&input).unwrap();
+
+    assert_eq!(rows.len(), 4);
+    let guest = rows[0].as_ref().unwrap();
+    assert_eq!(guest.line, 2);
+    assert_eq!(guest.role, None);
+    assert_eq!(guest.identifier.as_deref(), Some("CARD7"));
+    assert_eq!(guest.opening_balance, Money::from_minor(1250));
+
+    let user = rows[1].as_ref().unwrap();
+    assert_eq!(user.line, 4);
+    assert_eq!(user.role, Some(Role::Cashier));
+    assert_eq!(user.opening_balance, Money::ZERO);
+
+    assert_eq!(rows[2].as_ref().unwrap_err().line, 5);
+    assert_eq!(rows[3].as_ref().unwrap_err().line, 6);
+  }
+
+  #[test]
+  fn test_parse_member_csv_requires_columns() {
+    assert!(parse_member_csv("").is_err());
+    assert!(parse_member_csv("email,first_name\n").is_err());
+    assert!(parse_member_csv(HEADER).unwrap().is_empty());
+  }
+}
@@ -4,7 +4,7 @@ use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 
-use crate::{Email, Id, Role, UserId};
+use crate::{Email, Id, Locale, Role, UserId};
 
 pub type InviteId = Id<Invite>;
 
@@ -25,6 +25,8 @@ pub struct Invite {
   pub email: Email,
   pub token: String,
   pub role: Role,
+  /// Language of the invite emails and of the account once signed up
+  pub locale: Locale,
   pub status: InviteStatus,
   pub expires_in: Duration,
   pub created_at: DateTime<Utc>,
@@ -32,8 +34,70 @@ pub struct Invite {
 }
 
 impl Invite {
+  pub fn expires_at(&self) -> DateTime<Utc> {
+    self.created_at + self.expires_in
+  }
+
   pub fn is_expired(&self) -> bool {
-    Utc::now() > self.created_at + self.expires_in
+    Utc::now() > self.expires_at()
+  }
+
+  pub fn is_pending(&self) -> bool {
+    self.status == InviteStatus::Pending
+  }
+}
+
+/// Allows or denies inviting addresses at a domain.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InviteDomainRule {
+  /// Lowercase, e.g. `club.example`; covers its subdomains too
+  pub domain: String,
+  /// Allows the domain when set, denies it otherwise
+  pub allowed: bool,
+  /// Only invites with this role; every invite without one
+  pub role: Option<Role>,
+}
+
+impl InviteDomainRule {
+  fn covers(&self, domain: &str) -> bool {
+    domain == self.domain
+      || domain
+        .strip_suffix(&self.domain)
+        .is_some_and(|sub| sub.ends_with('.'))
+  }
+}
+
+/// Which email domains may be invited, and with which roles.
+///
+/// Denying rules win. Once any rule allows a domain for a role, only allowed
+/// domains may be invited with it; without one every domain may.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InviteDomainPolicy {
+  pub rules: Vec<InviteDomainRule>,
+}
+
+impl InviteDomainPolicy {
+  pub fn permits(&self, email: &Email, role: Role) -> bool {
+    let domain = email.domain().unwrap_or_default().to_lowercase();
+    let rules = self
+      .rules
+      .iter()
+      .filter(|rule| rule.role.is_none_or(|r| r == role));
+
+    let mut restricted = false;
+    let mut allowed = false;
+    for rule in rules {
+      let covered = rule.covers(&domain);
+      if !rule.allowed && covered {
+        return false;
+      }
+      if rule.allowed {
+        restricted = true;
+        allowed |= covered;
+      }
+    }
+
+    !restricted || allowed
   }
 }
 
@@ -60,3 +124,43 @@ impl From<&str> for InviteStatus {
     }
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn rule(domain: &str, allowed: bool, role: Option<Role>) -> InviteDomainRule {
+    InviteDomainRule {
+      domain: domain.to_string(),
+      allowed,
+      role,
+    }
+  }
+
+  #[test]
+  fn test_invite_domain_policy_restricts_role() {
+    let policy = InviteDomainPolicy {
+      rules: vec![rule("club.example", true, Some(Role::Admin))],
+    };
+
+    assert!(policy.permits(&Email::new("ada@club.example"), Role::Admin));
+    assert!(policy.permits(&Email::new("ada@Mail.Club.Example"), Role::Admin));
+    assert!(!policy.permits(&Email::new("ada@notclub.example"), Role::Admin));
+    assert!(policy.permits(&Email::new("ada@gmail.example"), Role::Cashier));
+  }
+
+  #[test]
+  fn test_invite_domain_policy_denial_wins() {
+    let policy = InviteDomainPolicy {
+      rules: vec![
+        rule("club.example", true, None),
+        rule("guests.club.example", false, None),
+      ],
+    };
+
+    assert!(policy.permits(&Email::new("ada@club.example"), Role::Cashier));
+    assert!(!policy.permits(&Email::new("bob@guests.club.example"), Role::Cashier));
+    assert!(!policy.permits(&Email::new("eve@other.example"), Role::Cashier));
+    assert!(InviteDomainPolicy::default().permits(&Email::new("eve@other.example"), Role::Owner));
+  }
+}
@@ -0,0 +1,157 @@
+use std::fmt::Display;
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{types::Currency, Id, OutboundEmailId, UserId, WalletId, WebhookDeliveryId};
+
+pub type JobId = Id<Job>;
+
+/// Wait before the first retry of a failed job; doubles with every attempt
+const BASE_RETRY_DELAY_SECS: i64 = 30;
+/// Longest wait between two attempts
+const MAX_RETRY_DELAY_SECS: i64 = 60 * 60;
+
+/// Work done in the background after the request that asked for it, and
+/// retried until it succeeds or runs out of attempts.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum JobTask {
+  /// Tell a user that another user sent them money
+  TransferNotice {
+    recipient: UserId,
+    sender_name: String,
+    amount_cents: i32,
+    /// Euros for notices queued before wallets had a currency
+    #[serde(default)]
+    currency: Currency,
+  },
+  /// Tell a wallet's owner that an online top-up was charged back
+  ChargebackNotice {
+    wallet_id: WalletId,
+    amount_cents: i32,
+    balance_cents: i32,
+  },
+  /// Deliver an email queued in the outbox
+  SendEmail { email_id: OutboundEmailId },
+  /// Post an event logged for a webhook to its endpoint
+  DeliverWebhook { delivery_id: WebhookDeliveryId },
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum JobStatus {
+  /// Waiting for its next attempt
+  #[default]
+  Pending,
+  /// Claimed by a worker
+  Running,
+  Succeeded,
+  /// Gave up after the last attempt
+  Failed,
+}
+
+#[derive(Debug, Clone)]
+pub struct Job {
+  pub id: JobId,
+  pub task: JobTask,
+  pub status: JobStatus,
+  /// Attempts started so far
+  pub attempts: i32,
+  pub max_attempts: i32,
+  /// When the job is due next
+  pub run_at: DateTime<Utc>,
+  /// Why the latest attempt failed
+  pub last_error: Option<String>,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl JobTask {
+  /// Short name for logs.
+  pub fn name(&self) -> &'static str {
+    match self {
+      JobTask::TransferNotice { .. } => "transfer_notice",
+      JobTask::ChargebackNotice { .. } => "chargeback_notice",
+      JobTask::SendEmail { .. } => "send_email",
+      JobTask::DeliverWebhook { .. } => "deliver_webhook",
+    }
+  }
+}
+
+impl Job {
+  /// Whether a failed attempt should be followed by another one.
+  pub fn can_retry(&self) -> bool {
+    self.attempts < self.max_attempts
+  }
+
+  /// Wait after the latest failed attempt: exponential, capped at an hour.
+  pub fn retry_delay(&self) -> Duration {
+    let doublings = u32::try_from(self.attempts.saturating_sub(1)).unwrap_or_default();
+    let secs = 2_i64
+      .checked_pow(doublings)
+      .and_then(|factor| factor.checked_mul(BASE_RETRY_DELAY_SECS))
+      .map_or(MAX_RETRY_DELAY_SECS, |secs| secs.min(MAX_RETRY_DELAY_SECS));
+
+    Duration::seconds(secs)
+  }
+}
+
+impl Display for JobStatus {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let status_str = match self {
+      JobStatus::Pending => "pending",
+      JobStatus::Running => "running",
+      JobStatus::Succeeded => "succeeded",
+      JobStatus::Failed => "failed",
+    };
+    write!(f, "{}", status_str)
+  }
+}
+
+impl From<&str> for JobStatus {
+  fn from(value: &str) -> Self {
+    match value {
+      "running" => JobStatus::Running,
+      "succeeded" => JobStatus::Succeeded,
+      "failed" => JobStatus::Failed,
+      _ => JobStatus::Pending,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use uuid::Uuid;
+
+  fn job(attempts: i32) -> Job {
+    Job {
+      id: Uuid::new_v4().into(),
+      task: JobTask::TransferNotice {
+        recipient: Uuid::new_v4().into(),
+        sender_name: "Ada Lovelace".to_string(),
+        amount_cents: 500,
+        currency: Currency::EUR,
+      },
+      status: JobStatus::Running,
+      attempts,
+      max_attempts: 5,
+      run_at: Utc::now(),
+      last_error: None,
+      created_at: Utc::now(),
+      updated_at: None,
+    }
+  }
+
+  #[test]
+  fn test_retry_delay() {
+    assert_eq!(job(1).retry_delay(), Duration::seconds(30));
+    assert_eq!(job(2).retry_delay(), Duration::seconds(60));
+    assert_eq!(job(4).retry_delay(), Duration::seconds(240));
+    assert_eq!(job(20).retry_delay(), Duration::hours(1));
+    assert_eq!(job(i32::MAX).retry_delay(), Duration::hours(1));
+
+    assert!(job(4).can_retry());
+    assert!(!job(5).can_retry());
+  }
+}
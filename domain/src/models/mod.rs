@@ -1,19 +1,86 @@
 pub mod actor;
+pub mod audit;
+pub mod balance_alert;
+pub mod budget;
+pub mod chargeback;
+pub mod client_app;
+pub mod debt;
+pub mod domain_event;
+pub mod feature;
 pub mod guest;
+pub mod import;
 pub mod invite;
+pub mod job;
+pub mod note;
+pub mod order;
+pub mod outbound_email;
+pub mod owner_transfer;
+pub mod payout;
+pub mod retention;
+pub mod risk;
 pub mod role;
 pub mod session;
+pub mod settings;
 pub mod shop;
+pub mod top_up;
 pub mod transaction;
 pub mod user;
 pub mod wallet;
+pub mod webhook;
 
-pub use actor::{Actor, ActorId};
-pub use guest::{Guest, GuestId};
-pub use invite::{Invite, InviteId, InviteStatus};
-pub use role::{Permission, Role};
-pub use session::{Session, SessionId};
-pub use shop::{Shop, ShopId, ShopMember, ShopMemberId, ShopOffering, ShopOfferingId};
-pub use transaction::{Transaction, TransactionId};
-pub use user::{User, UserId};
-pub use wallet::{Wallet, WalletId, WalletLabel};
+pub use actor::{
+  Actor, ActorDetails, ActorId, ActorKind, ActorSummary, DuplicateCandidate, DuplicateReason,
+};
+pub use audit::{AuditAction, AuditChainReport, AuditEntry, AuditEntryId};
+pub use balance_alert::{BalanceAlert, BalanceAlertDirection, BalanceAlertId};
+pub use budget::{BudgetEnvelope, BudgetEnvelopeId, BudgetPlan};
+pub use chargeback::{Chargeback, ChargebackId, ChargebackStatus, ChargebackTotals};
+pub use client_app::{
+  AccessToken, AccessTokenId, AuthorizationCode, AuthorizationCodeId, ClientApp, ClientAppId, Scope,
+};
+pub use debt::{Debt, DebtAge, DebtAgeTotals, DebtId, DebtRecoveryPolicy, Debtor};
+pub use domain_event::{
+  DomainEvent, DomainEventId, DomainEventRecord, OrderEventData, OrderItemEventData,
+  TransactionEventData,
+};
+pub use feature::{Feature, FeatureSwitch};
+pub use guest::{Guest, GuestClaim, GuestClaimId, GuestId};
+pub use import::{parse_member_csv, MemberImportError, MemberImportRow};
+pub use invite::{Invite, InviteDomainPolicy, InviteDomainRule, InviteId, InviteStatus};
+pub use job::{Job, JobId, JobStatus, JobTask};
+pub use note::{AccountNote, AccountNoteId};
+pub use order::{
+  DailySales, LiveShopStats, OfferingSales, Order, OrderEvent, OrderId, OrderItem, OrderItemId,
+  OrderStatus, OrderStatusChange, Purchaser, ReorderLine, SalesReport,
+};
+pub use outbound_email::{OutboundEmail, OutboundEmailId, OutboundEmailStatus};
+pub use owner_transfer::{OwnerTransfer, OwnerTransferId, OwnerTransferStatus};
+pub use payout::{
+  BankAccount, Payout, PayoutBatch, PayoutBatchId, PayoutId, PayoutStatus, SepaDebtor,
+};
+pub use retention::{RetentionOutcome, RetentionPolicy, RetentionRule};
+pub use risk::{
+  ReviewItem, ReviewItemId, ReviewStatus, RiskSignal, RiskThresholds, WalletSecurityEventKind,
+};
+pub use role::{Permission, PermissionOverride, Role};
+pub use session::{
+  DeviceNonce, LoginLockout, PasswordConfirmation, PasswordConfirmationId, Session, SessionClaims,
+  SessionId, SessionRevocation, SessionTimeouts,
+};
+pub use settings::Settings;
+pub use shop::{PickupSlots, Shop, ShopId, ShopMember, ShopMemberId, ShopOffering, ShopOfferingId};
+pub use top_up::{TopUp, TopUpId, TopUpStatus};
+pub use transaction::{
+  LedgerLine, MetadataSource, Transaction, TransactionId, TransactionMetadata,
+};
+pub use user::{
+  EmailChange, EmailChangeId, PasswordSetup, PasswordSetupId, Principal, User, UserId,
+  UserSortField,
+};
+pub use wallet::{
+  BalanceDrift, LegalHoldAction, Wallet, WalletAppearance, WalletDetails, WalletId, WalletLabel,
+  WalletLegalHoldEvent, WalletLegalHoldEventId,
+};
+pub use webhook::{
+  Webhook, WebhookDelivery, WebhookDeliveryId, WebhookDeliveryStatus, WebhookEvent, WebhookId,
+};
@@ -0,0 +1,19 @@
+use chrono::{DateTime, Utc};
+
+use crate::{ActorId, Id};
+
+pub type AccountNoteId = Id<AccountNote>;
+
+/// Internal support note on a user or guest, e.g. "refunded double charge on
+/// 2024-06-01". Only staff allowed to manage notes see them.
+#[derive(Debug, Clone)]
+pub struct AccountNote {
+  pub id: AccountNoteId,
+  /// User or guest the note is about
+  pub actor_id: ActorId,
+  /// `None` once the author's account is gone
+  pub author: Option<ActorId>,
+  pub body: String,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: Option<DateTime<Utc>>,
+}
@@ -0,0 +1,439 @@
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fmt::Display;
+use utoipa::ToSchema;
+
+use crate::{
+  types::{Money, VenueTimezone},
+  ActorId, ActorKind, Id, ShopId, ShopOffering, ShopOfferingId, TransactionId, WalletId,
+};
+
+pub type OrderId = Id<Order>;
+pub type OrderItemId = Id<OrderItem>;
+
+/// Fulfillment progress of an order, in the order it moves through them.
+#[derive(
+  Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, ToSchema,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum OrderStatus {
+  #[default]
+  Placed,
+  Preparing,
+  Ready,
+  Collected,
+}
+
+impl OrderStatus {
+  /// Orders only move forward. Steps may be skipped, e.g. a drink handed
+  /// over right away goes straight from `placed` to `collected`.
+  pub fn can_advance_to(self, next: OrderStatus) -> bool {
+    next > self
+  }
+
+  pub fn is_final(self) -> bool {
+    self == OrderStatus::Collected
+  }
+}
+
+#[derive(Debug, Clone)]
+pub struct Order {
+  pub id: OrderId,
+  pub shop_id: ShopId,
+  pub payer_wallet_id: WalletId,
+  /// The transfer from the payer to the shop's wallet; unset until a
+  /// pre-order paid at pickup is paid
+  pub transaction_id: Option<TransactionId>,
+  pub executor: Option<ActorId>,
+  pub total: Money,
+  pub status: OrderStatus,
+  /// Table to serve the order to
+  pub table_label: Option<String>,
+  /// Number called out when the order is ready for pickup
+  pub pickup_number: Option<i32>,
+  /// Start of the pickup slot of a pre-order
+  pub pickup_at: Option<DateTime<Utc>>,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// Published whenever an order moves on to another status.
+#[derive(Debug, Clone)]
+pub struct OrderStatusChange {
+  pub order_id: OrderId,
+  pub shop_id: ShopId,
+  pub status: OrderStatus,
+  pub changed_at: DateTime<Utc>,
+}
+
+/// Something that happened to an order, as published on the event bus.
+#[derive(Debug, Clone)]
+pub enum OrderEvent {
+  /// Paid at checkout, or at pickup for pre-orders
+  Paid {
+    order_id: OrderId,
+    shop_id: ShopId,
+    amount: Money,
+    at: DateTime<Utc>,
+  },
+  Refunded {
+    order_id: OrderId,
+    shop_id: ShopId,
+    amount: Money,
+    at: DateTime<Utc>,
+  },
+  StatusChanged(OrderStatusChange),
+}
+
+impl OrderEvent {
+  pub fn order_id(&self) -> OrderId {
+    match self {
+      OrderEvent::Paid { order_id, .. } | OrderEvent::Refunded { order_id, .. } => *order_id,
+      OrderEvent::StatusChanged(change) => change.order_id,
+    }
+  }
+
+  pub fn shop_id(&self) -> ShopId {
+    match self {
+      OrderEvent::Paid { shop_id, .. } | OrderEvent::Refunded { shop_id, .. } => *shop_id,
+      OrderEvent::StatusChanged(change) => change.shop_id,
+    }
+  }
+}
+
+/// Who placed an order, as far as the shop's staff get to know: the first
+/// name and last initial of users, nothing about guests.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Purchaser {
+  pub order_id: OrderId,
+  /// `None` if the payer's wallet belongs to neither a user nor a guest
+  pub kind: Option<ActorKind>,
+  /// Unset for guests and deleted users
+  pub first_name: Option<String>,
+  pub last_initial: Option<String>,
+}
+
+impl Purchaser {
+  /// Name to greet the purchaser by, e.g. "Ada L."
+  pub fn display_name(&self) -> Option<String> {
+    let first_name = self.first_name.as_deref()?.trim();
+
+    Some(match self.last_initial.as_deref().map(str::trim) {
+      Some(initial) if !initial.is_empty() => {
+        format!("{} {}.", first_name, initial.to_uppercase())
+      }
+      _ => first_name.to_string(),
+    })
+  }
+
+  /// Upper case initials for a placeholder avatar, e.g. "AL".
+  pub fn initials(&self) -> Option<String> {
+    let first = self.first_name.as_deref()?.trim().chars().next()?;
+    let last = self
+      .last_initial
+      .as_deref()
+      .and_then(|initial| initial.trim().chars().next());
+
+    Some(
+      first
+        .to_uppercase()
+        .chain(last.into_iter().flat_map(char::to_uppercase))
+        .collect(),
+    )
+  }
+}
+
+/// Running sales figures of a shop for the current day at the venue, kept up
+/// to date from order events.
+#[derive(Debug, Clone)]
+pub struct LiveShopStats {
+  pub day: NaiveDate,
+  timezone: VenueTimezone,
+  pub order_count: i64,
+  /// Paid minus refunded, in cents
+  pub revenue_cents: i64,
+  /// Payment times within the last minute
+  recent: VecDeque<DateTime<Utc>>,
+}
+
+impl LiveShopStats {
+  pub fn new(
+    now: DateTime<Utc>,
+    timezone: VenueTimezone,
+    order_count: i64,
+    revenue_cents: i64,
+    recent: Vec<DateTime<Utc>>,
+  ) -> Self {
+    Self {
+      day: timezone.day_of(now),
+      timezone,
+      order_count,
+      revenue_cents,
+      recent: recent.into(),
+    }
+  }
+
+  pub fn apply(&mut self, event: &OrderEvent) {
+    match event {
+      OrderEvent::Paid { amount, at, .. } => {
+        self.roll_over(*at);
+        self.order_count += 1;
+        self.revenue_cents += i64::from(amount.as_minor());
+        self.recent.push_back(*at);
+      }
+      OrderEvent::Refunded { amount, at, .. } => {
+        self.roll_over(*at);
+        self.revenue_cents -= i64::from(amount.as_minor());
+      }
+      OrderEvent::StatusChanged(_) => {}
+    }
+  }
+
+  /// Starts over at midnight at the venue.
+  pub fn roll_over(&mut self, now: DateTime<Utc>) {
+    if self.timezone.day_of(now) != self.day {
+      *self = Self::new(now, self.timezone, 0, 0, Vec::new());
+    }
+  }
+
+  pub fn orders_per_minute(&mut self, now: DateTime<Utc>) -> usize {
+    while self
+      .recent
+      .front()
+      .is_some_and(|at| now - *at > Duration::minutes(1))
+    {
+      self.recent.pop_front();
+    }
+    self.recent.len()
+  }
+
+  pub fn average_ticket_cents(&self) -> i64 {
+    if self.order_count == 0 {
+      0
+    } else {
+      self.revenue_cents / self.order_count
+    }
+  }
+}
+
+/// A line of an order, with name and price as they were at checkout.
+#[derive(Debug, Clone)]
+pub struct OrderItem {
+  pub id: OrderItemId,
+  pub order_id: OrderId,
+  /// Unset once the offering has been deleted
+  pub offering_id: Option<ShopOfferingId>,
+  pub name: String,
+  pub unit_price: Money,
+  pub quantity: i32,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// A line of a past order put back into the cart at today's price.
+#[derive(Debug, Clone)]
+pub struct ReorderLine {
+  /// The item as it was ordered
+  pub previous: OrderItem,
+  /// The offering as currently sold; unset once the shop stopped selling it
+  pub current: Option<ShopOffering>,
+}
+
+impl ReorderLine {
+  pub fn is_available(&self) -> bool {
+    self.current.is_some()
+  }
+
+  pub fn price_changed(&self) -> bool {
+    self
+      .current
+      .as_ref()
+      .is_some_and(|offering| offering.price_cents != self.previous.unit_price)
+  }
+}
+
+/// What a shop sold in a period. Amounts are summed in cents as `i64` since
+/// totals can outgrow [`Money`].
+#[derive(Debug, Clone)]
+pub struct SalesReport {
+  pub from: Option<DateTime<Utc>>,
+  pub to: Option<DateTime<Utc>>,
+  pub order_count: i64,
+  /// Everything sold, including orders refunded since
+  pub gross_cents: i64,
+  pub refunded_cents: i64,
+  pub by_offering: Vec<OfferingSales>,
+  pub by_day: Vec<DailySales>,
+}
+
+impl SalesReport {
+  pub fn net_cents(&self) -> i64 {
+    self.gross_cents - self.refunded_cents
+  }
+}
+
+#[derive(Debug, Clone)]
+pub struct OfferingSales {
+  pub offering_id: Option<ShopOfferingId>,
+  /// Name as captured on the orders
+  pub name: String,
+  pub quantity: i64,
+  pub revenue_cents: i64,
+}
+
+/// Sales of one calendar day at the venue.
+#[derive(Debug, Clone)]
+pub struct DailySales {
+  pub day: NaiveDate,
+  pub order_count: i64,
+  pub revenue_cents: i64,
+}
+
+impl Display for OrderStatus {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let status_str = match self {
+      OrderStatus::Placed => "placed",
+      OrderStatus::Preparing => "preparing",
+      OrderStatus::Ready => "ready",
+      OrderStatus::Collected => "collected",
+    };
+    write!(f, "{}", status_str)
+  }
+}
+
+impl From<&str> for OrderStatus {
+  fn from(value: &str) -> Self {
+    match value {
+      "preparing" => OrderStatus::Preparing,
+      "ready" => OrderStatus::Ready,
+      "collected" => OrderStatus::Collected,
+      _ => OrderStatus::Placed,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_live_shop_stats() {
+    let now = "2026-10-16T20:00:00Z".parse::<DateTime<Utc>>().unwrap();
+    let shop_id = Id::new();
+    let paid = |amount: i32, at: DateTime<Utc>| OrderEvent::Paid {
+      order_id: Id::new(),
+      shop_id,
+      amount: Money::from_minor(amount),
+      at,
+    };
+
+    let mut stats = LiveShopStats::new(
+      now,
+      VenueTimezone::default(),
+      2,
+      700,
+      vec![now - Duration::seconds(90)],
+    );
+    assert_eq!(stats.orders_per_minute(now), 0);
+
+    stats.apply(&paid(500, now));
+    stats.apply(&OrderEvent::Refunded {
+      order_id: Id::new(),
+      shop_id,
+      amount: Money::from_minor(300),
+      at: now,
+    });
+    assert_eq!(stats.order_count, 3);
+    assert_eq!(stats.revenue_cents, 900);
+    assert_eq!(stats.average_ticket_cents(), 300);
+    assert_eq!(stats.orders_per_minute(now + Duration::seconds(30)), 1);
+
+    stats.apply(&paid(400, now + Duration::days(1)));
+    assert_eq!(stats.order_count, 1);
+    assert_eq!(stats.revenue_cents, 400);
+  }
+
+  #[test]
+  fn test_reorder_line_flags_changes() {
+    let offering = ShopOffering {
+      id: Id::new(),
+      shop_id: Id::new(),
+      name: "Beer".to_string(),
+      description: None,
+      price_cents: Money::from_minor(400),
+      created_at: Utc::now(),
+      updated_at: None,
+    };
+    let previous = OrderItem {
+      id: Id::new(),
+      order_id: Id::new(),
+      offering_id: Some(offering.id),
+      name: "Beer".to_string(),
+      unit_price: Money::from_minor(350),
+      quantity: 2,
+      created_at: Utc::now(),
+      updated_at: None,
+    };
+
+    let repriced = ReorderLine {
+      previous: previous.clone(),
+      current: Some(offering),
+    };
+    assert!(repriced.is_available());
+    assert!(repriced.price_changed());
+
+    let gone = ReorderLine {
+      previous,
+      current: None,
+    };
+    assert!(!gone.is_available());
+    assert!(!gone.price_changed());
+  }
+
+  #[test]
+  fn test_net_cents_subtracts_refunds() {
+    let report = SalesReport {
+      from: None,
+      to: None,
+      order_count: 3,
+      gross_cents: 1500,
+      refunded_cents: 400,
+      by_offering: vec![],
+      by_day: vec![],
+    };
+
+    assert_eq!(report.net_cents(), 1100);
+  }
+
+  #[test]
+  fn test_purchaser_display_name_and_initials() {
+    let purchaser = |first_name: Option<&str>, last_initial: Option<&str>| Purchaser {
+      order_id: Id::new(),
+      kind: Some(ActorKind::User),
+      first_name: first_name.map(str::to_string),
+      last_initial: last_initial.map(str::to_string),
+    };
+
+    let ada = purchaser(Some("Ada"), Some("l"));
+    assert_eq!(ada.display_name().as_deref(), Some("Ada L."));
+    assert_eq!(ada.initials().as_deref(), Some("AL"));
+
+    let mononym = purchaser(Some("Cher"), Some(""));
+    assert_eq!(mononym.display_name().as_deref(), Some("Cher"));
+    assert_eq!(mononym.initials().as_deref(), Some("C"));
+
+    let guest = purchaser(None, None);
+    assert_eq!(guest.display_name(), None);
+    assert_eq!(guest.initials(), None);
+  }
+
+  #[test]
+  fn test_order_status_only_moves_forward() {
+    assert!(OrderStatus::Placed.can_advance_to(OrderStatus::Preparing));
+    assert!(OrderStatus::Placed.can_advance_to(OrderStatus::Collected));
+    assert!(!OrderStatus::Ready.can_advance_to(OrderStatus::Preparing));
+    assert!(!OrderStatus::Ready.can_advance_to(OrderStatus::Ready));
+    assert!(!OrderStatus::Collected.can_advance_to(OrderStatus::Placed));
+  }
+}
@@ -0,0 +1,56 @@
+use std::fmt::Display;
+
+use chrono::{DateTime, Utc};
+
+use crate::{Email, Id};
+
+pub type OutboundEmailId = Id<OutboundEmail>;
+
+/// An email waiting for, or done with, delivery by the job queue.
+#[derive(Debug, Clone)]
+pub struct OutboundEmail {
+  pub id: OutboundEmailId,
+  pub recipient: Email,
+  pub subject: String,
+  pub text_body: String,
+  pub html_body: String,
+  pub status: OutboundEmailStatus,
+  /// Delivery attempts made so far
+  pub attempts: i32,
+  /// Why the latest attempt failed
+  pub last_error: Option<String>,
+  pub sent_at: Option<DateTime<Utc>>,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum OutboundEmailStatus {
+  /// Not delivered yet; retried until it is or attempts run out
+  #[default]
+  Queued,
+  Sent,
+  /// Gave up after the last attempt
+  Failed,
+}
+
+impl Display for OutboundEmailStatus {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let status_str = match self {
+      OutboundEmailStatus::Queued => "queued",
+      OutboundEmailStatus::Sent => "sent",
+      OutboundEmailStatus::Failed => "failed",
+    };
+    write!(f, "{}", status_str)
+  }
+}
+
+impl From<&str> for OutboundEmailStatus {
+  fn from(value: &str) -> Self {
+    match value {
+      "sent" => OutboundEmailStatus::Sent,
+      "failed" => OutboundEmailStatus::Failed,
+      _ => OutboundEmailStatus::Queued,
+    }
+  }
+}
@@ -0,0 +1,117 @@
+use std::fmt::Display;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::{Id, Role, UserId};
+
+pub type OwnerTransferId = Id<OwnerTransfer>;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum OwnerTransferStatus {
+  /// Waiting for the new owner to accept
+  #[default]
+  Pending,
+  /// The new owner took over
+  Accepted,
+  /// Turned down by the new owner
+  Declined,
+  /// Withdrawn by the owner who offered it
+  Cancelled,
+  /// Not accepted in time, or the offering owner was no longer one
+  Expired,
+}
+
+/// The owner handing the Owner role to another user. Nothing changes until
+/// that user accepts; then both roles change at once, so there is an owner
+/// throughout.
+#[derive(Debug, Clone)]
+pub struct OwnerTransfer {
+  pub id: OwnerTransferId,
+  pub from_user_id: UserId,
+  pub to_user_id: UserId,
+  /// Role the current owner steps down to
+  pub demote_to: Role,
+  pub status: OwnerTransferStatus,
+  pub expires_at: DateTime<Utc>,
+  /// When it was accepted, declined or cancelled
+  pub decided_at: Option<DateTime<Utc>>,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl OwnerTransfer {
+  /// Whether it can still be accepted.
+  pub fn is_open(&self) -> bool {
+    self.status == OwnerTransferStatus::Pending && Utc::now() <= self.expires_at
+  }
+
+  /// Roles an owner may step down to: any they could give someone else,
+  /// except staying an owner.
+  pub fn can_demote_to(role: Role) -> bool {
+    role != Role::Owner && Role::Owner.can_assign_role(role)
+  }
+}
+
+impl Display for OwnerTransferStatus {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let status_str = match self {
+      OwnerTransferStatus::Pending => "pending",
+      OwnerTransferStatus::Accepted => "accepted",
+      OwnerTransferStatus::Declined => "declined",
+      OwnerTransferStatus::Cancelled => "cancelled",
+      OwnerTransferStatus::Expired => "expired",
+    };
+    write!(f, "{}", status_str)
+  }
+}
+
+impl From<&str> for OwnerTransferStatus {
+  fn from(value: &str) -> Self {
+    match value {
+      "accepted" => OwnerTransferStatus::Accepted,
+      "declined" => OwnerTransferStatus::Declined,
+      "cancelled" => OwnerTransferStatus::Cancelled,
+      "expired" => OwnerTransferStatus::Expired,
+      _ => OwnerTransferStatus::Pending,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use chrono::Duration;
+  use uuid::Uuid;
+
+  fn transfer(status: OwnerTransferStatus, expires_in: Duration) -> OwnerTransfer {
+    OwnerTransfer {
+      id: Uuid::nil().into(),
+      from_user_id: Uuid::new_v4().into(),
+      to_user_id: Uuid::new_v4().into(),
+      demote_to: Role::Admin,
+      status,
+      expires_at: Utc::now() + expires_in,
+      decided_at: None,
+      created_at: Utc::now(),
+      updated_at: None,
+    }
+  }
+
+  #[test]
+  fn test_is_open() {
+    assert!(transfer(OwnerTransferStatus::Pending, Duration::hours(1)).is_open());
+    assert!(!transfer(OwnerTransferStatus::Pending, Duration::hours(-1)).is_open());
+    assert!(!transfer(OwnerTransferStatus::Declined, Duration::hours(1)).is_open());
+  }
+
+  #[test]
+  fn test_can_demote_to() {
+    assert!(OwnerTransfer::can_demote_to(Role::Admin));
+    assert!(OwnerTransfer::can_demote_to(Role::Cashier));
+    assert!(!OwnerTransfer::can_demote_to(Role::Owner));
+    assert!(!OwnerTransfer::can_demote_to(Role::Undefined));
+  }
+}
@@ -0,0 +1,273 @@
+use std::fmt::{Display, Write};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::{
+  types::{Iban, Money},
+  ActorId, Id, TransactionId, WalletId,
+};
+
+pub type PayoutId = Id<Payout>;
+pub type PayoutBatchId = Id<PayoutBatch>;
+
+/// Longest name SEPA lets through in `Nm` elements
+const SEPA_NAME_MAX_LEN: usize = 70;
+/// Longest remittance information SEPA lets through
+const SEPA_REMITTANCE_MAX_LEN: usize = 140;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum PayoutStatus {
+  /// Queued for the next batch
+  #[default]
+  Requested,
+  /// Part of a batch handed to the bank
+  Exported,
+  /// Confirmed by the bank
+  Settled,
+  /// Not paid out; the money went back to the wallet
+  Rejected,
+}
+
+/// The account an actor wants payouts sent to.
+#[derive(Debug, Clone)]
+pub struct BankAccount {
+  pub actor_id: ActorId,
+  pub iban: Iban,
+  pub holder_name: String,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// A withdrawal to an external bank account. The amount leaves the wallet
+/// when requested and waits in the [`crate::WalletLabel::BankPayouts`] system
+/// wallet until the transfer goes out.
+#[derive(Debug, Clone)]
+pub struct Payout {
+  pub id: PayoutId,
+  pub wallet_id: WalletId,
+  pub amount: Money,
+  /// Account at the time of the request
+  pub iban: Iban,
+  pub holder_name: String,
+  pub status: PayoutStatus,
+  pub batch_id: Option<PayoutBatchId>,
+  /// Debit of the wallet
+  pub transaction_id: TransactionId,
+  /// Credit back to the wallet, for rejected payouts
+  pub refund_transaction_id: Option<TransactionId>,
+  pub requested_by: Option<ActorId>,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// Payouts exported together into one bank file.
+#[derive(Debug, Clone)]
+pub struct PayoutBatch {
+  pub id: PayoutBatchId,
+  pub created_by: Option<ActorId>,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// The organiser's own account that payouts are sent from.
+#[derive(Debug, Clone)]
+pub struct SepaDebtor {
+  pub name: String,
+  pub iban: Iban,
+  /// Only needed by banks that still ask for it
+  pub bic: Option<String>,
+}
+
+impl PayoutStatus {
+  /// Whether a payout may move from this status to `next`
+  pub fn can_transition_to(self, next: PayoutStatus) -> bool {
+    matches!(
+      (self, next),
+      (PayoutStatus::Requested, PayoutStatus::Exported)
+        | (PayoutStatus::Exported, PayoutStatus::Settled)
+        | (PayoutStatus::Requested, PayoutStatus::Rejected)
+        | (PayoutStatus::Exported, PayoutStatus::Rejected)
+    )
+  }
+}
+
+impl PayoutBatch {
+  /// The batch as a SEPA credit transfer initiation (`pain.001.001.03`), the
+  /// file banks accept for bulk transfers.
+  pub fn to_pain001(&self, debtor: &SepaDebtor, payouts: &[Payout]) -> String {
+    let count = payouts.len();
+    let total = format_amount(payouts.iter().map(|p| i64::from(p.amount.as_minor())).sum());
+    let message_id = self.id.into_inner().simple().to_string();
+    let debtor_name = sepa_text(&debtor.name, SEPA_NAME_MAX_LEN);
+
+    let mut xml = String::new();
+    xml.push_str(r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+    xml.push('\n');
+    xml.push_str(r#"<Document xmlns="urn:iso:std:iso:20022:tech:xsd:pain.001.001.03">"#);
+    xml.push_str("<CstmrCdtTrfInitn><GrpHdr>");
+    let _ = write!(
+      xml,
+      "<MsgId>{message_id}</MsgId><CreDtTm>{}</CreDtTm><NbOfTxs>{count}</NbOfTxs>\
+       <CtrlSum>{total}</CtrlSum><InitgPty><Nm>{debtor_name}</Nm></InitgPty>",
+      self.created_at.format("%Y-%m-%dT%H:%M:%S"),
+    );
+    xml.push_str("</GrpHdr><PmtInf>");
+    let _ = write!(
+      xml,
+      "<PmtInfId>{message_id}</PmtInfId><PmtMtd>TRF</PmtMtd><BtchBookg>true</BtchBookg>\
+       <NbOfTxs>{count}</NbOfTxs><CtrlSum>{total}</CtrlSum>\
+       <PmtTpInf><SvcLvl><Cd>SEPA</Cd></SvcLvl></PmtTpInf>\
+       <ReqdExctnDt>{}</ReqdExctnDt><Dbtr><Nm>{debtor_name}</Nm></Dbtr>\
+       <DbtrAcct><Id><IBAN>{}</IBAN></Id></DbtrAcct>",
+      self.created_at.format("%Y-%m-%d"),
+      debtor.iban.expose(),
+    );
+    match &debtor.bic {
+      Some(bic) => {
+        let _ = write!(
+          xml,
+          "<DbtrAgt><FinInstnId><BIC>{}</BIC></FinInstnId></DbtrAgt>",
+          sepa_text(bic, 11)
+        );
+      }
+      None => xml
+        .push_str("<DbtrAgt><FinInstnId><Othr><Id>NOTPROVIDED</Id></Othr></FinInstnId></DbtrAgt>"),
+    }
+    xml.push_str("<ChrgBr>SLEV</ChrgBr>");
+
+    for payout in payouts {
+      let _ = write!(
+        xml,
+        "<CdtTrfTxInf><PmtId><EndToEndId>{}</EndToEndId></PmtId>\
+         <Amt><InstdAmt Ccy=\"EUR\">{}</InstdAmt></Amt>\
+         <Cdtr><Nm>{}</Nm></Cdtr><CdtrAcct><Id><IBAN>{}</IBAN></Id></CdtrAcct>\
+         <RmtInf><Ustrd>{}</Ustrd></RmtInf></CdtTrfTxInf>",
+        payout.id.into_inner().simple(),
+        payout.amount,
+        sepa_text(&payout.holder_name, SEPA_NAME_MAX_LEN),
+        payout.iban.expose(),
+        sepa_text(
+          &format!("Payout {}", payout.id.into_inner().simple()),
+          SEPA_REMITTANCE_MAX_LEN
+        ),
+      );
+    }
+
+    xml.push_str("</PmtInf></CstmrCdtTrfInitn></Document>\n");
+    xml
+  }
+}
+
+fn format_amount(cents: i64) -> String {
+  format!("{}.{:02}", cents / 100, cents % 100)
+}
+
+/// Cuts `value` to what the field allows and escapes it for XML.
+fn sepa_text(value: &str, max_len: usize) -> String {
+  value
+    .trim()
+    .chars()
+    .take(max_len)
+    .fold(String::new(), |mut out, c| {
+      match c {
+        '&' => out.push_str("&amp;"),
+        '<' => out.push_str("&lt;"),
+        '>' => out.push_str("&gt;"),
+        '"' => out.push_str("&quot;"),
+        '\'' => out.push_str("&apos;"),
+        c if c.is_control() => out.push(' '),
+        c => out.push(c),
+      }
+      out
+    })
+}
+
+impl Display for PayoutStatus {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let status_str = match self {
+      PayoutStatus::Requested => "requested",
+      PayoutStatus::Exported => "exported",
+      PayoutStatus::Settled => "settled",
+      PayoutStatus::Rejected => "rejected",
+    };
+    write!(f, "{}", status_str)
+  }
+}
+
+impl From<&str> for PayoutStatus {
+  fn from(value: &str) -> Self {
+    match value {
+      "exported" => PayoutStatus::Exported,
+      "settled" => PayoutStatus::Settled,
+      "rejected" => PayoutStatus::Rejected,
+      _ => PayoutStatus::Requested,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use chrono::TimeZone;
+  use uuid::Uuid;
+
+  fn payout(cents: i32, holder_name: &str) -> Payout {
+    Payout {
+      id: Uuid::new_v4().into(),
+      wallet_id: Uuid::new_v4().into(),
+      amount: Money::from_minor(cents),
+      iban: Iban::parse("GB82WEST12345698765432").unwrap(),
+      holder_name: holder_name.to_string(),
+      status: PayoutStatus::Exported,
+      batch_id: None,
+      transaction_id: Uuid::new_v4().into(),
+      refund_transaction_id: None,
+      requested_by: None,
+      created_at: Utc::now(),
+      updated_at: None,
+    }
+  }
+
+  #[test]
+  fn test_can_transition_to() {
+    assert!(PayoutStatus::Requested.can_transition_to(PayoutStatus::Exported));
+    assert!(PayoutStatus::Exported.can_transition_to(PayoutStatus::Settled));
+    assert!(PayoutStatus::Exported.can_transition_to(PayoutStatus::Rejected));
+    assert!(!PayoutStatus::Requested.can_transition_to(PayoutStatus::Settled));
+    assert!(!PayoutStatus::Settled.can_transition_to(PayoutStatus::Rejected));
+    assert!(!PayoutStatus::Rejected.can_transition_to(PayoutStatus::Exported));
+  }
+
+  #[test]
+  fn test_to_pain001() {
+    let batch = PayoutBatch {
+      id: Uuid::new_v4().into(),
+      created_by: None,
+      created_at: Utc.with_ymd_and_hms(2026, 10, 16, 9, 30, 0).unwrap(),
+      updated_at: None,
+    };
+    let debtor = SepaDebtor {
+      name: "Club & Co".to_string(),
+      iban: Iban::parse("DE89370400440532013000").unwrap(),
+      bic: None,
+    };
+    let payouts = [payout(1250, "Ann <Smith>"), payout(5, "Bob")];
+
+    let xml = batch.to_pain001(&debtor, &payouts);
+
+    assert!(xml.starts_with("<?xml"));
+    assert!(xml.contains("<CreDtTm>2026-10-16T09:30:00</CreDtTm>"));
+    assert!(xml.contains("<ReqdExctnDt>2026-10-16</ReqdExctnDt>"));
+    assert_eq!(xml.matches("<NbOfTxs>2</NbOfTxs>").count(), 2);
+    assert_eq!(xml.matches("<CtrlSum>12.55</CtrlSum>").count(), 2);
+    assert!(xml.contains("<Nm>Club &amp; Co</Nm>"));
+    assert!(xml.contains("<Nm>Ann &lt;Smith&gt;</Nm>"));
+    assert!(xml.contains("<InstdAmt Ccy=\"EUR\">0.05</InstdAmt>"));
+    assert!(xml.contains("<IBAN>DE89370400440532013000</IBAN>"));
+    assert!(xml.contains("<Othr><Id>NOTPROVIDED</Id></Othr>"));
+    assert_eq!(xml.matches("<CdtTrfTxInf>").count(), 2);
+  }
+}
@@ -0,0 +1,89 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Data that is deleted or anonymized once it is older than configured.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RetentionRule {
+  /// Sessions that expired before the cutoff are deleted
+  ExpiredSessions,
+  /// Invites that expired before the cutoff are deleted, whether or not they
+  /// were revoked
+  ExpiredInvites,
+  /// Login attempts made before the cutoff are deleted
+  LoginAttempts,
+  /// Guests without activity since the cutoff lose their email and identifier
+  InactiveGuests,
+  /// Audit entries recorded before the cutoff lose their IP address
+  AuditIpAddresses,
+}
+
+/// How many days each kind of data is kept; 0 keeps it forever.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetentionPolicy {
+  pub expired_sessions_days: u32,
+  pub expired_invites_days: u32,
+  pub login_attempts_days: u32,
+  pub inactive_guests_days: u32,
+  pub audit_ip_addresses_days: u32,
+}
+
+/// What applying one rule did, or would do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetentionOutcome {
+  pub rule: RetentionRule,
+  pub retention_days: u32,
+  pub cutoff: DateTime<Utc>,
+  /// Rows deleted or anonymized
+  pub affected: u64,
+}
+
+impl RetentionPolicy {
+  /// The enabled rules with the cutoff each applies at `now`.
+  pub fn cutoffs(&self, now: DateTime<Utc>) -> Vec<(RetentionRule, u32, DateTime<Utc>)> {
+    [
+      (RetentionRule::ExpiredSessions, self.expired_sessions_days),
+      (RetentionRule::ExpiredInvites, self.expired_invites_days),
+      (RetentionRule::LoginAttempts, self.login_attempts_days),
+      (RetentionRule::InactiveGuests, self.inactive_guests_days),
+      (
+        RetentionRule::AuditIpAddresses,
+        self.audit_ip_addresses_days,
+      ),
+    ]
+    .into_iter()
+    .filter(|(_, days)| *days > 0)
+    .map(|(rule, days)| (rule, days, now - Duration::days(i64::from(days))))
+    .collect()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_cutoffs_skip_disabled_rules() {
+    let policy = RetentionPolicy {
+      expired_sessions_days: 90,
+      expired_invites_days: 0,
+      login_attempts_days: 0,
+      inactive_guests_days: 365,
+      audit_ip_addresses_days: 0,
+    };
+    let now = Utc::now();
+
+    assert_eq!(
+      policy.cutoffs(now),
+      vec![
+        (RetentionRule::ExpiredSessions, 90, now - Duration::days(90)),
+        (
+          RetentionRule::InactiveGuests,
+          365,
+          now - Duration::days(365)
+        ),
+      ]
+    );
+  }
+}
@@ -0,0 +1,231 @@
+use std::fmt::Display;
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::{types::Money, ActorId, Id, WalletId};
+
+pub type ReviewItemId = Id<ReviewItem>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RiskSignal {
+  RapidRepeatedCharges,
+  DrainedAfterCardBinding,
+  RepeatedFailedPinAttempts,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ReviewStatus {
+  #[default]
+  Open,
+  Dismissed,
+  Confirmed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WalletSecurityEventKind {
+  CardBound,
+  PinFailed,
+}
+
+#[derive(Debug, Clone)]
+pub struct ReviewItem {
+  pub id: ReviewItemId,
+  pub wallet_id: WalletId,
+  pub signal: RiskSignal,
+  pub details: String,
+  pub status: ReviewStatus,
+  pub wallet_frozen: bool,
+  pub resolved_by: Option<ActorId>,
+  pub resolution_note: Option<String>,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// Tunables for the suspicious-activity heuristics.
+#[derive(Debug, Clone)]
+pub struct RiskThresholds {
+  /// Outgoing transactions within `rapid_charge_window` that trigger a review
+  pub rapid_charge_count: usize,
+  pub rapid_charge_window: Duration,
+  /// How long after binding a card a drain is considered suspicious
+  pub drain_window: Duration,
+  /// Share (in percent) of the balance at binding time that counts as drained
+  pub drain_percent: i64,
+  /// Failed PIN attempts within `failed_pin_window` that trigger a review
+  pub failed_pin_count: usize,
+  pub failed_pin_window: Duration,
+}
+
+impl Default for RiskThresholds {
+  fn default() -> Self {
+    Self {
+      rapid_charge_count: 5,
+      rapid_charge_window: Duration::minutes(2),
+      drain_window: Duration::minutes(15),
+      drain_percent: 90,
+      failed_pin_count: 5,
+      failed_pin_window: Duration::minutes(10),
+    }
+  }
+}
+
+impl RiskThresholds {
+  /// Whether enough charges happened within the rapid charge window before `now`
+  pub fn is_rapid_charging(&self, charges: &[DateTime<Utc>], now: DateTime<Utc>) -> bool {
+    count_within(charges, now, self.rapid_charge_window) >= self.rapid_charge_count
+  }
+
+  /// Whether most of the balance left the wallet shortly after a card was bound
+  pub fn is_drained_after_binding(
+    &self,
+    bound_at: DateTime<Utc>,
+    balance_at_binding: Money,
+    outgoing_since_binding: Money,
+    now: DateTime<Utc>,
+  ) -> bool {
+    if !balance_at_binding.is_positive() || now - bound_at > self.drain_window {
+      return false;
+    }
+
+    let threshold = balance_at_binding.as_minor() as i64 * self.drain_percent / 100;
+    outgoing_since_binding.as_minor() as i64 >= threshold
+  }
+
+  /// Whether enough PIN attempts failed within the failed PIN window before `now`
+  pub fn has_repeated_failed_pins(&self, failures: &[DateTime<Utc>], now: DateTime<Utc>) -> bool {
+    count_within(failures, now, self.failed_pin_window) >= self.failed_pin_count
+  }
+}
+
+fn count_within(times: &[DateTime<Utc>], now: DateTime<Utc>, window: Duration) -> usize {
+  times
+    .iter()
+    .filter(|t| **t <= now && now - **t <= window)
+    .count()
+}
+
+impl Display for RiskSignal {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let signal_str = match self {
+      RiskSignal::RapidRepeatedCharges => "rapid_repeated_charges",
+      RiskSignal::DrainedAfterCardBinding => "drained_after_card_binding",
+      RiskSignal::RepeatedFailedPinAttempts => "repeated_failed_pin_attempts",
+    };
+    write!(f, "{}", signal_str)
+  }
+}
+
+impl From<&str> for RiskSignal {
+  fn from(value: &str) -> Self {
+    match value {
+      "drained_after_card_binding" => RiskSignal::DrainedAfterCardBinding,
+      "repeated_failed_pin_attempts" => RiskSignal::RepeatedFailedPinAttempts,
+      _ => RiskSignal::RapidRepeatedCharges,
+    }
+  }
+}
+
+impl Display for ReviewStatus {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let status_str = match self {
+      ReviewStatus::Open => "open",
+      ReviewStatus::Dismissed => "dismissed",
+      ReviewStatus::Confirmed => "confirmed",
+    };
+    write!(f, "{}", status_str)
+  }
+}
+
+impl From<&str> for ReviewStatus {
+  fn from(value: &str) -> Self {
+    match value {
+      "dismissed" => ReviewStatus::Dismissed,
+      "confirmed" => ReviewStatus::Confirmed,
+      _ => ReviewStatus::Open,
+    }
+  }
+}
+
+impl Display for WalletSecurityEventKind {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let kind_str = match self {
+      WalletSecurityEventKind::CardBound => "card_bound",
+      WalletSecurityEventKind::PinFailed => "pin_failed",
+    };
+    write!(f, "{}", kind_str)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn minutes_ago(now: DateTime<Utc>, minutes: i64) -> DateTime<Utc> {
+    now - Duration::minutes(minutes)
+  }
+
+  #[test]
+  fn test_rapid_charging() {
+    let thresholds = RiskThresholds::default();
+    let now = Utc::now();
+
+    let burst: Vec<_> = (0..5).map(|_| minutes_ago(now, 1)).collect();
+    assert!(thresholds.is_rapid_charging(&burst, now));
+
+    let spread: Vec<_> = (0..5).map(|i| minutes_ago(now, i * 10)).collect();
+    assert!(!thresholds.is_rapid_charging(&spread, now));
+  }
+
+  #[test]
+  fn test_drained_after_binding() {
+    let thresholds = RiskThresholds::default();
+    let now = Utc::now();
+    let bound_at = minutes_ago(now, 5);
+
+    assert!(thresholds.is_drained_after_binding(
+      bound_at,
+      Money::from_major(100),
+      Money::from_major(95),
+      now
+    ));
+    assert!(!thresholds.is_drained_after_binding(
+      bound_at,
+      Money::from_major(100),
+      Money::from_major(20),
+      now
+    ));
+  }
+
+  #[test]
+  fn test_drained_outside_window_or_empty_balance() {
+    let thresholds = RiskThresholds::default();
+    let now = Utc::now();
+
+    assert!(!thresholds.is_drained_after_binding(
+      minutes_ago(now, 60),
+      Money::from_major(100),
+      Money::from_major(100),
+      now
+    ));
+    assert!(!thresholds.is_drained_after_binding(
+      minutes_ago(now, 1),
+      Money::ZERO,
+      Money::from_major(10),
+      now
+    ));
+  }
+
+  #[test]
+  fn test_repeated_failed_pins() {
+    let thresholds = RiskThresholds::default();
+    let now = Utc::now();
+
+    let failures: Vec<_> = (0..5).map(|i| minutes_ago(now, i)).collect();
+    assert!(thresholds.has_repeated_failed_pins(&failures, now));
+    assert!(!thresholds.has_repeated_failed_pins(&failures[..4], now));
+  }
+}
@@ -2,18 +2,64 @@ use serde::{Deserialize, Serialize};
 use std::fmt::Display;
 use utoipa::ToSchema;
 
-#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Serialize, Deserialize, ToSchema)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Serialize, Deserialize, sqlx::Type, ToSchema)]
+#[sqlx(type_name = "text")]
 pub enum Permission {
   ConfigureSettings,
 
   SendInvite,
   ViewInvite,
+  RevokeInvite,
 
   RemoveUser,
+  /// Change other users' names, email addresses and roles
+  ManageUsers,
+  /// See who the users are, without contact details
+  ListUsers,
   ReadUserDetails,
 
+  CreateGuest,
   RemoveGuest,
+  /// See which guests exist, without contact details or identifiers
+  ListGuests,
   ReadGuestDetails,
+  ManageGuestIdentifiers,
+
+  ReadTransactions,
+  ReadReports,
+  TopUpWallet,
+  WithdrawFromWallet,
+  RefundTransaction,
+  /// Download the ledger, e.g. as CSV for spreadsheets
+  ExportData,
+  /// Bring over members and their balances from a legacy system
+  ImportMembers,
+  /// Export requested bank payouts for the bank and record their outcome
+  ManagePayouts,
+
+  /// Add or remove staff on any shop; shop owners manage their own without it
+  ManageShopMembers,
+  /// Register webhooks for any shop; shop owners manage their own without it
+  ManageWebhooks,
+
+  ManageLegalHold,
+  ReviewSuspiciousActivity,
+  /// Browse the audit trail of who did what
+  ReadAuditLog,
+  /// Grant or revoke single permissions on top of a user's role
+  ManagePermissions,
+  /// Lift the lock repeated failed logins put on an account
+  UnlockAccounts,
+  /// Read and write internal support notes on users and guests
+  ManageAccountNotes,
+}
+
+/// A per-user exception to what the user's role allows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PermissionOverride {
+  pub permission: Permission,
+  /// Grants the permission when set, revokes it otherwise
+  pub granted: bool,
 }
 
 #[derive(
@@ -27,6 +73,10 @@ pub enum Role {
 
   Owner,
   Admin,
+  /// Read-only access for bookkeeping, e.g. the club's accountant
+  Auditor,
+  /// Event staff at the till: tops up guest wallets and looks them up
+  Cashier,
 }
 
 impl Display for Role {
@@ -34,6 +84,8 @@ impl Display for Role {
     let s = match self {
       Role::Owner => "owner",
       Role::Admin => "admin",
+      Role::Auditor => "auditor",
+      Role::Cashier => "cashier",
       Role::Undefined => "undefined",
     };
     write!(f, "{}", s)
@@ -45,30 +97,88 @@ impl From<String> for Role {
     match s.as_str() {
       "owner" => Role::Owner,
       "admin" => Role::Admin,
+      "auditor" => Role::Auditor,
+      "cashier" => Role::Cashier,
       _ => Role::Undefined,
     }
   }
 }
 
 impl Role {
+  pub fn variants() -> &'static [Role] {
+    &[Role::Owner, Role::Admin, Role::Auditor, Role::Cashier]
+  }
+
   pub fn permissions(&self) -> Vec<Permission> {
     match self {
       Role::Owner => vec![
         Permission::ConfigureSettings,
         Permission::SendInvite,
         Permission::ViewInvite,
+        Permission::RevokeInvite,
         Permission::RemoveUser,
+        Permission::ManageUsers,
+        Permission::ListUsers,
         Permission::ReadUserDetails,
+        Permission::CreateGuest,
         Permission::RemoveGuest,
+        Permission::ListGuests,
         Permission::ReadGuestDetails,
+        Permission::ManageGuestIdentifiers,
+        Permission::ReadTransactions,
+        Permission::ReadReports,
+        Permission::TopUpWallet,
+        Permission::WithdrawFromWallet,
+        Permission::RefundTransaction,
+        Permission::ExportData,
+        Permission::ImportMembers,
+        Permission::ManagePayouts,
+        Permission::ManageShopMembers,
+        Permission::ManageWebhooks,
+        Permission::ManageLegalHold,
+        Permission::ReviewSuspiciousActivity,
+        Permission::ReadAuditLog,
+        Permission::ManagePermissions,
+        Permission::UnlockAccounts,
+        Permission::ManageAccountNotes,
       ],
       Role::Admin => vec![
         Permission::SendInvite,
         Permission::ViewInvite,
+        Permission::RevokeInvite,
         Permission::RemoveUser,
+        Permission::ManageUsers,
+        Permission::ListUsers,
         Permission::ReadUserDetails,
+        Permission::CreateGuest,
         Permission::RemoveGuest,
+        Permission::ListGuests,
+        Permission::ReadGuestDetails,
+        Permission::ManageGuestIdentifiers,
+        Permission::ReadTransactions,
+        Permission::ReadReports,
+        Permission::TopUpWallet,
+        Permission::WithdrawFromWallet,
+        Permission::RefundTransaction,
+        Permission::ExportData,
+        Permission::ManageShopMembers,
+        Permission::ManageWebhooks,
+        Permission::ReviewSuspiciousActivity,
+        Permission::ManageAccountNotes,
+      ],
+      Role::Auditor => vec![
+        Permission::ListUsers,
+        Permission::ReadUserDetails,
+        Permission::ListGuests,
         Permission::ReadGuestDetails,
+        Permission::ReadTransactions,
+        Permission::ReadReports,
+        Permission::ExportData,
+      ],
+      Role::Cashier => vec![
+        Permission::ListGuests,
+        Permission::ReadGuestDetails,
+        Permission::TopUpWallet,
       ],
       Role::Undefined => vec![],
     }
@@ -78,11 +188,31 @@ impl Role {
     self.permissions().contains(&perm)
   }
 
+  /// The role's permissions with a user's grants added and revocations removed.
+  pub fn permissions_with(&self, overrides: &[PermissionOverride]) -> Vec<Permission> {
+    let mut permissions = self.permissions();
+
+    for o in overrides {
+      if o.granted {
+        if !permissions.contains(&o.permission) {
+          permissions.push(o.permission);
+        }
+      } else {
+        permissions.retain(|p| *p != o.permission);
+      }
+    }
+
+    permissions
+  }
+
   pub fn can_assign_role(&self, target_role: Role) -> bool {
     match self {
-      Role::Owner => matches!(target_role, Role::Owner | Role::Admin),
-      Role::Admin => matches!(target_role, Role::Admin),
-      Role::Undefined => false,
+      Role::Owner => matches!(
+        target_role,
+        Role::Owner | Role::Admin | Role::Auditor | Role::Cashier
+      ),
+      Role::Admin => matches!(target_role, Role::Admin | Role::Cashier),
+      Role::Auditor | Role::Cashier | Role::Undefined => false,
     }
   }
 }
@@ -95,11 +225,31 @@ mod tests {
   fn test_role_permissions() {
     let owner_perms = Role::Owner.permissions();
     assert!(owner_perms.contains(&Permission::ConfigureSettings));
+    assert!(owner_perms.contains(&Permission::ManageLegalHold));
+    assert!(owner_perms.contains(&Permission::ReadAuditLog));
     assert!(owner_perms.contains(&Permission::SendInvite));
 
     let admin_perms = Role::Admin.permissions();
     assert!(!admin_perms.contains(&Permission::ConfigureSettings));
+    assert!(!admin_perms.contains(&Permission::ManageLegalHold));
+    assert!(!admin_perms.contains(&Permission::ReadAuditLog));
     assert!(admin_perms.contains(&Permission::SendInvite));
+    assert!(admin_perms.contains(&Permission::CreateGuest));
+    assert!(admin_perms.contains(&Permission::TopUpWallet));
+    assert!(admin_perms.contains(&Permission::ManageAccountNotes));
+
+    let auditor_perms = Role::Auditor.permissions();
+    assert!(auditor_perms.contains(&Permission::ListUsers));
+    assert!(auditor_perms.contains(&Permission::ReadUserDetails));
+    assert!(auditor_perms.contains(&Permission::ReadTransactions));
+    assert!(auditor_perms.contains(&Permission::ReadReports));
+    assert!(auditor_perms.contains(&Permission::ExportData));
+    assert!(!auditor_perms.contains(&Permission::ManageAccountNotes));
+
+    let cashier_perms = Role::Cashier.permissions();
+    assert!(cashier_perms.contains(&Permission::TopUpWallet));
+    assert!(cashier_perms.contains(&Permission::ReadGuestDetails));
+    assert!(!cashier_perms.contains(&Permission::WithdrawFromWallet));
 
     let undefined_perms = Role::Undefined.permissions();
     assert!(undefined_perms.is_empty());
@@ -112,23 +262,119 @@ mod tests {
 
     assert!(!Role::Admin.has_permission(Permission::ConfigureSettings));
     assert!(Role::Admin.has_permission(Permission::SendInvite));
+    assert!(Role::Admin.has_permission(Permission::RevokeInvite));
 
     assert!(!Role::Undefined.has_permission(Permission::ConfigureSettings));
     assert!(!Role::Undefined.has_permission(Permission::SendInvite));
   }
 
+  #[test]
+  fn test_permissions_with_overrides() {
+    let overrides = [
+      PermissionOverride {
+        permission: Permission::RefundTransaction,
+        granted: false,
+      },
+      PermissionOverride {
+        permission: Permission::ManageLegalHold,
+        granted: true,
+      },
+    ];
+
+    let admin = Role::Admin.permissions_with(&overrides);
+    assert!(!admin.contains(&Permission::RefundTransaction));
+    assert!(admin.contains(&Permission::ManageLegalHold));
+    assert!(admin.contains(&Permission::TopUpWallet));
+
+    // Granting what the role already has doesn't duplicate it
+    let owner = Role::Owner.permissions_with(&overrides[1..]);
+    assert_eq!(owner.len(), Role::Owner.permissions().len());
+  }
+
+  /// Whether holding `perm` lets a user change anything. Exhaustive, so
+  /// every new permission has to be classified here.
+  fn mutates(perm: Permission) -> bool {
+    match perm {
+      Permission::ViewInvite
+      | Permission::ListUsers
+      | Permission::ReadUserDetails
+      | Permission::ListGuests
+      | Permission::ReadGuestDetails
+      | Permission::ReadTransactions
+      | Permission::ReadReports
+      | Permission::ExportData
+      | Permission::ReadAuditLog => false,
+      Permission::ConfigureSettings
+      | Permission::SendInvite
+      | Permission::RevokeInvite
+      | Permission::RemoveUser
+      | Permission::ManageUsers
+      | Permission::CreateGuest
+      | Permission::RemoveGuest
+      | Permission::ManageGuestIdentifiers
+      | Permission::TopUpWallet
+      | Permission::WithdrawFromWallet
+      | Permission::RefundTransaction
+      | Permission::ImportMembers
+      | Permission::ManagePayouts
+      | Permission::ManageShopMembers
+      | Permission::ManageWebhooks
+      | Permission::ManageLegalHold
+      | Permission::ReviewSuspiciousActivity
+      | Permission::ManagePermissions
+      | Permission::UnlockAccounts
+      | Permission::ManageAccountNotes => true,
+    }
+  }
+
+  #[test]
+  fn test_auditor_is_read_only() {
+    for perm in Role::Auditor.permissions() {
+      assert!(!mutates(perm), "{:?}", perm);
+    }
+  }
+
+  #[test]
+  fn test_cashier_cannot_manage_users_or_settings() {
+    let forbidden = [
+      Permission::ConfigureSettings,
+      Permission::SendInvite,
+      Permission::ViewInvite,
+      Permission::RevokeInvite,
+      Permission::RemoveUser,
+      Permission::ListUsers,
+      Permission::ReadUserDetails,
+      Permission::ManageShopMembers,
+      Permission::ManageWebhooks,
+      Permission::ReadAuditLog,
+    ];
+
+    for perm in forbidden {
+      assert!(!Role::Cashier.has_permission(perm), "{:?}", perm);
+    }
+  }
+
   #[test]
   fn test_can_assign_role() {
-    // Owner can assign Owner and Admin
+    // Owner can assign Owner, Admin, Auditor and Cashier
     assert!(Role::Owner.can_assign_role(Role::Owner));
     assert!(Role::Owner.can_assign_role(Role::Admin));
+    assert!(Role::Owner.can_assign_role(Role::Auditor));
+    assert!(Role::Owner.can_assign_role(Role::Cashier));
     assert!(!Role::Owner.can_assign_role(Role::Undefined));
 
-    // Admin can assign Admin only
+    // Admin can assign Admin and Cashier
     assert!(!Role::Admin.can_assign_role(Role::Owner));
     assert!(Role::Admin.can_assign_role(Role::Admin));
+    assert!(!Role::Admin.can_assign_role(Role::Auditor));
+    assert!(Role::Admin.can_assign_role(Role::Cashier));
     assert!(!Role::Admin.can_assign_role(Role::Undefined));
 
+    // Auditor and Cashier can assign nothing
+    assert!(!Role::Auditor.can_assign_role(Role::Auditor));
+    assert!(!Role::Auditor.can_assign_role(Role::Admin));
+    assert!(!Role::Cashier.can_assign_role(Role::Cashier));
+
     // Undefined can assign nothing
     assert!(!Role::Undefined.can_assign_role(Role::Owner));
     assert!(!Role::Undefined.can_assign_role(Role::Admin));
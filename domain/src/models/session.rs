@@ -1,8 +1,9 @@
 use chrono::{DateTime, Duration, Utc};
 
-use crate::{Id, UserId};
+use crate::{DevicePublicKey, Id, UserId};
 
 pub type SessionId = Id<Session>;
+pub type PasswordConfirmationId = Id<PasswordConfirmation>;
 
 #[derive(Debug, Clone)]
 pub struct Session {
@@ -11,13 +12,283 @@ pub struct Session {
   pub token: String,
   pub user_agent: Option<String>,
   pub ip_address: Option<String>,
+  /// Key of the device the session is bound to; sensitive requests must be
+  /// signed with it
+  pub device_public_key: Option<DevicePublicKey>,
   pub expires_in: Duration,
+  pub last_used_at: Option<DateTime<Utc>>,
   pub created_at: DateTime<Utc>,
   pub updated_at: Option<DateTime<Utc>>,
 }
 
 impl Session {
   pub fn is_expired(&self) -> bool {
-    Utc::now() > self.created_at + self.expires_in
+    Utc::now() > self.expires_at()
+  }
+
+  pub fn expires_at(&self) -> DateTime<Utc> {
+    self.created_at + self.expires_in
+  }
+
+  pub fn is_device_bound(&self) -> bool {
+    self.device_public_key.is_some()
+  }
+}
+
+/// What a signed session token says about its session, trusted once its
+/// signature checks out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SessionClaims {
+  pub session_id: SessionId,
+  pub user_id: UserId,
+  /// To the millisecond, as tokens carry it
+  pub issued_at: DateTime<Utc>,
+  pub expires_at: DateTime<Utc>,
+}
+
+impl SessionClaims {
+  pub fn is_expired(&self) -> bool {
+    Utc::now() > self.expires_at
+  }
+}
+
+/// Signed tokens of a session that no longer count, though they have not
+/// expired yet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SessionRevocation {
+  pub session_id: SessionId,
+  /// Only tokens issued before this, as when the token was swapped; every
+  /// token of the session without one, as when it ended
+  pub issued_before: Option<DateTime<Utc>>,
+  /// When the last of the revoked tokens expires
+  pub expires_at: DateTime<Utc>,
+}
+
+impl SessionRevocation {
+  pub fn revokes(&self, claims: &SessionClaims) -> bool {
+    claims.session_id == self.session_id
+      && self
+        .issued_before
+        .is_none_or(|issued_before| claims.issued_at < issued_before)
+  }
+}
+
+/// How long sessions last.
+#[derive(Debug, Clone, Copy)]
+pub struct SessionTimeouts {
+  /// Longest a session lasts after signing in, however active
+  pub absolute: Duration,
+  /// How long an unused session lasts; each use pushes its expiry out again
+  pub idle: Option<Duration>,
+}
+
+impl SessionTimeouts {
+  /// Latest a session started at `created_at` can last until.
+  pub fn ends_at(&self, created_at: DateTime<Utc>) -> DateTime<Utc> {
+    created_at + self.absolute
+  }
+
+  /// When a session started at `created_at` and used at `now` expires.
+  pub fn expires_at(&self, created_at: DateTime<Utc>, now: DateTime<Utc>) -> DateTime<Utc> {
+    let ends_at = self.ends_at(created_at);
+
+    self.idle.map_or(ends_at, |idle| (now + idle).min(ends_at))
+  }
+
+  /// Whether using the session at `now` should push its expiry out, which is
+  /// once half of the idle timeout has passed, so not every request writes.
+  pub fn is_due_for_renewal(&self, session: &Session, now: DateTime<Utc>) -> bool {
+    let Some(idle) = self.idle else {
+      return false;
+    };
+
+    session.expires_at() - now < idle / 2
+      && self.expires_at(session.created_at, now) > session.expires_at()
+  }
+}
+
+/// Short-lived proof that the session owner re-entered their password.
+#[derive(Debug, Clone)]
+pub struct PasswordConfirmation {
+  pub id: PasswordConfirmationId,
+  pub session_id: SessionId,
+  pub token: String,
+  pub expires_at: DateTime<Utc>,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl PasswordConfirmation {
+  pub fn is_expired(&self) -> bool {
+    Utc::now() > self.expires_at
+  }
+}
+
+/// Single-use challenge a device-bound session signs to prove the request
+/// comes from its device.
+#[derive(Debug, Clone)]
+pub struct DeviceNonce {
+  pub nonce: String,
+  pub expires_at: DateTime<Utc>,
+}
+
+/// When repeated wrong passwords lock an account.
+#[derive(Debug, Clone, Copy)]
+pub struct LoginLockout {
+  /// Failed logins within `window` that lock the account; zero never locks
+  pub max_failures: usize,
+  /// How far back failures count, and how long the account stays locked
+  pub window: Duration,
+}
+
+impl Default for LoginLockout {
+  fn default() -> Self {
+    Self {
+      max_failures: 5,
+      window: Duration::minutes(15),
+    }
+  }
+}
+
+impl LoginLockout {
+  /// Until when to lock the account after the given failed logins, if at all.
+  pub fn locked_until(
+    &self,
+    failures: &[DateTime<Utc>],
+    now: DateTime<Utc>,
+  ) -> Option<DateTime<Utc>> {
+    if self.max_failures == 0 {
+      return None;
+    }
+
+    let recent = failures
+      .iter()
+      .filter(|t| **t <= now && now - **t <= self.window)
+      .count();
+
+    (recent >= self.max_failures).then(|| now + self.window)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_login_lockout_counts_recent_failures() {
+    let lockout = LoginLockout {
+      max_failures: 3,
+      window: Duration::minutes(15),
+    };
+    let now = Utc::now();
+    let recent = [now - Duration::minutes(1), now - Duration::minutes(5)];
+
+    assert_eq!(lockout.locked_until(&recent, now), None);
+
+    let stale = [recent[0], recent[1], now - Duration::minutes(30)];
+    assert_eq!(lockout.locked_until(&stale, now), None);
+
+    let enough = [recent[0], recent[1], now];
+    assert_eq!(
+      lockout.locked_until(&enough, now),
+      Some(now + Duration::minutes(15))
+    );
+  }
+
+  fn session(created_at: DateTime<Utc>, expires_at: DateTime<Utc>) -> Session {
+    Session {
+      id: uuid::Uuid::nil().into(),
+      user_id: uuid::Uuid::nil().into(),
+      token: String::new(),
+      user_agent: None,
+      ip_address: None,
+      device_public_key: None,
+      expires_in: expires_at - created_at,
+      last_used_at: None,
+      created_at,
+      updated_at: None,
+    }
+  }
+
+  #[test]
+  fn test_session_timeouts_slide_up_to_absolute() {
+    let timeouts = SessionTimeouts {
+      absolute: Duration::hours(24),
+      idle: Some(Duration::hours(2)),
+    };
+    let start = Utc::now();
+
+    assert_eq!(
+      timeouts.expires_at(start, start),
+      start + Duration::hours(2)
+    );
+    assert_eq!(
+      timeouts.expires_at(start, start + Duration::hours(23)),
+      start + Duration::hours(24)
+    );
+
+    let fresh = session(start, start + Duration::hours(2));
+    assert!(!timeouts.is_due_for_renewal(&fresh, start + Duration::minutes(30)));
+    assert!(timeouts.is_due_for_renewal(&fresh, start + Duration::minutes(90)));
+
+    let last = session(start, start + Duration::hours(24));
+    assert!(!timeouts.is_due_for_renewal(&last, start + Duration::minutes(23 * 60 + 30)));
+  }
+
+  #[test]
+  fn test_session_timeouts_without_idle_are_fixed() {
+    let timeouts = SessionTimeouts {
+      absolute: Duration::days(1),
+      idle: None,
+    };
+    let start = Utc::now();
+    let fixed = session(start, start + Duration::days(1));
+
+    assert_eq!(
+      timeouts.expires_at(start, start + Duration::hours(20)),
+      start + Duration::days(1)
+    );
+    assert!(!timeouts.is_due_for_renewal(&fixed, start + Duration::hours(23)));
+  }
+
+  #[test]
+  fn test_session_revocation_spares_newer_tokens() {
+    let now = Utc::now();
+    let claims = SessionClaims {
+      session_id: uuid::Uuid::nil().into(),
+      user_id: uuid::Uuid::nil().into(),
+      issued_at: now,
+      expires_at: now + Duration::hours(1),
+    };
+    let rotated = SessionRevocation {
+      session_id: claims.session_id,
+      issued_before: Some(now + Duration::seconds(1)),
+      expires_at: claims.expires_at,
+    };
+
+    assert!(rotated.revokes(&claims));
+    assert!(!rotated.revokes(&SessionClaims {
+      issued_at: now + Duration::seconds(1),
+      ..claims
+    }));
+    assert!(SessionRevocation {
+      issued_before: None,
+      ..rotated
+    }
+    .revokes(&SessionClaims {
+      issued_at: now + Duration::minutes(5),
+      ..claims
+    }));
+  }
+
+  #[test]
+  fn test_login_lockout_disabled() {
+    let lockout = LoginLockout {
+      max_failures: 0,
+      ..Default::default()
+    };
+    let now = Utc::now();
+
+    assert_eq!(lockout.locked_until(&[now, now, now], now), None);
   }
 }
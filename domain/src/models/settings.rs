@@ -0,0 +1,78 @@
+use chrono::{DateTime, Utc};
+
+use crate::types::Currency;
+use crate::ActorId;
+
+/// Settings operators change at runtime, as they are in effect. Until first
+/// changed they are the server's configured defaults.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Settings {
+  /// Days an invite can be accepted for
+  pub invite_expiration_days: i32,
+  /// Whether wallets of new members and guests may go below zero
+  pub allow_overdraft_by_default: bool,
+  /// Currency new wallets are opened in, and online payments taken in
+  /// unless the wallet is in another one
+  pub currency: Currency,
+  /// Shown as the sender of emails, e.g. `Cayo Club`; the address alone
+  /// without it
+  pub email_sender_name: Option<String>,
+  pub changed_by: Option<ActorId>,
+  pub changed_at: Option<DateTime<Utc>>,
+}
+
+impl Settings {
+  pub const MAX_INVITE_EXPIRATION_DAYS: i32 = 90;
+
+  /// Settings nobody changed yet.
+  pub fn defaults(currency: Currency) -> Self {
+    Self {
+      invite_expiration_days: 7,
+      allow_overdraft_by_default: false,
+      currency,
+      email_sender_name: None,
+      changed_by: None,
+      changed_at: None,
+    }
+  }
+
+  /// Why the settings can't take effect, if they can't.
+  pub fn check(&self) -> Result<(), String> {
+    if !(1..=Self::MAX_INVITE_EXPIRATION_DAYS).contains(&self.invite_expiration_days) {
+      return Err(format!(
+        "Invites must expire within 1 to {} days",
+        Self::MAX_INVITE_EXPIRATION_DAYS
+      ));
+    }
+    if self
+      .email_sender_name
+      .as_ref()
+      .is_some_and(|name| name.trim().is_empty() || name.contains(['<', '>', '"', '\r', '\n']))
+    {
+      return Err("The sender name must be plain text".to_string());
+    }
+
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_check() {
+    let defaults = Settings::defaults(Currency::EUR);
+    assert!(defaults.check().is_ok());
+
+    let with = |change: fn(&mut Settings)| {
+      let mut settings = defaults.clone();
+      change(&mut settings);
+      settings.check()
+    };
+    assert!(with(|s| s.invite_expiration_days = 0).is_err());
+    assert!(with(|s| s.invite_expiration_days = 90).is_ok());
+    assert!(with(|s| s.email_sender_name = Some("Cayo Club".to_string())).is_ok());
+    assert!(with(|s| s.email_sender_name = Some("Evil <x@y>".to_string())).is_err());
+  }
+}
@@ -1,6 +1,6 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
 
-use crate::{types::Money, Id, UserId};
+use crate::{types::Money, Id, UserId, WalletId};
 
 pub type ShopId = Id<Shop>;
 pub type ShopOfferingId = Id<ShopOffering>;
@@ -11,10 +11,60 @@ pub struct Shop {
   pub id: ShopId,
   pub owner: Option<UserId>,
   pub name: String,
+  /// Wallet the shop's sales are paid into
+  pub wallet_id: WalletId,
+  /// Set when the shop takes pre-orders
+  pub pickup_slots: Option<PickupSlots>,
   pub created_at: DateTime<Utc>,
   pub updated_at: Option<DateTime<Utc>>,
 }
 
+impl Shop {
+  pub fn is_owned_by(&self, user: &UserId) -> bool {
+    self.owner.as_ref() == Some(user)
+  }
+}
+
+/// How a shop splits pickup times for pre-orders: slots of equal length, each
+/// taking a limited number of orders.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PickupSlots {
+  pub length: Duration,
+  /// Pre-orders each slot takes
+  pub capacity: i32,
+}
+
+impl PickupSlots {
+  /// Whether a slot starts at `at`. Slots are aligned to the Unix epoch, so
+  /// 15 minute slots start on the hour and every quarter past.
+  pub fn is_slot_start(&self, at: DateTime<Utc>) -> bool {
+    let length = self.length.num_seconds();
+
+    length > 0 && at.timestamp_subsec_nanos() == 0 && at.timestamp().rem_euclid(length) == 0
+  }
+
+  /// Starts of the slots within `[from, to)`.
+  pub fn starts_between(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> Vec<DateTime<Utc>> {
+    let length = self.length.num_seconds();
+    if length <= 0 {
+      return Vec::new();
+    }
+
+    let mut start = from.timestamp().div_euclid(length) * length;
+    if start < from.timestamp() || (start == from.timestamp() && from.timestamp_subsec_nanos() > 0)
+    {
+      start += length;
+    }
+
+    let mut starts = Vec::new();
+    while let Some(at) = DateTime::from_timestamp(start, 0).filter(|at| *at < to) {
+      starts.push(at);
+      start += length;
+    }
+    starts
+  }
+}
+
 #[derive(Debug, Clone)]
 pub struct ShopOffering {
   pub id: ShopOfferingId,
@@ -34,3 +84,58 @@ pub struct ShopMember {
   pub created_at: DateTime<Utc>,
   pub updated_at: Option<DateTime<Utc>>,
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::Id;
+  use chrono::Utc;
+
+  fn create_shop(owner: Option<UserId>) -> Shop {
+    Shop {
+      id: Id::new(),
+      owner,
+      name: "Bar".to_string(),
+      wallet_id: Id::new(),
+      pickup_slots: None,
+      created_at: Utc::now(),
+      updated_at: None,
+    }
+  }
+
+  #[test]
+  fn test_is_owned_by() {
+    let owner = Id::new();
+    let other = Id::new();
+
+    let shop = create_shop(Some(owner));
+    assert!(shop.is_owned_by(&owner));
+    assert!(!shop.is_owned_by(&other));
+
+    let orphan = create_shop(None);
+    assert!(!orphan.is_owned_by(&owner));
+  }
+
+  #[test]
+  fn test_pickup_slots_are_aligned() {
+    let slots = PickupSlots {
+      length: Duration::minutes(15),
+      capacity: 10,
+    };
+    let at = |s: &str| s.parse::<DateTime<Utc>>().unwrap();
+
+    assert!(slots.is_slot_start(at("2026-10-16T18:00:00Z")));
+    assert!(slots.is_slot_start(at("2026-10-16T18:45:00Z")));
+    assert!(!slots.is_slot_start(at("2026-10-16T18:50:00Z")));
+    assert!(!slots.is_slot_start(at("2026-10-16T18:45:00.5Z")));
+
+    assert_eq!(
+      slots.starts_between(at("2026-10-16T18:00:00Z"), at("2026-10-16T18:30:00Z")),
+      vec![at("2026-10-16T18:00:00Z"), at("2026-10-16T18:15:00Z")]
+    );
+    assert_eq!(
+      slots.starts_between(at("2026-10-16T18:00:01Z"), at("2026-10-16T18:30:01Z")),
+      vec![at("2026-10-16T18:15:00Z"), at("2026-10-16T18:30:00Z")]
+    );
+  }
+}
@@ -0,0 +1,61 @@
+use std::fmt::Display;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::{types::Money, ActorId, Id, TransactionId, WalletId};
+
+pub type TopUpId = Id<TopUp>;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum TopUpStatus {
+  /// Waiting for the payer to finish the hosted checkout
+  #[default]
+  Pending,
+  /// Paid and credited to the wallet
+  Succeeded,
+  /// Abandoned, expired or declined
+  Failed,
+}
+
+/// A top-up paid by card or bank transfer through the payment service
+/// provider rather than in cash at the register.
+#[derive(Debug, Clone)]
+pub struct TopUp {
+  pub id: TopUpId,
+  pub wallet_id: WalletId,
+  pub amount: Money,
+  pub status: TopUpStatus,
+  /// Checkout session at the provider
+  pub provider_session_id: Option<String>,
+  /// Payment the provider booked for the checkout, once paid
+  pub provider_payment_id: Option<String>,
+  /// Credit from the clearing wallet; set once the provider confirms payment
+  pub transaction_id: Option<TransactionId>,
+  pub requested_by: Option<ActorId>,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl Display for TopUpStatus {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let status_str = match self {
+      TopUpStatus::Pending => "pending",
+      TopUpStatus::Succeeded => "succeeded",
+      TopUpStatus::Failed => "failed",
+    };
+    write!(f, "{}", status_str)
+  }
+}
+
+impl From<&str> for TopUpStatus {
+  fn from(value: &str) -> Self {
+    match value {
+      "succeeded" => TopUpStatus::Succeeded,
+      "failed" => TopUpStatus::Failed,
+      _ => TopUpStatus::Pending,
+    }
+  }
+}
@@ -1,6 +1,22 @@
+use std::collections::BTreeMap;
+
 use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::{
+  types::{Currency, Money},
+  wallet::WalletId,
+  ActorId, Id, WalletLabel,
+};
 
-use crate::{types::Money, wallet::WalletId, ActorId, Id};
+/// Most metadata entries a transaction carries
+const MAX_METADATA_ENTRIES: usize = 8;
+/// Longest metadata value, in characters
+const MAX_METADATA_VALUE_LEN: usize = 128;
+/// Largest metadata as the database renders it, in bytes; its check on
+/// `transactions.metadata` has the same bound
+const MAX_METADATA_BYTES: usize = 2048;
 
 pub type TransactionId = Id<Transaction>;
 
@@ -11,7 +27,142 @@ pub struct Transaction {
   pub destination: WalletId,
   pub executor: Option<ActorId>,
   pub amount: Money,
+  /// That of both wallets
+  pub currency: Currency,
   pub description: Option<String>,
+  /// The transaction this one compensates, if it is a refund
+  pub reversal_of: Option<TransactionId>,
+  pub metadata: TransactionMetadata,
   pub created_at: DateTime<Utc>,
   pub updated_at: Option<DateTime<Utc>>,
 }
+
+/// What booked a transaction, which decides the metadata keys it may carry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MetadataSource {
+  /// A till or terminal at a shop or the register
+  Pos,
+  /// A payment service provider settling an online top-up
+  OnlineTopUp,
+  /// A member import
+  Import,
+  /// A payment service provider taking back a disputed online top-up
+  Chargeback,
+}
+
+impl MetadataSource {
+  pub fn allowed_keys(self) -> &'static [&'static str] {
+    match self {
+      MetadataSource::Pos => &["terminal_id", "receipt_number", "external_reference"],
+      MetadataSource::OnlineTopUp => &["provider_payment_id"],
+      MetadataSource::Import => &["import_line", "external_reference"],
+      MetadataSource::Chargeback => &["provider_dispute_id"],
+    }
+  }
+}
+
+/// Structured context attached to a transaction, such as the terminal it
+/// was booked on. Only constructed through [`TransactionMetadata::new`] or
+/// read back from the database, so every value respects the key whitelist
+/// of its source and the size bounds.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(transparent)]
+#[schema(value_type = Object, example = json!({"terminal_id": "bar-2"}))]
+pub struct TransactionMetadata(BTreeMap<String, String>);
+
+impl TransactionMetadata {
+  pub fn new(source: MetadataSource, entries: BTreeMap<String, String>) -> Result<Self, String> {
+    if entries.len() > MAX_METADATA_ENTRIES {
+      return Err(format!(
+        "Metadata has at most {} entries",
+        MAX_METADATA_ENTRIES
+      ));
+    }
+
+    let allowed = source.allowed_keys();
+    for (key, value) in &entries {
+      if !allowed.contains(&key.as_str()) {
+        return Err(format!(
+          "Metadata key '{}' is not allowed; expected one of {}",
+          key,
+          allowed.join(", ")
+        ));
+      }
+      if value.is_empty() || value.chars().count() > MAX_METADATA_VALUE_LEN {
+        return Err(format!(
+          "Metadata value of '{}' must have 1 to {} characters",
+          key, MAX_METADATA_VALUE_LEN
+        ));
+      }
+    }
+
+    // The database writes a space after each colon and comma
+    let size = serde_json::to_string(&entries)
+      .map(|json| json.len() + (2 * entries.len()).saturating_sub(1))
+      .unwrap_or(usize::MAX);
+    if size > MAX_METADATA_BYTES {
+      return Err(format!(
+        "Metadata is larger than {} bytes",
+        MAX_METADATA_BYTES
+      ));
+    }
+
+    Ok(Self(entries))
+  }
+
+  /// Metadata as stored, which was checked when it was written.
+  pub fn from_stored(entries: BTreeMap<String, String>) -> Self {
+    Self(entries)
+  }
+
+  pub fn get(&self, key: &str) -> Option<&str> {
+    self.0.get(key).map(String::as_str)
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.0.is_empty()
+  }
+
+  pub fn entries(&self) -> &BTreeMap<String, String> {
+    &self.0
+  }
+}
+
+/// A transaction together with the names needed to read it outside the
+/// system, e.g. in a spreadsheet.
+#[derive(Debug, Clone)]
+pub struct LedgerLine {
+  pub transaction: Transaction,
+  pub source_label: Option<WalletLabel>,
+  pub destination_label: Option<WalletLabel>,
+  /// Full name of the executing user, if any
+  pub executor_name: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn entries(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+    pairs
+      .iter()
+      .map(|(k, v)| (k.to_string(), v.to_string()))
+      .collect()
+  }
+
+  #[test]
+  fn test_metadata_keys_depend_on_source() {
+    let pos = entries(&[("terminal_id", "bar-2")]);
+    assert!(TransactionMetadata::new(MetadataSource::Pos, pos.clone()).is_ok());
+    assert!(TransactionMetadata::new(MetadataSource::Import, pos).is_err());
+  }
+
+  #[test]
+  fn test_metadata_bounds() {
+    let empty_value = entries(&[("terminal_id", "")]);
+    assert!(TransactionMetadata::new(MetadataSource::Pos, empty_value).is_err());
+
+    let long_value = entries(&[("terminal_id", &"x".repeat(MAX_METADATA_VALUE_LEN + 1))]);
+    assert!(TransactionMetadata::new(MetadataSource::Pos, long_value).is_err());
+  }
+}
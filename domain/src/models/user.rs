@@ -1,8 +1,16 @@
+use std::fmt::Display;
+
 use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
-use crate::{actor::ActorId, Email, HashedPassword, Id, Role};
+use crate::{
+  actor::ActorId, Email, HashedPassword, Id, Locale, Permission, PermissionOverride, Role,
+};
 
 pub type UserId = Id<User>;
+pub type EmailChangeId = Id<EmailChange>;
+pub type PasswordSetupId = Id<PasswordSetup>;
 
 #[derive(Debug, Clone)]
 pub struct User {
@@ -13,6 +21,82 @@ pub struct User {
   pub first_name: String,
   pub last_name: String,
   pub role: Role,
+  /// Language of the emails sent to the user
+  pub locale: Locale,
   pub created_at: DateTime<Utc>,
   pub updated_at: Option<DateTime<Utc>>,
 }
+
+/// A signed-in user together with their grants and revocations, as needed
+/// to authorize a request.
+#[derive(Debug, Clone)]
+pub struct Principal {
+  pub user: User,
+  pub overrides: Vec<PermissionOverride>,
+}
+
+impl Principal {
+  /// What the user may do, see [`Role::permissions_with`].
+  pub fn permissions(&self) -> Vec<Permission> {
+    self.user.role.permissions_with(&self.overrides)
+  }
+}
+
+/// A new email address a user asked for. It replaces the current one only
+/// once someone follows the link sent to it.
+#[derive(Debug, Clone)]
+pub struct EmailChange {
+  pub id: EmailChangeId,
+  pub user_id: UserId,
+  pub email: Email,
+  pub token: String,
+  pub expires_at: DateTime<Utc>,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl EmailChange {
+  pub fn is_expired(&self) -> bool {
+    Utc::now() > self.expires_at
+  }
+}
+
+/// Lets a user who was created for them, e.g. by an import, choose their
+/// password with the token emailed to them.
+#[derive(Debug, Clone)]
+pub struct PasswordSetup {
+  pub id: PasswordSetupId,
+  pub user_id: UserId,
+  pub token: String,
+  pub expires_at: DateTime<Utc>,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl PasswordSetup {
+  pub fn is_expired(&self) -> bool {
+    Utc::now() > self.expires_at
+  }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum UserSortField {
+  #[default]
+  CreatedAt,
+  Email,
+  FirstName,
+  LastName,
+}
+
+impl Display for UserSortField {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let field_str = match self {
+      UserSortField::CreatedAt => "created_at",
+      UserSortField::Email => "email",
+      UserSortField::FirstName => "first_name",
+      UserSortField::LastName => "last_name",
+    };
+    write!(f, "{}", field_str)
+  }
+}
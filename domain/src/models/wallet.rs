@@ -1,15 +1,34 @@
 use std::fmt::Display;
 
 use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
-use crate::{ActorId, Id};
+use crate::{
+  types::{Currency, Money},
+  ActorDetails, ActorId, Id,
+};
 
 pub type WalletId = Id<Wallet>;
+pub type WalletLegalHoldEventId = Id<WalletLegalHoldEvent>;
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+/// Longest display name of a wallet, in characters
+const MAX_DISPLAY_NAME_LEN: usize = 40;
+/// Longest icon name
+const MAX_ICON_LEN: usize = 32;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
 pub enum WalletLabel {
   OutsideCash,
   OutsideCashDiscrepancy,
+  /// Source of the opening balances of members imported from legacy systems
+  LegacyImport,
+  /// Money paid in through the payment service provider, until it is paid out
+  /// to the organiser's bank account
+  PspClearing,
+  /// Money withdrawn to members' bank accounts, until the transfer goes out
+  BankPayouts,
 }
 
 #[derive(Debug, Clone)]
@@ -17,16 +36,175 @@ pub struct Wallet {
   pub id: WalletId,
   pub owner: Option<ActorId>,
   pub label: Option<WalletLabel>,
+  /// What the balance is counted in; money only moves between wallets of the
+  /// same currency
+  pub currency: Currency,
   pub allow_overdraft: bool,
+  pub legal_hold: bool,
+  pub frozen: bool,
+  pub appearance: WalletAppearance,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// How the owner wants a personal wallet shown in the app, e.g. "Bar
+/// budget" in orange. Purely cosmetic; only constructed through
+/// [`WalletAppearance::new`] or read back from the database.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WalletAppearance {
+  display_name: Option<String>,
+  /// Lower case `#rrggbb`
+  color: Option<String>,
+  /// Name of an icon in the app's set, e.g. `beer`
+  icon: Option<String>,
+}
+
+impl WalletAppearance {
+  /// Trims the display name and lower cases the color. Blank values count as
+  /// unset.
+  pub fn new(
+    display_name: Option<String>,
+    color: Option<String>,
+    icon: Option<String>,
+  ) -> Result<Self, String> {
+    let display_name = display_name
+      .map(|name| name.trim().to_string())
+      .filter(|name| !name.is_empty());
+    if display_name
+      .as_ref()
+      .is_some_and(|name| name.chars().count() > MAX_DISPLAY_NAME_LEN)
+    {
+      return Err(format!(
+        "Display name is limited to {} characters",
+        MAX_DISPLAY_NAME_LEN
+      ));
+    }
+
+    let color = color
+      .map(|color| color.trim().to_ascii_lowercase())
+      .filter(|color| !color.is_empty());
+    if color.as_ref().is_some_and(|color| {
+      color.len() != 7
+        || !color.starts_with('#')
+        || !color[1..].bytes().all(|b| b.is_ascii_hexdigit())
+    }) {
+      return Err("Color must look like #ff8800".to_string());
+    }
+
+    let icon = icon
+      .map(|icon| icon.trim().to_string())
+      .filter(|icon| !icon.is_empty());
+    if icon.as_ref().is_some_and(|icon| {
+      icon.len() > MAX_ICON_LEN
+        || !icon
+          .bytes()
+          .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'-')
+    }) {
+      return Err(format!(
+        "Icon must be up to {} lower case letters, digits and dashes",
+        MAX_ICON_LEN
+      ));
+    }
+
+    Ok(Self {
+      display_name,
+      color,
+      icon,
+    })
+  }
+
+  /// Appearance as stored, which was checked when it was written.
+  pub fn from_stored(
+    display_name: Option<String>,
+    color: Option<String>,
+    icon: Option<String>,
+  ) -> Self {
+    Self {
+      display_name,
+      color,
+      icon,
+    }
+  }
+
+  pub fn display_name(&self) -> Option<&str> {
+    self.display_name.as_deref()
+  }
+
+  pub fn color(&self) -> Option<&str> {
+    self.color.as_deref()
+  }
+
+  pub fn icon(&self) -> Option<&str> {
+    self.icon.as_deref()
+  }
+}
+
+/// A wallet with its owner resolved and its current balance.
+#[derive(Debug, Clone)]
+pub struct WalletDetails {
+  pub wallet: Wallet,
+  /// `None` for system wallets and owners that are neither user nor guest
+  pub owner: Option<ActorDetails>,
+  pub balance: Money,
+}
+
+/// A wallet whose running balance disagrees with the sum of its transactions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BalanceDrift {
+  pub wallet_id: WalletId,
+  pub stored_cents: i64,
+  pub ledger_cents: i64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum LegalHoldAction {
+  Placed,
+  Released,
+}
+
+#[derive(Debug, Clone)]
+pub struct WalletLegalHoldEvent {
+  pub id: WalletLegalHoldEventId,
+  pub wallet_id: WalletId,
+  pub actor_id: Option<ActorId>,
+  pub action: LegalHoldAction,
+  pub reason: String,
   pub created_at: DateTime<Utc>,
   pub updated_at: Option<DateTime<Utc>>,
 }
 
+impl Wallet {
+  /// Whether the wallet may send `amount` given its current `balance`.
+  ///
+  /// Wallets without overdraft must not end up below zero.
+  pub fn can_send(&self, balance: Money, amount: Money) -> bool {
+    self.allow_overdraft
+      || balance
+        .checked_sub(amount)
+        .is_some_and(|remaining| !remaining.is_negative())
+  }
+
+  /// Whether `amount` may leave the system to a bank account given the
+  /// wallet's current `balance`.
+  ///
+  /// Overdraft is credit inside the venue only, so a payout always has to be
+  /// covered by the balance.
+  pub fn can_pay_out(&self, balance: Money, amount: Money) -> bool {
+    balance
+      .checked_sub(amount)
+      .is_some_and(|remaining| !remaining.is_negative())
+  }
+}
+
 impl WalletLabel {
   pub fn variants() -> &'static [WalletLabel] {
     &[
       WalletLabel::OutsideCash,
       WalletLabel::OutsideCashDiscrepancy,
+      WalletLabel::LegacyImport,
+      WalletLabel::PspClearing,
+      WalletLabel::BankPayouts,
     ]
   }
 }
@@ -36,6 +214,9 @@ impl Display for WalletLabel {
     let label_str = match self {
       WalletLabel::OutsideCash => "outside_cash",
       WalletLabel::OutsideCashDiscrepancy => "outside_cash_discrepancy",
+      WalletLabel::LegacyImport => "legacy_import",
+      WalletLabel::PspClearing => "psp_clearing",
+      WalletLabel::BankPayouts => "bank_payouts",
     };
     write!(f, "{}", label_str)
   }
@@ -46,7 +227,96 @@ impl From<&str> for WalletLabel {
     match value {
       "outside_cash" => WalletLabel::OutsideCash,
       "outside_cash_discrepancy" => WalletLabel::OutsideCashDiscrepancy,
+      "legacy_import" => WalletLabel::LegacyImport,
+      "psp_clearing" => WalletLabel::PspClearing,
+      "bank_payouts" => WalletLabel::BankPayouts,
       _ => WalletLabel::OutsideCash,
     }
   }
 }
+
+impl Display for LegalHoldAction {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let action_str = match self {
+      LegalHoldAction::Placed => "placed",
+      LegalHoldAction::Released => "released",
+    };
+    write!(f, "{}", action_str)
+  }
+}
+
+impl From<&str> for LegalHoldAction {
+  fn from(value: &str) -> Self {
+    match value {
+      "released" => LegalHoldAction::Released,
+      _ => LegalHoldAction::Placed,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn create_wallet(allow_overdraft: bool) -> Wallet {
+    Wallet {
+      id: Id::new(),
+      owner: None,
+      label: None,
+      currency: Currency::EUR,
+      allow_overdraft,
+      legal_hold: false,
+      frozen: false,
+      appearance: WalletAppearance::default(),
+      created_at: Utc::now(),
+      updated_at: None,
+    }
+  }
+
+  #[test]
+  fn test_can_send_within_balance() {
+    let wallet = create_wallet(false);
+
+    assert!(wallet.can_send(Money::from_major(10), Money::from_major(10)));
+    assert!(!wallet.can_send(Money::from_major(10), Money::from_minor(1001)));
+    assert!(!wallet.can_send(Money::MIN, Money::from_minor(1)));
+  }
+
+  #[test]
+  fn test_can_send_with_overdraft() {
+    let wallet = create_wallet(true);
+
+    assert!(wallet.can_send(Money::ZERO, Money::from_major(50)));
+  }
+
+  #[test]
+  fn test_can_pay_out_ignores_overdraft() {
+    let wallet = create_wallet(true);
+
+    assert!(!wallet.can_pay_out(Money::ZERO, Money::from_minor(1)));
+    assert!(!wallet.can_pay_out(Money::from_major(10), Money::from_minor(1001)));
+    assert!(wallet.can_pay_out(Money::from_major(10), Money::from_major(10)));
+  }
+
+  #[test]
+  fn test_appearance_normalizes_values() {
+    let appearance = WalletAppearance::new(
+      Some("  Bar budget ".to_string()),
+      Some("#FF8800".to_string()),
+      Some("".to_string()),
+    )
+    .unwrap();
+
+    assert_eq!(appearance.display_name(), Some("Bar budget"));
+    assert_eq!(appearance.color(), Some("#ff8800"));
+    assert_eq!(appearance.icon(), None);
+  }
+
+  #[test]
+  fn test_appearance_rejects_invalid_values() {
+    assert!(WalletAppearance::new(None, Some("orange".to_string()), None).is_err());
+    assert!(WalletAppearance::new(None, Some("#ff88zz".to_string()), None).is_err());
+    assert!(WalletAppearance::new(None, None, Some("Beer Mug".to_string())).is_err());
+    assert!(WalletAppearance::new(Some("x".repeat(41)), None, None).is_err());
+  }
+}
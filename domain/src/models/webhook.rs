@@ -0,0 +1,205 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use utoipa::ToSchema;
+
+use crate::{ActorId, Id, ShopId};
+
+pub type WebhookId = Id<Webhook>;
+pub type WebhookDeliveryId = Id<WebhookDelivery>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub enum WebhookEvent {
+  #[serde(rename = "order.created")]
+  OrderCreated,
+  #[serde(rename = "order.refunded")]
+  OrderRefunded,
+  /// A pre-order's pickup slot is coming up and it should be prepared
+  #[serde(rename = "order.due")]
+  OrderDue,
+  /// Money moved between two wallets, including refunds and reversals
+  #[serde(rename = "transaction.created")]
+  TransactionCreated,
+  /// Someone got an account, through an invite or an import
+  #[serde(rename = "user.registered")]
+  UserRegistered,
+  /// Someone joined through an invite
+  #[serde(rename = "invite.accepted")]
+  InviteAccepted,
+  /// A wallet was frozen on suspicion of abuse
+  #[serde(rename = "wallet.frozen")]
+  WalletFrozen,
+  /// A frozen wallet was released after review
+  #[serde(rename = "wallet.unfrozen")]
+  WalletUnfrozen,
+}
+
+/// An endpoint receiving signed event notifications.
+#[derive(Debug, Clone)]
+pub struct Webhook {
+  pub id: WebhookId,
+  /// Only events of this shop are delivered; without one, the webhook gets
+  /// the events of every shop and those concerning no shop
+  pub shop_id: Option<ShopId>,
+  pub url: String,
+  /// Key for the HMAC signature sent along with every delivery
+  pub secret: String,
+  pub events: Vec<WebhookEvent>,
+  pub created_by: Option<ActorId>,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// One event sent, or being sent, to a webhook. Kept as its delivery log.
+#[derive(Debug, Clone)]
+pub struct WebhookDelivery {
+  pub id: WebhookDeliveryId,
+  pub webhook_id: WebhookId,
+  pub event: WebhookEvent,
+  /// The JSON body posted
+  pub payload: serde_json::Value,
+  pub status: WebhookDeliveryStatus,
+  /// Delivery attempts made so far
+  pub attempts: i32,
+  /// HTTP status of the latest response, if the endpoint answered
+  pub response_status: Option<i32>,
+  /// Why the latest attempt failed
+  pub last_error: Option<String>,
+  pub delivered_at: Option<DateTime<Utc>>,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub enum WebhookDeliveryStatus {
+  /// Not delivered yet; retried with backoff until it is or attempts run out
+  #[default]
+  #[serde(rename = "pending")]
+  Pending,
+  #[serde(rename = "delivered")]
+  Delivered,
+  /// Gave up after the last attempt
+  #[serde(rename = "failed")]
+  Failed,
+}
+
+impl WebhookEvent {
+  pub fn variants() -> &'static [WebhookEvent] {
+    &[
+      WebhookEvent::OrderCreated,
+      WebhookEvent::OrderRefunded,
+      WebhookEvent::OrderDue,
+      WebhookEvent::TransactionCreated,
+      WebhookEvent::UserRegistered,
+      WebhookEvent::InviteAccepted,
+      WebhookEvent::WalletFrozen,
+      WebhookEvent::WalletUnfrozen,
+    ]
+  }
+
+  /// Whether the event happens at a shop, so a shop's webhooks can
+  /// subscribe to it.
+  pub fn concerns_shop(&self) -> bool {
+    matches!(
+      self,
+      WebhookEvent::OrderCreated | WebhookEvent::OrderRefunded | WebhookEvent::OrderDue
+    )
+  }
+}
+
+impl Webhook {
+  pub fn is_subscribed_to(&self, event: WebhookEvent) -> bool {
+    self.events.contains(&event)
+  }
+}
+
+impl Display for WebhookEvent {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let event_str = match self {
+      WebhookEvent::OrderCreated => "order.created",
+      WebhookEvent::OrderRefunded => "order.refunded",
+      WebhookEvent::OrderDue => "order.due",
+      WebhookEvent::TransactionCreated => "transaction.created",
+      WebhookEvent::UserRegistered => "user.registered",
+      WebhookEvent::InviteAccepted => "invite.accepted",
+      WebhookEvent::WalletFrozen => "wallet.frozen",
+      WebhookEvent::WalletUnfrozen => "wallet.unfrozen",
+    };
+    write!(f, "{}", event_str)
+  }
+}
+
+impl TryFrom<&str> for WebhookEvent {
+  type Error = String;
+
+  fn try_from(value: &str) -> Result<Self, Self::Error> {
+    WebhookEvent::variants()
+      .iter()
+      .find(|event| event.to_string() == value)
+      .copied()
+      .ok_or_else(|| format!("`{}` is not a webhook event", value))
+  }
+}
+
+impl Display for WebhookDeliveryStatus {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let status_str = match self {
+      WebhookDeliveryStatus::Pending => "pending",
+      WebhookDeliveryStatus::Delivered => "delivered",
+      WebhookDeliveryStatus::Failed => "failed",
+    };
+    write!(f, "{}", status_str)
+  }
+}
+
+impl From<&str> for WebhookDeliveryStatus {
+  fn from(value: &str) -> Self {
+    match value {
+      "delivered" => WebhookDeliveryStatus::Delivered,
+      "failed" => WebhookDeliveryStatus::Failed,
+      _ => WebhookDeliveryStatus::Pending,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_event_names_round_trip() {
+    for event in WebhookEvent::variants() {
+      assert_eq!(
+        WebhookEvent::try_from(event.to_string().as_str()),
+        Ok(*event)
+      );
+      assert_eq!(
+        serde_json::to_value(event).unwrap(),
+        serde_json::Value::String(event.to_string())
+      );
+    }
+  }
+
+  #[test]
+  fn test_unknown_event_name_is_rejected() {
+    assert!(WebhookEvent::try_from("order.exploded").is_err());
+  }
+
+  #[test]
+  fn test_delivery_status_names_round_trip() {
+    for status in [
+      WebhookDeliveryStatus::Pending,
+      WebhookDeliveryStatus::Delivered,
+      WebhookDeliveryStatus::Failed,
+    ] {
+      assert_eq!(
+        WebhookDeliveryStatus::from(status.to_string().as_str()),
+        status
+      );
+      assert_eq!(
+        serde_json::to_value(status).unwrap(),
+        serde_json::Value::String(status.to_string())
+      );
+    }
+  }
+}
@@ -0,0 +1,196 @@
+use serde::{Deserialize, Serialize};
+use sqlx::postgres::{PgArgumentBuffer, PgTypeInfo, PgValueRef};
+use sqlx::{Decode, Encode, Postgres, Type};
+use std::fmt;
+use std::str::FromStr;
+use utoipa::ToSchema;
+
+/// ISO 4217 code of the currency an amount is in, e.g. `EUR`. Amounts are
+/// kept in hundredths of it, so only currencies with two decimal places are
+/// accepted.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+#[serde(try_from = "String", into = "String")]
+#[schema(value_type = String, example = "EUR")]
+pub struct Currency([u8; 3]);
+
+/// ISO 4217 currencies whose minor unit is not a hundredth, with their
+/// number of decimal places; `None` for those without a minor unit, such as
+/// gold
+const OTHER_MINOR_UNITS: &[(&[u8; 3], Option<u8>)] = &[
+  (b"BIF", Some(0)),
+  (b"CLP", Some(0)),
+  (b"DJF", Some(0)),
+  (b"GNF", Some(0)),
+  (b"ISK", Some(0)),
+  (b"JPY", Some(0)),
+  (b"KMF", Some(0)),
+  (b"KRW", Some(0)),
+  (b"PYG", Some(0)),
+  (b"RWF", Some(0)),
+  (b"UGX", Some(0)),
+  (b"UYI", Some(0)),
+  (b"VND", Some(0)),
+  (b"VUV", Some(0)),
+  (b"XAF", Some(0)),
+  (b"XOF", Some(0)),
+  (b"XPF", Some(0)),
+  (b"BHD", Some(3)),
+  (b"IQD", Some(3)),
+  (b"JOD", Some(3)),
+  (b"KWD", Some(3)),
+  (b"LYD", Some(3)),
+  (b"OMR", Some(3)),
+  (b"TND", Some(3)),
+  (b"CLF", Some(4)),
+  (b"UYW", Some(4)),
+  (b"XAG", None),
+  (b"XAU", None),
+  (b"XBA", None),
+  (b"XBB", None),
+  (b"XBC", None),
+  (b"XBD", None),
+  (b"XDR", None),
+  (b"XPD", None),
+  (b"XPT", None),
+  (b"XSU", None),
+  (b"XTS", None),
+  (b"XUA", None),
+  (b"XXX", None),
+];
+
+impl Currency {
+  pub const EUR: Currency = Currency(*b"EUR");
+
+  /// Takes three letters in either case. Refuses currencies that aren't
+  /// counted in hundredths, such as `JPY` or `KWD`.
+  pub fn parse(value: &str) -> Result<Self, String> {
+    let code: [u8; 3] = value
+      .trim()
+      .to_ascii_uppercase()
+      .into_bytes()
+      .try_into()
+      .ok()
+      .filter(|code: &[u8; 3]| code.iter().all(u8::is_ascii_uppercase))
+      .ok_or_else(|| format!("`{}` is not an ISO 4217 currency code", value))?;
+
+    let currency = Self(code);
+    match currency.minor_unit_digits() {
+      2 => Ok(currency),
+      _ => Err(format!(
+        "{} is not counted in hundredths, which amounts are kept in",
+        currency.code()
+      )),
+    }
+  }
+
+  /// Decimal places of the minor unit, 0 for currencies without one.
+  fn minor_unit_digits(&self) -> u8 {
+    OTHER_MINOR_UNITS
+      .iter()
+      .find(|(code, _)| **code == self.0)
+      .map_or(2, |(_, digits)| digits.unwrap_or(0))
+  }
+
+  pub fn code(&self) -> &str {
+    std::str::from_utf8(&self.0).expect("currency codes are ASCII")
+  }
+
+  /// Sign written instead of the code, for the currencies that have a
+  /// well-known one.
+  pub fn symbol(&self) -> Option<&'static str> {
+    match &self.0 {
+      b"EUR" => Some("€"),
+      b"USD" => Some("$"),
+      b"GBP" => Some("£"),
+      _ => None,
+    }
+  }
+}
+
+impl Default for Currency {
+  fn default() -> Self {
+    Self::EUR
+  }
+}
+
+impl fmt::Debug for Currency {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "Currency({})", self.code())
+  }
+}
+
+impl fmt::Display for Currency {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(self.code())
+  }
+}
+
+impl FromStr for Currency {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    Self::parse(s)
+  }
+}
+
+impl TryFrom<String> for Currency {
+  type Error = String;
+
+  fn try_from(value: String) -> Result<Self, Self::Error> {
+    Self::parse(&value)
+  }
+}
+
+impl From<Currency> for String {
+  fn from(currency: Currency) -> Self {
+    currency.code().to_string()
+  }
+}
+
+impl Type<Postgres> for Currency {
+  fn type_info() -> PgTypeInfo {
+    <&str as Type<Postgres>>::type_info()
+  }
+
+  fn compatible(ty: &PgTypeInfo) -> bool {
+    <&str as Type<Postgres>>::compatible(ty)
+  }
+}
+
+impl<'r> Decode<'r, Postgres> for Currency {
+  fn decode(
+    value: PgValueRef<'r>,
+  ) -> Result<Self, Box<dyn std::error::Error + Send + Sync + 'static>> {
+    let code = <&str as Decode<Postgres>>::decode(value)?;
+    Ok(Self::parse(code)?)
+  }
+}
+
+impl<'q> Encode<'q, Postgres> for Currency {
+  fn encode_by_ref(&self, buf: &mut PgArgumentBuffer) -> sqlx::encode::IsNull {
+    <&str as Encode<Postgres>>::encode_by_ref(&self.code(), buf)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_parse_normalizes_case() {
+    assert_eq!(Currency::parse("chf").unwrap().code(), "CHF");
+    assert_eq!(Currency::parse(" EUR ").unwrap(), Currency::EUR);
+    assert!(Currency::parse("EURO").is_err());
+    assert!(Currency::parse("E1R").is_err());
+    assert!(Currency::parse("").is_err());
+  }
+
+  #[test]
+  fn test_parse_refuses_other_minor_units() {
+    assert!(Currency::parse("USD").is_ok());
+    assert!(Currency::parse("jpy").is_err());
+    assert!(Currency::parse("KRW").is_err());
+    assert!(Currency::parse("KWD").is_err());
+    assert!(Currency::parse("XAU").is_err());
+  }
+}
@@ -0,0 +1,120 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use p256::ecdsa::{signature::Verifier, Signature, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use sqlx::Type;
+use std::fmt;
+use utoipa::ToSchema;
+
+/// Public half of a key pair kept in a device's secure hardware, as base64 of
+/// the SEC1-encoded P-256 point. Only constructed through
+/// [`DevicePublicKey::parse`] or read back from the database, so every value
+/// is a valid point.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Type, ToSchema)]
+#[serde(try_from = "String", into = "String")]
+#[sqlx(transparent)]
+#[schema(
+  value_type = String,
+  example = "BB4YUy/UdUwC8wQdnHXOszuD/9gax85P6ILMscmLxYlupGwxHE4v9A3ZajZT5uRURdMt/khuztdcepDGoYiBwKM="
+)]
+pub struct DevicePublicKey(String);
+
+impl DevicePublicKey {
+  /// Accepts compressed and uncompressed points.
+  pub fn parse(value: &str) -> Result<Self, String> {
+    let bytes = STANDARD
+      .decode(value.trim())
+      .map_err(|_| "Device key is not base64".to_string())?;
+    let key = VerifyingKey::from_sec1_bytes(&bytes)
+      .map_err(|_| "Device key is not a P-256 public key".to_string())?;
+
+    Ok(Self(STANDARD.encode(key.to_encoded_point(false))))
+  }
+
+  pub fn expose(&self) -> &str {
+    &self.0
+  }
+
+  /// Whether `signature`, base64 of a DER-encoded ECDSA signature over the
+  /// SHA-256 of `message`, was made with this key's private half.
+  pub fn verifies(&self, message: &[u8], signature: &str) -> bool {
+    let Ok(key) = STANDARD
+      .decode(&self.0)
+      .map_err(|_| ())
+      .and_then(|bytes| VerifyingKey::from_sec1_bytes(&bytes).map_err(|_| ()))
+    else {
+      return false;
+    };
+    let Ok(signature) = STANDARD
+      .decode(signature.trim())
+      .map_err(|_| ())
+      .and_then(|bytes| Signature::from_der(&bytes).map_err(|_| ()))
+    else {
+      return false;
+    };
+
+    key.verify(message, &signature).is_ok()
+  }
+}
+
+impl fmt::Debug for DevicePublicKey {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "DevicePublicKey({})", self.0)
+  }
+}
+
+impl TryFrom<String> for DevicePublicKey {
+  type Error = String;
+
+  fn try_from(value: String) -> Result<Self, Self::Error> {
+    Self::parse(&value)
+  }
+}
+
+impl From<DevicePublicKey> for String {
+  fn from(value: DevicePublicKey) -> Self {
+    value.0
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use p256::ecdsa::{signature::Signer, SigningKey};
+
+  fn signing_key() -> SigningKey {
+    SigningKey::from_bytes(&[7u8; 32].into()).unwrap()
+  }
+
+  fn public_key(compressed: bool) -> String {
+    STANDARD.encode(
+      signing_key()
+        .verifying_key()
+        .to_encoded_point(compressed)
+        .as_bytes(),
+    )
+  }
+
+  fn sign(message: &[u8]) -> String {
+    let signature: Signature = signing_key().sign(message);
+    STANDARD.encode(signature.to_der().as_bytes())
+  }
+
+  #[test]
+  fn test_parse_normalizes_to_uncompressed_point() {
+    let compressed = DevicePublicKey::parse(&public_key(true)).unwrap();
+    let uncompressed = DevicePublicKey::parse(&public_key(false)).unwrap();
+
+    assert_eq!(compressed, uncompressed);
+    assert!(DevicePublicKey::parse("not base64!").is_err());
+    assert!(DevicePublicKey::parse(&STANDARD.encode([4u8; 65])).is_err());
+  }
+
+  #[test]
+  fn test_verifies_signature_over_message() {
+    let key = DevicePublicKey::parse(&public_key(false)).unwrap();
+
+    assert!(key.verifies(b"nonce", &sign(b"nonce")));
+    assert!(!key.verifies(b"other nonce", &sign(b"nonce")));
+    assert!(!key.verifies(b"nonce", "not a signature"));
+  }
+}
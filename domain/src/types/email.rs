@@ -18,6 +18,26 @@ impl Email {
   pub fn expose(&self) -> &str {
     &self.0
   }
+
+  /// First and last name as spelled out by the address, lowercase, e.g.
+  /// `("ada", "lovelace")` for `Ada.Lovelace@example.com`.
+  pub fn name_hint(&self) -> Option<(String, String)> {
+    let (local, _) = self.0.rsplit_once('@')?;
+    let local = local.split('+').next().unwrap_or_default().to_lowercase();
+
+    let mut parts = local.split(['.', '_', '-']);
+    let (Some(first), Some(last), None) = (parts.next(), parts.next(), parts.next()) else {
+      return None;
+    };
+    let is_name = |part: &str| part.chars().count() > 1 && part.chars().all(char::is_alphabetic);
+
+    (is_name(first) && is_name(last)).then(|| (first.to_string(), last.to_string()))
+  }
+
+  /// The part after the `@`, if any.
+  pub fn domain(&self) -> Option<&str> {
+    self.0.rsplit_once('@').map(|(_, domain)| domain)
+  }
 }
 
 impl fmt::Debug for Email {
@@ -50,4 +70,30 @@ mod tests {
     let debug_str = format!("{:?}", email);
     assert_eq!(debug_str, "Email(***)");
   }
+
+  #[test]
+  fn test_name_hint() {
+    let hint = |email: &str| Email::new(email).name_hint();
+
+    assert_eq!(
+      hint("Ada.Lovelace+club@example.com"),
+      Some(("ada".to_string(), "lovelace".to_string()))
+    );
+    assert_eq!(
+      hint("ada_lovelace@example.com"),
+      Some(("ada".to_string(), "lovelace".to_string()))
+    );
+    assert_eq!(hint("a.lovelace@example.com"), None);
+    assert_eq!(hint("ada.b.lovelace@example.com"), None);
+    assert_eq!(hint("ada1984@example.com"), None);
+  }
+
+  #[test]
+  fn test_domain() {
+    assert_eq!(
+      Email::new("ada@Club.Example").domain(),
+      Some("Club.Example")
+    );
+    assert_eq!(Email::new("nobody").domain(), None);
+  }
 }
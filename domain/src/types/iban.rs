@@ -0,0 +1,112 @@
+use serde::{Deserialize, Serialize};
+use sqlx::Type;
+use std::fmt;
+use std::str::FromStr;
+use utoipa::ToSchema;
+
+/// International bank account number, stored without spaces and upper case.
+/// Only constructed through [`Iban::parse`] or read back from the database,
+/// so every value has a valid checksum.
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Type, ToSchema)]
+#[serde(try_from = "String", into = "String")]
+#[sqlx(transparent)]
+#[schema(value_type = String, example = "DE89370400440532013000")]
+pub struct Iban(String);
+
+impl Iban {
+  /// Checks the layout (country code, check digits, up to 30 letters and
+  /// digits) and the ISO 7064 mod-97 checksum. Spaces are ignored.
+  pub fn parse(value: &str) -> Result<Self, String> {
+    let iban: String = value
+      .chars()
+      .filter(|c| !c.is_whitespace())
+      .collect::<String>()
+      .to_ascii_uppercase();
+
+    let bytes = iban.as_bytes();
+    if !(15..=34).contains(&bytes.len())
+      || !bytes[..2].iter().all(u8::is_ascii_uppercase)
+      || !bytes[2..4].iter().all(u8::is_ascii_digit)
+      || !bytes.iter().all(u8::is_ascii_alphanumeric)
+    {
+      return Err("Not an IBAN".to_string());
+    }
+
+    // Country code and check digits move to the end, letters count as 10-35
+    let remainder = bytes[4..]
+      .iter()
+      .chain(&bytes[..4])
+      .fold(0u32, |acc, b| match b {
+        b'0'..=b'9' => (acc * 10 + u32::from(b - b'0')) % 97,
+        _ => (acc * 100 + u32::from(b - b'A' + 10)) % 97,
+      });
+    if remainder != 1 {
+      return Err("IBAN checksum does not match".to_string());
+    }
+
+    Ok(Self(iban))
+  }
+
+  pub fn expose(&self) -> &str {
+    &self.0
+  }
+
+  /// Country code and last four digits, enough to recognise an account
+  pub fn masked(&self) -> String {
+    format!("{}** **** {}", &self.0[..2], &self.0[self.0.len() - 4..])
+  }
+}
+
+impl fmt::Debug for Iban {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "Iban({})", self.masked())
+  }
+}
+
+impl FromStr for Iban {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    Self::parse(s)
+  }
+}
+
+impl TryFrom<String> for Iban {
+  type Error = String;
+
+  fn try_from(value: String) -> Result<Self, Self::Error> {
+    Self::parse(&value)
+  }
+}
+
+impl From<Iban> for String {
+  fn from(iban: Iban) -> Self {
+    iban.0
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_parse() {
+    let iban = Iban::parse("de89 3704 0044 0532 0130 00").unwrap();
+    assert_eq!(iban.expose(), "DE89370400440532013000");
+    assert_eq!(iban.masked(), "DE** **** 3000");
+    assert_eq!(format!("{:?}", iban), "Iban(DE** **** 3000)");
+
+    assert!(Iban::parse("GB82WEST12345698765432").is_ok());
+    // One digit off
+    assert!(Iban::parse("DE89370400440532013001").is_err());
+    assert!(Iban::parse("DE89").is_err());
+    assert!(Iban::parse("1289370400440532013000").is_err());
+    assert!(Iban::parse("DE89-3704-0044-0532-0130-00").is_err());
+  }
+
+  #[test]
+  fn test_deserialize_validates() {
+    assert!(serde_json::from_str::<Iban>("\"DE89370400440532013000\"").is_ok());
+    assert!(serde_json::from_str::<Iban>("\"DE00370400440532013000\"").is_err());
+  }
+}
@@ -0,0 +1,88 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::types::Currency;
+
+/// Language emails are written in for someone.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+  #[default]
+  En,
+  De,
+}
+
+impl Locale {
+  pub fn variants() -> &'static [Locale] {
+    &[Locale::En, Locale::De]
+  }
+
+  /// ISO 639-1 code, as stored and used in template names.
+  pub fn code(&self) -> &'static str {
+    match self {
+      Locale::En => "en",
+      Locale::De => "de",
+    }
+  }
+
+  /// Formats hundredths of `currency` the way the locale writes amounts,
+  /// e.g. "€12.50", "CHF 12.50" or "12,50 €". Currencies without a
+  /// well-known sign are written with their code.
+  pub fn format_cents(&self, cents: i64, currency: Currency) -> String {
+    let sign = if cents < 0 { "-" } else { "" };
+    let (major, minor) = ((cents / 100).abs(), (cents % 100).abs());
+    match (self, currency.symbol()) {
+      (Locale::En, Some(symbol)) => format!("{}{}{}.{:02}", symbol, sign, major, minor),
+      (Locale::En, None) => format!("{} {}{}.{:02}", currency, sign, major, minor),
+      (Locale::De, symbol) => format!(
+        "{}{},{:02} {}",
+        sign,
+        major,
+        minor,
+        symbol.unwrap_or(currency.code())
+      ),
+    }
+  }
+}
+
+impl fmt::Display for Locale {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{}", self.code())
+  }
+}
+
+impl From<&str> for Locale {
+  fn from(value: &str) -> Self {
+    Locale::variants()
+      .iter()
+      .copied()
+      .find(|locale| locale.code() == value)
+      .unwrap_or_default()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_locale_round_trips_its_code() {
+    for locale in Locale::variants() {
+      assert_eq!(Locale::from(locale.code()), *locale);
+    }
+    assert_eq!(Locale::from("fr"), Locale::En);
+  }
+
+  #[test]
+  fn test_format_cents_per_locale() {
+    let chf = Currency::parse("CHF").unwrap();
+    assert_eq!(Locale::En.format_cents(1250, Currency::EUR), "€12.50");
+    assert_eq!(Locale::En.format_cents(-5, Currency::EUR), "€-0.05");
+    assert_eq!(Locale::En.format_cents(1250, chf), "CHF 12.50");
+    assert_eq!(Locale::De.format_cents(1250, Currency::EUR), "12,50 €");
+    assert_eq!(Locale::De.format_cents(-5, Currency::EUR), "-0,05 €");
+    assert_eq!(Locale::De.format_cents(1250, chf), "12,50 CHF");
+  }
+}
@@ -1,11 +1,23 @@
+pub mod currency;
+pub mod device_key;
 pub mod email;
 pub mod hashed_password;
+pub mod iban;
 pub mod id;
+pub mod locale;
 pub mod money;
+pub mod page;
 pub mod raw_password;
+pub mod timezone;
 
+pub use currency::Currency;
+pub use device_key::DevicePublicKey;
 pub use email::Email;
 pub use hashed_password::HashedPassword;
+pub use iban::Iban;
 pub use id::Id;
+pub use locale::Locale;
 pub use money::Money;
+pub use page::{ListVersion, Page, PageRequest, SortOrder};
 pub use raw_password::RawPassword;
+pub use timezone::VenueTimezone;
@@ -1,5 +1,13 @@
 use std::fmt;
 use std::ops::{Add, Neg, Sub};
+use std::str::FromStr;
+
+use serde::de::{self, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use utoipa::openapi::{ObjectBuilder, OneOfBuilder, RefOr, Schema, SchemaFormat, SchemaType};
+use utoipa::ToSchema;
+
+use crate::types::{Currency, Locale};
 
 /// Money represented in minor currency units (cents)
 ///
@@ -66,13 +74,9 @@ impl Money {
     (self.0.saturating_abs() as u64) % 100
   }
 
-  /// Format as currency string (e.g., "€10.50" or "€-10.50")
-  pub fn format_eur(&self) -> String {
-    if self.0 < 0 {
-      format!("€-{}.{:02}", self.as_major().saturating_abs(), self.cents())
-    } else {
-      format!("€{}.{:02}", self.as_major(), self.cents())
-    }
+  /// Format as currency string (e.g., "€10.50", "€-10.50" or "CHF 10.50")
+  pub fn format_in(&self, currency: Currency) -> String {
+    Locale::En.format_cents(self.0.into(), currency)
   }
 
   /// Check if the money amount is zero
@@ -111,6 +115,14 @@ impl Money {
     }
   }
 
+  /// Checked multiplication by a quantity. Returns `None` if overflow occurred.
+  pub const fn checked_mul(self, quantity: i32) -> Option<Self> {
+    match self.0.checked_mul(quantity) {
+      Some(product) => Some(Self(product)),
+      None => None,
+    }
+  }
+
   /// Saturating addition. Returns the max/min value on overflow.
   pub const fn saturating_add(self, other: Self) -> Self {
     Self(self.0.saturating_add(other.0))
@@ -176,6 +188,120 @@ impl Neg for Money {
   }
 }
 
+/// Parses a decimal amount in major units, such as `"12.50"` or `"-3.20"`
+///
+/// Parsing is strict: exactly two fractional digits, no exponent, no
+/// grouping separators, no leading `+` and no surrounding whitespace. A bare
+/// `"1250"` is refused rather than guessed to mean euros or cents.
+impl FromStr for Money {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let invalid = || format!("`{}` is not an amount like 12.50", s);
+    let (negative, unsigned) = match s.strip_prefix('-') {
+      Some(rest) => (true, rest),
+      None => (false, s),
+    };
+    let Some((major, minor)) = unsigned.split_once('.') else {
+      return Err(invalid());
+    };
+    let is_digits = |part: &str| part.bytes().all(|b| b.is_ascii_digit());
+    if major.is_empty() || minor.len() != 2 || !is_digits(major) || !is_digits(minor) {
+      return Err(invalid());
+    }
+
+    let minor = minor.parse::<i64>().map_err(|_| invalid())?;
+    major
+      .parse::<i64>()
+      .ok()
+      .and_then(|major| major.checked_mul(100))
+      .and_then(|cents| cents.checked_add(minor))
+      .map(|cents| if negative { -cents } else { cents })
+      .and_then(|cents| i32::try_from(cents).ok())
+      .map(Money)
+      .ok_or_else(|| format!("`{}` is out of range", s))
+  }
+}
+
+/// Accepts integer minor units (`1250`) or a decimal string with two
+/// fractional digits (`"12.50"`)
+///
+/// JSON floats are rejected so that no amount ever passes through binary
+/// floating point.
+impl<'de> Deserialize<'de> for Money {
+  fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+  where
+    D: Deserializer<'de>,
+  {
+    struct MoneyVisitor;
+
+    impl Visitor<'_> for MoneyVisitor {
+      type Value = Money;
+
+      fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("integer cents or a decimal string such as \"12.50\"")
+      }
+
+      fn visit_i64<E: de::Error>(self, value: i64) -> Result<Money, E> {
+        i32::try_from(value)
+          .map(Money)
+          .map_err(|_| E::custom(format!("{} cents is out of range", value)))
+      }
+
+      fn visit_u64<E: de::Error>(self, value: u64) -> Result<Money, E> {
+        Money::try_from(value).map_err(|_| E::custom(format!("{} cents is out of range", value)))
+      }
+
+      fn visit_f64<E: de::Error>(self, _: f64) -> Result<Money, E> {
+        Err(E::custom(
+          "amounts must be integer cents or a decimal string, not a float",
+        ))
+      }
+
+      fn visit_str<E: de::Error>(self, value: &str) -> Result<Money, E> {
+        value.parse().map_err(E::custom)
+      }
+    }
+
+    deserializer.deserialize_any(MoneyVisitor)
+  }
+}
+
+/// Serializes as integer minor units, which deserialize back unchanged
+impl Serialize for Money {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_i32(self.0)
+  }
+}
+
+impl<'s> ToSchema<'s> for Money {
+  fn schema() -> (&'s str, RefOr<Schema>) {
+    (
+      "Money",
+      OneOfBuilder::new()
+        .item(
+          ObjectBuilder::new()
+            .schema_type(SchemaType::Integer)
+            .format(Some(SchemaFormat::KnownFormat(
+              utoipa::openapi::KnownFormat::Int32,
+            )))
+            .description(Some("Amount in minor units (cents)"))
+            .example(Some(1250.into())),
+        )
+        .item(
+          ObjectBuilder::new()
+            .schema_type(SchemaType::String)
+            .pattern(Some(r"^-?[0-9]+\.[0-9]{2}$"))
+            .description(Some(
+              "Decimal amount in major units, with two fractional digits",
+            ))
+            .example(Some("12.50".into())),
+        )
+        .into(),
+    )
+  }
+}
+
 // Database conversions
 impl From<i32> for Money {
   fn from(value: i32) -> Self {
@@ -355,18 +481,26 @@ mod tests {
 
   #[test]
   fn test_format_eur_positive() {
-    assert_eq!(Money::from_minor(1050).format_eur(), "€10.50");
-    assert_eq!(Money::from_minor(1000).format_eur(), "€10.00");
-    assert_eq!(Money::from_minor(99).format_eur(), "€0.99");
-    assert_eq!(Money::ZERO.format_eur(), "€0.00");
+    assert_eq!(Money::from_minor(1050).format_in(Currency::EUR), "€10.50");
+    assert_eq!(Money::from_minor(1000).format_in(Currency::EUR), "€10.00");
+    assert_eq!(Money::from_minor(99).format_in(Currency::EUR), "€0.99");
+    assert_eq!(Money::ZERO.format_in(Currency::EUR), "€0.00");
+  }
+
+  #[test]
+  fn test_format_in_other_currency() {
+    let usd = Currency::parse("USD").unwrap();
+    let chf = Currency::parse("CHF").unwrap();
+    assert_eq!(Money::from_minor(1050).format_in(usd), "$10.50");
+    assert_eq!(Money::from_minor(-1050).format_in(chf), "CHF -10.50");
   }
 
   #[test]
   fn test_format_eur_negative() {
-    assert_eq!(Money::from_minor(-1050).format_eur(), "€-10.50");
-    assert_eq!(Money::from_minor(-1000).format_eur(), "€-10.00");
-    assert_eq!(Money::from_minor(-99).format_eur(), "€-0.99");
-    assert_eq!(Money::from_minor(-1).format_eur(), "€-0.01");
+    assert_eq!(Money::from_minor(-1050).format_in(Currency::EUR), "€-10.50");
+    assert_eq!(Money::from_minor(-1000).format_in(Currency::EUR), "€-10.00");
+    assert_eq!(Money::from_minor(-99).format_in(Currency::EUR), "€-0.99");
+    assert_eq!(Money::from_minor(-1).format_in(Currency::EUR), "€-0.01");
   }
 
   #[test]
@@ -483,6 +617,16 @@ mod tests {
     assert_eq!(min.checked_add(neg_one), None);
   }
 
+  #[test]
+  fn test_checked_multiplication() {
+    let price = Money::from_minor(350);
+    assert_eq!(price.checked_mul(3), Some(Money::from_minor(1050)));
+    assert_eq!(price.checked_mul(0), Some(Money::ZERO));
+
+    let max = Money::from_minor(i32::MAX);
+    assert_eq!(max.checked_mul(2), None);
+  }
+
   #[test]
   fn test_checked_subtraction() {
     let a = Money::from_minor(1000);
@@ -498,6 +642,60 @@ mod tests {
     assert_eq!(max.checked_sub(neg_one), None);
   }
 
+  // ========================================================================
+  // Parsing Tests
+  // ========================================================================
+
+  #[test]
+  fn test_parse_decimal() {
+    assert_eq!("12.50".parse(), Ok(Money::from_minor(1250)));
+    assert_eq!("12.00".parse(), Ok(Money::from_minor(1200)));
+    assert_eq!("0.01".parse(), Ok(Money::from_minor(1)));
+    assert_eq!("-3.20".parse(), Ok(Money::from_minor(-320)));
+    assert_eq!("21474836.47".parse(), Ok(Money::MAX));
+  }
+
+  #[test]
+  fn test_parse_rejects_loose_input() {
+    for input in [
+      "", "-", ".50", "12.", "12.5", "12", "1250", "12.505", "1e3", "+1.00", " 1.00", "1,50",
+      "12,50", "€1.00", "NaN",
+    ] {
+      assert!(input.parse::<Money>().is_err(), "accepted {:?}", input);
+    }
+    assert!("21474836.48".parse::<Money>().is_err());
+    assert!("99999999999999999999999".parse::<Money>().is_err());
+  }
+
+  #[test]
+  fn test_parse_round_trips_display() {
+    for cents in [0, 1, -1, 1050, -1050, i32::MAX, i32::MIN + 1] {
+      let money = Money::from_minor(cents);
+      assert_eq!(money.to_string().parse(), Ok(money));
+    }
+  }
+
+  #[test]
+  fn test_deserialize_cents_or_decimal_string() {
+    let from_cents: Money = serde_json::from_str("1250").unwrap();
+    let from_string: Money = serde_json::from_str(r#""12.50""#).unwrap();
+    assert_eq!(from_cents, Money::from_minor(1250));
+    assert_eq!(from_string, Money::from_minor(1250));
+
+    assert!(serde_json::from_str::<Money>("12.5").is_err());
+    assert!(serde_json::from_str::<Money>("12.0").is_err());
+    assert!(serde_json::from_str::<Money>("4294967296").is_err());
+    assert!(serde_json::from_str::<Money>(r#""12.505""#).is_err());
+    assert!(serde_json::from_str::<Money>(r#""1250""#).is_err());
+  }
+
+  #[test]
+  fn test_serialize_as_cents() {
+    let money = Money::from_minor(-1250);
+    assert_eq!(serde_json::to_string(&money).unwrap(), "-1250");
+    assert_eq!(serde_json::from_str::<Money>("-1250").unwrap(), money);
+  }
+
   // ========================================================================
   // Comparison Tests
   // ========================================================================
@@ -587,7 +785,7 @@ mod tests {
     let new_balance = balance - withdrawal;
     assert_eq!(new_balance, Money::from_major(-5));
     assert!(new_balance.is_negative());
-    assert_eq!(new_balance.format_eur(), "€-5.00");
+    assert_eq!(new_balance.format_in(Currency::EUR), "€-5.00");
   }
 
   #[test]
@@ -664,8 +862,8 @@ mod tests {
     // These should not panic
     let _ = max.to_string();
     let _ = min.to_string();
-    let _ = max.format_eur();
-    let _ = min.format_eur();
+    let _ = max.format_in(Currency::EUR);
+    let _ = min.format_in(Currency::EUR);
   }
 
   // ========================================================================
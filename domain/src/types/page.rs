@@ -0,0 +1,147 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// A requested page of a listing, 1-based.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PageRequest {
+  page: u32,
+  per_page: u32,
+}
+
+impl PageRequest {
+  pub const DEFAULT_PER_PAGE: u32 = 25;
+  pub const MAX_PER_PAGE: u32 = 100;
+
+  /// Create a page request, clamping out of range values
+  ///
+  /// # Examples
+  /// ```
+  /// use domain::types::PageRequest;
+  /// let page = PageRequest::new(Some(0), Some(1000));
+  /// assert_eq!(page.page(), 1);
+  /// assert_eq!(page.per_page(), PageRequest::MAX_PER_PAGE);
+  /// ```
+  pub fn new(page: Option<u32>, per_page: Option<u32>) -> Self {
+    Self {
+      page: page.unwrap_or(1).max(1),
+      per_page: per_page
+        .unwrap_or(Self::DEFAULT_PER_PAGE)
+        .clamp(1, Self::MAX_PER_PAGE),
+    }
+  }
+
+  pub const fn page(&self) -> u32 {
+    self.page
+  }
+
+  pub const fn per_page(&self) -> u32 {
+    self.per_page
+  }
+
+  /// Number of rows to fetch (SQL `LIMIT`)
+  pub const fn limit(&self) -> i64 {
+    self.per_page as i64
+  }
+
+  /// Number of rows to skip (SQL `OFFSET`)
+  pub const fn offset(&self) -> i64 {
+    (self.page as i64 - 1) * self.per_page as i64
+  }
+}
+
+impl Default for PageRequest {
+  fn default() -> Self {
+    Self::new(None, None)
+  }
+}
+
+/// One page of a listing together with the total number of matching items.
+#[derive(Debug, Clone)]
+pub struct Page<T> {
+  pub items: Vec<T>,
+  pub total: i64,
+  pub request: PageRequest,
+}
+
+impl<T> Page<T> {
+  pub fn map<U>(self, f: impl FnMut(T) -> U) -> Page<U> {
+    Page {
+      items: self.items.into_iter().map(f).collect(),
+      total: self.total,
+      request: self.request,
+    }
+  }
+
+  pub fn total_pages(&self) -> i64 {
+    let per_page = self.request.per_page() as i64;
+    (self.total + per_page - 1) / per_page
+  }
+}
+
+/// How far a listing has come: the number of matching items and when the
+/// latest of them changed. Adding, changing or removing an item moves at
+/// least one of the two.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ListVersion {
+  pub count: i64,
+  pub changed_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+  #[default]
+  Asc,
+  Desc,
+}
+
+impl SortOrder {
+  pub const fn is_desc(&self) -> bool {
+    matches!(self, SortOrder::Desc)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_defaults() {
+    let page = PageRequest::default();
+    assert_eq!(page.page(), 1);
+    assert_eq!(page.per_page(), PageRequest::DEFAULT_PER_PAGE);
+    assert_eq!(page.offset(), 0);
+  }
+
+  #[test]
+  fn test_clamping() {
+    let page = PageRequest::new(Some(0), Some(0));
+    assert_eq!(page.page(), 1);
+    assert_eq!(page.per_page(), 1);
+
+    let page = PageRequest::new(Some(3), Some(500));
+    assert_eq!(page.per_page(), PageRequest::MAX_PER_PAGE);
+  }
+
+  #[test]
+  fn test_limit_offset() {
+    let page = PageRequest::new(Some(3), Some(20));
+    assert_eq!(page.limit(), 20);
+    assert_eq!(page.offset(), 40);
+  }
+
+  #[test]
+  fn test_total_pages() {
+    let request = PageRequest::new(Some(1), Some(10));
+    let page = |total| Page::<()> {
+      items: vec![],
+      total,
+      request,
+    };
+
+    assert_eq!(page(0).total_pages(), 0);
+    assert_eq!(page(10).total_pages(), 1);
+    assert_eq!(page(11).total_pages(), 2);
+  }
+}
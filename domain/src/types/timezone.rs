@@ -0,0 +1,80 @@
+use chrono::{DateTime, NaiveDate, TimeZone, Utc};
+use chrono_tz::Tz;
+use serde::Deserialize;
+
+/// Timezone of the venue, e.g. `Europe/Berlin`. Times are stored in UTC;
+/// this only decides where one day ends and the next begins.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(transparent)]
+pub struct VenueTimezone(Tz);
+
+impl VenueTimezone {
+  pub fn new(timezone: Tz) -> Self {
+    Self(timezone)
+  }
+
+  /// IANA name, as Postgres' `AT TIME ZONE` takes it.
+  pub fn name(&self) -> &'static str {
+    self.0.name()
+  }
+
+  /// Calendar day at the venue at `at`.
+  pub fn day_of(&self, at: DateTime<Utc>) -> NaiveDate {
+    at.with_timezone(&self.0).date_naive()
+  }
+
+  /// When `day` begins at the venue. Days whose midnight is skipped by a
+  /// clock change begin at the first time that exists.
+  pub fn start_of_day(&self, day: NaiveDate) -> DateTime<Utc> {
+    let mut start = day.and_hms_opt(0, 0, 0).expect("midnight is a valid time");
+    loop {
+      if let Some(at) = self.0.from_local_datetime(&start).earliest() {
+        return at.with_timezone(&Utc);
+      }
+      start += chrono::Duration::minutes(15);
+    }
+  }
+}
+
+impl Default for VenueTimezone {
+  fn default() -> Self {
+    Self(Tz::UTC)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn at(s: &str) -> DateTime<Utc> {
+    s.parse().unwrap()
+  }
+
+  #[test]
+  fn test_days_follow_the_venue() {
+    let berlin = VenueTimezone::new(Tz::Europe__Berlin);
+    let day = |s: &str| s.parse::<NaiveDate>().unwrap();
+
+    assert_eq!(berlin.day_of(at("2026-10-16T22:30:00Z")), day("2026-10-17"));
+    assert_eq!(
+      VenueTimezone::default().day_of(at("2026-10-16T22:30:00Z")),
+      day("2026-10-16")
+    );
+    assert_eq!(
+      berlin.start_of_day(day("2026-10-17")),
+      at("2026-10-16T22:00:00Z")
+    );
+    // Back to standard time on the 25th
+    assert_eq!(
+      berlin.start_of_day(day("2026-10-26")),
+      at("2026-10-25T23:00:00Z")
+    );
+
+    let havana = VenueTimezone::new(Tz::America__Havana);
+    // Clocks skip from midnight to one o'clock
+    assert_eq!(
+      havana.start_of_day(day("2026-03-08")),
+      at("2026-03-08T05:00:00Z")
+    );
+  }
+}
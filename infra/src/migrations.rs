@@ -0,0 +1,105 @@
+use std::{collections::HashMap, fmt::Display};
+
+use sqlx::{
+  migrate::{Migrate, MigrateError, Migrator},
+  PgPool,
+};
+
+/// A difference between the migrations embedded in the binary and the ones
+/// recorded in the database.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MigrationDrift {
+  /// Embedded but not applied yet; the database is behind this build
+  Pending { version: i64, description: String },
+  /// Applied but unknown to this build; the database is ahead of it
+  Unknown { version: i64 },
+  /// Applied from a file that has since been edited
+  ChecksumMismatch { version: i64, description: String },
+  /// A migration failed partway through and left the schema dirty
+  Dirty { version: i64 },
+}
+
+impl MigrationDrift {
+  pub fn is_pending(&self) -> bool {
+    matches!(self, MigrationDrift::Pending { .. })
+  }
+}
+
+impl Display for MigrationDrift {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      MigrationDrift::Pending {
+        version,
+        description,
+      } => write!(f, "migration {} ({}) is not applied", version, description),
+      MigrationDrift::Unknown { version } => write!(
+        f,
+        "migration {} is applied but not known to this build",
+        version
+      ),
+      MigrationDrift::ChecksumMismatch {
+        version,
+        description,
+      } => write!(
+        f,
+        "migration {} ({}) was modified after it was applied",
+        version, description
+      ),
+      MigrationDrift::Dirty { version } => {
+        write!(f, "migration {} failed and left the schema dirty", version)
+      }
+    }
+  }
+}
+
+/// Compares the embedded migrations against the `_sqlx_migrations` table.
+pub async fn detect_drift(
+  pool: &PgPool,
+  migrator: &Migrator,
+) -> Result<Vec<MigrationDrift>, MigrateError> {
+  let mut conn = pool.acquire().await?;
+
+  conn.ensure_migrations_table().await?;
+
+  let mut drift = Vec::new();
+
+  if let Some(version) = conn.dirty_version().await? {
+    drift.push(MigrationDrift::Dirty { version });
+  }
+
+  let mut applied: HashMap<i64, _> = conn
+    .list_applied_migrations()
+    .await?
+    .into_iter()
+    .map(|m| (m.version, m.checksum))
+    .collect();
+
+  for migration in migrator
+    .iter()
+    .filter(|m| !m.migration_type.is_down_migration())
+  {
+    match applied.remove(&migration.version) {
+      None => drift.push(MigrationDrift::Pending {
+        version: migration.version,
+        description: migration.description.to_string(),
+      }),
+      Some(checksum) if checksum != migration.checksum => {
+        drift.push(MigrationDrift::ChecksumMismatch {
+          version: migration.version,
+          description: migration.description.to_string(),
+        })
+      }
+      Some(_) => {}
+    }
+  }
+
+  let mut unknown: Vec<_> = applied.into_keys().collect();
+  unknown.sort_unstable();
+  drift.extend(
+    unknown
+      .into_iter()
+      .map(|version| MigrationDrift::Unknown { version }),
+  );
+
+  Ok(drift)
+}
@@ -1,6 +1,6 @@
 use domain::Email;
 use lettre::{
-  message::header::ContentType,
+  message::{Mailbox, MultiPart},
   transport::smtp::{
     authentication::Credentials,
     client::{Tls, TlsParameters},
@@ -17,6 +17,8 @@ pub enum EmailError {
   Build(#[from] lettre::error::Error),
   #[error("Failed to send email: {0}")]
   Transport(#[from] lettre::transport::smtp::Error),
+  #[error("Failed to render email: {0}")]
+  Template(#[from] minijinja::Error),
 }
 
 #[derive(Debug, Clone)]
@@ -26,12 +28,24 @@ pub struct EmailServiceConfig {
   pub username: String,
   pub password: String,
   pub from: String,
+  /// Count emails as sent without handing them to the SMTP server
+  pub suppressed: bool,
+}
+
+/// Subject and bodies of an email, rendered so it can be stored and sent
+/// later. Mail clients show whichever body they prefer.
+#[derive(Debug, Clone)]
+pub struct EmailContent {
+  pub subject: String,
+  pub text_body: String,
+  pub html_body: String,
 }
 
 #[derive(Clone)]
 pub struct EmailService {
   mailer: AsyncSmtpTransport<Tokio1Executor>,
   from: String,
+  suppressed: bool,
 }
 
 impl EmailService {
@@ -72,28 +86,48 @@ impl EmailService {
     Self {
       mailer,
       from: config.from,
+      suppressed: config.suppressed,
     }
   }
 
-  pub async fn send_invite(
+  /// Connects to the SMTP server and authenticates without sending mail.
+  pub async fn test_connection(&self) -> Result<bool, EmailError> {
+    Ok(self.mailer.test_connection().await?)
+  }
+
+  /// Sends an email rendered with [`super::EmailTemplates`], from
+  /// `sender_name` if given.
+  pub async fn send(
     &self,
     email: &Email,
-    token: &str,
-    inviter_name: &str,
+    content: &EmailContent,
+    sender_name: Option<&str>,
   ) -> Result<(), EmailError> {
-    let email_str = email.expose();
+    if self.suppressed {
+      tracing::debug!("Suppressed email \"{}\"", content.subject);
+      return Ok(());
+    }
+
+    let mut from: Mailbox = self
+      .from
+      .parse()
+      .map_err(|e| EmailError::AddressParse(format!("From address error: {}", e)))?;
+    if let Some(name) = sender_name {
+      from.name = Some(name.to_string());
+    }
+
     let email_msg = Message::builder()
-      .from(self.from.parse().map_err(|e| {
-        EmailError::AddressParse(format!("From address error: {}", e))
-      })?)
-      .to(email_str.parse().map_err(|e| {
-        EmailError::AddressParse(format!("To address error: {}", e))
-      })?)
-      .subject("You have been invited to CayoPay")
-      .header(ContentType::TEXT_HTML)
-      .body(format!(
-        "<h1>CayoPay Invitation</h1><br><p>You have been invited to CayoPay by <b>{}</b>.</p><p>Your invite token is: <i>{}</i></p>",
-        inviter_name, token
+      .from(from)
+      .to(
+        email
+          .expose()
+          .parse()
+          .map_err(|e| EmailError::AddressParse(format!("To address error: {}", e)))?,
+      )
+      .subject(content.subject.as_str())
+      .multipart(MultiPart::alternative_plain_html(
+        content.text_body.clone(),
+        content.html_body.clone(),
       ))?;
 
     self.mailer.send(email_msg).await?;
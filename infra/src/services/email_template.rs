@@ -0,0 +1,272 @@
+use std::sync::Arc;
+
+use domain::{types::Money, Currency, Locale};
+use minijinja::{context, Environment, Value};
+
+use super::{EmailContent, EmailError};
+
+/// Embeds a template file from `templates/email` into the binary.
+macro_rules! template {
+  ($name:literal) => {
+    (
+      $name,
+      include_str!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/templates/email/",
+        $name
+      )),
+    )
+  };
+}
+
+/// Every template, shipped with the binary so a deployment can't miss one.
+/// Each email has a subject, a plaintext and an HTML part per locale.
+const TEMPLATES: &[(&str, &str)] = &[
+  template!("layout.html"),
+  template!("en/invite.subject.txt"),
+  template!("en/invite.txt"),
+  template!("en/invite.html"),
+  template!("de/invite.subject.txt"),
+  template!("de/invite.txt"),
+  template!("de/invite.html"),
+  template!("en/email_change.subject.txt"),
+  template!("en/email_change.txt"),
+  template!("en/email_change.html"),
+  template!("de/email_change.subject.txt"),
+  template!("de/email_change.txt"),
+  template!("de/email_change.html"),
+  template!("en/password_setup.subject.txt"),
+  template!("en/password_setup.txt"),
+  template!("en/password_setup.html"),
+  template!("de/password_setup.subject.txt"),
+  template!("de/password_setup.txt"),
+  template!("de/password_setup.html"),
+  template!("en/transfer_notice.subject.txt"),
+  template!("en/transfer_notice.txt"),
+  template!("en/transfer_notice.html"),
+  template!("de/transfer_notice.subject.txt"),
+  template!("de/transfer_notice.txt"),
+  template!("de/transfer_notice.html"),
+  template!("en/chargeback_notice.subject.txt"),
+  template!("en/chargeback_notice.txt"),
+  template!("en/chargeback_notice.html"),
+  template!("de/chargeback_notice.subject.txt"),
+  template!("de/chargeback_notice.txt"),
+  template!("de/chargeback_notice.html"),
+  template!("en/balance_alert.subject.txt"),
+  template!("en/balance_alert.txt"),
+  template!("en/balance_alert.html"),
+  template!("de/balance_alert.subject.txt"),
+  template!("de/balance_alert.txt"),
+  template!("de/balance_alert.html"),
+  template!("en/debt_reminder.subject.txt"),
+  template!("en/debt_reminder.txt"),
+  template!("en/debt_reminder.html"),
+  template!("de/debt_reminder.subject.txt"),
+  template!("de/debt_reminder.txt"),
+  template!("de/debt_reminder.html"),
+];
+
+/// An email the server sends, with what it says.
+#[derive(Debug, Clone)]
+pub enum EmailTemplate {
+  /// Invites someone to create an account.
+  Invite { inviter_name: String, token: String },
+  /// Asks the owner of a new address to confirm it before it replaces a
+  /// user's current one.
+  EmailChange { token: String },
+  /// Lets someone an account was created for choose its password.
+  PasswordSetup { token: String },
+  /// Tells a user that another user sent them money.
+  TransferNotice {
+    sender_name: String,
+    amount: Money,
+    currency: Currency,
+  },
+  /// Tells a member that an online top-up was disputed and taken back.
+  ChargebackNotice {
+    amount: Money,
+    balance: Money,
+    currency: Currency,
+  },
+  /// Tells the organisers that a watched balance crossed its threshold.
+  /// Sums may not fit [`Money`].
+  BalanceAlert {
+    /// The watched wallet, or `None` for money owed to members
+    wallet_label: Option<String>,
+    /// "above" or "below"
+    direction: String,
+    threshold_cents: i64,
+    balance_cents: i64,
+    currency: Currency,
+  },
+  /// Reminds a member that their wallet has been below zero for a while.
+  DebtReminder {
+    days: i64,
+    balance_cents: i64,
+    currency: Currency,
+  },
+}
+
+/// Renders [`EmailTemplate`]s in the recipient's language. Templates are
+/// parsed once, when this is created, so broken ones fail at startup.
+#[derive(Clone)]
+pub struct EmailTemplates {
+  env: Arc<Environment<'static>>,
+}
+
+impl EmailTemplate {
+  fn name(&self) -> &'static str {
+    match self {
+      EmailTemplate::Invite { .. } => "invite",
+      EmailTemplate::EmailChange { .. } => "email_change",
+      EmailTemplate::PasswordSetup { .. } => "password_setup",
+      EmailTemplate::TransferNotice { .. } => "transfer_notice",
+      EmailTemplate::ChargebackNotice { .. } => "chargeback_notice",
+      EmailTemplate::BalanceAlert { .. } => "balance_alert",
+      EmailTemplate::DebtReminder { .. } => "debt_reminder",
+    }
+  }
+
+  fn context(&self, locale: Locale) -> Value {
+    let code = locale.code();
+    match self {
+      EmailTemplate::Invite {
+        inviter_name,
+        token,
+      } => context! { locale => code, inviter_name, token },
+      EmailTemplate::EmailChange { token } | EmailTemplate::PasswordSetup { token } => {
+        context! { locale => code, token }
+      }
+      EmailTemplate::TransferNotice {
+        sender_name,
+        amount,
+        currency,
+      } => context! {
+        locale => code,
+        sender_name,
+        amount => locale.format_cents(amount.as_minor().into(), *currency),
+      },
+      EmailTemplate::ChargebackNotice {
+        amount,
+        balance,
+        currency,
+      } => context! {
+        locale => code,
+        amount => locale.format_cents(amount.as_minor().into(), *currency),
+        balance => locale.format_cents(balance.as_minor().into(), *currency),
+      },
+      EmailTemplate::BalanceAlert {
+        wallet_label,
+        direction,
+        threshold_cents,
+        balance_cents,
+        currency,
+      } => context! {
+        locale => code,
+        wallet_label,
+        direction,
+        threshold => locale.format_cents(*threshold_cents, *currency),
+        balance => locale.format_cents(*balance_cents, *currency),
+      },
+      EmailTemplate::DebtReminder {
+        days,
+        balance_cents,
+        currency,
+      } => context! {
+        locale => code,
+        days,
+        balance => locale.format_cents(*balance_cents, *currency),
+      },
+    }
+  }
+}
+
+impl EmailTemplates {
+  pub fn load() -> Result<Self, EmailError> {
+    let mut env = Environment::new();
+    for (name, source) in TEMPLATES {
+      env.add_template(name, source)?;
+    }
+
+    Ok(Self { env: Arc::new(env) })
+  }
+
+  pub fn render(
+    &self,
+    template: &EmailTemplate,
+    locale: Locale,
+  ) -> Result<EmailContent, EmailError> {
+    let context = template.context(locale);
+    let render = |part: &str| {
+      self
+        .env
+        .get_template(&format!("{}/{}.{}", locale.code(), template.name(), part))?
+        .render(&context)
+    };
+
+    Ok(EmailContent {
+      subject: render("subject.txt")?.trim().to_string(),
+      text_body: render("txt")?,
+      html_body: render("html")?,
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_every_template_renders_in_every_locale() {
+    let templates = EmailTemplates::load().unwrap();
+    let emails = [
+      EmailTemplate::Invite {
+        inviter_name: "Ada <Lovelace>".to_string(),
+        token: "token".to_string(),
+      },
+      EmailTemplate::EmailChange {
+        token: "token".to_string(),
+      },
+      EmailTemplate::PasswordSetup {
+        token: "token".to_string(),
+      },
+      EmailTemplate::TransferNotice {
+        sender_name: "Ada".to_string(),
+        amount: Money::from_minor(1250),
+        currency: Currency::EUR,
+      },
+      EmailTemplate::ChargebackNotice {
+        amount: Money::from_minor(1250),
+        balance: Money::from_minor(-300),
+        currency: Currency::EUR,
+      },
+      EmailTemplate::BalanceAlert {
+        wallet_label: None,
+        direction: "below".to_string(),
+        threshold_cents: 10_000,
+        balance_cents: 9_950,
+        currency: Currency::EUR,
+      },
+      EmailTemplate::DebtReminder {
+        days: 21,
+        balance_cents: -300,
+        currency: Currency::parse("CHF").unwrap(),
+      },
+    ];
+
+    for locale in Locale::variants() {
+      for email in &emails {
+        let content = templates.render(email, *locale).unwrap();
+        assert!(!content.subject.is_empty());
+        assert!(!content.subject.contains('\n'));
+        assert!(!content.text_body.contains("<p>"));
+        assert!(content.html_body.contains(&format!("lang=\"{}\"", locale)));
+      }
+    }
+
+    let invite = templates.render(&emails[0], Locale::En).unwrap();
+    assert!(invite.html_body.contains("Ada &lt;Lovelace&gt;"));
+    assert!(invite.text_body.contains("Ada <Lovelace>"));
+  }
+}
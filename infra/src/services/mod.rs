@@ -1,3 +1,11 @@
 pub mod email;
+pub mod email_template;
+pub mod psp;
+pub mod session_token;
+pub mod webhook;
 
-pub use email::{EmailError, EmailService, EmailServiceConfig};
+pub use email::{EmailContent, EmailError, EmailService, EmailServiceConfig};
+pub use email_template::{EmailTemplate, EmailTemplates};
+pub use psp::{CheckoutSession, PspClient, PspClientConfig, PspError};
+pub use session_token::SessionTokenSigner;
+pub use webhook::{check_url, WebhookClient, WebhookError};
@@ -0,0 +1,283 @@
+use std::error::Error as _;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+
+use hmac::{Hmac, Mac};
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use reqwest::Url;
+use sha2::Sha256;
+use thiserror::Error;
+
+/// Header carrying the event type, e.g. `order.created`
+pub const EVENT_HEADER: &str = "x-cayopay-event";
+/// Header carrying the unix timestamp the signature was made at
+pub const TIMESTAMP_HEADER: &str = "x-cayopay-timestamp";
+/// Header carrying `v1=<hex HMAC-SHA256 of "{timestamp}.{body}">`
+pub const SIGNATURE_HEADER: &str = "x-cayopay-signature";
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Error)]
+pub enum WebhookError {
+  #[error("Failed to reach webhook: {0}")]
+  Transport(#[from] reqwest::Error),
+  #[error("Webhook responded with status {0}")]
+  Status(u16),
+  /// Not an https URL, or one pointing at a loopback, private or link-local
+  /// address; never requested, so nothing can be learned about the network
+  /// behind the server
+  #[error("Webhook address not allowed: {0}")]
+  Forbidden(&'static str),
+}
+
+/// A host resolving to an address webhooks may not be sent to
+#[derive(Debug, Error)]
+#[error("host resolves to a loopback, private or link-local address")]
+struct ForbiddenAddress;
+
+/// Resolves webhook hosts and refuses any host with an address outside the
+/// public internet. Checked on every connection, not only when the webhook
+/// is saved, so the host can't be rebound to an internal address later.
+struct PublicResolver;
+
+impl Resolve for PublicResolver {
+  fn resolve(&self, name: Name) -> Resolving {
+    let host = name.as_str().to_string();
+    Box::pin(async move {
+      let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host.as_str(), 0)).await?.collect();
+      if addrs.iter().any(|addr| !is_public(addr.ip())) {
+        return Err(ForbiddenAddress.into());
+      }
+
+      Ok(Box::new(addrs.into_iter()) as Addrs)
+    })
+  }
+}
+
+/// Posts signed JSON events to webhook endpoints.
+#[derive(Clone)]
+pub struct WebhookClient {
+  http: reqwest::Client,
+}
+
+impl WebhookClient {
+  pub fn new() -> Self {
+    // A proxy would resolve hosts itself, past the address check
+    let http = reqwest::Client::builder()
+      .timeout(REQUEST_TIMEOUT)
+      .redirect(reqwest::redirect::Policy::none())
+      .no_proxy()
+      .https_only(true)
+      .dns_resolver(Arc::new(PublicResolver))
+      .build()
+      .expect("webhook http client should have been created");
+
+    Self { http }
+  }
+
+  /// Delivers `body` once and returns the response status; any non-2xx
+  /// response counts as a failure. Refused without a request when the URL
+  /// fails [`check_url`] or its host resolves to a non-public address.
+  pub async fn deliver(
+    &self,
+    url: &str,
+    secret: &str,
+    event: &str,
+    body: String,
+  ) -> Result<u16, WebhookError> {
+    let url = check_url(url)?;
+    let timestamp = chrono::Utc::now().timestamp();
+    let signature = sign(secret, timestamp, &body);
+
+    let response = self
+      .http
+      .post(url)
+      .header(reqwest::header::CONTENT_TYPE, "application/json")
+      .header(EVENT_HEADER, event)
+      .header(TIMESTAMP_HEADER, timestamp.to_string())
+      .header(SIGNATURE_HEADER, format!("v1={}", signature))
+      .body(body)
+      .send()
+      .await
+      .map_err(|e| {
+        if refused_address(&e) {
+          WebhookError::Forbidden("host resolves to a loopback, private or link-local address")
+        } else {
+          WebhookError::Transport(e)
+        }
+      })?;
+
+    if response.status().is_success() {
+      Ok(response.status().as_u16())
+    } else {
+      Err(WebhookError::Status(response.status().as_u16()))
+    }
+  }
+}
+
+impl Default for WebhookClient {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+/// Checks what the URL alone tells: https, and no IP address outside the
+/// public internet. Hostnames are checked each time they are resolved for a
+/// delivery.
+pub fn check_url(url: &str) -> Result<Url, WebhookError> {
+  let url = Url::parse(url).map_err(|_| WebhookError::Forbidden("not a URL"))?;
+  if url.scheme() != "https" {
+    return Err(WebhookError::Forbidden("only https URLs are allowed"));
+  }
+
+  let host = url
+    .host_str()
+    .ok_or(WebhookError::Forbidden("the URL has no host"))?;
+  let literal = host.trim_start_matches('[').trim_end_matches(']');
+  if let Ok(ip) = literal.parse::<IpAddr>() {
+    if !is_public(ip) {
+      return Err(WebhookError::Forbidden(
+        "a loopback, private or link-local address",
+      ));
+    }
+  }
+
+  Ok(url)
+}
+
+/// Whether `ip` is reachable on the public internet, as opposed to loopback,
+/// private, link-local (cloud metadata lives at 169.254.169.254), shared,
+/// multicast or reserved ranges.
+fn is_public(ip: IpAddr) -> bool {
+  match ip {
+    IpAddr::V4(ip) => {
+      let [a, b, ..] = ip.octets();
+      !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_private()
+        || ip.is_link_local()
+        || ip.is_broadcast()
+        || ip.is_documentation()
+        || ip.is_multicast()
+        || a == 0
+        || (a == 100 && (64..128).contains(&b))
+        || (a == 192 && b == 0 && ip.octets()[2] == 0)
+        || (a == 198 && (b == 18 || b == 19))
+        || a >= 240)
+    }
+    IpAddr::V6(ip) => {
+      if let Some(v4) = ip.to_ipv4_mapped() {
+        return is_public(IpAddr::V4(v4));
+      }
+      let segments = ip.segments();
+      // NAT64 embeds an IPv4 address in the last 32 bits
+      if segments[..6] == [0x64, 0xff9b, 0, 0, 0, 0] {
+        let [.., hi, lo] = segments;
+        return is_public(IpAddr::V4(((u32::from(hi) << 16) | u32::from(lo)).into()));
+      }
+      !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_multicast()
+        || (segments[0] & 0xfe00) == 0xfc00
+        || (segments[0] & 0xffc0) == 0xfe80
+        || (segments[0] == 0x2001 && segments[1] == 0x0db8))
+    }
+  }
+}
+
+fn refused_address(error: &reqwest::Error) -> bool {
+  let mut source = error.source();
+  while let Some(e) = source {
+    if e.is::<ForbiddenAddress>() {
+      return true;
+    }
+    source = e.source();
+  }
+  false
+}
+
+/// Signs the timestamp together with the body so a captured delivery can't be
+/// replayed later with a fresh timestamp.
+pub fn sign(secret: &str, timestamp: i64, body: &str) -> String {
+  let mut mac =
+    Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+  mac.update(timestamp.to_string().as_bytes());
+  mac.update(b".");
+  mac.update(body.as_bytes());
+
+  hex::encode(mac.finalize().into_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_sign_matches_reference_hmac() {
+    let signature = sign("whsec_test", 1_700_000_000, r#"{"type":"order.created"}"#);
+
+    assert_eq!(
+      signature,
+      "fe321b5f1fdbe13da84d44f2ab83c05590d08405cb1286b2792f1e4dd5323006"
+    );
+  }
+
+  #[test]
+  fn test_check_url_allows_public_https() {
+    assert!(check_url("https://hooks.example.com/cayopay").is_ok());
+    assert!(check_url("https://93.184.216.34/hook").is_ok());
+  }
+
+  #[test]
+  fn test_check_url_refuses_internal_targets() {
+    for url in [
+      "http://hooks.example.com/cayopay",
+      "ftp://hooks.example.com",
+      "https://127.0.0.1/",
+      "https://10.0.0.5:8080/",
+      "https://172.16.0.1/",
+      "https://192.168.1.1/",
+      "https://169.254.169.254/latest/meta-data/",
+      "https://100.64.0.1/",
+      "https://0.0.0.0/",
+      "https://[::1]/",
+      "https://[fd00::1]/",
+      "https://[fe80::1]/",
+      "https://[::ffff:127.0.0.1]/",
+      "https://[64:ff9b::a9fe:a9fe]/",
+      "not a url",
+    ] {
+      assert!(
+        matches!(check_url(url), Err(WebhookError::Forbidden(_))),
+        "allowed {}",
+        url
+      );
+    }
+  }
+
+  #[tokio::test]
+  async fn test_deliver_refuses_hosts_resolving_to_loopback() {
+    let result = WebhookClient::new()
+      .deliver(
+        "https://localhost:9/",
+        "secret",
+        "order.created",
+        "{}".to_string(),
+      )
+      .await;
+
+    assert!(
+      matches!(result, Err(WebhookError::Forbidden(_))),
+      "{:?}",
+      result
+    );
+  }
+
+  #[test]
+  fn test_sign_covers_timestamp() {
+    let body = r#"{"type":"order.created"}"#;
+
+    assert_ne!(sign("secret", 1, body), sign("secret", 2, body));
+  }
+}
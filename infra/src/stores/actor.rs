@@ -1,5 +1,11 @@
-use domain::actor::ActorId;
+use domain::{actor::ActorId, types::PageRequest, ActorSummary, DuplicateCandidate, Email};
 use sqlx::{Executor, Postgres};
+use uuid::Uuid;
+
+use crate::stores::{
+  contains_pattern,
+  models::actor::{ActorSummaryFilter, ActorSummaryRow, DuplicateCandidateRow},
+};
 
 pub struct ActorStore;
 
@@ -20,3 +26,229 @@ impl ActorStore {
     Ok(row.id.into())
   }
 }
+
+pub struct ActorSummaryStore;
+
+impl ActorSummaryStore {
+  /// Rebuilds the summaries of those actors that are a user or guest and
+  /// returns how many it wrote.
+  pub async fn refresh<'c, E>(executor: E, actor_ids: &[ActorId]) -> Result<u64, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let ids: Vec<Uuid> = actor_ids.iter().map(|id| id.into_inner()).collect();
+
+    let result = sqlx::query!(
+      r#"
+      INSERT INTO actor_summaries (actor_id, kind, user_id, guest_id, display_name, email, identifier, role, wallet_count, currency)
+      SELECT a.id,
+             CASE WHEN u.id IS NOT NULL THEN 'user' ELSE 'guest' END,
+             u.id, g.id,
+             u.first_name || ' ' || u.last_name,
+             COALESCE(u.email, g.email),
+             g.identifier,
+             u.role,
+             (SELECT COUNT(*)::int FROM wallets WHERE owner_actor_id = a.id),
+             c.currency
+      FROM actors a
+      LEFT JOIN users u ON u.actor_id = a.id AND u.deleted_at IS NULL
+      LEFT JOIN guests g ON g.actor_id = a.id
+      LEFT JOIN LATERAL (
+        SELECT currency
+        FROM wallets
+        WHERE owner_actor_id = a.id
+        ORDER BY created_at, id
+        LIMIT 1
+      ) c ON true
+      WHERE a.id = ANY($1) AND (u.id IS NOT NULL OR g.id IS NOT NULL)
+      ON CONFLICT (actor_id) DO UPDATE
+      SET kind = EXCLUDED.kind,
+          user_id = EXCLUDED.user_id,
+          guest_id = EXCLUDED.guest_id,
+          display_name = EXCLUDED.display_name,
+          email = EXCLUDED.email,
+          identifier = EXCLUDED.identifier,
+          role = EXCLUDED.role,
+          wallet_count = EXCLUDED.wallet_count,
+          currency = EXCLUDED.currency
+      "#,
+      &ids,
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(result.rows_affected())
+  }
+
+  /// Drops the summaries of those actors that are no longer a user or guest,
+  /// e.g. deleted users.
+  pub async fn delete_orphaned<'c, E>(
+    executor: E,
+    actor_ids: &[ActorId],
+  ) -> Result<u64, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let ids: Vec<Uuid> = actor_ids.iter().map(|id| id.into_inner()).collect();
+
+    let result = sqlx::query!(
+      r#"
+      DELETE FROM actor_summaries s
+      WHERE s.actor_id = ANY($1)
+        AND NOT EXISTS (SELECT 1 FROM users u WHERE u.actor_id = s.actor_id AND u.deleted_at IS NULL)
+        AND NOT EXISTS (SELECT 1 FROM guests g WHERE g.actor_id = s.actor_id)
+      "#,
+      &ids,
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(result.rows_affected())
+  }
+
+  /// Users first, then guests, each by name and email. Balances are summed
+  /// as of now, as they change too often to project.
+  pub async fn list_paginated<'c, E>(
+    executor: E,
+    filter: &ActorSummaryFilter,
+    page: &PageRequest,
+  ) -> Result<Vec<ActorSummary>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let rows = sqlx::query_as!(
+      ActorSummaryRow,
+      r#"
+      SELECT s.actor_id, s.kind, s.user_id, s.guest_id, s.display_name, s.email, s.identifier, s.role,
+             s.wallet_count, s.currency AS "currency: _",
+             (SELECT COALESCE(SUM(w.balance_cents), 0)::bigint
+              FROM wallets w
+              WHERE w.owner_actor_id = s.actor_id AND w.currency = s.currency) AS "balance_cents!",
+             s.created_at, s.updated_at
+      FROM actor_summaries s
+      WHERE ($1::text IS NULL OR s.kind = $1)
+        AND ($2::text IS NULL OR s.display_name ILIKE $2 OR s.email ILIKE $2 OR s.identifier ILIKE $2)
+      ORDER BY s.kind DESC, s.display_name, s.email, s.actor_id
+      LIMIT $3 OFFSET $4
+      "#,
+      filter.kind.as_ref().map(ToString::to_string),
+      filter.search.as_deref().map(contains_pattern),
+      page.limit(),
+      page.offset(),
+    )
+    .fetch_all(executor)
+    .await?;
+
+    Ok(rows.into_iter().map(Into::into).collect())
+  }
+
+  /// Users and guests likely to be the person with `email`, closest first.
+  /// Guests already claimed by a user are left out.
+  pub async fn find_duplicates<'c, E>(
+    executor: E,
+    email: &Email,
+    limit: i64,
+  ) -> Result<Vec<DuplicateCandidate>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let (first_name, last_name) = email.name_hint().unzip();
+
+    let rows = sqlx::query_as!(
+      DuplicateCandidateRow,
+      r#"
+      SELECT actor_id, kind, user_id, guest_id, display_name, reason AS "reason!"
+      FROM (
+        SELECT s.actor_id, s.kind, s.user_id, s.guest_id, s.display_name,
+               CASE
+                 WHEN lower(s.email) = lower($1) THEN 'same_email'
+                 WHEN normalized_email(s.email) = normalized_email($1) THEN 'similar_email'
+                 ELSE 'similar_name'
+               END AS reason
+        FROM actor_summaries s
+        WHERE (normalized_email(s.email) = normalized_email($1)
+               OR lower(s.display_name) = $2 || ' ' || $3
+               OR lower(s.display_name) = $3 || ' ' || $2)
+          AND NOT EXISTS (SELECT 1 FROM guest_claims c WHERE c.guest_id = s.guest_id)
+      ) candidates
+      ORDER BY reason, kind DESC, actor_id
+      LIMIT $4
+      "#,
+      email.expose(),
+      first_name,
+      last_name,
+      limit,
+    )
+    .fetch_all(executor)
+    .await?;
+
+    Ok(rows.into_iter().map(Into::into).collect())
+  }
+
+  pub async fn count<'c, E>(executor: E, filter: &ActorSummaryFilter) -> Result<i64, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let count = sqlx::query_scalar!(
+      r#"
+      SELECT COUNT(*) AS "count!"
+      FROM actor_summaries
+      WHERE ($1::text IS NULL OR kind = $1)
+        AND ($2::text IS NULL OR display_name ILIKE $2 OR email ILIKE $2 OR identifier ILIKE $2)
+      "#,
+      filter.kind.as_ref().map(ToString::to_string),
+      filter.search.as_deref().map(contains_pattern),
+    )
+    .fetch_one(executor)
+    .await?;
+
+    Ok(count)
+  }
+}
+
+/// Actors whose summary needs rebuilding, queued by database triggers.
+pub struct ActorSummaryOutboxStore;
+
+impl ActorSummaryOutboxStore {
+  /// Oldest first. Locks the entries until the end of the transaction and
+  /// skips those locked by others, so projectors on several replicas split
+  /// the work, and changes queued meanwhile wait for the lock.
+  pub async fn lock_batch<'c, E>(executor: E, limit: i64) -> Result<Vec<ActorId>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let ids = sqlx::query_scalar!(
+      r#"
+      SELECT actor_id
+      FROM actor_summary_outbox
+      ORDER BY created_at, actor_id
+      LIMIT $1
+      FOR UPDATE SKIP LOCKED
+      "#,
+      limit,
+    )
+    .fetch_all(executor)
+    .await?;
+
+    Ok(ids.into_iter().map(Into::into).collect())
+  }
+
+  pub async fn delete<'c, E>(executor: E, actor_ids: &[ActorId]) -> Result<u64, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let ids: Vec<Uuid> = actor_ids.iter().map(|id| id.into_inner()).collect();
+
+    let result = sqlx::query!(
+      r#"
+      DELETE FROM actor_summary_outbox
+      WHERE actor_id = ANY($1)
+      "#,
+      &ids,
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(result.rows_affected())
+  }
+}
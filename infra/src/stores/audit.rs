@@ -0,0 +1,219 @@
+use chrono::{DateTime, Utc};
+use domain::{types::PageRequest, ActorId, AuditChainReport, AuditEntry};
+use sqlx::{Executor, Postgres};
+
+use crate::stores::models::audit::{AuditEntryCreation, AuditEntryRow, AuditFilter};
+
+pub struct AuditStore;
+
+impl AuditStore {
+  pub async fn create<'c, E>(
+    executor: E,
+    creation: &AuditEntryCreation,
+  ) -> Result<AuditEntry, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query_as!(
+      AuditEntryRow,
+      r#"
+      WITH entry AS (
+        INSERT INTO audit_log (actor_id, action, target, ip_address, details)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING id, seq, actor_id, action, target, ip_address, details, previous_hash, hash, created_at, updated_at
+      ), personal_data AS (
+        INSERT INTO audit_personal_data (audit_entry_id, data)
+        SELECT id, $6 FROM entry
+        WHERE $6::jsonb IS NOT NULL
+      )
+      SELECT id AS "id!", seq AS "seq!", actor_id, action AS "action!", target, ip_address, details,
+             $6::jsonb AS personal_data, previous_hash, hash AS "hash!", created_at AS "created_at!", updated_at
+      FROM entry
+      "#,
+      creation.actor_id.map(|a| a.into_inner()),
+      creation.action,
+      creation.target,
+      creation.ip_address,
+      creation.details,
+      creation.personal_data,
+    )
+    .fetch_one(executor)
+    .await?;
+
+    Ok(row.into())
+  }
+
+  pub async fn list_paginated<'c, E>(
+    executor: E,
+    filter: &AuditFilter,
+    page: &PageRequest,
+  ) -> Result<Vec<AuditEntry>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let rows = sqlx::query_as!(
+      AuditEntryRow,
+      r#"
+      SELECT a.id, a.seq, a.actor_id, a.action, a.target, a.ip_address, a.details, p.data AS "personal_data?",
+             a.previous_hash, a.hash, a.created_at, a.updated_at
+      FROM audit_log a
+      LEFT JOIN audit_personal_data p ON p.audit_entry_id = a.id
+      WHERE ($1::uuid IS NULL OR a.actor_id = $1)
+        AND ($2::text IS NULL OR a.action = $2)
+        AND ($3::timestamptz IS NULL OR a.created_at >= $3)
+        AND ($4::timestamptz IS NULL OR a.created_at < $4)
+      ORDER BY a.seq DESC
+      LIMIT $5 OFFSET $6
+      "#,
+      filter.actor_id.map(|id| id.into_inner()),
+      filter.action,
+      filter.from,
+      filter.to,
+      page.limit(),
+      page.offset(),
+    )
+    .fetch_all(executor)
+    .await?;
+
+    Ok(rows.into_iter().map(Into::into).collect())
+  }
+
+  pub async fn count<'c, E>(executor: E, filter: &AuditFilter) -> Result<i64, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let count = sqlx::query_scalar!(
+      r#"
+      SELECT COUNT(*) AS "count!"
+      FROM audit_log
+      WHERE ($1::uuid IS NULL OR actor_id = $1)
+        AND ($2::text IS NULL OR action = $2)
+        AND ($3::timestamptz IS NULL OR created_at >= $3)
+        AND ($4::timestamptz IS NULL OR created_at < $4)
+      "#,
+      filter.actor_id.map(|id| id.into_inner()),
+      filter.action,
+      filter.from,
+      filter.to,
+    )
+    .fetch_one(executor)
+    .await?;
+
+    Ok(count)
+  }
+
+  /// Entries after `after_seq` in chain order, at most `limit`.
+  pub async fn list_chain_page<'c, E>(
+    executor: E,
+    after_seq: i64,
+    limit: i64,
+  ) -> Result<Vec<AuditEntry>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let rows = sqlx::query_as!(
+      AuditEntryRow,
+      r#"
+      SELECT a.id, a.seq, a.actor_id, a.action, a.target, a.ip_address, a.details, p.data AS "personal_data?",
+             a.previous_hash, a.hash, a.created_at, a.updated_at
+      FROM audit_log a
+      LEFT JOIN audit_personal_data p ON p.audit_entry_id = a.id
+      WHERE a.seq > $1
+      ORDER BY a.seq
+      LIMIT $2
+      "#,
+      after_seq,
+      limit,
+    )
+    .fetch_all(executor)
+    .await?;
+
+    Ok(rows.into_iter().map(Into::into).collect())
+  }
+
+  /// Recomputes every entry's hash and checks it links to the entry before,
+  /// without gaps in between.
+  pub async fn verify_chain<'c, E>(executor: E) -> Result<AuditChainReport, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query!(
+      r#"
+      WITH chain AS (
+        SELECT seq, id, hash,
+               previous_hash IS NOT DISTINCT FROM lag(hash) OVER w
+                 AND seq = COALESCE(lag(seq) OVER w, 0) + 1
+                 AND hash = audit_log_hash(seq, previous_hash, id, actor_id, action, target, details, created_at)
+                 AS intact
+        FROM audit_log
+        WINDOW w AS (ORDER BY seq)
+      )
+      SELECT
+        (SELECT COUNT(*) FROM chain) AS "entries!",
+        (SELECT hash FROM chain ORDER BY seq DESC LIMIT 1) AS head_hash,
+        (SELECT seq FROM chain WHERE NOT intact ORDER BY seq LIMIT 1) AS broken_seq,
+        (SELECT id FROM chain WHERE NOT intact ORDER BY seq LIMIT 1) AS broken_id
+      "#,
+    )
+    .fetch_one(executor)
+    .await?;
+
+    Ok(AuditChainReport {
+      entries: row.entries,
+      head_hash: row.head_hash,
+      first_broken: row.broken_seq.zip(row.broken_id.map(Into::into)),
+    })
+  }
+
+  /// Clears the IP address of entries recorded before `cutoff`. Returns how
+  /// many.
+  pub async fn clear_ip_addresses_before<'c, E>(
+    executor: E,
+    cutoff: DateTime<Utc>,
+  ) -> Result<u64, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let result = sqlx::query!(
+      r#"
+      UPDATE audit_log
+      SET ip_address = NULL
+      WHERE ip_address IS NOT NULL AND created_at < $1
+      "#,
+      cutoff,
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(result.rows_affected())
+  }
+
+  /// Erases what the audit log keeps about the actor beyond the hashed
+  /// entries: the personal data recorded with their entries and the IP
+  /// addresses they acted from.
+  pub async fn erase_personal_data_by_actor_id<'c, E>(
+    executor: E,
+    actor_id: &ActorId,
+  ) -> Result<(), sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    sqlx::query!(
+      r#"
+      WITH erased AS (
+        DELETE FROM audit_personal_data p
+        USING audit_log a
+        WHERE p.audit_entry_id = a.id AND a.actor_id = $1
+      )
+      UPDATE audit_log
+      SET ip_address = NULL
+      WHERE actor_id = $1 AND ip_address IS NOT NULL
+      "#,
+      actor_id.into_inner(),
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(())
+  }
+}
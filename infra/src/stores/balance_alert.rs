@@ -0,0 +1,119 @@
+use domain::{BalanceAlert, BalanceAlertId};
+use sqlx::{Executor, Postgres};
+
+use crate::stores::models::balance_alert::{BalanceAlertCreation, BalanceAlertRow};
+
+pub struct BalanceAlertStore;
+
+impl BalanceAlertStore {
+  pub async fn create<'c, E>(
+    executor: E,
+    creation: &BalanceAlertCreation,
+  ) -> Result<BalanceAlert, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query_as!(
+      BalanceAlertRow,
+      r#"
+      INSERT INTO balance_alerts (wallet_label, direction, threshold_cents, notify_email, webhook_url, webhook_secret, created_by_actor_id)
+      VALUES ($1, $2, $3, $4, $5, $6, $7)
+      RETURNING id, wallet_label, direction, threshold_cents, notify_email, webhook_url, webhook_secret, breached, last_triggered_at, created_by_actor_id, created_at, updated_at
+      "#,
+      creation.wallet_label.as_ref().map(ToString::to_string),
+      creation.direction.to_string(),
+      creation.threshold_cents,
+      creation.notify_email.as_ref().map(|email| email.expose()),
+      creation.webhook_url,
+      creation.webhook_secret,
+      creation.created_by.map(|id| id.into_inner()),
+    )
+    .fetch_one(executor)
+    .await?;
+
+    Ok(row.into())
+  }
+
+  pub async fn find_by_id<'c, E>(
+    executor: E,
+    id: &BalanceAlertId,
+  ) -> Result<Option<BalanceAlert>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query_as!(
+      BalanceAlertRow,
+      r#"
+      SELECT id, wallet_label, direction, threshold_cents, notify_email, webhook_url, webhook_secret, breached, last_triggered_at, created_by_actor_id, created_at, updated_at
+      FROM balance_alerts
+      WHERE id = $1
+      "#,
+      id.into_inner(),
+    )
+    .fetch_optional(executor)
+    .await?;
+
+    Ok(row.map(Into::into))
+  }
+
+  pub async fn list<'c, E>(executor: E) -> Result<Vec<BalanceAlert>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let rows = sqlx::query_as!(
+      BalanceAlertRow,
+      r#"
+      SELECT id, wallet_label, direction, threshold_cents, notify_email, webhook_url, webhook_secret, breached, last_triggered_at, created_by_actor_id, created_at, updated_at
+      FROM balance_alerts
+      ORDER BY created_at, id
+      "#
+    )
+    .fetch_all(executor)
+    .await?;
+
+    Ok(rows.into_iter().map(Into::into).collect())
+  }
+
+  /// Records whether the balance is past the threshold, stamping the time
+  /// when it newly is.
+  pub async fn set_breached<'c, E>(
+    executor: E,
+    id: &BalanceAlertId,
+    breached: bool,
+  ) -> Result<(), sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    sqlx::query!(
+      r#"
+      UPDATE balance_alerts
+      SET breached = $2,
+          last_triggered_at = CASE WHEN $2 AND NOT breached THEN now() ELSE last_triggered_at END
+      WHERE id = $1
+      "#,
+      id.into_inner(),
+      breached,
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(())
+  }
+
+  pub async fn delete_by_id<'c, E>(executor: E, id: &BalanceAlertId) -> Result<(), sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    sqlx::query!(
+      r#"
+      DELETE FROM balance_alerts
+      WHERE id = $1
+      "#,
+      id.into_inner()
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(())
+  }
+}
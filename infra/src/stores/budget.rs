@@ -0,0 +1,179 @@
+use sqlx::{Executor, Postgres};
+use uuid::Uuid;
+
+use crate::stores::models::budget::{BudgetEnvelopeCreation, BudgetEnvelopeRow};
+use domain::{BudgetEnvelope, BudgetEnvelopeId, BudgetPlan, ShopId, WalletId};
+
+pub struct BudgetEnvelopeStore;
+
+impl BudgetEnvelopeStore {
+  /// Creates the envelope without any shops; they are added with
+  /// [`Self::set_shops`].
+  pub async fn create<'c, E>(
+    executor: E,
+    creation: &BudgetEnvelopeCreation,
+  ) -> Result<BudgetEnvelopeId, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let id = sqlx::query_scalar!(
+      r#"
+      INSERT INTO budget_envelopes (wallet_id, name, amount_cents)
+      VALUES ($1, $2, $3)
+      RETURNING id
+      "#,
+      creation.wallet_id.into_inner(),
+      creation.plan.name(),
+      creation.plan.amount().as_minor(),
+    )
+    .fetch_one(executor)
+    .await?;
+
+    Ok(id.into())
+  }
+
+  /// Replaces the name and amount; spending restarts from zero if `reset`.
+  pub async fn update<'c, E>(
+    executor: E,
+    id: &BudgetEnvelopeId,
+    plan: &BudgetPlan,
+    reset: bool,
+  ) -> Result<bool, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let result = sqlx::query!(
+      r#"
+      UPDATE budget_envelopes
+      SET name = $2,
+          amount_cents = $3,
+          spent_cents = CASE WHEN $4 THEN 0 ELSE spent_cents END,
+          spent_since = CASE WHEN $4 THEN now() ELSE spent_since END
+      WHERE id = $1
+      "#,
+      id.into_inner(),
+      plan.name(),
+      plan.amount().as_minor(),
+      reset,
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+  }
+
+  /// Makes `shops` exactly the shops the envelope covers.
+  pub async fn set_shops<'c, E>(
+    executor: E,
+    id: &BudgetEnvelopeId,
+    shops: &[ShopId],
+  ) -> Result<(), sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let shop_ids: Vec<Uuid> = shops.iter().map(|id| id.into_inner()).collect();
+
+    sqlx::query!(
+      r#"
+      WITH removed AS (
+        DELETE FROM budget_envelope_shops
+        WHERE envelope_id = $1 AND shop_id <> ALL($2)
+      )
+      INSERT INTO budget_envelope_shops (envelope_id, shop_id)
+      SELECT $1, UNNEST($2::uuid[])
+      ON CONFLICT (envelope_id, shop_id) DO NOTHING
+      "#,
+      id.into_inner(),
+      &shop_ids,
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(())
+  }
+
+  pub async fn find_by_id<'c, E>(
+    executor: E,
+    id: &BudgetEnvelopeId,
+  ) -> Result<Option<BudgetEnvelope>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query_as!(
+      BudgetEnvelopeRow,
+      r#"
+      SELECT e.id, e.wallet_id, e.name, e.amount_cents, e.spent_cents, e.spent_since,
+             ARRAY(
+               SELECT shop_id FROM budget_envelope_shops WHERE envelope_id = e.id ORDER BY shop_id
+             ) AS "shop_ids!",
+             e.created_at, e.updated_at
+      FROM budget_envelopes e
+      WHERE e.id = $1
+      "#,
+      id.into_inner(),
+    )
+    .fetch_optional(executor)
+    .await?;
+
+    row.map(TryInto::try_into).transpose()
+  }
+
+  /// Envelopes of the wallet, by name.
+  pub async fn list_by_wallet_id<'c, E>(
+    executor: E,
+    wallet_id: &WalletId,
+  ) -> Result<Vec<BudgetEnvelope>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let rows = sqlx::query_as!(
+      BudgetEnvelopeRow,
+      r#"
+      SELECT e.id, e.wallet_id, e.name, e.amount_cents, e.spent_cents, e.spent_since,
+             ARRAY(
+               SELECT shop_id FROM budget_envelope_shops WHERE envelope_id = e.id ORDER BY shop_id
+             ) AS "shop_ids!",
+             e.created_at, e.updated_at
+      FROM budget_envelopes e
+      WHERE e.wallet_id = $1
+      ORDER BY e.name
+      "#,
+      wallet_id.into_inner(),
+    )
+    .fetch_all(executor)
+    .await?;
+
+    rows.into_iter().map(TryInto::try_into).collect()
+  }
+
+  pub async fn count_by_wallet_id<'c, E>(
+    executor: E,
+    wallet_id: &WalletId,
+  ) -> Result<i64, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let count = sqlx::query_scalar!(
+      r#"SELECT COUNT(*) AS "count!" FROM budget_envelopes WHERE wallet_id = $1"#,
+      wallet_id.into_inner(),
+    )
+    .fetch_one(executor)
+    .await?;
+
+    Ok(count)
+  }
+
+  pub async fn delete_by_id<'c, E>(executor: E, id: &BudgetEnvelopeId) -> Result<bool, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let result = sqlx::query!(
+      "DELETE FROM budget_envelopes WHERE id = $1",
+      id.into_inner()
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+  }
+}
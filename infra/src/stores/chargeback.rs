@@ -0,0 +1,206 @@
+use domain::{
+  ActorId, Chargeback, ChargebackId, ChargebackStatus, TopUpId, TransactionId, WalletId,
+};
+use sqlx::{Executor, Postgres};
+
+use crate::stores::models::chargeback::{ChargebackCreation, ChargebackRow};
+
+pub struct ChargebackStore;
+
+impl ChargebackStore {
+  pub async fn create<'c, E>(
+    executor: E,
+    creation: &ChargebackCreation,
+  ) -> Result<Chargeback, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query_as!(
+      ChargebackRow,
+      r#"
+      INSERT INTO chargebacks (top_up_id, wallet_id, amount_cents, provider_dispute_id, transaction_id)
+      VALUES ($1, $2, $3, $4, $5)
+      RETURNING id, top_up_id, wallet_id, amount_cents, provider_dispute_id, status, transaction_id, reversal_transaction_id, resolved_by_actor_id, resolution_note, resolved_at, created_at, updated_at
+      "#,
+      creation.top_up_id.into_inner(),
+      creation.wallet_id.into_inner(),
+      creation.amount.as_minor(),
+      creation.provider_dispute_id,
+      creation.transaction_id.into_inner(),
+    )
+    .fetch_one(executor)
+    .await?;
+
+    Ok(row.into())
+  }
+
+  pub async fn find_by_id_for_update<'c, E>(
+    executor: E,
+    id: &ChargebackId,
+  ) -> Result<Option<Chargeback>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query_as!(
+      ChargebackRow,
+      r#"
+      SELECT id, top_up_id, wallet_id, amount_cents, provider_dispute_id, status, transaction_id, reversal_transaction_id, resolved_by_actor_id, resolution_note, resolved_at, created_at, updated_at
+      FROM chargebacks
+      WHERE id = $1
+      FOR UPDATE
+      "#,
+      id.into_inner(),
+    )
+    .fetch_optional(executor)
+    .await?;
+
+    Ok(row.map(Into::into))
+  }
+
+  /// Locks the chargeback until the end of the transaction, so repeated
+  /// deliveries of the provider's events apply only once.
+  pub async fn find_by_provider_dispute_id_for_update<'c, E>(
+    executor: E,
+    dispute_id: &str,
+  ) -> Result<Option<Chargeback>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query_as!(
+      ChargebackRow,
+      r#"
+      SELECT id, top_up_id, wallet_id, amount_cents, provider_dispute_id, status, transaction_id, reversal_transaction_id, resolved_by_actor_id, resolution_note, resolved_at, created_at, updated_at
+      FROM chargebacks
+      WHERE provider_dispute_id = $1
+      FOR UPDATE
+      "#,
+      dispute_id,
+    )
+    .fetch_optional(executor)
+    .await?;
+
+    Ok(row.map(Into::into))
+  }
+
+  /// Newest first; only those staff have yet to resolve if `unresolved`.
+  pub async fn list<'c, E>(executor: E, unresolved: bool) -> Result<Vec<Chargeback>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let rows = sqlx::query_as!(
+      ChargebackRow,
+      r#"
+      SELECT id, top_up_id, wallet_id, amount_cents, provider_dispute_id, status, transaction_id, reversal_transaction_id, resolved_by_actor_id, resolution_note, resolved_at, created_at, updated_at
+      FROM chargebacks
+      WHERE NOT $1 OR resolved_at IS NULL
+      ORDER BY created_at DESC, id DESC
+      "#,
+      unresolved,
+    )
+    .fetch_all(executor)
+    .await?;
+
+    Ok(rows.into_iter().map(Into::into).collect())
+  }
+
+  pub async fn has_unresolved<'c, E>(executor: E, wallet_id: &WalletId) -> Result<bool, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let exists = sqlx::query_scalar!(
+      r#"
+      SELECT EXISTS (
+        SELECT 1
+        FROM chargebacks
+        WHERE wallet_id = $1 AND resolved_at IS NULL
+      ) AS "exists!"
+      "#,
+      wallet_id.into_inner(),
+    )
+    .fetch_one(executor)
+    .await?;
+
+    Ok(exists)
+  }
+
+  /// Cents taken back from the top-up by its chargebacks and not returned,
+  /// i.e. all but the won ones.
+  pub async fn sum_taken_back_by_top_up_id<'c, E>(
+    executor: E,
+    top_up_id: &TopUpId,
+  ) -> Result<i64, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let cents = sqlx::query_scalar!(
+      r#"
+      SELECT COALESCE(SUM(amount_cents), 0)::bigint AS "cents!"
+      FROM chargebacks
+      WHERE top_up_id = $1 AND status <> 'won'
+      "#,
+      top_up_id.into_inner(),
+    )
+    .fetch_one(executor)
+    .await?;
+
+    Ok(cents)
+  }
+
+  /// Records the provider's decision on an open chargeback. Won disputes
+  /// count as resolved right away.
+  pub async fn close<'c, E>(
+    executor: E,
+    id: &ChargebackId,
+    status: ChargebackStatus,
+    reversal_transaction_id: Option<TransactionId>,
+  ) -> Result<Option<Chargeback>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query_as!(
+      ChargebackRow,
+      r#"
+      UPDATE chargebacks
+      SET status = $2,
+          reversal_transaction_id = $3,
+          resolved_at = CASE WHEN $2 = 'won' THEN COALESCE(resolved_at, now()) ELSE resolved_at END
+      WHERE id = $1 AND status = 'open'
+      RETURNING id, top_up_id, wallet_id, amount_cents, provider_dispute_id, status, transaction_id, reversal_transaction_id, resolved_by_actor_id, resolution_note, resolved_at, created_at, updated_at
+      "#,
+      id.into_inner(),
+      status.to_string(),
+      reversal_transaction_id.map(|id| id.into_inner()),
+    )
+    .fetch_optional(executor)
+    .await?;
+
+    Ok(row.map(Into::into))
+  }
+
+  pub async fn resolve<'c, E>(
+    executor: E,
+    id: &ChargebackId,
+    resolved_by: ActorId,
+    note: &str,
+  ) -> Result<Option<Chargeback>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query_as!(
+      ChargebackRow,
+      r#"
+      UPDATE chargebacks
+      SET resolved_by_actor_id = $2, resolution_note = $3, resolved_at = now()
+      WHERE id = $1
+      RETURNING id, top_up_id, wallet_id, amount_cents, provider_dispute_id, status, transaction_id, reversal_transaction_id, resolved_by_actor_id, resolution_note, resolved_at, created_at, updated_at
+      "#,
+      id.into_inner(),
+      resolved_by.into_inner(),
+      note,
+    )
+    .fetch_optional(executor)
+    .await?;
+
+    Ok(row.map(Into::into))
+  }
+}
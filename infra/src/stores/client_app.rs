@@ -0,0 +1,308 @@
+use domain::{AccessToken, AccessTokenId, AuthorizationCode, ClientApp, ClientAppId, UserId};
+use sqlx::{Executor, Postgres};
+
+use crate::stores::models::client_app::{
+  scope_names, AccessTokenCreation, AccessTokenRow, AuthorizationCodeCreation,
+  AuthorizationCodeRow, ClientAppCreation, ClientAppRow,
+};
+
+pub struct ClientAppStore;
+
+impl ClientAppStore {
+  pub async fn create<'c, E>(
+    executor: E,
+    creation: &ClientAppCreation,
+  ) -> Result<ClientApp, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query_as!(
+      ClientAppRow,
+      r#"
+      INSERT INTO client_apps (name, redirect_uris, allowed_scopes, created_by_actor_id)
+      VALUES ($1, $2, $3, $4)
+      RETURNING id, name, redirect_uris, allowed_scopes, created_by_actor_id, created_at, updated_at
+      "#,
+      creation.name,
+      &creation.redirect_uris,
+      &scope_names(&creation.allowed_scopes),
+      creation.created_by.map(|id| id.into_inner()),
+    )
+    .fetch_one(executor)
+    .await?;
+
+    Ok(row.into())
+  }
+
+  pub async fn find_by_id<'c, E>(
+    executor: E,
+    id: &ClientAppId,
+  ) -> Result<Option<ClientApp>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query_as!(
+      ClientAppRow,
+      r#"
+      SELECT id, name, redirect_uris, allowed_scopes, created_by_actor_id, created_at, updated_at
+      FROM client_apps
+      WHERE id = $1
+      "#,
+      id.into_inner(),
+    )
+    .fetch_optional(executor)
+    .await?;
+
+    Ok(row.map(Into::into))
+  }
+
+  pub async fn list<'c, E>(executor: E) -> Result<Vec<ClientApp>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let rows = sqlx::query_as!(
+      ClientAppRow,
+      r#"
+      SELECT id, name, redirect_uris, allowed_scopes, created_by_actor_id, created_at, updated_at
+      FROM client_apps
+      ORDER BY name, created_at
+      "#,
+    )
+    .fetch_all(executor)
+    .await?;
+
+    Ok(rows.into_iter().map(Into::into).collect())
+  }
+
+  /// Removes the app along with its codes and tokens. Returns whether it
+  /// existed.
+  pub async fn delete_by_id<'c, E>(executor: E, id: &ClientAppId) -> Result<bool, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let result = sqlx::query!(
+      r#"
+      DELETE FROM client_apps
+      WHERE id = $1
+      "#,
+      id.into_inner(),
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+  }
+}
+
+pub struct AuthorizationCodeStore;
+
+impl AuthorizationCodeStore {
+  pub async fn create<'c, E>(
+    executor: E,
+    creation: &AuthorizationCodeCreation,
+  ) -> Result<AuthorizationCode, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query_as!(
+      AuthorizationCodeRow,
+      r#"
+      INSERT INTO app_authorization_codes
+        (client_app_id, user_id, code, redirect_uri, scopes, code_challenge, expires_at)
+      VALUES ($1, $2, $3, $4, $5, $6, $7)
+      RETURNING id, client_app_id, user_id, code, redirect_uri, scopes, code_challenge,
+                expires_at, created_at, updated_at
+      "#,
+      creation.client_app_id.into_inner(),
+      creation.user_id.into_inner(),
+      creation.code,
+      creation.redirect_uri,
+      &scope_names(&creation.scopes),
+      creation.code_challenge,
+      chrono::Utc::now() + creation.expires_in,
+    )
+    .fetch_one(executor)
+    .await?;
+
+    Ok(row.into())
+  }
+
+  /// Deletes the code and returns it, so it can only be exchanged once.
+  pub async fn take_by_code<'c, E>(
+    executor: E,
+    code: &str,
+  ) -> Result<Option<AuthorizationCode>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query_as!(
+      AuthorizationCodeRow,
+      r#"
+      DELETE FROM app_authorization_codes
+      WHERE code = $1
+      RETURNING id, client_app_id, user_id, code, redirect_uri, scopes, code_challenge,
+                expires_at, created_at, updated_at
+      "#,
+      code,
+    )
+    .fetch_optional(executor)
+    .await?;
+
+    Ok(row.map(Into::into))
+  }
+
+  pub async fn delete_expired<'c, E>(executor: E) -> Result<u64, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let result = sqlx::query!(
+      r#"
+      DELETE FROM app_authorization_codes
+      WHERE expires_at < now()
+      "#,
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(result.rows_affected())
+  }
+}
+
+pub struct AccessTokenStore;
+
+impl AccessTokenStore {
+  pub async fn create<'c, E>(
+    executor: E,
+    creation: &AccessTokenCreation,
+  ) -> Result<AccessToken, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query_as!(
+      AccessTokenRow,
+      r#"
+      INSERT INTO app_access_tokens (client_app_id, user_id, token, scopes, expires_at)
+      VALUES ($1, $2, $3, $4, $5)
+      RETURNING id, client_app_id, user_id, token, scopes, expires_at, last_used_at,
+                created_at, updated_at
+      "#,
+      creation.client_app_id.into_inner(),
+      creation.user_id.into_inner(),
+      creation.token,
+      &scope_names(&creation.scopes),
+      chrono::Utc::now() + creation.expires_in,
+    )
+    .fetch_one(executor)
+    .await?;
+
+    Ok(row.into())
+  }
+
+  pub async fn find_by_token<'c, E>(
+    executor: E,
+    token: &str,
+  ) -> Result<Option<AccessToken>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query_as!(
+      AccessTokenRow,
+      r#"
+      SELECT id, client_app_id, user_id, token, scopes, expires_at, last_used_at,
+             created_at, updated_at
+      FROM app_access_tokens
+      WHERE token = $1
+      "#,
+      token,
+    )
+    .fetch_optional(executor)
+    .await?;
+
+    Ok(row.map(Into::into))
+  }
+
+  pub async fn list_by_user_id<'c, E>(
+    executor: E,
+    user_id: &UserId,
+  ) -> Result<Vec<AccessToken>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let rows = sqlx::query_as!(
+      AccessTokenRow,
+      r#"
+      SELECT id, client_app_id, user_id, token, scopes, expires_at, last_used_at,
+             created_at, updated_at
+      FROM app_access_tokens
+      WHERE user_id = $1
+      ORDER BY created_at DESC
+      "#,
+      user_id.into_inner(),
+    )
+    .fetch_all(executor)
+    .await?;
+
+    Ok(rows.into_iter().map(Into::into).collect())
+  }
+
+  /// Records that the token was just used, at most once a minute like
+  /// sessions.
+  pub async fn touch_by_token<'c, E>(executor: E, token: &str) -> Result<(), sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    sqlx::query!(
+      r#"
+      UPDATE app_access_tokens
+      SET last_used_at = now()
+      WHERE token = $1
+        AND (last_used_at IS NULL OR last_used_at < now() - interval '1 minute')
+      "#,
+      token,
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(())
+  }
+
+  /// Deletes one of the user's tokens. Returns whether it existed.
+  pub async fn delete_by_id_and_user_id<'c, E>(
+    executor: E,
+    id: &AccessTokenId,
+    user_id: &UserId,
+  ) -> Result<bool, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let result = sqlx::query!(
+      r#"
+      DELETE FROM app_access_tokens
+      WHERE id = $1 AND user_id = $2
+      "#,
+      id.into_inner(),
+      user_id.into_inner(),
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+  }
+
+  pub async fn delete_by_token<'c, E>(executor: E, token: &str) -> Result<(), sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    sqlx::query!(
+      r#"
+      DELETE FROM app_access_tokens
+      WHERE token = $1
+      "#,
+      token,
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(())
+  }
+}
@@ -0,0 +1,167 @@
+use chrono::{DateTime, Utc};
+use domain::{Debt, DebtId, Debtor};
+use sqlx::{Executor, Postgres};
+
+use crate::stores::models::debt::{DebtRow, DebtorRow};
+
+pub struct DebtStore;
+
+impl DebtStore {
+  /// Opens a debt for every member wallet below zero that has none, and
+  /// returns how many were opened.
+  pub async fn open_new<'c, E>(executor: E, at: DateTime<Utc>) -> Result<u64, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let result = sqlx::query!(
+      r#"
+      INSERT INTO debts (wallet_id, since)
+      SELECT id, $1
+      FROM wallets
+      WHERE owner_actor_id IS NOT NULL AND balance_cents < 0
+      ON CONFLICT (wallet_id) WHERE settled_at IS NULL DO NOTHING
+      "#,
+      at,
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(result.rows_affected())
+  }
+
+  /// Settles the open debts of wallets back at zero or above, and returns
+  /// how many were settled.
+  pub async fn settle_repaid<'c, E>(executor: E, at: DateTime<Utc>) -> Result<u64, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let result = sqlx::query!(
+      r#"
+      UPDATE debts d
+      SET settled_at = $1
+      FROM wallets w
+      WHERE w.id = d.wallet_id AND d.settled_at IS NULL AND w.balance_cents >= 0
+      "#,
+      at,
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(result.rows_affected())
+  }
+
+  /// Flags the open debts that are older than `cutoff`, and returns how many
+  /// were flagged.
+  pub async fn flag_overdue<'c, E>(
+    executor: E,
+    cutoff: DateTime<Utc>,
+    at: DateTime<Utc>,
+  ) -> Result<u64, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let result = sqlx::query!(
+      r#"
+      UPDATE debts
+      SET flagged_at = $2
+      WHERE settled_at IS NULL AND flagged_at IS NULL AND since < $1
+      "#,
+      cutoff,
+      at,
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(result.rows_affected())
+  }
+
+  /// Flagged open debts with fewer than `max_reminders` reminders, none of
+  /// them since `cutoff`.
+  pub async fn list_due_reminders<'c, E>(
+    executor: E,
+    cutoff: DateTime<Utc>,
+    max_reminders: i32,
+  ) -> Result<Vec<Debt>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let rows = sqlx::query_as!(
+      DebtRow,
+      r#"
+      SELECT id, wallet_id, since, flagged_at, reminders_sent, last_reminded_at, settled_at,
+             created_at, updated_at
+      FROM debts
+      WHERE settled_at IS NULL AND flagged_at IS NOT NULL
+        AND reminders_sent < $2
+        AND (last_reminded_at IS NULL OR last_reminded_at < $1)
+      ORDER BY since, id
+      "#,
+      cutoff,
+      max_reminders,
+    )
+    .fetch_all(executor)
+    .await?;
+
+    Ok(rows.into_iter().map(Into::into).collect())
+  }
+
+  /// Counts a reminder sent at `at`; `None` if the debt was settled or
+  /// reminded since it was listed.
+  pub async fn record_reminder<'c, E>(
+    executor: E,
+    id: &DebtId,
+    cutoff: DateTime<Utc>,
+    at: DateTime<Utc>,
+  ) -> Result<Option<Debt>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query_as!(
+      DebtRow,
+      r#"
+      UPDATE debts
+      SET reminders_sent = reminders_sent + 1, last_reminded_at = $3
+      WHERE id = $1 AND settled_at IS NULL
+        AND (last_reminded_at IS NULL OR last_reminded_at < $2)
+      RETURNING id, wallet_id, since, flagged_at, reminders_sent, last_reminded_at, settled_at,
+                created_at, updated_at
+      "#,
+      id.into_inner(),
+      cutoff,
+      at,
+    )
+    .fetch_optional(executor)
+    .await?;
+
+    Ok(row.map(Into::into))
+  }
+
+  /// Open debts, longest owed first, with the wallet's balance and owner.
+  pub async fn list_open_debtors<'c, E>(
+    executor: E,
+    flagged_only: bool,
+  ) -> Result<Vec<Debtor>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let rows = sqlx::query_as!(
+      DebtorRow,
+      r#"
+      SELECT d.id, d.wallet_id, d.since, d.flagged_at, d.reminders_sent, d.last_reminded_at,
+             d.settled_at, d.created_at, d.updated_at, w.balance_cents,
+             w.currency AS "currency: _", w.owner_actor_id AS "owner_actor_id!",
+             s.kind AS "kind?", s.display_name, s.email
+      FROM debts d
+      JOIN wallets w ON w.id = d.wallet_id
+      LEFT JOIN actor_summaries s ON s.actor_id = w.owner_actor_id
+      WHERE d.settled_at IS NULL AND (NOT $1 OR d.flagged_at IS NOT NULL)
+      ORDER BY d.since, d.id
+      "#,
+      flagged_only,
+    )
+    .fetch_all(executor)
+    .await?;
+
+    Ok(rows.into_iter().map(Into::into).collect())
+  }
+}
@@ -0,0 +1,221 @@
+use domain::{ActorId, DomainEvent, DomainEventId, DomainEventRecord, WalletId};
+use sqlx::{Executor, Postgres};
+use uuid::Uuid;
+
+use crate::stores::models::domain_event::{DomainEventRow, ScopedDomainEventRow};
+
+pub struct DomainEventStore;
+
+impl DomainEventStore {
+  pub async fn create<'c, E>(
+    executor: E,
+    event: &DomainEvent,
+  ) -> Result<DomainEventRecord, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query_as!(
+      DomainEventRow,
+      r#"
+      INSERT INTO domain_events (event_type, data)
+      VALUES ($1, $2)
+      RETURNING id, sequence, event_type, data, occurred_at, dispatched_at
+      "#,
+      event.name(),
+      event.data(),
+    )
+    .fetch_one(executor)
+    .await?;
+
+    row.try_into()
+  }
+
+  /// Oldest first. Locks the events until the end of the transaction and
+  /// skips those locked by others, so dispatchers on several replicas split
+  /// the work.
+  pub async fn lock_undispatched<'c, E>(
+    executor: E,
+    limit: i64,
+  ) -> Result<Vec<DomainEventRecord>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let rows = sqlx::query_as!(
+      DomainEventRow,
+      r#"
+      SELECT id, sequence, event_type, data, occurred_at, dispatched_at
+      FROM domain_events
+      WHERE dispatched_at IS NULL
+      ORDER BY sequence
+      LIMIT $1
+      FOR UPDATE SKIP LOCKED
+      "#,
+      limit,
+    )
+    .fetch_all(executor)
+    .await?;
+
+    rows.into_iter().map(TryInto::try_into).collect()
+  }
+
+  /// Events following the one with `sequence`, or from the start for 0,
+  /// dispatched or not. Ordered by the transaction that recorded them, then
+  /// sequence, and limited to transactions older than every one still
+  /// running: a running transaction may hold a lower sequence than events
+  /// already committed, but never an older transaction id, so what is
+  /// returned never gains a gap later.
+  /// Only events of `event_type` if given. Nothing is returned for a
+  /// `sequence` no event has, see [`Self::exists`].
+  pub async fn list_committed_after<'c, E>(
+    executor: E,
+    event_type: Option<&str>,
+    sequence: i64,
+    limit: i64,
+  ) -> Result<Vec<DomainEventRecord>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let rows = sqlx::query_as!(
+      DomainEventRow,
+      r#"
+      SELECT e.id, e.sequence, e.event_type, e.data, e.occurred_at, e.dispatched_at
+      FROM domain_events e
+      WHERE e.transaction_id < pg_snapshot_xmin(pg_current_snapshot())
+        AND ($3::text IS NULL OR e.event_type = $3)
+        AND ($1 = 0::bigint OR (e.transaction_id, e.sequence) > (
+          SELECT c.transaction_id, c.sequence FROM domain_events c WHERE c.sequence = $1
+        ))
+      ORDER BY e.transaction_id, e.sequence
+      LIMIT $2
+      "#,
+      sequence,
+      limit,
+      event_type,
+    )
+    .fetch_all(executor)
+    .await?;
+
+    rows.into_iter().map(TryInto::try_into).collect()
+  }
+
+  /// The next `limit` `transaction.created` events like
+  /// [`Self::list_committed_after`], each marked with whether it moves money
+  /// in or out of `wallet_id` or a wallet of `owner`. Either left out matches
+  /// every wallet.
+  pub async fn list_committed_transactions_after<'c, E>(
+    executor: E,
+    sequence: i64,
+    limit: i64,
+    wallet_id: Option<&WalletId>,
+    owner: Option<&ActorId>,
+  ) -> Result<Vec<(DomainEventRecord, bool)>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let rows = sqlx::query_as!(
+      ScopedDomainEventRow,
+      r#"
+      WITH page AS (
+        SELECT e.id, e.sequence, e.event_type, e.data, e.occurred_at, e.dispatched_at,
+          e.transaction_id,
+          (e.data->>'source_wallet_id')::uuid AS source_wallet_id,
+          (e.data->>'destination_wallet_id')::uuid AS destination_wallet_id
+        FROM domain_events e
+        WHERE e.transaction_id < pg_snapshot_xmin(pg_current_snapshot())
+          AND e.event_type = 'transaction.created'
+          AND ($1 = 0::bigint OR (e.transaction_id, e.sequence) > (
+            SELECT c.transaction_id, c.sequence FROM domain_events c WHERE c.sequence = $1
+          ))
+        ORDER BY e.transaction_id, e.sequence
+        LIMIT $2
+      )
+      SELECT p.id AS "id!", p.sequence AS "sequence!", p.event_type AS "event_type!",
+        p.data AS "data!", p.occurred_at AS "occurred_at!", p.dispatched_at,
+        (
+          ($3::uuid IS NULL OR $3 IN (p.source_wallet_id, p.destination_wallet_id))
+          AND ($4::uuid IS NULL OR EXISTS (
+            SELECT 1 FROM wallets w
+            WHERE w.id IN (p.source_wallet_id, p.destination_wallet_id)
+              AND w.owner_actor_id = $4
+          ))
+        ) AS "in_scope!"
+      FROM page p
+      ORDER BY p.transaction_id, p.sequence
+      "#,
+      sequence,
+      limit,
+      wallet_id.map(|id| id.into_inner()),
+      owner.map(|id| id.into_inner()),
+    )
+    .fetch_all(executor)
+    .await?;
+
+    rows
+      .into_iter()
+      .map(|row| {
+        let (row, in_scope) = row.split();
+        Ok((row.try_into()?, in_scope))
+      })
+      .collect()
+  }
+
+  /// Whether an event with `sequence` was recorded, so a cursor pointing
+  /// at it can be read from.
+  pub async fn exists<'c, E>(executor: E, sequence: i64) -> Result<bool, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let exists = sqlx::query_scalar!(
+      r#"SELECT EXISTS(SELECT 1 FROM domain_events WHERE sequence = $1) AS "exists!""#,
+      sequence,
+    )
+    .fetch_one(executor)
+    .await?;
+
+    Ok(exists)
+  }
+
+  /// Sequence of the last event [`Self::list_committed_after`] returns when
+  /// read to the end, 0 without any: a cursor for reading only what follows.
+  pub async fn latest_committed_sequence<'c, E>(executor: E) -> Result<i64, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let sequence = sqlx::query_scalar!(
+      r#"
+      SELECT sequence
+      FROM domain_events
+      WHERE transaction_id < pg_snapshot_xmin(pg_current_snapshot())
+      ORDER BY transaction_id DESC, sequence DESC
+      LIMIT 1
+      "#,
+    )
+    .fetch_optional(executor)
+    .await?;
+
+    Ok(sequence.unwrap_or(0))
+  }
+
+  pub async fn mark_dispatched<'c, E>(
+    executor: E,
+    ids: &[DomainEventId],
+  ) -> Result<u64, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let ids: Vec<Uuid> = ids.iter().map(|id| id.into_inner()).collect();
+
+    let result = sqlx::query!(
+      r#"
+      UPDATE domain_events
+      SET dispatched_at = now()
+      WHERE id = ANY($1)
+      "#,
+      &ids,
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(result.rows_affected())
+  }
+}
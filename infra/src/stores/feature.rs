@@ -0,0 +1,62 @@
+use sqlx::{Executor, Postgres};
+
+use crate::stores::models::feature::{FeatureSwitchRow, FeatureSwitchUpdate};
+use domain::FeatureSwitch;
+
+pub struct FeatureSwitchStore;
+
+impl FeatureSwitchStore {
+  /// Features switched so far. Switches of features this version doesn't
+  /// know are left out.
+  pub async fn list<'c, E>(executor: E) -> Result<Vec<FeatureSwitch>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let rows = sqlx::query_as!(
+      FeatureSwitchRow,
+      r#"
+      SELECT feature, enabled, reason, changed_by_actor_id, created_at, updated_at
+      FROM feature_switches
+      ORDER BY feature
+      "#
+    )
+    .fetch_all(executor)
+    .await?;
+
+    Ok(
+      rows
+        .into_iter()
+        .filter_map(|row| row.try_into().ok())
+        .collect(),
+    )
+  }
+
+  pub async fn upsert<'c, E>(
+    executor: E,
+    update: &FeatureSwitchUpdate,
+  ) -> Result<FeatureSwitch, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query_as!(
+      FeatureSwitchRow,
+      r#"
+      INSERT INTO feature_switches (feature, enabled, reason, changed_by_actor_id)
+      VALUES ($1, $2, $3, $4)
+      ON CONFLICT (feature) DO UPDATE
+      SET enabled = EXCLUDED.enabled,
+          reason = EXCLUDED.reason,
+          changed_by_actor_id = EXCLUDED.changed_by_actor_id
+      RETURNING feature, enabled, reason, changed_by_actor_id, created_at, updated_at
+      "#,
+      update.feature.to_string(),
+      update.enabled,
+      update.reason,
+      update.changed_by.map(|id| id.into_inner()),
+    )
+    .fetch_one(executor)
+    .await?;
+
+    row.try_into()
+  }
+}
@@ -1,7 +1,14 @@
+use chrono::{DateTime, Utc};
 use sqlx::{Executor, Postgres};
 
-use crate::stores::models::guest::{GuestCreation, GuestRow, GuestUpdate};
-use domain::{guest::GuestId, ActorId, Guest};
+use crate::stores::models::guest::{
+  GuestClaimCreation, GuestClaimRow, GuestCreation, GuestFilter, GuestRow, GuestUpdate,
+};
+use domain::{
+  guest::GuestId,
+  types::{ListVersion, PageRequest},
+  ActorId, Currency, Guest, GuestClaim,
+};
 
 pub struct GuestStore;
 
@@ -15,10 +22,10 @@ impl GuestStore {
       r#"
       INSERT INTO guests (actor_id, email, verified)
       VALUES ($1, $2, $3)
-      RETURNING id, actor_id, email, verified, created_at, updated_at
+      RETURNING id, actor_id, email, verified, identifier, created_at, updated_at
       "#,
       creation.actor_id.into_inner(),
-      creation.email.expose(),
+      creation.email.as_ref().map(|e| e.expose()),
       creation.verified,
     )
     .fetch_one(executor)
@@ -42,7 +49,7 @@ impl GuestStore {
       SET email = COALESCE($2, email),
           verified = COALESCE($3, verified)
       WHERE id = $1
-      RETURNING id, actor_id, email, verified, created_at, updated_at
+      RETURNING id, actor_id, email, verified, identifier, created_at, updated_at
       "#,
       id.into_inner(),
       update.email.as_ref().map(|e| e.expose()),
@@ -54,6 +61,55 @@ impl GuestStore {
     Ok(row.into())
   }
 
+  /// Binds `identifier` to the guest, replacing any previous one. `None`
+  /// unbinds the current identifier.
+  pub async fn set_identifier_by_id<'c, E>(
+    executor: E,
+    id: &GuestId,
+    identifier: Option<&str>,
+  ) -> Result<Option<Guest>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query_as!(
+      GuestRow,
+      r#"
+      UPDATE guests
+      SET identifier = $2
+      WHERE id = $1
+      RETURNING id, actor_id, email, verified, identifier, created_at, updated_at
+      "#,
+      id.into_inner(),
+      identifier,
+    )
+    .fetch_optional(executor)
+    .await?;
+
+    Ok(row.map(Into::into))
+  }
+
+  pub async fn find_by_identifier<'c, E>(
+    executor: E,
+    identifier: &str,
+  ) -> Result<Option<Guest>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query_as!(
+      GuestRow,
+      r#"
+      SELECT id, actor_id, email, verified, identifier, created_at, updated_at
+      FROM guests
+      WHERE identifier = $1
+      "#,
+      identifier,
+    )
+    .fetch_optional(executor)
+    .await?;
+
+    Ok(row.map(Into::into))
+  }
+
   pub async fn find_by_id<'c, E>(executor: E, id: &GuestId) -> Result<Option<Guest>, sqlx::Error>
   where
     E: Executor<'c, Database = Postgres>,
@@ -61,7 +117,7 @@ impl GuestStore {
     let row = sqlx::query_as!(
       GuestRow,
       r#"
-      SELECT id, actor_id, email, verified, created_at, updated_at
+      SELECT id, actor_id, email, verified, identifier, created_at, updated_at
       FROM guests
       WHERE id = $1
       "#,
@@ -73,6 +129,30 @@ impl GuestStore {
     Ok(row.map(Into::into))
   }
 
+  /// Locks the guest until the end of the transaction.
+  pub async fn find_by_id_for_update<'c, E>(
+    executor: E,
+    id: &GuestId,
+  ) -> Result<Option<Guest>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query_as!(
+      GuestRow,
+      r#"
+      SELECT id, actor_id, email, verified, identifier, created_at, updated_at
+      FROM guests
+      WHERE id = $1
+      FOR UPDATE
+      "#,
+      id.into_inner(),
+    )
+    .fetch_optional(executor)
+    .await?;
+
+    Ok(row.map(Into::into))
+  }
+
   pub async fn find_by_actor_id<'c, E>(
     executor: E,
     actor_id: &ActorId,
@@ -83,7 +163,7 @@ impl GuestStore {
     let row = sqlx::query_as!(
       GuestRow,
       r#"
-      SELECT id, actor_id, email, verified, created_at, updated_at
+      SELECT id, actor_id, email, verified, identifier, created_at, updated_at
       FROM guests
       WHERE actor_id = $1
       "#,
@@ -95,20 +175,209 @@ impl GuestStore {
     Ok(row.map(Into::into))
   }
 
-  pub async fn list_all<'c, E>(executor: E) -> Result<Vec<Guest>, sqlx::Error>
+  /// Oldest first. Balances are those of the guests' wallets.
+  pub async fn list_paginated<'c, E>(
+    executor: E,
+    filter: &GuestFilter,
+    page: &PageRequest,
+  ) -> Result<Vec<Guest>, sqlx::Error>
   where
     E: Executor<'c, Database = Postgres>,
   {
     let rows = sqlx::query_as!(
       GuestRow,
       r#"
-      SELECT id, actor_id, email, verified, created_at, updated_at
-      FROM guests
-      "#
+      SELECT g.id, g.actor_id, g.email, g.verified, g.identifier, g.created_at, g.updated_at
+      FROM guests g
+      CROSS JOIN LATERAL (
+        SELECT SUM(w.balance_cents) FILTER (WHERE w.currency = $6) AS balance_cents
+        FROM wallets w
+        WHERE w.owner_actor_id = g.actor_id
+      ) b
+      WHERE guest_listed(g, b.balance_cents, $1, $2, $3)
+      ORDER BY g.created_at, g.id
+      LIMIT $4 OFFSET $5
+      "#,
+      filter.card_bound,
+      filter.min_balance.map(|m| i64::from(m.as_minor())),
+      filter.max_balance.map(|m| i64::from(m.as_minor())),
+      page.limit(),
+      page.offset(),
+      filter.currency as Currency,
     )
     .fetch_all(executor)
     .await?;
 
     Ok(rows.into_iter().map(Into::into).collect())
   }
+
+  pub async fn count<'c, E>(executor: E, filter: &GuestFilter) -> Result<i64, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let count = sqlx::query_scalar!(
+      r#"
+      SELECT COUNT(*) AS "count!"
+      FROM guests g
+      CROSS JOIN LATERAL (
+        SELECT SUM(w.balance_cents) FILTER (WHERE w.currency = $4) AS balance_cents
+        FROM wallets w
+        WHERE w.owner_actor_id = g.actor_id
+      ) b
+      WHERE guest_listed(g, b.balance_cents, $1, $2, $3)
+      "#,
+      filter.card_bound,
+      filter.min_balance.map(|m| i64::from(m.as_minor())),
+      filter.max_balance.map(|m| i64::from(m.as_minor())),
+      filter.currency as Currency,
+    )
+    .fetch_one(executor)
+    .await?;
+
+    Ok(count)
+  }
+
+  /// Version of the guests matching `filter`, whatever the page. Counts
+  /// changes to their wallets too, as those move the balances listed.
+  pub async fn version<'c, E>(executor: E, filter: &GuestFilter) -> Result<ListVersion, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query!(
+      r#"
+      SELECT
+        COUNT(*) AS "count!",
+        MAX(GREATEST(COALESCE(g.updated_at, g.created_at), b.changed_at)) AS changed_at
+      FROM guests g
+      CROSS JOIN LATERAL (
+        SELECT
+          SUM(w.balance_cents) FILTER (WHERE w.currency = $4) AS balance_cents,
+          MAX(COALESCE(w.updated_at, w.created_at)) AS changed_at
+        FROM wallets w
+        WHERE w.owner_actor_id = g.actor_id
+      ) b
+      WHERE guest_listed(g, b.balance_cents, $1, $2, $3)
+      "#,
+      filter.card_bound,
+      filter.min_balance.map(|m| i64::from(m.as_minor())),
+      filter.max_balance.map(|m| i64::from(m.as_minor())),
+      filter.currency as Currency,
+    )
+    .fetch_one(executor)
+    .await?;
+
+    Ok(ListVersion {
+      count: row.count,
+      changed_at: row.changed_at,
+    })
+  }
+
+  /// Removes email and identifier of guests neither changed nor paying or
+  /// paid since `cutoff`, keeping their wallet and its history. Guests whose
+  /// wallet is under legal hold are left alone. Returns how many.
+  pub async fn anonymize_inactive_since<'c, E>(
+    executor: E,
+    cutoff: DateTime<Utc>,
+  ) -> Result<u64, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let result = sqlx::query!(
+      r#"
+      UPDATE guests g
+      SET email = NULL, identifier = NULL, verified = false
+      WHERE (g.email IS NOT NULL OR g.identifier IS NOT NULL)
+        AND COALESCE(g.updated_at, g.created_at) < $1
+        AND NOT EXISTS (
+          SELECT 1
+          FROM wallets w
+          WHERE w.owner_actor_id = g.actor_id
+            AND (w.legal_hold OR EXISTS (
+              SELECT 1
+              FROM transactions t
+              WHERE (t.source_wallet_id = w.id OR t.destination_wallet_id = w.id)
+                AND t.created_at >= $1
+            ))
+        )
+      "#,
+      cutoff,
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(result.rows_affected())
+  }
+
+  /// Removes email and identifier so the guest can no longer be looked up or
+  /// pay, keeping its wallet and history.
+  pub async fn retire_by_id<'c, E>(executor: E, id: &GuestId) -> Result<Option<Guest>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query_as!(
+      GuestRow,
+      r#"
+      UPDATE guests
+      SET email = NULL, identifier = NULL, verified = false
+      WHERE id = $1
+      RETURNING id, actor_id, email, verified, identifier, created_at, updated_at
+      "#,
+      id.into_inner(),
+    )
+    .fetch_optional(executor)
+    .await?;
+
+    Ok(row.map(Into::into))
+  }
+}
+
+pub struct GuestClaimStore;
+
+impl GuestClaimStore {
+  pub async fn create<'c, E>(
+    executor: E,
+    creation: &GuestClaimCreation,
+  ) -> Result<GuestClaim, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query_as!(
+      GuestClaimRow,
+      r#"
+      INSERT INTO guest_claims (guest_id, user_id, amount_cents, transaction_id)
+      VALUES ($1, $2, $3, $4)
+      RETURNING id, guest_id, user_id, amount_cents, transaction_id, created_at, updated_at
+      "#,
+      creation.guest_id.into_inner(),
+      creation.user_id.into_inner(),
+      creation.amount.as_minor(),
+      creation.transaction_id.map(|id| id.into_inner()),
+    )
+    .fetch_one(executor)
+    .await?;
+
+    Ok(row.into())
+  }
+
+  pub async fn find_by_guest_id<'c, E>(
+    executor: E,
+    guest_id: &GuestId,
+  ) -> Result<Option<GuestClaim>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query_as!(
+      GuestClaimRow,
+      r#"
+      SELECT id, guest_id, user_id, amount_cents, transaction_id, created_at, updated_at
+      FROM guest_claims
+      WHERE guest_id = $1
+      "#,
+      guest_id.into_inner(),
+    )
+    .fetch_optional(executor)
+    .await?;
+
+    Ok(row.map(Into::into))
+  }
 }
@@ -0,0 +1,28 @@
+use sqlx::{Executor, Postgres};
+
+/// Probes for readiness checks.
+pub struct HealthStore;
+
+impl HealthStore {
+  /// Round trip to the database.
+  pub async fn ping<'c, E>(executor: E) -> Result<(), sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    sqlx::query_scalar!("SELECT 1").fetch_one(executor).await?;
+
+    Ok(())
+  }
+
+  /// Newest migration successfully applied, if any.
+  pub async fn latest_migration<'c, E>(executor: E) -> Result<Option<i64>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    // Not checked at compile time: the table belongs to the migrator and
+    // need not exist in the database the queries are checked against
+    sqlx::query_scalar("SELECT MAX(version) FROM _sqlx_migrations WHERE success")
+      .fetch_one(executor)
+      .await
+  }
+}
@@ -1,7 +1,14 @@
-use domain::{Email, Invite, InviteId};
+use chrono::{DateTime, Duration, Utc};
+use domain::{
+  types::{ListVersion, PageRequest},
+  Email, Invite, InviteDomainRule, InviteId,
+};
 use sqlx::{Executor, Postgres};
 
-use crate::stores::models::invite::{InviteCreation, InviteRow, InviteUpdate};
+use crate::stores::{
+  models::invite::{InviteCreation, InviteDomainRuleRow, InviteFilter, InviteRow, InviteUpdate},
+  prefix_pattern,
+};
 
 pub struct InviteStore;
 
@@ -13,14 +20,15 @@ impl InviteStore {
     let row = sqlx::query_as!(
       InviteRow,
       r#"
-      INSERT INTO invites (invitor_user_id, email, token, role, expires_at)
-      VALUES ($1, $2, $3, $4, $5)
-      RETURNING id, invitor_user_id, email, token, role, status, expires_at, created_at, updated_at
+      INSERT INTO invites (invitor_user_id, email, token, role, locale, expires_at)
+      VALUES ($1, $2, $3, $4, $5, $6)
+      RETURNING id, invitor_user_id, email, token, role, locale, status, expires_at, created_at, updated_at
       "#,
       creation.invitor.into_inner(),
       creation.email.expose(),
       creation.token,
       creation.role.to_string(),
+      creation.locale.code(),
       chrono::Utc::now() + creation.expires_in,
     )
     .fetch_one(executor)
@@ -43,7 +51,7 @@ impl InviteStore {
       UPDATE invites
       SET status = COALESCE($2, status)
       WHERE id = $1
-      RETURNING id, invitor_user_id, email, token, role, status, expires_at, created_at, updated_at
+      RETURNING id, invitor_user_id, email, token, role, locale, status, expires_at, created_at, updated_at
       "#,
       id.into_inner(),
       update.status.as_ref().map(ToString::to_string)
@@ -54,6 +62,35 @@ impl InviteStore {
     Ok(row.map(Into::into))
   }
 
+  /// Replaces the token and restarts the expiry window from now.
+  pub async fn rotate_token_by_id<'c, E>(
+    executor: E,
+    id: &InviteId,
+    token: &str,
+    expires_in: Duration,
+  ) -> Result<Option<Invite>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query_as!(
+      InviteRow,
+      r#"
+      UPDATE invites
+      SET token = $2,
+          expires_at = $3
+      WHERE id = $1
+      RETURNING id, invitor_user_id, email, token, role, locale, status, expires_at, created_at, updated_at
+      "#,
+      id.into_inner(),
+      token,
+      chrono::Utc::now() + expires_in,
+    )
+    .fetch_optional(executor)
+    .await?;
+
+    Ok(row.map(Into::into))
+  }
+
   pub async fn delete_by_id<'c, E>(executor: E, id: &InviteId) -> Result<(), sqlx::Error>
   where
     E: Executor<'c, Database = Postgres>,
@@ -71,6 +108,46 @@ impl InviteStore {
     Ok(())
   }
 
+  /// Deletes invites that expired before `cutoff`. Returns how many.
+  pub async fn delete_expired_before<'c, E>(
+    executor: E,
+    cutoff: DateTime<Utc>,
+  ) -> Result<u64, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let result = sqlx::query!(
+      r#"
+      DELETE FROM invites
+      WHERE expires_at < $1
+      "#,
+      cutoff,
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(result.rows_affected())
+  }
+
+  pub async fn find_by_id<'c, E>(executor: E, id: &InviteId) -> Result<Option<Invite>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query_as!(
+      InviteRow,
+      r#"
+      SELECT id, invitor_user_id, email, token, role, locale, status, expires_at, created_at, updated_at
+      FROM invites
+      WHERE id = $1
+      "#,
+      id.into_inner(),
+    )
+    .fetch_optional(executor)
+    .await?;
+
+    Ok(row.map(Into::into))
+  }
+
   pub async fn find_by_token<'c, E>(executor: E, token: &str) -> Result<Option<Invite>, sqlx::Error>
   where
     E: Executor<'c, Database = Postgres>,
@@ -78,9 +155,32 @@ impl InviteStore {
     let row = sqlx::query_as!(
       InviteRow,
       r#"
-      SELECT id, invitor_user_id, email, token, role, status, expires_at, created_at, updated_at
+      SELECT id, invitor_user_id, email, token, role, locale, status, expires_at, created_at, updated_at
+      FROM invites
+      WHERE token = $1
+      "#,
+      token,
+    )
+    .fetch_optional(executor)
+    .await?;
+
+    Ok(row.map(Into::into))
+  }
+
+  pub async fn find_by_token_for_update<'c, E>(
+    executor: E,
+    token: &str,
+  ) -> Result<Option<Invite>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query_as!(
+      InviteRow,
+      r#"
+      SELECT id, invitor_user_id, email, token, role, locale, status, expires_at, created_at, updated_at
       FROM invites
       WHERE token = $1
+      FOR UPDATE
       "#,
       token,
     )
@@ -100,7 +200,7 @@ impl InviteStore {
     let row = sqlx::query_as!(
       InviteRow,
       r#"
-      SELECT id, invitor_user_id, email, token, role, status, expires_at, created_at, updated_at
+      SELECT id, invitor_user_id, email, token, role, locale, status, expires_at, created_at, updated_at
       FROM invites
       WHERE email = $1
       "#,
@@ -112,15 +212,103 @@ impl InviteStore {
     Ok(row.map(Into::into))
   }
 
-  pub async fn list_all<'c, E>(executor: E) -> Result<Vec<Invite>, sqlx::Error>
+  /// Newest first.
+  pub async fn list_paginated<'c, E>(
+    executor: E,
+    filter: &InviteFilter,
+    page: &PageRequest,
+  ) -> Result<Vec<Invite>, sqlx::Error>
   where
     E: Executor<'c, Database = Postgres>,
   {
     let rows = sqlx::query_as!(
       InviteRow,
       r#"
-      SELECT id, invitor_user_id, email, token, role, status, expires_at, created_at, updated_at
-      FROM invites
+      SELECT id, invitor_user_id, email, token, role, locale, status, expires_at, created_at, updated_at
+      FROM invites i
+      WHERE invite_listed(i, $1, $2, $3, $4, $5)
+      ORDER BY created_at DESC, id DESC
+      LIMIT $6 OFFSET $7
+      "#,
+      filter.status.as_ref().map(ToString::to_string),
+      filter.email_prefix.as_deref().map(prefix_pattern),
+      filter.invitor.map(|id| id.into_inner()),
+      filter.created_from,
+      filter.created_to,
+      page.limit(),
+      page.offset(),
+    )
+    .fetch_all(executor)
+    .await?;
+
+    Ok(rows.into_iter().map(Into::into).collect())
+  }
+
+  pub async fn count<'c, E>(executor: E, filter: &InviteFilter) -> Result<i64, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let count = sqlx::query_scalar!(
+      r#"
+      SELECT COUNT(*) AS "count!"
+      FROM invites i
+      WHERE invite_listed(i, $1, $2, $3, $4, $5)
+      "#,
+      filter.status.as_ref().map(ToString::to_string),
+      filter.email_prefix.as_deref().map(prefix_pattern),
+      filter.invitor.map(|id| id.into_inner()),
+      filter.created_from,
+      filter.created_to,
+    )
+    .fetch_one(executor)
+    .await?;
+
+    Ok(count)
+  }
+
+  /// Version of the invites matching `filter`, whatever the page.
+  pub async fn version<'c, E>(
+    executor: E,
+    filter: &InviteFilter,
+  ) -> Result<ListVersion, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query!(
+      r#"
+      SELECT COUNT(*) AS "count!", MAX(COALESCE(updated_at, created_at)) AS changed_at
+      FROM invites i
+      WHERE invite_listed(i, $1, $2, $3, $4, $5)
+      "#,
+      filter.status.as_ref().map(ToString::to_string),
+      filter.email_prefix.as_deref().map(prefix_pattern),
+      filter.invitor.map(|id| id.into_inner()),
+      filter.created_from,
+      filter.created_to,
+    )
+    .fetch_one(executor)
+    .await?;
+
+    Ok(ListVersion {
+      count: row.count,
+      changed_at: row.changed_at,
+    })
+  }
+}
+
+pub struct InviteDomainRuleStore;
+
+impl InviteDomainRuleStore {
+  pub async fn list<'c, E>(executor: E) -> Result<Vec<InviteDomainRule>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let rows = sqlx::query_as!(
+      InviteDomainRuleRow,
+      r#"
+      SELECT domain, allowed, role AS "role: _"
+      FROM invite_domain_rules
+      ORDER BY domain, role NULLS FIRST
       "#
     )
     .fetch_all(executor)
@@ -128,4 +316,38 @@ impl InviteStore {
 
     Ok(rows.into_iter().map(Into::into).collect())
   }
+
+  pub async fn create<'c, E>(executor: E, rule: &InviteDomainRule) -> Result<(), sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    sqlx::query!(
+      r#"
+      INSERT INTO invite_domain_rules (domain, allowed, role)
+      VALUES ($1, $2, $3)
+      "#,
+      rule.domain,
+      rule.allowed,
+      rule.role.map(|role| role.to_string()),
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(())
+  }
+
+  pub async fn delete_all<'c, E>(executor: E) -> Result<(), sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    sqlx::query!(
+      r#"
+      DELETE FROM invite_domain_rules
+      "#
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(())
+  }
 }
@@ -0,0 +1,158 @@
+use chrono::{DateTime, Duration, Utc};
+use domain::{Job, JobId};
+use sqlx::{Executor, Postgres};
+
+use crate::stores::models::job::{JobCreation, JobRow};
+
+pub struct JobStore;
+
+impl JobStore {
+  /// Queues the job to run right away. Enqueue within the transaction making
+  /// the change the job follows up on, so it only runs once that committed.
+  pub async fn create<'c, E>(executor: E, creation: &JobCreation) -> Result<Job, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query_as!(
+      JobRow,
+      r#"
+      INSERT INTO jobs (task, max_attempts)
+      VALUES ($1, $2)
+      RETURNING id, task, status, attempts, max_attempts, run_at, last_error, created_at, updated_at
+      "#,
+      serde_json::to_value(&creation.task).expect("job task serializes"),
+      creation.max_attempts,
+    )
+    .fetch_one(executor)
+    .await?;
+
+    row.try_into()
+  }
+
+  /// Marks the job due longest as running for at most `lease` and counts the
+  /// attempt. Running jobs whose lease ran out, e.g. as their worker died,
+  /// are claimed again while they have attempts left; see
+  /// [`Self::fail_exhausted`] for the others. Other workers skip the rows
+  /// locked by this one.
+  pub async fn claim_due<'c, E>(executor: E, lease: Duration) -> Result<Option<Job>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query_as!(
+      JobRow,
+      r#"
+      UPDATE jobs
+      SET status = 'running',
+          attempts = attempts + 1,
+          locked_until = now() + make_interval(secs => $1)
+      WHERE id = (
+        SELECT id
+        FROM jobs
+        WHERE ((status = 'pending' AND run_at <= now())
+           OR (status = 'running' AND locked_until < now()))
+          AND attempts < max_attempts
+        ORDER BY run_at, id
+        LIMIT 1
+        FOR UPDATE SKIP LOCKED
+      )
+      RETURNING id, task, status, attempts, max_attempts, run_at, last_error, created_at, updated_at
+      "#,
+      lease.num_milliseconds() as f64 / 1000.0,
+    )
+    .fetch_optional(executor)
+    .await?;
+
+    row.map(TryInto::try_into).transpose()
+  }
+
+  /// Fails the running jobs whose lease ran out on their last attempt, so
+  /// they don't stay running forever, and returns how many there were.
+  pub async fn fail_exhausted<'c, E>(executor: E) -> Result<u64, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let result = sqlx::query!(
+      r#"
+      UPDATE jobs
+      SET status = 'failed',
+          locked_until = NULL,
+          last_error = 'Lease ran out on the last attempt'
+      WHERE status = 'running' AND locked_until < now() AND attempts >= max_attempts
+      "#,
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(result.rows_affected())
+  }
+
+  pub async fn succeed<'c, E>(executor: E, id: &JobId) -> Result<(), sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    sqlx::query!(
+      r#"
+      UPDATE jobs
+      SET status = 'succeeded', locked_until = NULL, last_error = NULL
+      WHERE id = $1
+      "#,
+      id.into_inner(),
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(())
+  }
+
+  /// Records a failed attempt: the job is due again at `retry_at`, or failed
+  /// for good without one.
+  pub async fn fail<'c, E>(
+    executor: E,
+    id: &JobId,
+    error: &str,
+    retry_at: Option<DateTime<Utc>>,
+  ) -> Result<(), sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    sqlx::query!(
+      r#"
+      UPDATE jobs
+      SET status = CASE WHEN $3::timestamptz IS NULL THEN 'failed' ELSE 'pending' END,
+          run_at = COALESCE($3, run_at),
+          locked_until = NULL,
+          last_error = $2
+      WHERE id = $1
+      "#,
+      id.into_inner(),
+      error,
+      retry_at,
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(())
+  }
+
+  /// Deletes jobs that succeeded before `cutoff`. Failed ones stay for
+  /// inspection.
+  pub async fn delete_succeeded_before<'c, E>(
+    executor: E,
+    cutoff: DateTime<Utc>,
+  ) -> Result<u64, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let result = sqlx::query!(
+      r#"
+      DELETE FROM jobs
+      WHERE status = 'succeeded' AND updated_at < $1
+      "#,
+      cutoff,
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(result.rows_affected())
+  }
+}
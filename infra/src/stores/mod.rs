@@ -1,18 +1,101 @@
+use domain::types::Money;
+
 pub mod actor;
+pub mod audit;
+pub mod balance_alert;
+pub mod budget;
+pub mod chargeback;
+pub mod client_app;
+pub mod debt;
+pub mod domain_event;
+pub mod feature;
 pub mod guest;
+pub mod health;
 pub mod invite;
+pub mod job;
 pub mod models;
+pub mod note;
+pub mod notification;
+pub mod order;
+pub mod outbound_email;
+pub mod owner_transfer;
+pub mod payout;
+pub mod risk;
+pub mod scheduled_job;
 pub mod session;
+pub mod settings;
 pub mod shop;
+pub mod top_up;
 pub mod transaction;
 pub mod user;
 pub mod wallet;
+pub mod webhook;
 
-pub use actor::ActorStore;
-pub use guest::GuestStore;
-pub use invite::InviteStore;
-pub use session::SessionStore;
-pub use shop::{ShopMemberStore, ShopOfferingStore, ShopStore};
+pub use actor::{ActorStore, ActorSummaryOutboxStore, ActorSummaryStore};
+pub use audit::AuditStore;
+pub use balance_alert::BalanceAlertStore;
+pub use budget::BudgetEnvelopeStore;
+pub use chargeback::ChargebackStore;
+pub use client_app::{AccessTokenStore, AuthorizationCodeStore, ClientAppStore};
+pub use debt::DebtStore;
+pub use domain_event::DomainEventStore;
+pub use feature::FeatureSwitchStore;
+pub use guest::{GuestClaimStore, GuestStore};
+pub use health::HealthStore;
+pub use invite::{InviteDomainRuleStore, InviteStore};
+pub use job::JobStore;
+pub use note::AccountNoteStore;
+pub use notification::NotificationStore;
+pub use order::{OrderItemStore, OrderStore, SalesReportStore};
+pub use outbound_email::OutboundEmailStore;
+pub use owner_transfer::OwnerTransferStore;
+pub use payout::{BankAccountStore, PayoutBatchStore, PayoutStore};
+pub use risk::{ReviewItemStore, WalletSecurityEventStore};
+pub use scheduled_job::ScheduledJobStore;
+pub use session::{
+  LoginAttemptStore, LoginLockoutStore, PasswordConfirmationStore, SessionRevocationStore,
+  SessionStore,
+};
+pub use settings::SettingsStore;
+pub use shop::{FavoriteOfferingStore, ShopMemberStore, ShopOfferingStore, ShopStore};
+pub use top_up::TopUpStore;
 pub use transaction::TransactionStore;
-pub use user::UserStore;
-pub use wallet::WalletStore;
+pub use user::{EmailChangeStore, PasswordSetupStore, UserPermissionStore, UserStore};
+pub use wallet::{
+  is_currency_mismatch_violation, is_legal_hold_violation, is_wallet_frozen_violation,
+  WalletLegalHoldStore, WalletStore,
+};
+pub use webhook::{WebhookDeliveryStore, WebhookStore};
+
+/// Narrows a summed amount back to [`Money`], failing like a decode error if
+/// it doesn't fit.
+pub(crate) fn money_from_sum(sum: Option<i64>, column: &str) -> Result<Money, sqlx::Error> {
+  let sum = sum.unwrap_or_default();
+  let cents = i32::try_from(sum).map_err(|_| sqlx::Error::ColumnDecode {
+    index: column.to_string(),
+    source: Box::new(std::io::Error::new(
+      std::io::ErrorKind::InvalidData,
+      format!("Balance overflow: {} cents exceeds i32 range", sum),
+    )),
+  })?;
+
+  Ok(Money::from_minor(cents))
+}
+
+/// Builds an `ILIKE` pattern matching `term` anywhere, escaping wildcards in
+/// the user supplied term.
+pub(crate) fn contains_pattern(term: &str) -> String {
+  format!("%{}%", escape_like(term))
+}
+
+/// Builds an `ILIKE` pattern matching values starting with `term`.
+pub(crate) fn prefix_pattern(term: &str) -> String {
+  format!("{}%", escape_like(term))
+}
+
+fn escape_like(term: &str) -> String {
+  term
+    .replace('\\', "\\\\")
+    .replace('%', "\\%")
+    .replace('_', "\\_")
+}
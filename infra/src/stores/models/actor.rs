@@ -1,10 +1,75 @@
 use chrono::{DateTime, Utc};
+use domain::{ActorKind, ActorSummary, Currency, DuplicateCandidate, DuplicateReason};
 use sqlx::prelude::FromRow;
 use uuid::Uuid;
 
 #[derive(Clone, FromRow)]
-pub(crate) struct ActorRow {
-  pub id: Uuid,
+pub(crate) struct ActorSummaryRow {
+  pub actor_id: Uuid,
+  pub kind: String,
+  pub user_id: Option<Uuid>,
+  pub guest_id: Option<Uuid>,
+  pub display_name: Option<String>,
+  pub email: Option<String>,
+  pub identifier: Option<String>,
+  pub role: Option<String>,
+  pub wallet_count: i32,
+  pub currency: Option<Currency>,
+  pub balance_cents: i64,
   pub created_at: DateTime<Utc>,
   pub updated_at: Option<DateTime<Utc>>,
 }
+
+#[derive(Clone, FromRow)]
+pub(crate) struct DuplicateCandidateRow {
+  pub actor_id: Uuid,
+  pub kind: String,
+  pub user_id: Option<Uuid>,
+  pub guest_id: Option<Uuid>,
+  pub display_name: Option<String>,
+  pub reason: String,
+}
+
+#[derive(Clone, Default)]
+pub struct ActorSummaryFilter {
+  pub kind: Option<ActorKind>,
+  /// Case-insensitive substring matched against name, email and identifier
+  pub search: Option<String>,
+}
+
+impl From<ActorSummaryRow> for ActorSummary {
+  fn from(value: ActorSummaryRow) -> Self {
+    Self {
+      actor_id: value.actor_id.into(),
+      kind: ActorKind::from(value.kind.as_str()),
+      user_id: value.user_id.map(Into::into),
+      guest_id: value.guest_id.map(Into::into),
+      display_name: value.display_name,
+      email: value.email.map(Into::into),
+      identifier: value.identifier,
+      role: value.role.map(Into::into),
+      wallet_count: value.wallet_count,
+      currency: value.currency,
+      balance_cents: value.balance_cents,
+      created_at: value.created_at,
+      updated_at: value.updated_at,
+    }
+  }
+}
+
+impl From<DuplicateCandidateRow> for DuplicateCandidate {
+  fn from(value: DuplicateCandidateRow) -> Self {
+    Self {
+      actor_id: value.actor_id.into(),
+      kind: ActorKind::from(value.kind.as_str()),
+      user_id: value.user_id.map(Into::into),
+      guest_id: value.guest_id.map(Into::into),
+      display_name: value.display_name,
+      reason: match value.reason.as_str() {
+        "same_email" => DuplicateReason::SameEmail,
+        "similar_email" => DuplicateReason::SimilarEmail,
+        _ => DuplicateReason::SimilarName,
+      },
+    }
+  }
+}
@@ -0,0 +1,59 @@
+use chrono::{DateTime, Utc};
+use domain::{ActorId, AuditEntry};
+use serde_json::Value;
+use sqlx::prelude::FromRow;
+use uuid::Uuid;
+
+#[derive(Clone, FromRow)]
+pub(crate) struct AuditEntryRow {
+  pub id: Uuid,
+  pub seq: i64,
+  pub actor_id: Option<Uuid>,
+  pub action: String,
+  pub target: Option<String>,
+  pub ip_address: Option<String>,
+  pub details: Option<Value>,
+  pub personal_data: Option<Value>,
+  pub previous_hash: Option<String>,
+  pub hash: String,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone, Default)]
+pub struct AuditEntryCreation {
+  pub actor_id: Option<ActorId>,
+  pub action: String,
+  pub target: Option<String>,
+  pub ip_address: Option<String>,
+  pub details: Option<Value>,
+  /// Kept beside the entry, outside its hash, so it can be erased
+  pub personal_data: Option<Value>,
+}
+
+#[derive(Clone, Default)]
+pub struct AuditFilter {
+  pub actor_id: Option<ActorId>,
+  pub action: Option<String>,
+  pub from: Option<DateTime<Utc>>,
+  pub to: Option<DateTime<Utc>>,
+}
+
+impl From<AuditEntryRow> for AuditEntry {
+  fn from(value: AuditEntryRow) -> Self {
+    Self {
+      id: value.id.into(),
+      seq: value.seq,
+      actor_id: value.actor_id.map(Into::into),
+      action: value.action,
+      target: value.target,
+      ip_address: value.ip_address,
+      details: value.details,
+      personal_data: value.personal_data,
+      previous_hash: value.previous_hash,
+      hash: value.hash,
+      created_at: value.created_at,
+      updated_at: value.updated_at,
+    }
+  }
+}
@@ -0,0 +1,50 @@
+use chrono::{DateTime, Utc};
+use domain::{ActorId, BalanceAlert, BalanceAlertDirection, Email, WalletLabel};
+use sqlx::prelude::FromRow;
+use uuid::Uuid;
+
+#[derive(Clone, FromRow)]
+pub(crate) struct BalanceAlertRow {
+  pub id: Uuid,
+  pub wallet_label: Option<String>,
+  pub direction: String,
+  pub threshold_cents: i64,
+  pub notify_email: Option<String>,
+  pub webhook_url: Option<String>,
+  pub webhook_secret: Option<String>,
+  pub breached: bool,
+  pub last_triggered_at: Option<DateTime<Utc>>,
+  pub created_by_actor_id: Option<Uuid>,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone)]
+pub struct BalanceAlertCreation {
+  pub wallet_label: Option<WalletLabel>,
+  pub direction: BalanceAlertDirection,
+  pub threshold_cents: i64,
+  pub notify_email: Option<Email>,
+  pub webhook_url: Option<String>,
+  pub webhook_secret: Option<String>,
+  pub created_by: Option<ActorId>,
+}
+
+impl From<BalanceAlertRow> for BalanceAlert {
+  fn from(value: BalanceAlertRow) -> Self {
+    Self {
+      id: value.id.into(),
+      wallet_label: value.wallet_label.map(|l| l.as_str().into()),
+      direction: value.direction.as_str().into(),
+      threshold_cents: value.threshold_cents,
+      notify_email: value.notify_email.map(Into::into),
+      webhook_url: value.webhook_url,
+      webhook_secret: value.webhook_secret,
+      breached: value.breached,
+      last_triggered_at: value.last_triggered_at,
+      created_by: value.created_by_actor_id.map(Into::into),
+      created_at: value.created_at,
+      updated_at: value.updated_at,
+    }
+  }
+}
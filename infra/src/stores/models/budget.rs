@@ -0,0 +1,46 @@
+use chrono::{DateTime, Utc};
+use domain::{types::Money, BudgetEnvelope, BudgetPlan, WalletId};
+use sqlx::prelude::FromRow;
+use uuid::Uuid;
+
+use crate::stores::money_from_sum;
+
+/// An envelope with the shops it covers.
+#[derive(Clone, FromRow)]
+pub(crate) struct BudgetEnvelopeRow {
+  pub id: Uuid,
+  pub wallet_id: Uuid,
+  pub name: String,
+  pub amount_cents: i32,
+  pub spent_cents: i64,
+  pub spent_since: DateTime<Utc>,
+  pub shop_ids: Vec<Uuid>,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone)]
+pub struct BudgetEnvelopeCreation {
+  pub wallet_id: WalletId,
+  pub plan: BudgetPlan,
+}
+
+impl TryFrom<BudgetEnvelopeRow> for BudgetEnvelope {
+  type Error = sqlx::Error;
+
+  fn try_from(value: BudgetEnvelopeRow) -> Result<Self, Self::Error> {
+    Ok(Self {
+      id: value.id.into(),
+      wallet_id: value.wallet_id.into(),
+      plan: BudgetPlan::from_stored(
+        value.name,
+        Money::from_minor(value.amount_cents),
+        value.shop_ids.into_iter().map(Into::into).collect(),
+      ),
+      spent: money_from_sum(Some(value.spent_cents), "spent_cents")?,
+      spent_since: value.spent_since,
+      created_at: value.created_at,
+      updated_at: value.updated_at,
+    })
+  }
+}
@@ -0,0 +1,50 @@
+use chrono::{DateTime, Utc};
+use domain::{types::Money, Chargeback, ChargebackStatus, TopUpId, TransactionId, WalletId};
+use sqlx::prelude::FromRow;
+use uuid::Uuid;
+
+#[derive(Clone, FromRow)]
+pub(crate) struct ChargebackRow {
+  pub id: Uuid,
+  pub top_up_id: Uuid,
+  pub wallet_id: Uuid,
+  pub amount_cents: i32,
+  pub provider_dispute_id: String,
+  pub status: String,
+  pub transaction_id: Uuid,
+  pub reversal_transaction_id: Option<Uuid>,
+  pub resolved_by_actor_id: Option<Uuid>,
+  pub resolution_note: Option<String>,
+  pub resolved_at: Option<DateTime<Utc>>,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone)]
+pub struct ChargebackCreation {
+  pub top_up_id: TopUpId,
+  pub wallet_id: WalletId,
+  pub amount: Money,
+  pub provider_dispute_id: String,
+  pub transaction_id: TransactionId,
+}
+
+impl From<ChargebackRow> for Chargeback {
+  fn from(value: ChargebackRow) -> Self {
+    Self {
+      id: value.id.into(),
+      top_up_id: value.top_up_id.into(),
+      wallet_id: value.wallet_id.into(),
+      amount: Money::from_minor(value.amount_cents),
+      provider_dispute_id: value.provider_dispute_id,
+      status: ChargebackStatus::from(value.status.as_str()),
+      transaction_id: value.transaction_id.into(),
+      reversal_transaction_id: value.reversal_transaction_id.map(Into::into),
+      resolved_by: value.resolved_by_actor_id.map(Into::into),
+      resolution_note: value.resolution_note,
+      resolved_at: value.resolved_at,
+      created_at: value.created_at,
+      updated_at: value.updated_at,
+    }
+  }
+}
@@ -0,0 +1,125 @@
+use chrono::{DateTime, Duration, Utc};
+use domain::{AccessToken, ActorId, AuthorizationCode, ClientApp, ClientAppId, Scope, UserId};
+use sqlx::prelude::FromRow;
+use uuid::Uuid;
+
+#[derive(Clone, FromRow)]
+pub(crate) struct ClientAppRow {
+  pub id: Uuid,
+  pub name: String,
+  pub redirect_uris: Vec<String>,
+  pub allowed_scopes: Vec<String>,
+  pub created_by_actor_id: Option<Uuid>,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone)]
+pub struct ClientAppCreation {
+  pub name: String,
+  pub redirect_uris: Vec<String>,
+  pub allowed_scopes: Vec<Scope>,
+  pub created_by: Option<ActorId>,
+}
+
+#[derive(Clone, FromRow)]
+pub(crate) struct AuthorizationCodeRow {
+  pub id: Uuid,
+  pub client_app_id: Uuid,
+  pub user_id: Uuid,
+  pub code: String,
+  pub redirect_uri: String,
+  pub scopes: Vec<String>,
+  pub code_challenge: String,
+  pub expires_at: DateTime<Utc>,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone)]
+pub struct AuthorizationCodeCreation {
+  pub client_app_id: ClientAppId,
+  pub user_id: UserId,
+  pub code: String,
+  pub redirect_uri: String,
+  pub scopes: Vec<Scope>,
+  pub code_challenge: String,
+  pub expires_in: Duration,
+}
+
+#[derive(Clone, FromRow)]
+pub(crate) struct AccessTokenRow {
+  pub id: Uuid,
+  pub client_app_id: Uuid,
+  pub user_id: Uuid,
+  pub token: String,
+  pub scopes: Vec<String>,
+  pub expires_at: DateTime<Utc>,
+  pub last_used_at: Option<DateTime<Utc>>,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone)]
+pub struct AccessTokenCreation {
+  pub client_app_id: ClientAppId,
+  pub user_id: UserId,
+  pub token: String,
+  pub scopes: Vec<Scope>,
+  pub expires_in: Duration,
+}
+
+pub(crate) fn scope_names(scopes: &[Scope]) -> Vec<String> {
+  scopes.iter().map(ToString::to_string).collect()
+}
+
+fn parse_scopes(scopes: &[String]) -> Vec<Scope> {
+  scopes.iter().map(|s| Scope::from(s.as_str())).collect()
+}
+
+impl From<ClientAppRow> for ClientApp {
+  fn from(value: ClientAppRow) -> Self {
+    Self {
+      id: value.id.into(),
+      name: value.name,
+      redirect_uris: value.redirect_uris,
+      allowed_scopes: parse_scopes(&value.allowed_scopes),
+      created_by: value.created_by_actor_id.map(Into::into),
+      created_at: value.created_at,
+      updated_at: value.updated_at,
+    }
+  }
+}
+
+impl From<AuthorizationCodeRow> for AuthorizationCode {
+  fn from(value: AuthorizationCodeRow) -> Self {
+    Self {
+      id: value.id.into(),
+      client_app_id: value.client_app_id.into(),
+      user_id: value.user_id.into(),
+      code: value.code,
+      redirect_uri: value.redirect_uri,
+      scopes: parse_scopes(&value.scopes),
+      code_challenge: value.code_challenge,
+      expires_at: value.expires_at,
+      created_at: value.created_at,
+      updated_at: value.updated_at,
+    }
+  }
+}
+
+impl From<AccessTokenRow> for AccessToken {
+  fn from(value: AccessTokenRow) -> Self {
+    Self {
+      id: value.id.into(),
+      client_app_id: value.client_app_id.into(),
+      user_id: value.user_id.into(),
+      token: value.token,
+      scopes: parse_scopes(&value.scopes),
+      expires_at: value.expires_at,
+      last_used_at: value.last_used_at,
+      created_at: value.created_at,
+      updated_at: value.updated_at,
+    }
+  }
+}
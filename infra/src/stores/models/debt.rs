@@ -0,0 +1,76 @@
+use chrono::{DateTime, Utc};
+use domain::{ActorKind, Currency, Debt, Debtor};
+use sqlx::prelude::FromRow;
+use uuid::Uuid;
+
+#[derive(Clone, FromRow)]
+pub(crate) struct DebtRow {
+  pub id: Uuid,
+  pub wallet_id: Uuid,
+  pub since: DateTime<Utc>,
+  pub flagged_at: Option<DateTime<Utc>>,
+  pub reminders_sent: i32,
+  pub last_reminded_at: Option<DateTime<Utc>>,
+  pub settled_at: Option<DateTime<Utc>>,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone, FromRow)]
+pub(crate) struct DebtorRow {
+  pub id: Uuid,
+  pub wallet_id: Uuid,
+  pub since: DateTime<Utc>,
+  pub flagged_at: Option<DateTime<Utc>>,
+  pub reminders_sent: i32,
+  pub last_reminded_at: Option<DateTime<Utc>>,
+  pub settled_at: Option<DateTime<Utc>>,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: Option<DateTime<Utc>>,
+  pub balance_cents: i64,
+  pub currency: Currency,
+  pub owner_actor_id: Uuid,
+  pub kind: Option<String>,
+  pub display_name: Option<String>,
+  pub email: Option<String>,
+}
+
+impl From<DebtRow> for Debt {
+  fn from(value: DebtRow) -> Self {
+    Self {
+      id: value.id.into(),
+      wallet_id: value.wallet_id.into(),
+      since: value.since,
+      flagged_at: value.flagged_at,
+      reminders_sent: value.reminders_sent,
+      last_reminded_at: value.last_reminded_at,
+      settled_at: value.settled_at,
+      created_at: value.created_at,
+      updated_at: value.updated_at,
+    }
+  }
+}
+
+impl From<DebtorRow> for Debtor {
+  fn from(value: DebtorRow) -> Self {
+    Self {
+      debt: Debt {
+        id: value.id.into(),
+        wallet_id: value.wallet_id.into(),
+        since: value.since,
+        flagged_at: value.flagged_at,
+        reminders_sent: value.reminders_sent,
+        last_reminded_at: value.last_reminded_at,
+        settled_at: value.settled_at,
+        created_at: value.created_at,
+        updated_at: value.updated_at,
+      },
+      balance_cents: value.balance_cents,
+      currency: value.currency,
+      owner: value.owner_actor_id.into(),
+      kind: value.kind.as_deref().map(ActorKind::from),
+      display_name: value.display_name,
+      email: value.email.map(Into::into),
+    }
+  }
+}
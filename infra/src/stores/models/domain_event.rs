@@ -0,0 +1,66 @@
+use chrono::{DateTime, Utc};
+use domain::{DomainEvent, DomainEventRecord};
+use serde_json::{json, Value};
+use sqlx::prelude::FromRow;
+use uuid::Uuid;
+
+#[derive(Clone, FromRow)]
+pub(crate) struct DomainEventRow {
+  pub id: Uuid,
+  pub sequence: i64,
+  pub event_type: String,
+  pub data: Value,
+  pub occurred_at: DateTime<Utc>,
+  pub dispatched_at: Option<DateTime<Utc>>,
+}
+
+/// A `transaction.created` event and whether it touches the wallets asked for.
+#[derive(Clone, FromRow)]
+pub(crate) struct ScopedDomainEventRow {
+  pub id: Uuid,
+  pub sequence: i64,
+  pub event_type: String,
+  pub data: Value,
+  pub occurred_at: DateTime<Utc>,
+  pub dispatched_at: Option<DateTime<Utc>>,
+  pub in_scope: bool,
+}
+
+impl ScopedDomainEventRow {
+  pub(crate) fn split(self) -> (DomainEventRow, bool) {
+    (
+      DomainEventRow {
+        id: self.id,
+        sequence: self.sequence,
+        event_type: self.event_type,
+        data: self.data,
+        occurred_at: self.occurred_at,
+        dispatched_at: self.dispatched_at,
+      },
+      self.in_scope,
+    )
+  }
+}
+
+impl TryFrom<DomainEventRow> for DomainEventRecord {
+  type Error = sqlx::Error;
+
+  fn try_from(value: DomainEventRow) -> Result<Self, Self::Error> {
+    let event = serde_json::from_value::<DomainEvent>(json!({
+      "type": value.event_type,
+      "data": value.data,
+    }))
+    .map_err(|e| sqlx::Error::ColumnDecode {
+      index: "data".to_string(),
+      source: Box::new(e),
+    })?;
+
+    Ok(Self {
+      id: value.id.into(),
+      sequence: value.sequence,
+      event,
+      occurred_at: value.occurred_at,
+      dispatched_at: value.dispatched_at,
+    })
+  }
+}
@@ -0,0 +1,44 @@
+use chrono::{DateTime, Utc};
+use domain::{ActorId, Feature, FeatureSwitch};
+use sqlx::prelude::FromRow;
+use uuid::Uuid;
+
+#[derive(Clone, FromRow)]
+pub(crate) struct FeatureSwitchRow {
+  pub feature: String,
+  pub enabled: bool,
+  pub reason: Option<String>,
+  pub changed_by_actor_id: Option<Uuid>,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone)]
+pub struct FeatureSwitchUpdate {
+  pub feature: Feature,
+  pub enabled: bool,
+  pub reason: Option<String>,
+  pub changed_by: Option<ActorId>,
+}
+
+impl TryFrom<FeatureSwitchRow> for FeatureSwitch {
+  type Error = sqlx::Error;
+
+  fn try_from(value: FeatureSwitchRow) -> Result<Self, Self::Error> {
+    let feature = value
+      .feature
+      .parse()
+      .map_err(|e: String| sqlx::Error::ColumnDecode {
+        index: "feature".to_string(),
+        source: e.into(),
+      })?;
+
+    Ok(Self {
+      feature,
+      enabled: value.enabled,
+      reason: value.reason,
+      changed_by: value.changed_by_actor_id.map(Into::into),
+      changed_at: Some(value.updated_at.unwrap_or(value.created_at)),
+    })
+  }
+}
@@ -1,5 +1,7 @@
 use chrono::{DateTime, Utc};
-use domain::{ActorId, Email, Guest};
+use domain::{
+  types::Money, ActorId, Currency, Email, Guest, GuestClaim, GuestId, TransactionId, UserId,
+};
 use sqlx::prelude::FromRow;
 use uuid::Uuid;
 
@@ -9,6 +11,18 @@ pub(crate) struct GuestRow {
   pub actor_id: Uuid,
   pub email: Option<String>,
   pub verified: bool,
+  pub identifier: Option<String>,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone, FromRow)]
+pub(crate) struct GuestClaimRow {
+  pub id: Uuid,
+  pub guest_id: Uuid,
+  pub user_id: Uuid,
+  pub amount_cents: i32,
+  pub transaction_id: Option<Uuid>,
   pub created_at: DateTime<Utc>,
   pub updated_at: Option<DateTime<Utc>>,
 }
@@ -16,7 +30,7 @@ pub(crate) struct GuestRow {
 #[derive(Clone)]
 pub struct GuestCreation {
   pub actor_id: ActorId,
-  pub email: Email,
+  pub email: Option<Email>,
   pub verified: bool,
 }
 
@@ -26,6 +40,28 @@ pub struct GuestUpdate {
   pub verified: Option<bool>,
 }
 
+#[derive(Clone, Default)]
+pub struct GuestFilter {
+  /// What the balance bounds are counted in; guests without a wallet in it
+  /// never match them
+  pub currency: Currency,
+  /// Lowest wallet balance, inclusive
+  pub min_balance: Option<Money>,
+  /// Highest wallet balance, inclusive
+  pub max_balance: Option<Money>,
+  /// Only guests with a card or wristband bound if set, only those without if
+  /// unset
+  pub card_bound: Option<bool>,
+}
+
+#[derive(Clone)]
+pub struct GuestClaimCreation {
+  pub guest_id: GuestId,
+  pub user_id: UserId,
+  pub amount: Money,
+  pub transaction_id: Option<TransactionId>,
+}
+
 impl From<GuestRow> for Guest {
   fn from(value: GuestRow) -> Self {
     Self {
@@ -33,6 +69,21 @@ impl From<GuestRow> for Guest {
       actor_id: value.actor_id.into(),
       email: value.email.map(Into::into),
       verified: value.verified,
+      identifier: value.identifier,
+      created_at: value.created_at,
+      updated_at: value.updated_at,
+    }
+  }
+}
+
+impl From<GuestClaimRow> for GuestClaim {
+  fn from(value: GuestClaimRow) -> Self {
+    Self {
+      id: value.id.into(),
+      guest_id: value.guest_id.into(),
+      user_id: value.user_id.into(),
+      amount: Money::from_minor(value.amount_cents),
+      transaction_id: value.transaction_id.map(Into::into),
       created_at: value.created_at,
       updated_at: value.updated_at,
     }
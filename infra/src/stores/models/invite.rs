@@ -1,5 +1,5 @@
 use chrono::{DateTime, Duration, Utc};
-use domain::{invite::InviteStatus, Email, Invite, Role, UserId};
+use domain::{invite::InviteStatus, Email, Invite, InviteDomainRule, Locale, Role, UserId};
 use sqlx::prelude::FromRow;
 use uuid::Uuid;
 
@@ -10,21 +10,40 @@ pub(crate) struct InviteRow {
   pub email: String,
   pub token: String,
   pub role: String,
+  pub locale: String,
   pub status: String,
   pub expires_at: DateTime<Utc>,
   pub created_at: DateTime<Utc>,
   pub updated_at: Option<DateTime<Utc>>,
 }
 
+#[derive(Clone, FromRow)]
+pub(crate) struct InviteDomainRuleRow {
+  pub domain: String,
+  pub allowed: bool,
+  pub role: Option<Role>,
+}
+
 #[derive(Clone)]
 pub struct InviteCreation {
   pub invitor: UserId,
   pub email: Email,
   pub token: String,
   pub role: Role,
+  pub locale: Locale,
   pub expires_in: Duration,
 }
 
+#[derive(Clone, Default)]
+pub struct InviteFilter {
+  pub status: Option<InviteStatus>,
+  /// Case-insensitive start of the invited address
+  pub email_prefix: Option<String>,
+  pub invitor: Option<UserId>,
+  pub created_from: Option<DateTime<Utc>>,
+  pub created_to: Option<DateTime<Utc>>,
+}
+
 #[derive(Clone)]
 pub struct InviteUpdate {
   pub status: Option<InviteStatus>,
@@ -38,6 +57,7 @@ impl From<InviteRow> for Invite {
       email: value.email.into(),
       token: value.token,
       role: value.role.into(),
+      locale: Locale::from(value.locale.as_str()),
       status: value.status.as_str().into(),
       expires_in: value.expires_at - value.created_at,
       created_at: value.created_at,
@@ -45,3 +65,13 @@ impl From<InviteRow> for Invite {
     }
   }
 }
+
+impl From<InviteDomainRuleRow> for InviteDomainRule {
+  fn from(value: InviteDomainRuleRow) -> Self {
+    Self {
+      domain: value.domain,
+      allowed: value.allowed,
+      role: value.role,
+    }
+  }
+}
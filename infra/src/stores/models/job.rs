@@ -0,0 +1,47 @@
+use chrono::{DateTime, Utc};
+use domain::{Job, JobStatus, JobTask};
+use serde_json::Value;
+use sqlx::prelude::FromRow;
+use uuid::Uuid;
+
+#[derive(Clone, FromRow)]
+pub(crate) struct JobRow {
+  pub id: Uuid,
+  pub task: Value,
+  pub status: String,
+  pub attempts: i32,
+  pub max_attempts: i32,
+  pub run_at: DateTime<Utc>,
+  pub last_error: Option<String>,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone)]
+pub struct JobCreation {
+  pub task: JobTask,
+  pub max_attempts: i32,
+}
+
+impl TryFrom<JobRow> for Job {
+  type Error = sqlx::Error;
+
+  fn try_from(value: JobRow) -> Result<Self, Self::Error> {
+    let task = serde_json::from_value(value.task).map_err(|e| sqlx::Error::ColumnDecode {
+      index: "task".to_string(),
+      source: Box::new(e),
+    })?;
+
+    Ok(Self {
+      id: value.id.into(),
+      task,
+      status: JobStatus::from(value.status.as_str()),
+      attempts: value.attempts,
+      max_attempts: value.max_attempts,
+      run_at: value.run_at,
+      last_error: value.last_error,
+      created_at: value.created_at,
+      updated_at: value.updated_at,
+    })
+  }
+}
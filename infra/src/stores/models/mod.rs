@@ -1,15 +1,50 @@
 pub mod actor;
+pub mod audit;
+pub mod balance_alert;
+pub mod budget;
+pub mod chargeback;
+pub mod client_app;
+pub mod debt;
+pub mod domain_event;
+pub mod feature;
 pub mod guest;
 pub mod invite;
+pub mod job;
+pub mod note;
+pub mod order;
+pub mod outbound_email;
+pub mod owner_transfer;
+pub mod payout;
+pub mod risk;
 pub mod session;
+pub mod settings;
 pub mod shop;
+pub mod top_up;
 pub mod transaction;
 pub mod user;
 pub mod wallet;
+pub mod webhook;
 
-pub use guest::{GuestCreation, GuestUpdate};
-pub use invite::{InviteCreation, InviteUpdate};
-pub use session::SessionCreation;
+pub use actor::ActorSummaryFilter;
+pub use audit::{AuditEntryCreation, AuditFilter};
+pub use balance_alert::BalanceAlertCreation;
+pub use budget::BudgetEnvelopeCreation;
+pub use chargeback::ChargebackCreation;
+pub use client_app::{AccessTokenCreation, AuthorizationCodeCreation, ClientAppCreation};
+pub use feature::FeatureSwitchUpdate;
+pub use guest::{GuestClaimCreation, GuestCreation, GuestFilter, GuestUpdate};
+pub use invite::{InviteCreation, InviteFilter, InviteUpdate};
+pub use job::JobCreation;
+pub use note::AccountNoteCreation;
+pub use order::{OrderCreation, OrderFilter, OrderItemCreation, SalesTotals};
+pub use outbound_email::OutboundEmailCreation;
+pub use owner_transfer::OwnerTransferCreation;
+pub use payout::{BankAccountCreation, PayoutCreation};
+pub use risk::{ReviewItemCreation, ReviewItemResolution};
+pub use session::{LoginAttemptCreation, PasswordConfirmationCreation, SessionCreation};
+pub use settings::SettingsUpdate;
+pub use top_up::TopUpCreation;
 pub use transaction::TransactionCreation;
-pub use user::{UserCreation, UserUpdate};
-pub use wallet::{WalletCreation, WalletUpdate};
+pub use user::{EmailChangeCreation, PasswordSetupCreation, UserCreation, UserFilter, UserUpdate};
+pub use wallet::{WalletCreation, WalletFilter, WalletLegalHoldEventCreation, WalletUpdate};
+pub use webhook::{WebhookCreation, WebhookDeliveryCreation};
@@ -0,0 +1,34 @@
+use chrono::{DateTime, Utc};
+use domain::{AccountNote, ActorId};
+use sqlx::prelude::FromRow;
+use uuid::Uuid;
+
+#[derive(Clone, FromRow)]
+pub(crate) struct AccountNoteRow {
+  pub id: Uuid,
+  pub actor_id: Uuid,
+  pub author_actor_id: Option<Uuid>,
+  pub body: String,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone)]
+pub struct AccountNoteCreation {
+  pub actor_id: ActorId,
+  pub author: ActorId,
+  pub body: String,
+}
+
+impl From<AccountNoteRow> for AccountNote {
+  fn from(value: AccountNoteRow) -> Self {
+    Self {
+      id: value.id.into(),
+      actor_id: value.actor_id.into(),
+      author: value.author_actor_id.map(Into::into),
+      body: value.body,
+      created_at: value.created_at,
+      updated_at: value.updated_at,
+    }
+  }
+}
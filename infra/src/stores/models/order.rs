@@ -0,0 +1,164 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use domain::{
+  types::Money, ActorId, ActorKind, DailySales, OfferingSales, Order, OrderId, OrderItem,
+  OrderStatus, Purchaser, ShopId, ShopOfferingId, TransactionId, WalletId,
+};
+use sqlx::prelude::FromRow;
+use uuid::Uuid;
+
+#[derive(Clone, FromRow)]
+pub(crate) struct OrderRow {
+  pub id: Uuid,
+  pub shop_id: Uuid,
+  pub payer_wallet_id: Uuid,
+  pub transaction_id: Option<Uuid>,
+  pub executor_actor_id: Option<Uuid>,
+  pub total_cents: i32,
+  pub status: String,
+  pub table_label: Option<String>,
+  pub pickup_number: Option<i32>,
+  pub pickup_at: Option<DateTime<Utc>>,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone, FromRow)]
+pub(crate) struct OrderItemRow {
+  pub id: Uuid,
+  pub order_id: Uuid,
+  pub offering_id: Option<Uuid>,
+  pub name: String,
+  pub unit_price_cents: i32,
+  pub quantity: i32,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone, FromRow)]
+pub(crate) struct PurchaserRow {
+  pub order_id: Uuid,
+  pub kind: Option<String>,
+  pub first_name: Option<String>,
+  pub last_initial: Option<String>,
+}
+
+#[derive(Clone, FromRow)]
+pub(crate) struct OfferingSalesRow {
+  pub offering_id: Option<Uuid>,
+  pub name: String,
+  pub quantity: i64,
+  pub revenue_cents: i64,
+}
+
+#[derive(Clone, FromRow)]
+pub(crate) struct DailySalesRow {
+  pub day: NaiveDate,
+  pub order_count: i64,
+  pub revenue_cents: i64,
+}
+
+/// Order count, gross and refunded amount in cents of a shop's orders.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SalesTotals {
+  pub order_count: i64,
+  pub gross_cents: i64,
+  pub refunded_cents: i64,
+}
+
+#[derive(Clone)]
+pub struct OrderCreation {
+  pub shop_id: ShopId,
+  pub payer_wallet_id: WalletId,
+  /// Unset for pre-orders paid at pickup
+  pub transaction_id: Option<TransactionId>,
+  pub executor: Option<ActorId>,
+  pub total: Money,
+  pub table_label: Option<String>,
+  pub pickup_number: Option<i32>,
+  pub pickup_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone, Default)]
+pub struct OrderFilter {
+  pub shop_id: Option<ShopId>,
+  pub payer_wallet_id: Option<WalletId>,
+  /// Inclusive lower bound on the order time
+  pub from: Option<DateTime<Utc>>,
+  /// Exclusive upper bound on the order time
+  pub to: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone)]
+pub struct OrderItemCreation {
+  pub order_id: OrderId,
+  pub offering_id: Option<ShopOfferingId>,
+  pub name: String,
+  pub unit_price: Money,
+  pub quantity: i32,
+}
+
+impl From<PurchaserRow> for Purchaser {
+  fn from(value: PurchaserRow) -> Self {
+    Self {
+      order_id: value.order_id.into(),
+      kind: value.kind.as_deref().map(ActorKind::from),
+      first_name: value.first_name,
+      last_initial: value.last_initial,
+    }
+  }
+}
+
+impl From<OrderRow> for Order {
+  fn from(value: OrderRow) -> Self {
+    Self {
+      id: value.id.into(),
+      shop_id: value.shop_id.into(),
+      payer_wallet_id: value.payer_wallet_id.into(),
+      transaction_id: value.transaction_id.map(Into::into),
+      executor: value.executor_actor_id.map(Into::into),
+      total: Money::from_minor(value.total_cents),
+      status: OrderStatus::from(value.status.as_str()),
+      table_label: value.table_label,
+      pickup_number: value.pickup_number,
+      pickup_at: value.pickup_at,
+      created_at: value.created_at,
+      updated_at: value.updated_at,
+    }
+  }
+}
+
+impl From<OrderItemRow> for OrderItem {
+  fn from(value: OrderItemRow) -> Self {
+    Self {
+      id: value.id.into(),
+      order_id: value.order_id.into(),
+      offering_id: value.offering_id.map(Into::into),
+      name: value.name,
+      unit_price: Money::from_minor(value.unit_price_cents),
+      quantity: value.quantity,
+      created_at: value.created_at,
+      updated_at: value.updated_at,
+    }
+  }
+}
+
+impl From<OfferingSalesRow> for OfferingSales {
+  fn from(value: OfferingSalesRow) -> Self {
+    Self {
+      offering_id: value.offering_id.map(Into::into),
+      name: value.name,
+      quantity: value.quantity,
+      revenue_cents: value.revenue_cents,
+    }
+  }
+}
+
+impl From<DailySalesRow> for DailySales {
+  fn from(value: DailySalesRow) -> Self {
+    Self {
+      day: value.day,
+      order_count: value.order_count,
+      revenue_cents: value.revenue_cents,
+    }
+  }
+}
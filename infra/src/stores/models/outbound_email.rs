@@ -0,0 +1,45 @@
+use chrono::{DateTime, Utc};
+use domain::{Email, OutboundEmail, OutboundEmailStatus};
+use sqlx::prelude::FromRow;
+use uuid::Uuid;
+
+#[derive(Clone, FromRow)]
+pub(crate) struct OutboundEmailRow {
+  pub id: Uuid,
+  pub recipient: String,
+  pub subject: String,
+  pub text_body: String,
+  pub html_body: String,
+  pub status: String,
+  pub attempts: i32,
+  pub last_error: Option<String>,
+  pub sent_at: Option<DateTime<Utc>>,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone)]
+pub struct OutboundEmailCreation {
+  pub recipient: Email,
+  pub subject: String,
+  pub text_body: String,
+  pub html_body: String,
+}
+
+impl From<OutboundEmailRow> for OutboundEmail {
+  fn from(value: OutboundEmailRow) -> Self {
+    Self {
+      id: value.id.into(),
+      recipient: Email::new(value.recipient),
+      subject: value.subject,
+      text_body: value.text_body,
+      html_body: value.html_body,
+      status: OutboundEmailStatus::from(value.status.as_str()),
+      attempts: value.attempts,
+      last_error: value.last_error,
+      sent_at: value.sent_at,
+      created_at: value.created_at,
+      updated_at: value.updated_at,
+    }
+  }
+}
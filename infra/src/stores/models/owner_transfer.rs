@@ -0,0 +1,41 @@
+use chrono::{DateTime, Utc};
+use domain::{OwnerTransfer, OwnerTransferStatus, Role, UserId};
+use sqlx::prelude::FromRow;
+use uuid::Uuid;
+
+#[derive(Clone, FromRow)]
+pub(crate) struct OwnerTransferRow {
+  pub id: Uuid,
+  pub from_user_id: Uuid,
+  pub to_user_id: Uuid,
+  pub demote_to: String,
+  pub status: String,
+  pub expires_at: DateTime<Utc>,
+  pub decided_at: Option<DateTime<Utc>>,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone)]
+pub struct OwnerTransferCreation {
+  pub from_user_id: UserId,
+  pub to_user_id: UserId,
+  pub demote_to: Role,
+  pub expires_at: DateTime<Utc>,
+}
+
+impl From<OwnerTransferRow> for OwnerTransfer {
+  fn from(value: OwnerTransferRow) -> Self {
+    Self {
+      id: value.id.into(),
+      from_user_id: value.from_user_id.into(),
+      to_user_id: value.to_user_id.into(),
+      demote_to: value.demote_to.into(),
+      status: OwnerTransferStatus::from(value.status.as_str()),
+      expires_at: value.expires_at,
+      decided_at: value.decided_at,
+      created_at: value.created_at,
+      updated_at: value.updated_at,
+    }
+  }
+}
@@ -0,0 +1,99 @@
+use chrono::{DateTime, Utc};
+use domain::{
+  types::Money, ActorId, BankAccount, Iban, Payout, PayoutBatch, PayoutStatus, TransactionId,
+  WalletId,
+};
+use sqlx::prelude::FromRow;
+use uuid::Uuid;
+
+#[derive(Clone, FromRow)]
+pub(crate) struct BankAccountRow {
+  pub actor_id: Uuid,
+  pub iban: Iban,
+  pub holder_name: String,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone, FromRow)]
+pub(crate) struct PayoutRow {
+  pub id: Uuid,
+  pub wallet_id: Uuid,
+  pub amount_cents: i32,
+  pub iban: Iban,
+  pub holder_name: String,
+  pub status: String,
+  pub batch_id: Option<Uuid>,
+  pub transaction_id: Uuid,
+  pub refund_transaction_id: Option<Uuid>,
+  pub requested_by_actor_id: Option<Uuid>,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone, FromRow)]
+pub(crate) struct PayoutBatchRow {
+  pub id: Uuid,
+  pub created_by_actor_id: Option<Uuid>,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone)]
+pub struct BankAccountCreation {
+  pub actor_id: ActorId,
+  pub iban: Iban,
+  pub holder_name: String,
+}
+
+#[derive(Clone)]
+pub struct PayoutCreation {
+  pub wallet_id: WalletId,
+  pub amount: Money,
+  pub iban: Iban,
+  pub holder_name: String,
+  pub transaction_id: TransactionId,
+  pub requested_by: Option<ActorId>,
+}
+
+impl From<BankAccountRow> for BankAccount {
+  fn from(value: BankAccountRow) -> Self {
+    Self {
+      actor_id: value.actor_id.into(),
+      iban: value.iban,
+      holder_name: value.holder_name,
+      created_at: value.created_at,
+      updated_at: value.updated_at,
+    }
+  }
+}
+
+impl From<PayoutRow> for Payout {
+  fn from(value: PayoutRow) -> Self {
+    Self {
+      id: value.id.into(),
+      wallet_id: value.wallet_id.into(),
+      amount: Money::from_minor(value.amount_cents),
+      iban: value.iban,
+      holder_name: value.holder_name,
+      status: PayoutStatus::from(value.status.as_str()),
+      batch_id: value.batch_id.map(Into::into),
+      transaction_id: value.transaction_id.into(),
+      refund_transaction_id: value.refund_transaction_id.map(Into::into),
+      requested_by: value.requested_by_actor_id.map(Into::into),
+      created_at: value.created_at,
+      updated_at: value.updated_at,
+    }
+  }
+}
+
+impl From<PayoutBatchRow> for PayoutBatch {
+  fn from(value: PayoutBatchRow) -> Self {
+    Self {
+      id: value.id.into(),
+      created_by: value.created_by_actor_id.map(Into::into),
+      created_at: value.created_at,
+      updated_at: value.updated_at,
+    }
+  }
+}
@@ -0,0 +1,50 @@
+use chrono::{DateTime, Utc};
+use domain::{ActorId, ReviewItem, ReviewStatus, RiskSignal, WalletId};
+use sqlx::prelude::FromRow;
+use uuid::Uuid;
+
+#[derive(Clone, FromRow)]
+pub(crate) struct ReviewItemRow {
+  pub id: Uuid,
+  pub wallet_id: Uuid,
+  pub signal: String,
+  pub details: String,
+  pub status: String,
+  pub wallet_frozen: bool,
+  pub resolved_by_actor_id: Option<Uuid>,
+  pub resolution_note: Option<String>,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone)]
+pub struct ReviewItemCreation {
+  pub wallet_id: WalletId,
+  pub signal: RiskSignal,
+  pub details: String,
+  pub wallet_frozen: bool,
+}
+
+#[derive(Clone)]
+pub struct ReviewItemResolution {
+  pub status: ReviewStatus,
+  pub resolved_by: ActorId,
+  pub note: Option<String>,
+}
+
+impl From<ReviewItemRow> for ReviewItem {
+  fn from(value: ReviewItemRow) -> Self {
+    Self {
+      id: value.id.into(),
+      wallet_id: value.wallet_id.into(),
+      signal: value.signal.as_str().into(),
+      details: value.details,
+      status: value.status.as_str().into(),
+      wallet_frozen: value.wallet_frozen,
+      resolved_by: value.resolved_by_actor_id.map(Into::into),
+      resolution_note: value.resolution_note,
+      created_at: value.created_at,
+      updated_at: value.updated_at,
+    }
+  }
+}
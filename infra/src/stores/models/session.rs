@@ -1,5 +1,5 @@
 use chrono::{DateTime, Duration, Utc};
-use domain::UserId;
+use domain::{DevicePublicKey, Email, SessionId, SessionRevocation, UserId};
 use sqlx::prelude::FromRow;
 use uuid::Uuid;
 
@@ -10,17 +10,46 @@ pub(crate) struct SessionRow {
   pub token: String,
   pub user_agent: Option<String>,
   pub ip_address: Option<String>,
+  pub device_public_key: Option<String>,
   pub expires_at: DateTime<Utc>,
+  pub last_used_at: Option<DateTime<Utc>>,
   pub created_at: DateTime<Utc>,
   pub updated_at: Option<DateTime<Utc>>,
 }
 
 #[derive(Clone)]
 pub struct SessionCreation {
+  /// Chosen up front, as signed tokens carry it
+  pub id: SessionId,
   pub user_id: UserId,
   pub token: String,
   pub user_agent: Option<String>,
   pub ip_address: Option<String>,
+  pub device_public_key: Option<DevicePublicKey>,
+  pub expires_in: Duration,
+}
+
+#[derive(Clone, FromRow)]
+pub(crate) struct SessionRevocationRow {
+  pub session_id: Uuid,
+  pub issued_before: Option<DateTime<Utc>>,
+  pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Clone, FromRow)]
+pub(crate) struct PasswordConfirmationRow {
+  pub id: Uuid,
+  pub session_id: Uuid,
+  pub token: String,
+  pub expires_at: DateTime<Utc>,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone)]
+pub struct PasswordConfirmationCreation {
+  pub session_id: SessionId,
+  pub token: String,
   pub expires_in: Duration,
 }
 
@@ -32,9 +61,45 @@ impl From<SessionRow> for domain::Session {
       token: value.token,
       user_agent: value.user_agent,
       ip_address: value.ip_address,
+      device_public_key: value
+        .device_public_key
+        .and_then(|key| DevicePublicKey::parse(&key).ok()),
       expires_in: value.expires_at - value.created_at,
+      last_used_at: value.last_used_at,
       created_at: value.created_at,
       updated_at: value.updated_at,
     }
   }
 }
+
+impl From<SessionRevocationRow> for SessionRevocation {
+  fn from(value: SessionRevocationRow) -> Self {
+    Self {
+      session_id: value.session_id.into(),
+      issued_before: value.issued_before,
+      expires_at: value.expires_at,
+    }
+  }
+}
+
+impl From<PasswordConfirmationRow> for domain::PasswordConfirmation {
+  fn from(value: PasswordConfirmationRow) -> Self {
+    Self {
+      id: value.id.into(),
+      session_id: value.session_id.into(),
+      token: value.token,
+      expires_at: value.expires_at,
+      created_at: value.created_at,
+      updated_at: value.updated_at,
+    }
+  }
+}
+
+#[derive(Clone)]
+pub struct LoginAttemptCreation {
+  /// Address the attempt was made for, whether or not an account uses it
+  pub email: Email,
+  pub user_id: Option<UserId>,
+  pub succeeded: bool,
+  pub ip_address: Option<String>,
+}
@@ -0,0 +1,37 @@
+use chrono::{DateTime, Utc};
+use domain::{ActorId, Currency, Settings};
+use sqlx::prelude::FromRow;
+use uuid::Uuid;
+
+#[derive(Clone, FromRow)]
+pub(crate) struct SettingsRow {
+  pub invite_expiration_days: i32,
+  pub allow_overdraft_by_default: bool,
+  pub currency: Currency,
+  pub email_sender_name: Option<String>,
+  pub changed_by_actor_id: Option<Uuid>,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone)]
+pub struct SettingsUpdate {
+  pub invite_expiration_days: i32,
+  pub allow_overdraft_by_default: bool,
+  pub currency: Currency,
+  pub email_sender_name: Option<String>,
+  pub changed_by: Option<ActorId>,
+}
+
+impl From<SettingsRow> for Settings {
+  fn from(value: SettingsRow) -> Self {
+    Self {
+      invite_expiration_days: value.invite_expiration_days,
+      allow_overdraft_by_default: value.allow_overdraft_by_default,
+      currency: value.currency,
+      email_sender_name: value.email_sender_name,
+      changed_by: value.changed_by_actor_id.map(Into::into),
+      changed_at: Some(value.updated_at.unwrap_or(value.created_at)),
+    }
+  }
+}
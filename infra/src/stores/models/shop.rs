@@ -1,5 +1,5 @@
-use chrono::{DateTime, Utc};
-use domain::{types::Money, Shop, ShopMember, ShopOffering, UserId};
+use chrono::{DateTime, Duration, Utc};
+use domain::{types::Money, PickupSlots, Shop, ShopMember, ShopOffering, UserId, WalletId};
 use sqlx::prelude::FromRow;
 use uuid::Uuid;
 
@@ -8,6 +8,9 @@ pub(crate) struct ShopRow {
   pub id: Uuid,
   pub owner_user_id: Option<Uuid>,
   pub name: String,
+  pub wallet_id: Uuid,
+  pub pickup_slot_minutes: Option<i32>,
+  pub pickup_slot_capacity: Option<i32>,
   pub created_at: DateTime<Utc>,
   pub updated_at: Option<DateTime<Utc>>,
 }
@@ -36,6 +39,7 @@ pub(crate) struct ShopMemberRow {
 pub struct ShopCreation {
   pub owner: Option<UserId>,
   pub name: String,
+  pub wallet_id: WalletId,
 }
 
 #[derive(Clone)]
@@ -64,6 +68,14 @@ impl From<ShopRow> for Shop {
       id: value.id.into(),
       owner: value.owner_user_id.map(Into::into),
       name: value.name,
+      wallet_id: value.wallet_id.into(),
+      pickup_slots: value
+        .pickup_slot_minutes
+        .zip(value.pickup_slot_capacity)
+        .map(|(minutes, capacity)| PickupSlots {
+          length: Duration::minutes(minutes.into()),
+          capacity,
+        }),
       created_at: value.created_at,
       updated_at: value.updated_at,
     }
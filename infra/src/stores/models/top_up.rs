@@ -0,0 +1,42 @@
+use chrono::{DateTime, Utc};
+use domain::{types::Money, ActorId, TopUp, TopUpStatus, WalletId};
+use sqlx::prelude::FromRow;
+use uuid::Uuid;
+
+#[derive(Clone, FromRow)]
+pub(crate) struct TopUpRow {
+  pub id: Uuid,
+  pub wallet_id: Uuid,
+  pub amount_cents: i32,
+  pub status: String,
+  pub provider_session_id: Option<String>,
+  pub provider_payment_id: Option<String>,
+  pub transaction_id: Option<Uuid>,
+  pub requested_by_actor_id: Option<Uuid>,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone)]
+pub struct TopUpCreation {
+  pub wallet_id: WalletId,
+  pub amount: Money,
+  pub requested_by: Option<ActorId>,
+}
+
+impl From<TopUpRow> for TopUp {
+  fn from(value: TopUpRow) -> Self {
+    Self {
+      id: value.id.into(),
+      wallet_id: value.wallet_id.into(),
+      amount: Money::from_minor(value.amount_cents),
+      status: TopUpStatus::from(value.status.as_str()),
+      provider_session_id: value.provider_session_id,
+      provider_payment_id: value.provider_payment_id,
+      transaction_id: value.transaction_id.map(Into::into),
+      requested_by: value.requested_by_actor_id.map(Into::into),
+      created_at: value.created_at,
+      updated_at: value.updated_at,
+    }
+  }
+}
@@ -1,5 +1,10 @@
 use chrono::{DateTime, Utc};
-use domain::{types::Money, wallet::WalletId, ActorId, Transaction};
+use domain::{
+  types::{Currency, Money},
+  wallet::WalletId,
+  ActorId, LedgerLine, Transaction, TransactionId, TransactionMetadata, WalletLabel,
+};
+use serde_json::Value;
 use sqlx::prelude::FromRow;
 use uuid::Uuid;
 
@@ -10,7 +15,28 @@ pub(crate) struct TransactionRow {
   pub destination_wallet_id: Uuid,
   pub executor_actor_id: Option<Uuid>,
   pub amount_cents: i32,
+  pub currency: Currency,
   pub description: Option<String>,
+  pub reversal_of: Option<Uuid>,
+  pub metadata: Value,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone, FromRow)]
+pub(crate) struct LedgerLineRow {
+  pub id: Uuid,
+  pub source_wallet_id: Uuid,
+  pub source_label: Option<String>,
+  pub destination_wallet_id: Uuid,
+  pub destination_label: Option<String>,
+  pub executor_actor_id: Option<Uuid>,
+  pub executor_name: Option<String>,
+  pub amount_cents: i32,
+  pub currency: Currency,
+  pub description: Option<String>,
+  pub reversal_of: Option<Uuid>,
+  pub metadata: Value,
   pub created_at: DateTime<Utc>,
   pub updated_at: Option<DateTime<Utc>>,
 }
@@ -22,6 +48,8 @@ pub struct TransactionCreation {
   pub executor: Option<ActorId>,
   pub amount: Money,
   pub description: Option<String>,
+  pub reversal_of: Option<TransactionId>,
+  pub metadata: TransactionMetadata,
 }
 
 impl From<TransactionRow> for Transaction {
@@ -32,9 +60,41 @@ impl From<TransactionRow> for Transaction {
       destination: value.destination_wallet_id.into(),
       executor: value.executor_actor_id.map(Into::into),
       amount: Money::from_minor(value.amount_cents),
+      currency: value.currency,
       description: value.description,
+      reversal_of: value.reversal_of.map(Into::into),
+      metadata: metadata_from_row(value.metadata),
       created_at: value.created_at,
       updated_at: value.updated_at,
     }
   }
 }
+
+impl From<LedgerLineRow> for LedgerLine {
+  fn from(value: LedgerLineRow) -> Self {
+    Self {
+      transaction: Transaction {
+        id: value.id.into(),
+        source: value.source_wallet_id.into(),
+        destination: value.destination_wallet_id.into(),
+        executor: value.executor_actor_id.map(Into::into),
+        amount: Money::from_minor(value.amount_cents),
+        currency: value.currency,
+        description: value.description,
+        reversal_of: value.reversal_of.map(Into::into),
+        metadata: metadata_from_row(value.metadata),
+        created_at: value.created_at,
+        updated_at: value.updated_at,
+      },
+      source_label: value.source_label.map(|l| WalletLabel::from(l.as_str())),
+      destination_label: value
+        .destination_label
+        .map(|l| WalletLabel::from(l.as_str())),
+      executor_name: value.executor_name,
+    }
+  }
+}
+
+fn metadata_from_row(value: Value) -> TransactionMetadata {
+  TransactionMetadata::from_stored(serde_json::from_value(value).unwrap_or_default())
+}
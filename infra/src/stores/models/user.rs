@@ -1,5 +1,8 @@
-use chrono::{DateTime, Utc};
-use domain::{ActorId, Email, HashedPassword, Role, User};
+use chrono::{DateTime, Duration, Utc};
+use domain::{
+  types::SortOrder, ActorId, Email, EmailChange, HashedPassword, Locale, PasswordSetup, Permission,
+  PermissionOverride, Role, User, UserId, UserSortField,
+};
 use sqlx::prelude::FromRow;
 use uuid::Uuid;
 
@@ -12,6 +15,34 @@ pub(crate) struct UserRow {
   pub first_name: String,
   pub last_name: String,
   pub role: String,
+  pub locale: String,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone, FromRow)]
+pub(crate) struct PermissionOverrideRow {
+  pub permission: Permission,
+  pub granted: bool,
+}
+
+#[derive(Clone, FromRow)]
+pub(crate) struct EmailChangeRow {
+  pub id: Uuid,
+  pub user_id: Uuid,
+  pub email: String,
+  pub token: String,
+  pub expires_at: DateTime<Utc>,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone, FromRow)]
+pub(crate) struct PasswordSetupRow {
+  pub id: Uuid,
+  pub user_id: Uuid,
+  pub token: String,
+  pub expires_at: DateTime<Utc>,
   pub created_at: DateTime<Utc>,
   pub updated_at: Option<DateTime<Utc>>,
 }
@@ -24,6 +55,7 @@ pub struct UserCreation {
   pub first_name: String,
   pub last_name: String,
   pub role: Role,
+  pub locale: Locale,
 }
 
 #[derive(Clone)]
@@ -33,6 +65,31 @@ pub struct UserUpdate {
   pub first_name: Option<String>,
   pub last_name: Option<String>,
   pub role: Option<Role>,
+  pub locale: Option<Locale>,
+}
+
+#[derive(Clone)]
+pub struct EmailChangeCreation {
+  pub user_id: UserId,
+  pub email: Email,
+  pub token: String,
+  pub expires_in: Duration,
+}
+
+#[derive(Clone)]
+pub struct PasswordSetupCreation {
+  pub user_id: UserId,
+  pub token: String,
+  pub expires_in: Duration,
+}
+
+#[derive(Clone, Default)]
+pub struct UserFilter {
+  pub role: Option<Role>,
+  /// Case-insensitive substring matched against email, first and last name
+  pub search: Option<String>,
+  pub sort_by: UserSortField,
+  pub sort_order: SortOrder,
 }
 
 impl From<UserRow> for User {
@@ -45,8 +102,45 @@ impl From<UserRow> for User {
       first_name: value.first_name,
       last_name: value.last_name,
       role: value.role.into(),
+      locale: Locale::from(value.locale.as_str()),
+      created_at: value.created_at,
+      updated_at: value.updated_at,
+    }
+  }
+}
+
+impl From<EmailChangeRow> for EmailChange {
+  fn from(value: EmailChangeRow) -> Self {
+    Self {
+      id: value.id.into(),
+      user_id: value.user_id.into(),
+      email: value.email.into(),
+      token: value.token,
+      expires_at: value.expires_at,
+      created_at: value.created_at,
+      updated_at: value.updated_at,
+    }
+  }
+}
+
+impl From<PasswordSetupRow> for PasswordSetup {
+  fn from(value: PasswordSetupRow) -> Self {
+    Self {
+      id: value.id.into(),
+      user_id: value.user_id.into(),
+      token: value.token,
+      expires_at: value.expires_at,
       created_at: value.created_at,
       updated_at: value.updated_at,
     }
   }
 }
+
+impl From<PermissionOverrideRow> for PermissionOverride {
+  fn from(value: PermissionOverrideRow) -> Self {
+    Self {
+      permission: value.permission,
+      granted: value.granted,
+    }
+  }
+}
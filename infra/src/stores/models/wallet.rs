@@ -1,14 +1,71 @@
 use chrono::{DateTime, Utc};
-use domain::{wallet::WalletLabel, ActorId, Wallet};
+use domain::{
+  types::Currency, wallet::WalletLabel, ActorDetails, ActorId, LegalHoldAction, Wallet,
+  WalletAppearance, WalletDetails, WalletId, WalletLegalHoldEvent,
+};
 use sqlx::prelude::FromRow;
 use uuid::Uuid;
 
+use crate::stores::{
+  models::{guest::GuestRow, user::UserRow},
+  money_from_sum,
+};
+
 #[derive(Clone, FromRow)]
 pub(crate) struct WalletRow {
   pub id: Uuid,
   pub owner_actor_id: Option<Uuid>,
   pub label: Option<String>,
+  pub currency: Currency,
+  pub allow_overdraft: bool,
+  pub legal_hold: bool,
+  pub frozen: bool,
+  pub display_name: Option<String>,
+  pub color: Option<String>,
+  pub icon: Option<String>,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// A wallet joined with the user or guest owning it.
+#[derive(Clone, FromRow)]
+pub(crate) struct WalletDetailsRow {
+  pub id: Uuid,
+  pub owner_actor_id: Option<Uuid>,
+  pub label: Option<String>,
+  pub currency: Currency,
   pub allow_overdraft: bool,
+  pub legal_hold: bool,
+  pub frozen: bool,
+  pub display_name: Option<String>,
+  pub color: Option<String>,
+  pub icon: Option<String>,
+  pub balance_cents: i64,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: Option<DateTime<Utc>>,
+  pub user_id: Option<Uuid>,
+  pub user_email: Option<String>,
+  pub user_first_name: Option<String>,
+  pub user_last_name: Option<String>,
+  pub user_role: Option<String>,
+  pub user_locale: Option<String>,
+  pub user_created_at: Option<DateTime<Utc>>,
+  pub user_updated_at: Option<DateTime<Utc>>,
+  pub guest_id: Option<Uuid>,
+  pub guest_email: Option<String>,
+  pub guest_verified: Option<bool>,
+  pub guest_identifier: Option<String>,
+  pub guest_created_at: Option<DateTime<Utc>>,
+  pub guest_updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone, FromRow)]
+pub(crate) struct WalletLegalHoldEventRow {
+  pub id: Uuid,
+  pub wallet_id: Uuid,
+  pub actor_id: Option<Uuid>,
+  pub action: String,
+  pub reason: String,
   pub created_at: DateTime<Utc>,
   pub updated_at: Option<DateTime<Utc>>,
 }
@@ -17,6 +74,7 @@ pub(crate) struct WalletRow {
 pub struct WalletCreation {
   pub owner: Option<ActorId>,
   pub label: Option<WalletLabel>,
+  pub currency: Currency,
   pub allow_overdraft: bool,
 }
 
@@ -26,13 +84,122 @@ pub struct WalletUpdate {
   pub allow_overdraft: Option<bool>,
 }
 
+#[derive(Clone, Default)]
+pub struct WalletFilter {
+  /// Only system wallets if true, only member wallets if false, both if unset
+  pub system: Option<bool>,
+  /// Case-insensitive substring matched against the owner's email, names and
+  /// guest identifier
+  pub search: Option<String>,
+}
+
+#[derive(Clone)]
+pub struct WalletLegalHoldEventCreation {
+  pub wallet_id: WalletId,
+  pub actor_id: Option<ActorId>,
+  pub action: LegalHoldAction,
+  pub reason: String,
+}
+
 impl From<WalletRow> for Wallet {
   fn from(value: WalletRow) -> Self {
     Self {
       id: value.id.into(),
       owner: value.owner_actor_id.map(Into::into),
       label: value.label.map(|l| l.as_str().into()),
+      currency: value.currency,
       allow_overdraft: value.allow_overdraft,
+      legal_hold: value.legal_hold,
+      frozen: value.frozen,
+      appearance: WalletAppearance::from_stored(value.display_name, value.color, value.icon),
+      created_at: value.created_at,
+      updated_at: value.updated_at,
+    }
+  }
+}
+
+impl TryFrom<WalletDetailsRow> for WalletDetails {
+  type Error = sqlx::Error;
+
+  fn try_from(value: WalletDetailsRow) -> Result<Self, Self::Error> {
+    let owner = value.owner_actor_id.and_then(|actor_id| {
+      let user = match (
+        value.user_id,
+        value.user_email,
+        value.user_first_name,
+        value.user_last_name,
+        value.user_role,
+        value.user_locale,
+        value.user_created_at,
+      ) {
+        (
+          Some(id),
+          Some(email),
+          Some(first_name),
+          Some(last_name),
+          Some(role),
+          Some(locale),
+          Some(created_at),
+        ) => Some(UserRow {
+          id,
+          actor_id,
+          email,
+          // Owners are listed, never signed in; the hash isn't selected
+          password_hash: String::new(),
+          first_name,
+          last_name,
+          role,
+          locale,
+          created_at,
+          updated_at: value.user_updated_at,
+        }),
+        _ => None,
+      };
+      let guest = match (value.guest_id, value.guest_verified, value.guest_created_at) {
+        (Some(id), Some(verified), Some(created_at)) => Some(GuestRow {
+          id,
+          actor_id,
+          email: value.guest_email,
+          verified,
+          identifier: value.guest_identifier,
+          created_at,
+          updated_at: value.guest_updated_at,
+        }),
+        _ => None,
+      };
+
+      user
+        .map(|row| ActorDetails::User(row.into()))
+        .or_else(|| guest.map(|row| ActorDetails::Guest(row.into())))
+    });
+
+    Ok(Self {
+      wallet: Wallet {
+        id: value.id.into(),
+        owner: value.owner_actor_id.map(Into::into),
+        label: value.label.map(|l| l.as_str().into()),
+        currency: value.currency,
+        allow_overdraft: value.allow_overdraft,
+        legal_hold: value.legal_hold,
+        frozen: value.frozen,
+        appearance: WalletAppearance::from_stored(value.display_name, value.color, value.icon),
+        created_at: value.created_at,
+        updated_at: value.updated_at,
+      },
+      owner,
+      balance: money_from_sum(Some(value.balance_cents), "balance_cents")?,
+    })
+  }
+}
+
+impl From<WalletLegalHoldEventRow> for WalletLegalHoldEvent {
+  fn from(value: WalletLegalHoldEventRow) -> Self {
+    Self {
+      id: value.id.into(),
+      wallet_id: value.wallet_id.into(),
+      actor_id: value.actor_id.map(Into::into),
+      action: value.action.as_str().into(),
+      reason: value.reason,
       created_at: value.created_at,
       updated_at: value.updated_at,
     }
@@ -0,0 +1,99 @@
+use chrono::{DateTime, Utc};
+use domain::{
+  ActorId, ShopId, Webhook, WebhookDelivery, WebhookDeliveryStatus, WebhookEvent, WebhookId,
+};
+use sqlx::prelude::FromRow;
+use uuid::Uuid;
+
+#[derive(Clone, FromRow)]
+pub(crate) struct WebhookRow {
+  pub id: Uuid,
+  pub shop_id: Option<Uuid>,
+  pub url: String,
+  pub secret: String,
+  pub events: Vec<String>,
+  pub created_by_actor_id: Option<Uuid>,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone, FromRow)]
+pub(crate) struct WebhookDeliveryRow {
+  pub id: Uuid,
+  pub webhook_id: Uuid,
+  pub event: String,
+  pub payload: serde_json::Value,
+  pub status: String,
+  pub attempts: i32,
+  pub response_status: Option<i32>,
+  pub last_error: Option<String>,
+  pub delivered_at: Option<DateTime<Utc>>,
+  pub created_at: DateTime<Utc>,
+  pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone)]
+pub struct WebhookCreation {
+  pub shop_id: Option<ShopId>,
+  pub url: String,
+  pub secret: String,
+  pub events: Vec<WebhookEvent>,
+  pub created_by: Option<ActorId>,
+}
+
+fn decode_event(index: &str, value: &str) -> Result<WebhookEvent, sqlx::Error> {
+  WebhookEvent::try_from(value).map_err(|e| sqlx::Error::ColumnDecode {
+    index: index.to_string(),
+    source: e.into(),
+  })
+}
+
+impl TryFrom<WebhookRow> for Webhook {
+  type Error = sqlx::Error;
+
+  fn try_from(value: WebhookRow) -> Result<Self, Self::Error> {
+    let events = value
+      .events
+      .iter()
+      .map(|e| decode_event("events", e))
+      .collect::<Result<_, _>>()?;
+
+    Ok(Self {
+      id: value.id.into(),
+      shop_id: value.shop_id.map(Into::into),
+      url: value.url,
+      secret: value.secret,
+      events,
+      created_by: value.created_by_actor_id.map(Into::into),
+      created_at: value.created_at,
+      updated_at: value.updated_at,
+    })
+  }
+}
+
+#[derive(Clone)]
+pub struct WebhookDeliveryCreation {
+  pub webhook_id: WebhookId,
+  pub event: WebhookEvent,
+  pub payload: serde_json::Value,
+}
+
+impl TryFrom<WebhookDeliveryRow> for WebhookDelivery {
+  type Error = sqlx::Error;
+
+  fn try_from(value: WebhookDeliveryRow) -> Result<Self, Self::Error> {
+    Ok(Self {
+      id: value.id.into(),
+      webhook_id: value.webhook_id.into(),
+      event: decode_event("event", &value.event)?,
+      payload: value.payload,
+      status: WebhookDeliveryStatus::from(value.status.as_str()),
+      attempts: value.attempts,
+      response_status: value.response_status,
+      last_error: value.last_error,
+      delivered_at: value.delivered_at,
+      created_at: value.created_at,
+      updated_at: value.updated_at,
+    })
+  }
+}
@@ -0,0 +1,76 @@
+use domain::{AccountNote, ActorId};
+use sqlx::{Executor, Postgres};
+
+use crate::stores::models::note::{AccountNoteCreation, AccountNoteRow};
+
+pub struct AccountNoteStore;
+
+impl AccountNoteStore {
+  pub async fn create<'c, E>(
+    executor: E,
+    creation: &AccountNoteCreation,
+  ) -> Result<AccountNote, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query_as!(
+      AccountNoteRow,
+      r#"
+      INSERT INTO account_notes (actor_id, author_actor_id, body)
+      VALUES ($1, $2, $3)
+      RETURNING id, actor_id, author_actor_id, body, created_at, updated_at
+      "#,
+      creation.actor_id.into_inner(),
+      creation.author.into_inner(),
+      creation.body,
+    )
+    .fetch_one(executor)
+    .await?;
+
+    Ok(row.into())
+  }
+
+  /// Newest first.
+  pub async fn list_by_actor_id<'c, E>(
+    executor: E,
+    actor_id: &ActorId,
+  ) -> Result<Vec<AccountNote>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let rows = sqlx::query_as!(
+      AccountNoteRow,
+      r#"
+      SELECT id, actor_id, author_actor_id, body, created_at, updated_at
+      FROM account_notes
+      WHERE actor_id = $1
+      ORDER BY created_at DESC, id DESC
+      "#,
+      actor_id.into_inner(),
+    )
+    .fetch_all(executor)
+    .await?;
+
+    Ok(rows.into_iter().map(Into::into).collect())
+  }
+
+  pub async fn delete_by_actor_id<'c, E>(
+    executor: E,
+    actor_id: &ActorId,
+  ) -> Result<u64, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let result = sqlx::query!(
+      r#"
+      DELETE FROM account_notes
+      WHERE actor_id = $1
+      "#,
+      actor_id.into_inner(),
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(result.rows_affected())
+  }
+}
@@ -0,0 +1,20 @@
+use sqlx::{Executor, Postgres};
+
+/// Postgres `NOTIFY`, to reach every server instance connected to the
+/// database.
+pub struct NotificationStore;
+
+impl NotificationStore {
+  /// Delivers `payload` to every connection listening on `channel`; within a
+  /// transaction, once it commits.
+  pub async fn notify<'c, E>(executor: E, channel: &str, payload: &str) -> Result<(), sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    sqlx::query!("SELECT pg_notify($1, $2)", channel, payload)
+      .execute(executor)
+      .await?;
+
+    Ok(())
+  }
+}
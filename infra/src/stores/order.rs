@@ -0,0 +1,557 @@
+use sqlx::{Executor, Postgres};
+
+use chrono::{DateTime, Utc};
+
+use crate::stores::models::order::{
+  DailySalesRow, OfferingSalesRow, OrderCreation, OrderFilter, OrderItemCreation, OrderItemRow,
+  OrderRow, PurchaserRow, SalesTotals,
+};
+use domain::{
+  types::{PageRequest, VenueTimezone},
+  DailySales, OfferingSales, Order, OrderId, OrderItem, OrderStatus, Purchaser, ShopId,
+  TransactionId,
+};
+
+pub struct OrderStore;
+
+impl OrderStore {
+  pub async fn create<'c, E>(executor: E, creation: &OrderCreation) -> Result<Order, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query_as!(
+      OrderRow,
+      r#"
+      INSERT INTO orders (shop_id, payer_wallet_id, transaction_id, executor_actor_id, total_cents, table_label, pickup_number, pickup_at)
+      VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+      RETURNING id, shop_id, payer_wallet_id, transaction_id, executor_actor_id, total_cents, status, table_label, pickup_number, pickup_at, created_at, updated_at
+      "#,
+      creation.shop_id.into_inner(),
+      creation.payer_wallet_id.into_inner(),
+      creation.transaction_id.map(|id| id.into_inner()),
+      creation.executor.as_ref().map(|e| e.into_inner()),
+      creation.total.as_minor(),
+      creation.table_label,
+      creation.pickup_number,
+      creation.pickup_at,
+    )
+    .fetch_one(executor)
+    .await?;
+
+    Ok(row.into())
+  }
+
+  pub async fn find_by_id<'c, E>(executor: E, id: &OrderId) -> Result<Option<Order>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query_as!(
+      OrderRow,
+      r#"
+      SELECT id, shop_id, payer_wallet_id, transaction_id, executor_actor_id, total_cents, status, table_label, pickup_number, pickup_at, created_at, updated_at
+      FROM orders
+      WHERE id = $1
+      "#,
+      id.into_inner()
+    )
+    .fetch_optional(executor)
+    .await?;
+
+    Ok(row.map(Into::into))
+  }
+
+  pub async fn find_by_transaction_id<'c, E>(
+    executor: E,
+    transaction_id: &TransactionId,
+  ) -> Result<Option<Order>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query_as!(
+      OrderRow,
+      r#"
+      SELECT id, shop_id, payer_wallet_id, transaction_id, executor_actor_id, total_cents, status, table_label, pickup_number, pickup_at, created_at, updated_at
+      FROM orders
+      WHERE transaction_id = $1
+      "#,
+      transaction_id.into_inner()
+    )
+    .fetch_optional(executor)
+    .await?;
+
+    Ok(row.map(Into::into))
+  }
+
+  /// Locks the order until the surrounding transaction ends.
+  pub async fn find_by_id_for_update<'c, E>(
+    executor: E,
+    id: &OrderId,
+  ) -> Result<Option<Order>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query_as!(
+      OrderRow,
+      r#"
+      SELECT id, shop_id, payer_wallet_id, transaction_id, executor_actor_id, total_cents, status, table_label, pickup_number, pickup_at, created_at, updated_at
+      FROM orders
+      WHERE id = $1
+      FOR UPDATE
+      "#,
+      id.into_inner()
+    )
+    .fetch_optional(executor)
+    .await?;
+
+    Ok(row.map(Into::into))
+  }
+
+  /// Who paid for the order, limited to what its shop's staff may see. Only
+  /// the last name's initial leaves the database.
+  pub async fn find_purchaser<'c, E>(
+    executor: E,
+    id: &OrderId,
+  ) -> Result<Option<Purchaser>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query_as!(
+      PurchaserRow,
+      r#"
+      SELECT
+        o.id AS order_id,
+        CASE
+          WHEN u.id IS NOT NULL THEN 'user'
+          WHEN g.id IS NOT NULL THEN 'guest'
+        END AS kind,
+        CASE WHEN u.deleted_at IS NULL THEN u.first_name END AS first_name,
+        CASE WHEN u.deleted_at IS NULL THEN left(u.last_name, 1) END AS last_initial
+      FROM orders o
+      JOIN wallets w ON w.id = o.payer_wallet_id
+      LEFT JOIN users u ON u.actor_id = w.owner_actor_id
+      LEFT JOIN guests g ON g.actor_id = w.owner_actor_id
+      WHERE o.id = $1
+      "#,
+      id.into_inner()
+    )
+    .fetch_optional(executor)
+    .await?;
+
+    Ok(row.map(Into::into))
+  }
+
+  /// Records the payment of an order paid at pickup. Nothing is returned when
+  /// the order has been paid already.
+  pub async fn set_transaction_id<'c, E>(
+    executor: E,
+    id: &OrderId,
+    transaction_id: &TransactionId,
+  ) -> Result<Option<Order>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query_as!(
+      OrderRow,
+      r#"
+      UPDATE orders
+      SET transaction_id = $2
+      WHERE id = $1 AND transaction_id IS NULL
+      RETURNING id, shop_id, payer_wallet_id, transaction_id, executor_actor_id, total_cents, status, table_label, pickup_number, pickup_at, created_at, updated_at
+      "#,
+      id.into_inner(),
+      transaction_id.into_inner(),
+    )
+    .fetch_optional(executor)
+    .await?;
+
+    Ok(row.map(Into::into))
+  }
+
+  /// Pre-orders per pickup slot starting within `[from, to)`.
+  pub async fn count_by_pickup_at<'c, E>(
+    executor: E,
+    shop_id: &ShopId,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+  ) -> Result<Vec<(DateTime<Utc>, i64)>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let rows = sqlx::query!(
+      r#"
+      SELECT pickup_at AS "pickup_at!", COUNT(*) AS "count!"
+      FROM orders
+      WHERE shop_id = $1 AND pickup_at >= $2 AND pickup_at < $3
+      GROUP BY pickup_at
+      ORDER BY pickup_at
+      "#,
+      shop_id.into_inner(),
+      from,
+      to,
+    )
+    .fetch_all(executor)
+    .await?;
+
+    Ok(rows.into_iter().map(|r| (r.pickup_at, r.count)).collect())
+  }
+
+  /// When the shop's orders paid since `since` were paid, oldest first.
+  pub async fn list_paid_at_since<'c, E>(
+    executor: E,
+    shop_id: &ShopId,
+    since: DateTime<Utc>,
+  ) -> Result<Vec<DateTime<Utc>>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let rows = sqlx::query!(
+      r#"
+      SELECT t.created_at
+      FROM orders o
+      JOIN transactions t ON t.id = o.transaction_id
+      WHERE o.shop_id = $1 AND t.created_at >= $2
+      ORDER BY t.created_at
+      "#,
+      shop_id.into_inner(),
+      since,
+    )
+    .fetch_all(executor)
+    .await?;
+
+    Ok(rows.into_iter().map(|r| r.created_at).collect())
+  }
+
+  /// Puts pre-orders into their shop's queue once their pickup slot is at most
+  /// one slot length away, and returns them.
+  pub async fn release_due<'c, E>(
+    executor: E,
+    now: DateTime<Utc>,
+  ) -> Result<Vec<Order>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let rows = sqlx::query_as!(
+      OrderRow,
+      r#"
+      UPDATE orders o
+      SET released_at = $1
+      FROM shops s
+      WHERE s.id = o.shop_id
+        AND o.pickup_at IS NOT NULL
+        AND o.released_at IS NULL
+        AND o.pickup_at - make_interval(mins => COALESCE(s.pickup_slot_minutes, 0)) <= $1
+      RETURNING o.id, o.shop_id, o.payer_wallet_id, o.transaction_id, o.executor_actor_id, o.total_cents, o.status, o.table_label, o.pickup_number, o.pickup_at, o.created_at, o.updated_at
+      "#,
+      now,
+    )
+    .fetch_all(executor)
+    .await?;
+
+    Ok(rows.into_iter().map(Into::into).collect())
+  }
+
+  /// Moves the order on unless someone else changed its status since it was
+  /// read as `from`, in which case nothing is returned.
+  pub async fn update_status<'c, E>(
+    executor: E,
+    id: &OrderId,
+    from: OrderStatus,
+    to: OrderStatus,
+  ) -> Result<Option<Order>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query_as!(
+      OrderRow,
+      r#"
+      UPDATE orders
+      SET status = $3
+      WHERE id = $1 AND status = $2
+      RETURNING id, shop_id, payer_wallet_id, transaction_id, executor_actor_id, total_cents, status, table_label, pickup_number, pickup_at, created_at, updated_at
+      "#,
+      id.into_inner(),
+      from.to_string(),
+      to.to_string(),
+    )
+    .fetch_optional(executor)
+    .await?;
+
+    Ok(row.map(Into::into))
+  }
+
+  /// Orders of the shop that have not been collected yet, oldest first.
+  /// Pre-orders only show up once released.
+  pub async fn list_open_by_shop_id<'c, E>(
+    executor: E,
+    shop_id: &ShopId,
+  ) -> Result<Vec<Order>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let rows = sqlx::query_as!(
+      OrderRow,
+      r#"
+      SELECT id, shop_id, payer_wallet_id, transaction_id, executor_actor_id, total_cents, status, table_label, pickup_number, pickup_at, created_at, updated_at
+      FROM orders
+      WHERE shop_id = $1 AND status <> $2
+        AND (pickup_at IS NULL OR released_at IS NOT NULL)
+      ORDER BY COALESCE(pickup_at, created_at), id
+      "#,
+      shop_id.into_inner(),
+      OrderStatus::Collected.to_string(),
+    )
+    .fetch_all(executor)
+    .await?;
+
+    Ok(rows.into_iter().map(Into::into).collect())
+  }
+
+  /// Newest orders first.
+  pub async fn list_paginated<'c, E>(
+    executor: E,
+    filter: &OrderFilter,
+    page: &PageRequest,
+  ) -> Result<Vec<Order>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let rows = sqlx::query_as!(
+      OrderRow,
+      r#"
+      SELECT id, shop_id, payer_wallet_id, transaction_id, executor_actor_id, total_cents, status, table_label, pickup_number, pickup_at, created_at, updated_at
+      FROM orders
+      WHERE ($1::uuid IS NULL OR shop_id = $1)
+        AND ($2::uuid IS NULL OR payer_wallet_id = $2)
+        AND ($3::timestamptz IS NULL OR created_at >= $3)
+        AND ($4::timestamptz IS NULL OR created_at < $4)
+      ORDER BY created_at DESC, id DESC
+      LIMIT $5 OFFSET $6
+      "#,
+      filter.shop_id.map(|id| id.into_inner()),
+      filter.payer_wallet_id.map(|id| id.into_inner()),
+      filter.from,
+      filter.to,
+      page.limit(),
+      page.offset(),
+    )
+    .fetch_all(executor)
+    .await?;
+
+    Ok(rows.into_iter().map(Into::into).collect())
+  }
+
+  pub async fn count<'c, E>(executor: E, filter: &OrderFilter) -> Result<i64, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let count = sqlx::query_scalar!(
+      r#"
+      SELECT COUNT(*) AS "count!"
+      FROM orders
+      WHERE ($1::uuid IS NULL OR shop_id = $1)
+        AND ($2::uuid IS NULL OR payer_wallet_id = $2)
+        AND ($3::timestamptz IS NULL OR created_at >= $3)
+        AND ($4::timestamptz IS NULL OR created_at < $4)
+      "#,
+      filter.shop_id.map(|id| id.into_inner()),
+      filter.payer_wallet_id.map(|id| id.into_inner()),
+      filter.from,
+      filter.to,
+    )
+    .fetch_one(executor)
+    .await?;
+
+    Ok(count)
+  }
+}
+
+/// Aggregates over a shop's orders within `[from, to)`; unset bounds are open.
+pub struct SalesReportStore;
+
+impl SalesReportStore {
+  pub async fn totals<'c, E>(
+    executor: E,
+    shop_id: &ShopId,
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+  ) -> Result<SalesTotals, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query!(
+      r#"
+      SELECT COUNT(*) AS "order_count!",
+             COALESCE(SUM(o.total_cents), 0)::bigint AS "gross_cents!",
+             COALESCE(SUM(o.total_cents) FILTER (
+               WHERE EXISTS (SELECT 1 FROM transactions r WHERE r.reversal_of = o.transaction_id)
+             ), 0)::bigint AS "refunded_cents!"
+      FROM orders o
+      WHERE o.shop_id = $1
+        AND o.transaction_id IS NOT NULL
+        AND ($2::timestamptz IS NULL OR o.created_at >= $2)
+        AND ($3::timestamptz IS NULL OR o.created_at < $3)
+      "#,
+      shop_id.into_inner(),
+      from,
+      to,
+    )
+    .fetch_one(executor)
+    .await?;
+
+    Ok(SalesTotals {
+      order_count: row.order_count,
+      gross_cents: row.gross_cents,
+      refunded_cents: row.refunded_cents,
+    })
+  }
+
+  /// Best sellers first.
+  pub async fn by_offering<'c, E>(
+    executor: E,
+    shop_id: &ShopId,
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+  ) -> Result<Vec<OfferingSales>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let rows = sqlx::query_as!(
+      OfferingSalesRow,
+      r#"
+      SELECT i.offering_id,
+             i.name,
+             SUM(i.quantity)::bigint AS "quantity!",
+             SUM(i.unit_price_cents::bigint * i.quantity)::bigint AS "revenue_cents!"
+      FROM order_items i
+      JOIN orders o ON o.id = i.order_id
+      WHERE o.shop_id = $1
+        AND o.transaction_id IS NOT NULL
+        AND ($2::timestamptz IS NULL OR o.created_at >= $2)
+        AND ($3::timestamptz IS NULL OR o.created_at < $3)
+      GROUP BY i.offering_id, i.name
+      ORDER BY 4 DESC, i.name
+      "#,
+      shop_id.into_inner(),
+      from,
+      to,
+    )
+    .fetch_all(executor)
+    .await?;
+
+    Ok(rows.into_iter().map(Into::into).collect())
+  }
+
+  /// Sales per calendar day at the venue in `timezone`.
+  pub async fn by_day<'c, E>(
+    executor: E,
+    shop_id: &ShopId,
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+    timezone: VenueTimezone,
+  ) -> Result<Vec<DailySales>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let rows = sqlx::query_as!(
+      DailySalesRow,
+      r#"
+      SELECT (o.created_at AT TIME ZONE $4)::date AS "day!",
+             COUNT(*) AS "order_count!",
+             SUM(o.total_cents)::bigint AS "revenue_cents!"
+      FROM orders o
+      WHERE o.shop_id = $1
+        AND o.transaction_id IS NOT NULL
+        AND ($2::timestamptz IS NULL OR o.created_at >= $2)
+        AND ($3::timestamptz IS NULL OR o.created_at < $3)
+      GROUP BY 1
+      ORDER BY 1
+      "#,
+      shop_id.into_inner(),
+      from,
+      to,
+      timezone.name(),
+    )
+    .fetch_all(executor)
+    .await?;
+
+    Ok(rows.into_iter().map(Into::into).collect())
+  }
+}
+
+pub struct OrderItemStore;
+
+impl OrderItemStore {
+  pub async fn create<'c, E>(
+    executor: E,
+    creation: &OrderItemCreation,
+  ) -> Result<OrderItem, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query_as!(
+      OrderItemRow,
+      r#"
+      INSERT INTO order_items (order_id, offering_id, name, unit_price_cents, quantity)
+      VALUES ($1, $2, $3, $4, $5)
+      RETURNING id, order_id, offering_id, name, unit_price_cents, quantity, created_at, updated_at
+      "#,
+      creation.order_id.into_inner(),
+      creation.offering_id.as_ref().map(|o| o.into_inner()),
+      creation.name,
+      creation.unit_price.as_minor(),
+      creation.quantity,
+    )
+    .fetch_one(executor)
+    .await?;
+
+    Ok(row.into())
+  }
+
+  pub async fn list_by_order_id<'c, E>(
+    executor: E,
+    order_id: &OrderId,
+  ) -> Result<Vec<OrderItem>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let rows = sqlx::query_as!(
+      OrderItemRow,
+      r#"
+      SELECT id, order_id, offering_id, name, unit_price_cents, quantity, created_at, updated_at
+      FROM order_items
+      WHERE order_id = $1
+      ORDER BY created_at
+      "#,
+      order_id.into_inner()
+    )
+    .fetch_all(executor)
+    .await?;
+
+    Ok(rows.into_iter().map(Into::into).collect())
+  }
+
+  pub async fn list_by_order_ids<'c, E>(
+    executor: E,
+    order_ids: &[OrderId],
+  ) -> Result<Vec<OrderItem>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let ids: Vec<_> = order_ids.iter().map(|id| id.into_inner()).collect();
+
+    let rows = sqlx::query_as!(
+      OrderItemRow,
+      r#"
+      SELECT id, order_id, offering_id, name, unit_price_cents, quantity, created_at, updated_at
+      FROM order_items
+      WHERE order_id = ANY($1)
+      ORDER BY created_at
+      "#,
+      &ids
+    )
+    .fetch_all(executor)
+    .await?;
+
+    Ok(rows.into_iter().map(Into::into).collect())
+  }
+}
@@ -0,0 +1,128 @@
+use chrono::{DateTime, Utc};
+use domain::{OutboundEmail, OutboundEmailId};
+use sqlx::{Executor, Postgres};
+
+use crate::stores::models::outbound_email::{OutboundEmailCreation, OutboundEmailRow};
+
+pub struct OutboundEmailStore;
+
+impl OutboundEmailStore {
+  pub async fn create<'c, E>(
+    executor: E,
+    creation: &OutboundEmailCreation,
+  ) -> Result<OutboundEmail, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query_as!(
+      OutboundEmailRow,
+      r#"
+      INSERT INTO outbound_emails (recipient, subject, text_body, html_body)
+      VALUES ($1, $2, $3, $4)
+      RETURNING id, recipient, subject, text_body, html_body, status, attempts, last_error, sent_at,
+                created_at, updated_at
+      "#,
+      creation.recipient.expose(),
+      creation.subject,
+      creation.text_body,
+      creation.html_body,
+    )
+    .fetch_one(executor)
+    .await?;
+
+    Ok(row.into())
+  }
+
+  pub async fn find_by_id<'c, E>(
+    executor: E,
+    id: &OutboundEmailId,
+  ) -> Result<Option<OutboundEmail>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query_as!(
+      OutboundEmailRow,
+      r#"
+      SELECT id, recipient, subject, text_body, html_body, status, attempts, last_error, sent_at,
+             created_at, updated_at
+      FROM outbound_emails
+      WHERE id = $1
+      "#,
+      id.into_inner(),
+    )
+    .fetch_optional(executor)
+    .await?;
+
+    Ok(row.map(Into::into))
+  }
+
+  /// Records a successful delivery attempt.
+  pub async fn mark_sent<'c, E>(executor: E, id: &OutboundEmailId) -> Result<(), sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    sqlx::query!(
+      r#"
+      UPDATE outbound_emails
+      SET status = 'sent', attempts = attempts + 1, sent_at = now()
+      WHERE id = $1
+      "#,
+      id.into_inner(),
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(())
+  }
+
+  /// Records a failed delivery attempt; the email stays queued unless this
+  /// was the last one.
+  pub async fn mark_failed<'c, E>(
+    executor: E,
+    id: &OutboundEmailId,
+    error: &str,
+    gave_up: bool,
+  ) -> Result<(), sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    sqlx::query!(
+      r#"
+      UPDATE outbound_emails
+      SET status = CASE WHEN $3 THEN 'failed' ELSE 'queued' END,
+          attempts = attempts + 1,
+          last_error = $2
+      WHERE id = $1
+      "#,
+      id.into_inner(),
+      error,
+      gave_up,
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(())
+  }
+
+  /// Deletes emails sent before `cutoff`, which may hold tokens no longer
+  /// worth keeping. Failed ones stay for inspection.
+  pub async fn delete_sent_before<'c, E>(
+    executor: E,
+    cutoff: DateTime<Utc>,
+  ) -> Result<u64, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let result = sqlx::query!(
+      r#"
+      DELETE FROM outbound_emails
+      WHERE status = 'sent' AND sent_at < $1
+      "#,
+      cutoff,
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(result.rows_affected())
+  }
+}
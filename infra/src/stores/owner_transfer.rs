@@ -0,0 +1,104 @@
+use chrono::{DateTime, Utc};
+use domain::{OwnerTransfer, OwnerTransferId, OwnerTransferStatus};
+use sqlx::{Executor, Postgres};
+
+use crate::stores::models::owner_transfer::{OwnerTransferCreation, OwnerTransferRow};
+
+pub struct OwnerTransferStore;
+
+impl OwnerTransferStore {
+  pub async fn create<'c, E>(
+    executor: E,
+    creation: &OwnerTransferCreation,
+  ) -> Result<OwnerTransfer, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query_as!(
+      OwnerTransferRow,
+      r#"
+      INSERT INTO owner_transfers (from_user_id, to_user_id, demote_to, expires_at)
+      VALUES ($1, $2, $3, $4)
+      RETURNING id, from_user_id, to_user_id, demote_to, status, expires_at, decided_at, created_at, updated_at
+      "#,
+      creation.from_user_id.into_inner(),
+      creation.to_user_id.into_inner(),
+      creation.demote_to.to_string(),
+      creation.expires_at,
+    )
+    .fetch_one(executor)
+    .await?;
+
+    Ok(row.into())
+  }
+
+  /// The transfer waiting to be accepted, if any, even if it ran out.
+  pub async fn find_pending<'c, E>(executor: E) -> Result<Option<OwnerTransfer>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query_as!(
+      OwnerTransferRow,
+      r#"
+      SELECT id, from_user_id, to_user_id, demote_to, status, expires_at, decided_at, created_at, updated_at
+      FROM owner_transfers
+      WHERE status = 'pending'
+      "#,
+    )
+    .fetch_optional(executor)
+    .await?;
+
+    Ok(row.map(Into::into))
+  }
+
+  pub async fn find_by_id_for_update<'c, E>(
+    executor: E,
+    id: &OwnerTransferId,
+  ) -> Result<Option<OwnerTransfer>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query_as!(
+      OwnerTransferRow,
+      r#"
+      SELECT id, from_user_id, to_user_id, demote_to, status, expires_at, decided_at, created_at, updated_at
+      FROM owner_transfers
+      WHERE id = $1
+      FOR UPDATE
+      "#,
+      id.into_inner(),
+    )
+    .fetch_optional(executor)
+    .await?;
+
+    Ok(row.map(Into::into))
+  }
+
+  /// Closes a pending transfer; `None` if it was no longer pending.
+  pub async fn close<'c, E>(
+    executor: E,
+    id: &OwnerTransferId,
+    status: OwnerTransferStatus,
+    at: DateTime<Utc>,
+  ) -> Result<Option<OwnerTransfer>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query_as!(
+      OwnerTransferRow,
+      r#"
+      UPDATE owner_transfers
+      SET status = $2, decided_at = $3
+      WHERE id = $1 AND status = 'pending'
+      RETURNING id, from_user_id, to_user_id, demote_to, status, expires_at, decided_at, created_at, updated_at
+      "#,
+      id.into_inner(),
+      status.to_string(),
+      at,
+    )
+    .fetch_optional(executor)
+    .await?;
+
+    Ok(row.map(Into::into))
+  }
+}
@@ -0,0 +1,282 @@
+use domain::{
+  ActorId, BankAccount, Payout, PayoutBatch, PayoutBatchId, PayoutId, PayoutStatus, TransactionId,
+};
+use sqlx::{Executor, Postgres};
+
+use crate::stores::models::payout::{
+  BankAccountCreation, BankAccountRow, PayoutBatchRow, PayoutCreation, PayoutRow,
+};
+
+pub struct BankAccountStore;
+
+impl BankAccountStore {
+  /// Saves the actor's bank account, replacing the previous one.
+  pub async fn upsert<'c, E>(
+    executor: E,
+    creation: &BankAccountCreation,
+  ) -> Result<BankAccount, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query_as!(
+      BankAccountRow,
+      r#"
+      INSERT INTO bank_accounts (actor_id, iban, holder_name)
+      VALUES ($1, $2, $3)
+      ON CONFLICT (actor_id) DO UPDATE
+      SET iban = EXCLUDED.iban, holder_name = EXCLUDED.holder_name
+      RETURNING actor_id, iban AS "iban: _", holder_name, created_at, updated_at
+      "#,
+      creation.actor_id.into_inner(),
+      creation.iban.expose(),
+      creation.holder_name,
+    )
+    .fetch_one(executor)
+    .await?;
+
+    Ok(row.into())
+  }
+
+  pub async fn delete_by_actor_id<'c, E>(
+    executor: E,
+    actor_id: &ActorId,
+  ) -> Result<u64, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let result = sqlx::query!(
+      r#"
+      DELETE FROM bank_accounts
+      WHERE actor_id = $1
+      "#,
+      actor_id.into_inner(),
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(result.rows_affected())
+  }
+
+  pub async fn find_by_actor_id<'c, E>(
+    executor: E,
+    actor_id: &ActorId,
+  ) -> Result<Option<BankAccount>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query_as!(
+      BankAccountRow,
+      r#"
+      SELECT actor_id, iban AS "iban: _", holder_name, created_at, updated_at
+      FROM bank_accounts
+      WHERE actor_id = $1
+      "#,
+      actor_id.into_inner(),
+    )
+    .fetch_optional(executor)
+    .await?;
+
+    Ok(row.map(Into::into))
+  }
+}
+
+pub struct PayoutStore;
+
+impl PayoutStore {
+  pub async fn create<'c, E>(executor: E, creation: &PayoutCreation) -> Result<Payout, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query_as!(
+      PayoutRow,
+      r#"
+      INSERT INTO payouts (wallet_id, amount_cents, iban, holder_name, transaction_id, requested_by_actor_id)
+      VALUES ($1, $2, $3, $4, $5, $6)
+      RETURNING id, wallet_id, amount_cents, iban AS "iban: _", holder_name, status, batch_id, transaction_id, refund_transaction_id, requested_by_actor_id, created_at, updated_at
+      "#,
+      creation.wallet_id.into_inner(),
+      creation.amount.as_minor(),
+      creation.iban.expose(),
+      creation.holder_name,
+      creation.transaction_id.into_inner(),
+      creation.requested_by.map(|id| id.into_inner()),
+    )
+    .fetch_one(executor)
+    .await?;
+
+    Ok(row.into())
+  }
+
+  /// Locks the payout until the end of the transaction, so its outcome is
+  /// recorded only once.
+  pub async fn find_by_id_for_update<'c, E>(
+    executor: E,
+    id: &PayoutId,
+  ) -> Result<Option<Payout>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query_as!(
+      PayoutRow,
+      r#"
+      SELECT id, wallet_id, amount_cents, iban AS "iban: _", holder_name, status, batch_id, transaction_id, refund_transaction_id, requested_by_actor_id, created_at, updated_at
+      FROM payouts
+      WHERE id = $1
+      FOR UPDATE
+      "#,
+      id.into_inner(),
+    )
+    .fetch_optional(executor)
+    .await?;
+
+    Ok(row.map(Into::into))
+  }
+
+  /// Oldest first, optionally only those in `status`.
+  pub async fn list<'c, E>(
+    executor: E,
+    status: Option<PayoutStatus>,
+  ) -> Result<Vec<Payout>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let rows = sqlx::query_as!(
+      PayoutRow,
+      r#"
+      SELECT id, wallet_id, amount_cents, iban AS "iban: _", holder_name, status, batch_id, transaction_id, refund_transaction_id, requested_by_actor_id, created_at, updated_at
+      FROM payouts
+      WHERE ($1::text IS NULL OR status = $1)
+      ORDER BY created_at, id
+      "#,
+      status.map(|s| s.to_string()),
+    )
+    .fetch_all(executor)
+    .await?;
+
+    Ok(rows.into_iter().map(Into::into).collect())
+  }
+
+  pub async fn list_by_batch_id<'c, E>(
+    executor: E,
+    batch_id: &PayoutBatchId,
+  ) -> Result<Vec<Payout>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let rows = sqlx::query_as!(
+      PayoutRow,
+      r#"
+      SELECT id, wallet_id, amount_cents, iban AS "iban: _", holder_name, status, batch_id, transaction_id, refund_transaction_id, requested_by_actor_id, created_at, updated_at
+      FROM payouts
+      WHERE batch_id = $1
+      ORDER BY created_at, id
+      "#,
+      batch_id.into_inner(),
+    )
+    .fetch_all(executor)
+    .await?;
+
+    Ok(rows.into_iter().map(Into::into).collect())
+  }
+
+  /// Moves every requested payout into the batch and marks it exported.
+  pub async fn export_requested<'c, E>(
+    executor: E,
+    batch_id: &PayoutBatchId,
+  ) -> Result<Vec<Payout>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let rows = sqlx::query_as!(
+      PayoutRow,
+      r#"
+      UPDATE payouts
+      SET status = 'exported', batch_id = $1
+      WHERE status = 'requested'
+      RETURNING id, wallet_id, amount_cents, iban AS "iban: _", holder_name, status, batch_id, transaction_id, refund_transaction_id, requested_by_actor_id, created_at, updated_at
+      "#,
+      batch_id.into_inner(),
+    )
+    .fetch_all(executor)
+    .await?;
+
+    let mut payouts: Vec<Payout> = rows.into_iter().map(Into::into).collect();
+    payouts.sort_by_key(|p| (p.created_at, p.id.into_inner()));
+    Ok(payouts)
+  }
+
+  pub async fn set_status<'c, E>(
+    executor: E,
+    id: &PayoutId,
+    status: PayoutStatus,
+    refund_transaction_id: Option<TransactionId>,
+  ) -> Result<Option<Payout>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query_as!(
+      PayoutRow,
+      r#"
+      UPDATE payouts
+      SET status = $2, refund_transaction_id = $3
+      WHERE id = $1
+      RETURNING id, wallet_id, amount_cents, iban AS "iban: _", holder_name, status, batch_id, transaction_id, refund_transaction_id, requested_by_actor_id, created_at, updated_at
+      "#,
+      id.into_inner(),
+      status.to_string(),
+      refund_transaction_id.map(|id| id.into_inner()),
+    )
+    .fetch_optional(executor)
+    .await?;
+
+    Ok(row.map(Into::into))
+  }
+}
+
+pub struct PayoutBatchStore;
+
+impl PayoutBatchStore {
+  pub async fn create<'c, E>(
+    executor: E,
+    created_by: Option<ActorId>,
+  ) -> Result<PayoutBatch, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query_as!(
+      PayoutBatchRow,
+      r#"
+      INSERT INTO payout_batches (created_by_actor_id)
+      VALUES ($1)
+      RETURNING id, created_by_actor_id, created_at, updated_at
+      "#,
+      created_by.map(|id| id.into_inner()),
+    )
+    .fetch_one(executor)
+    .await?;
+
+    Ok(row.into())
+  }
+
+  pub async fn find_by_id<'c, E>(
+    executor: E,
+    id: &PayoutBatchId,
+  ) -> Result<Option<PayoutBatch>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query_as!(
+      PayoutBatchRow,
+      r#"
+      SELECT id, created_by_actor_id, created_at, updated_at
+      FROM payout_batches
+      WHERE id = $1
+      "#,
+      id.into_inner(),
+    )
+    .fetch_optional(executor)
+    .await?;
+
+    Ok(row.map(Into::into))
+  }
+}
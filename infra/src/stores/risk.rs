@@ -0,0 +1,161 @@
+use chrono::{DateTime, Utc};
+use domain::{ReviewItem, ReviewItemId, ReviewStatus, WalletId, WalletSecurityEventKind};
+use sqlx::{Executor, Postgres};
+
+use crate::stores::models::risk::{ReviewItemCreation, ReviewItemResolution, ReviewItemRow};
+
+pub struct ReviewItemStore;
+
+impl ReviewItemStore {
+  /// Opens a review, or returns `None` when the wallet already has an open
+  /// review for the signal.
+  pub async fn create<'c, E>(
+    executor: E,
+    creation: &ReviewItemCreation,
+  ) -> Result<Option<ReviewItem>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query_as!(
+      ReviewItemRow,
+      r#"
+      INSERT INTO review_items (wallet_id, signal, details, wallet_frozen)
+      VALUES ($1, $2, $3, $4)
+      ON CONFLICT (wallet_id, signal) WHERE status = 'open' DO NOTHING
+      RETURNING id, wallet_id, signal, details, status, wallet_frozen, resolved_by_actor_id, resolution_note, created_at, updated_at
+      "#,
+      creation.wallet_id.into_inner(),
+      creation.signal.to_string(),
+      creation.details,
+      creation.wallet_frozen,
+    )
+    .fetch_optional(executor)
+    .await?;
+
+    Ok(row.map(Into::into))
+  }
+
+  pub async fn resolve_by_id<'c, E>(
+    executor: E,
+    id: &ReviewItemId,
+    resolution: &ReviewItemResolution,
+  ) -> Result<Option<ReviewItem>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query_as!(
+      ReviewItemRow,
+      r#"
+      UPDATE review_items
+      SET status = $2,
+          resolved_by_actor_id = $3,
+          resolution_note = $4
+      WHERE id = $1
+      RETURNING id, wallet_id, signal, details, status, wallet_frozen, resolved_by_actor_id, resolution_note, created_at, updated_at
+      "#,
+      id.into_inner(),
+      resolution.status.to_string(),
+      resolution.resolved_by.into_inner(),
+      resolution.note,
+    )
+    .fetch_optional(executor)
+    .await?;
+
+    Ok(row.map(Into::into))
+  }
+
+  pub async fn find_by_id<'c, E>(
+    executor: E,
+    id: &ReviewItemId,
+  ) -> Result<Option<ReviewItem>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query_as!(
+      ReviewItemRow,
+      r#"
+      SELECT id, wallet_id, signal, details, status, wallet_frozen, resolved_by_actor_id, resolution_note, created_at, updated_at
+      FROM review_items
+      WHERE id = $1
+      "#,
+      id.into_inner(),
+    )
+    .fetch_optional(executor)
+    .await?;
+
+    Ok(row.map(Into::into))
+  }
+
+  pub async fn list_by_status<'c, E>(
+    executor: E,
+    status: &ReviewStatus,
+  ) -> Result<Vec<ReviewItem>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let rows = sqlx::query_as!(
+      ReviewItemRow,
+      r#"
+      SELECT id, wallet_id, signal, details, status, wallet_frozen, resolved_by_actor_id, resolution_note, created_at, updated_at
+      FROM review_items
+      WHERE status = $1
+      ORDER BY created_at ASC
+      "#,
+      status.to_string(),
+    )
+    .fetch_all(executor)
+    .await?;
+
+    Ok(rows.into_iter().map(Into::into).collect())
+  }
+}
+
+pub struct WalletSecurityEventStore;
+
+impl WalletSecurityEventStore {
+  pub async fn create<'c, E>(
+    executor: E,
+    wallet_id: &WalletId,
+    kind: &WalletSecurityEventKind,
+  ) -> Result<(), sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    sqlx::query!(
+      r#"
+      INSERT INTO wallet_security_events (wallet_id, kind)
+      VALUES ($1, $2)
+      "#,
+      wallet_id.into_inner(),
+      kind.to_string(),
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(())
+  }
+
+  pub async fn list_times_since<'c, E>(
+    executor: E,
+    wallet_id: &WalletId,
+    kind: &WalletSecurityEventKind,
+    since: DateTime<Utc>,
+  ) -> Result<Vec<DateTime<Utc>>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    sqlx::query_scalar!(
+      r#"
+      SELECT created_at
+      FROM wallet_security_events
+      WHERE wallet_id = $1 AND kind = $2 AND created_at >= $3
+      ORDER BY created_at DESC
+      "#,
+      wallet_id.into_inner(),
+      kind.to_string(),
+      since,
+    )
+    .fetch_all(executor)
+    .await
+  }
+}
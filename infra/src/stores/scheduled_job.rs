@@ -0,0 +1,34 @@
+use chrono::Duration;
+use sqlx::{Executor, Postgres};
+
+pub struct ScheduledJobStore;
+
+impl ScheduledJobStore {
+  /// Records that `job` starts now, unless it started less than `period` ago,
+  /// e.g. on another replica. Returns whether the caller claimed the run.
+  pub async fn try_claim<'c, E>(
+    executor: E,
+    job: &str,
+    period: Duration,
+  ) -> Result<bool, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let claimed = sqlx::query_scalar!(
+      r#"
+      INSERT INTO scheduled_job_runs (job, last_started_at)
+      VALUES ($1, now())
+      ON CONFLICT (job) DO UPDATE
+      SET last_started_at = now()
+      WHERE scheduled_job_runs.last_started_at <= now() - make_interval(secs => $2)
+      RETURNING job
+      "#,
+      job,
+      period.num_milliseconds() as f64 / 1000.0,
+    )
+    .fetch_optional(executor)
+    .await?;
+
+    Ok(claimed.is_some())
+  }
+}
@@ -1,7 +1,11 @@
-use domain::{Session, UserId};
+use chrono::{DateTime, Utc};
+use domain::{Email, PasswordConfirmation, Session, SessionId, SessionRevocation, UserId};
 use sqlx::{Executor, Postgres};
 
-use crate::stores::models::session::{SessionCreation, SessionRow};
+use crate::stores::models::session::{
+  LoginAttemptCreation, PasswordConfirmationCreation, PasswordConfirmationRow, SessionCreation,
+  SessionRevocationRow, SessionRow,
+};
 
 pub struct SessionStore;
 
@@ -16,14 +20,18 @@ impl SessionStore {
     let row = sqlx::query_as!(
       SessionRow,
       r#"
-      INSERT INTO sessions (user_id, token, user_agent, ip_address, expires_at)
-      VALUES ($1, $2, $3, $4, $5)
-      RETURNING id, user_id, token, user_agent, ip_address, expires_at, created_at, updated_at
+      INSERT INTO sessions (id, user_id, token, user_agent, ip_address, device_public_key,
+                            expires_at)
+      VALUES ($1, $2, $3, $4, $5, $6, $7)
+      RETURNING id, user_id, token, user_agent, ip_address, device_public_key, expires_at,
+                last_used_at, created_at, updated_at
       "#,
+      creation.id.into_inner(),
       creation.user_id.into_inner(),
       creation.token,
       creation.user_agent,
       creation.ip_address,
+      creation.device_public_key.as_ref().map(|key| key.expose()),
       chrono::Utc::now() + creation.expires_in,
     )
     .fetch_one(executor)
@@ -49,6 +57,196 @@ impl SessionStore {
     Ok(())
   }
 
+  pub async fn delete_by_id<'c, E>(executor: E, id: &SessionId) -> Result<(), sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    sqlx::query!(
+      r#"
+      DELETE FROM sessions
+      WHERE id = $1
+      "#,
+      id.into_inner(),
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(())
+  }
+
+  /// Signs the user out everywhere.
+  pub async fn delete_by_user_id<'c, E>(executor: E, user_id: &UserId) -> Result<u64, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let result = sqlx::query!(
+      r#"
+      DELETE FROM sessions
+      WHERE user_id = $1
+      "#,
+      user_id.into_inner(),
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(result.rows_affected())
+  }
+
+  /// Records that the session was just used.
+  ///
+  /// Writes are throttled to one per minute so hot sessions don't turn every
+  /// authenticated request into an UPDATE.
+  pub async fn touch_by_token<'c, E>(executor: E, token: &str) -> Result<(), sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    sqlx::query!(
+      r#"
+      UPDATE sessions
+      SET last_used_at = now()
+      WHERE token = $1
+        AND (last_used_at IS NULL OR last_used_at < now() - interval '1 minute')
+      "#,
+      token,
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(())
+  }
+
+  /// Moves the session's expiry to `expires_at`, keeping its token.
+  pub async fn extend_by_token<'c, E>(
+    executor: E,
+    token: &str,
+    expires_at: DateTime<Utc>,
+  ) -> Result<(), sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    sqlx::query!(
+      r#"
+      UPDATE sessions
+      SET expires_at = $2
+      WHERE token = $1
+      "#,
+      token,
+      expires_at,
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(())
+  }
+
+  /// Gives the session a new token and expiry. The old token stops working
+  /// right away.
+  pub async fn rotate_token<'c, E>(
+    executor: E,
+    token: &str,
+    new_token: &str,
+    expires_at: DateTime<Utc>,
+  ) -> Result<Option<Session>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query_as!(
+      SessionRow,
+      r#"
+      UPDATE sessions
+      SET token = $2, expires_at = $3, last_used_at = now()
+      WHERE token = $1 AND expires_at > now()
+      RETURNING id, user_id, token, user_agent, ip_address, device_public_key, expires_at,
+                last_used_at, created_at, updated_at
+      "#,
+      token,
+      new_token,
+      expires_at,
+    )
+    .fetch_optional(executor)
+    .await?;
+
+    Ok(row.map(Into::into))
+  }
+
+  /// Replaces the device-bound session's outstanding nonce. Returns whether
+  /// the session exists and is bound to a device.
+  pub async fn set_device_nonce<'c, E>(
+    executor: E,
+    token: &str,
+    nonce: &str,
+    expires_at: DateTime<Utc>,
+  ) -> Result<bool, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let result = sqlx::query!(
+      r#"
+      UPDATE sessions
+      SET device_nonce = $2, device_nonce_expires_at = $3
+      WHERE token = $1 AND device_public_key IS NOT NULL
+      "#,
+      token,
+      nonce,
+      expires_at,
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+  }
+
+  /// Uses up the session's nonce if it is `nonce` and still valid, returning
+  /// the session. Each nonce works once.
+  pub async fn take_device_nonce<'c, E>(
+    executor: E,
+    token: &str,
+    nonce: &str,
+  ) -> Result<Option<Session>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query_as!(
+      SessionRow,
+      r#"
+      UPDATE sessions
+      SET device_nonce = NULL, device_nonce_expires_at = NULL
+      WHERE token = $1 AND device_nonce = $2 AND device_nonce_expires_at > now()
+      RETURNING id, user_id, token, user_agent, ip_address, device_public_key, expires_at,
+                last_used_at, created_at, updated_at
+      "#,
+      token,
+      nonce,
+    )
+    .fetch_optional(executor)
+    .await?;
+
+    Ok(row.map(Into::into))
+  }
+
+  pub async fn find_by_id<'c, E>(
+    executor: E,
+    id: &SessionId,
+  ) -> Result<Option<Session>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query_as!(
+      SessionRow,
+      r#"
+      SELECT id, user_id, token, user_agent, ip_address, device_public_key, expires_at, last_used_at,
+             created_at, updated_at
+      FROM sessions
+      WHERE id = $1
+      "#,
+      id.into_inner(),
+    )
+    .fetch_optional(executor)
+    .await?;
+
+    Ok(row.map(Into::into))
+  }
+
   pub async fn find_by_token<'c, E>(
     executor: E,
     token: &str,
@@ -59,7 +257,8 @@ impl SessionStore {
     let row = sqlx::query_as!(
       SessionRow,
       r#"
-      SELECT id, user_id, token, user_agent, ip_address, expires_at, created_at, updated_at
+      SELECT id, user_id, token, user_agent, ip_address, device_public_key, expires_at, last_used_at,
+             created_at, updated_at
       FROM sessions
       WHERE token = $1
       "#,
@@ -81,9 +280,11 @@ impl SessionStore {
     let rows = sqlx::query_as!(
       SessionRow,
       r#"
-      SELECT id, user_id, token, user_agent, ip_address, expires_at, created_at, updated_at
+      SELECT id, user_id, token, user_agent, ip_address, device_public_key, expires_at, last_used_at,
+             created_at, updated_at
       FROM sessions
       WHERE user_id = $1
+      ORDER BY created_at DESC
       "#,
       user_id.into_inner(),
     )
@@ -92,4 +293,357 @@ impl SessionStore {
 
     Ok(rows.into_iter().map(Into::into).collect())
   }
+
+  /// Deletes sessions that expired before `cutoff`. Returns how many.
+  pub async fn delete_expired_before<'c, E>(
+    executor: E,
+    cutoff: DateTime<Utc>,
+  ) -> Result<u64, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let result = sqlx::query!(
+      r#"
+      DELETE FROM sessions
+      WHERE expires_at < $1
+      "#,
+      cutoff,
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(result.rows_affected())
+  }
+}
+
+/// Signed tokens that no longer count. Deleting a session that has not
+/// expired yet revokes all of its tokens by itself.
+pub struct SessionRevocationStore;
+
+impl SessionRevocationStore {
+  /// Revokes the session's tokens issued before `issued_before`, on top of
+  /// any revoked already.
+  pub async fn revoke_issued_before<'c, E>(
+    executor: E,
+    session_id: &SessionId,
+    issued_before: DateTime<Utc>,
+    expires_at: DateTime<Utc>,
+  ) -> Result<(), sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    sqlx::query!(
+      r#"
+      INSERT INTO session_revocations (session_id, issued_before, expires_at)
+      VALUES ($1, $2, $3)
+      ON CONFLICT (session_id) DO UPDATE
+      SET issued_before = greatest(session_revocations.issued_before, excluded.issued_before),
+          expires_at = greatest(session_revocations.expires_at, excluded.expires_at)
+      WHERE session_revocations.issued_before IS NOT NULL
+      "#,
+      session_id.into_inner(),
+      issued_before,
+      expires_at,
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(())
+  }
+
+  /// Unexpired revocations made or changed after `since`, or all of them.
+  pub async fn list_changed_since<'c, E>(
+    executor: E,
+    since: Option<DateTime<Utc>>,
+  ) -> Result<Vec<SessionRevocation>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let rows = sqlx::query_as!(
+      SessionRevocationRow,
+      r#"
+      SELECT session_id, issued_before, expires_at
+      FROM session_revocations
+      WHERE expires_at > now()
+        AND ($1::timestamptz IS NULL OR coalesce(updated_at, created_at) > $1)
+      "#,
+      since,
+    )
+    .fetch_all(executor)
+    .await?;
+
+    Ok(rows.into_iter().map(Into::into).collect())
+  }
+
+  /// Deletes revocations of tokens that expired before `cutoff`. Returns how
+  /// many.
+  pub async fn delete_expired_before<'c, E>(
+    executor: E,
+    cutoff: DateTime<Utc>,
+  ) -> Result<u64, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let result = sqlx::query!(
+      r#"
+      DELETE FROM session_revocations
+      WHERE expires_at < $1
+      "#,
+      cutoff,
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(result.rows_affected())
+  }
+}
+
+pub struct PasswordConfirmationStore;
+
+impl PasswordConfirmationStore {
+  pub async fn create<'c, E>(
+    executor: E,
+    creation: &PasswordConfirmationCreation,
+  ) -> Result<PasswordConfirmation, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query_as!(
+      PasswordConfirmationRow,
+      r#"
+      INSERT INTO password_confirmations (session_id, token, expires_at)
+      VALUES ($1, $2, $3)
+      RETURNING id, session_id, token, expires_at, created_at, updated_at
+      "#,
+      creation.session_id.into_inner(),
+      creation.token,
+      chrono::Utc::now() + creation.expires_in,
+    )
+    .fetch_one(executor)
+    .await?;
+
+    Ok(row.into())
+  }
+
+  /// Looks up a confirmation token, but only if it was issued to the session
+  /// identified by `session_token`.
+  pub async fn find_by_token_and_session_token<'c, E>(
+    executor: E,
+    token: &str,
+    session_token: &str,
+  ) -> Result<Option<PasswordConfirmation>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query_as!(
+      PasswordConfirmationRow,
+      r#"
+      SELECT pc.id, pc.session_id, pc.token, pc.expires_at, pc.created_at, pc.updated_at
+      FROM password_confirmations pc
+      JOIN sessions s ON s.id = pc.session_id
+      WHERE pc.token = $1
+        AND s.token = $2
+      "#,
+      token,
+      session_token,
+    )
+    .fetch_optional(executor)
+    .await?;
+
+    Ok(row.map(Into::into))
+  }
+
+  pub async fn delete_expired<'c, E>(executor: E) -> Result<(), sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    sqlx::query!(
+      r#"
+      DELETE FROM password_confirmations
+      WHERE expires_at < now()
+      "#
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(())
+  }
+}
+
+pub struct LoginAttemptStore;
+
+impl LoginAttemptStore {
+  pub async fn create<'c, E>(
+    executor: E,
+    creation: &LoginAttemptCreation,
+  ) -> Result<(), sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    sqlx::query!(
+      r#"
+      INSERT INTO login_attempts (email, user_id, succeeded, ip_address)
+      VALUES ($1, $2, $3, $4)
+      "#,
+      creation.email.expose(),
+      creation.user_id.map(UserId::into_inner),
+      creation.succeeded,
+      creation.ip_address,
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(())
+  }
+
+  /// Failed logins for `email` at or after `since` that no successful login
+  /// followed.
+  pub async fn list_failure_times_since<'c, E>(
+    executor: E,
+    email: &Email,
+    since: DateTime<Utc>,
+  ) -> Result<Vec<DateTime<Utc>>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    sqlx::query_scalar!(
+      r#"
+      SELECT created_at
+      FROM login_attempts a
+      WHERE a.email = $1
+        AND NOT a.succeeded
+        AND a.created_at >= $2
+        AND NOT EXISTS (
+          SELECT 1 FROM login_attempts s
+          WHERE s.email = a.email AND s.succeeded AND s.created_at > a.created_at
+        )
+      ORDER BY created_at DESC
+      "#,
+      email.expose(),
+      since,
+    )
+    .fetch_all(executor)
+    .await
+  }
+
+  pub async fn delete_by_user_id<'c, E>(executor: E, user_id: &UserId) -> Result<u64, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let result = sqlx::query!(
+      r#"
+      DELETE FROM login_attempts
+      WHERE user_id = $1
+      "#,
+      user_id.into_inner(),
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(result.rows_affected())
+  }
+
+  /// Deletes attempts made before `cutoff`. Returns how many.
+  pub async fn delete_before<'c, E>(executor: E, cutoff: DateTime<Utc>) -> Result<u64, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let result = sqlx::query!(
+      r#"
+      DELETE FROM login_attempts
+      WHERE created_at < $1
+      "#,
+      cutoff,
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(result.rows_affected())
+  }
+}
+
+pub struct LoginLockoutStore;
+
+impl LoginLockoutStore {
+  /// Until when repeated failed logins locked `email`. Times in the past mark
+  /// when the lock ended or was lifted.
+  pub async fn find_locked_until<'c, E>(
+    executor: E,
+    email: &Email,
+  ) -> Result<Option<DateTime<Utc>>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    sqlx::query_scalar!(
+      r#"
+      SELECT locked_until
+      FROM login_lockouts
+      WHERE email = $1
+      "#,
+      email.expose()
+    )
+    .fetch_optional(executor)
+    .await
+  }
+
+  pub async fn set_locked_until<'c, E>(
+    executor: E,
+    email: &Email,
+    locked_until: DateTime<Utc>,
+  ) -> Result<(), sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    sqlx::query!(
+      r#"
+      INSERT INTO login_lockouts (email, locked_until)
+      VALUES ($1, $2)
+      ON CONFLICT (email) DO UPDATE SET locked_until = EXCLUDED.locked_until
+      "#,
+      email.expose(),
+      locked_until,
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(())
+  }
+
+  pub async fn delete_by_email<'c, E>(executor: E, email: &Email) -> Result<u64, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let result = sqlx::query!(
+      r#"
+      DELETE FROM login_lockouts
+      WHERE email = $1
+      "#,
+      email.expose(),
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(result.rows_affected())
+  }
+
+  /// Deletes locks that ran out before `cutoff`. Returns how many.
+  pub async fn delete_expired_before<'c, E>(
+    executor: E,
+    cutoff: DateTime<Utc>,
+  ) -> Result<u64, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let result = sqlx::query!(
+      r#"
+      DELETE FROM login_lockouts
+      WHERE locked_until < $1
+      "#,
+      cutoff,
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(result.rows_affected())
+  }
 }
@@ -0,0 +1,61 @@
+use sqlx::{Executor, Postgres};
+
+use crate::stores::models::settings::{SettingsRow, SettingsUpdate};
+use domain::{Currency, Settings};
+
+pub struct SettingsStore;
+
+impl SettingsStore {
+  /// The settings, unless nobody changed them yet.
+  pub async fn find<'c, E>(executor: E) -> Result<Option<Settings>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query_as!(
+      SettingsRow,
+      r#"
+      SELECT invite_expiration_days, allow_overdraft_by_default, currency AS "currency: _", email_sender_name,
+             changed_by_actor_id, created_at, updated_at
+      FROM settings
+      "#
+    )
+    .fetch_optional(executor)
+    .await?;
+
+    Ok(row.map(Into::into))
+  }
+
+  pub async fn upsert<'c, E>(executor: E, update: &SettingsUpdate) -> Result<Settings, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query_as!(
+      SettingsRow,
+      r#"
+      INSERT INTO settings (
+        invite_expiration_days, allow_overdraft_by_default, currency, email_sender_name,
+        changed_by_actor_id
+      )
+      VALUES ($1, $2, $3, $4, $5)
+      ON CONFLICT (id) DO UPDATE
+      SET invite_expiration_days = EXCLUDED.invite_expiration_days,
+          allow_overdraft_by_default = EXCLUDED.allow_overdraft_by_default,
+          currency = EXCLUDED.currency,
+          email_sender_name = EXCLUDED.email_sender_name,
+          changed_by_actor_id = EXCLUDED.changed_by_actor_id
+      RETURNING invite_expiration_days, allow_overdraft_by_default, currency AS "currency: _",
+                email_sender_name,
+                changed_by_actor_id, created_at, updated_at
+      "#,
+      update.invite_expiration_days,
+      update.allow_overdraft_by_default,
+      update.currency as Currency,
+      update.email_sender_name,
+      update.changed_by.map(|id| id.into_inner()),
+    )
+    .fetch_one(executor)
+    .await?;
+
+    Ok(row.into())
+  }
+}
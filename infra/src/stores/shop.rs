@@ -4,7 +4,9 @@ use crate::stores::models::shop::{
   ShopCreation, ShopMemberRow, ShopOfferingCreation, ShopOfferingRow, ShopOfferingUpdate, ShopRow,
   ShopUpdate,
 };
-use domain::{Shop, ShopId, ShopMember, ShopMemberId, ShopOffering, ShopOfferingId, UserId};
+use domain::{
+  PickupSlots, Shop, ShopId, ShopMember, ShopMemberId, ShopOffering, ShopOfferingId, UserId,
+};
 
 pub struct ShopStore;
 
@@ -16,12 +18,13 @@ impl ShopStore {
     let row = sqlx::query_as!(
       ShopRow,
       r#"
-      INSERT INTO shops (owner_user_id, name)
-      VALUES ($1, $2)
-      RETURNING id, owner_user_id, name, created_at, updated_at
+      INSERT INTO shops (owner_user_id, name, wallet_id)
+      VALUES ($1, $2, $3)
+      RETURNING id, owner_user_id, name, wallet_id, pickup_slot_minutes, pickup_slot_capacity, created_at, updated_at
       "#,
       creation.owner.map(|id| id.into_inner()),
       creation.name,
+      creation.wallet_id.into_inner(),
     )
     .fetch_one(executor)
     .await?;
@@ -44,7 +47,7 @@ impl ShopStore {
       SET owner_user_id = CASE WHEN $2::boolean THEN $3 ELSE owner_user_id END,
           name = COALESCE($4, name)
       WHERE id = $1
-      RETURNING id, owner_user_id, name, created_at, updated_at
+      RETURNING id, owner_user_id, name, wallet_id, pickup_slot_minutes, pickup_slot_capacity, created_at, updated_at
       "#,
       id.into_inner(),
       update.owner.is_some(),
@@ -64,9 +67,34 @@ impl ShopStore {
     let row = sqlx::query_as!(
       ShopRow,
       r#"
-      SELECT id, owner_user_id, name, created_at, updated_at
+      SELECT id, owner_user_id, name, wallet_id, pickup_slot_minutes, pickup_slot_capacity, created_at, updated_at
+      FROM shops
+      WHERE id = $1
+      "#,
+      id.into_inner()
+    )
+    .fetch_optional(executor)
+    .await?;
+
+    Ok(row.map(Into::into))
+  }
+
+  /// Locks the shop row until the surrounding transaction ends, serializing
+  /// bookings of its pickup slots.
+  pub async fn find_by_id_for_update<'c, E>(
+    executor: E,
+    id: &ShopId,
+  ) -> Result<Option<Shop>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query_as!(
+      ShopRow,
+      r#"
+      SELECT id, owner_user_id, name, wallet_id, pickup_slot_minutes, pickup_slot_capacity, created_at, updated_at
       FROM shops
       WHERE id = $1
+      FOR UPDATE
       "#,
       id.into_inner()
     )
@@ -76,6 +104,34 @@ impl ShopStore {
     Ok(row.map(Into::into))
   }
 
+  /// Turns pre-ordering on with the given slots, or off with `None`.
+  pub async fn update_pickup_slots<'c, E>(
+    executor: E,
+    id: &ShopId,
+    slots: Option<&PickupSlots>,
+  ) -> Result<Option<Shop>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query_as!(
+      ShopRow,
+      r#"
+      UPDATE shops
+      SET pickup_slot_minutes = $2,
+          pickup_slot_capacity = $3
+      WHERE id = $1
+      RETURNING id, owner_user_id, name, wallet_id, pickup_slot_minutes, pickup_slot_capacity, created_at, updated_at
+      "#,
+      id.into_inner(),
+      slots.map(|s| s.length.num_minutes() as i32),
+      slots.map(|s| s.capacity),
+    )
+    .fetch_optional(executor)
+    .await?;
+
+    Ok(row.map(Into::into))
+  }
+
   pub async fn list_all<'c, E>(executor: E) -> Result<Vec<Shop>, sqlx::Error>
   where
     E: Executor<'c, Database = Postgres>,
@@ -83,7 +139,7 @@ impl ShopStore {
     let rows = sqlx::query_as!(
       ShopRow,
       r#"
-      SELECT id, owner_user_id, name, created_at, updated_at
+      SELECT id, owner_user_id, name, wallet_id, pickup_slot_minutes, pickup_slot_capacity, created_at, updated_at
       FROM shops
       "#
     )
@@ -286,6 +342,24 @@ impl ShopMemberStore {
     Ok(row.map(Into::into))
   }
 
+  /// Removes the user from the staff of every shop.
+  pub async fn delete_by_user_id<'c, E>(executor: E, user_id: &UserId) -> Result<u64, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let result = sqlx::query!(
+      r#"
+      DELETE FROM shop_members
+      WHERE user_id = $1
+      "#,
+      user_id.into_inner(),
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(result.rows_affected())
+  }
+
   pub async fn find_by_shop_and_user_id<'c, E>(
     executor: E,
     shop_id: &ShopId,
@@ -354,3 +428,95 @@ impl ShopMemberStore {
     Ok(rows.into_iter().map(Into::into).collect())
   }
 }
+
+pub struct FavoriteOfferingStore;
+
+impl FavoriteOfferingStore {
+  /// The user's favorites, most recently added first.
+  pub async fn list_by_user_id<'c, E>(
+    executor: E,
+    user_id: &UserId,
+  ) -> Result<Vec<ShopOffering>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let rows = sqlx::query_as!(
+      ShopOfferingRow,
+      r#"
+      SELECT o.id, o.shop_id, o.name, o.description, o.price_cents, o.created_at, o.updated_at
+      FROM favorite_offerings f
+      JOIN shop_offerings o ON o.id = f.offering_id
+      WHERE f.user_id = $1
+      ORDER BY f.created_at DESC
+      "#,
+      user_id.into_inner()
+    )
+    .fetch_all(executor)
+    .await?;
+
+    Ok(rows.into_iter().map(Into::into).collect())
+  }
+
+  /// Favoriting an offering twice is a no-op.
+  pub async fn create<'c, E>(
+    executor: E,
+    user_id: &UserId,
+    offering_id: &ShopOfferingId,
+  ) -> Result<(), sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    sqlx::query!(
+      r#"
+      INSERT INTO favorite_offerings (user_id, offering_id)
+      VALUES ($1, $2)
+      ON CONFLICT (user_id, offering_id) DO NOTHING
+      "#,
+      user_id.into_inner(),
+      offering_id.into_inner(),
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(())
+  }
+
+  pub async fn delete<'c, E>(
+    executor: E,
+    user_id: &UserId,
+    offering_id: &ShopOfferingId,
+  ) -> Result<(), sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    sqlx::query!(
+      r#"
+      DELETE FROM favorite_offerings
+      WHERE user_id = $1 AND offering_id = $2
+      "#,
+      user_id.into_inner(),
+      offering_id.into_inner(),
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(())
+  }
+
+  pub async fn delete_by_user_id<'c, E>(executor: E, user_id: &UserId) -> Result<u64, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let result = sqlx::query!(
+      r#"
+      DELETE FROM favorite_offerings
+      WHERE user_id = $1
+      "#,
+      user_id.into_inner(),
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(result.rows_affected())
+  }
+}
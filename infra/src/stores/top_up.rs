@@ -0,0 +1,134 @@
+use domain::{TopUp, TopUpId, TopUpStatus, TransactionId};
+use sqlx::{Executor, Postgres};
+
+use crate::stores::models::top_up::{TopUpCreation, TopUpRow};
+
+pub struct TopUpStore;
+
+impl TopUpStore {
+  pub async fn create<'c, E>(executor: E, creation: &TopUpCreation) -> Result<TopUp, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query_as!(
+      TopUpRow,
+      r#"
+      INSERT INTO top_ups (wallet_id, amount_cents, requested_by_actor_id)
+      VALUES ($1, $2, $3)
+      RETURNING id, wallet_id, amount_cents, status, provider_session_id, provider_payment_id, transaction_id, requested_by_actor_id, created_at, updated_at
+      "#,
+      creation.wallet_id.into_inner(),
+      creation.amount.as_minor(),
+      creation.requested_by.map(|id| id.into_inner()),
+    )
+    .fetch_one(executor)
+    .await?;
+
+    Ok(row.into())
+  }
+
+  pub async fn set_provider_session_id<'c, E>(
+    executor: E,
+    id: &TopUpId,
+    session_id: &str,
+  ) -> Result<Option<TopUp>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query_as!(
+      TopUpRow,
+      r#"
+      UPDATE top_ups
+      SET provider_session_id = $2
+      WHERE id = $1
+      RETURNING id, wallet_id, amount_cents, status, provider_session_id, provider_payment_id, transaction_id, requested_by_actor_id, created_at, updated_at
+      "#,
+      id.into_inner(),
+      session_id,
+    )
+    .fetch_optional(executor)
+    .await?;
+
+    Ok(row.map(Into::into))
+  }
+
+  /// Locks the top-up until the end of the transaction, so concurrent
+  /// deliveries of the same provider event settle it only once.
+  pub async fn find_by_provider_session_id_for_update<'c, E>(
+    executor: E,
+    session_id: &str,
+  ) -> Result<Option<TopUp>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query_as!(
+      TopUpRow,
+      r#"
+      SELECT id, wallet_id, amount_cents, status, provider_session_id, provider_payment_id, transaction_id, requested_by_actor_id, created_at, updated_at
+      FROM top_ups
+      WHERE provider_session_id = $1
+      FOR UPDATE
+      "#,
+      session_id,
+    )
+    .fetch_optional(executor)
+    .await?;
+
+    Ok(row.map(Into::into))
+  }
+
+  /// Like [`TopUpStore::find_by_provider_session_id_for_update`], by the
+  /// provider's payment rather than its checkout session.
+  pub async fn find_by_provider_payment_id_for_update<'c, E>(
+    executor: E,
+    payment_id: &str,
+  ) -> Result<Option<TopUp>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query_as!(
+      TopUpRow,
+      r#"
+      SELECT id, wallet_id, amount_cents, status, provider_session_id, provider_payment_id, transaction_id, requested_by_actor_id, created_at, updated_at
+      FROM top_ups
+      WHERE provider_payment_id = $1
+      FOR UPDATE
+      "#,
+      payment_id,
+    )
+    .fetch_optional(executor)
+    .await?;
+
+    Ok(row.map(Into::into))
+  }
+
+  /// Settles a pending top-up; `None` if it was settled already.
+  pub async fn settle<'c, E>(
+    executor: E,
+    id: &TopUpId,
+    status: TopUpStatus,
+    transaction_id: Option<TransactionId>,
+    provider_payment_id: Option<&str>,
+  ) -> Result<Option<TopUp>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query_as!(
+      TopUpRow,
+      r#"
+      UPDATE top_ups
+      SET status = $2, transaction_id = $3, provider_payment_id = $4
+      WHERE id = $1 AND status = 'pending'
+      RETURNING id, wallet_id, amount_cents, status, provider_session_id, provider_payment_id, transaction_id, requested_by_actor_id, created_at, updated_at
+      "#,
+      id.into_inner(),
+      status.to_string(),
+      transaction_id.map(|id| id.into_inner()),
+      provider_payment_id,
+    )
+    .fetch_optional(executor)
+    .await?;
+
+    Ok(row.map(Into::into))
+  }
+}
@@ -1,7 +1,11 @@
-use domain::{transaction::TransactionId, types::Money, wallet::WalletId, Transaction};
+use chrono::{DateTime, Utc};
+use domain::{transaction::TransactionId, types::Money, wallet::WalletId, LedgerLine, Transaction};
 use sqlx::{Executor, Postgres};
 
-use crate::stores::models::transaction::{TransactionCreation, TransactionRow};
+use crate::stores::{
+  models::transaction::{LedgerLineRow, TransactionCreation, TransactionRow},
+  money_from_sum,
+};
 
 pub struct TransactionStore;
 
@@ -16,15 +20,17 @@ impl TransactionStore {
     let row = sqlx::query_as!(
       TransactionRow,
       r#"
-      INSERT INTO transactions (source_wallet_id, destination_wallet_id, executor_actor_id, amount_cents, description)
-      VALUES ($1, $2, $3, $4, $5)
-      RETURNING id, source_wallet_id, destination_wallet_id, executor_actor_id, amount_cents, description, created_at, updated_at
+      INSERT INTO transactions (source_wallet_id, destination_wallet_id, executor_actor_id, amount_cents, description, reversal_of, metadata)
+      VALUES ($1, $2, $3, $4, $5, $6, $7)
+      RETURNING id, source_wallet_id, destination_wallet_id, executor_actor_id, amount_cents, currency AS "currency: _", description, reversal_of, metadata, created_at, updated_at
       "#,
       creation.source.into_inner(),
       creation.destination.into_inner(),
       creation.executor.as_ref().map(|e| e.into_inner()),
       creation.amount.as_minor(),
       creation.description,
+      creation.reversal_of.map(|r| r.into_inner()),
+      serde_json::to_value(&creation.metadata).expect("metadata serializes"),
     )
     .fetch_one(executor)
     .await?;
@@ -42,7 +48,7 @@ impl TransactionStore {
     let row = sqlx::query_as!(
       TransactionRow,
       r#"
-      SELECT id, source_wallet_id, destination_wallet_id, executor_actor_id, amount_cents, description, created_at, updated_at
+      SELECT id, source_wallet_id, destination_wallet_id, executor_actor_id, amount_cents, currency AS "currency: _", description, reversal_of, metadata, created_at, updated_at
       FROM transactions
       WHERE id = $1
       "#,
@@ -54,6 +60,29 @@ impl TransactionStore {
     Ok(row.map(Into::into))
   }
 
+  /// The refund compensating `id`, if it has been refunded.
+  pub async fn find_reversal_of<'c, E>(
+    executor: E,
+    id: &TransactionId,
+  ) -> Result<Option<Transaction>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query_as!(
+      TransactionRow,
+      r#"
+      SELECT id, source_wallet_id, destination_wallet_id, executor_actor_id, amount_cents, currency AS "currency: _", description, reversal_of, metadata, created_at, updated_at
+      FROM transactions
+      WHERE reversal_of = $1
+      "#,
+      id.into_inner(),
+    )
+    .fetch_optional(executor)
+    .await?;
+
+    Ok(row.map(Into::into))
+  }
+
   pub async fn list_by_wallet_id<'c, E>(
     executor: E,
     wallet_id: &WalletId,
@@ -64,7 +93,7 @@ impl TransactionStore {
     let rows = sqlx::query_as!(
       TransactionRow,
       r#"
-      SELECT id, source_wallet_id, destination_wallet_id, executor_actor_id, amount_cents, description, created_at, updated_at
+      SELECT id, source_wallet_id, destination_wallet_id, executor_actor_id, amount_cents, currency AS "currency: _", description, reversal_of, metadata, created_at, updated_at
       FROM transactions
       WHERE source_wallet_id = $1 OR destination_wallet_id = $1
       ORDER BY created_at DESC
@@ -77,9 +106,46 @@ impl TransactionStore {
     Ok(rows.into_iter().map(Into::into).collect())
   }
 
-  pub async fn calculate_wallet_balance<'c, E>(
+  /// Oldest first, resuming after the `(created_at, id)` of the last line of
+  /// the previous page.
+  pub async fn list_ledger_page<'c, E>(
+    executor: E,
+    after: Option<(DateTime<Utc>, TransactionId)>,
+    limit: i64,
+  ) -> Result<Vec<LedgerLine>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let rows = sqlx::query_as!(
+      LedgerLineRow,
+      r#"
+      SELECT t.id, t.source_wallet_id, sw.label AS source_label,
+             t.destination_wallet_id, dw.label AS destination_label,
+             t.executor_actor_id, NULLIF(CONCAT_WS(' ', u.first_name, u.last_name), '') AS executor_name,
+             t.amount_cents, t.currency AS "currency: _", t.description, t.reversal_of, t.metadata, t.created_at, t.updated_at
+      FROM transactions t
+      JOIN wallets sw ON sw.id = t.source_wallet_id
+      JOIN wallets dw ON dw.id = t.destination_wallet_id
+      LEFT JOIN users u ON u.actor_id = t.executor_actor_id
+      WHERE $1::timestamptz IS NULL OR (t.created_at, t.id) > ($1, $2::uuid)
+      ORDER BY t.created_at, t.id
+      LIMIT $3
+      "#,
+      after.map(|(created_at, _)| created_at),
+      after.map(|(_, id)| id.into_inner()),
+      limit,
+    )
+    .fetch_all(executor)
+    .await?;
+
+    Ok(rows.into_iter().map(Into::into).collect())
+  }
+
+  /// Balance of the wallet counting only transactions created before `at`
+  pub async fn calculate_wallet_balance_at<'c, E>(
     executor: E,
     wallet_id: &WalletId,
+    at: DateTime<Utc>,
   ) -> Result<Money, sqlx::Error>
   where
     E: Executor<'c, Database = Postgres>,
@@ -95,22 +161,61 @@ impl TransactionStore {
             END
           ), 0) AS balance
         FROM transactions
-        WHERE source_wallet_id = $1 OR destination_wallet_id = $1
+        WHERE (source_wallet_id = $1 OR destination_wallet_id = $1)
+          AND created_at < $2
         "#,
       wallet_id.into_inner(),
+      at,
     )
     .fetch_one(executor)
     .await?;
 
-    let balance = balance.unwrap_or_default();
-    let balance_i32 = i32::try_from(balance).map_err(|_| sqlx::Error::ColumnDecode {
-      index: "balance".to_string(),
-      source: Box::new(std::io::Error::new(
-        std::io::ErrorKind::InvalidData,
-        format!("Balance overflow: {} cents exceeds i32 range", balance),
-      )),
-    })?;
+    money_from_sum(balance, "balance")
+  }
+
+  /// Creation times of transactions leaving the wallet since `since`
+  pub async fn list_outgoing_times_since<'c, E>(
+    executor: E,
+    wallet_id: &WalletId,
+    since: DateTime<Utc>,
+  ) -> Result<Vec<DateTime<Utc>>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    sqlx::query_scalar!(
+      r#"
+      SELECT created_at
+      FROM transactions
+      WHERE source_wallet_id = $1 AND created_at >= $2
+      ORDER BY created_at DESC
+      "#,
+      wallet_id.into_inner(),
+      since,
+    )
+    .fetch_all(executor)
+    .await
+  }
+
+  pub async fn sum_outgoing_since<'c, E>(
+    executor: E,
+    wallet_id: &WalletId,
+    since: DateTime<Utc>,
+  ) -> Result<Money, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let total: Option<i64> = sqlx::query_scalar!(
+      r#"
+      SELECT COALESCE(SUM(amount_cents), 0) AS total
+      FROM transactions
+      WHERE source_wallet_id = $1 AND created_at >= $2
+      "#,
+      wallet_id.into_inner(),
+      since,
+    )
+    .fetch_one(executor)
+    .await?;
 
-    Ok(Money::from_minor(balance_i32))
+    money_from_sum(total, "total")
   }
 }
@@ -1,7 +1,18 @@
+use chrono::Utc;
 use sqlx::{Executor, Postgres};
 
-use crate::stores::models::user::{UserCreation, UserRow, UserUpdate};
-use domain::{ActorId, Email, User, UserId};
+use crate::stores::{
+  contains_pattern,
+  models::user::{
+    EmailChangeCreation, EmailChangeRow, PasswordSetupCreation, PasswordSetupRow,
+    PermissionOverrideRow, UserCreation, UserFilter, UserRow, UserUpdate,
+  },
+};
+use domain::{
+  types::{ListVersion, PageRequest},
+  ActorId, Email, EmailChange, EmailChangeId, PasswordSetup, PasswordSetupId, PermissionOverride,
+  Role, ShopId, User, UserId,
+};
 
 pub struct UserStore;
 
@@ -13,9 +24,9 @@ impl UserStore {
     let row = sqlx::query_as!(
       UserRow,
       r#"
-      INSERT INTO users (actor_id, email, password_hash, first_name, last_name, role)
-      VALUES ($1, $2, $3, $4, $5, $6)
-      RETURNING id, actor_id, email, password_hash, first_name, last_name, role, created_at, updated_at
+      INSERT INTO users (actor_id, email, password_hash, first_name, last_name, role, locale)
+      VALUES ($1, $2, $3, $4, $5, $6, $7)
+      RETURNING id, actor_id, email, password_hash, first_name, last_name, role, locale, created_at, updated_at
       "#,
       creation.actor_id.into_inner(),
       creation.email.expose(),
@@ -23,6 +34,7 @@ impl UserStore {
       creation.first_name,
       creation.last_name,
       creation.role.to_string(),
+      creation.locale.code(),
     )
     .fetch_one(executor)
     .await?;
@@ -30,6 +42,33 @@ impl UserStore {
     Ok(row.into())
   }
 
+  /// Replaces the user's personal data with placeholders and marks them as
+  /// deleted. The row stays, so transactions, orders and the audit log keep
+  /// their references. Returns `None` if there is no such user left.
+  pub async fn anonymize_by_id<'c, E>(executor: E, id: &UserId) -> Result<Option<User>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query_as!(
+      UserRow,
+      r#"
+      UPDATE users
+      SET email = 'deleted-' || id || '@deleted.invalid',
+          password_hash = '!',
+          first_name = 'Deleted',
+          last_name = 'User',
+          deleted_at = now()
+      WHERE id = $1 AND deleted_at IS NULL
+      RETURNING id, actor_id, email, password_hash, first_name, last_name, role, locale, created_at, updated_at
+      "#,
+      id.into_inner(),
+    )
+    .fetch_optional(executor)
+    .await?;
+
+    Ok(row.map(Into::into))
+  }
+
   pub async fn update_by_id<'c, E>(
     executor: E,
     id: &UserId,
@@ -46,9 +85,10 @@ impl UserStore {
           password_hash = COALESCE($3, password_hash),
           first_name = COALESCE($4, first_name),
           last_name = COALESCE($5, last_name),
-          role = COALESCE($6, role)
+          role = COALESCE($6, role),
+          locale = COALESCE($7, locale)
       WHERE id = $1
-      RETURNING id, actor_id, email, password_hash, first_name, last_name, role, created_at, updated_at
+      RETURNING id, actor_id, email, password_hash, first_name, last_name, role, locale, created_at, updated_at
       "#,
       id.into_inner(),
       update.email.as_ref().map(|e| e.expose()),
@@ -56,6 +96,7 @@ impl UserStore {
       update.first_name.as_ref(),
       update.last_name.as_ref(),
       update.role.as_ref().map(ToString::to_string),
+      update.locale.map(|locale| locale.code()),
     )
     .fetch_optional(executor)
     .await?;
@@ -63,6 +104,28 @@ impl UserStore {
     Ok(row.map(Into::into))
   }
 
+  /// Locks every user with the role until the end of the transaction, so
+  /// concurrent role changes see each other's outcome.
+  pub async fn lock_ids_by_role<'c, E>(executor: E, role: Role) -> Result<Vec<UserId>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let ids = sqlx::query_scalar!(
+      r#"
+      SELECT id
+      FROM users
+      WHERE role = $1 AND deleted_at IS NULL
+      ORDER BY id
+      FOR UPDATE
+      "#,
+      role.to_string(),
+    )
+    .fetch_all(executor)
+    .await?;
+
+    Ok(ids.into_iter().map(Into::into).collect())
+  }
+
   pub async fn find_by_id<'c, E>(executor: E, id: &UserId) -> Result<Option<User>, sqlx::Error>
   where
     E: Executor<'c, Database = Postgres>,
@@ -70,9 +133,9 @@ impl UserStore {
     let row = sqlx::query_as!(
       UserRow,
       r#"
-      SELECT id, actor_id, email, password_hash, first_name, last_name, role, created_at, updated_at
+      SELECT id, actor_id, email, password_hash, first_name, last_name, role, locale, created_at, updated_at
       FROM users
-      WHERE id = $1
+      WHERE id = $1 AND deleted_at IS NULL
       "#,
       id.into_inner()
     )
@@ -89,9 +152,9 @@ impl UserStore {
     let row = sqlx::query_as!(
       UserRow,
       r#"
-      SELECT id, actor_id, email, password_hash, first_name, last_name, role, created_at, updated_at
+      SELECT id, actor_id, email, password_hash, first_name, last_name, role, locale, created_at, updated_at
       FROM users
-      WHERE email = $1
+      WHERE email = $1 AND deleted_at IS NULL
       "#,
       email.expose()
     )
@@ -111,9 +174,9 @@ impl UserStore {
     let row = sqlx::query_as!(
       UserRow,
       r#"
-      SELECT id, actor_id, email, password_hash, first_name, last_name, role, created_at, updated_at
+      SELECT id, actor_id, email, password_hash, first_name, last_name, role, locale, created_at, updated_at
       FROM users
-      WHERE actor_id = $1
+      WHERE actor_id = $1 AND deleted_at IS NULL
       "#,
       actor_id.into_inner()
     )
@@ -123,6 +186,30 @@ impl UserStore {
     Ok(row.map(Into::into))
   }
 
+  pub async fn list_by_shop_id<'c, E>(
+    executor: E,
+    shop_id: &ShopId,
+  ) -> Result<Vec<User>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let rows = sqlx::query_as!(
+      UserRow,
+      r#"
+      SELECT u.id, u.actor_id, u.email, u.password_hash, u.first_name, u.last_name, u.role,
+             u.locale, u.created_at, u.updated_at
+      FROM users u
+      JOIN shop_members m ON m.user_id = u.id
+      WHERE m.shop_id = $1 AND u.deleted_at IS NULL
+      "#,
+      shop_id.into_inner()
+    )
+    .fetch_all(executor)
+    .await?;
+
+    Ok(rows.into_iter().map(Into::into).collect())
+  }
+
   pub async fn list_all<'c, E>(executor: E) -> Result<Vec<User>, sqlx::Error>
   where
     E: Executor<'c, Database = Postgres>,
@@ -130,8 +217,9 @@ impl UserStore {
     let rows = sqlx::query_as!(
       UserRow,
       r#"
-      SELECT id, actor_id, email, password_hash, first_name, last_name, role, created_at, updated_at
+      SELECT id, actor_id, email, password_hash, first_name, last_name, role, locale, created_at, updated_at
       FROM users
+      WHERE deleted_at IS NULL
       "#
     )
     .fetch_all(executor)
@@ -139,4 +227,326 @@ impl UserStore {
 
     Ok(rows.into_iter().map(Into::into).collect())
   }
+
+  pub async fn list_paginated<'c, E>(
+    executor: E,
+    filter: &UserFilter,
+    page: &PageRequest,
+  ) -> Result<Vec<User>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let rows = sqlx::query_as!(
+      UserRow,
+      r#"
+      SELECT id, actor_id, email, password_hash, first_name, last_name, role, locale, created_at, updated_at
+      FROM users u
+      WHERE user_listed(u, $1, $2)
+      ORDER BY
+        CASE WHEN NOT $4 THEN
+          CASE $3::text
+            WHEN 'email' THEN email
+            WHEN 'first_name' THEN first_name
+            WHEN 'last_name' THEN last_name
+          END
+        END ASC,
+        CASE WHEN $4 THEN
+          CASE $3::text
+            WHEN 'email' THEN email
+            WHEN 'first_name' THEN first_name
+            WHEN 'last_name' THEN last_name
+          END
+        END DESC,
+        CASE WHEN $3 = 'created_at' AND NOT $4 THEN created_at END ASC,
+        CASE WHEN $3 = 'created_at' AND $4 THEN created_at END DESC,
+        id ASC
+      LIMIT $5 OFFSET $6
+      "#,
+      filter.role.as_ref().map(ToString::to_string),
+      filter.search.as_deref().map(contains_pattern),
+      filter.sort_by.to_string(),
+      filter.sort_order.is_desc(),
+      page.limit(),
+      page.offset(),
+    )
+    .fetch_all(executor)
+    .await?;
+
+    Ok(rows.into_iter().map(Into::into).collect())
+  }
+
+  pub async fn count<'c, E>(executor: E, filter: &UserFilter) -> Result<i64, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let count = sqlx::query_scalar!(
+      r#"
+      SELECT COUNT(*) AS "count!"
+      FROM users u
+      WHERE user_listed(u, $1, $2)
+      "#,
+      filter.role.as_ref().map(ToString::to_string),
+      filter.search.as_deref().map(contains_pattern),
+    )
+    .fetch_one(executor)
+    .await?;
+
+    Ok(count)
+  }
+
+  /// Version of the users matching `filter`, whatever the page.
+  pub async fn version<'c, E>(executor: E, filter: &UserFilter) -> Result<ListVersion, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query!(
+      r#"
+      SELECT COUNT(*) AS "count!", MAX(COALESCE(updated_at, created_at)) AS changed_at
+      FROM users u
+      WHERE user_listed(u, $1, $2)
+      "#,
+      filter.role.as_ref().map(ToString::to_string),
+      filter.search.as_deref().map(contains_pattern),
+    )
+    .fetch_one(executor)
+    .await?;
+
+    Ok(ListVersion {
+      count: row.count,
+      changed_at: row.changed_at,
+    })
+  }
+}
+
+pub struct UserPermissionStore;
+
+impl UserPermissionStore {
+  pub async fn list_by_user_id<'c, E>(
+    executor: E,
+    user_id: &UserId,
+  ) -> Result<Vec<PermissionOverride>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let rows = sqlx::query_as!(
+      PermissionOverrideRow,
+      r#"
+      SELECT permission AS "permission: _", granted
+      FROM user_permissions
+      WHERE user_id = $1
+      ORDER BY permission
+      "#,
+      user_id.into_inner()
+    )
+    .fetch_all(executor)
+    .await?;
+
+    Ok(rows.into_iter().map(Into::into).collect())
+  }
+
+  pub async fn create<'c, E>(
+    executor: E,
+    user_id: &UserId,
+    permission_override: &PermissionOverride,
+  ) -> Result<(), sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    sqlx::query!(
+      r#"
+      INSERT INTO user_permissions (user_id, permission, granted)
+      VALUES ($1, $2, $3)
+      "#,
+      user_id.into_inner(),
+      permission_override.permission as _,
+      permission_override.granted,
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(())
+  }
+
+  pub async fn delete_by_user_id<'c, E>(executor: E, user_id: &UserId) -> Result<(), sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    sqlx::query!(
+      r#"
+      DELETE FROM user_permissions
+      WHERE user_id = $1
+      "#,
+      user_id.into_inner()
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(())
+  }
+}
+
+pub struct EmailChangeStore;
+
+impl EmailChangeStore {
+  /// Records the requested address, superseding any earlier request of the
+  /// same user and its token.
+  pub async fn replace<'c, E>(
+    executor: E,
+    creation: &EmailChangeCreation,
+  ) -> Result<EmailChange, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let expires_at = Utc::now() + creation.expires_in;
+
+    let row = sqlx::query_as!(
+      EmailChangeRow,
+      r#"
+      INSERT INTO email_changes (user_id, email, token, expires_at)
+      VALUES ($1, $2, $3, $4)
+      ON CONFLICT (user_id) DO UPDATE
+      SET email = EXCLUDED.email, token = EXCLUDED.token, expires_at = EXCLUDED.expires_at
+      RETURNING id, user_id, email, token, expires_at, created_at, updated_at
+      "#,
+      creation.user_id.into_inner(),
+      creation.email.expose(),
+      creation.token,
+      expires_at,
+    )
+    .fetch_one(executor)
+    .await?;
+
+    Ok(row.into())
+  }
+
+  pub async fn find_by_token_for_update<'c, E>(
+    executor: E,
+    token: &str,
+  ) -> Result<Option<EmailChange>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query_as!(
+      EmailChangeRow,
+      r#"
+      SELECT id, user_id, email, token, expires_at, created_at, updated_at
+      FROM email_changes
+      WHERE token = $1
+      FOR UPDATE
+      "#,
+      token,
+    )
+    .fetch_optional(executor)
+    .await?;
+
+    Ok(row.map(Into::into))
+  }
+
+  pub async fn delete_by_id<'c, E>(executor: E, id: &EmailChangeId) -> Result<u64, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let result = sqlx::query!(
+      r#"
+      DELETE FROM email_changes
+      WHERE id = $1
+      "#,
+      id.into_inner(),
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(result.rows_affected())
+  }
+}
+
+pub struct PasswordSetupStore;
+
+impl PasswordSetupStore {
+  /// Records a token for the user to choose their password with, superseding
+  /// any earlier one.
+  pub async fn replace<'c, E>(
+    executor: E,
+    creation: &PasswordSetupCreation,
+  ) -> Result<PasswordSetup, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let expires_at = Utc::now() + creation.expires_in;
+
+    let row = sqlx::query_as!(
+      PasswordSetupRow,
+      r#"
+      INSERT INTO password_setups (user_id, token, expires_at)
+      VALUES ($1, $2, $3)
+      ON CONFLICT (user_id) DO UPDATE
+      SET token = EXCLUDED.token, expires_at = EXCLUDED.expires_at
+      RETURNING id, user_id, token, expires_at, created_at, updated_at
+      "#,
+      creation.user_id.into_inner(),
+      creation.token,
+      expires_at,
+    )
+    .fetch_one(executor)
+    .await?;
+
+    Ok(row.into())
+  }
+
+  pub async fn find_by_token_for_update<'c, E>(
+    executor: E,
+    token: &str,
+  ) -> Result<Option<PasswordSetup>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query_as!(
+      PasswordSetupRow,
+      r#"
+      SELECT id, user_id, token, expires_at, created_at, updated_at
+      FROM password_setups
+      WHERE token = $1
+      FOR UPDATE
+      "#,
+      token,
+    )
+    .fetch_optional(executor)
+    .await?;
+
+    Ok(row.map(Into::into))
+  }
+
+  pub async fn delete_by_id<'c, E>(executor: E, id: &PasswordSetupId) -> Result<u64, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let result = sqlx::query!(
+      r#"
+      DELETE FROM password_setups
+      WHERE id = $1
+      "#,
+      id.into_inner(),
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(result.rows_affected())
+  }
+
+  pub async fn delete_by_user_id<'c, E>(executor: E, user_id: &UserId) -> Result<u64, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let result = sqlx::query!(
+      r#"
+      DELETE FROM password_setups
+      WHERE user_id = $1
+      "#,
+      user_id.into_inner(),
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(result.rows_affected())
+  }
 }
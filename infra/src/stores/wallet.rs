@@ -1,10 +1,49 @@
 use domain::{
+  types::{Currency, Money},
+  types::{ListVersion, PageRequest},
   wallet::{WalletId, WalletLabel},
-  Wallet,
+  ActorId, BalanceDrift, Wallet, WalletAppearance, WalletDetails, WalletLegalHoldEvent,
 };
 use sqlx::{Executor, Postgres};
 
-use crate::stores::models::wallet::{WalletCreation, WalletRow, WalletUpdate};
+use crate::stores::{
+  contains_pattern,
+  models::wallet::{
+    WalletCreation, WalletDetailsRow, WalletFilter, WalletLegalHoldEventCreation,
+    WalletLegalHoldEventRow, WalletRow, WalletUpdate,
+  },
+  money_from_sum,
+};
+
+/// SQLSTATE raised by the database when a write touches a wallet (or one of
+/// its transactions) that is under legal hold.
+pub const LEGAL_HOLD_VIOLATION: &str = "LH001";
+
+/// SQLSTATE raised by the database when a frozen wallet tries to send money.
+pub const WALLET_FROZEN_VIOLATION: &str = "WF001";
+
+/// SQLSTATE raised by the database when money would move between wallets of
+/// different currencies.
+pub const CURRENCY_MISMATCH_VIOLATION: &str = "CM001";
+
+pub fn is_legal_hold_violation(err: &sqlx::Error) -> bool {
+  has_sqlstate(err, LEGAL_HOLD_VIOLATION)
+}
+
+pub fn is_wallet_frozen_violation(err: &sqlx::Error) -> bool {
+  has_sqlstate(err, WALLET_FROZEN_VIOLATION)
+}
+
+pub fn is_currency_mismatch_violation(err: &sqlx::Error) -> bool {
+  has_sqlstate(err, CURRENCY_MISMATCH_VIOLATION)
+}
+
+fn has_sqlstate(err: &sqlx::Error, code: &str) -> bool {
+  matches!(
+    err,
+    sqlx::Error::Database(db_err) if db_err.code().as_deref() == Some(code)
+  )
+}
 
 pub struct WalletStore;
 
@@ -16,12 +55,13 @@ impl WalletStore {
     let row = sqlx::query_as!(
       WalletRow,
       r#"
-      INSERT INTO wallets (owner_actor_id, label, allow_overdraft)
-      VALUES ($1, $2, $3)
-      RETURNING id, owner_actor_id, label, allow_overdraft, created_at, updated_at
+      INSERT INTO wallets (owner_actor_id, label, currency, allow_overdraft)
+      VALUES ($1, $2, $3, $4)
+      RETURNING id, owner_actor_id, label, currency AS "currency: _", allow_overdraft, legal_hold, frozen, display_name, color, icon, created_at, updated_at
       "#,
       creation.owner.map(|o| o.into_inner()),
       creation.label.as_ref().map(ToString::to_string),
+      creation.currency as Currency,
       creation.allow_overdraft,
     )
     .fetch_one(executor)
@@ -30,6 +70,32 @@ impl WalletStore {
     Ok(row.into())
   }
 
+  /// Opens the system wallet with `label` in `currency` unless it exists.
+  /// Returns whether it was opened.
+  pub async fn create_system<'c, E>(
+    executor: E,
+    label: &WalletLabel,
+    currency: Currency,
+  ) -> Result<bool, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let result = sqlx::query!(
+      r#"
+      INSERT INTO wallets (label, currency, allow_overdraft)
+      VALUES ($1, $2, TRUE)
+      ON CONFLICT (label, currency) DO NOTHING
+      "#,
+      label.to_string(),
+      currency as Currency,
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+  }
+
+  /// Fails with [`LEGAL_HOLD_VIOLATION`] while the wallet is held.
   pub async fn update_by_id<'c, E>(
     executor: E,
     id: &WalletId,
@@ -45,7 +111,7 @@ impl WalletStore {
       SET label = CASE WHEN $2 THEN $3 ELSE label END,
           allow_overdraft = COALESCE($4, allow_overdraft)
       WHERE id = $1
-      RETURNING id, owner_actor_id, label, allow_overdraft, created_at, updated_at
+      RETURNING id, owner_actor_id, label, currency AS "currency: _", allow_overdraft, legal_hold, frozen, display_name, color, icon, created_at, updated_at
       "#,
       id.into_inner(),
       update.label.is_some(),
@@ -63,6 +129,88 @@ impl WalletStore {
     Ok(row.map(Into::into))
   }
 
+  /// Toggles the legal hold flag. Apart from the balance and the abuse
+  /// freeze, this is the only column the database lets change on a wallet
+  /// while it is held; anything else fails with [`LEGAL_HOLD_VIOLATION`].
+  pub async fn set_legal_hold<'c, E>(
+    executor: E,
+    id: &WalletId,
+    legal_hold: bool,
+  ) -> Result<Option<Wallet>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query_as!(
+      WalletRow,
+      r#"
+      UPDATE wallets
+      SET legal_hold = $2
+      WHERE id = $1
+      RETURNING id, owner_actor_id, label, currency AS "currency: _", allow_overdraft, legal_hold, frozen, display_name, color, icon, created_at, updated_at
+      "#,
+      id.into_inner(),
+      legal_hold,
+    )
+    .fetch_optional(executor)
+    .await?;
+
+    Ok(row.map(Into::into))
+  }
+
+  pub async fn set_frozen<'c, E>(
+    executor: E,
+    id: &WalletId,
+    frozen: bool,
+  ) -> Result<Option<Wallet>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query_as!(
+      WalletRow,
+      r#"
+      UPDATE wallets
+      SET frozen = $2
+      WHERE id = $1
+      RETURNING id, owner_actor_id, label, currency AS "currency: _", allow_overdraft, legal_hold, frozen, display_name, color, icon, created_at, updated_at
+      "#,
+      id.into_inner(),
+      frozen,
+    )
+    .fetch_optional(executor)
+    .await?;
+
+    Ok(row.map(Into::into))
+  }
+
+  /// Replaces the display name, color and icon. Fails with
+  /// [`LEGAL_HOLD_VIOLATION`] while the wallet is held.
+  pub async fn set_appearance<'c, E>(
+    executor: E,
+    id: &WalletId,
+    appearance: &WalletAppearance,
+  ) -> Result<Option<Wallet>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query_as!(
+      WalletRow,
+      r#"
+      UPDATE wallets
+      SET display_name = $2, color = $3, icon = $4
+      WHERE id = $1
+      RETURNING id, owner_actor_id, label, currency AS "currency: _", allow_overdraft, legal_hold, frozen, display_name, color, icon, created_at, updated_at
+      "#,
+      id.into_inner(),
+      appearance.display_name(),
+      appearance.color(),
+      appearance.icon(),
+    )
+    .fetch_optional(executor)
+    .await?;
+
+    Ok(row.map(Into::into))
+  }
+
   pub async fn find_by_id<'c, E>(executor: E, id: &WalletId) -> Result<Option<Wallet>, sqlx::Error>
   where
     E: Executor<'c, Database = Postgres>,
@@ -70,9 +218,34 @@ impl WalletStore {
     let row = sqlx::query_as!(
       WalletRow,
       r#"
-      SELECT id, owner_actor_id, label, allow_overdraft, created_at, updated_at
+      SELECT id, owner_actor_id, label, currency AS "currency: _", allow_overdraft, legal_hold, frozen, display_name, color, icon, created_at, updated_at
+      FROM wallets
+      WHERE id = $1
+      "#,
+      id.into_inner(),
+    )
+    .fetch_optional(executor)
+    .await?;
+
+    Ok(row.map(Into::into))
+  }
+
+  /// Like [`WalletStore::find_by_id`], but locks the row until the surrounding
+  /// transaction ends so concurrent balance checks serialize.
+  pub async fn find_by_id_for_update<'c, E>(
+    executor: E,
+    id: &WalletId,
+  ) -> Result<Option<Wallet>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query_as!(
+      WalletRow,
+      r#"
+      SELECT id, owner_actor_id, label, currency AS "currency: _", allow_overdraft, legal_hold, frozen, display_name, color, icon, created_at, updated_at
       FROM wallets
       WHERE id = $1
+      FOR UPDATE
       "#,
       id.into_inner(),
     )
@@ -82,9 +255,71 @@ impl WalletStore {
     Ok(row.map(Into::into))
   }
 
+  /// Like [`WalletStore::find_by_id_for_update`] for both ends of a
+  /// transfer, locked in id order as the balance trigger locks them. Taking
+  /// only the source first would let opposite transfers between the same
+  /// wallets each hold one and wait for the other. Returned in the order
+  /// asked for.
+  pub async fn find_pair_for_update<'c, E>(
+    executor: E,
+    a: &WalletId,
+    b: &WalletId,
+  ) -> Result<(Option<Wallet>, Option<Wallet>), sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let rows = sqlx::query_as!(
+      WalletRow,
+      r#"
+      SELECT id, owner_actor_id, label, currency AS "currency: _", allow_overdraft, legal_hold, frozen, display_name, color, icon, created_at, updated_at
+      FROM wallets
+      WHERE id IN ($1, $2)
+      ORDER BY id
+      FOR UPDATE
+      "#,
+      a.into_inner(),
+      b.into_inner(),
+    )
+    .fetch_all(executor)
+    .await?;
+
+    let wallets: Vec<Wallet> = rows.into_iter().map(Into::into).collect();
+    let find = |id: &WalletId| wallets.iter().find(|wallet| wallet.id == *id).cloned();
+
+    Ok((find(a), find(b)))
+  }
+
+  /// Personal (unlabelled) wallet of the given actor.
+  pub async fn find_by_owner<'c, E>(
+    executor: E,
+    owner: &ActorId,
+  ) -> Result<Option<Wallet>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query_as!(
+      WalletRow,
+      r#"
+      SELECT id, owner_actor_id, label, currency AS "currency: _", allow_overdraft, legal_hold, frozen, display_name, color, icon, created_at, updated_at
+      FROM wallets
+      WHERE owner_actor_id = $1
+        AND label IS NULL
+      ORDER BY created_at
+      LIMIT 1
+      "#,
+      owner.into_inner(),
+    )
+    .fetch_optional(executor)
+    .await?;
+
+    Ok(row.map(Into::into))
+  }
+
+  /// System wallet with the given label in the given currency.
   pub async fn find_by_label<'c, E>(
     executor: E,
     label: &WalletLabel,
+    currency: Currency,
   ) -> Result<Option<Wallet>, sqlx::Error>
   where
     E: Executor<'c, Database = Postgres>,
@@ -92,15 +327,352 @@ impl WalletStore {
     let row = sqlx::query_as!(
       WalletRow,
       r#"
-      SELECT id, owner_actor_id, label, allow_overdraft, created_at, updated_at
+      SELECT id, owner_actor_id, label, currency AS "currency: _", allow_overdraft, legal_hold, frozen, display_name, color, icon, created_at, updated_at
       FROM wallets
       WHERE label = $1
+        AND currency = $2
       "#,
       label.to_string(),
+      currency as Currency,
     )
     .fetch_optional(executor)
     .await?;
 
     Ok(row.map(Into::into))
   }
+
+  /// The wallet with its balance and the user or guest owning it.
+  pub async fn find_details_by_id<'c, E>(
+    executor: E,
+    id: &WalletId,
+  ) -> Result<Option<WalletDetails>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query_as!(
+      WalletDetailsRow,
+      r#"
+      SELECT w.id, w.owner_actor_id, w.label, w.currency AS "currency: _", w.allow_overdraft, w.legal_hold, w.frozen,
+             w.display_name, w.color, w.icon, w.balance_cents, w.created_at, w.updated_at,
+             u.id AS "user_id?", u.email AS "user_email?",
+             u.first_name AS "user_first_name?", u.last_name AS "user_last_name?",
+             u.role AS "user_role?", u.locale AS "user_locale?",
+             u.created_at AS "user_created_at?",
+             u.updated_at AS "user_updated_at?",
+             g.id AS "guest_id?", g.email AS "guest_email?", g.verified AS "guest_verified?",
+             g.identifier AS "guest_identifier?", g.created_at AS "guest_created_at?",
+             g.updated_at AS "guest_updated_at?"
+      FROM wallets w
+      LEFT JOIN users u ON u.actor_id = w.owner_actor_id
+      LEFT JOIN guests g ON g.actor_id = w.owner_actor_id
+      WHERE w.id = $1
+      "#,
+      id.into_inner(),
+    )
+    .fetch_optional(executor)
+    .await?;
+
+    row.map(TryInto::try_into).transpose()
+  }
+
+  /// Oldest first, each with its balance and owner.
+  pub async fn list_details_paginated<'c, E>(
+    executor: E,
+    filter: &WalletFilter,
+    page: &PageRequest,
+  ) -> Result<Vec<WalletDetails>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let rows = sqlx::query_as!(
+      WalletDetailsRow,
+      r#"
+      SELECT w.id, w.owner_actor_id, w.label, w.currency AS "currency: _", w.allow_overdraft, w.legal_hold, w.frozen,
+             w.display_name, w.color, w.icon, w.balance_cents, w.created_at, w.updated_at,
+             u.id AS "user_id?", u.email AS "user_email?",
+             u.first_name AS "user_first_name?", u.last_name AS "user_last_name?",
+             u.role AS "user_role?", u.locale AS "user_locale?",
+             u.created_at AS "user_created_at?",
+             u.updated_at AS "user_updated_at?",
+             g.id AS "guest_id?", g.email AS "guest_email?", g.verified AS "guest_verified?",
+             g.identifier AS "guest_identifier?", g.created_at AS "guest_created_at?",
+             g.updated_at AS "guest_updated_at?"
+      FROM wallets w
+      LEFT JOIN users u ON u.actor_id = w.owner_actor_id
+      LEFT JOIN guests g ON g.actor_id = w.owner_actor_id
+      WHERE wallet_listed(w, u, g, $1, $2)
+      ORDER BY w.created_at, w.id
+      LIMIT $3 OFFSET $4
+      "#,
+      filter.system,
+      filter.search.as_deref().map(contains_pattern),
+      page.limit(),
+      page.offset(),
+    )
+    .fetch_all(executor)
+    .await?;
+
+    rows.into_iter().map(TryInto::try_into).collect()
+  }
+
+  pub async fn count<'c, E>(executor: E, filter: &WalletFilter) -> Result<i64, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let count = sqlx::query_scalar!(
+      r#"
+      SELECT COUNT(*) AS "count!"
+      FROM wallets w
+      LEFT JOIN users u ON u.actor_id = w.owner_actor_id
+      LEFT JOIN guests g ON g.actor_id = w.owner_actor_id
+      WHERE wallet_listed(w, u, g, $1, $2)
+      "#,
+      filter.system,
+      filter.search.as_deref().map(contains_pattern),
+    )
+    .fetch_one(executor)
+    .await?;
+
+    Ok(count)
+  }
+
+  /// Version of the wallets matching `filter`, whatever the page. Counts
+  /// changes to their owners too, as those are listed with them.
+  pub async fn version<'c, E>(
+    executor: E,
+    filter: &WalletFilter,
+  ) -> Result<ListVersion, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query!(
+      r#"
+      SELECT
+        COUNT(*) AS "count!",
+        MAX(GREATEST(
+          COALESCE(w.updated_at, w.created_at),
+          COALESCE(u.updated_at, u.created_at),
+          COALESCE(g.updated_at, g.created_at)
+        )) AS changed_at
+      FROM wallets w
+      LEFT JOIN users u ON u.actor_id = w.owner_actor_id
+      LEFT JOIN guests g ON g.actor_id = w.owner_actor_id
+      WHERE wallet_listed(w, u, g, $1, $2)
+      "#,
+      filter.system,
+      filter.search.as_deref().map(contains_pattern),
+    )
+    .fetch_one(executor)
+    .await?;
+
+    Ok(ListVersion {
+      count: row.count,
+      changed_at: row.changed_at,
+    })
+  }
+
+  /// Running balance of the wallet, kept up to date by the database with
+  /// every transaction. Lock the wallet first when the balance gates a
+  /// transfer.
+  pub async fn find_balance<'c, E>(executor: E, id: &WalletId) -> Result<Money, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let balance = sqlx::query_scalar!(
+      r#"
+      SELECT balance_cents
+      FROM wallets
+      WHERE id = $1
+      "#,
+      id.into_inner(),
+    )
+    .fetch_optional(executor)
+    .await?;
+
+    money_from_sum(balance, "balance_cents")
+  }
+
+  /// Money owed to members: the sum of all positive balances of wallets in
+  /// `currency` owned by a user or guest.
+  pub async fn sum_member_balances<'c, E>(
+    executor: E,
+    currency: Currency,
+  ) -> Result<i64, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let sum = sqlx::query_scalar!(
+      r#"
+      SELECT COALESCE(SUM(balance_cents) FILTER (WHERE balance_cents > 0), 0)::bigint AS "sum!"
+      FROM wallets
+      WHERE owner_actor_id IS NOT NULL
+        AND currency = $1
+      "#,
+      currency as Currency,
+    )
+    .fetch_one(executor)
+    .await?;
+
+    Ok(sum)
+  }
+
+  /// Wallets whose running balance no longer matches the sum of their
+  /// transactions.
+  pub async fn list_balance_drift<'c, E>(executor: E) -> Result<Vec<BalanceDrift>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let rows = sqlx::query!(
+      r#"
+      SELECT w.id, w.balance_cents, COALESCE(l.ledger_cents, 0) AS "ledger_cents!"
+      FROM wallets w
+      LEFT JOIN (
+        SELECT wallet_id, SUM(amount_cents) AS ledger_cents
+        FROM (
+          SELECT destination_wallet_id AS wallet_id, amount_cents
+          FROM transactions
+          UNION ALL
+          SELECT source_wallet_id, -amount_cents
+          FROM transactions
+        ) entries
+        GROUP BY wallet_id
+      ) l ON l.wallet_id = w.id
+      WHERE w.balance_cents <> COALESCE(l.ledger_cents, 0)
+      ORDER BY w.id
+      "#,
+    )
+    .fetch_all(executor)
+    .await?;
+
+    Ok(
+      rows
+        .into_iter()
+        .map(|row| BalanceDrift {
+          wallet_id: row.id.into(),
+          stored_cents: row.balance_cents,
+          ledger_cents: row.ledger_cents,
+        })
+        .collect(),
+    )
+  }
+}
+
+pub struct WalletLegalHoldStore;
+
+impl WalletLegalHoldStore {
+  pub async fn create<'c, E>(
+    executor: E,
+    creation: &WalletLegalHoldEventCreation,
+  ) -> Result<WalletLegalHoldEvent, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query_as!(
+      WalletLegalHoldEventRow,
+      r#"
+      INSERT INTO wallet_legal_hold_events (wallet_id, actor_id, action, reason)
+      VALUES ($1, $2, $3, $4)
+      RETURNING id, wallet_id, actor_id, action, reason, created_at, updated_at
+      "#,
+      creation.wallet_id.into_inner(),
+      creation.actor_id.map(|a| a.into_inner()),
+      creation.action.to_string(),
+      creation.reason,
+    )
+    .fetch_one(executor)
+    .await?;
+
+    Ok(row.into())
+  }
+
+  pub async fn list_by_wallet_id<'c, E>(
+    executor: E,
+    wallet_id: &WalletId,
+  ) -> Result<Vec<WalletLegalHoldEvent>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let rows = sqlx::query_as!(
+      WalletLegalHoldEventRow,
+      r#"
+      SELECT id, wallet_id, actor_id, action, reason, created_at, updated_at
+      FROM wallet_legal_hold_events
+      WHERE wallet_id = $1
+      ORDER BY created_at DESC
+      "#,
+      wallet_id.into_inner(),
+    )
+    .fetch_all(executor)
+    .await?;
+
+    Ok(rows.into_iter().map(Into::into).collect())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::stores::{models::transaction::TransactionCreation, TransactionStore};
+  use domain::TransactionMetadata;
+  use sqlx::PgPool;
+  use std::time::Duration;
+
+  async fn open_wallet(pool: &PgPool) -> Wallet {
+    WalletStore::create(
+      pool,
+      &WalletCreation {
+        owner: None,
+        label: None,
+        currency: Currency::EUR,
+        allow_overdraft: true,
+      },
+    )
+    .await
+    .unwrap()
+  }
+
+  fn transfer(source: WalletId, destination: WalletId, cents: i32) -> TransactionCreation {
+    TransactionCreation {
+      source,
+      destination,
+      executor: None,
+      amount: Money::from_minor(cents),
+      description: None,
+      reversal_of: None,
+      metadata: TransactionMetadata::default(),
+    }
+  }
+
+  #[sqlx::test(migrations = "../migrations")]
+  async fn test_opposite_transfers_queue_instead_of_deadlocking(pool: PgPool) {
+    let a = open_wallet(&pool).await;
+    let b = open_wallet(&pool).await;
+
+    let mut first = pool.begin().await.unwrap();
+    WalletStore::find_pair_for_update(&mut *first, &a.id, &b.id)
+      .await
+      .unwrap();
+
+    let second = tokio::spawn({
+      let pool = pool.clone();
+      async move {
+        let mut tx = pool.begin().await?;
+        WalletStore::find_pair_for_update(&mut *tx, &b.id, &a.id).await?;
+        TransactionStore::create(&mut *tx, &transfer(b.id, a.id, 300)).await?;
+        tx.commit().await
+      }
+    });
+    // Let the opposite transfer queue up behind the first one's locks
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    TransactionStore::create(&mut *first, &transfer(a.id, b.id, 500))
+      .await
+      .unwrap();
+    first.commit().await.unwrap();
+    second.await.unwrap().unwrap();
+
+    let balance = |id| WalletStore::find_balance(&pool, id);
+    assert_eq!(balance(&a.id).await.unwrap(), Money::from_minor(-200));
+    assert_eq!(balance(&b.id).await.unwrap(), Money::from_minor(200));
+  }
 }
@@ -0,0 +1,305 @@
+use chrono::{DateTime, Utc};
+use domain::{ShopId, Webhook, WebhookDelivery, WebhookDeliveryId, WebhookEvent, WebhookId};
+use sqlx::{Executor, Postgres};
+
+use crate::stores::models::webhook::{
+  WebhookCreation, WebhookDeliveryCreation, WebhookDeliveryRow, WebhookRow,
+};
+
+pub struct WebhookStore;
+pub struct WebhookDeliveryStore;
+
+impl WebhookStore {
+  pub async fn create<'c, E>(
+    executor: E,
+    creation: &WebhookCreation,
+  ) -> Result<Webhook, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let events: Vec<String> = creation.events.iter().map(ToString::to_string).collect();
+
+    let row = sqlx::query_as!(
+      WebhookRow,
+      r#"
+      INSERT INTO webhooks (shop_id, url, secret, events, created_by_actor_id)
+      VALUES ($1, $2, $3, $4, $5)
+      RETURNING id, shop_id, url, secret, events, created_by_actor_id, created_at, updated_at
+      "#,
+      creation.shop_id.map(|id| id.into_inner()),
+      creation.url,
+      creation.secret,
+      &events,
+      creation.created_by.map(|id| id.into_inner()),
+    )
+    .fetch_one(executor)
+    .await?;
+
+    row.try_into()
+  }
+
+  pub async fn find_by_id<'c, E>(
+    executor: E,
+    id: &WebhookId,
+  ) -> Result<Option<Webhook>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query_as!(
+      WebhookRow,
+      r#"
+      SELECT id, shop_id, url, secret, events, created_by_actor_id, created_at, updated_at
+      FROM webhooks
+      WHERE id = $1
+      "#,
+      id.into_inner()
+    )
+    .fetch_optional(executor)
+    .await?;
+
+    row.map(TryInto::try_into).transpose()
+  }
+
+  pub async fn list_by_shop_id<'c, E>(
+    executor: E,
+    shop_id: &ShopId,
+  ) -> Result<Vec<Webhook>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let rows = sqlx::query_as!(
+      WebhookRow,
+      r#"
+      SELECT id, shop_id, url, secret, events, created_by_actor_id, created_at, updated_at
+      FROM webhooks
+      WHERE shop_id = $1
+      ORDER BY created_at
+      "#,
+      shop_id.into_inner()
+    )
+    .fetch_all(executor)
+    .await?;
+
+    rows.into_iter().map(TryInto::try_into).collect()
+  }
+
+  /// Webhooks not tied to a shop.
+  pub async fn list_account_wide<'c, E>(executor: E) -> Result<Vec<Webhook>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let rows = sqlx::query_as!(
+      WebhookRow,
+      r#"
+      SELECT id, shop_id, url, secret, events, created_by_actor_id, created_at, updated_at
+      FROM webhooks
+      WHERE shop_id IS NULL
+      ORDER BY created_at
+      "#
+    )
+    .fetch_all(executor)
+    .await?;
+
+    rows.into_iter().map(TryInto::try_into).collect()
+  }
+
+  /// Webhooks that want to hear about `event`: those of the shop it
+  /// happened at, if any, and those not tied to a shop.
+  pub async fn list_subscribed<'c, E>(
+    executor: E,
+    shop_id: Option<&ShopId>,
+    event: WebhookEvent,
+  ) -> Result<Vec<Webhook>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let rows = sqlx::query_as!(
+      WebhookRow,
+      r#"
+      SELECT id, shop_id, url, secret, events, created_by_actor_id, created_at, updated_at
+      FROM webhooks
+      WHERE (shop_id IS NULL OR shop_id = $1) AND $2 = ANY(events)
+      "#,
+      shop_id.map(|id| id.into_inner()),
+      event.to_string(),
+    )
+    .fetch_all(executor)
+    .await?;
+
+    rows.into_iter().map(TryInto::try_into).collect()
+  }
+
+  pub async fn delete_by_id<'c, E>(executor: E, id: &WebhookId) -> Result<(), sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    sqlx::query!(
+      r#"
+      DELETE FROM webhooks
+      WHERE id = $1
+      "#,
+      id.into_inner()
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(())
+  }
+}
+
+impl WebhookDeliveryStore {
+  pub async fn create<'c, E>(
+    executor: E,
+    creation: &WebhookDeliveryCreation,
+  ) -> Result<WebhookDelivery, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query_as!(
+      WebhookDeliveryRow,
+      r#"
+      INSERT INTO webhook_deliveries (webhook_id, event, payload)
+      VALUES ($1, $2, $3)
+      RETURNING id, webhook_id, event, payload, status, attempts, response_status, last_error,
+                delivered_at, created_at, updated_at
+      "#,
+      creation.webhook_id.into_inner(),
+      creation.event.to_string(),
+      creation.payload,
+    )
+    .fetch_one(executor)
+    .await?;
+
+    row.try_into()
+  }
+
+  pub async fn find_by_id<'c, E>(
+    executor: E,
+    id: &WebhookDeliveryId,
+  ) -> Result<Option<WebhookDelivery>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let row = sqlx::query_as!(
+      WebhookDeliveryRow,
+      r#"
+      SELECT id, webhook_id, event, payload, status, attempts, response_status, last_error,
+             delivered_at, created_at, updated_at
+      FROM webhook_deliveries
+      WHERE id = $1
+      "#,
+      id.into_inner(),
+    )
+    .fetch_optional(executor)
+    .await?;
+
+    row.map(TryInto::try_into).transpose()
+  }
+
+  /// The webhook's latest deliveries, newest first.
+  pub async fn list_by_webhook_id<'c, E>(
+    executor: E,
+    webhook_id: &WebhookId,
+    limit: i64,
+  ) -> Result<Vec<WebhookDelivery>, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let rows = sqlx::query_as!(
+      WebhookDeliveryRow,
+      r#"
+      SELECT id, webhook_id, event, payload, status, attempts, response_status, last_error,
+             delivered_at, created_at, updated_at
+      FROM webhook_deliveries
+      WHERE webhook_id = $1
+      ORDER BY created_at DESC
+      LIMIT $2
+      "#,
+      webhook_id.into_inner(),
+      limit,
+    )
+    .fetch_all(executor)
+    .await?;
+
+    rows.into_iter().map(TryInto::try_into).collect()
+  }
+
+  /// Records a successful delivery attempt.
+  pub async fn mark_delivered<'c, E>(
+    executor: E,
+    id: &WebhookDeliveryId,
+    response_status: i32,
+  ) -> Result<(), sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    sqlx::query!(
+      r#"
+      UPDATE webhook_deliveries
+      SET status = 'delivered', attempts = attempts + 1, response_status = $2,
+          last_error = NULL, delivered_at = now()
+      WHERE id = $1
+      "#,
+      id.into_inner(),
+      response_status,
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(())
+  }
+
+  /// Records a failed delivery attempt; the delivery stays pending unless
+  /// this was the last one.
+  pub async fn mark_failed<'c, E>(
+    executor: E,
+    id: &WebhookDeliveryId,
+    response_status: Option<i32>,
+    error: &str,
+    gave_up: bool,
+  ) -> Result<(), sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    sqlx::query!(
+      r#"
+      UPDATE webhook_deliveries
+      SET status = CASE WHEN $4 THEN 'failed' ELSE 'pending' END,
+          attempts = attempts + 1,
+          response_status = $2,
+          last_error = $3
+      WHERE id = $1
+      "#,
+      id.into_inner(),
+      response_status,
+      error,
+      gave_up,
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(())
+  }
+
+  /// Deletes deliveries that succeeded before `cutoff`. Failed ones stay for
+  /// inspection until their webhook is removed.
+  pub async fn delete_delivered_before<'c, E>(
+    executor: E,
+    cutoff: DateTime<Utc>,
+  ) -> Result<u64, sqlx::Error>
+  where
+    E: Executor<'c, Database = Postgres>,
+  {
+    let result = sqlx::query!(
+      r#"
+      DELETE FROM webhook_deliveries
+      WHERE status = 'delivered' AND delivered_at < $1
+      "#,
+      cutoff,
+    )
+    .execute(executor)
+    .await?;
+
+    Ok(result.rows_affected())
+  }
+}
@@ -0,0 +1,2 @@
+alter table sessions
+    drop column if exists last_used_at;
@@ -0,0 +1,2 @@
+alter table sessions
+    add column last_used_at timestamptz;
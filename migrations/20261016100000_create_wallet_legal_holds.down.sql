@@ -0,0 +1,11 @@
+drop trigger if exists transactions_legal_hold on transactions;
+drop trigger if exists wallets_legal_hold on wallets;
+drop trigger if exists wallet_legal_hold_events_audit_timestamps on wallet_legal_hold_events;
+
+drop function if exists enforce_transaction_legal_hold;
+drop function if exists enforce_wallet_legal_hold;
+
+drop table if exists wallet_legal_hold_events;
+
+alter table wallets
+    drop column if exists legal_hold;
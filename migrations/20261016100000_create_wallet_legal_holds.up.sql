@@ -0,0 +1,77 @@
+alter table wallets
+    add column legal_hold boolean not null default false;
+
+create table wallet_legal_hold_events (
+    id uuid primary key default uuidv7(),
+    wallet_id uuid not null references wallets(id) on delete restrict,
+    actor_id uuid references actors(id) on delete set null,
+    action text not null check (action in ('placed', 'released')),
+    reason text not null,
+    created_at timestamptz not null default now(),
+    updated_at timestamptz
+);
+
+create trigger wallet_legal_hold_events_audit_timestamps
+    before insert or update on wallet_legal_hold_events
+    for each row
+    execute function enforce_audit_timestamps();
+
+-- Wallets under legal hold may only have their hold flag toggled. Everything
+-- else, including the `set null` cascade from deleting the owning actor, is
+-- rejected with SQLSTATE LH001 so the application can report it distinctly.
+create or replace function enforce_wallet_legal_hold()
+returns trigger as $$
+begin
+    if not old.legal_hold then
+        if tg_op = 'DELETE' then
+            return old;
+        end if;
+
+        return new;
+    end if;
+
+    if tg_op = 'DELETE' then
+        raise exception 'wallet % is under legal hold', old.id
+            using errcode = 'LH001';
+    end if;
+
+    if (new.owner_actor_id, new.label, new.allow_overdraft)
+        is distinct from (old.owner_actor_id, old.label, old.allow_overdraft) then
+        raise exception 'wallet % is under legal hold', old.id
+            using errcode = 'LH001';
+    end if;
+
+    return new;
+end;
+$$ language plpgsql;
+
+create or replace function enforce_transaction_legal_hold()
+returns trigger as $$
+begin
+    if exists (
+        select 1
+        from wallets
+        where id in (old.source_wallet_id, old.destination_wallet_id)
+          and legal_hold
+    ) then
+        raise exception 'transaction % belongs to a wallet under legal hold', old.id
+            using errcode = 'LH001';
+    end if;
+
+    if tg_op = 'DELETE' then
+        return old;
+    end if;
+
+    return new;
+end;
+$$ language plpgsql;
+
+create trigger wallets_legal_hold
+    before update or delete on wallets
+    for each row
+    execute function enforce_wallet_legal_hold();
+
+create trigger transactions_legal_hold
+    before update or delete on transactions
+    for each row
+    execute function enforce_transaction_legal_hold();
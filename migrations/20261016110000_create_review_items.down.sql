@@ -0,0 +1,11 @@
+drop trigger if exists transactions_wallet_not_frozen on transactions;
+drop trigger if exists review_items_audit_timestamps on review_items;
+drop trigger if exists wallet_security_events_audit_timestamps on wallet_security_events;
+
+drop function if exists enforce_wallet_not_frozen;
+
+drop table if exists review_items;
+drop table if exists wallet_security_events;
+
+alter table wallets
+    drop column if exists frozen;
@@ -0,0 +1,62 @@
+alter table wallets
+    add column frozen boolean not null default false;
+
+create table wallet_security_events (
+    id uuid primary key default uuidv7(),
+    wallet_id uuid not null references wallets(id) on delete cascade,
+    kind text not null check (kind in ('card_bound', 'pin_failed')),
+    created_at timestamptz not null default now(),
+    updated_at timestamptz
+);
+
+create index wallet_security_events_wallet_id_created_at_idx
+    on wallet_security_events (wallet_id, created_at);
+
+create table review_items (
+    id uuid primary key default uuidv7(),
+    wallet_id uuid not null references wallets(id) on delete cascade,
+    signal text not null check (signal in ('rapid_repeated_charges', 'drained_after_card_binding', 'repeated_failed_pin_attempts')),
+    details text not null,
+    status text not null default 'open' check (status in ('open', 'dismissed', 'confirmed')),
+    wallet_frozen boolean not null default false,
+    resolved_by_actor_id uuid references actors(id) on delete set null,
+    resolution_note text,
+    created_at timestamptz not null default now(),
+    updated_at timestamptz
+);
+
+create index review_items_status_idx on review_items (status);
+
+create trigger wallet_security_events_audit_timestamps
+    before insert or update on wallet_security_events
+    for each row
+    execute function enforce_audit_timestamps();
+
+create trigger review_items_audit_timestamps
+    before insert or update on review_items
+    for each row
+    execute function enforce_audit_timestamps();
+
+-- Frozen wallets may still receive money but can't send any. Rejected with
+-- SQLSTATE WF001 so the application can report it distinctly.
+create or replace function enforce_wallet_not_frozen()
+returns trigger as $$
+begin
+    if exists (
+        select 1
+        from wallets
+        where id = new.source_wallet_id
+          and frozen
+    ) then
+        raise exception 'wallet % is frozen', new.source_wallet_id
+            using errcode = 'WF001';
+    end if;
+
+    return new;
+end;
+$$ language plpgsql;
+
+create trigger transactions_wallet_not_frozen
+    before insert on transactions
+    for each row
+    execute function enforce_wallet_not_frozen();
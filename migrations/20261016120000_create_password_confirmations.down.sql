@@ -0,0 +1,3 @@
+drop trigger if exists password_confirmations_audit_timestamps on password_confirmations;
+
+drop table if exists password_confirmations;
@@ -0,0 +1,15 @@
+create table password_confirmations (
+    id uuid primary key default uuidv7(),
+    session_id uuid not null references sessions(id) on delete cascade,
+    token text not null unique,
+    expires_at timestamptz not null,
+    created_at timestamptz not null default now(),
+    updated_at timestamptz
+);
+
+create index password_confirmations_session_id_idx on password_confirmations(session_id);
+
+create trigger password_confirmations_audit_timestamps
+    before insert or update on password_confirmations
+    for each row
+    execute function enforce_audit_timestamps();
@@ -0,0 +1,2 @@
+alter table guests
+    drop column if exists identifier;
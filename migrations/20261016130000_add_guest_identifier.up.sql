@@ -0,0 +1,2 @@
+alter table guests
+    add column identifier text unique;
@@ -0,0 +1,5 @@
+drop index if exists transactions_reversal_of_key;
+
+alter table transactions
+    drop constraint if exists transactions_reversal_of_self_check,
+    drop column if exists reversal_of;
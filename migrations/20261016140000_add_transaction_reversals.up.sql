@@ -0,0 +1,8 @@
+alter table transactions
+    add column reversal_of uuid references transactions(id) on delete set null,
+    add constraint transactions_reversal_of_self_check check (reversal_of <> id);
+
+-- A transaction can be reversed at most once
+create unique index transactions_reversal_of_key
+    on transactions (reversal_of)
+    where reversal_of is not null;
@@ -0,0 +1,3 @@
+drop trigger if exists audit_log_audit_timestamps on audit_log;
+
+drop table if exists audit_log;
@@ -0,0 +1,18 @@
+create table audit_log (
+    id uuid primary key default uuidv7(),
+    actor_id uuid references actors(id) on delete set null,
+    action text not null,
+    target text,
+    ip_address text,
+    details jsonb,
+    created_at timestamptz not null default now(),
+    updated_at timestamptz
+);
+
+create index audit_log_created_at_idx on audit_log (created_at);
+create index audit_log_actor_id_idx on audit_log (actor_id);
+
+create trigger audit_log_audit_timestamps
+    before insert or update on audit_log
+    for each row
+    execute function enforce_audit_timestamps();
@@ -0,0 +1,8 @@
+drop trigger if exists order_items_audit_timestamps on order_items;
+drop trigger if exists orders_audit_timestamps on orders;
+
+drop table if exists order_items;
+drop table if exists orders;
+
+alter table shops
+    drop column if exists wallet_id;
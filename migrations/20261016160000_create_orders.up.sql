@@ -0,0 +1,55 @@
+-- Every shop collects its sales in a wallet of its own
+alter table shops
+    add column wallet_id uuid unique references wallets(id) on delete restrict;
+
+do $$
+declare
+    shop_id uuid;
+    new_wallet_id uuid;
+begin
+    for shop_id in select id from shops where wallet_id is null loop
+        insert into wallets default values returning id into new_wallet_id;
+        update shops set wallet_id = new_wallet_id where id = shop_id;
+    end loop;
+end
+$$;
+
+alter table shops
+    alter column wallet_id set not null;
+
+create table orders (
+    id uuid primary key default uuidv7(),
+    shop_id uuid not null references shops(id) on delete restrict,
+    payer_wallet_id uuid not null references wallets(id) on delete restrict,
+    transaction_id uuid not null unique references transactions(id) on delete restrict,
+    executor_actor_id uuid references actors(id) on delete set null,
+    total_cents int not null check (total_cents > 0),
+    created_at timestamptz not null default now(),
+    updated_at timestamptz
+);
+
+-- Name and price are captured at checkout so later catalogue edits don't
+-- rewrite history
+create table order_items (
+    id uuid primary key default uuidv7(),
+    order_id uuid not null references orders(id) on delete cascade,
+    offering_id uuid references shop_offerings(id) on delete set null,
+    name text not null,
+    unit_price_cents int not null check (unit_price_cents > 0),
+    quantity int not null check (quantity > 0),
+    created_at timestamptz not null default now(),
+    updated_at timestamptz
+);
+
+create index orders_shop_id_idx on orders (shop_id);
+create index order_items_order_id_idx on order_items (order_id);
+
+create trigger orders_audit_timestamps
+    before insert or update on orders
+    for each row
+    execute function enforce_audit_timestamps();
+
+create trigger order_items_audit_timestamps
+    before insert or update on order_items
+    for each row
+    execute function enforce_audit_timestamps();
@@ -0,0 +1,3 @@
+drop trigger if exists webhooks_audit_timestamps on webhooks;
+
+drop table if exists webhooks;
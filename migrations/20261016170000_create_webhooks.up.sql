@@ -0,0 +1,17 @@
+create table webhooks (
+    id uuid primary key default uuidv7(),
+    shop_id uuid references shops(id) on delete cascade,
+    url text not null,
+    secret text not null,
+    events text[] not null,
+    created_by_actor_id uuid references actors(id) on delete set null,
+    created_at timestamptz not null default now(),
+    updated_at timestamptz
+);
+
+create index webhooks_shop_id_idx on webhooks (shop_id);
+
+create trigger webhooks_audit_timestamps
+    before insert or update on webhooks
+    for each row
+    execute function enforce_audit_timestamps();
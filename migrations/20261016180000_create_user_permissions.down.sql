@@ -0,0 +1,3 @@
+drop trigger if exists user_permissions_audit_timestamps on user_permissions;
+
+drop table if exists user_permissions;
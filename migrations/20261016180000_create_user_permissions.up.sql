@@ -0,0 +1,15 @@
+create table user_permissions (
+    id uuid primary key default uuidv7(),
+    user_id uuid not null references users(id) on delete cascade,
+    permission text not null,
+    granted boolean not null,
+    created_at timestamptz not null default now(),
+    updated_at timestamptz,
+
+    unique (user_id, permission)
+);
+
+create trigger user_permissions_audit_timestamps
+    before insert or update on user_permissions
+    for each row
+    execute function enforce_audit_timestamps();
@@ -0,0 +1,3 @@
+drop index if exists orders_shop_id_status_idx;
+
+alter table orders drop column if exists status;
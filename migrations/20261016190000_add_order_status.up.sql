@@ -0,0 +1,3 @@
+alter table orders add column status text not null default 'placed';
+
+create index orders_shop_id_status_idx on orders (shop_id, status);
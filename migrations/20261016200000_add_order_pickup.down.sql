@@ -0,0 +1,2 @@
+alter table orders drop column if exists pickup_number;
+alter table orders drop column if exists table_label;
@@ -0,0 +1,2 @@
+alter table orders add column table_label text;
+alter table orders add column pickup_number integer check (pickup_number > 0);
@@ -0,0 +1,5 @@
+alter table users drop column if exists locked_until;
+
+drop trigger if exists login_attempts_audit_timestamps on login_attempts;
+
+drop table if exists login_attempts;
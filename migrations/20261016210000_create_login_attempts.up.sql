@@ -0,0 +1,17 @@
+create table login_attempts (
+    id uuid primary key default uuidv7(),
+    user_id uuid not null references users(id) on delete cascade,
+    succeeded boolean not null,
+    ip_address text,
+    created_at timestamptz not null default now(),
+    updated_at timestamptz
+);
+
+create index login_attempts_user_id_created_at_idx on login_attempts (user_id, created_at);
+
+create trigger login_attempts_audit_timestamps
+    before insert or update on login_attempts
+    for each row
+    execute function enforce_audit_timestamps();
+
+alter table users add column locked_until timestamptz;
@@ -0,0 +1,11 @@
+drop index if exists orders_unreleased_pickup_at_idx;
+drop index if exists orders_shop_id_pickup_at_idx;
+
+alter table orders drop column if exists released_at;
+alter table orders drop column if exists pickup_at;
+delete from orders where transaction_id is null;
+alter table orders alter column transaction_id set not null;
+
+alter table shops drop constraint if exists shops_pickup_slots_check;
+alter table shops drop column if exists pickup_slot_capacity;
+alter table shops drop column if exists pickup_slot_minutes;
@@ -0,0 +1,12 @@
+alter table shops add column pickup_slot_minutes integer check (pickup_slot_minutes > 0);
+alter table shops add column pickup_slot_capacity integer check (pickup_slot_capacity > 0);
+alter table shops add constraint shops_pickup_slots_check
+    check ((pickup_slot_minutes is null) = (pickup_slot_capacity is null));
+
+-- Pre-orders paid at pickup have no transaction until then
+alter table orders alter column transaction_id drop not null;
+alter table orders add column pickup_at timestamptz;
+alter table orders add column released_at timestamptz;
+
+create index orders_shop_id_pickup_at_idx on orders (shop_id, pickup_at) where pickup_at is not null;
+create index orders_unreleased_pickup_at_idx on orders (pickup_at) where pickup_at is not null and released_at is null;
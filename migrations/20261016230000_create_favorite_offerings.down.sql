@@ -0,0 +1,3 @@
+drop trigger if exists favorite_offerings_audit_timestamps on favorite_offerings;
+
+drop table if exists favorite_offerings;
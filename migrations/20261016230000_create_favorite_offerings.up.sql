@@ -0,0 +1,14 @@
+create table favorite_offerings (
+    id uuid primary key default uuidv7(),
+    user_id uuid not null references users(id) on delete cascade,
+    offering_id uuid not null references shop_offerings(id) on delete cascade,
+    created_at timestamptz not null default now(),
+    updated_at timestamptz,
+
+    unique (user_id, offering_id)
+);
+
+create trigger favorite_offerings_audit_timestamps
+    before insert or update on favorite_offerings
+    for each row
+    execute function enforce_audit_timestamps();
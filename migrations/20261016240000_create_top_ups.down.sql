@@ -0,0 +1,3 @@
+drop trigger if exists top_ups_audit_timestamps on top_ups;
+
+drop table if exists top_ups;
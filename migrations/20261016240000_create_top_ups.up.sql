@@ -0,0 +1,20 @@
+create table top_ups (
+    id uuid primary key default uuidv7(),
+    wallet_id uuid not null references wallets(id),
+    amount_cents integer not null check (amount_cents > 0),
+    status text not null default 'pending',
+    provider_session_id text unique,
+    transaction_id uuid references transactions(id),
+    requested_by_actor_id uuid references actors(id) on delete set null,
+    created_at timestamptz not null default now(),
+    updated_at timestamptz,
+
+    check ((status = 'succeeded') = (transaction_id is not null))
+);
+
+create index top_ups_wallet_id_idx on top_ups (wallet_id);
+
+create trigger top_ups_audit_timestamps
+    before insert or update on top_ups
+    for each row
+    execute function enforce_audit_timestamps();
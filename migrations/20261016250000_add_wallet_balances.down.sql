@@ -0,0 +1,5 @@
+drop trigger if exists transactions_apply_to_balances on transactions;
+
+drop function if exists apply_transaction_to_balances();
+
+alter table wallets drop column if exists balance_cents;
@@ -0,0 +1,37 @@
+alter table wallets add column balance_cents bigint not null default 0;
+
+update wallets w
+set balance_cents = coalesce((
+    select sum(case when t.destination_wallet_id = w.id then t.amount_cents else -t.amount_cents end)
+    from transactions t
+    where t.source_wallet_id = w.id or t.destination_wallet_id = w.id
+), 0);
+
+-- Keeps the running balance of both wallets in step with every transfer,
+-- within the inserting transaction. Both rows are locked in id order so
+-- opposite transfers between the same wallets can't deadlock.
+create or replace function apply_transaction_to_balances()
+returns trigger as $$
+begin
+    perform 1
+    from wallets
+    where id in (new.source_wallet_id, new.destination_wallet_id)
+    order by id
+    for update;
+
+    update wallets
+    set balance_cents = balance_cents - new.amount_cents
+    where id = new.source_wallet_id;
+
+    update wallets
+    set balance_cents = balance_cents + new.amount_cents
+    where id = new.destination_wallet_id;
+
+    return new;
+end;
+$$ language plpgsql;
+
+create trigger transactions_apply_to_balances
+    after insert on transactions
+    for each row
+    execute function apply_transaction_to_balances();
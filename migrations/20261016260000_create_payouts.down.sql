@@ -0,0 +1,7 @@
+drop trigger if exists payouts_audit_timestamps on payouts;
+drop trigger if exists payout_batches_audit_timestamps on payout_batches;
+drop trigger if exists bank_accounts_audit_timestamps on bank_accounts;
+
+drop table if exists payouts;
+drop table if exists payout_batches;
+drop table if exists bank_accounts;
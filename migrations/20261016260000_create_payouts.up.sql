@@ -0,0 +1,51 @@
+create table bank_accounts (
+    actor_id uuid primary key references actors(id) on delete cascade,
+    iban text not null,
+    holder_name text not null,
+    created_at timestamptz not null default now(),
+    updated_at timestamptz
+);
+
+create trigger bank_accounts_audit_timestamps
+    before insert or update on bank_accounts
+    for each row
+    execute function enforce_audit_timestamps();
+
+create table payout_batches (
+    id uuid primary key default uuidv7(),
+    created_by_actor_id uuid references actors(id) on delete set null,
+    created_at timestamptz not null default now(),
+    updated_at timestamptz
+);
+
+create trigger payout_batches_audit_timestamps
+    before insert or update on payout_batches
+    for each row
+    execute function enforce_audit_timestamps();
+
+create table payouts (
+    id uuid primary key default uuidv7(),
+    wallet_id uuid not null references wallets(id),
+    amount_cents integer not null check (amount_cents > 0),
+    iban text not null,
+    holder_name text not null,
+    status text not null default 'requested',
+    batch_id uuid references payout_batches(id),
+    transaction_id uuid not null unique references transactions(id),
+    refund_transaction_id uuid unique references transactions(id),
+    requested_by_actor_id uuid references actors(id) on delete set null,
+    created_at timestamptz not null default now(),
+    updated_at timestamptz,
+
+    check ((status = 'rejected') = (refund_transaction_id is not null)),
+    check ((status = 'requested') = (batch_id is null) or status = 'rejected')
+);
+
+create index payouts_wallet_id_idx on payouts (wallet_id);
+create index payouts_batch_id_idx on payouts (batch_id);
+create index payouts_status_idx on payouts (status);
+
+create trigger payouts_audit_timestamps
+    before insert or update on payouts
+    for each row
+    execute function enforce_audit_timestamps();
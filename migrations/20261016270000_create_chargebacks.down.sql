@@ -0,0 +1,22 @@
+create or replace function enforce_wallet_not_frozen()
+returns trigger as $$
+begin
+    if exists (
+        select 1
+        from wallets
+        where id = new.source_wallet_id
+          and frozen
+    ) then
+        raise exception 'wallet % is frozen', new.source_wallet_id
+            using errcode = 'WF001';
+    end if;
+
+    return new;
+end;
+$$ language plpgsql;
+
+drop trigger if exists chargebacks_audit_timestamps on chargebacks;
+
+drop table if exists chargebacks;
+
+alter table top_ups drop column if exists provider_payment_id;
@@ -0,0 +1,51 @@
+-- Payment the provider booked for a checkout, which disputes refer to
+alter table top_ups add column provider_payment_id text unique;
+
+create table chargebacks (
+    id uuid primary key default uuidv7(),
+    top_up_id uuid not null references top_ups(id),
+    wallet_id uuid not null references wallets(id),
+    amount_cents integer not null check (amount_cents > 0),
+    provider_dispute_id text not null unique,
+    status text not null default 'open',
+    transaction_id uuid not null unique references transactions(id),
+    reversal_transaction_id uuid unique references transactions(id),
+    resolved_by_actor_id uuid references actors(id) on delete set null,
+    resolution_note text,
+    resolved_at timestamptz,
+    created_at timestamptz not null default now(),
+    updated_at timestamptz,
+
+    check ((status = 'won') = (reversal_transaction_id is not null))
+);
+
+create index chargebacks_wallet_id_idx on chargebacks (wallet_id);
+
+create trigger chargebacks_audit_timestamps
+    before insert or update on chargebacks
+    for each row
+    execute function enforce_audit_timestamps();
+
+-- Frozen wallets may still receive money but can't send any, except to pay
+-- back the payment provider: a chargeback takes the money either way.
+create or replace function enforce_wallet_not_frozen()
+returns trigger as $$
+begin
+    if exists (
+        select 1
+        from wallets
+        where id = new.source_wallet_id
+          and frozen
+    ) and not exists (
+        select 1
+        from wallets
+        where id = new.destination_wallet_id
+          and label = 'psp_clearing'
+    ) then
+        raise exception 'wallet % is frozen', new.source_wallet_id
+            using errcode = 'WF001';
+    end if;
+
+    return new;
+end;
+$$ language plpgsql;
@@ -0,0 +1,3 @@
+drop trigger if exists balance_alerts_audit_timestamps on balance_alerts;
+
+drop table if exists balance_alerts;
@@ -0,0 +1,23 @@
+create table balance_alerts (
+    id uuid primary key default uuidv7(),
+    -- System wallet watched; the money owed to members when null
+    wallet_label text,
+    direction text not null check (direction in ('above', 'below')),
+    threshold_cents bigint not null,
+    notify_email text,
+    webhook_url text,
+    webhook_secret text,
+    breached boolean not null default false,
+    last_triggered_at timestamptz,
+    created_by_actor_id uuid references actors(id) on delete set null,
+    created_at timestamptz not null default now(),
+    updated_at timestamptz,
+
+    check (notify_email is not null or webhook_url is not null),
+    check ((webhook_url is null) = (webhook_secret is null))
+);
+
+create trigger balance_alerts_audit_timestamps
+    before insert or update on balance_alerts
+    for each row
+    execute function enforce_audit_timestamps();
@@ -0,0 +1,3 @@
+drop trigger if exists guest_claims_audit_timestamps on guest_claims;
+
+drop table if exists guest_claims;
@@ -0,0 +1,21 @@
+-- A guest taken over by a registered user; the guest is retired afterwards
+create table guest_claims (
+    id uuid primary key default uuidv7(),
+    guest_id uuid not null unique references guests(id),
+    user_id uuid not null references users(id),
+    -- Balance moved into the user's wallet; negative if the user took on a debt
+    amount_cents integer not null,
+    -- None if the guest's wallet was empty
+    transaction_id uuid unique references transactions(id),
+    created_at timestamptz not null default now(),
+    updated_at timestamptz,
+
+    check ((amount_cents = 0) = (transaction_id is null))
+);
+
+create index guest_claims_user_id_idx on guest_claims (user_id);
+
+create trigger guest_claims_audit_timestamps
+    before insert or update on guest_claims
+    for each row
+    execute function enforce_audit_timestamps();
@@ -0,0 +1,3 @@
+drop trigger if exists scheduled_job_runs_audit_timestamps on scheduled_job_runs;
+
+drop table if exists scheduled_job_runs;
@@ -0,0 +1,13 @@
+-- Last start of each scheduled job, so that with several replicas running
+-- the same schedule only the first to claim a period runs the job
+create table scheduled_job_runs (
+    job text primary key,
+    last_started_at timestamptz not null,
+    created_at timestamptz not null default now(),
+    updated_at timestamptz
+);
+
+create trigger scheduled_job_runs_audit_timestamps
+    before insert or update on scheduled_job_runs
+    for each row
+    execute function enforce_audit_timestamps();
@@ -0,0 +1,2 @@
+-- Deleted users stay behind, anonymized, so the ledger keeps its references
+alter table users add column deleted_at timestamptz;
@@ -0,0 +1,3 @@
+drop trigger if exists email_changes_audit_timestamps on email_changes;
+
+drop table if exists email_changes;
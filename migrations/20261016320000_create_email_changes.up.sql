@@ -0,0 +1,14 @@
+create table email_changes (
+    id uuid primary key default uuidv7(),
+    user_id uuid not null unique references users(id) on delete cascade,
+    email text not null,
+    token text not null unique,
+    expires_at timestamptz not null,
+    created_at timestamptz not null default now(),
+    updated_at timestamptz
+);
+
+create trigger email_changes_audit_timestamps
+    before insert or update on email_changes
+    for each row
+    execute function enforce_audit_timestamps();
@@ -0,0 +1,9 @@
+drop trigger if exists wallets_queue_actor_summaries on wallets;
+drop trigger if exists guests_queue_actor_summaries on guests;
+drop trigger if exists users_queue_actor_summaries on users;
+
+drop function if exists queue_actor_summaries();
+drop function if exists queue_actor_summary(uuid);
+
+drop table if exists actor_summary_outbox;
+drop table if exists actor_summaries;
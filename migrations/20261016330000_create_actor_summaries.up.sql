@@ -0,0 +1,89 @@
+-- Read model of every user and guest for admin search, so listings don't
+-- join users, guests and wallets on every request. Rebuilt per actor from
+-- actor_summary_outbox by the application.
+create table actor_summaries (
+    actor_id uuid primary key references actors(id) on delete cascade,
+    kind text not null check (kind in ('user', 'guest')),
+    user_id uuid,
+    guest_id uuid,
+    display_name text,
+    email text,
+    identifier text,
+    role text,
+    wallet_count integer not null,
+    balance_cents bigint not null,
+    created_at timestamptz not null default now(),
+    updated_at timestamptz
+);
+
+create index actor_summaries_kind_idx on actor_summaries (kind);
+
+create trigger actor_summaries_audit_timestamps
+    before insert or update on actor_summaries
+    for each row
+    execute function enforce_audit_timestamps();
+
+-- Actors whose summary is out of date. Queued in the same transaction as the
+-- change, so no change is missed; no foreign key, as the actor may be gone.
+create table actor_summary_outbox (
+    actor_id uuid primary key,
+    created_at timestamptz not null default now(),
+    updated_at timestamptz
+);
+
+create trigger actor_summary_outbox_audit_timestamps
+    before insert or update on actor_summary_outbox
+    for each row
+    execute function enforce_audit_timestamps();
+
+-- Updating an already queued actor locks its row, so the projector either
+-- sees the change or the actor is queued again once it is done.
+create or replace function queue_actor_summary(actor uuid)
+returns void as $$
+begin
+    if actor is null then
+        return;
+    end if;
+
+    insert into actor_summary_outbox (actor_id)
+    values (actor)
+    on conflict (actor_id) do update set actor_id = excluded.actor_id;
+
+    perform pg_notify('actor_summaries', '');
+end;
+$$ language plpgsql;
+
+-- Queues the actors in the column named by the trigger's argument, before
+-- and after the change.
+create or replace function queue_actor_summaries()
+returns trigger as $$
+begin
+    if tg_op <> 'INSERT' then
+        perform queue_actor_summary((to_jsonb(old) ->> tg_argv[0])::uuid);
+    end if;
+
+    if tg_op <> 'DELETE' then
+        perform queue_actor_summary((to_jsonb(new) ->> tg_argv[0])::uuid);
+    end if;
+
+    return null;
+end;
+$$ language plpgsql;
+
+create trigger users_queue_actor_summaries
+    after insert or delete or update of actor_id, email, first_name, last_name, role, deleted_at on users
+    for each row
+    execute function queue_actor_summaries('actor_id');
+
+create trigger guests_queue_actor_summaries
+    after insert or delete or update of actor_id, email, identifier on guests
+    for each row
+    execute function queue_actor_summaries('actor_id');
+
+create trigger wallets_queue_actor_summaries
+    after insert or delete or update of owner_actor_id, balance_cents on wallets
+    for each row
+    execute function queue_actor_summaries('owner_actor_id');
+
+insert into actor_summary_outbox (actor_id)
+select id from actors;
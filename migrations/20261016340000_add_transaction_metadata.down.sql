@@ -0,0 +1,3 @@
+alter table transactions
+    drop constraint if exists transactions_metadata_check,
+    drop column if exists metadata;
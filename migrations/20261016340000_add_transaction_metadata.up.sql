@@ -0,0 +1,8 @@
+-- Structured context such as the terminal a transaction was booked on. The
+-- application whitelists keys per source; the database only bounds the size.
+alter table transactions
+    add column metadata jsonb not null default '{}',
+    add constraint transactions_metadata_check check (
+        jsonb_typeof(metadata) = 'object'
+        and octet_length(metadata::text) <= 4096
+    );
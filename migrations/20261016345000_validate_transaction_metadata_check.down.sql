@@ -0,0 +1,2 @@
+-- Nothing to undo: the constraint is dropped with the column by the previous
+-- migration's down migration
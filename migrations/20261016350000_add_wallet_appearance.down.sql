@@ -0,0 +1,4 @@
+alter table wallets
+    drop column if exists display_name,
+    drop column if exists color,
+    drop column if exists icon;
@@ -0,0 +1,5 @@
+-- Set by the owner of a personal wallet so the app can tell wallets apart
+alter table wallets
+    add column display_name text check (char_length(display_name) between 1 and 40),
+    add column color text check (color ~ '^#[0-9a-f]{6}$'),
+    add column icon text check (icon ~ '^[a-z0-9-]{1,32}$');
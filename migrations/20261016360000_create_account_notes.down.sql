@@ -0,0 +1,3 @@
+drop trigger if exists account_notes_audit_timestamps on account_notes;
+
+drop table if exists account_notes;
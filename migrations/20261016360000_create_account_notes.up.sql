@@ -0,0 +1,16 @@
+create table account_notes (
+    id uuid primary key default uuidv7(),
+    -- User or guest the note is about
+    actor_id uuid not null references actors(id) on delete cascade,
+    author_actor_id uuid references actors(id) on delete set null,
+    body text not null check (char_length(body) between 1 and 2000),
+    created_at timestamptz not null default now(),
+    updated_at timestamptz
+);
+
+create index account_notes_actor_id_created_at_idx on account_notes (actor_id, created_at);
+
+create trigger account_notes_audit_timestamps
+    before insert or update on account_notes
+    for each row
+    execute function enforce_audit_timestamps();
@@ -0,0 +1,3 @@
+drop trigger if exists jobs_audit_timestamps on jobs;
+
+drop table if exists jobs;
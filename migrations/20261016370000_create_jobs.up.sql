@@ -0,0 +1,23 @@
+-- Durable background work, retried with backoff until it succeeds or runs out
+-- of attempts
+create table jobs (
+    id uuid primary key default uuidv7(),
+    task jsonb not null check (jsonb_typeof(task) = 'object'),
+    status text not null default 'pending'
+        check (status in ('pending', 'running', 'succeeded', 'failed')),
+    attempts integer not null default 0,
+    max_attempts integer not null check (max_attempts > 0),
+    run_at timestamptz not null default now(),
+    -- A running job past this is taken to have lost its worker and is retried
+    locked_until timestamptz,
+    last_error text,
+    created_at timestamptz not null default now(),
+    updated_at timestamptz
+);
+
+create index jobs_status_run_at_idx on jobs (status, run_at);
+
+create trigger jobs_audit_timestamps
+    before insert or update on jobs
+    for each row
+    execute function enforce_audit_timestamps();
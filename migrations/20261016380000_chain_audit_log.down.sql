@@ -0,0 +1,11 @@
+drop trigger if exists audit_log_chain on audit_log;
+
+drop function if exists chain_audit_log;
+
+alter table audit_log
+    drop constraint if exists audit_log_seq_key,
+    drop column if exists seq,
+    drop column if exists previous_hash,
+    drop column if exists hash;
+
+drop function if exists audit_log_hash;
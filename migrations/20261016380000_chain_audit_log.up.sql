@@ -0,0 +1,92 @@
+-- Chains every audit entry to the one before it, so changing or deleting an
+-- entry after the fact breaks the chain from there on.
+--
+-- An entry's hash is the hex SHA-256 of the text of the jsonb array
+-- [seq, previous_hash, id, actor_id, action, target, details, created_at],
+-- with created_at in UTC as YYYY-MM-DDTHH:MM:SS.ffffffZ. The IP address is
+-- left out, as the retention rules clear it.
+create or replace function audit_log_hash(
+    seq bigint,
+    previous_hash text,
+    id uuid,
+    actor_id uuid,
+    action text,
+    target text,
+    details jsonb,
+    created_at timestamptz
+)
+returns text as $$
+    select encode(sha256(convert_to(jsonb_build_array(
+        seq,
+        previous_hash,
+        id,
+        actor_id,
+        action,
+        target,
+        details,
+        to_char(created_at at time zone 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS.US"Z"')
+    )::text, 'UTF8')), 'hex');
+$$ language sql stable;
+
+alter table audit_log
+    add column seq bigint,
+    add column previous_hash text,
+    add column hash text;
+
+-- Chain the entries recorded so far in the order they were recorded, without
+-- marking every one of them as updated
+alter table audit_log disable trigger audit_log_audit_timestamps;
+
+do $$
+declare
+    entry record;
+    next_seq bigint := 0;
+    last_hash text := null;
+begin
+    for entry in select * from audit_log order by created_at, id loop
+        next_seq := next_seq + 1;
+        update audit_log
+        set seq = next_seq,
+            previous_hash = last_hash,
+            hash = audit_log_hash(next_seq, last_hash, entry.id, entry.actor_id,
+                entry.action, entry.target, entry.details, entry.created_at)
+        where id = entry.id
+        returning hash into last_hash;
+    end loop;
+end;
+$$;
+
+alter table audit_log enable trigger audit_log_audit_timestamps;
+
+alter table audit_log
+    alter column seq set not null,
+    alter column hash set not null,
+    add constraint audit_log_seq_key unique (seq);
+
+-- Runs after audit_log_audit_timestamps, which sets created_at. The lock
+-- keeps concurrent entries from linking to the same predecessor.
+create or replace function chain_audit_log()
+returns trigger as $$
+declare
+    last record;
+begin
+    perform pg_advisory_xact_lock(hashtext('audit_log'));
+
+    select seq, hash into last
+    from audit_log
+    order by seq desc
+    limit 1;
+
+    new.seq = coalesce(last.seq, 0) + 1;
+    new.previous_hash = last.hash;
+    new.hash = audit_log_hash(new.seq, new.previous_hash, new.id, new.actor_id,
+        new.action, new.target, new.details, new.created_at);
+
+    return new;
+end;
+$$ language plpgsql;
+
+create trigger audit_log_chain
+    before insert on audit_log
+    for each row
+    execute function chain_audit_log();
@@ -0,0 +1,3 @@
+drop trigger if exists outbound_emails_audit_timestamps on outbound_emails;
+
+drop table if exists outbound_emails;
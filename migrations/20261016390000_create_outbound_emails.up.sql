@@ -0,0 +1,23 @@
+-- Emails rendered by a request and delivered in the background, so a slow or
+-- unreachable mail server neither stalls the request nor loses the email
+create table outbound_emails (
+    id uuid primary key default uuidv7(),
+    recipient text not null,
+    subject text not null,
+    html_body text not null,
+    status text not null default 'queued'
+        check (status in ('queued', 'sent', 'failed')),
+    attempts integer not null default 0,
+    last_error text,
+    sent_at timestamptz,
+    created_at timestamptz not null default now(),
+    updated_at timestamptz,
+    check ((status = 'sent') = (sent_at is not null))
+);
+
+create index outbound_emails_status_idx on outbound_emails (status, updated_at);
+
+create trigger outbound_emails_audit_timestamps
+    before insert or update on outbound_emails
+    for each row
+    execute function enforce_audit_timestamps();
@@ -0,0 +1,8 @@
+alter table outbound_emails
+    drop column if exists text_body;
+
+alter table invites
+    drop column if exists locale;
+
+alter table users
+    drop column if exists locale;
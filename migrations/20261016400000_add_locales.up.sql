@@ -0,0 +1,10 @@
+-- Language of the emails sent to a user, and to an invited person until they
+-- sign up
+alter table users
+    add column locale text not null default 'en' check (locale in ('en', 'de'));
+
+alter table invites
+    add column locale text not null default 'en' check (locale in ('en', 'de'));
+
+alter table outbound_emails
+    add column text_body text not null default '';
@@ -0,0 +1,7 @@
+drop trigger if exists app_access_tokens_audit_timestamps on app_access_tokens;
+drop trigger if exists app_authorization_codes_audit_timestamps on app_authorization_codes;
+drop trigger if exists client_apps_audit_timestamps on client_apps;
+
+drop table if exists app_access_tokens;
+drop table if exists app_authorization_codes;
+drop table if exists client_apps;
@@ -0,0 +1,52 @@
+create table client_apps (
+    id uuid primary key default uuidv7(),
+    name text not null,
+    redirect_uris text[] not null,
+    allowed_scopes text[] not null,
+    created_by_actor_id uuid references actors(id) on delete set null,
+    created_at timestamptz not null default now(),
+    updated_at timestamptz
+);
+
+create trigger client_apps_audit_timestamps
+    before insert or update on client_apps
+    for each row
+    execute function enforce_audit_timestamps();
+
+-- Single-use codes a user's approval is handed to the app with
+create table app_authorization_codes (
+    id uuid primary key default uuidv7(),
+    client_app_id uuid not null references client_apps(id) on delete cascade,
+    user_id uuid not null references users(id) on delete cascade,
+    code text not null unique,
+    redirect_uri text not null,
+    scopes text[] not null,
+    code_challenge text not null,
+    expires_at timestamptz not null,
+    created_at timestamptz not null default now(),
+    updated_at timestamptz
+);
+
+create trigger app_authorization_codes_audit_timestamps
+    before insert or update on app_authorization_codes
+    for each row
+    execute function enforce_audit_timestamps();
+
+create table app_access_tokens (
+    id uuid primary key default uuidv7(),
+    client_app_id uuid not null references client_apps(id) on delete cascade,
+    user_id uuid not null references users(id) on delete cascade,
+    token text not null unique,
+    scopes text[] not null,
+    expires_at timestamptz not null,
+    last_used_at timestamptz,
+    created_at timestamptz not null default now(),
+    updated_at timestamptz
+);
+
+create index app_access_tokens_user_id_idx on app_access_tokens (user_id);
+
+create trigger app_access_tokens_audit_timestamps
+    before insert or update on app_access_tokens
+    for each row
+    execute function enforce_audit_timestamps();
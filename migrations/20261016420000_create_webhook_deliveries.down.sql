@@ -0,0 +1,3 @@
+drop trigger if exists webhook_deliveries_audit_timestamps on webhook_deliveries;
+
+drop table if exists webhook_deliveries;
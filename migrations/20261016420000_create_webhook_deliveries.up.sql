@@ -0,0 +1,25 @@
+-- Every event sent to a webhook, delivered by the job queue and kept as the
+-- webhook's delivery log
+create table webhook_deliveries (
+    id uuid primary key default uuidv7(),
+    webhook_id uuid not null references webhooks(id) on delete cascade,
+    event text not null,
+    payload jsonb not null,
+    status text not null default 'pending'
+        check (status in ('pending', 'delivered', 'failed')),
+    attempts integer not null default 0,
+    response_status integer,
+    last_error text,
+    delivered_at timestamptz,
+    created_at timestamptz not null default now(),
+    updated_at timestamptz,
+    check ((status = 'delivered') = (delivered_at is not null))
+);
+
+create index webhook_deliveries_webhook_id_idx on webhook_deliveries (webhook_id, created_at);
+create index webhook_deliveries_status_idx on webhook_deliveries (status, delivered_at);
+
+create trigger webhook_deliveries_audit_timestamps
+    before insert or update on webhook_deliveries
+    for each row
+    execute function enforce_audit_timestamps();
@@ -0,0 +1,4 @@
+alter table sessions
+    drop column if exists device_nonce_expires_at,
+    drop column if exists device_nonce,
+    drop column if exists device_public_key;
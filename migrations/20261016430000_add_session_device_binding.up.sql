@@ -0,0 +1,6 @@
+-- Sessions of the mobile app are bound to a key pair in the device's secure
+-- hardware; sensitive requests sign a single-use nonce with it
+alter table sessions
+    add column device_public_key text,
+    add column device_nonce text,
+    add column device_nonce_expires_at timestamptz;
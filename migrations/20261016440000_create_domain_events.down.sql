@@ -0,0 +1,4 @@
+drop trigger if exists domain_events_notify on domain_events;
+drop function if exists notify_domain_events();
+
+drop table if exists domain_events;
@@ -0,0 +1,27 @@
+-- Outbox of everything that happened, written in the transaction that made
+-- it happen so no event is lost, and dispatched to subscribers such as
+-- webhooks and notices by the application afterwards.
+create table domain_events (
+    id uuid primary key default uuidv7(),
+    sequence bigint generated always as identity unique,
+    event_type text not null,
+    data jsonb not null,
+    occurred_at timestamptz not null default now(),
+    dispatched_at timestamptz
+);
+
+create index domain_events_undispatched_idx on domain_events (sequence)
+    where dispatched_at is null;
+
+create or replace function notify_domain_events()
+returns trigger as $$
+begin
+    perform pg_notify('domain_events', '');
+    return null;
+end;
+$$ language plpgsql;
+
+create trigger domain_events_notify
+    after insert on domain_events
+    for each statement
+    execute function notify_domain_events();
@@ -0,0 +1,3 @@
+drop trigger if exists invite_domain_rules_audit_timestamps on invite_domain_rules;
+
+drop table if exists invite_domain_rules;
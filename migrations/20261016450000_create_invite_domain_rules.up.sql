@@ -0,0 +1,17 @@
+create table invite_domain_rules (
+    id uuid primary key default uuidv7(),
+    domain text not null,
+    allowed boolean not null,
+    -- Every role if unset
+    role text,
+    created_at timestamptz not null default now(),
+    updated_at timestamptz,
+
+    constraint domain_lowercase
+        check (domain = lower(domain) and domain <> '')
+);
+
+create trigger invite_domain_rules_audit_timestamps
+    before insert or update on invite_domain_rules
+    for each row
+    execute function enforce_audit_timestamps();
@@ -0,0 +1,5 @@
+drop trigger if exists sessions_revoke_deleted on sessions;
+drop function if exists revoke_deleted_session();
+drop trigger if exists session_revocations_audit_timestamps on session_revocations;
+
+drop table if exists session_revocations;
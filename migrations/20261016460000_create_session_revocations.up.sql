@@ -0,0 +1,36 @@
+-- Signed session tokens are checked without looking their session up, so
+-- they stay good until they expire unless listed here.
+create table session_revocations (
+    session_id uuid primary key,
+    -- Only tokens issued before this; every token of the session if unset
+    issued_before timestamptz,
+    expires_at timestamptz not null,
+    created_at timestamptz not null default now(),
+    updated_at timestamptz
+);
+
+create index session_revocations_changed_at_idx
+    on session_revocations ((coalesce(updated_at, created_at)));
+
+create trigger session_revocations_audit_timestamps
+    before insert or update on session_revocations
+    for each row
+    execute function enforce_audit_timestamps();
+
+create or replace function revoke_deleted_session()
+returns trigger as $$
+begin
+    if old.expires_at > now() then
+        insert into session_revocations (session_id, expires_at)
+        values (old.id, old.expires_at)
+        on conflict (session_id) do update
+            set issued_before = null, expires_at = excluded.expires_at;
+    end if;
+    return null;
+end;
+$$ language plpgsql;
+
+create trigger sessions_revoke_deleted
+    after delete on sessions
+    for each row
+    execute function revoke_deleted_session();
@@ -0,0 +1,4 @@
+drop index if exists actor_summaries_lower_display_name_idx;
+drop index if exists actor_summaries_normalized_email_idx;
+
+drop function if exists normalized_email(text);
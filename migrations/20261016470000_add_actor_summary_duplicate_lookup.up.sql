@@ -0,0 +1,13 @@
+-- An address with case, `+tags` and dots in the local part ignored, which
+-- most providers deliver to the same mailbox.
+create or replace function normalized_email(email text)
+returns text as $$
+    select replace(split_part(split_part(lower(email), '@', 1), '+', 1), '.', '')
+        || '@' || split_part(lower(email), '@', 2)
+$$ language sql immutable strict;
+
+create index actor_summaries_normalized_email_idx
+    on actor_summaries (normalized_email(email));
+
+create index actor_summaries_lower_display_name_idx
+    on actor_summaries (lower(display_name));
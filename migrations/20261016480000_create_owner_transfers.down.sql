@@ -0,0 +1,3 @@
+drop trigger if exists owner_transfers_audit_timestamps on owner_transfers;
+
+drop table if exists owner_transfers;
@@ -0,0 +1,23 @@
+create table owner_transfers (
+    id uuid primary key default uuidv7(),
+    from_user_id uuid not null references users(id) on delete cascade,
+    to_user_id uuid not null references users(id) on delete cascade,
+    demote_to text not null,
+    status text not null default 'pending'
+        check (status in ('pending', 'accepted', 'declined', 'cancelled', 'expired')),
+    expires_at timestamptz not null,
+    decided_at timestamptz,
+    created_at timestamptz not null default now(),
+    updated_at timestamptz,
+
+    check (from_user_id <> to_user_id),
+    check ((status = 'pending') = (decided_at is null))
+);
+
+-- One handover at a time
+create unique index owner_transfers_pending_idx on owner_transfers ((true)) where status = 'pending';
+
+create trigger owner_transfers_audit_timestamps
+    before insert or update on owner_transfers
+    for each row
+    execute function enforce_audit_timestamps();
@@ -0,0 +1,3 @@
+drop trigger if exists debts_audit_timestamps on debts;
+
+drop table if exists debts;
@@ -0,0 +1,21 @@
+-- Member wallets below zero, from when that was noticed until the balance
+-- is back at zero. Settled debts stay as the history of who owed what.
+create table debts (
+    id uuid primary key default uuidv7(),
+    wallet_id uuid not null references wallets(id) on delete cascade,
+    since timestamptz not null,
+    flagged_at timestamptz,
+    reminders_sent integer not null default 0,
+    last_reminded_at timestamptz,
+    settled_at timestamptz,
+    created_at timestamptz not null default now(),
+    updated_at timestamptz
+);
+
+-- One open debt per wallet
+create unique index debts_open_wallet_idx on debts (wallet_id) where settled_at is null;
+
+create trigger debts_audit_timestamps
+    before insert or update on debts
+    for each row
+    execute function enforce_audit_timestamps();
@@ -0,0 +1,3 @@
+drop trigger if exists feature_switches_audit_timestamps on feature_switches;
+
+drop table if exists feature_switches;
@@ -0,0 +1,14 @@
+-- Capabilities operators switched at runtime. Features without a row are on.
+create table feature_switches (
+    feature text primary key,
+    enabled boolean not null,
+    reason text,
+    changed_by_actor_id uuid references actors(id) on delete set null,
+    created_at timestamptz not null default now(),
+    updated_at timestamptz
+);
+
+create trigger feature_switches_audit_timestamps
+    before insert or update on feature_switches
+    for each row
+    execute function enforce_audit_timestamps();
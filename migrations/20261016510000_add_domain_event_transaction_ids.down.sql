@@ -0,0 +1 @@
+alter table domain_events drop column if exists transaction_id;
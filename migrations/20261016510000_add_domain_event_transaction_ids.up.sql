@@ -0,0 +1,6 @@
+-- The transaction that recorded each event. Sequences are handed out before
+-- commit, so an event may become visible after one with a later sequence;
+-- readers of the log only go up to events of transactions older than every
+-- one still running.
+alter table domain_events
+    add column transaction_id xid8 not null default pg_current_xact_id();
@@ -0,0 +1,3 @@
+drop trigger if exists settings_audit_timestamps on settings;
+
+drop table if exists settings;
@@ -0,0 +1,17 @@
+-- Settings operators change at runtime; a single row, created by the first
+-- change. Until then the server's configured defaults apply.
+create table settings (
+    id boolean primary key default true check (id),
+    invite_expiration_days integer not null check (invite_expiration_days between 1 and 90),
+    allow_overdraft_by_default boolean not null,
+    currency text not null check (currency ~ '^[A-Z]{3}$'),
+    email_sender_name text,
+    changed_by_actor_id uuid references actors(id) on delete set null,
+    created_at timestamptz not null default now(),
+    updated_at timestamptz
+);
+
+create trigger settings_audit_timestamps
+    before insert or update on settings
+    for each row
+    execute function enforce_audit_timestamps();
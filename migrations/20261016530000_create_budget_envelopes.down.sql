@@ -0,0 +1,11 @@
+drop trigger if exists transactions_apply_to_envelopes on transactions;
+
+drop function if exists apply_transaction_to_envelopes();
+
+drop trigger if exists budget_envelope_shops_audit_timestamps on budget_envelope_shops;
+
+drop trigger if exists budget_envelopes_audit_timestamps on budget_envelopes;
+
+drop table if exists budget_envelope_shops;
+
+drop table if exists budget_envelopes;
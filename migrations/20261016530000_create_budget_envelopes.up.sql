@@ -0,0 +1,78 @@
+-- Parts of a personal wallet's balance its owner earmarked for spending at
+-- some shops. Purely informational: nothing is refused for going past one.
+create table budget_envelopes (
+    id uuid primary key default uuidv7(),
+    wallet_id uuid not null references wallets(id) on delete cascade,
+    name text not null check (char_length(name) between 1 and 40),
+    amount_cents int not null check (amount_cents > 0),
+    -- Paid at the envelope's shops since spent_since, less refunds of it
+    spent_cents bigint not null default 0 check (spent_cents >= 0),
+    spent_since timestamptz not null default now(),
+    created_at timestamptz not null default now(),
+    updated_at timestamptz,
+
+    unique (wallet_id, name)
+);
+
+create table budget_envelope_shops (
+    envelope_id uuid not null references budget_envelopes(id) on delete cascade,
+    shop_id uuid not null references shops(id) on delete cascade,
+    created_at timestamptz not null default now(),
+    updated_at timestamptz,
+
+    primary key (envelope_id, shop_id)
+);
+
+create index budget_envelope_shops_shop_id_idx on budget_envelope_shops (shop_id);
+
+create trigger budget_envelopes_audit_timestamps
+    before insert or update on budget_envelopes
+    for each row
+    execute function enforce_audit_timestamps();
+
+create trigger budget_envelope_shops_audit_timestamps
+    before insert or update on budget_envelope_shops
+    for each row
+    execute function enforce_audit_timestamps();
+
+-- Counts payments from a wallet to the shops of its envelopes against them,
+-- within the inserting transaction. Refunds only count back against
+-- envelopes that counted the payment they reverse.
+create or replace function apply_transaction_to_envelopes()
+returns trigger as $$
+begin
+    update budget_envelopes e
+    set spent_cents = e.spent_cents + new.amount_cents
+    where e.wallet_id = new.source_wallet_id
+      and exists (
+          select 1
+          from budget_envelope_shops es
+          join shops s on s.id = es.shop_id
+          where es.envelope_id = e.id
+            and s.wallet_id = new.destination_wallet_id
+      );
+
+    if new.reversal_of is not null then
+        update budget_envelopes e
+        set spent_cents = greatest(e.spent_cents - new.amount_cents, 0)
+        from transactions reversed
+        where reversed.id = new.reversal_of
+          and reversed.created_at >= e.spent_since
+          and e.wallet_id = new.destination_wallet_id
+          and exists (
+              select 1
+              from budget_envelope_shops es
+              join shops s on s.id = es.shop_id
+              where es.envelope_id = e.id
+                and s.wallet_id = new.source_wallet_id
+          );
+    end if;
+
+    return new;
+end;
+$$ language plpgsql;
+
+create trigger transactions_apply_to_envelopes
+    after insert on transactions
+    for each row
+    execute function apply_transaction_to_envelopes();
@@ -0,0 +1,11 @@
+drop trigger if exists wallets_currency on wallets;
+drop function if exists enforce_wallet_currency();
+drop trigger if exists transactions_currency on transactions;
+drop function if exists enforce_transaction_currency();
+
+alter table transactions drop column if exists currency;
+
+alter table wallets
+    drop constraint if exists wallets_label_currency_key,
+    add constraint wallets_label_key unique (label),
+    drop column if exists currency;
@@ -0,0 +1,69 @@
+-- Every wallet holds a single currency; amounts stay in hundredths of it.
+-- System wallets exist once per currency, hence the label is only unique
+-- within one.
+alter table wallets
+    add column currency text not null default 'EUR'
+        check (currency ~ '^[A-Z]{3}$'),
+    drop constraint wallets_label_key,
+    add constraint wallets_label_currency_key unique (label, currency);
+
+alter table transactions add column currency text not null default 'EUR';
+alter table transactions alter column currency drop default;
+
+-- A transaction is in the currency of its source wallet and may only move
+-- money into a wallet of the same one; converting between currencies has to
+-- be booked explicitly. Mismatches are rejected with SQLSTATE CM001 so the
+-- application can report them distinctly.
+create or replace function enforce_transaction_currency()
+returns trigger as $$
+declare
+    source_currency text;
+    destination_currency text;
+begin
+    select currency into source_currency
+    from wallets
+    where id = new.source_wallet_id;
+
+    select currency into destination_currency
+    from wallets
+    where id = new.destination_wallet_id;
+
+    if source_currency is distinct from destination_currency then
+        raise exception 'wallet % holds %, wallet % holds %',
+            new.source_wallet_id, source_currency,
+            new.destination_wallet_id, destination_currency
+            using errcode = 'CM001';
+    end if;
+
+    new.currency := source_currency;
+
+    return new;
+end;
+$$ language plpgsql;
+
+create trigger transactions_currency
+    before insert on transactions
+    for each row
+    execute function enforce_transaction_currency();
+
+-- Wallets can't change currency once money moved through them.
+create or replace function enforce_wallet_currency()
+returns trigger as $$
+begin
+    if new.currency <> old.currency and exists (
+        select 1
+        from transactions
+        where source_wallet_id = old.id or destination_wallet_id = old.id
+    ) then
+        raise exception 'wallet % already holds %', old.id, old.currency
+            using errcode = 'CM001';
+    end if;
+
+    return new;
+end;
+$$ language plpgsql;
+
+create trigger wallets_currency
+    before update of currency on wallets
+    for each row
+    execute function enforce_wallet_currency();
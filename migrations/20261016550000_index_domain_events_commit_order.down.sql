@@ -0,0 +1 @@
+drop index if exists domain_events_commit_order_idx;
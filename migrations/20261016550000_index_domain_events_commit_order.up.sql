@@ -0,0 +1,4 @@
+-- The event log is read in the order of the transactions that recorded the
+-- events: once every transaction up to a point has finished, no event can
+-- appear before that point any more, which does not hold for sequences.
+create index domain_events_commit_order_idx on domain_events (transaction_id, sequence);
@@ -0,0 +1,25 @@
+create or replace function enforce_wallet_legal_hold()
+returns trigger as $$
+begin
+    if not old.legal_hold then
+        if tg_op = 'DELETE' then
+            return old;
+        end if;
+
+        return new;
+    end if;
+
+    if tg_op = 'DELETE' then
+        raise exception 'wallet % is under legal hold', old.id
+            using errcode = 'LH001';
+    end if;
+
+    if (new.owner_actor_id, new.label, new.allow_overdraft)
+        is distinct from (old.owner_actor_id, old.label, old.allow_overdraft) then
+        raise exception 'wallet % is under legal hold', old.id
+            using errcode = 'LH001';
+    end if;
+
+    return new;
+end;
+$$ language plpgsql;
@@ -0,0 +1,30 @@
+-- A held wallet keeps every column as it was when the hold was placed, except
+-- for the hold flag itself, the timestamp it bumps, the running balance (new
+-- transactions still settle against held wallets) and the abuse freeze, which
+-- only ever restricts the wallet further. Columns added later are frozen too.
+create or replace function enforce_wallet_legal_hold()
+returns trigger as $$
+declare
+    exempt constant text[] := array['legal_hold', 'updated_at', 'balance_cents', 'frozen'];
+begin
+    if not old.legal_hold then
+        if tg_op = 'DELETE' then
+            return old;
+        end if;
+
+        return new;
+    end if;
+
+    if tg_op = 'DELETE' then
+        raise exception 'wallet % is under legal hold', old.id
+            using errcode = 'LH001';
+    end if;
+
+    if (to_jsonb(new) - exempt) is distinct from (to_jsonb(old) - exempt) then
+        raise exception 'wallet % is under legal hold', old.id
+            using errcode = 'LH001';
+    end if;
+
+    return new;
+end;
+$$ language plpgsql;
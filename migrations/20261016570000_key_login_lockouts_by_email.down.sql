@@ -0,0 +1,18 @@
+alter table users add column locked_until timestamptz;
+
+update users u
+set locked_until = l.locked_until
+from login_lockouts l
+where l.email = u.email;
+
+drop trigger if exists login_lockouts_audit_timestamps on login_lockouts;
+
+drop table if exists login_lockouts;
+
+drop index if exists login_attempts_email_created_at_idx;
+
+delete from login_attempts where user_id is null;
+
+alter table login_attempts
+    drop column if exists email,
+    alter column user_id set not null;
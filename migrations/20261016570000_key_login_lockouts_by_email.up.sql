@@ -0,0 +1,35 @@
+-- Failed logins are counted per address, whether or not an account uses it,
+-- so being locked out does not reveal which addresses are registered.
+alter table login_attempts
+    add column email text,
+    alter column user_id drop not null;
+
+update login_attempts a
+set email = u.email
+from users u
+where u.id = a.user_id;
+
+delete from login_attempts where email is null;
+
+alter table login_attempts alter column email set not null;
+
+create index login_attempts_email_created_at_idx on login_attempts (email, created_at);
+
+create table login_lockouts (
+    email text primary key,
+    locked_until timestamptz not null,
+    created_at timestamptz not null default now(),
+    updated_at timestamptz
+);
+
+create trigger login_lockouts_audit_timestamps
+    before insert or update on login_lockouts
+    for each row
+    execute function enforce_audit_timestamps();
+
+insert into login_lockouts (email, locked_until)
+select email, locked_until
+from users
+where locked_until is not null and deleted_at is null;
+
+alter table users drop column locked_until;
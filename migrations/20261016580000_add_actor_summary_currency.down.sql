@@ -0,0 +1 @@
+alter table actor_summaries drop column if exists currency;
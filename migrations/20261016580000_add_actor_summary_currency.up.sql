@@ -0,0 +1,7 @@
+-- Balances are only summed over wallets of one currency: that of the actor's
+-- first wallet. Unset for actors without a wallet.
+alter table actor_summaries add column currency text;
+
+insert into actor_summary_outbox (actor_id)
+select actor_id from actor_summaries
+on conflict (actor_id) do nothing;
@@ -0,0 +1 @@
+drop index if exists review_items_open_wallet_signal_idx;
@@ -0,0 +1,18 @@
+-- Concurrent assessments could each open a review for the same signal. Keep
+-- the oldest open review of each wallet and signal before ruling that out.
+update review_items r
+set status = 'dismissed',
+    resolution_note = 'Duplicate of an earlier open review'
+where r.status = 'open'
+  and exists (
+    select 1
+    from review_items o
+    where o.wallet_id = r.wallet_id
+      and o.signal = r.signal
+      and o.status = 'open'
+      and o.id < r.id
+  );
+
+create unique index review_items_open_wallet_signal_idx
+    on review_items (wallet_id, signal)
+    where status = 'open';
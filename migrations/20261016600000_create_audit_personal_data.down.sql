@@ -0,0 +1,2 @@
+drop trigger if exists audit_personal_data_audit_timestamps on audit_personal_data;
+drop table if exists audit_personal_data;
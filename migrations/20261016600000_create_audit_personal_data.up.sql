@@ -0,0 +1,14 @@
+-- Personal data recorded with an audit entry, such as request bodies. Kept
+-- out of the hashed details so erasing a user can remove it without breaking
+-- the chain. Entries recorded before keep theirs in the details.
+create table audit_personal_data (
+    audit_entry_id uuid primary key references audit_log(id) on delete cascade,
+    data jsonb not null,
+    created_at timestamptz not null default now(),
+    updated_at timestamptz
+);
+
+create trigger audit_personal_data_audit_timestamps
+    before insert or update on audit_personal_data
+    for each row
+    execute function enforce_audit_timestamps();
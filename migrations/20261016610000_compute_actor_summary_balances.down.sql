@@ -0,0 +1,14 @@
+drop index if exists wallets_owner_actor_id_idx;
+
+alter table actor_summaries add column balance_cents bigint not null default 0;
+
+drop trigger if exists wallets_queue_actor_summaries on wallets;
+
+create trigger wallets_queue_actor_summaries
+    after insert or delete or update of owner_actor_id, balance_cents on wallets
+    for each row
+    execute function queue_actor_summaries('owner_actor_id');
+
+insert into actor_summary_outbox (actor_id)
+select actor_id from actor_summaries
+on conflict (actor_id) do nothing;
@@ -0,0 +1,13 @@
+-- Every payment changes balances; requeueing the actors on each one kept the
+-- projector busy and the outbox rows locked. Balances are summed from the
+-- wallets when summaries are read instead.
+drop trigger if exists wallets_queue_actor_summaries on wallets;
+
+create trigger wallets_queue_actor_summaries
+    after insert or delete or update of owner_actor_id, currency on wallets
+    for each row
+    execute function queue_actor_summaries('owner_actor_id');
+
+alter table actor_summaries drop column balance_cents;
+
+create index wallets_owner_actor_id_idx on wallets (owner_actor_id);
@@ -0,0 +1,21 @@
+create or replace function enforce_wallet_not_frozen()
+returns trigger as $$
+begin
+    if exists (
+        select 1
+        from wallets
+        where id = new.source_wallet_id
+          and frozen
+    ) and not exists (
+        select 1
+        from wallets
+        where id = new.destination_wallet_id
+          and label = 'psp_clearing'
+    ) then
+        raise exception 'wallet % is frozen', new.source_wallet_id
+            using errcode = 'WF001';
+    end if;
+
+    return new;
+end;
+$$ language plpgsql;
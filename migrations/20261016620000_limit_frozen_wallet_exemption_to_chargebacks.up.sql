@@ -0,0 +1,28 @@
+-- Frozen wallets may still receive money but can't send any, except for
+-- chargebacks: the payment provider takes the money back either way. Only
+-- chargebacks carry a provider dispute ID, so other transfers to the clearing
+-- wallet stay blocked.
+create or replace function enforce_wallet_not_frozen()
+returns trigger as $$
+begin
+    if exists (
+        select 1
+        from wallets
+        where id = new.source_wallet_id
+          and frozen
+    ) and not (
+        new.metadata ? 'provider_dispute_id'
+        and exists (
+            select 1
+            from wallets
+            where id = new.destination_wallet_id
+              and label = 'psp_clearing'
+        )
+    ) then
+        raise exception 'wallet % is frozen', new.source_wallet_id
+            using errcode = 'WF001';
+    end if;
+
+    return new;
+end;
+$$ language plpgsql;
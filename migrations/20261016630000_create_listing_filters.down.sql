@@ -0,0 +1,4 @@
+drop function if exists wallet_listed;
+drop function if exists invite_listed;
+drop function if exists guest_listed;
+drop function if exists user_listed;
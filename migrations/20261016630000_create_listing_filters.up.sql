@@ -0,0 +1,61 @@
+-- Filters of the paginated listings, shared by the queries for a page, the
+-- total and the version of each so they can't drift apart. Simple enough for
+-- the planner to inline.
+
+create function user_listed(u users, wanted_role text, search_pattern text)
+returns boolean as $$
+    select u.deleted_at is null
+        and (wanted_role is null or u.role = wanted_role)
+        and (search_pattern is null
+            or u.email ilike search_pattern
+            or u.first_name ilike search_pattern
+            or u.last_name ilike search_pattern)
+$$ language sql stable;
+
+-- `balance_cents` is the guest's balance in the listing's currency
+create function guest_listed(
+    g guests,
+    balance_cents numeric,
+    card_bound boolean,
+    min_balance_cents bigint,
+    max_balance_cents bigint
+)
+returns boolean as $$
+    select (card_bound is null or (g.identifier is not null) = card_bound)
+        and (min_balance_cents is null or balance_cents >= min_balance_cents)
+        and (max_balance_cents is null or balance_cents <= max_balance_cents)
+$$ language sql stable;
+
+create function invite_listed(
+    i invites,
+    wanted_status text,
+    email_pattern text,
+    invitor_id uuid,
+    created_from timestamptz,
+    created_to timestamptz
+)
+returns boolean as $$
+    select (wanted_status is null or i.status = wanted_status)
+        and (email_pattern is null or i.email ilike email_pattern)
+        and (invitor_id is null or i.invitor_user_id = invitor_id)
+        and (created_from is null or i.created_at >= created_from)
+        and (created_to is null or i.created_at < created_to)
+$$ language sql stable;
+
+-- `u` and `g` are the user or guest owning the wallet, if any
+create function wallet_listed(
+    w wallets,
+    u users,
+    g guests,
+    system boolean,
+    search_pattern text
+)
+returns boolean as $$
+    select (system is null or (w.label is not null) = system)
+        and (search_pattern is null
+            or u.email ilike search_pattern
+            or u.first_name ilike search_pattern
+            or u.last_name ilike search_pattern
+            or g.email ilike search_pattern
+            or g.identifier ilike search_pattern)
+$$ language sql stable;
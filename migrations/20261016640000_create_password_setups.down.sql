@@ -0,0 +1,3 @@
+drop trigger if exists password_setups_audit_timestamps on password_setups;
+
+drop table if exists password_setups;
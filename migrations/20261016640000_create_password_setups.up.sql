@@ -0,0 +1,13 @@
+create table password_setups (
+    id uuid primary key default uuidv7(),
+    user_id uuid not null unique references users(id) on delete cascade,
+    token text not null unique,
+    expires_at timestamptz not null,
+    created_at timestamptz not null default now(),
+    updated_at timestamptz
+);
+
+create trigger password_setups_audit_timestamps
+    before insert or update on password_setups
+    for each row
+    execute function enforce_audit_timestamps();
@@ -0,0 +1,20 @@
+create or replace function apply_transaction_to_balances()
+returns trigger as $$
+begin
+    perform 1
+    from wallets
+    where id in (new.source_wallet_id, new.destination_wallet_id)
+    order by id
+    for update;
+
+    update wallets
+    set balance_cents = balance_cents - new.amount_cents
+    where id = new.source_wallet_id;
+
+    update wallets
+    set balance_cents = balance_cents + new.amount_cents
+    where id = new.destination_wallet_id;
+
+    return new;
+end;
+$$ language plpgsql;
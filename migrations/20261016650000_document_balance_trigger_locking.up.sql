@@ -0,0 +1,26 @@
+-- Keeps the running balance of both wallets in step with every transfer,
+-- within the inserting transaction. Both rows are locked in id order, which
+-- only keeps opposite transfers between the same wallets from deadlocking if
+-- nothing locked either one before: services lock both ends of a transfer
+-- the same way ahead of their balance check, see
+-- `WalletStore::find_pair_for_update`.
+create or replace function apply_transaction_to_balances()
+returns trigger as $$
+begin
+    perform 1
+    from wallets
+    where id in (new.source_wallet_id, new.destination_wallet_id)
+    order by id
+    for update;
+
+    update wallets
+    set balance_cents = balance_cents - new.amount_cents
+    where id = new.source_wallet_id;
+
+    update wallets
+    set balance_cents = balance_cents + new.amount_cents
+    where id = new.destination_wallet_id;
+
+    return new;
+end;
+$$ language plpgsql;
@@ -0,0 +1,6 @@
+alter table transactions
+    drop constraint if exists transactions_metadata_check,
+    add constraint transactions_metadata_check check (
+        jsonb_typeof(metadata) = 'object'
+        and octet_length(metadata::text) <= 4096
+    );
@@ -0,0 +1,9 @@
+-- Bounds transaction metadata as the application does. The check is validated
+-- by the next migration, so replacing it doesn't scan the ledger while holding
+-- an exclusive lock.
+alter table transactions
+    drop constraint transactions_metadata_check,
+    add constraint transactions_metadata_check check (
+        jsonb_typeof(metadata) = 'object'
+        and octet_length(metadata::text) <= 2048
+    ) not valid;
@@ -0,0 +1,2 @@
+-- Nothing to undo: the previous migration's down migration replaces the
+-- constraint
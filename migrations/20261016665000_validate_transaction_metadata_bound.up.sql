@@ -0,0 +1,2 @@
+-- Checks the existing transactions without blocking new ones
+alter table transactions validate constraint transactions_metadata_check;
@@ -1,10 +1,17 @@
 use application::{config::Config, state::AppState};
-use domain::{wallet::WalletLabel, Role};
-use infra::stores::{models::WalletCreation, WalletStore};
-use sqlx::postgres::PgPoolOptions;
-use std::net::SocketAddr;
+use domain::{wallet::WalletLabel, Locale, Role};
+use infra::{migrations, services::EmailService, stores::WalletStore};
+use sqlx::{
+  migrate::Migrator,
+  postgres::{PgConnectOptions, PgPoolOptions},
+  PgPool,
+};
+use std::{net::SocketAddr, str::FromStr, time::Duration};
+use tokio::{task::JoinHandle, time::Instant};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
+const MAX_DATABASE_CONNECT_BACKOFF: Duration = Duration::from_secs(30);
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
   // Initialize tracing
@@ -15,36 +22,78 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     )
     .with(tracing_subscriber::fmt::layer())
     .init();
+  api::middleware::panic::install_hook();
 
   // Load configuration
-  let config = Config::init();
+  let config = Config::load().inspect_err(|e| tracing::error!("{}", e))?;
+  tracing::debug!("Loaded {:?}", config);
 
   // Connect to database
   tracing::info!("Connecting to database at {}...", config.database_url);
-  let pool = PgPoolOptions::new()
-    .max_connections(5)
-    .connect(&config.database_url)
-    .await
-    .expect("Failed to connect to database");
+  let pool = connect_with_retry(&config).await?;
+
+  // Check and run migrations
+  let migrator = sqlx::migrate!("./migrations");
+  check_migration_drift(&config, &pool, &migrator).await?;
 
-  // Run migrations
   if config.database_migrations {
     tracing::info!("Running database migrations...");
-    sqlx::migrate!("./migrations")
-      .run(&pool)
-      .await
-      .expect("Failed to run migrations");
+    migrator.run(&pool).await.expect("Failed to run migrations");
   }
 
   // Initialize application state
   let state = AppState::new(&config, pool);
 
+  // Mail is only needed for invites, so a broken SMTP setup is not fatal
+  // unless asked to be
+  if config.smtp_check_on_startup {
+    if !state.email_service.test_connection().await? {
+      return Err("SMTP server rejected the connection test".into());
+    }
+    tracing::info!("SMTP server reachable");
+  } else {
+    tokio::spawn(verify_smtp(state.email_service.clone()));
+  }
+
+  let scheduler = tokio::spawn(application::scheduler::run(state.clone()));
+  let jobs = tokio::spawn(application::jobs::run(
+    state.job_service.clone(),
+    state.shutdown.clone(),
+  ));
+  // Listeners hold on to a pooled connection each until aborted
+  let listeners = [
+    tokio::spawn(application::events::relay(
+      state.event_bus.clone(),
+      state.pool.clone(),
+    )),
+    tokio::spawn(application::projection::run(
+      state.actor_service.clone(),
+      state.pool.clone(),
+    )),
+    tokio::spawn(application::dispatch::run(
+      state.domain_event_service.clone(),
+      state.pool.clone(),
+    )),
+  ];
+
   // Seed databasse
   seed_owner(&state).await?;
   seed_wallets(&state).await?;
 
+  let mut background = vec![scheduler, jobs];
+  let sandbox = if config.sandbox_enabled {
+    let (sandbox, sandbox_jobs) =
+      start_sandbox(&config, &state.pool, &migrator, &state.shutdown).await?;
+    background.push(sandbox_jobs);
+    Some(sandbox)
+  } else {
+    None
+  };
+
   // Create router
-  let app = api::router(state);
+  let shutdown = state.shutdown.clone();
+  let pool = state.pool.clone();
+  let app = api::router(state, sandbox);
 
   // Start server
   let addr_str = config.server_addr();
@@ -52,10 +101,215 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
   tracing::info!("Server listening on http://{}", addr);
 
   let listener = tokio::net::TcpListener::bind(addr).await?;
-  axum::serve(listener, app)
-    .with_graceful_shutdown(shutdown_signal())
+  tokio::spawn({
+    let shutdown = shutdown.clone();
+    async move {
+      shutdown_signal().await;
+      shutdown.trigger();
+    }
+  });
+
+  let server = axum::serve(
+    listener,
+    app.into_make_service_with_connect_info::<SocketAddr>(),
+  )
+  .with_graceful_shutdown({
+    let shutdown = shutdown.clone();
+    async move { shutdown.triggered().await }
+  });
+
+  // Open streams and slow reads don't hold up the shutdown, only mutations
+  // still under way do
+  let deadline = tokio::select! {
+    served = server => {
+      served?;
+      Instant::now() + Duration::from_secs(config.shutdown_grace_secs)
+    }
+    deadline = drain_requests(&shutdown, config.shutdown_grace_secs) => deadline,
+  };
+
+  drain_background_work(background, deadline).await;
+
+  for task in listeners {
+    task.abort();
+  }
+  pool.close().await;
+  tracing::info!("Shutdown complete");
+
+  Ok(())
+}
+
+/// Waits for the shutdown and then, up to `grace_secs`, for the mutations
+/// under way. Returns by when the rest of the shutdown must be done.
+async fn drain_requests(shutdown: &application::Shutdown, grace_secs: u64) -> Instant {
+  shutdown.triggered().await;
+  let deadline = Instant::now() + Duration::from_secs(grace_secs);
+
+  if tokio::time::timeout_at(deadline, shutdown.drained())
+    .await
+    .is_err()
+  {
+    tracing::warn!("Shutting down with requests still in flight");
+  }
+
+  deadline
+}
+
+/// Lets the scheduler finish its tick and the job runners send what is
+/// queued, until `deadline`. Jobs cut off are claimed again after their lease.
+async fn drain_background_work(tasks: Vec<JoinHandle<()>>, deadline: Instant) {
+  let aborts: Vec<_> = tasks.iter().map(JoinHandle::abort_handle).collect();
+
+  let drained = tokio::time::timeout_at(deadline, async {
+    for task in tasks {
+      let _ = task.await;
+    }
+  })
+  .await;
+
+  if drained.is_err() {
+    tracing::warn!("Shutting down with background jobs still running");
+    for abort in aborts {
+      abort.abort();
+    }
+  }
+}
+
+/// Connects to the database, retrying with exponential backoff so the server
+/// survives being started before the database is ready.
+async fn connect_with_retry(config: &Config) -> Result<PgPool, sqlx::Error> {
+  let attempts = config.database_connect_attempts.max(1);
+  let mut backoff = Duration::from_millis(config.database_connect_backoff_ms);
+  let mut attempt = 1;
+
+  loop {
+    match PgPoolOptions::new()
+      .max_connections(5)
+      .connect(config.database_url.expose())
+      .await
+    {
+      Ok(pool) => return Ok(pool),
+      Err(e) if attempt < attempts => {
+        tracing::warn!(
+          "Database connection attempt {}/{} failed: {}. Retrying in {:?}",
+          attempt,
+          attempts,
+          e,
+          backoff
+        );
+        tokio::time::sleep(backoff).await;
+        backoff = (backoff * 2).min(MAX_DATABASE_CONNECT_BACKOFF);
+        attempt += 1;
+      }
+      Err(e) => {
+        tracing::error!(
+          "Giving up on the database after {} attempts: {}",
+          attempts,
+          e
+        );
+        return Err(e);
+      }
+    }
+  }
+}
+
+/// Sets up the sandbox in its own schema: brings it up to date with the
+/// migrations, seeds it and runs the background work it needs.
+///
+/// Webhooks are delivered from the sandbox like from the real API, so
+/// integrators can test against them; emails are suppressed. Its order events
+/// are kept from other instances.
+async fn start_sandbox(
+  config: &Config,
+  pool: &PgPool,
+  migrator: &Migrator,
+  shutdown: &application::Shutdown,
+) -> Result<(AppState, JoinHandle<()>), Box<dyn std::error::Error>> {
+  let sandbox_config = config
+    .sandbox()
+    .ok_or("the sandbox needs SANDBOX_OWNER_EMAIL and SANDBOX_OWNER_PASSWORD")?;
+
+  tracing::info!(
+    "Preparing the sandbox in schema {}...",
+    config.sandbox_schema
+  );
+
+  let schema = format!("\"{}\"", config.sandbox_schema.replace('"', "\"\""));
+  sqlx::query(&format!("create schema if not exists {}", schema))
+    .execute(pool)
     .await?;
 
+  let options = PgConnectOptions::from_str(config.database_url.expose())?
+    .options([("search_path", schema.as_str())]);
+  // The projection and dispatch listeners keep one connection each
+  let sandbox_pool = PgPoolOptions::new()
+    .max_connections(4)
+    .connect_with(options)
+    .await?;
+  migrator.run(&sandbox_pool).await?;
+
+  // Its requests hold off the shutdown like those of the real API
+  let state = AppState {
+    shutdown: shutdown.clone(),
+    ..AppState::new(&sandbox_config, sandbox_pool)
+  };
+  seed_owner(&state).await?;
+  seed_wallets(&state).await?;
+  application::sandbox::seed(&state).await?;
+
+  tokio::spawn(application::projection::run(
+    state.actor_service.clone(),
+    state.pool.clone(),
+  ));
+  tokio::spawn(application::dispatch::run(
+    state.domain_event_service.clone(),
+    state.pool.clone(),
+  ));
+  let jobs = tokio::spawn(application::jobs::run(
+    state.job_service.clone(),
+    shutdown.clone(),
+  ));
+
+  tracing::info!("Sandbox served under /api/sandbox");
+
+  Ok((state, jobs))
+}
+
+async fn verify_smtp(email_service: EmailService) {
+  match email_service.test_connection().await {
+    Ok(true) => tracing::info!("SMTP server reachable"),
+    Ok(false) => tracing::warn!("SMTP server rejected the connection test; invites will fail"),
+    Err(e) => tracing::warn!("SMTP server unreachable, invites will fail: {}", e),
+  }
+}
+
+/// Compares the embedded migrations with the database before anything else
+/// touches the schema. Pending migrations only count as drift when they won't
+/// be applied on startup.
+async fn check_migration_drift(
+  config: &Config,
+  pool: &PgPool,
+  migrator: &Migrator,
+) -> Result<(), Box<dyn std::error::Error>> {
+  let drift: Vec<_> = migrations::detect_drift(pool, migrator)
+    .await?
+    .into_iter()
+    .filter(|d| !(config.database_migrations && d.is_pending()))
+    .collect();
+
+  if drift.is_empty() {
+    return Ok(());
+  }
+
+  for d in &drift {
+    tracing::error!("Migration drift: {}", d);
+  }
+
+  if config.database_refuse_on_drift {
+    return Err("database schema does not match the migrations of this build".into());
+  }
+
+  tracing::warn!("Starting despite migration drift (DATABASE_REFUSE_ON_DRIFT=false)");
   Ok(())
 }
 
@@ -86,18 +340,25 @@ async fn shutdown_signal() {
 }
 
 async fn seed_owner(state: &AppState) -> Result<(), Box<dyn std::error::Error>> {
+  let mut tx = state.pool.begin().await?;
+
   match state
     .auth_service
     .register(
+      &mut tx,
       state.config.owner_email.clone(),
       state.config.owner_password.clone(),
       state.config.owner_first_name.clone(),
       state.config.owner_last_name.clone(),
       Role::Owner,
+      Locale::default(),
     )
     .await
   {
-    Ok(_) => tracing::info!("Seeded default owner user"),
+    Ok(_) => {
+      tx.commit().await?;
+      tracing::info!("Seeded default owner user");
+    }
     Err(application::error::AppError::UserAlreadyExists) => {
       tracing::debug!("Default owner user already exists");
     }
@@ -110,23 +371,16 @@ async fn seed_owner(state: &AppState) -> Result<(), Box<dyn std::error::Error>>
 }
 
 async fn seed_wallets(state: &AppState) -> Result<(), Box<dyn std::error::Error>> {
+  let currency = state.settings_service.get().await?.currency;
+
   for label in WalletLabel::variants() {
-    match WalletStore::create(
-      &state.pool,
-      &WalletCreation {
-        owner: None,
-        label: Some(label.clone()),
-        allow_overdraft: true,
-      },
-    )
-    .await
-    {
-      Ok(_) => tracing::info!("Seeded wallet with label {:?}", label),
-      Err(sqlx::Error::Database(db_err))
-        if db_err.kind() == sqlx::error::ErrorKind::UniqueViolation =>
-      {
-        tracing::debug!("Wallet with label {:?} already exists", label);
-      }
+    match WalletStore::create_system(&state.pool, label, currency).await {
+      Ok(true) => tracing::info!("Seeded wallet with label {:?} in {}", label, currency),
+      Ok(false) => tracing::debug!(
+        "Wallet with label {:?} in {} already exists",
+        label,
+        currency
+      ),
       Err(e) => {
         tracing::warn!("Failed to seed wallet with label {:?}: {}", label, e);
         return Err(Box::new(e));